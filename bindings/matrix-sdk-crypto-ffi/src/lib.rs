@@ -512,6 +512,11 @@ fn collect_sessions(
             history_visibility: None,
             shared_history: false,
             algorithm: RustEventEncryptionAlgorithm::MegolmV1AesSha2,
+            forwarding_curve25519_key_chain: session
+                .forwarding_chains
+                .iter()
+                .map(|key| Curve25519PublicKey::from_base64(key))
+                .collect::<Result<_, _>>()?,
         };
 
         let session = matrix_sdk_crypto::olm::InboundGroupSession::from_pickle(pickle)?;
@@ -929,11 +929,12 @@ impl OlmMachine {
                 curve25519_key,
                 sender_claimed_keys,
                 session_id: _,
+                forwarding_curve25519_key_chain,
             } => DecryptedEvent {
                 clear_event: serde_json::to_string(&event_json)?,
                 sender_curve25519_key: curve25519_key.to_owned(),
                 claimed_ed25519_key: sender_claimed_keys.get(&DeviceKeyAlgorithm::Ed25519).cloned(),
-                forwarding_curve25519_chain: vec![],
+                forwarding_curve25519_chain: forwarding_curve25519_key_chain.to_owned(),
                 shield_state: if strict_shields {
                     encryption_info.verification_state.to_shield_state_strict().into()
                 } else {
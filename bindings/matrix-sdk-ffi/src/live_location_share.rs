@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crate::ruma::LocationContent;
-#[derive(uniffi::Record)]
+#[derive(Clone, uniffi::Record)]
 pub struct LastLocation {
     /// The most recent location content of the user.
     pub location: LocationContent,
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, pin::pin, sync::Arc};
+use std::{collections::HashMap, pin::pin, sync::Arc, time::Duration};
 
 use anyhow::{Context, Result};
 use futures_util::{pin_mut, StreamExt};
@@ -228,10 +228,32 @@ impl Room {
             .with_focus(configuration.focus.try_into()?)
             .with_date_divider_mode(configuration.date_divider_mode.into());
 
-        if configuration.track_read_receipts {
+        if configuration.lazy_load_read_receipts {
+            builder = builder.lazy_load_read_receipts();
+        } else if configuration.track_read_receipts {
             builder = builder.track_read_marker_and_receipts();
         }
 
+        if configuration.collapse_membership_changes {
+            builder = builder.collapse_membership_changes();
+        }
+
+        if let Some(ordering) = configuration.reaction_ordering {
+            builder = builder.with_reaction_ordering(ordering.into());
+        }
+
+        if configuration.fetch_url_previews {
+            builder = builder.with_url_previews();
+        }
+
+        if let Some(effects) = configuration.message_effects {
+            builder = builder.with_message_effects(effects.into());
+        }
+
+        if let Some(window) = configuration.diffs_coalescing_window_ms {
+            builder = builder.coalesce_diffs(Duration::from_millis(window));
+        }
+
         match configuration.filter {
             TimelineFilter::All => {
                 // #nofilter.
@@ -390,6 +412,44 @@ impl Room {
         Ok(())
     }
 
+    /// Whether the URLs sent in this room can be previewed, which sends them to
+    /// the homeserver.
+    ///
+    /// Unless the user has chosen otherwise, this is only true in unencrypted
+    /// rooms.
+    pub async fn url_previews_enabled(&self) -> Result<bool, ClientError> {
+        Ok(self.inner.url_previews_enabled().await?)
+    }
+
+    /// Enable or disable the previews of the URLs sent in this room.
+    pub async fn set_url_previews_enabled(&self, enabled: bool) -> Result<(), ClientError> {
+        self.inner.set_url_previews_enabled(enabled).await?;
+        Ok(())
+    }
+
+    /// The IDs of the events the user has starred in this room, from the first
+    /// one to be starred to the most recent one.
+    pub async fn starred_event_ids(&self) -> Result<Vec<String>, ClientError> {
+        Ok(self.inner.starred_event_ids().await?.into_iter().map(|id| id.to_string()).collect())
+    }
+
+    /// Star the event with the given ID, so it's shown in the timelines with
+    /// the `StarredEvents` focus.
+    ///
+    /// Returns `false` if the event was already starred.
+    pub async fn star_event(&self, event_id: String) -> Result<bool, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+        Ok(self.inner.star_event(&event_id).await?)
+    }
+
+    /// Remove the star of the event with the given ID.
+    ///
+    /// Returns `false` if the event wasn't starred.
+    pub async fn unstar_event(&self, event_id: String) -> Result<bool, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+        Ok(self.inner.unstar_event(&event_id).await?)
+    }
+
     pub async fn set_is_low_priority(
         &self,
         is_low_priority: bool,
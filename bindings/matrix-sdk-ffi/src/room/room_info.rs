@@ -51,6 +51,9 @@ pub struct RoomInfo {
     highlight_count: u64,
     notification_count: u64,
     cached_user_defined_notification_mode: Option<RoomNotificationMode>,
+    /// The notification mode of the room, either user defined or the default
+    /// one for this kind of room, kept up to date by the sync.
+    cached_notification_mode: Option<RoomNotificationMode>,
     has_room_call: bool,
     active_room_call_participants: Vec<String>,
     /// Whether this room has been explicitly marked as unread
@@ -143,6 +146,7 @@ impl RoomInfo {
             cached_user_defined_notification_mode: room
                 .cached_user_defined_notification_mode()
                 .map(Into::into),
+            cached_notification_mode: room.cached_notification_mode().map(Into::into),
             has_room_call: room.has_active_room_call(),
             active_room_call_participants: room
                 .active_room_call_participants()
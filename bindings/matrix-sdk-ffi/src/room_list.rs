@@ -1,8 +1,14 @@
 #![allow(deprecated)]
 
-use std::{fmt::Debug, mem::MaybeUninit, ptr::addr_of_mut, sync::Arc, time::Duration};
+use std::{
+    fmt::Debug,
+    mem::MaybeUninit,
+    ptr::addr_of_mut,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use eyeball_im::VectorDiff;
+use eyeball_im::{Vector, VectorDiff};
 use futures_util::{pin_mut, StreamExt};
 use matrix_sdk::{
     ruma::{
@@ -13,12 +19,16 @@ use matrix_sdk::{
 };
 use matrix_sdk_common::{SendOutsideWasm, SyncOutsideWasm};
 use matrix_sdk_ui::{
-    room_list_service::filters::{
-        new_filter_all, new_filter_any, new_filter_category, new_filter_deduplicate_versions,
-        new_filter_favourite, new_filter_fuzzy_match_room_name, new_filter_invite,
-        new_filter_joined, new_filter_low_priority, new_filter_non_left, new_filter_none,
-        new_filter_normalized_match_room_name, new_filter_not, new_filter_space, new_filter_unread,
-        BoxedFilterFn, RoomCategory,
+    room_list_service::{
+        filters::{
+            new_filter_all, new_filter_any, new_filter_category, new_filter_deduplicate_versions,
+            new_filter_encrypted, new_filter_favourite, new_filter_fuzzy_match_room_name,
+            new_filter_in_space, new_filter_invite, new_filter_joined, new_filter_low_priority,
+            new_filter_non_left, new_filter_none, new_filter_normalized_match_room_name,
+            new_filter_not, new_filter_section, new_filter_space, new_filter_unread, BoxedFilterFn,
+            RoomCategory, RoomListSection,
+        },
+        sorters::new_sorter_fuzzy_match_room_name,
     },
     unable_to_decrypt_hook::UtdHookManager,
 };
@@ -26,6 +36,7 @@ use matrix_sdk_ui::{
 use crate::{
     room::{Membership, Room},
     runtime::get_runtime_handle,
+    spaces::SpaceService,
     TaskHandle,
 };
 
@@ -48,6 +59,8 @@ pub enum RoomListError {
          observed {actual:?}"
     )]
     IncorrectRoomMembership { expected: Vec<Membership>, actual: Membership },
+    #[error("invalid room list settings: {error}")]
+    InvalidSettings { error: String },
 }
 
 impl From<matrix_sdk_ui::room_list_service::Error> for RoomListError {
@@ -59,6 +72,7 @@ impl From<matrix_sdk_ui::room_list_service::Error> for RoomListError {
             UnknownList(list_name) => Self::UnknownList { list_name },
             RoomNotFound(room_id) => Self::RoomNotFound { room_name: room_id.to_string() },
             EventCache(error) => Self::EventCache { error: error.to_string() },
+            InvalidSettings(error) => Self::InvalidSettings { error: error.to_string() },
         }
     }
 }
@@ -136,6 +150,127 @@ impl RoomListService {
 
         Ok(())
     }
+
+    /// Subscribe to the rooms the user has been invited to, with the profile
+    /// of the user who sent each invite.
+    ///
+    /// The listener is called with all the invites every time they change.
+    fn subscribe_to_invites(
+        &self,
+        listener: Box<dyn RoomListServiceInvitesListener>,
+    ) -> Arc<TaskHandle> {
+        let invites_stream = self.inner.subscribe_to_invites();
+        let utd_hook = self.utd_hook.clone();
+
+        Arc::new(TaskHandle::new(get_runtime_handle().spawn(async move {
+            pin_mut!(invites_stream);
+
+            let mut invites = Vector::new();
+
+            while let Some(diffs) = invites_stream.next().await {
+                for diff in diffs {
+                    diff.apply(&mut invites);
+                }
+
+                listener.on_update(
+                    invites
+                        .iter()
+                        .cloned()
+                        .map(|invite| RoomListInvite::new(invite, utd_hook.clone()))
+                        .collect(),
+                );
+            }
+        })))
+    }
+
+    /// Subscribe to the rooms the user has knocked on, with the details of
+    /// each knock.
+    ///
+    /// The listener is called with all the knocks every time they change.
+    fn subscribe_to_knocks(
+        &self,
+        listener: Box<dyn RoomListServiceKnocksListener>,
+    ) -> Arc<TaskHandle> {
+        let knocks_stream = self.inner.subscribe_to_knocks();
+        let utd_hook = self.utd_hook.clone();
+
+        Arc::new(TaskHandle::new(get_runtime_handle().spawn(async move {
+            pin_mut!(knocks_stream);
+
+            let mut knocks = Vector::new();
+
+            while let Some(diffs) = knocks_stream.next().await {
+                for diff in diffs {
+                    diff.apply(&mut knocks);
+                }
+
+                listener.on_update(
+                    knocks
+                        .iter()
+                        .cloned()
+                        .map(|knock| RoomListKnock::new(knock, utd_hook.clone()))
+                        .collect(),
+                );
+            }
+        })))
+    }
+}
+
+/// A room the user has been invited to.
+#[derive(uniffi::Record)]
+pub struct RoomListInvite {
+    /// The room the user has been invited to.
+    pub room: Arc<Room>,
+    /// The profile of the user who sent the invite, if known.
+    pub inviter: Option<InviterProfile>,
+}
+
+impl RoomListInvite {
+    fn new(
+        invite: matrix_sdk_ui::room_list_service::RoomListInvite,
+        utd_hook: Option<Arc<UtdHookManager>>,
+    ) -> Self {
+        Self {
+            room: Arc::new(Room::new(invite.room().clone(), utd_hook)),
+            inviter: invite.inviter().map(|inviter| InviterProfile {
+                user_id: inviter.user_id.to_string(),
+                display_name: inviter.display_name.clone(),
+                avatar_url: inviter.avatar_url.as_ref().map(ToString::to_string),
+            }),
+        }
+    }
+}
+
+/// The profile of the user who sent an invite.
+#[derive(uniffi::Record)]
+pub struct InviterProfile {
+    pub user_id: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+/// A room the user has knocked on.
+#[derive(uniffi::Record)]
+pub struct RoomListKnock {
+    /// The room the user has knocked on.
+    pub room: Arc<Room>,
+    /// The reason the user gave when knocking, if any.
+    pub reason: Option<String>,
+    /// When the user knocked, in milliseconds since the Unix epoch, if known.
+    pub knocked_at: Option<u64>,
+}
+
+impl RoomListKnock {
+    fn new(
+        knock: matrix_sdk_ui::room_list_service::RoomListKnock,
+        utd_hook: Option<Arc<UtdHookManager>>,
+    ) -> Self {
+        Self {
+            room: Arc::new(Room::new(knock.room().clone(), utd_hook)),
+            reason: knock.reason().map(ToOwned::to_owned),
+            knocked_at: knock.knocked_at().map(|timestamp| timestamp.0.into()),
+        }
+    }
 }
 
 #[derive(uniffi::Object)]
@@ -368,6 +503,16 @@ pub trait RoomListServiceSyncIndicatorListener: SendOutsideWasm + SyncOutsideWas
     fn on_update(&self, sync_indicator: RoomListServiceSyncIndicator);
 }
 
+#[matrix_sdk_ffi_macros::export(callback_interface)]
+pub trait RoomListServiceInvitesListener: SendOutsideWasm + SyncOutsideWasm + Debug {
+    fn on_update(&self, invites: Vec<RoomListInvite>);
+}
+
+#[matrix_sdk_ffi_macros::export(callback_interface)]
+pub trait RoomListServiceKnocksListener: SendOutsideWasm + SyncOutsideWasm + Debug {
+    fn on_update(&self, knocks: Vec<RoomListKnock>);
+}
+
 #[derive(uniffi::Enum)]
 pub enum RoomListEntriesUpdate {
     Append { values: Vec<Arc<Room>> },
@@ -430,21 +575,71 @@ pub trait RoomListEntriesListener: SendOutsideWasm + SyncOutsideWasm + Debug {
 
 #[derive(uniffi::Object)]
 pub struct RoomListDynamicEntriesController {
-    inner: matrix_sdk_ui::room_list_service::RoomListDynamicEntriesController,
+    inner: Arc<matrix_sdk_ui::room_list_service::RoomListDynamicEntriesController>,
+    /// The task setting the filter again every time the descendants of a space
+    /// change, see [`Self::set_filter_in_space`].
+    space_filter_task: Mutex<Option<TaskHandle>>,
 }
 
 impl RoomListDynamicEntriesController {
     fn new(
         dynamic_entries_controller: matrix_sdk_ui::room_list_service::RoomListDynamicEntriesController,
     ) -> Self {
-        Self { inner: dynamic_entries_controller }
+        Self { inner: Arc::new(dynamic_entries_controller), space_filter_task: Mutex::new(None) }
     }
 }
 
 #[matrix_sdk_ffi_macros::export]
 impl RoomListDynamicEntriesController {
+    /// Set the filter.
+    ///
+    /// When the filter fuzzy matches the room names, the rooms are sorted by
+    /// relevance first, i.e. the best matches come first.
     fn set_filter(&self, kind: RoomListEntriesDynamicFilterKind) -> bool {
-        self.inner.set_filter(kind.into())
+        // Stop following the descendants of a space, if any.
+        self.space_filter_task.lock().unwrap().take();
+
+        let pattern = kind.fuzzy_match_pattern().map(ToOwned::to_owned);
+        set_filter_with_pattern(&self.inner, kind.into(), pattern.as_deref())
+    }
+
+    /// Set the filter, and only keep the rooms which are descendants of the
+    /// given space, i.e. its children, their children, and so on.
+    ///
+    /// Unlike the `InSpace` filter, the descendants of the space are observed
+    /// with `SpaceService::subscribe_to_space_descendants`, and the filter is
+    /// set again every time they change, until another filter is set.
+    fn set_filter_in_space(
+        &self,
+        space_service: Arc<SpaceService>,
+        space_id: String,
+        kind: RoomListEntriesDynamicFilterKind,
+    ) -> Result<(), RoomListError> {
+        let space_id = RoomId::parse(space_id)?;
+        let descendants_stream = space_service.inner.subscribe_to_space_descendants(space_id);
+        let inner = self.inner.clone();
+
+        let task = TaskHandle::new(get_runtime_handle().spawn(async move {
+            pin_mut!(descendants_stream);
+
+            let pattern = kind.fuzzy_match_pattern().map(ToOwned::to_owned);
+
+            while let Some(descendants) = descendants_stream.next().await {
+                let filter = Box::new(new_filter_all(vec![
+                    Box::new(new_filter_in_space(descendants)),
+                    kind.clone().into(),
+                ]));
+
+                if !set_filter_with_pattern(&inner, filter, pattern.as_deref()) {
+                    // The stream of the room list has been dropped.
+                    break;
+                }
+            }
+        }));
+
+        *self.space_filter_task.lock().unwrap() = Some(task);
+
+        Ok(())
     }
 
     fn add_one_page(&self) {
@@ -456,10 +651,20 @@ impl RoomListDynamicEntriesController {
     }
 }
 
-#[derive(uniffi::Enum)]
+#[derive(Clone, uniffi::Enum)]
 pub enum RoomListEntriesDynamicFilterKind {
-    All { filters: Vec<RoomListEntriesDynamicFilterKind> },
-    Any { filters: Vec<RoomListEntriesDynamicFilterKind> },
+    All {
+        filters: Vec<RoomListEntriesDynamicFilterKind>,
+    },
+    Any {
+        filters: Vec<RoomListEntriesDynamicFilterKind>,
+    },
+    /// The rooms which match none of the filters, i.e. the negation of
+    /// `Any`. With a single filter, it negates this filter.
+    NotAny {
+        filters: Vec<RoomListEntriesDynamicFilterKind>,
+    },
+    Space,
     NonSpace,
     NonLeft,
     // Not { filter: RoomListEntriesDynamicFilterKind } - requires recursive enum
@@ -470,19 +675,89 @@ pub enum RoomListEntriesDynamicFilterKind {
     LowPriority,
     NonLowPriority,
     Invite,
-    Category { expect: RoomListFilterCategory },
+    /// The rooms which are known to be encrypted.
+    Encrypted,
+    Category {
+        expect: RoomListFilterCategory,
+    },
+    /// The rooms of one section of the room list, each room belonging to
+    /// exactly one section.
+    Section {
+        section: RoomListFilterSection,
+    },
+    /// The rooms which are descendants of a space, as returned by
+    /// `SpaceService::space_descendants`. The invalid room IDs are ignored.
+    ///
+    /// The room IDs aren't updated when the space changes, see
+    /// `RoomListDynamicEntriesController::set_filter_in_space` to do so.
+    InSpace {
+        room_ids: Vec<String>,
+    },
     None,
-    NormalizedMatchRoomName { pattern: String },
-    FuzzyMatchRoomName { pattern: String },
+    NormalizedMatchRoomName {
+        pattern: String,
+    },
+    FuzzyMatchRoomName {
+        pattern: String,
+    },
     DeduplicateVersions,
 }
 
-#[derive(uniffi::Enum)]
+/// Set the filter of `controller`, and sort the rooms by relevance first if the
+/// filter fuzzy matches the room names with `pattern`.
+fn set_filter_with_pattern(
+    controller: &matrix_sdk_ui::room_list_service::RoomListDynamicEntriesController,
+    filter: BoxedFilterFn,
+    pattern: Option<&str>,
+) -> bool {
+    match pattern {
+        Some(pattern) => controller
+            .set_filter_with_sorter(filter, Box::new(new_sorter_fuzzy_match_room_name(pattern))),
+        None => controller.set_filter(filter),
+    }
+}
+
+impl RoomListEntriesDynamicFilterKind {
+    /// The pattern of the fuzzy match on the room names that all the rooms
+    /// passing this filter must satisfy, if any.
+    fn fuzzy_match_pattern(&self) -> Option<&str> {
+        match self {
+            Self::FuzzyMatchRoomName { pattern } => Some(pattern),
+            Self::All { filters } => filters.iter().find_map(Self::fuzzy_match_pattern),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, uniffi::Enum)]
 pub enum RoomListFilterCategory {
     Group,
     People,
 }
 
+#[derive(Clone, uniffi::Enum)]
+pub enum RoomListFilterSection {
+    Invites,
+    Knocks,
+    Favourites,
+    LowPriority,
+    People,
+    Rooms,
+}
+
+impl From<RoomListFilterSection> for RoomListSection {
+    fn from(value: RoomListFilterSection) -> Self {
+        match value {
+            RoomListFilterSection::Invites => Self::Invites,
+            RoomListFilterSection::Knocks => Self::Knocks,
+            RoomListFilterSection::Favourites => Self::Favourites,
+            RoomListFilterSection::LowPriority => Self::LowPriority,
+            RoomListFilterSection::People => Self::People,
+            RoomListFilterSection::Rooms => Self::Rooms,
+        }
+    }
+}
+
 impl From<RoomListFilterCategory> for RoomCategory {
     fn from(value: RoomListFilterCategory) -> Self {
         match value {
@@ -503,7 +778,11 @@ impl From<RoomListEntriesDynamicFilterKind> for BoxedFilterFn {
             Kind::Any { filters } => Box::new(new_filter_any(
                 filters.into_iter().map(|filter| BoxedFilterFn::from(filter)).collect(),
             )),
+            Kind::NotAny { filters } => Box::new(new_filter_not(Box::new(new_filter_any(
+                filters.into_iter().map(|filter| BoxedFilterFn::from(filter)).collect(),
+            )))),
             Kind::NonLeft => Box::new(new_filter_non_left()),
+            Kind::Space => Box::new(new_filter_space()),
             Kind::NonSpace => Box::new(new_filter_not(Box::new(new_filter_space()))),
             Kind::Joined => Box::new(new_filter_joined()),
             Kind::Unread => Box::new(new_filter_unread()),
@@ -511,7 +790,12 @@ impl From<RoomListEntriesDynamicFilterKind> for BoxedFilterFn {
             Kind::LowPriority => Box::new(new_filter_low_priority()),
             Kind::NonLowPriority => Box::new(new_filter_not(Box::new(new_filter_low_priority()))),
             Kind::Invite => Box::new(new_filter_invite()),
+            Kind::Encrypted => Box::new(new_filter_encrypted()),
             Kind::Category { expect } => Box::new(new_filter_category(expect.into())),
+            Kind::Section { section } => Box::new(new_filter_section(section.into())),
+            Kind::InSpace { room_ids } => Box::new(new_filter_in_space(
+                room_ids.iter().filter_map(|room_id| RoomId::parse(room_id).ok()).collect(),
+            )),
             Kind::None => Box::new(new_filter_none()),
             Kind::NormalizedMatchRoomName { pattern } => {
                 Box::new(new_filter_normalized_match_room_name(&pattern))
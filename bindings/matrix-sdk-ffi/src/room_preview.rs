@@ -1,5 +1,8 @@
+use std::sync::Arc;
+
 use anyhow::Context as _;
 use matrix_sdk::{room_preview::RoomPreview as SdkRoomPreview, Client};
+use matrix_sdk_ui::timeline::TimelineBuilder;
 use ruma::room::{JoinRuleSummary, RoomType as RumaRoomType};
 
 use crate::{
@@ -7,6 +10,7 @@ use crate::{
     error::ClientError,
     room::{Membership, RoomHero},
     room_member::{RoomMember, RoomMemberWithSenderInfo},
+    timeline::Timeline,
     utils::AsyncRuntimeDropped,
 };
 
@@ -72,6 +76,16 @@ impl RoomPreview {
         Ok(())
     }
 
+    /// Get a read-only timeline with the `num_events` most recent events of
+    /// the room, if its history is world-readable, even if the room isn't
+    /// known locally.
+    pub async fn timeline(&self, num_events: u16) -> Result<Arc<Timeline>, ClientError> {
+        let timeline = TimelineBuilder::for_room_preview(&self.client, &self.inner, num_events)
+            .build()
+            .await?;
+        Ok(Timeline::new(timeline))
+    }
+
     /// Get the membership details for the current user.
     pub async fn own_membership_details(&self) -> Option<RoomMemberWithSenderInfo> {
         let room = self.client.get_room(&self.inner.room_id)?;
@@ -39,7 +39,7 @@ use crate::{
 /// events, and providing access to the top-level spaces and their children.
 #[derive(uniffi::Object)]
 pub struct SpaceService {
-    inner: UISpaceService,
+    pub(crate) inner: UISpaceService,
 }
 
 impl SpaceService {
@@ -85,6 +85,44 @@ impl SpaceService {
         let space_id = RoomId::parse(space_id)?;
         Ok(Arc::new(SpaceRoomList::new(self.inner.space_room_list(space_id).await)))
     }
+
+    /// Returns the IDs of the descendants of the given space, i.e. its
+    /// children, their children, and so on.
+    ///
+    /// They can be used with the `InSpace` room list filter to only show the
+    /// rooms of a space in a room list.
+    pub async fn space_descendants(&self, space_id: String) -> Result<Vec<String>, ClientError> {
+        let space_id = RoomId::parse(space_id)?;
+        Ok(self
+            .inner
+            .space_descendants(&space_id)
+            .await
+            .into_iter()
+            .map(|room_id| room_id.to_string())
+            .collect())
+    }
+
+    /// Subscribes to the descendants of the given space. The listener is
+    /// called with the current descendants first, and then with the new ones
+    /// every time they change.
+    pub fn subscribe_to_space_descendants(
+        &self,
+        space_id: String,
+        listener: Box<dyn SpaceServiceSpaceDescendantsListener>,
+    ) -> Result<Arc<TaskHandle>, ClientError> {
+        let space_id = RoomId::parse(space_id)?;
+        let stream = self.inner.subscribe_to_space_descendants(space_id);
+
+        Ok(Arc::new(TaskHandle::new(get_runtime_handle().spawn(async move {
+            pin_mut!(stream);
+
+            while let Some(descendants) = stream.next().await {
+                listener.on_update(
+                    descendants.into_iter().map(|room_id| room_id.to_string()).collect(),
+                );
+            }
+        }))))
+    }
 }
 
 /// The `SpaceRoomList`represents a paginated list of direct rooms
@@ -202,6 +240,11 @@ pub trait SpaceServiceJoinedSpacesListener: SendOutsideWasm + SyncOutsideWasm +
     fn on_update(&self, room_updates: Vec<SpaceListUpdate>);
 }
 
+#[matrix_sdk_ffi_macros::export(callback_interface)]
+pub trait SpaceServiceSpaceDescendantsListener: SendOutsideWasm + SyncOutsideWasm + Debug {
+    fn on_update(&self, room_ids: Vec<String>);
+}
+
 /// Structure representing a room in a space and aggregated information
 /// relevant to the UI layer.
 #[derive(uniffi::Record)]
@@ -18,9 +18,11 @@ use futures_util::pin_mut;
 use matrix_sdk::Client;
 use matrix_sdk_common::{SendOutsideWasm, SyncOutsideWasm};
 use matrix_sdk_ui::{
+    room_list_service::RoomListServiceSettings as MatrixRoomListServiceSettings,
     sync_service::{
-        State as MatrixSyncServiceState, SyncService as MatrixSyncService,
-        SyncServiceBuilder as MatrixSyncServiceBuilder,
+        State as MatrixSyncServiceState, SyncErrorKind as MatrixSyncErrorKind,
+        SyncService as MatrixSyncService, SyncServiceBuilder as MatrixSyncServiceBuilder,
+        SyncServiceStatus as MatrixSyncServiceStatus,
     },
     unable_to_decrypt_hook::UtdHookManager,
 };
@@ -56,6 +58,68 @@ pub trait SyncServiceStateObserver: SendOutsideWasm + SyncOutsideWasm + Debug {
     fn on_update(&self, state: SyncServiceState);
 }
 
+/// The kind of an error the sync service ran into.
+#[derive(uniffi::Enum)]
+pub enum SyncErrorKind {
+    /// The access token has expired or isn't known by the server.
+    TokenExpired {
+        /// Whether the session can be restored by logging in again.
+        soft_logout: bool,
+    },
+    /// The server couldn't be reached.
+    Network,
+    /// The server responded with an error in the 5xx range.
+    Server { status_code: u16 },
+    /// The server asked to send fewer requests.
+    RateLimited {
+        /// How long the server asked to wait before sending another request.
+        retry_after_ms: Option<u64>,
+    },
+    /// Any other error.
+    Other,
+}
+
+impl From<MatrixSyncErrorKind> for SyncErrorKind {
+    fn from(value: MatrixSyncErrorKind) -> Self {
+        match value {
+            MatrixSyncErrorKind::TokenExpired { soft_logout } => Self::TokenExpired { soft_logout },
+            MatrixSyncErrorKind::Network => Self::Network,
+            MatrixSyncErrorKind::Server { status_code } => Self::Server { status_code },
+            MatrixSyncErrorKind::RateLimited { retry_after } => Self::RateLimited {
+                retry_after_ms: retry_after.map(|delay| delay.as_millis() as u64),
+            },
+            MatrixSyncErrorKind::Other => Self::Other,
+        }
+    }
+}
+
+/// The state of the sync service, with details about the last error it ran
+/// into and its attempts to reach the server.
+#[derive(uniffi::Record)]
+pub struct SyncServiceStatus {
+    pub state: SyncServiceState,
+    /// The kind of the last error, reset once the sync service runs again.
+    pub last_error: Option<SyncErrorKind>,
+    /// The delay before the next attempt to reach the server, in the offline
+    /// state.
+    pub backoff_ms: Option<u64>,
+}
+
+impl From<MatrixSyncServiceStatus> for SyncServiceStatus {
+    fn from(value: MatrixSyncServiceStatus) -> Self {
+        Self {
+            state: value.state.into(),
+            last_error: value.last_error.map(Into::into),
+            backoff_ms: value.backoff.map(|backoff| backoff.as_millis() as u64),
+        }
+    }
+}
+
+#[matrix_sdk_ffi_macros::export(callback_interface)]
+pub trait SyncServiceStatusObserver: SendOutsideWasm + SyncOutsideWasm + Debug {
+    fn on_update(&self, status: SyncServiceStatus);
+}
+
 #[derive(uniffi::Object)]
 pub struct SyncService {
     pub(crate) inner: Arc<MatrixSyncService>,
@@ -91,6 +155,21 @@ impl SyncService {
         })))
     }
 
+    /// Observe the state of the sync service, with details about the last
+    /// error it ran into and the delay before its next attempt to reach the
+    /// server.
+    pub fn status(&self, listener: Box<dyn SyncServiceStatusObserver>) -> Arc<TaskHandle> {
+        let status_stream = self.inner.status();
+
+        Arc::new(TaskHandle::new(get_runtime_handle().spawn(async move {
+            pin_mut!(status_stream);
+
+            while let Some(status) = status_stream.next().await {
+                listener.on_update(status.into());
+            }
+        })))
+    }
+
     /// Force expiring both sliding sync sessions.
     ///
     /// This ensures that the sync service is stopped before expiring both
@@ -99,6 +178,23 @@ impl SyncService {
     pub async fn expire_sessions(&self) {
         self.inner.expire_sessions().await;
     }
+
+    /// Tell the sync service whether the network is available.
+    ///
+    /// While the network is unavailable, the sync service is in the offline
+    /// state and doesn't send any request. It restarts syncing as soon as the
+    /// network is available again.
+    pub async fn set_network_available(&self, available: bool) {
+        self.inner.set_network_available(available).await;
+    }
+
+    /// Whether the sync service has fallen back to sync v2, because the server
+    /// doesn't support sliding sync.
+    ///
+    /// See [`SyncServiceBuilder::with_sync_v2_fallback`].
+    pub async fn uses_sync_v2(&self) -> bool {
+        self.inner.uses_sync_v2().await
+    }
 }
 
 #[derive(Clone, uniffi::Object)]
@@ -128,12 +224,32 @@ impl SyncServiceBuilder {
         Arc::new(Self { builder, ..this })
     }
 
+    /// Fall back to sync v2 if the homeserver doesn't support sliding sync.
+    pub fn with_sync_v2_fallback(self: Arc<Self>) -> Arc<Self> {
+        let this = unwrap_or_clone_arc(self);
+        let builder = this.builder.with_sync_v2_fallback();
+        Arc::new(Self { builder, ..this })
+    }
+
     pub fn with_share_pos(self: Arc<Self>, enable: bool) -> Arc<Self> {
         let this = unwrap_or_clone_arc(self);
         let builder = this.builder.with_share_pos(enable);
         Arc::new(Self { builder, ..this })
     }
 
+    /// Customize the required state and the timeline limits of the sliding
+    /// sync of the room list service.
+    ///
+    /// The settings are validated when calling [`Self::finish`].
+    pub fn with_room_list_settings(
+        self: Arc<Self>,
+        settings: RoomListServiceSettings,
+    ) -> Arc<Self> {
+        let this = unwrap_or_clone_arc(self);
+        let builder = this.builder.with_room_list_settings(settings.into());
+        Arc::new(Self { builder, ..this })
+    }
+
     pub async fn finish(self: Arc<Self>) -> Result<Arc<SyncService>, ClientError> {
         let this = unwrap_or_clone_arc(self);
         Ok(Arc::new(SyncService {
@@ -142,3 +258,47 @@ impl SyncServiceBuilder {
         }))
     }
 }
+
+/// A state event requested by the sliding sync of the room list service.
+#[derive(uniffi::Record)]
+pub struct RequiredState {
+    /// The type of the state event, e.g. `m.room.topic`.
+    pub event_type: String,
+    /// The state key of the state event, which can be `*` for all the state
+    /// keys, or `$ME` for the state key of the current user.
+    pub state_key: String,
+}
+
+/// The settings of the sliding sync of the room list service.
+#[derive(uniffi::Record)]
+pub struct RoomListServiceSettings {
+    /// The state events to request in addition to the default ones.
+    pub extra_required_state: Vec<RequiredState>,
+    /// The number of events requested for the rooms of the list, 1 by
+    /// default.
+    pub timeline_limit: Option<u32>,
+    /// The number of events requested for the subscribed rooms, 20 by
+    /// default.
+    pub room_subscription_timeline_limit: Option<u32>,
+}
+
+impl From<RoomListServiceSettings> for MatrixRoomListServiceSettings {
+    fn from(value: RoomListServiceSettings) -> Self {
+        let mut settings = value.extra_required_state.into_iter().fold(
+            MatrixRoomListServiceSettings::default(),
+            |settings, RequiredState { event_type, state_key }| {
+                settings.with_required_state(event_type.into(), state_key)
+            },
+        );
+
+        if let Some(timeline_limit) = value.timeline_limit {
+            settings = settings.with_timeline_limit(timeline_limit);
+        }
+
+        if let Some(timeline_limit) = value.room_subscription_timeline_limit {
+            settings = settings.with_room_subscription_timeline_limit(timeline_limit);
+        }
+
+        settings
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use matrix_sdk_ui::timeline::event_type_filter::TimelineEventTypeFilter as InnerTimelineEventTypeFilter;
 use ruma::{
@@ -84,6 +84,18 @@ pub enum TimelineFocus {
         max_events_to_load: u16,
         max_concurrent_requests: u16,
     },
+    /// Only show the events starred with
+    /// [`Room::star_event`](crate::room::Room::star_event).
+    StarredEvents {
+        max_events_to_load: u16,
+        max_concurrent_requests: u16,
+    },
+    /// Preview the most recent events of a room the user isn't a member of,
+    /// if its history is world-readable.
+    Preview {
+        /// The number of events to load initially.
+        num_events: u16,
+    },
 }
 
 impl TryFrom<TimelineFocus> for matrix_sdk_ui::timeline::TimelineFocus {
@@ -120,16 +132,21 @@ impl TryFrom<TimelineFocus> for matrix_sdk_ui::timeline::TimelineFocus {
             TimelineFocus::PinnedEvents { max_events_to_load, max_concurrent_requests } => {
                 Ok(Self::PinnedEvents { max_events_to_load, max_concurrent_requests })
             }
+            TimelineFocus::StarredEvents { max_events_to_load, max_concurrent_requests } => {
+                Ok(Self::StarredEvents { max_events_to_load, max_concurrent_requests })
+            }
+            TimelineFocus::Preview { num_events } => Ok(Self::Preview { num_events }),
         }
     }
 }
 
 /// Changes how date dividers get inserted, either in between each day or in
-/// between each month
+/// between each month, or not at all.
 #[derive(uniffi::Enum)]
 pub enum DateDividerMode {
     Daily,
     Monthly,
+    Disabled,
 }
 
 impl From<DateDividerMode> for matrix_sdk_ui::timeline::DateDividerMode {
@@ -137,6 +154,62 @@ impl From<DateDividerMode> for matrix_sdk_ui::timeline::DateDividerMode {
         match value {
             DateDividerMode::Daily => Self::Daily,
             DateDividerMode::Monthly => Self::Monthly,
+            DateDividerMode::Disabled => Self::None,
+        }
+    }
+}
+
+/// The mapping from the `msgtype`s of the messages to the names of the
+/// effects they trigger, like `confetti`.
+#[derive(uniffi::Enum)]
+pub enum MessageEffects {
+    /// Recognize the effects sent by Element: `confetti`, `fireworks`,
+    /// `space_invaders`, and the `io.element.effect.*` ones.
+    Default,
+    /// Only recognize the given effects.
+    Custom {
+        /// The names of the effects, by the `msgtype` of the messages
+        /// triggering them.
+        names_by_msgtype: HashMap<String, String>,
+        /// The prefixes of the `msgtype`s recognized as effects, named after
+        /// the rest of their `msgtype`.
+        msgtype_prefixes: Vec<String>,
+    },
+}
+
+impl From<MessageEffects> for matrix_sdk_ui::timeline::MessageEffects {
+    fn from(value: MessageEffects) -> Self {
+        match value {
+            MessageEffects::Default => Self::default(),
+            MessageEffects::Custom { names_by_msgtype, msgtype_prefixes } => {
+                let effects =
+                    names_by_msgtype.into_iter().fold(Self::empty(), |effects, (msgtype, name)| {
+                        effects.with_effect(msgtype, name)
+                    });
+
+                msgtype_prefixes
+                    .into_iter()
+                    .fold(effects, |effects, prefix| effects.with_msgtype_prefix(prefix))
+            }
+        }
+    }
+}
+
+/// The order in which the reactions of an event are kept.
+#[derive(uniffi::Enum)]
+pub enum ReactionOrdering {
+    /// The keys with the most reactions first, the oldest first for the keys
+    /// with the same number of reactions.
+    ByCount,
+    /// The keys which received a reaction most recently first.
+    ByRecency,
+}
+
+impl From<ReactionOrdering> for matrix_sdk_ui::timeline::ReactionOrdering {
+    fn from(value: ReactionOrdering) -> Self {
+        match value {
+            ReactionOrdering::ByCount => Self::ByCount,
+            ReactionOrdering::ByRecency => Self::ByRecency,
         }
     }
 }
@@ -179,6 +252,40 @@ pub struct TimelineConfiguration {
     /// only when you need it.
     pub track_read_receipts: bool,
 
+    /// Should the read receipts only be computed for the window of items set
+    /// with `Timeline::load_receipts_for`?
+    ///
+    /// This enables tracking of the read receipts and read markers, like
+    /// `track_read_receipts`, and is useful for very large rooms.
+    #[uniffi(default = false)]
+    pub lazy_load_read_receipts: bool,
+
+    /// Should the consecutive membership and profile changes received together
+    /// be collapsed into a `VirtualTimelineItem::MembershipSummary`?
+    #[uniffi(default = false)]
+    pub collapse_membership_changes: bool,
+
+    /// The order in which the reactions of the events should be kept, or
+    /// `None` to keep them in the order they were received.
+    #[uniffi(default = None)]
+    pub reaction_ordering: Option<ReactionOrdering>,
+
+    /// Should the previews of the links of the messages be fetched, in the
+    /// rooms where `Room::url_previews_enabled` is true?
+    #[uniffi(default = false)]
+    pub fetch_url_previews: bool,
+
+    /// The time window, in milliseconds, over which the timeline diffs are
+    /// coalesced before being sent to the listeners, or `None` to send them
+    /// right away.
+    #[uniffi(default = None)]
+    pub diffs_coalescing_window_ms: Option<u64>,
+
+    /// The effects to recognize in the messages, setting their
+    /// `MessageContent::effect`, or `None` to not recognize any.
+    #[uniffi(default = None)]
+    pub message_effects: Option<MessageEffects>,
+
     /// Whether this timeline instance should report UTDs through the client's
     /// delegate.
     pub report_utds: bool,
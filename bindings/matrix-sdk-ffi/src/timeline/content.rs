@@ -18,7 +18,10 @@ use matrix_sdk::room::power_levels::power_level_user_changes;
 use matrix_sdk_ui::timeline::RoomPinnedEventsChange;
 use ruma::events::FullStateEventContent;
 
-use crate::{timeline::msg_like::MsgLikeContent, utils::Timestamp};
+use crate::{
+    live_location_share::LastLocation, ruma::LocationContent, timeline::msg_like::MsgLikeContent,
+    utils::Timestamp,
+};
 
 impl From<matrix_sdk_ui::timeline::TimelineItemContent> for TimelineItemContent {
     fn from(value: matrix_sdk_ui::timeline::TimelineItemContent) -> Self {
@@ -37,6 +40,23 @@ impl From<matrix_sdk_ui::timeline::TimelineItemContent> for TimelineItemContent
 
             Content::RtcNotification => TimelineItemContent::RtcNotification,
 
+            Content::LiveLocation(state) => TimelineItemContent::LiveLocation {
+                description: state.description().map(ToOwned::to_owned),
+                is_live: state.is_live(),
+                start_ts: state.beacon_info().ts.into(),
+                timeout_ms: state.beacon_info().timeout.as_millis().try_into().unwrap_or(u64::MAX),
+                last_location: state.last_location().map(|last| LastLocation {
+                    location: LocationContent {
+                        body: "".to_owned(),
+                        geo_uri: last.location.uri.clone(),
+                        description: last.location.description.clone(),
+                        zoom_level: None,
+                        asset: None,
+                    },
+                    ts: last.ts.0.into(),
+                }),
+            },
+
             Content::MembershipChange(membership) => {
                 let reason = match membership.content() {
                     FullStateEventContent::Original { content, .. } => content.reason.clone(),
@@ -110,6 +130,15 @@ pub enum TimelineItemContent {
     },
     CallInvite,
     RtcNotification,
+    /// A live location share, with the last location received for it.
+    LiveLocation {
+        description: Option<String>,
+        /// Whether the share hasn't been stopped, nor has expired.
+        is_live: bool,
+        start_ts: Timestamp,
+        timeout_ms: u64,
+        last_location: Option<LastLocation>,
+    },
     RoomMembership {
         user_id: String,
         user_display_name: Option<String>,
@@ -140,6 +169,10 @@ pub enum TimelineItemContent {
 #[derive(Clone, uniffi::Record)]
 pub struct Reaction {
     pub key: String,
+    /// The number of users who reacted with this key.
+    pub count: u32,
+    /// When the first reaction with this key was sent.
+    pub first_reaction_timestamp: Timestamp,
     pub senders: Vec<ReactionSenderData>,
 }
 
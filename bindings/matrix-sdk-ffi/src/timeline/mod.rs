@@ -16,23 +16,26 @@ use std::{collections::HashMap, fmt::Write as _, fs, panic, sync::Arc};
 
 use anyhow::{Context, Result};
 use eyeball_im::VectorDiff;
-use futures_util::pin_mut;
+use futures_util::{pin_mut, Stream};
 use matrix_sdk::{
     attachment::{
         AttachmentInfo, BaseAudioInfo, BaseFileInfo, BaseImageInfo, BaseVideoInfo, Thumbnail,
     },
     deserialized_responses::{ShieldState as SdkShieldState, ShieldStateCode},
     event_cache::RoomPaginationStatus,
-    room::edit::EditedContent as SdkEditedContent,
+    room::{
+        edit::EditedContent as SdkEditedContent, ReportedContentScore,
+        TryFromReportedContentScoreError,
+    },
 };
 use matrix_sdk_common::{
     executor::{AbortHandle, JoinHandle},
     stream::StreamExt,
 };
 use matrix_sdk_ui::timeline::{
-    self, AttachmentConfig, AttachmentSource, EventItemOrigin,
+    self, futures::AttachmentUploadPhase, AttachmentConfig, AttachmentSource, EventItemOrigin,
     LatestEventValue as UiLatestEventValue, MediaUploadProgress as SdkMediaUploadProgress, Profile,
-    TimelineDetails, TimelineUniqueId as SdkTimelineUniqueId,
+    TimelineDetails, TimelinePaginationStatus, TimelineUniqueId as SdkTimelineUniqueId,
 };
 use mime::Mime;
 use reply::{EmbeddedEventDetails, InReplyToDetails};
@@ -52,7 +55,7 @@ use ruma::{
         },
         AnyMessageLikeEventContent,
     },
-    EventId, UInt,
+    EventId, IdParseError, UInt,
 };
 use tokio::sync::Mutex;
 use tracing::{error, warn};
@@ -63,6 +66,7 @@ pub use self::msg_like::MessageContent;
 use crate::{
     error::{ClientError, RoomError},
     event::EventOrTransactionId,
+    room::{ComposerDraft, Room},
     ruma::{
         AssetType, AudioInfo, FileInfo, FormattedBody, ImageInfo, Mentions, PollKind,
         ThumbnailInfo, VideoInfo,
@@ -131,6 +135,7 @@ impl Timeline {
                 .send_attachment(params.source, mime_type, attachment_config)
                 .use_send_queue()
                 .await
+                .map(|_| ())
                 .map_err(|_| RoomError::FailedSendingAttachment)
         }));
 
@@ -239,11 +244,15 @@ pub struct MediaUploadProgress {
     /// The current combined upload progress for both the file and,
     /// if it exists, its thumbnail.
     pub progress: AbstractProgress,
+
+    /// What's being uploaded, or `Sending` once the media have been uploaded
+    /// and the event is being sent.
+    pub phase: AttachmentUploadPhase,
 }
 
 impl From<SdkMediaUploadProgress> for MediaUploadProgress {
     fn from(value: SdkMediaUploadProgress) -> Self {
-        Self { index: value.index, progress: value.progress.into() }
+        Self { index: value.index, progress: value.progress.into(), phase: value.phase }
     }
 }
 
@@ -271,6 +280,25 @@ impl From<matrix_sdk::send_queue::AbstractProgress> for AbstractProgress {
     }
 }
 
+/// The outcome of an attempt to decrypt the unable-to-decrypt items of a
+/// timeline again.
+#[derive(Clone, Copy, uniffi::Record)]
+pub struct DecryptionRetryReport {
+    /// The number of unable-to-decrypt items whose decryption was retried.
+    pub num_retried: u64,
+    /// The number of items which could be decrypted.
+    pub num_decrypted: u64,
+}
+
+impl From<matrix_sdk_ui::timeline::DecryptionRetryReport> for DecryptionRetryReport {
+    fn from(value: matrix_sdk_ui::timeline::DecryptionRetryReport) -> Self {
+        Self {
+            num_retried: value.num_retried.try_into().unwrap_or(u64::MAX),
+            num_decrypted: value.num_decrypted.try_into().unwrap_or(u64::MAX),
+        }
+    }
+}
+
 #[matrix_sdk_ffi_macros::export]
 impl Timeline {
     pub async fn add_listener(&self, listener: Box<dyn TimelineListener>) -> Arc<TaskHandle> {
@@ -300,10 +328,57 @@ impl Timeline {
         });
     }
 
+    /// Retry the decryption of every unable-to-decrypt item of the timeline,
+    /// for instance after keys have been imported or the key backup has been
+    /// restored, and report how many of them could be decrypted.
+    pub async fn retry_decryption_for_all_utds(&self) -> DecryptionRetryReport {
+        self.inner.retry_decryption_for_all_utds().await.into()
+    }
+
     pub async fn fetch_members(&self) {
         self.inner.fetch_members().await
     }
 
+    /// Compute the read receipts of the items from index `start` (included) to
+    /// `end` (excluded), if the timeline lazily loads its read receipts.
+    ///
+    /// The items outside of this window lose their read receipts, until they
+    /// are requested again.
+    pub async fn load_receipts_for(&self, start: u32, end: u32) {
+        self.inner.load_receipts_for(start as usize..end as usize).await
+    }
+
+    /// Show again the items collapsed into the membership summary item with
+    /// the given unique ID.
+    ///
+    /// Returns `false` if there's no such summary in the timeline.
+    pub async fn expand_membership_summary(&self, unique_id: TimelineUniqueId) -> bool {
+        self.inner.expand_membership_summary(&(&unique_id).into()).await
+    }
+
+    /// Insert a custom virtual item next to its anchor event, replacing the
+    /// custom item with the same ID if any.
+    ///
+    /// The item is kept next to its anchor, and is only in the timeline while
+    /// the anchor event is.
+    pub async fn insert_custom_item(
+        &self,
+        id: String,
+        anchor: CustomItemAnchor,
+        content: String,
+    ) -> Result<(), ClientError> {
+        let anchor = anchor.try_into()?;
+        self.inner.insert_custom_item(timeline::CustomVirtualItem::new(id, anchor, content)).await;
+        Ok(())
+    }
+
+    /// Remove the custom item with the given ID from the timeline.
+    ///
+    /// Returns `false` if there was no such item.
+    pub async fn remove_custom_item(&self, id: String) -> bool {
+        self.inner.remove_custom_item(&id).await
+    }
+
     pub async fn subscribe_to_back_pagination_status(
         &self,
         listener: Box<dyn PaginationStatusListener>,
@@ -328,6 +403,26 @@ impl Timeline {
         }))))
     }
 
+    /// Subscribe to the status of the back-paginations of this timeline,
+    /// whatever its focus mode.
+    pub fn subscribe_to_timeline_back_pagination_status(
+        &self,
+        listener: Box<dyn TimelinePaginationStatusListener>,
+    ) -> Arc<TaskHandle> {
+        let (initial, subscriber) = self.inner.back_pagination_status();
+        listen_to_pagination_status(initial, subscriber, listener)
+    }
+
+    /// Subscribe to the status of the forward-paginations of this timeline,
+    /// whatever its focus mode.
+    pub fn subscribe_to_timeline_forward_pagination_status(
+        &self,
+        listener: Box<dyn TimelinePaginationStatusListener>,
+    ) -> Arc<TaskHandle> {
+        let (initial, subscriber) = self.inner.forward_pagination_status();
+        listen_to_pagination_status(initial, subscriber, listener)
+    }
+
     /// Paginate backwards, whether we are in focused mode or in live mode.
     ///
     /// Returns whether we hit the start of the timeline or not.
@@ -342,6 +437,22 @@ impl Timeline {
         Ok(self.inner.paginate_forwards(num_events).await?)
     }
 
+    /// Paginate backwards until the read marker is in the timeline, to jump to
+    /// the first unread message.
+    ///
+    /// Returns the unique ID of the read marker item, or `None` if the
+    /// fully-read event isn't known or couldn't be found. The fully-read event
+    /// is only known if the timeline has been built with
+    /// `TimelineConfiguration::track_read_receipts` or
+    /// `TimelineConfiguration::lazy_load_read_receipts`.
+    pub async fn paginate_backwards_to_read_marker(
+        &self,
+        num_events: u16,
+    ) -> Result<Option<TimelineUniqueId>, ClientError> {
+        let unique_id = self.inner.paginate_backwards_to_read_marker(num_events).await?;
+        Ok(unique_id.as_ref().map(TimelineUniqueId::from))
+    }
+
     pub async fn send_read_receipt(
         &self,
         receipt_type: ReceiptType,
@@ -615,6 +726,20 @@ impl Timeline {
         Ok(self.inner.toggle_reaction(&item_id.try_into()?, &key).await?)
     }
 
+    /// Forward the room message with the given event ID to another room,
+    /// through the send queue of the target room.
+    ///
+    /// Returns a handle to the local echo of the forwarded message.
+    pub async fn forward(
+        &self,
+        event_id: String,
+        target_room: Arc<Room>,
+    ) -> Result<Arc<SendHandle>, ClientError> {
+        let event_id = EventId::parse(event_id)?;
+        let handle = self.inner.forward(&event_id, &target_room.inner).await?;
+        Ok(Arc::new(SendHandle::new(handle)))
+    }
+
     pub async fn fetch_details_for_event(&self, event_id: String) -> Result<(), ClientError> {
         let event_id = <&EventId>::try_from(event_id.as_str())?;
         self.inner
@@ -662,6 +787,38 @@ impl Timeline {
         Ok(self.inner.redact(&(event_or_transaction_id.try_into()?), reason.as_deref()).await?)
     }
 
+    /// Report an event as inappropriate to the homeserver's administrator.
+    ///
+    /// Only works for remote events that exist as timeline items.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_or_transaction_id` - The ID of the item to report.
+    ///
+    /// * `score` - The score to rate this content as where -100 is most
+    ///   offensive and 0 is inoffensive (optional).
+    ///
+    /// * `reason` - The reason for the event being reported (optional).
+    pub async fn report_event(
+        &self,
+        event_or_transaction_id: EventOrTransactionId,
+        score: Option<i32>,
+        reason: Option<String>,
+    ) -> Result<(), ClientError> {
+        let score = score
+            .map(ReportedContentScore::try_from)
+            .transpose()
+            .map_err(|error: TryFromReportedContentScoreError| ClientError::from_err(error))?;
+
+        Ok(self.inner.report(&(event_or_transaction_id.try_into()?), score, reason).await?)
+    }
+
+    /// Report the room of this timeline as inappropriate to the homeserver's
+    /// administrator.
+    pub async fn report_room(&self, reason: String) -> Result<(), ClientError> {
+        Ok(self.inner.report_room(reason).await?)
+    }
+
     /// Load the reply details for the given event id.
     ///
     /// This will return an `InReplyToDetails` object that contains the details
@@ -721,6 +878,22 @@ impl Timeline {
         self.inner.unpin_event(&event_id).await.map_err(ClientError::from)
     }
 
+    /// Store the draft of the composer in the state store, for the room or
+    /// for the thread this timeline is focused on.
+    pub async fn save_draft(&self, draft: ComposerDraft) -> Result<(), ClientError> {
+        Ok(self.inner.save_draft(draft.try_into()?).await?)
+    }
+
+    /// Retrieve the draft of the composer saved for this timeline, if any.
+    pub async fn load_draft(&self) -> Result<Option<ComposerDraft>, ClientError> {
+        Ok(self.inner.load_draft().await?.map(Into::into))
+    }
+
+    /// Remove the draft of the composer saved for this timeline.
+    pub async fn clear_draft(&self) -> Result<(), ClientError> {
+        Ok(self.inner.clear_draft().await?)
+    }
+
     pub fn create_message_content(
         &self,
         msg_type: crate::ruma::MessageType,
@@ -809,6 +982,29 @@ pub trait PaginationStatusListener: SyncOutsideWasm + SendOutsideWasm {
     fn on_update(&self, status: RoomPaginationStatus);
 }
 
+#[matrix_sdk_ffi_macros::export(callback_interface)]
+pub trait TimelinePaginationStatusListener: SyncOutsideWasm + SendOutsideWasm {
+    fn on_update(&self, status: TimelinePaginationStatus);
+}
+
+fn listen_to_pagination_status(
+    initial: TimelinePaginationStatus,
+    subscriber: impl Stream<Item = TimelinePaginationStatus> + SendOutsideWasm + 'static,
+    listener: Box<dyn TimelinePaginationStatusListener>,
+) -> Arc<TaskHandle> {
+    // Send the current state right away, so that the caller is immediately aware
+    // of it.
+    listener.on_update(initial);
+
+    Arc::new(TaskHandle::new(get_runtime_handle().spawn(async move {
+        pin_mut!(subscriber);
+
+        while let Some(status) = subscriber.next().await {
+            listener.on_update(status);
+        }
+    })))
+}
+
 #[derive(Clone, uniffi::Enum)]
 pub enum TimelineDiff {
     Append { values: Vec<Arc<TimelineItem>> },
@@ -900,6 +1096,18 @@ impl TimelineItem {
             VItem::DateDivider(ts) => Some(VirtualTimelineItem::DateDivider { ts: (*ts).into() }),
             VItem::ReadMarker => Some(VirtualTimelineItem::ReadMarker),
             VItem::TimelineStart => Some(VirtualTimelineItem::TimelineStart),
+            VItem::TimelineReset => Some(VirtualTimelineItem::TimelineReset),
+            VItem::MembershipSummary(summary) => Some(VirtualTimelineItem::MembershipSummary {
+                ts: summary.timestamp().into(),
+                joined_count: summary.joined_count() as u32,
+                left_count: summary.left_count() as u32,
+                profile_changes_count: summary.profile_changes_count() as u32,
+                items: summary.items().iter().cloned().map(TimelineItem::from_arc).collect(),
+            }),
+            VItem::Custom(item) => Some(VirtualTimelineItem::Custom {
+                id: item.id().to_owned(),
+                content: item.content().to_owned(),
+            }),
         }
     }
 
@@ -998,6 +1206,10 @@ pub struct EventTimelineItem {
     sender_profile: ProfileDetails,
     is_own: bool,
     is_editable: bool,
+    /// Whether the event mentions the current user, or the whole room.
+    mentions_me: bool,
+    /// The previews of the links of the message, if they've been fetched.
+    url_previews: Vec<UrlPreview>,
     content: TimelineItemContent,
     timestamp: Timestamp,
     local_send_state: Option<EventSendState>,
@@ -1021,6 +1233,8 @@ impl From<matrix_sdk_ui::timeline::EventTimelineItem> for EventTimelineItem {
             sender_profile: item.sender_profile().clone().into(),
             is_own: item.is_own(),
             is_editable: item.is_editable(),
+            mentions_me: item.mentions_me(),
+            url_previews: item.url_previews().iter().cloned().map(Into::into).collect(),
             content: item.content().clone().into(),
             timestamp: item.timestamp().into(),
             local_send_state: item.send_state().map(|s| s.into()),
@@ -1033,6 +1247,39 @@ impl From<matrix_sdk_ui::timeline::EventTimelineItem> for EventTimelineItem {
     }
 }
 
+/// The preview of a URL, made of the OpenGraph data of the page.
+#[derive(Clone, uniffi::Record)]
+pub struct UrlPreview {
+    /// The URL which has been previewed.
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+    /// The MXC URI of the image of the page.
+    pub image: Option<String>,
+    /// The width of the image, in pixels.
+    pub image_width: Option<u64>,
+    /// The height of the image, in pixels.
+    pub image_height: Option<u64>,
+    /// The size of the image, in bytes.
+    pub image_size: Option<u64>,
+}
+
+impl From<matrix_sdk::media::UrlPreview> for UrlPreview {
+    fn from(preview: matrix_sdk::media::UrlPreview) -> Self {
+        Self {
+            url: preview.url,
+            title: preview.title,
+            description: preview.description,
+            site_name: preview.site_name,
+            image: preview.image.map(|uri| uri.to_string()),
+            image_width: preview.image_width.map(Into::into),
+            image_height: preview.image_height.map(Into::into),
+            image_size: preview.image_size.map(Into::into),
+        }
+    }
+}
+
 #[derive(Clone, uniffi::Record)]
 pub struct Receipt {
     pub timestamp: Option<Timestamp>,
@@ -1049,6 +1296,12 @@ pub struct EventTimelineItemDebugInfo {
     model: String,
     original_json: Option<String>,
     latest_edit_json: Option<String>,
+    encrypted_json: Option<String>,
+    algorithm: Option<String>,
+    sender_key: Option<String>,
+    sender_device: Option<String>,
+    session_id: Option<String>,
+    forwarding_curve25519_key_chain: Vec<String>,
 }
 
 #[derive(Clone, uniffi::Enum)]
@@ -1175,6 +1428,54 @@ pub enum VirtualTimelineItem {
 
     /// The timeline start, that is, the *oldest* event in time for that room.
     TimelineStart,
+
+    /// The timeline has been reset because of a gap in the history, e.g. after
+    /// a limited sync; the events before and after it may not be contiguous.
+    /// It's removed once the gap has been filled by back-paginating.
+    TimelineReset,
+
+    /// Membership and profile changes collapsed into a single item, which can
+    /// be expanded with [`Timeline::expand_membership_summary`].
+    MembershipSummary {
+        /// The timestamp of the first collapsed event.
+        ts: Timestamp,
+        /// The number of users who joined the room.
+        joined_count: u32,
+        /// The number of users who left the room, or were removed from it.
+        left_count: u32,
+        /// The number of users who changed their display name or avatar.
+        profile_changes_count: u32,
+        /// The collapsed items.
+        items: Vec<Arc<TimelineItem>>,
+    },
+
+    /// An item inserted with [`Timeline::insert_custom_item`].
+    Custom {
+        /// The identifier chosen when inserting the item.
+        id: String,
+        /// The content chosen when inserting the item.
+        content: String,
+    },
+}
+
+/// Where a custom virtual item is placed in the timeline.
+#[derive(uniffi::Enum)]
+pub enum CustomItemAnchor {
+    /// Right before the event with the given ID.
+    Before { event_id: String },
+    /// Right after the event with the given ID.
+    After { event_id: String },
+}
+
+impl TryFrom<CustomItemAnchor> for timeline::CustomItemAnchor {
+    type Error = IdParseError;
+
+    fn try_from(value: CustomItemAnchor) -> Result<Self, Self::Error> {
+        Ok(match value {
+            CustomItemAnchor::Before { event_id } => Self::Before(EventId::parse(event_id)?),
+            CustomItemAnchor::After { event_id } => Self::After(EventId::parse(event_id)?),
+        })
+    }
 }
 
 /// A [`TimelineItem`](super::TimelineItem) that doesn't correspond to an event.
@@ -1268,10 +1569,22 @@ impl LazyTimelineItemProvider {
 
     /// Returns some debug information for this event timeline item.
     fn debug_info(&self) -> EventTimelineItemDebugInfo {
+        let source = self.0.source();
+
         EventTimelineItemDebugInfo {
             model: format!("{:#?}", self.0),
             original_json: self.0.original_json().map(|raw| raw.json().get().to_owned()),
             latest_edit_json: self.0.latest_edit_json().map(|raw| raw.json().get().to_owned()),
+            encrypted_json: source
+                .and_then(|source| source.encrypted_json())
+                .map(|raw| raw.json().get().to_owned()),
+            algorithm: source.and_then(|source| source.algorithm()).map(|a| a.to_string()),
+            sender_key: source.and_then(|source| source.sender_key()).map(ToOwned::to_owned),
+            sender_device: source.and_then(|source| source.sender_device()).map(|d| d.to_string()),
+            session_id: source.and_then(|source| source.session_id()).map(ToOwned::to_owned),
+            forwarding_curve25519_key_chain: source
+                .map(|source| source.forwarding_curve25519_key_chain().to_vec())
+                .unwrap_or_default(),
         }
     }
 
@@ -15,7 +15,10 @@
 use std::{collections::HashMap, sync::Arc};
 
 use matrix_sdk::crypto::types::events::UtdCause;
-use ruma::events::{room::MediaSource as RumaMediaSource, MessageLikeEventContent};
+use ruma::{
+    events::{room::MediaSource as RumaMediaSource, MessageLikeEventContent},
+    MilliSecondsSinceUnixEpoch,
+};
 
 use super::{
     content::Reaction,
@@ -73,6 +76,29 @@ pub struct MessageContent {
     pub body: String,
     pub is_edited: bool,
     pub mentions: Option<Mentions>,
+    /// The `mentions`, or the ones detected in the body if there are none.
+    pub parsed_mentions: Mentions,
+    /// The name of the effect the message triggers, like `confetti`, when
+    /// `TimelineConfiguration::message_effects` recognizes its `msgtype`.
+    pub effect: Option<String>,
+}
+
+impl TryFrom<&matrix_sdk_ui::timeline::Message> for MessageContent {
+    type Error = (ClientError, String);
+
+    fn try_from(message: &matrix_sdk_ui::timeline::Message) -> Result<Self, Self::Error> {
+        let msg_type = TryInto::<MessageType>::try_into(message.msgtype().clone())
+            .map_err(|e| (e, message.msgtype().msgtype().to_owned()))?;
+
+        Ok(Self {
+            msg_type,
+            body: message.body().to_owned(),
+            is_edited: message.is_edited(),
+            mentions: message.mentions().cloned().map(|m| m.into()),
+            parsed_mentions: message.parsed_mentions().into(),
+            effect: message.effect().map(ToOwned::to_owned),
+        })
+    }
 }
 
 impl TryFrom<matrix_sdk_ui::timeline::MsgLikeContent> for MsgLikeContent {
@@ -86,6 +112,13 @@ impl TryFrom<matrix_sdk_ui::timeline::MsgLikeContent> for MsgLikeContent {
             .iter()
             .map(|(k, v)| Reaction {
                 key: k.to_owned(),
+                count: v.len() as u32,
+                first_reaction_timestamp: v
+                    .values()
+                    .map(|info| info.timestamp)
+                    .min()
+                    .unwrap_or(MilliSecondsSinceUnixEpoch(0u32.into()))
+                    .into(),
                 senders: v
                     .into_iter()
                     .map(|(sender_id, info)| ReactionSenderData {
@@ -103,25 +136,13 @@ impl TryFrom<matrix_sdk_ui::timeline::MsgLikeContent> for MsgLikeContent {
         let thread_summary = value.thread_summary.map(|t| Arc::new(t.into()));
 
         Ok(match value.kind {
-            Kind::Message(message) => {
-                let msg_type = TryInto::<MessageType>::try_into(message.msgtype().clone())
-                    .map_err(|e| (e, message.msgtype().msgtype().to_owned()))?;
-
-                Self {
-                    kind: MsgLikeKind::Message {
-                        content: MessageContent {
-                            msg_type,
-                            body: message.body().to_owned(),
-                            is_edited: message.is_edited(),
-                            mentions: message.mentions().cloned().map(|m| m.into()),
-                        },
-                    },
-                    reactions,
-                    in_reply_to,
-                    thread_root,
-                    thread_summary,
-                }
-            }
+            Kind::Message(message) => Self {
+                kind: MsgLikeKind::Message { content: (&message).try_into()? },
+                reactions,
+                in_reply_to,
+                thread_root,
+                thread_summary,
+            },
             Kind::Sticker(sticker) => {
                 let content = sticker.content();
 
@@ -242,6 +263,8 @@ pub struct PollAnswer {
 pub struct ThreadSummary {
     pub latest_event: EmbeddedEventDetails,
     pub num_replies: u32,
+    pub num_participants: Option<u32>,
+    pub is_unread: bool,
 }
 
 #[matrix_sdk_ffi_macros::export]
@@ -253,6 +276,16 @@ impl ThreadSummary {
     pub fn num_replies(&self) -> u64 {
         self.num_replies as u64
     }
+
+    /// The number of distinct users who replied in the thread, if known.
+    pub fn num_participants(&self) -> Option<u64> {
+        self.num_participants.map(u64::from)
+    }
+
+    /// Whether the latest reply hasn't been read by the current user.
+    pub fn is_unread(&self) -> bool {
+        self.is_unread
+    }
 }
 
 impl From<matrix_sdk_ui::timeline::ThreadSummary> for ThreadSummary {
@@ -260,6 +293,8 @@ impl From<matrix_sdk_ui::timeline::ThreadSummary> for ThreadSummary {
         Self {
             latest_event: EmbeddedEventDetails::from(value.latest_event),
             num_replies: value.num_replies,
+            num_participants: value.num_participants,
+            is_unread: value.is_unread,
         }
     }
 }
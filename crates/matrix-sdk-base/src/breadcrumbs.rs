@@ -0,0 +1,101 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Data types used for handling the breadcrumbs, i.e. the rooms the user
+//! visited recently.
+//!
+//! There is no formal spec for this, only the implementation in Element Web:
+//! <https://github.com/element-hq/element-web/blob/develop/src/stores/BreadcrumbsStore.ts>
+
+use ruma::{OwnedRoomId, events::macros::EventContent};
+use serde::{Deserialize, Serialize};
+
+/// An event type containing the list of rooms the user visited recently.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "im.vector.setting.breadcrumbs", kind = GlobalAccountData)]
+pub struct BreadcrumbsEventContent {
+    /// The rooms the user visited recently, the most recent first.
+    #[serde(default)]
+    pub recent_rooms: Vec<OwnedRoomId>,
+}
+
+impl BreadcrumbsEventContent {
+    /// Creates a new breadcrumbs event content given the provided recent
+    /// rooms.
+    pub fn new(recent_rooms: Vec<OwnedRoomId>) -> Self {
+        Self { recent_rooms }
+    }
+
+    /// Move the given room to the front of the breadcrumbs, keeping at most
+    /// `max_count` rooms.
+    ///
+    /// Returns `false` if the room was already the most recent one, i.e. the
+    /// content didn't change.
+    pub fn track_visit(&mut self, room_id: OwnedRoomId, max_count: usize) -> bool {
+        if self.recent_rooms.first() == Some(&room_id) {
+            return false;
+        }
+
+        self.recent_rooms.retain(|id| *id != room_id);
+        self.recent_rooms.insert(0, room_id);
+        self.recent_rooms.truncate(max_count);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ruma::{owned_room_id, room_id};
+    use serde_json::{from_value, json};
+
+    use super::BreadcrumbsEventContent;
+
+    #[test]
+    fn test_deserialization() {
+        let json = json!({ "recent_rooms": ["!a:localhost", "!b:localhost"] });
+        let content = from_value::<BreadcrumbsEventContent>(json).unwrap();
+
+        assert_eq!(
+            content.recent_rooms,
+            vec![owned_room_id!("!a:localhost"), owned_room_id!("!b:localhost")]
+        );
+    }
+
+    #[test]
+    fn test_track_visit() {
+        let max_count = 3;
+        let mut content = BreadcrumbsEventContent::new(vec![
+            owned_room_id!("!a:localhost"),
+            owned_room_id!("!b:localhost"),
+        ]);
+
+        // Visiting the most recent room doesn't change anything.
+        assert!(!content.track_visit(owned_room_id!("!a:localhost"), max_count));
+
+        // Visiting another room moves it to the front, without duplicates.
+        assert!(content.track_visit(owned_room_id!("!b:localhost"), max_count));
+        assert_eq!(
+            content.recent_rooms,
+            vec![owned_room_id!("!b:localhost"), owned_room_id!("!a:localhost")]
+        );
+
+        // The breadcrumbs are bounded.
+        for i in 0..max_count {
+            content.track_visit(format!("!{i}:localhost").try_into().unwrap(), max_count);
+        }
+        assert_eq!(content.recent_rooms.len(), max_count);
+        assert!(!content.recent_rooms.iter().any(|id| id == room_id!("!a:localhost")));
+    }
+}
@@ -36,14 +36,21 @@ use ruma::DeviceId;
 use ruma::events::room::{history_visibility::HistoryVisibility, member::MembershipState};
 use ruma::{
     MilliSecondsSinceUnixEpoch, OwnedRoomId, OwnedUserId, RoomId, UserId,
-    api::client::{self as api, sync::sync_events::v5},
+    api::client::{
+        self as api,
+        sync::sync_events::{
+            v3::{InviteState, InvitedRoom},
+            v5,
+        },
+    },
     events::{
-        StateEvent, StateEventType,
+        AnyStrippedStateEvent, StateEvent, StateEventType,
         ignored_user_list::IgnoredUserListEventContent,
         push_rules::{PushRulesEvent, PushRulesEventContent},
         room::member::SyncRoomMemberEvent,
     },
     push::Ruleset,
+    serde::Raw,
     time::Instant,
 };
 use tokio::sync::{Mutex, broadcast};
@@ -58,6 +65,7 @@ use crate::{
     deserialized_responses::DisplayName,
     error::{Error, Result},
     event_cache::store::EventCacheStoreLock,
+    invite_policy::{InvitePolicy, QuarantinedInvite},
     media::store::MediaStoreLock,
     response_processors::{self as processors, Context},
     room::{
@@ -131,6 +139,14 @@ pub struct BaseClient {
 
     /// Whether the client supports threads or not.
     pub threading_support: ThreadingSupport,
+
+    /// The policy deciding which new invites are quarantined, instead of being
+    /// turned into invited rooms.
+    invite_policy: InvitePolicy,
+
+    /// A lock making sure the quarantined invites aren't loaded, modified and
+    /// saved concurrently, which would lose some of them.
+    quarantined_invites_lock: Arc<Mutex<()>>,
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -210,6 +226,8 @@ impl BaseClient {
             #[cfg(feature = "e2e-encryption")]
             handle_verification_events: true,
             threading_support,
+            invite_policy: Default::default(),
+            quarantined_invites_lock: Default::default(),
         }
     }
 
@@ -243,6 +261,8 @@ impl BaseClient {
             decryption_settings: self.decryption_settings.clone(),
             handle_verification_events,
             threading_support: self.threading_support,
+            invite_policy: self.invite_policy.clone(),
+            quarantined_invites_lock: Default::default(),
         };
 
         copy.state_store
@@ -679,6 +699,10 @@ impl BaseClient {
         let mut updated_members_in_room: BTreeMap<OwnedRoomId, BTreeSet<OwnedUserId>> =
             BTreeMap::new();
 
+        // The invites of the rooms we joined or left aren't pending anymore.
+        let settled_invites: BTreeSet<OwnedRoomId> =
+            response.rooms.join.keys().chain(response.rooms.leave.keys()).cloned().collect();
+
         for (room_id, joined_room) in response.rooms.join {
             let joined_room_update = processors::room::sync_v2::update_joined_room(
                 &mut context,
@@ -734,7 +758,20 @@ impl BaseClient {
             room_updates.left.insert(room_id, left_room_update);
         }
 
+        let mut quarantined_invites = BTreeMap::new();
+
         for (room_id, invited_room) in response.rooms.invite {
+            if self
+                .quarantine_invite_if_needed(
+                    &room_id,
+                    &invited_room.invite_state.events,
+                    &mut quarantined_invites,
+                )
+                .await?
+            {
+                continue;
+            }
+
             let invited_room_update = processors::room::sync_v2::update_invited_room(
                 &mut context,
                 &room_id,
@@ -768,6 +805,8 @@ impl BaseClient {
             room_updates.knocked.insert(room_id, knocked_room_update);
         }
 
+        self.update_quarantined_invites(quarantined_invites, settled_invites).await?;
+
         global_account_data_processor.apply(&mut context, &self.state_store).await;
 
         context.state_changes.presence = response
@@ -1092,6 +1131,212 @@ impl BaseClient {
         }
     }
 
+    /// The policy deciding which new invites are quarantined.
+    pub fn invite_policy(&self) -> &InvitePolicy {
+        &self.invite_policy
+    }
+
+    /// Set the policy deciding which new invites are quarantined.
+    ///
+    /// It only applies to the invites received after this call: the invites
+    /// which have already been quarantined or let through are left as is.
+    pub fn set_invite_policy(&mut self, invite_policy: InvitePolicy) {
+        self.invite_policy = invite_policy;
+    }
+
+    /// Check a new invite against the [`InvitePolicy`], and put it in
+    /// `quarantined_invites` if it doesn't satisfy it.
+    ///
+    /// Returns `true` if the invite has been quarantined, in which case it must
+    /// not be processed any further.
+    pub(crate) async fn quarantine_invite_if_needed(
+        &self,
+        room_id: &RoomId,
+        invite_state: &[Raw<AnyStrippedStateEvent>],
+        quarantined_invites: &mut BTreeMap<OwnedRoomId, QuarantinedInvite>,
+    ) -> Result<bool> {
+        if self.invite_policy.is_permissive() {
+            return Ok(false);
+        }
+
+        // The invite has already been let through (or we're already in the room), don't
+        // evaluate it again.
+        if let Some(room) = self.get_room(room_id)
+            && matches!(room.state(), RoomState::Invited | RoomState::Joined)
+        {
+            return Ok(false);
+        }
+
+        let Some(session_meta) = self.session_meta() else {
+            return Ok(false);
+        };
+
+        let (_, events) = processors::state_events::stripped::collect(invite_state);
+
+        let Some((inviter, reason)) =
+            self.invite_policy.evaluate(&session_meta.user_id, &events, &self.state_store).await?
+        else {
+            return Ok(false);
+        };
+
+        debug!(?room_id, ?inviter, ?reason, "Quarantining an invite");
+
+        quarantined_invites.insert(
+            room_id.to_owned(),
+            QuarantinedInvite {
+                room_id: room_id.to_owned(),
+                inviter,
+                reason,
+                quarantined_at: MilliSecondsSinceUnixEpoch::now(),
+                invite_state: invite_state.to_vec(),
+            },
+        );
+
+        Ok(true)
+    }
+
+    /// Add new quarantined invites to the ones saved in the store, and prune
+    /// the quarantined invites which aren't pending anymore.
+    ///
+    /// If an invite was already quarantined, e.g. because it's received again
+    /// after a restart of the sync, it keeps the time it was first quarantined
+    /// at.
+    ///
+    /// The invites of the rooms in `settled_invites`, which the user joined
+    /// or left (e.g. from another device, or because the invite was
+    /// rescinded), are removed from the quarantined invites.
+    pub(crate) async fn update_quarantined_invites(
+        &self,
+        new_invites: BTreeMap<OwnedRoomId, QuarantinedInvite>,
+        settled_invites: BTreeSet<OwnedRoomId>,
+    ) -> StoreResult<()> {
+        // Pruning requires reading the store, which isn't worth it for every sync
+        // when there's no policy quarantining invites.
+        if new_invites.is_empty()
+            && (settled_invites.is_empty() || self.invite_policy.is_permissive())
+        {
+            return Ok(());
+        }
+
+        let _lock = self.quarantined_invites_lock.lock().await;
+
+        let mut invites = self.load_quarantined_invites().await?;
+        let mut has_changed = false;
+
+        for room_id in &settled_invites {
+            if invites.remove(room_id).is_some() {
+                debug!(?room_id, "Pruning a quarantined invite which isn't pending anymore");
+                has_changed = true;
+            }
+        }
+
+        for (room_id, mut invite) in new_invites {
+            if let Some(previous_invite) = invites.get(&room_id) {
+                invite.quarantined_at = previous_invite.quarantined_at;
+            }
+
+            invites.insert(room_id, invite);
+            has_changed = true;
+        }
+
+        if !has_changed {
+            return Ok(());
+        }
+
+        self.state_store
+            .set_kv_data(
+                StateStoreDataKey::QuarantinedInvites,
+                StateStoreDataValue::QuarantinedInvites(invites),
+            )
+            .await
+    }
+
+    async fn load_quarantined_invites(
+        &self,
+    ) -> StoreResult<BTreeMap<OwnedRoomId, QuarantinedInvite>> {
+        Ok(self
+            .state_store
+            .get_kv_data(StateStoreDataKey::QuarantinedInvites)
+            .await?
+            .and_then(|value| value.into_quarantined_invites())
+            .unwrap_or_default())
+    }
+
+    /// Get the invites that have been quarantined by the [`InvitePolicy`], so
+    /// the user can review them.
+    pub async fn quarantined_invites(&self) -> StoreResult<Vec<QuarantinedInvite>> {
+        Ok(self.load_quarantined_invites().await?.into_values().collect())
+    }
+
+    /// Remove an invite from the quarantined invites, without processing it.
+    ///
+    /// Returns the removed invite, if there was one for this room.
+    pub async fn remove_quarantined_invite(
+        &self,
+        room_id: &RoomId,
+    ) -> StoreResult<Option<QuarantinedInvite>> {
+        let _lock = self.quarantined_invites_lock.lock().await;
+
+        let mut invites = self.load_quarantined_invites().await?;
+
+        let Some(invite) = invites.remove(room_id) else {
+            return Ok(None);
+        };
+
+        self.state_store
+            .set_kv_data(
+                StateStoreDataKey::QuarantinedInvites,
+                StateStoreDataValue::QuarantinedInvites(invites),
+            )
+            .await?;
+
+        Ok(Some(invite))
+    }
+
+    /// Release a quarantined invite: remove it from the quarantined invites,
+    /// and process it as if it had just been received from a sync.
+    ///
+    /// Returns the invited room, or `None` if there was no quarantined invite
+    /// for this room.
+    pub async fn release_quarantined_invite(&self, room_id: &RoomId) -> Result<Option<Room>> {
+        let Some(invite) = self.remove_quarantined_invite(room_id).await? else {
+            return Ok(None);
+        };
+
+        let mut context = Context::default();
+
+        let global_account_data_processor = processors::account_data::global(&[]);
+        let push_rules = self.get_push_rules(&global_account_data_processor).await?;
+        let mut notifications = Default::default();
+
+        processors::room::sync_v2::update_invited_room(
+            &mut context,
+            room_id,
+            InvitedRoom::from(InviteState::from(invite.invite_state)),
+            self.room_info_notable_update_sender.clone(),
+            processors::notification::Notification::new(
+                &push_rules,
+                &mut notifications,
+                &self.state_store,
+            ),
+        )
+        .await?;
+
+        {
+            let _sync_lock = self.sync_lock().lock().await;
+
+            processors::changes::save_and_apply(
+                context,
+                &self.state_store,
+                &self.ignore_user_list_changes,
+                None,
+            )
+            .await?;
+        }
+
+        Ok(self.get_room(room_id))
+    }
+
     /// Returns a subscriber that publishes an event every time the ignore user
     /// list changes
     pub fn subscribe_to_ignore_user_list_changes(&self) -> Subscriber<Vec<String>> {
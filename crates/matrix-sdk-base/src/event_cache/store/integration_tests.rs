@@ -62,6 +62,7 @@ pub fn make_test_event_with_event_id(
             curve25519_key: "1337".to_owned(),
             sender_claimed_keys: Default::default(),
             session_id: Some("mysessionid9".to_owned()),
+            forwarding_curve25519_key_chain: Vec::new(),
         },
         verification_state: VerificationState::Verified,
     });
@@ -73,7 +74,12 @@ pub fn make_test_event_with_event_id(
     let event = builder.into_raw();
 
     TimelineEvent::from_decrypted(
-        DecryptedRoomEvent { event, encryption_info, unsigned_encryption_info: None },
+        DecryptedRoomEvent {
+            event,
+            encryption_info,
+            unsigned_encryption_info: None,
+            encrypted_event: None,
+        },
         Some(vec![Action::Notify]),
     )
 }
@@ -200,7 +200,18 @@ impl<T: EventCacheStore> EventCacheStore for EraseEventCacheStoreError<T> {
         linked_chunk_id: LinkedChunkId<'_>,
         updates: Vec<Update<Event, Gap>>,
     ) -> Result<(), Self::Error> {
-        self.0.handle_linked_chunk_updates(linked_chunk_id, updates).await.map_err(Into::into)
+        let future = async {
+            self.0.handle_linked_chunk_updates(linked_chunk_id, updates).await.map_err(Into::into)
+        };
+
+        #[cfg(feature = "opentelemetry")]
+        let future = matrix_sdk_common::telemetry::instrument_store_transaction(
+            "event_cache",
+            "handle_linked_chunk_updates",
+            future,
+        );
+
+        future.await
     }
 
     async fn load_all_chunks(
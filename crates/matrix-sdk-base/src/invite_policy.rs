@@ -0,0 +1,283 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Policies to protect the user against unwanted (spam) invites.
+//!
+//! An [`InvitePolicy`] is evaluated by the [`BaseClient`] for every new invite
+//! received from a sync. Invites that don't satisfy the policy are not turned
+//! into rooms: they are put aside as [`QuarantinedInvite`]s instead, so they
+//! never reach the room list, and the user can review them later.
+//!
+//! [`BaseClient`]: crate::BaseClient
+
+use std::collections::BTreeSet;
+
+use ruma::{
+    MilliSecondsSinceUnixEpoch, OwnedRoomId, OwnedServerName, OwnedUserId, UserId,
+    events::{AnyStrippedStateEvent, direct::DirectUserIdentifier, room::member::MembershipState},
+    serde::Raw,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    RoomStateFilter,
+    store::{BaseStateStore, Result as StoreResult},
+};
+
+/// A policy deciding which invites are let through, and which ones are
+/// quarantined.
+///
+/// The default policy lets all the invites through.
+#[derive(Clone, Debug, Default)]
+pub struct InvitePolicy {
+    /// Quarantine invites sent by users we don't share any joined room with.
+    pub block_without_shared_rooms: bool,
+
+    /// Quarantine invites sent by users from any of these servers.
+    pub blocked_servers: BTreeSet<OwnedServerName>,
+
+    /// Quarantine invites sent by users we never had a direct conversation
+    /// with.
+    pub block_unless_previously_messaged: bool,
+}
+
+impl InvitePolicy {
+    /// Whether this policy lets all the invites through, i.e. there is no need
+    /// to evaluate it.
+    pub fn is_permissive(&self) -> bool {
+        !self.block_without_shared_rooms
+            && self.blocked_servers.is_empty()
+            && !self.block_unless_previously_messaged
+    }
+
+    /// Evaluate this policy against the stripped state of an invite.
+    ///
+    /// Returns the reason why the invite must be quarantined, if it must be,
+    /// along with the inviter if it could be found.
+    pub(crate) async fn evaluate(
+        &self,
+        own_user_id: &UserId,
+        invite_state: &[AnyStrippedStateEvent],
+        state_store: &BaseStateStore,
+    ) -> StoreResult<Option<(Option<OwnedUserId>, InviteQuarantineReason)>> {
+        if self.is_permissive() {
+            return Ok(None);
+        }
+
+        // The stripped state may also describe a knock, which is not subject to the
+        // policy.
+        let (membership, inviter) = match find_own_membership(own_user_id, invite_state) {
+            Some((membership, sender)) => (membership, Some(sender)),
+            None => (MembershipState::Invite, None),
+        };
+
+        if membership != MembershipState::Invite {
+            return Ok(None);
+        }
+
+        if let Some(inviter) = &inviter
+            && self.blocked_servers.contains(inviter.server_name())
+        {
+            return Ok(Some((Some(inviter.clone()), InviteQuarantineReason::BlockedServer)));
+        }
+
+        if self.block_unless_previously_messaged {
+            let previously_messaged = inviter.as_deref().is_some_and(|inviter| {
+                state_store.rooms().iter().any(|room| {
+                    room.direct_targets().contains(<&DirectUserIdentifier>::from(inviter))
+                })
+            });
+
+            if !previously_messaged {
+                return Ok(Some((inviter, InviteQuarantineReason::NotPreviouslyMessaged)));
+            }
+        }
+
+        if self.block_without_shared_rooms {
+            let mut has_shared_room = false;
+
+            if let Some(inviter) = &inviter {
+                for room in state_store.rooms_filtered(RoomStateFilter::JOINED) {
+                    if let Some(member) = room.get_member(inviter).await?
+                        && *member.membership() == MembershipState::Join
+                    {
+                        has_shared_room = true;
+                        break;
+                    }
+                }
+            }
+
+            if !has_shared_room {
+                return Ok(Some((inviter, InviteQuarantineReason::NoSharedRooms)));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Find our own membership, and the sender of the associated membership event,
+/// in the stripped state of an invite.
+fn find_own_membership(
+    own_user_id: &UserId,
+    invite_state: &[AnyStrippedStateEvent],
+) -> Option<(MembershipState, OwnedUserId)> {
+    invite_state.iter().find_map(|event| match event {
+        AnyStrippedStateEvent::RoomMember(member) if member.state_key == *own_user_id => {
+            Some((member.content.membership.clone(), member.sender.clone()))
+        }
+        _ => None,
+    })
+}
+
+/// The reason why an invite has been quarantined.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InviteQuarantineReason {
+    /// The inviter belongs to one of the
+    /// [`InvitePolicy::blocked_servers`].
+    BlockedServer,
+
+    /// We don't share any joined room with the inviter, see
+    /// [`InvitePolicy::block_without_shared_rooms`].
+    NoSharedRooms,
+
+    /// We never had a direct conversation with the inviter, see
+    /// [`InvitePolicy::block_unless_previously_messaged`].
+    NotPreviouslyMessaged,
+}
+
+/// An invite that didn't satisfy the [`InvitePolicy`], and has been put aside
+/// for the user to review.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuarantinedInvite {
+    /// The room the user has been invited to.
+    pub room_id: OwnedRoomId,
+
+    /// The user who sent the invite, if known.
+    pub inviter: Option<OwnedUserId>,
+
+    /// Why the invite has been quarantined.
+    pub reason: InviteQuarantineReason,
+
+    /// When the invite has been quarantined.
+    pub quarantined_at: MilliSecondsSinceUnixEpoch,
+
+    /// The stripped state of the invite, as received from the sync.
+    ///
+    /// It's used to create the invited room if the user decides to release
+    /// the invite.
+    pub invite_state: Vec<Raw<AnyStrippedStateEvent>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk_test::{async_test, event_factory::EventFactory};
+    use ruma::{
+        events::{AnyStrippedStateEvent, room::member::MembershipState},
+        owned_server_name, user_id,
+    };
+
+    use super::{InvitePolicy, InviteQuarantineReason};
+    use crate::test_utils::logged_in_base_client;
+
+    fn invite_state(inviter: &ruma::UserId) -> Vec<AnyStrippedStateEvent> {
+        let me = user_id!("@example:localhost");
+        let event = EventFactory::new()
+            .member(me)
+            .sender(inviter)
+            .membership(MembershipState::Invite)
+            .into_raw::<AnyStrippedStateEvent>();
+
+        vec![event.deserialize().unwrap()]
+    }
+
+    #[async_test]
+    async fn test_default_policy_is_permissive() {
+        let client = logged_in_base_client(None).await;
+        let policy = InvitePolicy::default();
+
+        assert!(policy.is_permissive());
+        assert!(
+            policy
+                .evaluate(
+                    user_id!("@example:localhost"),
+                    &invite_state(user_id!("@spammer:spam.org")),
+                    &client.state_store,
+                )
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[async_test]
+    async fn test_blocked_servers() {
+        let client = logged_in_base_client(None).await;
+        let policy = InvitePolicy {
+            blocked_servers: [owned_server_name!("spam.org")].into(),
+            ..Default::default()
+        };
+
+        let (inviter, reason) = policy
+            .evaluate(
+                user_id!("@example:localhost"),
+                &invite_state(user_id!("@spammer:spam.org")),
+                &client.state_store,
+            )
+            .await
+            .unwrap()
+            .expect("the invite should be quarantined");
+        assert_eq!(inviter.as_deref(), Some(user_id!("@spammer:spam.org")));
+        assert_eq!(reason, InviteQuarantineReason::BlockedServer);
+
+        assert!(
+            policy
+                .evaluate(
+                    user_id!("@example:localhost"),
+                    &invite_state(user_id!("@friend:example.org")),
+                    &client.state_store,
+                )
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[async_test]
+    async fn test_no_shared_rooms() {
+        let client = logged_in_base_client(None).await;
+        let policy = InvitePolicy { block_without_shared_rooms: true, ..Default::default() };
+
+        // We don't know any room yet, so we can't share any room with the inviter.
+        let (_, reason) = policy
+            .evaluate(
+                user_id!("@example:localhost"),
+                &invite_state(user_id!("@stranger:example.org")),
+                &client.state_store,
+            )
+            .await
+            .unwrap()
+            .expect("the invite should be quarantined");
+        assert_eq!(reason, InviteQuarantineReason::NoSharedRooms);
+
+        // An invite without any inviter can't be checked either.
+        let (inviter, reason) = policy
+            .evaluate(user_id!("@example:localhost"), &[], &client.state_store)
+            .await
+            .unwrap()
+            .expect("the invite should be quarantined");
+        assert!(inviter.is_none());
+        assert_eq!(reason, InviteQuarantineReason::NoSharedRooms);
+    }
+}
@@ -24,12 +24,14 @@ use serde::{Deserialize, Serialize};
 
 pub use crate::error::{Error, Result};
 
+pub mod breadcrumbs;
 mod client;
 pub use client::RequestedRequiredStates;
 pub mod debug;
 pub mod deserialized_responses;
 mod error;
 pub mod event_cache;
+pub mod invite_policy;
 pub mod latest_event;
 pub mod media;
 pub mod notification_settings;
@@ -429,6 +429,31 @@ impl Room {
         self.info.read().cached_user_defined_notification_mode
     }
 
+    /// Update the cached notification mode, and emit a
+    /// [`RoomInfoNotableUpdateReasons::NOTIFICATION_MODE`] notable update if
+    /// it has changed.
+    ///
+    /// This is automatically recomputed on every successful sync, and the
+    /// cached result can be retrieved in [`Self::cached_notification_mode`].
+    pub fn update_cached_notification_mode(&self, mode: Option<RoomNotificationMode>) {
+        if self.info.read().cached_notification_mode == mode {
+            return;
+        }
+
+        let mut info = self.clone_info();
+        info.cached_notification_mode = mode;
+        self.set_room_info(info, RoomInfoNotableUpdateReasons::NOTIFICATION_MODE);
+    }
+
+    /// Returns the cached notification mode, if available.
+    ///
+    /// Contrary to [`Self::cached_user_defined_notification_mode`], it
+    /// falls back to the default notification mode for this kind of room, so
+    /// it's always known for the joined rooms once they have been synced.
+    pub fn cached_notification_mode(&self) -> Option<RoomNotificationMode> {
+        self.info.read().cached_notification_mode
+    }
+
     /// Get the list of users ids that are considered to be joined members of
     /// this room.
     pub async fn joined_user_ids(&self) -> StoreResult<Vec<OwnedUserId>> {
@@ -488,6 +488,11 @@ pub struct RoomInfo {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) cached_user_defined_notification_mode: Option<RoomNotificationMode>,
 
+    /// Cached notification mode, either user defined or the default one for
+    /// this kind of room.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cached_notification_mode: Option<RoomNotificationMode>,
+
     /// The recency stamp of this room.
     ///
     /// It's not to be confused with the `origin_server_ts` value of an event.
@@ -536,6 +541,7 @@ impl RoomInfo {
             warned_about_unknown_room_version_rules: Arc::new(false.into()),
             cached_display_name: None,
             cached_user_defined_notification_mode: None,
+            cached_notification_mode: None,
             recency_stamp: None,
             invite_acceptance_details: None,
         }
@@ -1083,6 +1089,14 @@ impl RoomInfo {
             .unwrap_or_default()
     }
 
+    /// Replace the pinned event ids of this room.
+    ///
+    /// This is useful to reflect an `m.room.pinned_events` state event that
+    /// has just been sent, before it's received via sync.
+    pub fn set_pinned_event_ids(&mut self, pinned: Vec<OwnedEventId>) {
+        self.base_info.pinned_events = Some(RoomPinnedEventsEventContent::new(pinned));
+    }
+
     /// Apply migrations to this `RoomInfo` if needed.
     ///
     /// This should be used to populate new fields with data from the state
@@ -1257,6 +1271,9 @@ bitflags! {
         /// The display name has changed.
         const DISPLAY_NAME = 0b0010_0000;
 
+        /// The notification mode has changed.
+        const NOTIFICATION_MODE = 0b0100_0000;
+
         /// This is a temporary hack.
         ///
         /// So here is the thing. Ideally, we DO NOT want to emit this reason. It does not
@@ -1341,6 +1358,7 @@ mod tests {
             warned_about_unknown_room_version_rules: Arc::new(false.into()),
             cached_display_name: None,
             cached_user_defined_notification_mode: None,
+            cached_notification_mode: None,
             recency_stamp: Some(42.into()),
             invite_acceptance_details: None,
         };
@@ -14,6 +14,8 @@
 
 //! Extend `BaseClient` with capabilities to handle MSC4186.
 
+use std::collections::{BTreeMap, BTreeSet};
+
 #[cfg(feature = "e2e-encryption")]
 use matrix_sdk_common::deserialized_responses::ProcessedToDeviceEvent;
 use matrix_sdk_common::{deserialized_responses::TimelineEvent, timer};
@@ -147,7 +149,30 @@ impl BaseClient {
             .user_id
             .to_owned();
 
+        let mut quarantined_invites = BTreeMap::new();
+        // The invites of the rooms which aren't invites anymore, because we joined or
+        // left them, aren't pending anymore.
+        let mut settled_invites = BTreeSet::new();
+
         for (room_id, room_response) in rooms {
+            match &room_response.invite_state {
+                Some(invite_state) => {
+                    if self
+                        .quarantine_invite_if_needed(
+                            room_id,
+                            invite_state,
+                            &mut quarantined_invites,
+                        )
+                        .await?
+                    {
+                        continue;
+                    }
+                }
+                None => {
+                    settled_invites.insert(room_id.to_owned());
+                }
+            }
+
             let Some((room_info, room_update)) = processors::room::msc4186::update_any_room(
                 &mut context,
                 &user_id,
@@ -214,6 +239,8 @@ impl BaseClient {
             &self.state_store,
         );
 
+        self.update_quarantined_invites(quarantined_invites, settled_invites).await?;
+
         global_account_data_processor.apply(&mut context, &state_store).await;
 
         context.state_changes.ambiguity_maps = ambiguity_cache.cache;
@@ -1059,6 +1086,96 @@ mod tests {
         assert!(!sync_resp.rooms.joined.contains_key(room_id));
     }
 
+    #[async_test]
+    async fn test_invitation_from_blocked_server_is_quarantined() {
+        use crate::invite_policy::{InvitePolicy, InviteQuarantineReason};
+
+        // Given a logged-in client, blocking invites from `spam.org`
+        let mut client = logged_in_base_client(None).await;
+        client.set_invite_policy(InvitePolicy {
+            blocked_servers: [ruma::owned_server_name!("spam.org")].into(),
+            ..Default::default()
+        });
+
+        let room_id = room_id!("!r:e.uk");
+        let user_id = user_id!("@u:e.uk");
+        let inviter = user_id!("@spammer:spam.org");
+
+        // When I send sliding sync response containing an invite from this server
+        let mut room = http::response::Room::new();
+        set_room_invited(&mut room, inviter, user_id);
+        let response = response_with_room(room_id, room);
+        let sync_resp = client
+            .process_sliding_sync(&response, &RequestedRequiredStates::default())
+            .await
+            .expect("Failed to process sync");
+
+        // Then the room isn't known by the client
+        assert!(client.get_room(room_id).is_none());
+        assert!(!sync_resp.rooms.invited.contains_key(room_id));
+
+        // But the invite is quarantined
+        let quarantined = client.quarantined_invites().await.unwrap();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].room_id, room_id);
+        assert_eq!(quarantined[0].inviter.as_deref(), Some(inviter));
+        assert_eq!(quarantined[0].reason, InviteQuarantineReason::BlockedServer);
+        let quarantined_at = quarantined[0].quarantined_at;
+
+        // And when the same invite is received again, it's still quarantined since the
+        // same time
+        client
+            .process_sliding_sync(&response, &RequestedRequiredStates::default())
+            .await
+            .expect("Failed to process sync");
+        let quarantined = client.quarantined_invites().await.unwrap();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].quarantined_at, quarantined_at);
+
+        // And when the user releases it, the invited room is created
+        let released = client.release_quarantined_invite(room_id).await.unwrap();
+        assert_eq!(released.expect("No room found").state(), RoomState::Invited);
+        assert!(client.quarantined_invites().await.unwrap().is_empty());
+    }
+
+    #[async_test]
+    async fn test_quarantined_invite_is_pruned_once_the_room_is_left() {
+        use crate::invite_policy::InvitePolicy;
+
+        // Given a logged-in client, blocking invites from `spam.org`
+        let mut client = logged_in_base_client(None).await;
+        client.set_invite_policy(InvitePolicy {
+            blocked_servers: [ruma::owned_server_name!("spam.org")].into(),
+            ..Default::default()
+        });
+
+        let room_id = room_id!("!r:e.uk");
+        let user_id = user_id!("@u:e.uk");
+        let inviter = user_id!("@spammer:spam.org");
+
+        // And a quarantined invite
+        let mut room = http::response::Room::new();
+        set_room_invited(&mut room, inviter, user_id);
+        let response = response_with_room(room_id, room);
+        client
+            .process_sliding_sync(&response, &RequestedRequiredStates::default())
+            .await
+            .expect("Failed to process sync");
+        assert_eq!(client.quarantined_invites().await.unwrap().len(), 1);
+
+        // When the invite is rejected from another device
+        let mut room = http::response::Room::new();
+        set_room_left(&mut room, user_id);
+        let response = response_with_room(room_id, room);
+        client
+            .process_sliding_sync(&response, &RequestedRequiredStates::default())
+            .await
+            .expect("Failed to process sync");
+
+        // Then the quarantined invite is pruned
+        assert!(client.quarantined_invites().await.unwrap().is_empty());
+    }
+
     #[async_test]
     async fn test_avatar_is_found_in_invitation_room_when_processing_sliding_sync_response() {
         // Given a logged-in client
@@ -280,6 +280,11 @@ impl StateStoreIntegrationTests for DynStateStore {
         assert!(
             self.get_account_data_event(GlobalAccountDataEventType::PushRules).await?.is_some()
         );
+        assert_eq!(
+            self.get_account_data_events().await?.len(),
+            1,
+            "Expected to find 1 account data event"
+        );
 
         assert!(self.get_state_event(room_id, StateEventType::RoomName, "").await?.is_some());
         assert_eq!(
@@ -314,6 +319,11 @@ impl StateStoreIntegrationTests for DynStateStore {
                 .await?
                 .is_some()
         );
+        assert_eq!(
+            self.get_room_account_data_events(room_id).await?.len(),
+            1,
+            "Expected to find 1 room account data event"
+        );
         assert!(
             self.get_user_room_receipt_event(
                 room_id,
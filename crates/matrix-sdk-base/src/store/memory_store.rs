@@ -45,6 +45,7 @@ use super::{
 use crate::{
     MinimalRoomMemberEvent, RoomMemberships, StateStoreDataKey, StateStoreDataValue,
     deserialized_responses::{DisplayName, RawAnySyncOrStrippedState},
+    invite_policy::QuarantinedInvite,
     store::{
         QueueWedgeError, StoredThreadSubscription,
         traits::{ThreadSubscriptionCatchupToken, compare_thread_subscription_bump_stamps},
@@ -89,6 +90,7 @@ struct MemoryStoreInner {
     seen_knock_requests: BTreeMap<OwnedRoomId, BTreeMap<OwnedEventId, OwnedUserId>>,
     thread_subscriptions: BTreeMap<OwnedRoomId, BTreeMap<OwnedEventId, StoredThreadSubscription>>,
     thread_subscriptions_catchup_tokens: Option<Vec<ThreadSubscriptionCatchupToken>>,
+    quarantined_invites: Option<BTreeMap<OwnedRoomId, QuarantinedInvite>>,
 }
 
 /// In-memory, non-persistent implementation of the `StateStore`.
@@ -189,6 +191,9 @@ impl StateStore for MemoryStore {
                 .thread_subscriptions_catchup_tokens
                 .clone()
                 .map(StateStoreDataValue::ThreadSubscriptionsCatchupTokens),
+            StateStoreDataKey::QuarantinedInvites => {
+                inner.quarantined_invites.clone().map(StateStoreDataValue::QuarantinedInvites)
+            }
         })
     }
 
@@ -258,6 +263,13 @@ impl StateStore for MemoryStore {
                         "Session data is not a list of thread subscription catchup tokens",
                     ));
             }
+            StateStoreDataKey::QuarantinedInvites => {
+                inner.quarantined_invites = Some(
+                    value
+                        .into_quarantined_invites()
+                        .expect("Session data is not a list of quarantined invites"),
+                );
+            }
         }
 
         Ok(())
@@ -291,6 +303,9 @@ impl StateStore for MemoryStore {
             StateStoreDataKey::ThreadSubscriptionsCatchupTokens => {
                 inner.thread_subscriptions_catchup_tokens = None;
             }
+            StateStoreDataKey::QuarantinedInvites => {
+                inner.quarantined_invites = None;
+            }
         }
         Ok(())
     }
@@ -733,6 +748,24 @@ impl StateStore for MemoryStore {
             .cloned())
     }
 
+    async fn get_account_data_events(&self) -> Result<Vec<Raw<AnyGlobalAccountDataEvent>>> {
+        Ok(self.inner.read().unwrap().account_data.values().cloned().collect())
+    }
+
+    async fn get_room_account_data_events(
+        &self,
+        room_id: &RoomId,
+    ) -> Result<Vec<Raw<AnyRoomAccountDataEvent>>> {
+        Ok(self
+            .inner
+            .read()
+            .unwrap()
+            .room_account_data
+            .get(room_id)
+            .map(|events| events.values().cloned().collect())
+            .unwrap_or_default())
+    }
+
     async fn get_user_room_receipt_event(
         &self,
         room_id: &RoomId,
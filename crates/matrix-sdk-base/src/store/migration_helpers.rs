@@ -122,6 +122,7 @@ impl RoomInfoV1 {
             warned_about_unknown_room_version_rules: Arc::new(false.into()),
             cached_display_name: None,
             cached_user_defined_notification_mode: None,
+            cached_notification_mode: None,
             recency_stamp: None,
             invite_acceptance_details: None,
         }
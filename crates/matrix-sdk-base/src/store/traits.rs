@@ -55,6 +55,7 @@ use crate::{
     deserialized_responses::{
         DisplayName, RawAnySyncOrStrippedState, RawMemberEvent, RawSyncOrStrippedState,
     },
+    invite_policy::QuarantinedInvite,
     store::StoredThreadSubscription,
 };
 
@@ -258,6 +259,23 @@ pub trait StateStore: AsyncTraitDeps {
         event_type: RoomAccountDataEventType,
     ) -> Result<Option<Raw<AnyRoomAccountDataEvent>>, Self::Error>;
 
+    /// Get all the events out of the account data store.
+    async fn get_account_data_events(
+        &self,
+    ) -> Result<Vec<Raw<AnyGlobalAccountDataEvent>>, Self::Error>;
+
+    /// Get all the events out of the room account data store, for the given
+    /// room.
+    ///
+    /// # Arguments
+    ///
+    /// * `room_id` - The id of the room for which the room account data events
+    ///   should be fetched.
+    async fn get_room_account_data_events(
+        &self,
+        room_id: &RoomId,
+    ) -> Result<Vec<Raw<AnyRoomAccountDataEvent>>, Self::Error>;
+
     /// Get an event out of the user room receipt store.
     ///
     /// # Arguments
@@ -550,7 +568,16 @@ impl<T: StateStore> StateStore for EraseStateStoreError<T> {
     }
 
     async fn save_changes(&self, changes: &StateChanges) -> Result<(), Self::Error> {
-        self.0.save_changes(changes).await.map_err(Into::into)
+        let future = async { self.0.save_changes(changes).await.map_err(Into::into) };
+
+        #[cfg(feature = "opentelemetry")]
+        let future = matrix_sdk_common::telemetry::instrument_store_transaction(
+            "state",
+            "save_changes",
+            future,
+        );
+
+        future.await
     }
 
     async fn get_presence_event(
@@ -655,6 +682,19 @@ impl<T: StateStore> StateStore for EraseStateStoreError<T> {
         self.0.get_room_account_data_event(room_id, event_type).await.map_err(Into::into)
     }
 
+    async fn get_account_data_events(
+        &self,
+    ) -> Result<Vec<Raw<AnyGlobalAccountDataEvent>>, Self::Error> {
+        self.0.get_account_data_events().await.map_err(Into::into)
+    }
+
+    async fn get_room_account_data_events(
+        &self,
+        room_id: &RoomId,
+    ) -> Result<Vec<Raw<AnyRoomAccountDataEvent>>, Self::Error> {
+        self.0.get_room_account_data_events(room_id).await.map_err(Into::into)
+    }
+
     async fn get_user_room_receipt_event(
         &self,
         room_id: &RoomId,
@@ -1157,6 +1197,10 @@ pub enum StateStoreDataValue {
     /// See documentation of [`ThreadSubscriptionCatchupToken`] for more
     /// details.
     ThreadSubscriptionsCatchupTokens(Vec<ThreadSubscriptionCatchupToken>),
+
+    /// The list of invites that have been quarantined by the
+    /// [`InvitePolicy`](crate::invite_policy::InvitePolicy).
+    QuarantinedInvites(BTreeMap<OwnedRoomId, QuarantinedInvite>),
 }
 
 /// Tokens to use when catching up on thread subscriptions.
@@ -1262,6 +1306,11 @@ impl StateStoreDataValue {
     ) -> Option<Vec<ThreadSubscriptionCatchupToken>> {
         as_variant!(self, Self::ThreadSubscriptionsCatchupTokens)
     }
+
+    /// Get this value if it is the list of quarantined invites.
+    pub fn into_quarantined_invites(self) -> Option<BTreeMap<OwnedRoomId, QuarantinedInvite>> {
+        as_variant!(self, Self::QuarantinedInvites)
+    }
 }
 
 /// A key for key-value data.
@@ -1301,6 +1350,9 @@ pub enum StateStoreDataKey<'a> {
 
     /// A list of thread subscriptions catchup tokens.
     ThreadSubscriptionsCatchupTokens,
+
+    /// The list of quarantined invites.
+    QuarantinedInvites,
 }
 
 impl StateStoreDataKey<'_> {
@@ -1342,6 +1394,10 @@ impl StateStoreDataKey<'_> {
     /// [`ThreadSubscriptionsCatchupTokens`][Self::ThreadSubscriptionsCatchupTokens] variant.
     pub const THREAD_SUBSCRIPTIONS_CATCHUP_TOKENS: &'static str =
         "thread_subscriptions_catchup_tokens";
+
+    /// Key to use for the [`QuarantinedInvites`][Self::QuarantinedInvites]
+    /// variant.
+    pub const QUARANTINED_INVITES: &'static str = "quarantined_invites";
 }
 
 /// Compare two thread subscription changes bump stamps, given a fixed room and
@@ -0,0 +1,221 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An abstraction over the passing of time.
+//!
+//! The SDK reads the time and sleeps through a [`Clock`], so that tests can
+//! replace the [`SystemClock`] by a [`MockClock`], and fast-forward time
+//! deterministically instead of sleeping in real time.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use ruma::time::Instant;
+
+use crate::{BoxFuture, SendOutsideWasm, SyncOutsideWasm, sleep::sleep};
+
+/// A source of time.
+pub trait Clock: Debug + SendOutsideWasm + SyncOutsideWasm {
+    /// The current instant.
+    fn now(&self) -> Instant;
+
+    /// Wait until the given duration has elapsed.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The [`Clock`] of the system, i.e. the real time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(sleep(duration))
+    }
+}
+
+/// A [`Clock`] whose time only passes when calling [`MockClock::advance`].
+///
+/// Cloning it returns a handle to the same clock.
+#[derive(Clone, Debug)]
+pub struct MockClock {
+    inner: Arc<Mutex<MockClockInner>>,
+}
+
+#[derive(Debug)]
+struct MockClockInner {
+    /// The instant at which the clock was created.
+    origin: Instant,
+
+    /// The time that has passed since the clock was created.
+    elapsed: Duration,
+
+    /// The tasks waiting in [`MockClock::sleep`], by sleep.
+    sleepers: BTreeMap<u64, Waker>,
+
+    /// The identifier of the next sleep.
+    next_sleep_id: u64,
+}
+
+impl MockClock {
+    /// Create a new clock, frozen at the current instant.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(MockClockInner {
+                origin: Instant::now(),
+                elapsed: Duration::ZERO,
+                sleepers: BTreeMap::new(),
+                next_sleep_id: 0,
+            })),
+        }
+    }
+
+    /// Move the time forward by the given duration, waking up all the tasks
+    /// whose sleep is over.
+    pub fn advance(&self, duration: Duration) {
+        let sleepers = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.elapsed += duration;
+            std::mem::take(&mut inner.sleepers)
+        };
+
+        // Every sleeper checks its own deadline when it's polled again, and registers
+        // itself again if it's not over yet.
+        for waker in sleepers.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// The time that has passed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.inner.lock().unwrap().elapsed
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        let inner = self.inner.lock().unwrap();
+        inner.origin + inner.elapsed
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let mut inner = self.inner.lock().unwrap();
+        let deadline = inner.elapsed + duration;
+        let id = inner.next_sleep_id;
+        inner.next_sleep_id += 1;
+
+        Box::pin(MockSleep { inner: self.inner.clone(), id, deadline })
+    }
+}
+
+/// The future returned by [`MockClock::sleep`].
+struct MockSleep {
+    inner: Arc<Mutex<MockClockInner>>,
+    id: u64,
+    deadline: Duration,
+}
+
+impl Future for MockSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.elapsed >= self.deadline {
+            inner.sleepers.remove(&self.id);
+            return Poll::Ready(());
+        }
+
+        // Only keep the waker of the last poll, a sleep that is polled many times
+        // mustn't accumulate wakers.
+        match inner.sleepers.get_mut(&self.id) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => {
+                inner.sleepers.insert(self.id, cx.waker().clone());
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for MockSleep {
+    fn drop(&mut self) {
+        self.inner.lock().unwrap().sleepers.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::FutureExt as _;
+    use matrix_sdk_test_macros::async_test;
+
+    use super::{Clock, MockClock};
+
+    #[async_test]
+    async fn test_mock_clock_sleep() {
+        let clock = MockClock::new();
+        let start = clock.now();
+
+        let mut sleep = clock.sleep(Duration::from_secs(10));
+        assert!((&mut sleep).now_or_never().is_none());
+
+        // Not enough time has passed.
+        clock.advance(Duration::from_secs(5));
+        assert!((&mut sleep).now_or_never().is_none());
+
+        // Now it's over.
+        clock.advance(Duration::from_secs(5));
+        assert!(sleep.now_or_never().is_some());
+
+        assert_eq!(clock.now() - start, Duration::from_secs(10));
+
+        // A sleep of zero is over immediately.
+        assert!(clock.sleep(Duration::ZERO).now_or_never().is_some());
+    }
+
+    #[async_test]
+    async fn test_mock_clock_sleep_keeps_a_single_waker() {
+        let clock = MockClock::new();
+
+        let mut sleep = clock.sleep(Duration::from_secs(10));
+        for _ in 0..10 {
+            assert!((&mut sleep).now_or_never().is_none());
+        }
+        assert_eq!(clock.inner.lock().unwrap().sleepers.len(), 1);
+
+        // A dropped sleep doesn't keep its waker around.
+        drop(sleep);
+        assert!(clock.inner.lock().unwrap().sleepers.is_empty());
+    }
+}
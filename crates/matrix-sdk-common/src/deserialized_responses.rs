@@ -310,6 +310,12 @@ pub enum AlgorithmInfo {
         /// if this info was stored before we collected this data.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         session_id: Option<String>,
+
+        /// The chain of Curve25519 keys, encoded as base64, of the devices
+        /// through which the megolm decryption key was forwarded to us. Empty
+        /// if we received the key from the device that created it.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        forwarding_curve25519_key_chain: Vec<String>,
     },
 
     /// The info if the event was encrypted using m.olm.v1.curve25519-aes-sha2
@@ -371,14 +377,18 @@ impl<'de> Deserialize<'de> for EncryptionInfo {
             Helper::deserialize(deserializer)?;
 
         let algorithm_info = match algorithm_info {
-            AlgorithmInfo::MegolmV1AesSha2 { curve25519_key, sender_claimed_keys, session_id } => {
-                AlgorithmInfo::MegolmV1AesSha2 {
-                    // Migration, merge the old_session_id in algorithm_info
-                    session_id: session_id.or(old_session_id),
-                    curve25519_key,
-                    sender_claimed_keys,
-                }
-            }
+            AlgorithmInfo::MegolmV1AesSha2 {
+                curve25519_key,
+                sender_claimed_keys,
+                session_id,
+                forwarding_curve25519_key_chain,
+            } => AlgorithmInfo::MegolmV1AesSha2 {
+                // Migration, merge the old_session_id in algorithm_info
+                session_id: session_id.or(old_session_id),
+                curve25519_key,
+                sender_claimed_keys,
+                forwarding_curve25519_key_chain,
+            },
             other => other,
         };
 
@@ -408,6 +418,13 @@ pub struct ThreadSummary {
     /// events in the thread are considered to be meaningful (or they've all
     /// been redacted).
     pub num_replies: u32,
+
+    /// The number of distinct users who replied to the thread.
+    ///
+    /// This is `None` when it's unknown, e.g. for a summary bundled by the
+    /// server, until the replies have been seen by the event cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_participants: Option<u32>,
 }
 
 /// The status of a thread summary.
@@ -668,6 +685,7 @@ impl TimelineEvent {
                                         // a replacement event, but we don't carry this information
                                         // around.
                                         unsigned_encryption_info: None,
+                                        encrypted_event: None,
                                     },
                                     None,
                                     max_timestamp,
@@ -795,6 +813,13 @@ impl TimelineEvent {
         self.kind.encryption_info()
     }
 
+    /// If the event was a decrypted event that was successfully decrypted, get
+    /// the encrypted event as it was received from the homeserver, if known.
+    /// Otherwise, `None`.
+    pub fn encrypted_raw(&self) -> Option<&Raw<AnySyncTimelineEvent>> {
+        self.kind.encrypted_raw()
+    }
+
     /// Takes ownership of this [`TimelineEvent`], returning the (potentially
     /// decrypted) Matrix event within.
     pub fn into_raw(self) -> Raw<AnySyncTimelineEvent> {
@@ -904,6 +929,16 @@ impl TimelineEventKind {
         }
     }
 
+    /// If the event was a decrypted event that was successfully decrypted, get
+    /// the encrypted event as it was received from the homeserver, if known.
+    /// Otherwise, `None`.
+    pub fn encrypted_raw(&self) -> Option<&Raw<AnySyncTimelineEvent>> {
+        match self {
+            TimelineEventKind::Decrypted(d) => d.encrypted_event.as_ref(),
+            TimelineEventKind::UnableToDecrypt { .. } | TimelineEventKind::PlainText { .. } => None,
+        }
+    }
+
     /// If the event was a decrypted event that was successfully decrypted, get
     /// the map of decryption metadata related to the bundled events.
     pub fn unsigned_encryption_map(
@@ -986,12 +1021,25 @@ pub struct DecryptedRoomEvent {
     /// Will be `None` if no bundled event was encrypted.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unsigned_encryption_info: Option<BTreeMap<UnsignedEventLocation, UnsignedDecryptionResult>>,
+
+    /// The encrypted event, as it was received from the homeserver.
+    ///
+    /// It's only kept in memory, and isn't serialized, so that the stored
+    /// events don't grow twice as large: it will be `None` if the event has
+    /// been loaded from a store, or if it's a bundled event.
+    #[serde(skip)]
+    pub encrypted_event: Option<Raw<AnySyncTimelineEvent>>,
 }
 
 #[cfg(not(tarpaulin_include))]
 impl fmt::Debug for DecryptedRoomEvent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let DecryptedRoomEvent { event, encryption_info, unsigned_encryption_info } = self;
+        let DecryptedRoomEvent {
+            event,
+            encryption_info,
+            unsigned_encryption_info,
+            encrypted_event: _,
+        } = self;
 
         f.debug_struct("DecryptedRoomEvent")
             .field("event", &DebugRawEvent(event))
@@ -1327,6 +1375,7 @@ impl From<SyncTimelineEventDeserializationHelperV0> for TimelineEvent {
                     event: event.cast_unchecked(),
                     encryption_info,
                     unsigned_encryption_info,
+                    encrypted_event: None,
                 })
             }
 
@@ -1588,6 +1637,7 @@ mod tests {
                         curve25519_key: "xxx".to_owned(),
                         sender_claimed_keys: Default::default(),
                         session_id: Some("xyz".to_owned()),
+                        forwarding_curve25519_key_chain: Vec::new(),
                     },
                     verification_state: VerificationState::Verified,
                 }),
@@ -1598,6 +1648,7 @@ mod tests {
                         reason: UnableToDecryptReason::MalformedEncryptedEvent,
                     }),
                 )])),
+                encrypted_event: None,
             }),
             timestamp: Some(MilliSecondsSinceUnixEpoch(UInt::new_saturating(2189))),
             push_actions: Default::default(),
@@ -1733,6 +1784,69 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_decrypted_event_keeps_its_encrypted_source_in_memory() {
+        let encrypted_event = Raw::new(&json!({
+            "content": {
+                "algorithm": "m.megolm.v1.aes-sha2",
+                "ciphertext": "AwgAEnACgAkLmt6qF84IK++J7UDH2Za1YVchHyprqTqsg",
+                "device_id": "SKCGPNUWAU",
+                "sender_key": "Gim/c7uQdSXyrrUbmUOrBT6sMC0gO7QSLmOK6B7NOm0",
+                "session_id": "hgLyeSqXfb8vc5AjQLsg6TSHVu0HJ7HZ4B6jgMvxkrs",
+            },
+            "event_id": "$xxxxx:example.org",
+            "origin_server_ts": 2189,
+            "sender": "@carl:example.com",
+            "type": "m.room.encrypted",
+        }))
+        .unwrap()
+        .cast_unchecked();
+
+        let room_event = TimelineEvent::from_decrypted(
+            DecryptedRoomEvent {
+                event: Raw::new(&example_event()).unwrap().cast_unchecked(),
+                encryption_info: Arc::new(EncryptionInfo {
+                    sender: user_id!("@sender:example.com").to_owned(),
+                    sender_device: None,
+                    algorithm_info: AlgorithmInfo::MegolmV1AesSha2 {
+                        curve25519_key: "xxx".to_owned(),
+                        sender_claimed_keys: Default::default(),
+                        session_id: Some("xyz".to_owned()),
+                        forwarding_curve25519_key_chain: vec!["yyy".to_owned()],
+                    },
+                    verification_state: VerificationState::Verified,
+                }),
+                unsigned_encryption_info: None,
+                encrypted_event: Some(encrypted_event),
+            },
+            None,
+        );
+
+        assert_eq!(
+            room_event.encrypted_raw().unwrap().get_field::<String>("type").unwrap().as_deref(),
+            Some("m.room.encrypted")
+        );
+
+        // The forwarding chain survives a round-trip through the serialized format, but
+        // the encrypted event isn't serialized.
+        let serialized = serde_json::to_value(&room_event).unwrap();
+        assert!(serialized["kind"]["Decrypted"].get("encrypted_event").is_none());
+
+        let event: TimelineEvent = serde_json::from_value(serialized).unwrap();
+
+        assert!(event.encrypted_raw().is_none());
+        assert_eq!(
+            event.raw().get_field::<String>("type").unwrap().as_deref(),
+            Some("m.room.message")
+        );
+        assert_matches!(
+            &event.encryption_info().unwrap().algorithm_info,
+            AlgorithmInfo::MegolmV1AesSha2 { forwarding_curve25519_key_chain, .. } => {
+                assert_eq!(forwarding_curve25519_key_chain, &["yyy".to_owned()]);
+            }
+        );
+    }
+
     #[test]
     fn test_creating_or_deserializing_an_event_extracts_summary() {
         let event = json!({
@@ -1768,7 +1882,7 @@ mod tests {
         // When creating a timeline event from a raw event, the thread summary is always
         // extracted, if available.
         let timeline_event = TimelineEvent::from_plaintext(raw);
-        assert_matches!(timeline_event.thread_summary, ThreadSummaryStatus::Some(ThreadSummary { num_replies, latest_reply }) => {
+        assert_matches!(timeline_event.thread_summary, ThreadSummaryStatus::Some(ThreadSummary { num_replies, latest_reply, .. }) => {
             assert_eq!(num_replies, 2);
             assert_eq!(latest_reply.as_deref(), Some(event_id!("$latest_event:example.com")));
         });
@@ -1968,6 +2082,7 @@ mod tests {
                 (DeviceKeyAlgorithm::Ed25519, "claimedclaimeded25519".to_owned()),
             ]),
             session_id: None,
+            forwarding_curve25519_key_chain: Vec::new(),
         };
 
         with_settings!({ prepend_module_to_snapshot => false }, {
@@ -2012,6 +2127,7 @@ mod tests {
                 curve25519_key: "curvecurvecurve".into(),
                 sender_claimed_keys: Default::default(),
                 session_id: Some("mysessionid76".to_owned()),
+                forwarding_curve25519_key_chain: Vec::new(),
             },
             verification_state: VerificationState::Verified,
         };
@@ -2042,6 +2158,7 @@ mod tests {
                             ),
                         ]),
                         session_id: Some("mysessionid112".to_owned()),
+                        forwarding_curve25519_key_chain: Vec::new(),
                     },
                     verification_state: VerificationState::Verified,
                 }),
@@ -2054,12 +2171,14 @@ mod tests {
                         },
                     }),
                 )])),
+                encrypted_event: None,
             }),
             timestamp: Some(MilliSecondsSinceUnixEpoch(UInt::new_saturating(2189))),
             push_actions: Default::default(),
             thread_summary: ThreadSummaryStatus::Some(ThreadSummary {
                 num_replies: 2,
                 latest_reply: None,
+                num_participants: None,
             }),
             bundled_latest_thread_event: None,
         };
@@ -27,10 +27,23 @@ use std::{
 
 #[cfg(not(target_family = "wasm"))]
 mod sys {
+    #[cfg(not(feature = "opentelemetry"))]
+    pub use tokio::task::spawn;
     pub use tokio::{
         runtime::{Handle, Runtime},
-        task::{AbortHandle, JoinError, JoinHandle, spawn},
+        task::{AbortHandle, JoinError, JoinHandle},
     };
+
+    /// Spawn a new task, which inherits the current span and OpenTelemetry
+    /// context, so the trace IDs are propagated to it.
+    #[cfg(feature = "opentelemetry")]
+    pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        tokio::task::spawn(super::WithCurrentContext::new(future))
+    }
 }
 
 #[cfg(target_family = "wasm")]
@@ -38,6 +51,10 @@ mod sys {
     use std::{
         future::Future,
         pin::Pin,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
         task::{Context, Poll},
     };
 
@@ -81,6 +98,8 @@ mod sys {
     pub struct JoinHandle<T> {
         remote_handle: Option<RemoteHandle<T>>,
         abort_handle: AbortHandle,
+        /// Whether the spawned future has run to completion.
+        finished: Arc<AtomicBool>,
     }
 
     impl<T> JoinHandle<T> {
@@ -95,9 +114,10 @@ mod sys {
             self.abort_handle.clone()
         }
 
-        /// Returns true if the spawned future has been aborted.
+        /// Returns true if the spawned future has completed, or has been
+        /// aborted.
         pub fn is_finished(&self) -> bool {
-            self.abort_handle.is_aborted()
+            self.finished.load(Ordering::Acquire) || self.abort_handle.is_aborted()
         }
     }
 
@@ -127,26 +147,70 @@ mod sys {
 
     /// A Wasm specific version of `tokio::task::spawn` that utilizes
     /// wasm_bindgen_futures to spawn futures on the local executor.
+    ///
+    /// With the `opentelemetry` feature, the task inherits the current span and
+    /// OpenTelemetry context, so the trace IDs are propagated to it.
     pub fn spawn<F, T>(future: F) -> JoinHandle<T>
     where
         F: Future<Output = T> + 'static,
     {
+        #[cfg(feature = "opentelemetry")]
+        let future = super::WithCurrentContext::new(future);
+
         let (future, remote_handle) = future.remote_handle();
         let (abort_handle, abort_registration) = AbortHandle::new_pair();
         let future = Abortable::new(future, abort_registration);
+        let finished = Arc::new(AtomicBool::new(false));
 
-        wasm_bindgen_futures::spawn_local(async {
-            // Poll the future, and ignore the result (either it's `Ok(())`, or it's
-            // `Err(Aborted)`).
-            let _ = future.await;
+        wasm_bindgen_futures::spawn_local({
+            let finished = finished.clone();
+
+            async move {
+                // Poll the future, and ignore the result (either it's `Ok(())`, or it's
+                // `Err(Aborted)`).
+                let _ = future.await;
+                finished.store(true, Ordering::Release);
+            }
         });
 
-        JoinHandle { remote_handle: Some(remote_handle), abort_handle }
+        JoinHandle { remote_handle: Some(remote_handle), abort_handle, finished }
     }
 }
 
 pub use sys::*;
 
+/// A future running in the tracing span and the OpenTelemetry context that
+/// were current when it was created.
+#[cfg(feature = "opentelemetry")]
+struct WithCurrentContext<F> {
+    future: Pin<Box<tracing::instrument::Instrumented<F>>>,
+    context: opentelemetry::Context,
+}
+
+#[cfg(feature = "opentelemetry")]
+impl<F: Future> WithCurrentContext<F> {
+    fn new(future: F) -> Self {
+        use tracing::Instrument as _;
+
+        Self {
+            future: Box::pin(future.in_current_span()),
+            context: opentelemetry::Context::current(),
+        }
+    }
+}
+
+#[cfg(feature = "opentelemetry")]
+impl<F: Future> Future for WithCurrentContext<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The context must only be current while the future is polled, not while it's
+        // suspended, to not leak into the other tasks of the same thread.
+        let _guard = self.context.clone().attach();
+        self.future.as_mut().poll(cx)
+    }
+}
+
 /// A type ensuring a task is aborted on drop.
 #[derive(Debug)]
 pub struct AbortOnDrop<T>(JoinHandle<T>);
@@ -184,10 +248,13 @@ impl<T> JoinHandleExt<T> for JoinHandle<T> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use assert_matches::assert_matches;
     use matrix_sdk_test_macros::async_test;
 
     use super::spawn;
+    use crate::{sleep::sleep, timeout::timeout};
 
     #[async_test]
     async fn test_spawn() {
@@ -197,6 +264,62 @@ mod tests {
         assert_matches!(join_handle.await, Ok(42));
     }
 
+    #[async_test]
+    async fn test_is_finished() {
+        let finished = spawn(async {});
+        let pending = spawn(std::future::pending::<()>());
+
+        // Wait for the first task to be done, without relying on how long the
+        // executor takes to run it.
+        timeout(
+            async {
+                while !finished.is_finished() {
+                    sleep(Duration::from_millis(1)).await;
+                }
+            },
+            Duration::from_secs(1),
+        )
+        .await
+        .expect("the finished task should be done");
+
+        assert!(!pending.is_finished());
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[async_test]
+    async fn test_spawn_propagates_the_current_span() {
+        use tracing::{Instrument as _, Span};
+
+        // Without a subscriber, the spans are disabled and don't have an ID.
+        let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry());
+
+        let parent = tracing::info_span!("parent");
+        let parent_id = parent.id();
+        assert!(parent_id.is_some());
+
+        let child_id =
+            async { spawn(async { Span::current().id() }).await.unwrap() }.instrument(parent).await;
+
+        assert_eq!(child_id, parent_id);
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[async_test]
+    async fn test_spawn_propagates_the_current_context() {
+        use opentelemetry::Context;
+
+        #[derive(Debug, PartialEq)]
+        struct Marker(u8);
+
+        let _context = Context::current_with_value(Marker(42)).attach();
+
+        let value = spawn(async { Context::current().get::<Marker>().map(|marker| marker.0) })
+            .await
+            .unwrap();
+
+        assert_eq!(value, Some(42));
+    }
+
     #[async_test]
     async fn test_abort() {
         let future = async { 42 };
@@ -24,6 +24,7 @@ use futures_core::Future;
 #[doc(no_inline)]
 pub use ruma;
 
+pub mod clock;
 pub mod cross_process_lock;
 pub mod debug;
 pub mod deserialized_responses;
@@ -35,6 +36,8 @@ pub mod ring_buffer;
 pub mod serde_helpers;
 pub mod sleep;
 pub mod stream;
+#[cfg(feature = "opentelemetry")]
+pub mod telemetry;
 pub mod timeout;
 pub mod tracing_timer;
 pub mod ttl_cache;
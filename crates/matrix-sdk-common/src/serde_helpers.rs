@@ -107,7 +107,11 @@ pub fn extract_bundled_thread_summary(
                 bundled_thread.latest_event.get_field::<OwnedEventId>("event_id").ok().flatten();
 
             (
-                ThreadSummaryStatus::Some(ThreadSummary { num_replies: count, latest_reply }),
+                ThreadSummaryStatus::Some(ThreadSummary {
+                    num_replies: count,
+                    latest_reply,
+                    num_participants: None,
+                }),
                 Some(bundled_thread.latest_event),
             )
         }
@@ -0,0 +1,154 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! OpenTelemetry metrics of the SDK.
+//!
+//! The SDK records its metrics with the global [`MeterProvider`] of
+//! OpenTelemetry, under the `matrix-sdk` meter. The provider must be
+//! installed, with [`opentelemetry::global::set_meter_provider`], before any
+//! client is created, otherwise the metrics are silently dropped.
+//!
+//! The spans of the SDK are emitted with `tracing`; they can be exported to
+//! OpenTelemetry with the `tracing-opentelemetry` layer. With this feature,
+//! the tasks spawned by the SDK inherit the span of the code that spawned
+//! them, so the trace IDs are propagated to the background work too.
+//!
+//! [`MeterProvider`]: opentelemetry::metrics::MeterProvider
+
+use std::{future::Future, sync::LazyLock, time::Duration};
+
+use opentelemetry::{
+    KeyValue, global,
+    metrics::{Counter, Histogram},
+};
+use ruma::time::Instant;
+
+/// The name of the meter of the SDK.
+const METER_NAME: &str = "matrix-sdk";
+
+/// The instruments used to record the metrics of the SDK.
+struct Instruments {
+    http_requests: Counter<u64>,
+    http_request_duration: Histogram<f64>,
+    sync_iterations: Counter<u64>,
+    sync_iteration_duration: Histogram<f64>,
+    crypto_operation_duration: Histogram<f64>,
+    store_transaction_duration: Histogram<f64>,
+}
+
+static INSTRUMENTS: LazyLock<Instruments> = LazyLock::new(|| {
+    let meter = global::meter(METER_NAME);
+
+    Instruments {
+        http_requests: meter
+            .u64_counter("matrix_sdk.http.requests")
+            .with_description("The number of HTTP requests sent to the homeserver")
+            .build(),
+        http_request_duration: meter
+            .f64_histogram("matrix_sdk.http.request.duration")
+            .with_description("The duration of the HTTP requests sent to the homeserver")
+            .with_unit("s")
+            .build(),
+        sync_iterations: meter
+            .u64_counter("matrix_sdk.sync.iterations")
+            .with_description("The number of iterations of the sync loops")
+            .build(),
+        sync_iteration_duration: meter
+            .f64_histogram("matrix_sdk.sync.iteration.duration")
+            .with_description("The duration of an iteration of a sync loop")
+            .with_unit("s")
+            .build(),
+        crypto_operation_duration: meter
+            .f64_histogram("matrix_sdk.crypto.operation.duration")
+            .with_description("The duration of the encryption and decryption operations")
+            .with_unit("s")
+            .build(),
+        store_transaction_duration: meter
+            .f64_histogram("matrix_sdk.store.transaction.duration")
+            .with_description("The duration of the transactions of the stores")
+            .with_unit("s")
+            .build(),
+    }
+});
+
+fn outcome<T, E>(result: &Result<T, E>) -> KeyValue {
+    KeyValue::new("outcome", if result.is_ok() { "success" } else { "error" })
+}
+
+/// Record an attempt of sending an HTTP request.
+///
+/// The `status` is `None` if no response has been received, e.g. because of a
+/// network error.
+pub fn record_http_request(method: &str, status: Option<u16>, duration: Duration) {
+    let mut attributes = vec![KeyValue::new("http.request.method", method.to_owned())];
+    if let Some(status) = status {
+        attributes.push(KeyValue::new("http.response.status_code", i64::from(status)));
+    }
+
+    INSTRUMENTS.http_requests.add(1, &attributes);
+    INSTRUMENTS.http_request_duration.record(duration.as_secs_f64(), &attributes);
+}
+
+/// Measure an iteration of a sync loop, i.e. sending the sync request and
+/// processing its response.
+///
+/// `endpoint` is the sync endpoint (e.g. `sync` or `sliding_sync`).
+pub async fn instrument_sync_iteration<T, E>(
+    endpoint: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let result = future.await;
+
+    let attributes = [KeyValue::new("endpoint", endpoint), outcome(&result)];
+    INSTRUMENTS.sync_iterations.add(1, &attributes);
+    INSTRUMENTS.sync_iteration_duration.record(start.elapsed().as_secs_f64(), &attributes);
+
+    result
+}
+
+/// Measure a crypto operation, e.g. the decryption of an event.
+pub async fn instrument_crypto_operation<T, E>(
+    operation: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let result = future.await;
+
+    INSTRUMENTS.crypto_operation_duration.record(
+        start.elapsed().as_secs_f64(),
+        &[KeyValue::new("operation", operation), outcome(&result)],
+    );
+
+    result
+}
+
+/// Measure a transaction of a store, e.g. saving the changes of a sync.
+///
+/// `store` is the kind of the store (e.g. `state`).
+pub async fn instrument_store_transaction<T, E>(
+    store: &'static str,
+    operation: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let result = future.await;
+
+    INSTRUMENTS.store_transaction_duration.record(
+        start.elapsed().as_secs_f64(),
+        &[KeyValue::new("store", store), KeyValue::new("operation", operation), outcome(&result)],
+    );
+
+    result
+}
@@ -48,8 +48,10 @@ where
 
     #[cfg(target_family = "wasm")]
     {
+        // Like `sleep`, saturate overlong durations instead of panicking: waiting
+        // for `u32::MAX` milliseconds is waiting forever in practice.
         let timeout_future =
-            TimeoutFuture::new(u32::try_from(duration.as_millis()).expect("Overlong duration"));
+            TimeoutFuture::new(u32::try_from(duration.as_millis()).unwrap_or(u32::MAX));
 
         match select(std::pin::pin!(future), timeout_future).await {
             Either::Left((res, _)) => Ok(res),
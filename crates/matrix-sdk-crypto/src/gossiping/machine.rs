@@ -971,7 +971,10 @@ impl GossipMachine {
         event: &DecryptedForwardedRoomKeyEvent,
     ) -> Result<Option<InboundGroupSession>, CryptoStoreError> {
         match InboundGroupSession::try_from(event) {
-            Ok(session) => {
+            Ok(mut session) => {
+                // Remember that the session was forwarded to us by this device.
+                session.push_forwarder(sender_key);
+
                 if self.inner.store.compare_group_session(&session).await?
                     == SessionOrdering::Better
                 {
@@ -1987,6 +1987,11 @@ impl OlmMachine {
                     .map(|(k, v)| (k.to_owned(), v.to_base64()))
                     .collect(),
                 session_id: Some(session.session_id().to_owned()),
+                forwarding_curve25519_key_chain: session
+                    .forwarding_curve25519_key_chain()
+                    .iter()
+                    .map(|key| key.to_base64())
+                    .collect(),
             },
             verification_state,
         }))
@@ -2205,6 +2210,7 @@ impl OlmMachine {
     ) -> MegolmResult<DecryptedRoomEvent> {
         let _timer = timer!(tracing::Level::TRACE, "_method");
 
+        let encrypted_event = event.clone().cast_unchecked();
         let event = event.deserialize()?;
 
         Span::current()
@@ -2270,7 +2276,12 @@ impl OlmMachine {
         #[cfg(feature = "experimental-encrypted-state-events")]
         self.verify_packed_state_key(&event, &decrypted_event)?;
 
-        Ok(DecryptedRoomEvent { event: decrypted_event, encryption_info, unsigned_encryption_info })
+        Ok(DecryptedRoomEvent {
+            event: decrypted_event,
+            encryption_info,
+            unsigned_encryption_info,
+            encrypted_event: Some(encrypted_event),
+        })
     }
 
     /// If the passed event is a state event, verify its outer packed state key
@@ -45,7 +45,7 @@ use crate::types::events::room_key::RoomKeyContent;
 use crate::{
     error::{EventError, MegolmResult},
     types::{
-        deserialize_curve_key,
+        deserialize_curve_key, deserialize_curve_key_vec,
         events::{
             forwarded_room_key::{
                 ForwardedMegolmV1AesSha2Content, ForwardedMegolmV2AesSha2Content,
@@ -56,7 +56,7 @@ use crate::{
             room_key,
         },
         room_history::HistoricRoomKey,
-        serialize_curve_key, EventEncryptionAlgorithm, SigningKeys,
+        serialize_curve_key, serialize_curve_key_vec, EventEncryptionAlgorithm, SigningKeys,
     },
 };
 // TODO: add creation times to the inbound group sessions so we can export
@@ -217,6 +217,12 @@ pub struct InboundGroupSession {
     ///
     /// [MSC3061]: https://github.com/matrix-org/matrix-spec-proposals/pull/3061
     shared_history: bool,
+
+    /// The chain of Curve25519 keys of the devices through which this session
+    /// was forwarded to us, via `m.forwarded_room_key` events.
+    ///
+    /// Empty if the session was received directly from its creator.
+    forwarding_curve25519_key_chain: Arc<[Curve25519PublicKey]>,
 }
 
 impl InboundGroupSession {
@@ -291,6 +297,7 @@ impl InboundGroupSession {
             algorithm: encryption_algorithm.into(),
             backed_up: AtomicBool::new(false).into(),
             shared_history,
+            forwarding_curve25519_key_chain: Arc::new([]),
         })
     }
 
@@ -356,6 +363,7 @@ impl InboundGroupSession {
             history_visibility: self.history_visibility.as_ref().clone(),
             algorithm: (*self.algorithm).to_owned(),
             shared_history: self.shared_history,
+            forwarding_curve25519_key_chain: self.forwarding_curve25519_key_chain.to_vec(),
         }
     }
 
@@ -393,6 +401,21 @@ impl InboundGroupSession {
         &self.creator_info.signing_keys
     }
 
+    /// Get the chain of Curve25519 keys of the devices through which this
+    /// session was forwarded to us.
+    ///
+    /// Empty if the session was received directly from its creator.
+    pub fn forwarding_curve25519_key_chain(&self) -> &[Curve25519PublicKey] {
+        &self.forwarding_curve25519_key_chain
+    }
+
+    /// Append the Curve25519 key of the device which forwarded this session to
+    /// us to the forwarding chain of the session.
+    pub(crate) fn push_forwarder(&mut self, forwarder_key: Curve25519PublicKey) {
+        self.forwarding_curve25519_key_chain =
+            self.forwarding_curve25519_key_chain.iter().copied().chain([forwarder_key]).collect();
+    }
+
     /// Export this session at the given message index.
     pub async fn export_at_index(&self, message_index: u32) -> ExportedRoomKey {
         let message_index = std::cmp::max(self.first_known_index(), message_index);
@@ -405,7 +428,7 @@ impl InboundGroupSession {
             room_id: self.room_id().to_owned(),
             sender_key: self.creator_info.curve25519_key,
             session_id: self.session_id().to_owned(),
-            forwarding_curve25519_key_chain: vec![],
+            forwarding_curve25519_key_chain: self.forwarding_curve25519_key_chain.to_vec(),
             sender_claimed_keys: (*self.creator_info.signing_keys).clone(),
             session_key,
             shared_history: self.shared_history,
@@ -435,6 +458,7 @@ impl InboundGroupSession {
             history_visibility,
             algorithm,
             shared_history,
+            forwarding_curve25519_key_chain,
         } = pickle;
 
         let session: InnerSession = pickle.into();
@@ -456,6 +480,7 @@ impl InboundGroupSession {
             algorithm: algorithm.into(),
             imported,
             shared_history,
+            forwarding_curve25519_key_chain: forwarding_curve25519_key_chain.into(),
         })
     }
 
@@ -666,6 +691,15 @@ pub struct PickledInboundGroupSession {
     /// [MSC3061]: https://github.com/matrix-org/matrix-spec-proposals/pull/3061
     #[serde(default)]
     pub shared_history: bool,
+    /// The chain of Curve25519 keys of the devices through which the session
+    /// was forwarded to us.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_curve_key_vec",
+        serialize_with = "serialize_curve_key_vec"
+    )]
+    pub forwarding_curve25519_key_chain: Vec<Curve25519PublicKey>,
 }
 
 fn default_algorithm() -> EventEncryptionAlgorithm {
@@ -706,6 +740,7 @@ impl TryFrom<&HistoricRoomKey> for InboundGroupSession {
             algorithm: algorithm.to_owned().into(),
             backed_up: AtomicBool::from(false).into(),
             shared_history: true,
+            forwarding_curve25519_key_chain: Arc::new([]),
         })
     }
 }
@@ -721,7 +756,7 @@ impl TryFrom<&ExportedRoomKey> for InboundGroupSession {
             session_id,
             session_key,
             sender_claimed_keys,
-            forwarding_curve25519_key_chain: _,
+            forwarding_curve25519_key_chain,
             shared_history,
         } = key;
 
@@ -746,6 +781,7 @@ impl TryFrom<&ExportedRoomKey> for InboundGroupSession {
             algorithm: algorithm.to_owned().into(),
             backed_up: AtomicBool::from(false).into(),
             shared_history: *shared_history,
+            forwarding_curve25519_key_chain: forwarding_curve25519_key_chain.as_slice().into(),
         })
     }
 }
@@ -777,6 +813,10 @@ impl From<&ForwardedMegolmV1AesSha2Content> for InboundGroupSession {
             algorithm: EventEncryptionAlgorithm::MegolmV1AesSha2.into(),
             backed_up: AtomicBool::from(false).into(),
             shared_history: false,
+            forwarding_curve25519_key_chain: value
+                .forwarding_curve25519_key_chain
+                .as_slice()
+                .into(),
         }
     }
 }
@@ -804,6 +844,7 @@ impl From<&ForwardedMegolmV2AesSha2Content> for InboundGroupSession {
             algorithm: EventEncryptionAlgorithm::MegolmV1AesSha2.into(),
             backed_up: AtomicBool::from(false).into(),
             shared_history: false,
+            forwarding_curve25519_key_chain: Arc::new([]),
         }
     }
 }
@@ -1271,4 +1312,39 @@ mod tests {
             "The unset shared history flag should have been copied to the room key export"
         );
     }
+
+    #[async_test]
+    async fn test_forwarding_chain_in_pickle_and_export() {
+        let alice = Account::with_device_id(alice_id(), alice_device_id());
+        let room_id = room_id!("!test:localhost");
+
+        let (_, mut inbound) = alice.create_group_session_pair_with_defaults(room_id).await;
+        assert!(inbound.forwarding_curve25519_key_chain().is_empty());
+
+        let forwarder_key =
+            Curve25519PublicKey::from_base64("LTpv2DGMhggPAXO02+7f68CNEp6A40F0Yl8B094Y8gc")
+                .unwrap();
+        inbound.push_forwarder(forwarder_key);
+        assert_eq!(inbound.forwarding_curve25519_key_chain(), [forwarder_key]);
+
+        let pickle = inbound.pickle().await;
+        assert_eq!(pickle.forwarding_curve25519_key_chain, [forwarder_key]);
+
+        let unpickled = InboundGroupSession::from_pickle(pickle).unwrap();
+        assert_eq!(
+            unpickled.forwarding_curve25519_key_chain(),
+            [forwarder_key],
+            "The forwarding chain should have been restored from the pickle"
+        );
+
+        let export = inbound.export().await;
+        assert_eq!(export.forwarding_curve25519_key_chain, [forwarder_key]);
+
+        let imported = InboundGroupSession::from_export(&export).unwrap();
+        assert_eq!(
+            imported.forwarding_curve25519_key_chain(),
+            [forwarder_key],
+            "The forwarding chain should have been restored from the room key export"
+        );
+    }
 }
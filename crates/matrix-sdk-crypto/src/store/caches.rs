@@ -83,6 +83,28 @@ impl SessionStore {
     pub async fn set_for_sender(&self, sender_key: &str, sessions: Vec<Session>) {
         self.entries.write().await.insert(sender_key.to_owned(), Arc::new(Mutex::new(sessions)));
     }
+
+    /// Count the sender keys and the sessions currently held in memory.
+    pub async fn stats(&self) -> SessionStoreStats {
+        let entries = self.entries.read().await;
+
+        let mut sessions = 0;
+        for sender_sessions in entries.values() {
+            sessions += sender_sessions.lock().await.len();
+        }
+
+        SessionStoreStats { sender_keys: entries.len(), sessions }
+    }
+}
+
+/// The number of Olm sessions held in memory by a [`SessionStore`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SessionStoreStats {
+    /// The number of sender keys for which sessions are cached.
+    pub sender_keys: usize,
+
+    /// The total number of cached sessions, across all the sender keys.
+    pub sessions: usize,
 }
 
 /// In-memory store holding the devices of users.
@@ -406,7 +428,7 @@ mod tests {
     use matrix_sdk_test::async_test;
     use proptest::prelude::*;
 
-    use super::{DeviceStore, SequenceNumber, SessionStore};
+    use super::{DeviceStore, SequenceNumber, SessionStore, SessionStoreStats};
     use crate::{
         identities::device::testing::get_device, olm::tests::get_account_and_session_test_helper,
     };
@@ -443,6 +465,20 @@ mod tests {
         assert_eq!(&session, loaded_session);
     }
 
+    #[async_test]
+    async fn test_session_store_stats() {
+        let (_, session) = get_account_and_session_test_helper();
+
+        let store = SessionStore::new();
+        assert_eq!(store.stats().await, SessionStoreStats::default());
+
+        store.add(session.clone()).await;
+        assert_eq!(store.stats().await, SessionStoreStats { sender_keys: 1, sessions: 1 });
+
+        store.clear().await;
+        assert_eq!(store.stats().await, SessionStoreStats::default());
+    }
+
     #[async_test]
     async fn test_device_store() {
         let device = get_device();
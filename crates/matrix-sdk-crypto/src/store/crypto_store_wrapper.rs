@@ -9,8 +9,9 @@ use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 use tracing::{debug, trace, warn};
 
 use super::{
-    caches::SessionStore, types::RoomKeyBundleInfo, DeviceChanges, IdentityChanges,
-    LockableCryptoStore,
+    caches::{SessionStore, SessionStoreStats},
+    types::RoomKeyBundleInfo,
+    DeviceChanges, IdentityChanges, LockableCryptoStore,
 };
 use crate::{
     olm::InboundGroupSession,
@@ -258,6 +259,11 @@ impl CryptoStoreWrapper {
         Ok(())
     }
 
+    /// Count the Olm sessions held in the in-memory cache.
+    pub async fn session_cache_stats(&self) -> SessionStoreStats {
+        self.sessions.stats().await
+    }
+
     pub async fn get_sessions(
         &self,
         sender_key: &str,
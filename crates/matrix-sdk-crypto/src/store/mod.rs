@@ -104,7 +104,9 @@ use matrix_sdk_common::{
 pub use memorystore::MemoryStore;
 pub use traits::{CryptoStore, DynCryptoStore, IntoCryptoStore};
 
-use self::caches::{SequenceNumber, StoreCache, StoreCacheGuard, UsersForKeyQuery};
+use self::caches::{
+    SequenceNumber, SessionStoreStats, StoreCache, StoreCacheGuard, UsersForKeyQuery,
+};
 use crate::types::{
     events::room_key_withheld::RoomKeyWithheldContent, room_history::RoomKeyBundle,
 };
@@ -1088,6 +1090,15 @@ impl Store {
         self.set_value("only_allow_trusted_devices", &block_untrusted_devices).await
     }
 
+    /// Count the Olm sessions currently cached in memory.
+    ///
+    /// The sessions are loaded into this cache lazily, when a message is
+    /// encrypted for or decrypted from a device, and are kept there for the
+    /// lifetime of the store.
+    pub async fn session_cache_stats(&self) -> SessionStoreStats {
+        self.inner.store.session_cache_stats().await
+    }
+
     /// Get custom stored value associated with a key
     pub async fn get_value<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
         let Some(value) = self.get_custom_value(key).await? else {
@@ -25,6 +25,7 @@ use growable_bloom_filter::GrowableBloom;
 use indexed_db_futures::prelude::*;
 use matrix_sdk_base::{
     deserialized_responses::{DisplayName, RawAnySyncOrStrippedState},
+    invite_policy::QuarantinedInvite,
     store::{
         compare_thread_subscription_bump_stamps, ChildTransactionId, ComposerDraft,
         DependentQueuedRequest, DependentQueuedRequestKind, QueuedRequest, QueuedRequestKind,
@@ -442,6 +443,9 @@ impl IndexeddbStateStore {
             StateStoreDataKey::ThreadSubscriptionsCatchupTokens => {
                 self.encode_key(keys::KV, StateStoreDataKey::THREAD_SUBSCRIPTIONS_CATCHUP_TOKENS)
             }
+            StateStoreDataKey::QuarantinedInvites => {
+                self.encode_key(keys::KV, StateStoreDataKey::QUARANTINED_INVITES)
+            }
         }
     }
 }
@@ -598,6 +602,10 @@ impl_state_store!({
                 .map(|f| self.deserialize_value::<Vec<ThreadSubscriptionCatchupToken>>(&f))
                 .transpose()?
                 .map(StateStoreDataValue::ThreadSubscriptionsCatchupTokens),
+            StateStoreDataKey::QuarantinedInvites => value
+                .map(|f| self.deserialize_value::<BTreeMap<OwnedRoomId, QuarantinedInvite>>(&f))
+                .transpose()?
+                .map(StateStoreDataValue::QuarantinedInvites),
         };
 
         Ok(value)
@@ -644,6 +652,11 @@ impl_state_store!({
                     .into_thread_subscriptions_catchup_tokens()
                     .expect("Session data is not a list of thread subscription catchup tokens"),
             ),
+            StateStoreDataKey::QuarantinedInvites => self.serialize_value(
+                &value
+                    .into_quarantined_invites()
+                    .expect("Session data is not a list of quarantined invites"),
+            ),
         };
 
         let tx =
@@ -1296,6 +1309,33 @@ impl_state_store!({
             .transpose()
     }
 
+    async fn get_account_data_events(&self) -> Result<Vec<Raw<AnyGlobalAccountDataEvent>>> {
+        self.inner
+            .transaction_on_one_with_mode(keys::ACCOUNT_DATA, IdbTransactionMode::Readonly)?
+            .object_store(keys::ACCOUNT_DATA)?
+            .get_all()?
+            .await?
+            .iter()
+            .map(|f| self.deserialize_value(&f))
+            .collect()
+    }
+
+    async fn get_room_account_data_events(
+        &self,
+        room_id: &RoomId,
+    ) -> Result<Vec<Raw<AnyRoomAccountDataEvent>>> {
+        let range = self.encode_to_range(keys::ROOM_ACCOUNT_DATA, room_id)?;
+
+        self.inner
+            .transaction_on_one_with_mode(keys::ROOM_ACCOUNT_DATA, IdbTransactionMode::Readonly)?
+            .object_store(keys::ROOM_ACCOUNT_DATA)?
+            .get_all_with_key(&range)?
+            .await?
+            .iter()
+            .map(|f| self.deserialize_value(&f))
+            .collect()
+    }
+
     async fn get_user_room_receipt_event(
         &self,
         room_id: &RoomId,
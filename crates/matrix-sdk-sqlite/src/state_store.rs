@@ -419,6 +419,9 @@ impl SqliteStateStore {
             StateStoreDataKey::ThreadSubscriptionsCatchupTokens => {
                 Cow::Borrowed(StateStoreDataKey::THREAD_SUBSCRIPTIONS_CATCHUP_TOKENS)
             }
+            StateStoreDataKey::QuarantinedInvites => {
+                Cow::Borrowed(StateStoreDataKey::QUARANTINED_INVITES)
+            }
         };
 
         self.encode_key(keys::KV_BLOB, &*key_s)
@@ -932,6 +935,22 @@ trait SqliteObjectStateStoreExt: SqliteAsyncConnExt {
             .optional()?)
     }
 
+    async fn get_all_global_account_data(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(self
+            .prepare("SELECT data FROM global_account_data", move |mut stmt| {
+                stmt.query_map((), |row| row.get(0))?.collect()
+            })
+            .await?)
+    }
+
+    async fn get_all_room_account_data(&self, room_id: Key) -> Result<Vec<Vec<u8>>> {
+        Ok(self
+            .prepare("SELECT data FROM room_account_data WHERE room_id = ?", move |mut stmt| {
+                stmt.query((room_id,))?.mapped(|row| row.get(0)).collect()
+            })
+            .await?)
+    }
+
     async fn get_display_names(
         &self,
         room_id: Key,
@@ -1045,6 +1064,9 @@ impl StateStore for SqliteStateStore {
                             self.deserialize_value(&data)?,
                         )
                     }
+                    StateStoreDataKey::QuarantinedInvites => {
+                        StateStoreDataValue::QuarantinedInvites(self.deserialize_value(&data)?)
+                    }
                 })
             })
             .transpose()
@@ -1090,6 +1112,11 @@ impl StateStore for SqliteStateStore {
                     .into_thread_subscriptions_catchup_tokens()
                     .expect("Session data is not a list of thread subscription catchup tokens"),
             )?,
+            StateStoreDataKey::QuarantinedInvites => self.serialize_value(
+                &value
+                    .into_quarantined_invites()
+                    .expect("Session data is not a list of quarantined invites"),
+            )?,
         };
 
         self.acquire()
@@ -1688,6 +1715,30 @@ impl StateStore for SqliteStateStore {
             .transpose()
     }
 
+    async fn get_account_data_events(&self) -> Result<Vec<Raw<AnyGlobalAccountDataEvent>>> {
+        self.acquire()
+            .await?
+            .get_all_global_account_data()
+            .await?
+            .into_iter()
+            .map(|value| self.deserialize_json(&value))
+            .collect()
+    }
+
+    async fn get_room_account_data_events(
+        &self,
+        room_id: &RoomId,
+    ) -> Result<Vec<Raw<AnyRoomAccountDataEvent>>> {
+        let room_id = self.encode_key(keys::ROOM_ACCOUNT_DATA, room_id);
+        self.acquire()
+            .await?
+            .get_all_room_account_data(room_id)
+            .await?
+            .into_iter()
+            .map(|value| self.deserialize_json(&value))
+            .collect()
+    }
+
     async fn get_user_room_receipt_event(
         &self,
         room_id: &RoomId,
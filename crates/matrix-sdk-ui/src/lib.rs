@@ -26,6 +26,8 @@ pub mod sync_service;
 pub mod timeline;
 pub mod unable_to_decrypt_hook;
 
+mod room_updates;
+
 pub use self::{room_list_service::RoomListService, timeline::Timeline};
 
 /// The default sanitizer mode used when sanitizing HTML.
@@ -12,53 +12,125 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::borrow::Cow;
+
 pub use fuzzy_matcher::{FuzzyMatcher as _, skim::SkimMatcherV2};
 
-use super::{Filter, normalize_string};
+use super::{super::RoomListItem, Filter, normalize_string};
+
+/// The bonus added to the score of a subject starting with the pattern.
+const PREFIX_BONUS: i64 = 1_000;
 
-struct FuzzyMatcher {
+/// The bonus added to the score of a subject with a word starting with the
+/// pattern.
+const WORD_PREFIX_BONUS: i64 = 500;
+
+pub(in crate::room_list_service) struct FuzzyMatcher {
     matcher: SkimMatcherV2,
     pattern: Option<String>,
 }
 
 impl FuzzyMatcher {
-    fn new() -> Self {
+    pub(in crate::room_list_service) fn new() -> Self {
         Self { matcher: SkimMatcherV2::default().smart_case().use_cache(true), pattern: None }
     }
 
-    fn with_pattern(mut self, pattern: &str) -> Self {
+    pub(in crate::room_list_service) fn with_pattern(mut self, pattern: &str) -> Self {
         self.pattern = Some(normalize_string(pattern));
 
         self
     }
 
+    #[cfg(test)]
     fn matches(&self, subject: &str) -> bool {
+        self.score(subject).is_some()
+    }
+
+    /// The score of the match of the pattern on `subject`, the higher the
+    /// better, or `None` if it doesn't match.
+    ///
+    /// The subjects starting with the pattern, or with a word starting with
+    /// the pattern, are ranked higher.
+    fn score(&self, subject: &str) -> Option<i64> {
         // No pattern means there is a match.
-        let Some(pattern) = self.pattern.as_ref() else { return true };
+        let Some(pattern) = self.pattern.as_ref() else { return Some(0) };
+
+        let subject = normalize_string(subject);
+        let score = self.matcher.fuzzy_match(&subject, pattern)?;
+
+        Some(score + prefix_bonus(&subject, pattern))
+    }
 
-        self.matcher.fuzzy_match(&normalize_string(subject), pattern).is_some()
+    /// The score of the match of the pattern on a room, see [`Self::score`].
+    ///
+    /// It's the best score of the room name, and of the alias part of its
+    /// canonical alias. The server name of the alias is ignored, otherwise all
+    /// the rooms of a server would match its name. The room ID matches only
+    /// when it starts with the pattern, i.e. when the pattern is (the
+    /// beginning of) a room ID.
+    pub(in crate::room_list_service) fn score_room(&self, room: &RoomListItem) -> Option<i64> {
+        let pattern = self.pattern.as_deref().unwrap_or_default();
+        let room_id_score = room.room_id().as_str().starts_with(pattern).then_some(PREFIX_BONUS);
+
+        [
+            room.cached_display_name.as_deref(),
+            room.cached_canonical_alias.as_ref().map(|alias| alias.alias()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|subject| self.score(subject))
+        .chain(room_id_score)
+        .max()
     }
 }
 
-/// Create a new filter that will fuzzy match a pattern on room names.
+/// The bonus of `subject` if it starts with `pattern`, or if one of its words
+/// does.
+///
+/// Like the fuzzy matcher, the comparison is case-insensitive unless the
+/// pattern contains an uppercase letter.
+fn prefix_bonus(subject: &str, pattern: &str) -> i64 {
+    let subject = if pattern.chars().any(char::is_uppercase) {
+        Cow::Borrowed(subject)
+    } else {
+        Cow::Owned(subject.to_lowercase())
+    };
+
+    if subject.starts_with(pattern) {
+        PREFIX_BONUS
+    } else if subject.split(|c: char| !c.is_alphanumeric()).any(|word| word.starts_with(pattern)) {
+        WORD_PREFIX_BONUS
+    } else {
+        0
+    }
+}
+
+/// Create a new filter that will fuzzy match a pattern on room names,
+/// canonical aliases and room IDs.
 ///
 /// Rooms are fetched from the `Client`. The pattern and the room names are
-/// normalized with `normalize_string`.
+/// normalized with `normalize_string`, so the case of the letters, their
+/// diacritics, and some other variants like `ø` or `ß`, are ignored: “cafe”
+/// matches “Café”.
+///
+/// The score of the match, with which
+/// [`new_sorter_fuzzy_match_room_name`](crate::room_list_service::sorters::new_sorter_fuzzy_match_room_name)
+/// sorts the rooms, ranks the rooms whose name starts with the pattern higher.
 pub fn new_filter(pattern: &str) -> impl Filter + use<> {
     let searcher = FuzzyMatcher::new().with_pattern(pattern);
 
-    move |room| -> bool {
-        let Some(room_name) = &room.cached_display_name else { return false };
-
-        searcher.matches(room_name)
-    }
+    move |room| -> bool { searcher.score_room(room).is_some() }
 }
 
 #[cfg(test)]
 mod tests {
     use std::ops::Not;
 
-    use super::*;
+    use matrix_sdk::test_utils::logged_in_client_with_server;
+    use matrix_sdk_test::async_test;
+    use ruma::room_id;
+
+    use super::{super::new_rooms, *};
 
     #[test]
     fn test_no_pattern() {
@@ -124,5 +196,45 @@ mod tests {
         // Another concrete test.
         let matcher = matcher.with_pattern("stf");
         assert!(matcher.matches("Ștefan"));
+
+        // Diacritics are ignored on both sides.
+        let matcher = matcher.with_pattern("cafe");
+        assert!(matcher.matches("Café"));
+
+        let matcher = matcher.with_pattern("ö");
+        assert!(matcher.matches("office"));
+
+        // Letters which aren't decomposed are transliterated.
+        let matcher = matcher.with_pattern("strasse");
+        assert!(matcher.matches("Straße"));
+
+        let matcher = matcher.with_pattern("oresund");
+        assert!(matcher.matches("Øresund"));
+    }
+
+    #[test]
+    fn test_prefix_ranks_higher() {
+        let matcher = FuzzyMatcher::new().with_pattern("mat");
+
+        let prefix = matcher.score("Matrix").unwrap();
+        let word_prefix = matcher.score("The Matrix").unwrap();
+        let other = matcher.score("My cat").unwrap();
+
+        assert!(prefix > word_prefix);
+        assert!(word_prefix > other);
+    }
+
+    #[async_test]
+    async fn test_room_id() {
+        let (client, server) = logged_in_client_with_server().await;
+        let [room] = new_rooms([room_id!("!abc:b.c")], &client, &server).await;
+
+        // The room ID matches when it starts with the pattern.
+        let matcher = FuzzyMatcher::new().with_pattern("!abc");
+        assert!(matcher.score_room(&room).is_some());
+
+        // But not when the pattern is elsewhere in the room ID.
+        let matcher = FuzzyMatcher::new().with_pattern("b.c");
+        assert!(matcher.score_room(&room).is_none());
     }
 }
@@ -0,0 +1,61 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+
+use ruma::OwnedRoomId;
+
+use super::{super::RoomListItem, Filter};
+
+fn matches(descendants: &BTreeSet<OwnedRoomId>, room: &RoomListItem) -> bool {
+    descendants.contains(room.room_id())
+}
+
+/// Create a new filter that will filter out rooms that aren't descendants of a
+/// space.
+///
+/// The descendants are the ones returned by
+/// [`SpaceService::space_descendants`], or the latest ones yielded by
+/// [`SpaceService::subscribe_to_space_descendants`], in which case the filter
+/// must be created again every time they change.
+///
+/// [`SpaceService::space_descendants`]: crate::spaces::SpaceService::space_descendants
+/// [`SpaceService::subscribe_to_space_descendants`]: crate::spaces::SpaceService::subscribe_to_space_descendants
+pub fn new_filter(descendants: BTreeSet<OwnedRoomId>) -> impl Filter {
+    move |room| -> bool { matches(&descendants, room) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use matrix_sdk::test_utils::logged_in_client_with_server;
+    use matrix_sdk_test::async_test;
+    use ruma::room_id;
+
+    use super::{super::new_rooms, *};
+
+    #[async_test]
+    async fn test_in_space() {
+        let (client, server) = logged_in_client_with_server().await;
+        let [room_a, room_b] =
+            new_rooms([room_id!("!a:b.c"), room_id!("!b:b.c")], &client, &server).await;
+
+        let descendants = BTreeSet::from([room_id!("!a:b.c").to_owned()]);
+
+        assert!(matches(&descendants, &room_a));
+        assert!(!matches(&descendants, &room_b));
+        assert!(!matches(&BTreeSet::new(), &room_a));
+    }
+}
@@ -55,8 +55,10 @@ mod all;
 mod any;
 mod category;
 mod deduplicate_versions;
+mod encrypted;
 mod favourite;
 mod fuzzy_match_room_name;
+mod in_space;
 mod invite;
 mod joined;
 mod low_priority;
@@ -64,6 +66,7 @@ mod non_left;
 mod none;
 mod normalized_match_room_name;
 mod not;
+mod section;
 mod space;
 mod unread;
 
@@ -71,8 +74,11 @@ pub use all::new_filter as new_filter_all;
 pub use any::new_filter as new_filter_any;
 pub use category::{RoomCategory, new_filter as new_filter_category};
 pub use deduplicate_versions::new_filter as new_filter_deduplicate_versions;
+pub use encrypted::new_filter as new_filter_encrypted;
 pub use favourite::new_filter as new_filter_favourite;
+pub(super) use fuzzy_match_room_name::FuzzyMatcher;
 pub use fuzzy_match_room_name::new_filter as new_filter_fuzzy_match_room_name;
+pub use in_space::new_filter as new_filter_in_space;
 pub use invite::new_filter as new_filter_invite;
 pub use joined::new_filter as new_filter_joined;
 pub use low_priority::new_filter as new_filter_low_priority;
@@ -86,6 +92,7 @@ pub use normalized_match_room_name::new_filter as new_filter_normalized_match_ro
 pub use not::new_filter as new_filter_not;
 #[cfg(test)]
 use ruma::RoomId;
+pub use section::{RoomListSection, new_filter as new_filter_section};
 pub use space::new_filter as new_filter_space;
 use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
 pub use unread::new_filter as new_filter_unread;
@@ -111,10 +118,46 @@ pub type BoxedFilterFn = Box<dyn Filter + Send + Sync>;
 pub type BoxedFilterFn = Box<dyn Filter>;
 
 /// Normalize a string, i.e. decompose it into NFD (Normalization Form D, i.e. a
-/// canonical decomposition, see http://www.unicode.org/reports/tr15/) and
-/// filter out the combining marks.
+/// canonical decomposition, see http://www.unicode.org/reports/tr15/), filter
+/// out the combining marks, and transliterate the letters which can't be
+/// decomposed (see [`transliterate`]).
 fn normalize_string(str: &str) -> String {
-    str.nfd().filter(|c| !is_combining_mark(*c)).collect::<String>()
+    let mut normalized = String::with_capacity(str.len());
+
+    for c in str.nfd().filter(|c| !is_combining_mark(*c)) {
+        match transliterate(c) {
+            Some(transliteration) => normalized.push_str(transliteration),
+            None => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+/// Transliterate the Latin letters which aren't decomposed into a base letter
+/// and combining marks by the Unicode normalization, e.g. `ø` or `ß`, so that
+/// they can be matched by the letters people type instead.
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'ß' => "ss",
+        'ẞ' => "SS",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ø' => "o",
+        'Ø' => "O",
+        'ł' => "l",
+        'Ł' => "L",
+        'đ' | 'ð' => "d",
+        'Đ' | 'Ð' => "D",
+        'ħ' => "h",
+        'Ħ' => "H",
+        'ı' => "i",
+        'þ' => "th",
+        'Þ' => "TH",
+        _ => return None,
+    })
 }
 
 #[cfg(test)]
@@ -153,5 +196,7 @@ mod tests {
         assert_eq!(&normalize_string("Ștefan Été"), "Stefan Ete");
         assert_eq!(&normalize_string("Ç ṩ ḋ Å"), "C s d A");
         assert_eq!(&normalize_string("هند"), "هند");
+        assert_eq!(&normalize_string("Straße Øresund Łódź"), "Strasse Oresund Lodz");
+        assert_eq!(&normalize_string("Æther Þór"), "AEther THor");
     }
 }
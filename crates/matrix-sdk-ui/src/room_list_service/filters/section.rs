@@ -0,0 +1,137 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk_base::RoomState;
+
+use super::{super::RoomListItem, Filter};
+
+/// The sections a room list can be partitioned into, to render the rooms in
+/// groups.
+///
+/// Each room belongs to exactly one section: the first one of this list which
+/// applies to it.
+///
+/// 1. [`Self::Invites`], for the rooms the user has been invited to,
+/// 2. [`Self::Knocks`], for the rooms the user has knocked on,
+/// 3. [`Self::Favourites`], for the rooms marked as favourite,
+/// 4. [`Self::LowPriority`], for the rooms marked as low priority,
+/// 5. [`Self::People`], for the direct rooms with another user (see
+///    [`RoomCategory::People`](super::RoomCategory::People)),
+/// 6. [`Self::Rooms`], for all the other rooms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoomListSection {
+    /// The rooms the user has been invited to.
+    Invites,
+    /// The rooms the user has knocked on, waiting for their knock to be
+    /// accepted.
+    Knocks,
+    /// The rooms marked as favourite.
+    Favourites,
+    /// The rooms marked as low priority.
+    LowPriority,
+    /// The direct rooms with another user.
+    People,
+    /// All the other rooms.
+    Rooms,
+}
+
+impl RoomListSection {
+    /// The section the given room belongs to.
+    pub fn of(room: &RoomListItem) -> Self {
+        Self::from_parts(
+            room.cached_state,
+            room.is_favourite(),
+            room.is_low_priority(),
+            room.direct_targets_length(),
+        )
+    }
+
+    fn from_parts(
+        state: RoomState,
+        is_favourite: bool,
+        is_low_priority: bool,
+        number_of_direct_targets: usize,
+    ) -> Self {
+        if state == RoomState::Invited {
+            Self::Invites
+        } else if state == RoomState::Knocked {
+            Self::Knocks
+        } else if is_favourite {
+            Self::Favourites
+        } else if is_low_priority {
+            Self::LowPriority
+        } else if number_of_direct_targets == 1 {
+            Self::People
+        } else {
+            Self::Rooms
+        }
+    }
+}
+
+/// Create a new filter that will filter out rooms that don't belong to
+/// `expected_section`, see [`RoomListSection`] to learn more.
+pub fn new_filter(expected_section: RoomListSection) -> impl Filter {
+    move |room| -> bool { RoomListSection::of(room) == expected_section }
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk_base::RoomState;
+
+    use super::RoomListSection;
+
+    #[test]
+    fn test_sections_are_exclusive() {
+        // Invites come first, even when they are tagged.
+        assert_eq!(
+            RoomListSection::from_parts(RoomState::Invited, true, false, 1),
+            RoomListSection::Invites
+        );
+
+        // Then the knocks, even when they are tagged.
+        assert_eq!(
+            RoomListSection::from_parts(RoomState::Knocked, true, false, 1),
+            RoomListSection::Knocks
+        );
+
+        // Then the favourites, even when they are direct rooms.
+        assert_eq!(
+            RoomListSection::from_parts(RoomState::Joined, true, true, 1),
+            RoomListSection::Favourites
+        );
+
+        // Then the low priority rooms.
+        assert_eq!(
+            RoomListSection::from_parts(RoomState::Joined, false, true, 1),
+            RoomListSection::LowPriority
+        );
+
+        // Then the direct rooms with another user.
+        assert_eq!(
+            RoomListSection::from_parts(RoomState::Joined, false, false, 1),
+            RoomListSection::People
+        );
+
+        // And finally all the other rooms, including the direct rooms with several
+        // users.
+        assert_eq!(
+            RoomListSection::from_parts(RoomState::Joined, false, false, 2),
+            RoomListSection::Rooms
+        );
+        assert_eq!(
+            RoomListSection::from_parts(RoomState::Joined, false, false, 0),
+            RoomListSection::Rooms
+        );
+    }
+}
@@ -0,0 +1,505 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{cmp::Ordering, collections::BTreeSet, future::Future};
+
+use async_stream::stream;
+use eyeball_im::{Vector, VectorDiff};
+use futures_util::Stream;
+use matrix_sdk::{Client, Room, RoomState, room::RoomMember};
+use matrix_sdk_base::sync::RoomUpdates;
+use ruma::{MilliSecondsSinceUnixEpoch, OwnedMxcUri, OwnedUserId, RoomId};
+use tracing::warn;
+
+use crate::room_updates::{RelevantRoomUpdates, next_relevant_room_updates};
+
+/// The profile of the user who sent an invite.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InviterProfile {
+    /// The user ID of the inviter.
+    pub user_id: OwnedUserId,
+    /// The display name of the inviter, if any.
+    pub display_name: Option<String>,
+    /// The avatar URL of the inviter, if any.
+    pub avatar_url: Option<OwnedMxcUri>,
+}
+
+impl From<&RoomMember> for InviterProfile {
+    fn from(member: &RoomMember) -> Self {
+        Self {
+            user_id: member.user_id().to_owned(),
+            display_name: member.display_name().map(ToOwned::to_owned),
+            avatar_url: member.avatar_url().map(ToOwned::to_owned),
+        }
+    }
+}
+
+/// A room the user has been invited to, see
+/// [`RoomListService::subscribe_to_invites`](super::RoomListService::subscribe_to_invites).
+#[derive(Clone, Debug)]
+pub struct RoomListInvite {
+    room: Room,
+    inviter: Option<InviterProfile>,
+}
+
+impl RoomListInvite {
+    /// The room the user has been invited to.
+    pub fn room(&self) -> &Room {
+        &self.room
+    }
+
+    /// The profile of the user who sent the invite, if known.
+    pub fn inviter(&self) -> Option<&InviterProfile> {
+        self.inviter.as_ref()
+    }
+
+    async fn new(room: Room) -> Self {
+        let inviter = match room.invite_details().await {
+            Ok(invite) => invite.inviter.as_ref().map(InviterProfile::from),
+            Err(error) => {
+                warn!(?error, room_id = ?room.room_id(), "Failed to load the details of an invite");
+                None
+            }
+        };
+
+        Self { room, inviter }
+    }
+}
+
+impl PartialEq for RoomListInvite {
+    fn eq(&self, other: &Self) -> bool {
+        self.room.room_id() == other.room.room_id() && self.inviter == other.inviter
+    }
+}
+
+/// A room the user has knocked on, see
+/// [`RoomListService::subscribe_to_knocks`](super::RoomListService::subscribe_to_knocks).
+#[derive(Clone, Debug)]
+pub struct RoomListKnock {
+    room: Room,
+    reason: Option<String>,
+    knocked_at: Option<MilliSecondsSinceUnixEpoch>,
+}
+
+impl RoomListKnock {
+    /// The room the user has knocked on.
+    pub fn room(&self) -> &Room {
+        &self.room
+    }
+
+    /// The reason the user gave when knocking, if any.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// When the user knocked, if known.
+    pub fn knocked_at(&self) -> Option<MilliSecondsSinceUnixEpoch> {
+        self.knocked_at
+    }
+
+    async fn new(room: Room) -> Self {
+        let own_member = match room.get_member_no_sync(room.own_user_id()).await {
+            Ok(own_member) => own_member,
+            Err(error) => {
+                warn!(?error, room_id = ?room.room_id(), "Failed to load the details of a knock");
+                None
+            }
+        };
+
+        let (reason, knocked_at) = own_member
+            .map(|member| {
+                let event = member.event();
+                (
+                    event.reason().map(ToOwned::to_owned),
+                    event.timestamp().map(MilliSecondsSinceUnixEpoch),
+                )
+            })
+            .unwrap_or_default();
+
+        Self { room, reason, knocked_at }
+    }
+}
+
+impl PartialEq for RoomListKnock {
+    fn eq(&self, other: &Self) -> bool {
+        self.room.room_id() == other.room.room_id()
+            && self.reason == other.reason
+            && self.knocked_at == other.knocked_at
+    }
+}
+
+/// A value of [`observe_rooms`], describing one room.
+pub(super) trait ObservedRoom: Clone + PartialEq {
+    /// The room described by this value.
+    fn room(&self) -> &Room;
+
+    /// The order in which the values are kept.
+    fn compare(&self, other: &Self) -> Ordering;
+}
+
+impl ObservedRoom for RoomListInvite {
+    fn room(&self) -> &Room {
+        &self.room
+    }
+
+    /// The invites are sorted by room ID.
+    fn compare(&self, other: &Self) -> Ordering {
+        self.room.room_id().cmp(other.room.room_id())
+    }
+}
+
+impl ObservedRoom for RoomListKnock {
+    fn room(&self) -> &Room {
+        &self.room
+    }
+
+    /// The most recent knocks come first.
+    fn compare(&self, other: &Self) -> Ordering {
+        other
+            .knocked_at
+            .cmp(&self.knocked_at)
+            .then_with(|| self.room.room_id().cmp(other.room.room_id()))
+    }
+}
+
+/// Observe the rooms the user has been invited to, see [`observe_rooms`].
+pub(super) fn observe_invites(
+    client: Client,
+) -> impl Stream<Item = Vec<VectorDiff<RoomListInvite>>> {
+    observe_rooms(client, RoomState::Invited, RoomListInvite::new)
+}
+
+/// Observe the rooms the user has knocked on, see [`observe_rooms`].
+pub(super) fn observe_knocks(client: Client) -> impl Stream<Item = Vec<VectorDiff<RoomListKnock>>> {
+    observe_rooms(client, RoomState::Knocked, RoomListKnock::new)
+}
+
+/// The values computed by `value_for` from all the rooms of the client in the
+/// given `state`, sorted.
+async fn values_for<T, F, Fut>(client: &Client, state: RoomState, value_for: &F) -> Vector<T>
+where
+    T: ObservedRoom,
+    F: Fn(Room) -> Fut,
+    Fut: Future<Output = T>,
+{
+    let mut values = Vec::new();
+
+    for room in client.rooms().into_iter().filter(|room| room.state() == state) {
+        values.push(value_for(room).await);
+    }
+
+    values.sort_by(T::compare);
+    values.into()
+}
+
+/// Observe the values computed by `value_for` from the rooms of the client in
+/// the given `state`.
+///
+/// The stream yields a [`VectorDiff::Reset`] with the initial values first,
+/// and then the diffs of the values every time a room update changes them.
+/// Only the values of the rooms touched by an update, which are or were in
+/// the given `state`, are computed again, unless some updates have been
+/// missed.
+fn observe_rooms<T, F, Fut>(
+    client: Client,
+    state: RoomState,
+    value_for: F,
+) -> impl Stream<Item = Vec<VectorDiff<T>>>
+where
+    T: ObservedRoom,
+    F: Fn(Room) -> Fut,
+    Fut: Future<Output = T>,
+{
+    stream! {
+        // Subscribe before computing the initial values, to not miss any update.
+        let mut all_room_updates_receiver = client.subscribe_to_all_room_updates();
+
+        let mut values = values_for(&client, state, &value_for).await;
+        yield vec![VectorDiff::Reset { values: values.clone() }];
+
+        while let Some(updates) =
+            next_relevant_room_updates(&mut all_room_updates_receiver, |updates| {
+                touches_observed_rooms(&client, updates, state, &values)
+            })
+            .await
+        {
+            let new_values = match updates {
+                RelevantRoomUpdates::Updates(updates) => {
+                    let room_ids =
+                        updates.iter_all_room_ids().map(|room_id| &**room_id).collect::<BTreeSet<_>>();
+
+                    // Keep the values of the rooms which haven't been updated, and compute the
+                    // ones of the updated rooms which are in the state.
+                    let mut new_values = values
+                        .iter()
+                        .filter(|value| !room_ids.contains(value.room().room_id()))
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    for room_id in room_ids {
+                        if let Some(room) = client.get_room(room_id)
+                            && room.state() == state
+                        {
+                            new_values.push(value_for(room).await);
+                        }
+                    }
+
+                    new_values.sort_by(T::compare);
+                    new_values.into()
+                }
+
+                RelevantRoomUpdates::Lagged => values_for(&client, state, &value_for).await,
+            };
+
+            let diffs = diff_values(&values, &new_values);
+
+            if !diffs.is_empty() {
+                values = new_values;
+                yield diffs;
+            }
+        }
+    }
+}
+
+/// Whether the updates touch a room which is in the given `state`, or which
+/// is one of the current `values`, i.e. which was in this state.
+fn touches_observed_rooms<T: ObservedRoom>(
+    client: &Client,
+    updates: &RoomUpdates,
+    state: RoomState,
+    values: &Vector<T>,
+) -> bool {
+    updates.iter_all_room_ids().any(|room_id| {
+        values.iter().any(|value| value.room().room_id() == room_id)
+            || client.get_room(room_id).is_some_and(|room| room.state() == state)
+    })
+}
+
+/// Compute the diffs turning `old` into `new`, matching the values by room.
+fn diff_values<T: ObservedRoom>(old: &Vector<T>, new: &Vector<T>) -> Vec<VectorDiff<T>> {
+    let same_room = |left: &T, right: &RoomId| left.room().room_id() == right;
+
+    let mut current = old.clone();
+    let mut diffs = Vec::new();
+
+    // Remove the rooms which are gone, from the last one so the indices stay valid.
+    for index in (0..current.len()).rev() {
+        let room_id = current[index].room().room_id();
+
+        if !new.iter().any(|value| same_room(value, room_id)) {
+            current.remove(index);
+            diffs.push(VectorDiff::Remove { index });
+        }
+    }
+
+    // Then, all the rooms left in `current` are in `new`: move, update or insert
+    // them at their new position.
+    for (index, value) in new.iter().enumerate() {
+        let room_id = value.room().room_id();
+
+        match current.iter().skip(index).position(|current| same_room(current, room_id)) {
+            Some(0) => {
+                if current[index] != *value {
+                    current.set(index, value.clone());
+                    diffs.push(VectorDiff::Set { index, value: value.clone() });
+                }
+            }
+
+            Some(offset) => {
+                current.remove(index + offset);
+                diffs.push(VectorDiff::Remove { index: index + offset });
+                current.insert(index, value.clone());
+                diffs.push(VectorDiff::Insert { index, value: value.clone() });
+            }
+
+            None => {
+                current.insert(index, value.clone());
+                diffs.push(VectorDiff::Insert { index, value: value.clone() });
+            }
+        }
+    }
+
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use eyeball_im::{Vector, VectorDiff};
+    use futures_util::{FutureExt as _, StreamExt as _, pin_mut};
+    use matrix_sdk::{Room, RoomState, test_utils::mocks::MatrixMockServer};
+    use matrix_sdk_test::{
+        InvitedRoomBuilder, KnockedRoomBuilder, StrippedStateTestEvent, async_test,
+        event_factory::EventFactory,
+    };
+    use ruma::{events::AnyStrippedStateEvent, room_id, user_id};
+    use serde_json::json;
+
+    use super::{
+        InviterProfile, RoomListInvite, RoomListKnock, diff_values, observe_invites, values_for,
+    };
+
+    #[async_test]
+    async fn test_invites() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+        let f = EventFactory::new();
+
+        let own_user_id = client.user_id().unwrap();
+        let inviter = user_id!("@alice:b.c");
+        let room_id = room_id!("!invite:b.c");
+
+        let stream = observe_invites(client.clone());
+        pin_mut!(stream);
+
+        // There is no invite at first.
+        assert_matches!(stream.next().await, Some(diffs) => {
+            assert_matches!(&diffs[..], [VectorDiff::Reset { values }] => {
+                assert!(values.is_empty());
+            });
+        });
+
+        server
+            .sync_room(
+                &client,
+                InvitedRoomBuilder::new(room_id)
+                    .add_state_event(
+                        f.member(inviter).invited(own_user_id).into_raw::<AnyStrippedStateEvent>(),
+                    )
+                    .add_state_event(
+                        f.member(inviter).display_name("Alice").into_raw::<AnyStrippedStateEvent>(),
+                    ),
+            )
+            .await;
+
+        // The invite is received, with its inviter.
+        assert_matches!(stream.next().await, Some(diffs) => {
+            assert_matches!(&diffs[..], [VectorDiff::Insert { index: 0, value }] => {
+                assert_eq!(value.room().room_id(), room_id);
+                assert_eq!(
+                    value.inviter(),
+                    Some(&InviterProfile {
+                        user_id: inviter.to_owned(),
+                        display_name: Some("Alice".to_owned()),
+                        avatar_url: None,
+                    })
+                );
+            });
+        });
+
+        assert!(stream.next().now_or_never().is_none());
+
+        // An update of a room which has never been invited doesn't touch the invites.
+        server.sync_joined_room(&client, room_id!("!joined:b.c")).await;
+        assert!(stream.next().now_or_never().is_none());
+
+        // Once the invite is accepted, it is removed.
+        server.sync_joined_room(&client, room_id).await;
+        assert_matches!(stream.next().await, Some(diffs) => {
+            assert_matches!(&diffs[..], [VectorDiff::Remove { index: 0 }]);
+        });
+
+        // The knocks aren't affected.
+        assert!(values_for(&client, RoomState::Knocked, &RoomListKnock::new).await.is_empty());
+    }
+
+    #[async_test]
+    async fn test_diff_values() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+
+        let room_a = server.sync_joined_room(&client, room_id!("!a:b.c")).await;
+        let room_b = server.sync_joined_room(&client, room_id!("!b:b.c")).await;
+        let room_c = server.sync_joined_room(&client, room_id!("!c:b.c")).await;
+
+        let invite = |room: &Room, display_name: Option<&str>| RoomListInvite {
+            room: room.clone(),
+            inviter: Some(InviterProfile {
+                user_id: user_id!("@alice:b.c").to_owned(),
+                display_name: display_name.map(ToOwned::to_owned),
+                avatar_url: None,
+            }),
+        };
+
+        let old = Vector::from(vec![invite(&room_a, None), invite(&room_b, None)]);
+
+        // Nothing changed.
+        assert!(diff_values(&old, &old).is_empty());
+
+        // `room_a` is removed, `room_b` is updated, and `room_c` is inserted.
+        let new = Vector::from(vec![invite(&room_b, Some("Alice")), invite(&room_c, None)]);
+        let diffs = diff_values(&old, &new);
+        assert_matches!(
+            &diffs[..],
+            [
+                VectorDiff::Remove { index: 0 },
+                VectorDiff::Set { index: 0, value: updated },
+                VectorDiff::Insert { index: 1, value: inserted },
+            ] => {
+                assert_eq!(updated.room().room_id(), room_b.room_id());
+                assert_eq!(inserted.room().room_id(), room_c.room_id());
+            }
+        );
+
+        // A room which moves is removed and inserted again.
+        let new = Vector::from(vec![invite(&room_b, None), invite(&room_a, None)]);
+        let diffs = diff_values(&old, &new);
+        assert_matches!(
+            &diffs[..],
+            [VectorDiff::Remove { index: 1 }, VectorDiff::Insert { index: 0, value }] => {
+                assert_eq!(value.room().room_id(), room_b.room_id());
+            }
+        );
+
+        // Applying the diffs gives the new values.
+        let mut values = old.clone();
+        for diff in diffs {
+            diff.apply(&mut values);
+        }
+        assert_eq!(values, new);
+    }
+
+    #[async_test]
+    async fn test_knocks() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+        let own_user_id = client.user_id().unwrap();
+        let room_id = room_id!("!knock:b.c");
+
+        server
+            .sync_room(
+                &client,
+                KnockedRoomBuilder::new(room_id).add_state_event(StrippedStateTestEvent::Custom(
+                    json!({
+                        "content": {
+                            "membership": "knock",
+                            "reason": "Hi!",
+                        },
+                        "sender": own_user_id,
+                        "state_key": own_user_id,
+                        "type": "m.room.member",
+                    }),
+                )),
+            )
+            .await;
+
+        let knocks = values_for(&client, RoomState::Knocked, &RoomListKnock::new).await;
+        assert_eq!(knocks.len(), 1);
+        assert_eq!(knocks[0].room().room_id(), room_id);
+        assert_eq!(knocks[0].reason(), Some("Hi!"));
+
+        // The invites aren't affected.
+        assert!(values_for(&client, RoomState::Invited, &RoomListInvite::new).await.is_empty());
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::{
+    Room, RoomHero,
+    latest_events::{LatestEventValue, LocalLatestEventValue},
+};
+use ruma::{
+    OwnedUserId, UserId,
+    events::{
+        AnyMessageLikeEventContent, AnySyncMessageLikeEvent, AnySyncTimelineEvent,
+        poll::unstable_start::SyncUnstablePollStartEvent, room::message::SyncRoomMessageEvent,
+        sticker::SyncStickerEvent,
+    },
+    html::RemoveReplyFallback,
+};
+use tracing::warn;
+
+use crate::DEFAULT_SANITIZER_MODE;
+
+/// The maximum number of characters of the body of a [`LatestEventPreview`].
+const MAX_BODY_LENGTH: usize = 256;
+
+/// A preview of the latest event of a room, to be displayed in a room list.
+///
+/// Contrary to [`LatestEventValue`](crate::timeline::LatestEventValue), it
+/// doesn't require a [`Timeline`](crate::Timeline) nor any asynchronous
+/// lookup: it's computed from the latest event of the room, which is kept up
+/// to date by the event cache for the rooms listened to by
+/// [`LatestEvents`](matrix_sdk::latest_events::LatestEvents), e.g. with
+/// [`RoomListService::subscribe_to_rooms`](super::RoomListService::subscribe_to_rooms).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatestEventPreview {
+    sender: OwnedUserId,
+    sender_display_name: Option<String>,
+    is_own: bool,
+    is_local: bool,
+    content: LatestEventPreviewContent,
+}
+
+/// The content of a [`LatestEventPreview`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LatestEventPreviewContent {
+    /// The body of the event, sanitized and on a single line: the reply
+    /// fallback is removed, the whitespaces are collapsed, and it's truncated
+    /// if it's too long.
+    Text(String),
+
+    /// The event is encrypted and couldn't be decrypted, yet.
+    UnableToDecrypt,
+}
+
+/// The sender of the latest event of a room, with their display name from the
+/// members of the room.
+#[derive(Clone, Debug)]
+pub(super) struct LatestEventSender {
+    user_id: OwnedUserId,
+    display_name: Option<String>,
+}
+
+impl LatestEventSender {
+    /// Load the sender of the latest event of `room`, and their display name
+    /// from the members of the room which are in the store, without any
+    /// request to the server.
+    ///
+    /// Returns `None` if there is no latest event.
+    pub(super) async fn load(room: &Room) -> Option<Self> {
+        let user_id = match room.new_latest_event() {
+            LatestEventValue::None => return None,
+            LatestEventValue::Remote(timeline_event) => {
+                timeline_event.raw().get_field::<OwnedUserId>("sender").ok()??
+            }
+            LatestEventValue::LocalIsSending(_) | LatestEventValue::LocalCannotBeSent(_) => {
+                room.own_user_id().to_owned()
+            }
+        };
+
+        let display_name = match room.get_member_no_sync(&user_id).await {
+            Ok(member) => member.and_then(|member| member.display_name().map(ToOwned::to_owned)),
+            Err(error) => {
+                warn!(room_id = %room.room_id(), "Failed to load the sender of the latest event: {error}");
+                None
+            }
+        };
+
+        Some(Self { user_id, display_name })
+    }
+}
+
+impl LatestEventPreview {
+    /// Compute the preview of the given latest event value.
+    ///
+    /// The display name of the sender is taken from `latest_event_sender` if
+    /// it's the sender of this event, otherwise from the `heroes` of the room.
+    ///
+    /// Returns `None` if there is no latest event, or if it can't be
+    /// previewed.
+    pub(super) fn from_latest_event_value(
+        value: &LatestEventValue,
+        own_user_id: &UserId,
+        latest_event_sender: Option<&LatestEventSender>,
+        heroes: &[RoomHero],
+    ) -> Option<Self> {
+        let (sender, is_local, content) = match value {
+            LatestEventValue::None => return None,
+
+            LatestEventValue::Remote(timeline_event) if timeline_event.kind.is_utd() => {
+                let sender = timeline_event.raw().get_field::<OwnedUserId>("sender").ok()??;
+
+                (sender, false, LatestEventPreviewContent::UnableToDecrypt)
+            }
+
+            LatestEventValue::Remote(timeline_event) => {
+                let event = timeline_event.raw().deserialize().ok()?;
+                let body = body_of_event(&event)?;
+
+                (event.sender().to_owned(), false, LatestEventPreviewContent::Text(body))
+            }
+
+            LatestEventValue::LocalIsSending(LocalLatestEventValue { content, .. })
+            | LatestEventValue::LocalCannotBeSent(LocalLatestEventValue { content, .. }) => {
+                let body = body_of_content(content.deserialize().ok()?)?;
+
+                (own_user_id.to_owned(), true, LatestEventPreviewContent::Text(body))
+            }
+        };
+
+        let is_own = sender == own_user_id;
+        let sender_display_name = latest_event_sender
+            .filter(|latest_event_sender| latest_event_sender.user_id == sender)
+            .and_then(|latest_event_sender| latest_event_sender.display_name.clone())
+            .or_else(|| {
+                heroes
+                    .iter()
+                    .find(|hero| hero.user_id == sender)
+                    .and_then(|hero| hero.display_name.clone())
+            });
+
+        Some(Self { sender, sender_display_name, is_own, is_local, content })
+    }
+
+    /// The sender of the event.
+    pub fn sender(&self) -> &UserId {
+        &self.sender
+    }
+
+    /// The display name of the sender, if known from the members of the room
+    /// which are in the store, or from the heroes of the room.
+    pub fn sender_display_name(&self) -> Option<&str> {
+        self.sender_display_name.as_deref()
+    }
+
+    /// Whether the event has been sent by the current user.
+    pub fn is_own(&self) -> bool {
+        self.is_own
+    }
+
+    /// Whether the event is a local event, which hasn't been sent yet.
+    pub fn is_local(&self) -> bool {
+        self.is_local
+    }
+
+    /// The content of the event.
+    pub fn content(&self) -> &LatestEventPreviewContent {
+        &self.content
+    }
+}
+
+/// The body of a remote event, if it's a kind of event which can be
+/// previewed.
+fn body_of_event(event: &AnySyncTimelineEvent) -> Option<String> {
+    let AnySyncTimelineEvent::MessageLike(event) = event else { return None };
+
+    let body = match event {
+        AnySyncMessageLikeEvent::RoomMessage(SyncRoomMessageEvent::Original(event)) => {
+            let mut msgtype = event.content.msgtype.clone();
+            msgtype.sanitize(DEFAULT_SANITIZER_MODE, RemoveReplyFallback::Yes);
+            msgtype.body().to_owned()
+        }
+        AnySyncMessageLikeEvent::Sticker(SyncStickerEvent::Original(event)) => {
+            event.content.body.clone()
+        }
+        AnySyncMessageLikeEvent::UnstablePollStart(SyncUnstablePollStartEvent::Original(event)) => {
+            event.content.poll_start().question.text.clone()
+        }
+        _ => return None,
+    };
+
+    Some(to_one_line(&body))
+}
+
+/// The body of the content of a local event, if it's a kind of event which can
+/// be previewed.
+fn body_of_content(content: AnyMessageLikeEventContent) -> Option<String> {
+    let body = match content {
+        // Local events don't contain a reply fallback.
+        AnyMessageLikeEventContent::RoomMessage(content) => content.msgtype.body().to_owned(),
+        AnyMessageLikeEventContent::Sticker(content) => content.body,
+        AnyMessageLikeEventContent::UnstablePollStart(content) => {
+            content.poll_start().question.text.clone()
+        }
+        _ => return None,
+    };
+
+    Some(to_one_line(&body))
+}
+
+/// Collapse the whitespaces of `body`, including the line breaks, and truncate
+/// it to [`MAX_BODY_LENGTH`] characters.
+fn to_one_line(body: &str) -> String {
+    let mut line = String::with_capacity(body.len().min(MAX_BODY_LENGTH));
+
+    for word in body.split_whitespace() {
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.push_str(word);
+
+        if line.chars().count() > MAX_BODY_LENGTH {
+            line = line.chars().take(MAX_BODY_LENGTH - 1).collect();
+            line.push('…');
+            break;
+        }
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk::{
+        RoomHero,
+        deserialized_responses::{TimelineEvent, UnableToDecryptInfo, UnableToDecryptReason},
+        latest_events::{LatestEventValue, LocalLatestEventValue},
+        store::SerializableEventContent,
+    };
+    use matrix_sdk_test::{ALICE, BOB, event_factory::EventFactory};
+    use ruma::{
+        MilliSecondsSinceUnixEpoch, event_id,
+        events::{AnyMessageLikeEventContent, room::message::RoomMessageEventContent},
+        serde::Raw,
+        uint,
+    };
+
+    use super::{
+        LatestEventPreview, LatestEventPreviewContent, LatestEventSender, MAX_BODY_LENGTH,
+        to_one_line,
+    };
+
+    #[test]
+    fn test_to_one_line() {
+        assert_eq!(to_one_line("  hello\n\n  world \t!  "), "hello world !");
+
+        let long_body = "raclette ".repeat(100);
+        let line = to_one_line(&long_body);
+        assert_eq!(line.chars().count(), MAX_BODY_LENGTH);
+        assert!(line.starts_with("raclette raclette"));
+        assert!(line.ends_with('…'));
+    }
+
+    #[test]
+    fn test_remote_message() {
+        let f = EventFactory::new();
+        let heroes = [RoomHero {
+            user_id: BOB.to_owned(),
+            display_name: Some("Bob".to_owned()),
+            avatar_url: None,
+        }];
+
+        let value = LatestEventValue::Remote(
+            f.text_msg("> <@alice:server.name> hi\n\nhello\nthere")
+                .sender(&BOB)
+                .reply_to(event_id!("$replied"))
+                .into_event(),
+        );
+
+        let preview =
+            LatestEventPreview::from_latest_event_value(&value, &ALICE, None, &heroes).unwrap();
+        assert_eq!(preview.sender(), *BOB);
+        assert_eq!(preview.sender_display_name(), Some("Bob"));
+        assert!(!preview.is_own());
+        assert!(!preview.is_local());
+        assert_eq!(preview.content(), &LatestEventPreviewContent::Text("hello there".to_owned()));
+
+        // The display name of the senders which aren't heroes is unknown.
+        let preview =
+            LatestEventPreview::from_latest_event_value(&value, &ALICE, None, &[]).unwrap();
+        assert_eq!(preview.sender_display_name(), None);
+
+        // Unless it's known from the members of the room.
+        let latest_event_sender =
+            LatestEventSender { user_id: BOB.to_owned(), display_name: Some("Bobby".to_owned()) };
+        let preview = LatestEventPreview::from_latest_event_value(
+            &value,
+            &ALICE,
+            Some(&latest_event_sender),
+            &heroes,
+        )
+        .unwrap();
+        assert_eq!(preview.sender_display_name(), Some("Bobby"));
+
+        // The known sender is ignored if it's not the sender of the event.
+        let latest_event_sender =
+            LatestEventSender { user_id: ALICE.to_owned(), display_name: Some("Alice".to_owned()) };
+        let preview = LatestEventPreview::from_latest_event_value(
+            &value,
+            &ALICE,
+            Some(&latest_event_sender),
+            &heroes,
+        )
+        .unwrap();
+        assert_eq!(preview.sender_display_name(), Some("Bob"));
+    }
+
+    #[test]
+    fn test_remote_utd() {
+        let f = EventFactory::new();
+
+        let value = LatestEventValue::Remote(TimelineEvent::from_utd(
+            f.text_msg("secret").sender(&BOB).into_raw_sync(),
+            UnableToDecryptInfo {
+                session_id: None,
+                reason: UnableToDecryptReason::MissingMegolmSession { withheld_code: None },
+            },
+        ));
+
+        let preview =
+            LatestEventPreview::from_latest_event_value(&value, &ALICE, None, &[]).unwrap();
+        assert_eq!(preview.sender(), *BOB);
+        assert_eq!(preview.content(), &LatestEventPreviewContent::UnableToDecrypt);
+    }
+
+    #[test]
+    fn test_local_message() {
+        let value = LatestEventValue::LocalIsSending(LocalLatestEventValue {
+            timestamp: MilliSecondsSinceUnixEpoch(uint!(42)),
+            content: SerializableEventContent::from_raw(
+                Raw::new(&AnyMessageLikeEventContent::RoomMessage(
+                    RoomMessageEventContent::text_plain("raclette"),
+                ))
+                .unwrap(),
+                "m.room.message".to_owned(),
+            ),
+        });
+
+        let preview =
+            LatestEventPreview::from_latest_event_value(&value, &ALICE, None, &[]).unwrap();
+        assert_eq!(preview.sender(), *ALICE);
+        assert!(preview.is_own());
+        assert!(preview.is_local());
+        assert_eq!(preview.content(), &LatestEventPreviewContent::Text("raclette".to_owned()));
+    }
+
+    #[test]
+    fn test_no_preview() {
+        let f = EventFactory::new();
+
+        assert!(
+            LatestEventPreview::from_latest_event_value(&LatestEventValue::None, &ALICE, None, &[])
+                .is_none()
+        );
+
+        let value = LatestEventValue::Remote(f.reaction(event_id!("$ev"), "👍").into_event());
+        assert!(LatestEventPreview::from_latest_event_value(&value, &ALICE, None, &[]).is_none());
+    }
+}
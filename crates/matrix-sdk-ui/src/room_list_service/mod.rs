@@ -52,15 +52,21 @@
 //! machine's state, which can be pretty helpful for the client app.
 
 pub mod filters;
+mod invites;
+mod latest_event_preview;
 mod room_list;
+mod settings;
 pub mod sorters;
 mod state;
 
 use std::{sync::Arc, time::Duration};
 
 use async_stream::stream;
-use eyeball::Subscriber;
+use eyeball::{SharedObservable, Subscriber};
+use eyeball_im::VectorDiff;
 use futures_util::{Stream, StreamExt, pin_mut};
+pub use invites::{InviterProfile, RoomListInvite, RoomListKnock};
+pub use latest_event_preview::{LatestEventPreview, LatestEventPreviewContent};
 use matrix_sdk::{
     Client, Error as SlidingSyncError, Room, SlidingSync, SlidingSyncList, SlidingSyncMode,
     event_cache::EventCacheError, timeout::timeout,
@@ -72,6 +78,7 @@ use ruma::{
     assign,
     events::StateEventType,
 };
+pub use settings::{InvalidSettingsError, MAX_TIMELINE_LIMIT, RoomListServiceSettings};
 pub use state::*;
 use thiserror::Error;
 use tracing::{debug, error, warn};
@@ -123,6 +130,14 @@ pub struct RoomListService {
     ///
     /// `RoomListService` is a simple state-machine.
     state_machine: StateMachine,
+
+    /// The settings of the sliding sync.
+    settings: RoomListServiceSettings,
+
+    /// The number of rooms known by the client after the last successful
+    /// `/sync`, when the [`SyncService`][crate::SyncService] has fallen back
+    /// to sync v2, since there's no sliding sync list to count them.
+    sync_v2_number_of_rooms: SharedObservable<Option<u32>>,
 }
 
 impl RoomListService {
@@ -143,6 +158,21 @@ impl RoomListService {
     ///
     /// [`SlidingSyncBuilder::share_pos`]: matrix_sdk::sliding_sync::SlidingSyncBuilder::share_pos
     pub async fn new_with_share_pos(client: Client, share_pos: bool) -> Result<Self, Error> {
+        Self::new_with_settings(client, share_pos, RoomListServiceSettings::default()).await
+    }
+
+    /// Like [`RoomListService::new_with_share_pos`] but with custom
+    /// [`RoomListServiceSettings`], to customize the required state and the
+    /// timeline limits of the sliding sync.
+    ///
+    /// Returns [`Error::InvalidSettings`] if the settings aren't valid.
+    pub async fn new_with_settings(
+        client: Client,
+        share_pos: bool,
+        settings: RoomListServiceSettings,
+    ) -> Result<Self, Error> {
+        settings.validate()?;
+
         let mut builder = client
             .sliding_sync("room-list")
             .map_err(Error::SlidingSync)?
@@ -191,13 +221,8 @@ impl RoomListService {
                         SlidingSyncMode::new_selective()
                             .add_range(ALL_ROOMS_DEFAULT_SELECTIVE_RANGE),
                     )
-                    .timeline_limit(1)
-                    .required_state(
-                        DEFAULT_REQUIRED_STATE
-                            .iter()
-                            .map(|(state_event, value)| (state_event.clone(), (*value).to_owned()))
-                            .collect(),
-                    )
+                    .timeline_limit(settings.timeline_limit())
+                    .required_state(settings.required_state().to_vec())
                     .filters(Some(assign!(http::request::ListFilters::default(), {
                         // As defined in the [SlidingSync MSC](https://github.com/matrix-org/matrix-spec-proposals/blob/9450ced7fb9cf5ea9077d029b3adf36aebfa8709/proposals/3575-sync.md?plain=1#L444)
                         // If unset, both invited and joined rooms are returned. If false, no invited rooms are
@@ -225,7 +250,13 @@ impl RoomListService {
             state_machine.set(State::SettingUp);
         }
 
-        Ok(Self { client, sliding_sync, state_machine })
+        Ok(Self {
+            client,
+            sliding_sync,
+            state_machine,
+            settings,
+            sync_v2_number_of_rooms: SharedObservable::new(None),
+        })
     }
 
     /// Start to sync the room list.
@@ -344,6 +375,33 @@ impl RoomListService {
         }
     }
 
+    /// Update the state after a successful `/sync`, when the
+    /// [`SyncService`][crate::SyncService] has fallen back to sync v2.
+    ///
+    /// There is no sliding sync list to grow with sync v2, so the state jumps
+    /// straight to [`State::Running`], and the room lists are loaded with all
+    /// the rooms known by the client.
+    pub(crate) fn set_synced_with_sync_v2(&self) {
+        let number_of_rooms = self.client.rooms().len().try_into().unwrap_or(u32::MAX);
+        self.sync_v2_number_of_rooms.set_if_not_eq(Some(number_of_rooms));
+
+        if !matches!(self.state_machine.get(), State::Running) {
+            self.state_machine.set(State::Running);
+        }
+    }
+
+    /// Update the state after the sync v2 `/sync` loop has terminated, with an
+    /// error or not.
+    pub(crate) fn set_sync_v2_terminated(&self, with_error: bool) {
+        let from = Box::new(self.state_machine.get());
+
+        if with_error {
+            self.state_machine.set(State::Error { from });
+        } else {
+            self.state_machine.set(State::Terminated { from });
+        }
+    }
+
     /// Get a [`Stream`] of [`SyncIndicator`].
     ///
     /// Read the documentation of [`SyncIndicator`] to learn more about it.
@@ -412,7 +470,14 @@ impl RoomListService {
     }
 
     async fn list_for(&self, sliding_sync_list_name: &str) -> Result<RoomList, Error> {
-        RoomList::new(&self.client, &self.sliding_sync, sliding_sync_list_name, self.state()).await
+        RoomList::new(
+            &self.client,
+            &self.sliding_sync,
+            sliding_sync_list_name,
+            self.state(),
+            self.sync_v2_number_of_rooms.subscribe(),
+        )
+        .await
     }
 
     /// Get a [`RoomList`] for all rooms.
@@ -425,6 +490,33 @@ impl RoomListService {
         self.client.get_room(room_id).ok_or_else(|| Error::RoomNotFound(room_id.to_owned()))
     }
 
+    /// Subscribe to the rooms the user has been invited to.
+    ///
+    /// Contrary to filtering a [`RoomList`] with
+    /// [`filters::new_filter_invite`], the invites come with the profile of
+    /// the user who sent them.
+    ///
+    /// The stream yields a [`VectorDiff::Reset`] with the current invites
+    /// first, and then the diffs of the invites every time they change. They
+    /// are sorted by room ID.
+    pub fn subscribe_to_invites(
+        &self,
+    ) -> impl Stream<Item = Vec<VectorDiff<RoomListInvite>>> + use<> {
+        invites::observe_invites(self.client.clone())
+    }
+
+    /// Subscribe to the rooms the user has knocked on, with the details of
+    /// their knocks.
+    ///
+    /// The stream yields a [`VectorDiff::Reset`] with the current knocks first,
+    /// and then the diffs of the knocks every time they change, e.g. when a
+    /// knock has been accepted or denied. The most recent knocks come first.
+    pub fn subscribe_to_knocks(
+        &self,
+    ) -> impl Stream<Item = Vec<VectorDiff<RoomListKnock>>> + use<> {
+        invites::observe_knocks(self.client.clone())
+    }
+
     /// Subscribe to rooms.
     ///
     /// It means that all events from these rooms will be received every time,
@@ -439,16 +531,8 @@ impl RoomListService {
     pub async fn subscribe_to_rooms(&self, room_ids: &[&RoomId]) {
         // Calculate the settings for the room subscriptions.
         let settings = assign!(http::request::RoomSubscription::default(), {
-            required_state: DEFAULT_REQUIRED_STATE.iter().map(|(state_event, value)| {
-                (state_event.clone(), (*value).to_owned())
-            })
-            .chain(
-                DEFAULT_ROOM_SUBSCRIPTION_EXTRA_REQUIRED_STATE.iter().map(|(state_event, value)| {
-                    (state_event.clone(), (*value).to_owned())
-                })
-            )
-            .collect(),
-            timeline_limit: UInt::from(DEFAULT_ROOM_SUBSCRIPTION_TIMELINE_LIMIT),
+            required_state: self.settings.room_subscription_required_state(),
+            timeline_limit: UInt::from(self.settings.room_subscription_timeline_limit()),
         });
 
         // Decide whether the in-flight request (if any) should be cancelled if needed.
@@ -498,6 +582,10 @@ pub enum Error {
 
     #[error(transparent)]
     EventCache(#[from] EventCacheError),
+
+    /// The [`RoomListServiceSettings`] aren't valid.
+    #[error("Invalid room list settings: {0}")]
+    InvalidSettings(#[from] InvalidSettingsError),
 }
 
 /// An hint whether a _sync spinner/loader/toaster_ should be prompted to the
@@ -12,21 +12,27 @@
 // See the License for that specific language governing permissions and
 // limitations under the License.
 
-use std::{future::ready, ops::Deref, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::ready,
+    ops::{Deref, Range},
+    sync::{Arc, OnceLock},
+};
 
 use async_cell::sync::AsyncCell;
 use async_rx::StreamExt as _;
 use async_stream::stream;
 use eyeball::{SharedObservable, Subscriber};
-use eyeball_im::{Vector, VectorDiff};
+use eyeball_im::{ObservableVector, ObservableVectorTransaction, Vector, VectorDiff};
 use eyeball_im_util::vector::VectorObserverExt;
 use futures_util::{Stream, StreamExt as _, pin_mut, stream};
 use matrix_sdk::{
     Client, Room, RoomRecencyStamp, RoomState, SlidingSync, SlidingSyncList,
-    executor::{JoinHandle, spawn},
+    executor::{AbortOnDrop, JoinHandle, spawn},
+    locks::Mutex,
 };
-use matrix_sdk_base::RoomInfoNotableUpdate;
-use ruma::MilliSecondsSinceUnixEpoch;
+use matrix_sdk_base::{RoomInfoNotableUpdate, RoomInfoNotableUpdateReasons};
+use ruma::{MilliSecondsSinceUnixEpoch, OwnedRoomAliasId, OwnedRoomId};
 use tokio::{
     select,
     sync::broadcast::{self, error::RecvError},
@@ -34,9 +40,10 @@ use tokio::{
 use tracing::{error, trace};
 
 use super::{
-    Error, State,
-    filters::BoxedFilterFn,
-    sorters::{new_sorter_lexicographic, new_sorter_name, new_sorter_recency},
+    Error, LatestEventPreview, State,
+    filters::{BoxedFilterFn, RoomListSection},
+    latest_event_preview::LatestEventSender,
+    sorters::{BoxedSorterFn, new_sorter_lexicographic, new_sorter_name, new_sorter_recency},
 };
 use crate::room_list_service::sorters::new_sorter_latest_event;
 
@@ -48,6 +55,7 @@ pub struct RoomList {
     sliding_sync_list: SlidingSyncList,
     loading_state: SharedObservable<RoomListLoadingState>,
     loading_state_task: JoinHandle<()>,
+    latest_event_previews: LatestEventPreviews,
 }
 
 impl Drop for RoomList {
@@ -62,6 +70,7 @@ impl RoomList {
         sliding_sync: &Arc<SlidingSync>,
         sliding_sync_list_name: &str,
         room_list_service_state: Subscriber<State>,
+        mut sync_v2_number_of_rooms: Subscriber<Option<u32>>,
     ) -> Result<Self, Error> {
         let sliding_sync_list = sliding_sync
             .on_list(sliding_sync_list_name, |list| ready(list.clone()))
@@ -80,6 +89,7 @@ impl RoomList {
             client: client.clone(),
             sliding_sync_list: sliding_sync_list.clone(),
             loading_state: loading_state.clone(),
+            latest_event_previews: LatestEventPreviews::default(),
             loading_state_task: spawn(async move {
                 pin_mut!(room_list_service_state);
 
@@ -95,8 +105,11 @@ impl RoomList {
                     }
                 }
 
-                // Let's jump from `NotLoaded` to `Loaded`.
-                let maximum_number_of_rooms = sliding_sync_list.maximum_number_of_rooms();
+                // Let's jump from `NotLoaded` to `Loaded`. With sync v2, there's no
+                // sliding sync list, so the rooms are counted after each `/sync`.
+                let maximum_number_of_rooms = sliding_sync_list
+                    .maximum_number_of_rooms()
+                    .or_else(|| sync_v2_number_of_rooms.get());
 
                 loading_state.set(RoomListLoadingState::Loaded { maximum_number_of_rooms });
 
@@ -104,9 +117,15 @@ impl RoomList {
                 let mut maximum_number_of_rooms_stream =
                     sliding_sync_list.maximum_number_of_rooms_stream();
 
-                while let Some(maximum_number_of_rooms) =
-                    maximum_number_of_rooms_stream.next().await
-                {
+                loop {
+                    let maximum_number_of_rooms = select! {
+                        Some(maximum_number_of_rooms) = maximum_number_of_rooms_stream.next() => {
+                            maximum_number_of_rooms
+                        }
+                        Some(number_of_rooms) = sync_v2_number_of_rooms.next() => number_of_rooms,
+                        else => break,
+                    };
+
                     loading_state.set(RoomListLoadingState::Loaded { maximum_number_of_rooms });
                 }
             }),
@@ -120,11 +139,6 @@ impl RoomList {
         self.loading_state.subscribe_reset()
     }
 
-    /// Get a stream of rooms.
-    fn entries(&self) -> (Vector<Room>, impl Stream<Item = Vec<VectorDiff<Room>>> + '_) {
-        self.client.rooms_stream()
-    }
-
     /// Get a configurable stream of rooms.
     ///
     /// It's possible to provide a filter that will filter out room list
@@ -158,30 +172,144 @@ impl RoomList {
 
         let stream = stream! {
             loop {
-                let filter_fn = filter_fn_cell.take().await;
+                let EntriesAdapters { filter: filter_fn, sorter } = filter_fn_cell.take().await;
+
+                let (values, stream) = filtered_and_sorted_entries(
+                    &self.client,
+                    filter_fn,
+                    sorter,
+                    room_info_notable_update_receiver.resubscribe(),
+                    self.latest_event_previews.clone(),
+                )
+                .await
+                .dynamic_head_with_initial_value(page_size, limit_stream.clone());
+
+                // Clearing the stream before chaining with the real stream.
+                yield stream::once(ready(vec![VectorDiff::Reset { values }]))
+                    .chain(stream);
+            }
+        }
+        .switch();
+
+        (stream, dynamic_entries_controller)
+    }
+
+    /// Get the rooms partitioned into [`RoomListSection`]s, to render the room
+    /// list in groups.
+    ///
+    /// The rooms are filtered and sorted once for all the sections, with the
+    /// filter set through [`RoomListSections::set_filter`], and each update is
+    /// then dispatched to the section of the room. Each section can be
+    /// paginated independently from the others, see
+    /// [`RoomListSections::section_entries`].
+    pub fn sections(&self) -> RoomListSections {
+        let filter_fn_cell = AsyncCell::shared();
+        let entries = Arc::new(Mutex::new(ObservableVector::new()));
+
+        let task = spawn({
+            let client = self.client.clone();
+            let room_info_notable_update_receiver = self.client.room_info_notable_update_receiver();
+            let latest_event_previews = self.latest_event_previews.clone();
+            let filter_fn_cell = filter_fn_cell.clone();
+            let entries = entries.clone();
+
+            async move {
+                let client = &client;
+
+                let stream = stream! {
+                    loop {
+                        let EntriesAdapters { filter: filter_fn, sorter } =
+                            filter_fn_cell.take().await;
+
+                        let (values, stream) = filtered_and_sorted_entries(
+                            client,
+                            filter_fn,
+                            sorter,
+                            room_info_notable_update_receiver.resubscribe(),
+                            latest_event_previews.clone(),
+                        )
+                        .await;
+
+                        // Clearing the stream before chaining with the real stream.
+                        yield stream::once(ready(vec![VectorDiff::Reset { values }]))
+                            .chain(stream);
+                    }
+                }
+                .switch();
+
+                pin_mut!(stream);
+
+                while let Some(diffs) = stream.next().await {
+                    let mut entries = entries.lock();
+                    let mut entries = entries.transaction();
+
+                    for diff in diffs {
+                        // The section of a room is computed once, for all the sections.
+                        apply_section_diff(
+                            &mut entries,
+                            diff.map(|room| (RoomListSection::of(&room), room)),
+                        );
+                    }
+
+                    entries.commit();
+                }
+            }
+        });
 
-                let (raw_values, raw_stream) = self.entries();
-                let values = raw_values.into_iter().map(Into::into).collect::<Vector<RoomListItem>>();
+        RoomListSections { filter: filter_fn_cell, entries, _task: AbortOnDrop::new(task) }
+    }
 
-                // Combine normal stream events with other updates from rooms
-                let stream = merge_stream_and_receiver(values.clone(), raw_stream, room_info_notable_update_receiver.resubscribe());
+    /// Get a configurable stream of rooms, restricted to a window.
+    ///
+    /// It's like [`Self::entries_with_dynamic_adapters`], except that instead
+    /// of paginating from the top of the room list, only the rooms of the
+    /// visible range set through the returned
+    /// [`RoomListWindowedEntriesController`] are yielded, with `margin` more
+    /// rooms before and after it. It's useful for the accounts with a huge
+    /// number of rooms, rendered with a virtual scrolling: the rooms outside
+    /// the window don't yield any diff, and since the preview of the latest
+    /// event is computed when it's read, see
+    /// [`RoomListItem::latest_event_preview`], it's only computed for the
+    /// rooms which are rendered.
+    ///
+    /// The returned stream will only start yielding diffs once a filter is set
+    /// through the returned [`RoomListWindowedEntriesController`], and the
+    /// window is empty until a visible range is set.
+    pub fn windowed_entries_with_dynamic_adapters(
+        &self,
+        margin: usize,
+    ) -> (impl Stream<Item = Vec<VectorDiff<RoomListItem>>> + '_, RoomListWindowedEntriesController)
+    {
+        let room_info_notable_update_receiver = self.client.room_info_notable_update_receiver();
+
+        let filter_fn_cell = AsyncCell::shared();
 
-                let (values, stream) = (values, stream)
-                    .filter(filter_fn)
-                    .sort_by(new_sorter_lexicographic(vec![
-                        // Sort by latest event's kind, i.e. put the rooms with a
-                        // **local** latest event first.
-                        Box::new(new_sorter_latest_event()),
+        let skip = SharedObservable::<usize>::new(0);
+        let skip_stream = skip.subscribe();
+        let limit = SharedObservable::<usize>::new(0);
+        let limit_stream = limit.subscribe();
 
-                        // Sort rooms by their recency (either by looking
-                        // at their latest event's timestamp, or their
-                        // `recency_stamp`).
-                        Box::new(new_sorter_recency()),
+        let windowed_entries_controller = RoomListWindowedEntriesController {
+            filter: filter_fn_cell.clone(),
+            margin,
+            skip: skip.clone(),
+            limit: limit.clone(),
+        };
 
-                        // Finally, sort by name.
-                        Box::new(new_sorter_name())
-                    ]))
-                    .dynamic_head_with_initial_value(page_size, limit_stream.clone());
+        let stream = stream! {
+            loop {
+                let EntriesAdapters { filter: filter_fn, sorter } = filter_fn_cell.take().await;
+
+                let (values, stream) = filtered_and_sorted_entries(
+                    &self.client,
+                    filter_fn,
+                    sorter,
+                    room_info_notable_update_receiver.resubscribe(),
+                    self.latest_event_previews.clone(),
+                )
+                .await
+                .dynamic_skip_with_initial_count(skip.get(), skip_stream.clone())
+                .dynamic_head_with_initial_value(limit.get(), limit_stream.clone());
 
                 // Clearing the stream before chaining with the real stream.
                 yield stream::once(ready(vec![VectorDiff::Reset { values }]))
@@ -190,10 +318,53 @@ impl RoomList {
         }
         .switch();
 
-        (stream, dynamic_entries_controller)
+        (stream, windowed_entries_controller)
     }
 }
 
+/// Get the rooms passing `filter_fn`, sorted, with a stream of their
+/// updates.
+///
+/// The rooms are sorted by `sorter` first if any, and then by the default
+/// sorters.
+async fn filtered_and_sorted_entries(
+    client: &Client,
+    filter_fn: BoxedFilterFn,
+    sorter: Option<BoxedSorterFn>,
+    room_info_notable_update_receiver: broadcast::Receiver<RoomInfoNotableUpdate>,
+    latest_event_previews: LatestEventPreviews,
+) -> (Vector<RoomListItem>, impl Stream<Item = Vec<VectorDiff<RoomListItem>>> + '_) {
+    let (raw_values, raw_stream) = client.rooms_stream();
+    let values = RoomListItem::new_items(raw_values, &latest_event_previews).await;
+
+    // Combine normal stream events with other updates from rooms
+    let stream = merge_stream_and_receiver(
+        values.clone(),
+        raw_stream,
+        room_info_notable_update_receiver,
+        latest_event_previews,
+    );
+
+    // The sorter of the caller, e.g. sorting the results of a search by relevance,
+    // comes before the default ones.
+    let sorters = sorter
+        .into_iter()
+        .chain([
+            // Sort by latest event's kind, i.e. put the rooms with a
+            // **local** latest event first.
+            Box::new(new_sorter_latest_event()) as BoxedSorterFn,
+            // Sort rooms by their recency (either by looking
+            // at their latest event's timestamp, or their
+            // `recency_stamp`).
+            Box::new(new_sorter_recency()),
+            // Finally, sort by name.
+            Box::new(new_sorter_name()),
+        ])
+        .collect();
+
+    (values, stream).filter(filter_fn).sort_by(new_sorter_lexicographic(sorters))
+}
+
 /// This function remembers the current state of the unfiltered room list, so it
 /// knows where all rooms are. When the receiver is triggered, a Set operation
 /// for the room position is inserted to the stream.
@@ -201,6 +372,7 @@ fn merge_stream_and_receiver(
     mut current_values: Vector<RoomListItem>,
     raw_stream: impl Stream<Item = Vec<VectorDiff<Room>>>,
     mut room_info_notable_update_receiver: broadcast::Receiver<RoomInfoNotableUpdate>,
+    latest_event_previews: LatestEventPreviews,
 ) -> impl Stream<Item = Vec<VectorDiff<RoomListItem>>> {
     stream! {
         pin_mut!(raw_stream);
@@ -212,7 +384,13 @@ fn merge_stream_and_receiver(
 
                 diffs = raw_stream.next() => {
                     if let Some(diffs) = diffs {
-                        let diffs = diffs.into_iter().map(|diff| diff.map(RoomListItem::from)).collect::<Vec<_>>();
+                        let mut items_diffs = Vec::with_capacity(diffs.len());
+
+                        for diff in diffs {
+                            items_diffs.push(RoomListItem::new_items_diff(diff, &latest_event_previews).await);
+                        }
+
+                        let diffs = items_diffs;
 
                         for diff in &diffs {
                             diff.clone().map(|room| {
@@ -236,6 +414,12 @@ fn merge_stream_and_receiver(
                                 let mut room = current_values[index].clone();
                                 room.refresh_cached_data();
 
+                                if update.reasons.contains(RoomInfoNotableUpdateReasons::LATEST_EVENT) {
+                                    room.reload_cached_latest_event_preview(&latest_event_previews).await;
+                                }
+
+                                current_values.set(index, room.clone());
+
                                 yield vec![VectorDiff::Set { index, value: room }];
                             }
                         }
@@ -297,12 +481,34 @@ pub enum RoomListLoadingState {
     },
 }
 
+/// The adapters set through a [`RoomListDynamicEntriesController`] or a
+/// [`RoomListWindowedEntriesController`].
+struct EntriesAdapters {
+    /// The filter of the rooms.
+    filter: BoxedFilterFn,
+
+    /// The sorter applied before the default ones, if any.
+    sorter: Option<BoxedSorterFn>,
+}
+
+/// Set the adapters of a stream, unless it has been dropped.
+fn set_entries_adapters(cell: &Arc<AsyncCell<EntriesAdapters>>, adapters: EntriesAdapters) -> bool {
+    if Arc::strong_count(cell) == 1 {
+        // there is no other reference to the adapters, setting them would be
+        // pointless (no new references can be created from self, either)
+        false
+    } else {
+        cell.set(adapters);
+        true
+    }
+}
+
 /// Controller for the [`RoomList`] dynamic entries.
 ///
 /// To get one value of this type, use
 /// [`RoomList::entries_with_dynamic_adapters`]
 pub struct RoomListDynamicEntriesController {
-    filter: Arc<AsyncCell<BoxedFilterFn>>,
+    filter: Arc<AsyncCell<EntriesAdapters>>,
     page_size: usize,
     limit: SharedObservable<usize>,
     maximum_number_of_rooms: Subscriber<Option<u32>>,
@@ -310,7 +516,7 @@ pub struct RoomListDynamicEntriesController {
 
 impl RoomListDynamicEntriesController {
     fn new(
-        filter: Arc<AsyncCell<BoxedFilterFn>>,
+        filter: Arc<AsyncCell<EntriesAdapters>>,
         page_size: usize,
         limit_stream: SharedObservable<usize>,
         maximum_number_of_rooms: Subscriber<Option<u32>>,
@@ -323,15 +529,23 @@ impl RoomListDynamicEntriesController {
     /// If the associated stream has been dropped, returns `false` to indicate
     /// the operation didn't have an effect.
     pub fn set_filter(&self, filter: BoxedFilterFn) -> bool {
-        if Arc::strong_count(&self.filter) == 1 {
-            // there is no other reference to the boxed filter fn, setting it
-            // would be pointless (no new references can be created from self,
-            // either)
-            false
-        } else {
-            self.filter.set(filter);
-            true
-        }
+        set_entries_adapters(&self.filter, EntriesAdapters { filter, sorter: None })
+    }
+
+    /// Set the filter, and sort the rooms with `sorter` before the default
+    /// sorters, which only order the rooms that are equal for `sorter`.
+    ///
+    /// It's meant to sort the results of a search by relevance, with
+    /// [`new_sorter_fuzzy_match_room_name`] along with
+    /// [`new_filter_fuzzy_match_room_name`].
+    ///
+    /// If the associated stream has been dropped, returns `false` to indicate
+    /// the operation didn't have an effect.
+    ///
+    /// [`new_sorter_fuzzy_match_room_name`]: super::sorters::new_sorter_fuzzy_match_room_name
+    /// [`new_filter_fuzzy_match_room_name`]: super::filters::new_filter_fuzzy_match_room_name
+    pub fn set_filter_with_sorter(&self, filter: BoxedFilterFn, sorter: BoxedSorterFn) -> bool {
+        set_entries_adapters(&self.filter, EntriesAdapters { filter, sorter: Some(sorter) })
     }
 
     /// Add one page, i.e. view `page_size` more entries in the room list if
@@ -360,6 +574,187 @@ impl RoomListDynamicEntriesController {
     }
 }
 
+/// Controller for the [`RoomList`] windowed entries.
+///
+/// To get one value of this type, use
+/// [`RoomList::windowed_entries_with_dynamic_adapters`]
+pub struct RoomListWindowedEntriesController {
+    filter: Arc<AsyncCell<EntriesAdapters>>,
+    margin: usize,
+    skip: SharedObservable<usize>,
+    limit: SharedObservable<usize>,
+}
+
+impl RoomListWindowedEntriesController {
+    /// Set the filter.
+    ///
+    /// If the associated stream has been dropped, returns `false` to indicate
+    /// the operation didn't have an effect.
+    pub fn set_filter(&self, filter: BoxedFilterFn) -> bool {
+        set_entries_adapters(&self.filter, EntriesAdapters { filter, sorter: None })
+    }
+
+    /// Set the filter, and sort the rooms with `sorter` before the default
+    /// sorters, see
+    /// [`RoomListDynamicEntriesController::set_filter_with_sorter`].
+    ///
+    /// If the associated stream has been dropped, returns `false` to indicate
+    /// the operation didn't have an effect.
+    pub fn set_filter_with_sorter(&self, filter: BoxedFilterFn, sorter: BoxedSorterFn) -> bool {
+        set_entries_adapters(&self.filter, EntriesAdapters { filter, sorter: Some(sorter) })
+    }
+
+    /// Set the range of the visible rooms, i.e. the indices of the rooms in
+    /// the whole filtered room list.
+    ///
+    /// The stream then yields the rooms of this range, plus the margin before
+    /// and after it, see [`Self::window`].
+    pub fn set_visible_range(&self, visible_range: Range<usize>) {
+        let window = window_of(visible_range, self.margin);
+
+        self.skip.set_if_not_eq(window.start);
+        self.limit.set_if_not_eq(window.len());
+    }
+
+    /// The range of the rooms yielded by the stream, i.e. the index of the
+    /// first room of the stream in the whole filtered room list, and the
+    /// maximum number of rooms of the stream.
+    pub fn window(&self) -> Range<usize> {
+        let start = self.skip.get();
+
+        start..start + self.limit.get()
+    }
+}
+
+/// The rooms to yield for the given visible range, with `margin` more rooms
+/// before and after it.
+fn window_of(visible_range: Range<usize>, margin: usize) -> Range<usize> {
+    let start = visible_range.start.saturating_sub(margin);
+    let end = visible_range.end.max(visible_range.start).saturating_add(margin);
+
+    start..end
+}
+
+/// The rooms of a [`RoomList`], partitioned into [`RoomListSection`]s.
+///
+/// To get one value of this type, use [`RoomList::sections`]. The streams of
+/// the sections stop being updated once this value is dropped.
+pub struct RoomListSections {
+    filter: Arc<AsyncCell<EntriesAdapters>>,
+    entries: Arc<Mutex<ObservableVector<(RoomListSection, RoomListItem)>>>,
+    _task: AbortOnDrop<()>,
+}
+
+impl RoomListSections {
+    /// Set the filter of all the sections.
+    ///
+    /// Returns `false` to indicate the operation didn't have an effect, which
+    /// can't happen as long as this value is alive.
+    pub fn set_filter(&self, filter: BoxedFilterFn) -> bool {
+        set_entries_adapters(&self.filter, EntriesAdapters { filter, sorter: None })
+    }
+
+    /// Set the filter of all the sections, and sort the rooms with `sorter`
+    /// before the default sorters, see
+    /// [`RoomListDynamicEntriesController::set_filter_with_sorter`].
+    pub fn set_filter_with_sorter(&self, filter: BoxedFilterFn, sorter: BoxedSorterFn) -> bool {
+        set_entries_adapters(&self.filter, EntriesAdapters { filter, sorter: Some(sorter) })
+    }
+
+    /// Get a stream of the rooms of one section, “paginated” by `page_size`.
+    ///
+    /// The stream yields a [`VectorDiff::Reset`] with the current rooms of the
+    /// section first, which is empty until a filter is set, followed by their
+    /// updates.
+    pub fn section_entries(
+        &self,
+        section: RoomListSection,
+        page_size: usize,
+    ) -> (impl Stream<Item = Vec<VectorDiff<RoomListItem>>> + use<>, RoomListSectionController)
+    {
+        let limit = SharedObservable::<usize>::new(page_size);
+
+        let (values, stream) = self
+            .entries
+            .lock()
+            .subscribe()
+            .into_values_and_batched_stream()
+            .filter(move |(room_section, _)| *room_section == section)
+            .dynamic_head_with_initial_value(page_size, limit.subscribe());
+
+        let values = values.into_iter().map(|(_, room)| room).collect();
+        let stream = stream.map(|diffs| {
+            diffs.into_iter().map(|diff| diff.map(|(_, room)| room)).collect::<Vec<_>>()
+        });
+
+        let section_controller =
+            RoomListSectionController { section, page_size, limit, entries: self.entries.clone() };
+
+        (stream::once(ready(vec![VectorDiff::Reset { values }])).chain(stream), section_controller)
+    }
+}
+
+/// Apply a diff of the filtered and sorted rooms to the rooms of the sections.
+fn apply_section_diff(
+    entries: &mut ObservableVectorTransaction<'_, (RoomListSection, RoomListItem)>,
+    diff: VectorDiff<(RoomListSection, RoomListItem)>,
+) {
+    match diff {
+        VectorDiff::Append { values } => entries.append(values),
+        VectorDiff::Clear => entries.clear(),
+        VectorDiff::PushFront { value } => entries.push_front(value),
+        VectorDiff::PushBack { value } => entries.push_back(value),
+        VectorDiff::PopFront => {
+            entries.pop_front();
+        }
+        VectorDiff::PopBack => {
+            entries.pop_back();
+        }
+        VectorDiff::Insert { index, value } => entries.insert(index, value),
+        VectorDiff::Set { index, value } => {
+            entries.set(index, value);
+        }
+        VectorDiff::Remove { index } => {
+            entries.remove(index);
+        }
+        VectorDiff::Truncate { length } => entries.truncate(length),
+        VectorDiff::Reset { values } => {
+            entries.clear();
+            entries.append(values);
+        }
+    }
+}
+
+/// Controller for the pagination of one section of the [`RoomListSections`].
+///
+/// To get one value of this type, use [`RoomListSections::section_entries`].
+pub struct RoomListSectionController {
+    section: RoomListSection,
+    page_size: usize,
+    limit: SharedObservable<usize>,
+    entries: Arc<Mutex<ObservableVector<(RoomListSection, RoomListItem)>>>,
+}
+
+impl RoomListSectionController {
+    /// Add one page, i.e. view `page_size` more entries in the section if
+    /// any.
+    pub fn add_one_page(&self) {
+        let number_of_rooms =
+            self.entries.lock().iter().filter(|(section, _)| *section == self.section).count();
+        let limit = self.limit.get();
+
+        if limit < number_of_rooms {
+            self.limit.set_if_not_eq(limit + self.page_size);
+        }
+    }
+
+    /// Reset the one page, i.e. forget all pages and move back to the first
+    /// page.
+    pub fn reset_to_one_page(&self) {
+        self.limit.set_if_not_eq(self.page_size);
+    }
+}
+
 /// A facade type that derefs to [`Room`] and that caches data from
 /// [`RoomInfo`].
 ///
@@ -388,11 +783,17 @@ pub struct RoomListItem {
     /// Cache of `Room::cached_display_name`, already as a string.
     pub(super) cached_display_name: Option<String>,
 
+    /// Cache of `Room::canonical_alias`.
+    pub(super) cached_canonical_alias: Option<OwnedRoomAliasId>,
+
     /// Cache of `Room::is_space`.
     pub(super) cached_is_space: bool,
 
     // Cache of `Room::state`.
     pub(super) cached_state: RoomState,
+
+    /// Preview of `Room::new_latest_event`.
+    cached_latest_event_preview: CachedLatestEventPreview,
 }
 
 impl RoomListItem {
@@ -401,23 +802,36 @@ impl RoomListItem {
         self.inner
     }
 
-    /// Refresh the cached data.
-    pub(super) fn refresh_cached_data(&mut self) {
-        self.cached_latest_event_timestamp = self.inner.new_latest_event_timestamp();
-        self.cached_latest_event_is_local = self.inner.new_latest_event_is_local();
-        self.cached_recency_stamp = self.inner.recency_stamp();
-        self.cached_display_name = self.inner.cached_display_name().map(|name| name.to_string());
-        // no need to refresh `Self::is_space`.
-        self.cached_state = self.inner.state();
+    /// The preview of the latest event of the room, if any.
+    ///
+    /// It's kept up to date in the streams of the [`RoomList`], so that room
+    /// lists can show the latest message of each room without creating a
+    /// timeline for each of them.
+    ///
+    /// It's computed the first time it's read, so only the rooms which are
+    /// rendered pay for it, and it's shared by all the streams of the
+    /// [`RoomList`]: it's only computed again when the latest event of the
+    /// room changes.
+    pub fn latest_event_preview(&self) -> Option<&LatestEventPreview> {
+        self.cached_latest_event_preview
+            .preview
+            .get_or_init(|| {
+                LatestEventPreview::from_latest_event_value(
+                    &self.inner.new_latest_event(),
+                    self.inner.own_user_id(),
+                    self.cached_latest_event_preview.sender.as_ref(),
+                    &self.inner.heroes(),
+                )
+            })
+            .as_ref()
     }
-}
 
-impl From<Room> for RoomListItem {
-    fn from(inner: Room) -> Self {
+    fn new(inner: Room, cached_latest_event_preview: CachedLatestEventPreview) -> Self {
         let cached_latest_event_timestamp = inner.new_latest_event_timestamp();
         let cached_latest_event_is_local = inner.new_latest_event_is_local();
         let cached_recency_stamp = inner.recency_stamp();
         let cached_display_name = inner.cached_display_name().map(|name| name.to_string());
+        let cached_canonical_alias = inner.canonical_alias();
         let cached_is_space = inner.is_space();
         let cached_state = inner.state();
 
@@ -427,10 +841,161 @@ impl From<Room> for RoomListItem {
             cached_latest_event_is_local,
             cached_recency_stamp,
             cached_display_name,
+            cached_canonical_alias,
             cached_is_space,
             cached_state,
+            cached_latest_event_preview,
+        }
+    }
+
+    /// Create the item of `room`, with the preview of its latest event from
+    /// `latest_event_previews`.
+    async fn with_latest_event_preview(
+        room: Room,
+        latest_event_previews: &LatestEventPreviews,
+    ) -> Self {
+        let cached_latest_event_preview =
+            CachedLatestEventPreview::get_or_load(&room, latest_event_previews).await;
+
+        Self::new(room, cached_latest_event_preview)
+    }
+
+    /// Create the items of `rooms`, see [`Self::with_latest_event_preview`].
+    async fn new_items(
+        rooms: Vector<Room>,
+        latest_event_previews: &LatestEventPreviews,
+    ) -> Vector<RoomListItem> {
+        let mut items = Vector::new();
+
+        for room in rooms {
+            items.push_back(Self::with_latest_event_preview(room, latest_event_previews).await);
+        }
+
+        items
+    }
+
+    /// Create the items of the rooms of `diff`, see
+    /// [`Self::with_latest_event_preview`].
+    async fn new_items_diff(
+        diff: VectorDiff<Room>,
+        latest_event_previews: &LatestEventPreviews,
+    ) -> VectorDiff<RoomListItem> {
+        let new_item =
+            async |room: Room| Self::with_latest_event_preview(room, latest_event_previews).await;
+
+        match diff {
+            VectorDiff::Append { values } => {
+                VectorDiff::Append { values: Self::new_items(values, latest_event_previews).await }
+            }
+            VectorDiff::Clear => VectorDiff::Clear,
+            VectorDiff::PushFront { value } => {
+                VectorDiff::PushFront { value: new_item(value).await }
+            }
+            VectorDiff::PushBack { value } => VectorDiff::PushBack { value: new_item(value).await },
+            VectorDiff::PopFront => VectorDiff::PopFront,
+            VectorDiff::PopBack => VectorDiff::PopBack,
+            VectorDiff::Insert { index, value } => {
+                VectorDiff::Insert { index, value: new_item(value).await }
+            }
+            VectorDiff::Set { index, value } => {
+                VectorDiff::Set { index, value: new_item(value).await }
+            }
+            VectorDiff::Remove { index } => VectorDiff::Remove { index },
+            VectorDiff::Truncate { length } => VectorDiff::Truncate { length },
+            VectorDiff::Reset { values } => {
+                VectorDiff::Reset { values: Self::new_items(values, latest_event_previews).await }
+            }
         }
     }
+
+    /// Refresh the cached data.
+    pub(super) fn refresh_cached_data(&mut self) {
+        self.cached_latest_event_timestamp = self.inner.new_latest_event_timestamp();
+        self.cached_latest_event_is_local = self.inner.new_latest_event_is_local();
+        self.cached_recency_stamp = self.inner.recency_stamp();
+        self.cached_display_name = self.inner.cached_display_name().map(|name| name.to_string());
+        self.cached_canonical_alias = self.inner.canonical_alias();
+        // no need to refresh `Self::is_space`.
+        self.cached_state = self.inner.state();
+    }
+
+    /// Load again the sender of the latest event, and forget the cached
+    /// preview of the latest event, so that it's computed again the next time
+    /// it's read.
+    async fn reload_cached_latest_event_preview(
+        &mut self,
+        latest_event_previews: &LatestEventPreviews,
+    ) {
+        self.cached_latest_event_preview =
+            CachedLatestEventPreview::load(&self.inner, latest_event_previews).await;
+    }
+}
+
+impl From<Room> for RoomListItem {
+    fn from(inner: Room) -> Self {
+        Self::new(inner, CachedLatestEventPreview::default())
+    }
+}
+
+/// The previews of the latest events of the rooms, by room ID, shared by all
+/// the streams of a [`RoomList`].
+///
+/// They are kept when the filter changes, or when a room is updated by a sync,
+/// so that they are only computed again for the rooms whose latest event has
+/// changed.
+type LatestEventPreviews = Arc<Mutex<HashMap<OwnedRoomId, CachedLatestEventPreview>>>;
+
+/// The cached preview of the latest event of a room.
+#[derive(Clone, Debug, Default)]
+struct CachedLatestEventPreview {
+    /// The timestamp of the latest event, and whether it's a local event, to
+    /// know whether the latest event has changed since it's been cached.
+    latest_event: (Option<MilliSecondsSinceUnixEpoch>, bool),
+
+    /// The sender of the latest event, with their display name from the
+    /// members of the room.
+    sender: Option<LatestEventSender>,
+
+    /// The preview of the latest event.
+    ///
+    /// It's computed the first time it's read, since computing it requires to
+    /// clone and deserialize the latest event.
+    preview: Arc<OnceLock<Option<LatestEventPreview>>>,
+}
+
+impl CachedLatestEventPreview {
+    /// Get the cached preview of the latest event of `room`, or load it if
+    /// it's not in `latest_event_previews` yet, or if the latest event has
+    /// changed.
+    async fn get_or_load(room: &Room, latest_event_previews: &LatestEventPreviews) -> Self {
+        let latest_event = Self::latest_event_of(room);
+
+        if let Some(cached) = latest_event_previews.lock().get(room.room_id())
+            && cached.latest_event == latest_event
+        {
+            return cached.clone();
+        }
+
+        Self::load(room, latest_event_previews).await
+    }
+
+    /// Load the sender of the latest event of `room`, and replace the previous
+    /// preview in `latest_event_previews`.
+    async fn load(room: &Room, latest_event_previews: &LatestEventPreviews) -> Self {
+        let cached = Self {
+            latest_event: Self::latest_event_of(room),
+            sender: LatestEventSender::load(room).await,
+            preview: Default::default(),
+        };
+
+        latest_event_previews.lock().insert(room.room_id().to_owned(), cached.clone());
+
+        cached
+    }
+
+    fn latest_event_of(room: &Room) -> (Option<MilliSecondsSinceUnixEpoch>, bool) {
+        (room.new_latest_event_timestamp(), room.new_latest_event_is_local())
+    }
 }
 
 impl Deref for RoomListItem {
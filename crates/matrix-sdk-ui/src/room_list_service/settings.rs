@@ -0,0 +1,240 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ruma::events::StateEventType;
+use thiserror::Error;
+
+use super::{
+    DEFAULT_REQUIRED_STATE, DEFAULT_ROOM_SUBSCRIPTION_EXTRA_REQUIRED_STATE,
+    DEFAULT_ROOM_SUBSCRIPTION_TIMELINE_LIMIT,
+};
+
+/// The default `timeline_limit` value of the sliding sync list of all the
+/// rooms.
+const DEFAULT_LIST_TIMELINE_LIMIT: u32 = 1;
+
+/// The maximum `timeline_limit` value accepted by [`RoomListServiceSettings`].
+///
+/// The timeline limits are applied to every room of the sync responses, so a
+/// larger value would make them huge.
+pub const MAX_TIMELINE_LIMIT: u32 = 100;
+
+/// The settings of the sliding sync used by a
+/// [`RoomListService`](super::RoomListService).
+///
+/// The default settings request the state events needed by the room list,
+/// the latest event of every room, and the 20 latest events of the subscribed
+/// rooms. Applications can request more state events, e.g. to display the
+/// topic of the rooms, or change the timeline limits, e.g. to not receive any
+/// event for the rooms in the list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoomListServiceSettings {
+    required_state: Vec<(StateEventType, String)>,
+    timeline_limit: u32,
+    room_subscription_timeline_limit: u32,
+}
+
+impl RoomListServiceSettings {
+    /// Also request the state event with the given type and state key, for
+    /// all the rooms.
+    ///
+    /// The state events requested by default are always kept. The `state_key`
+    /// can be one of the special values of sliding sync, like `*` for all the
+    /// state keys, or `$ME` for the state key of the current user.
+    pub fn with_required_state(
+        mut self,
+        event_type: StateEventType,
+        state_key: impl Into<String>,
+    ) -> Self {
+        let required_state = (event_type, state_key.into());
+
+        if !self.required_state.contains(&required_state) {
+            self.required_state.push(required_state);
+        }
+
+        self
+    }
+
+    /// Set the number of events requested for the rooms of the list.
+    ///
+    /// It defaults to 1, to compute the latest event of the rooms. It can be
+    /// 0 to not receive any event, in which case the latest events must be
+    /// computed with room subscriptions.
+    pub fn with_timeline_limit(mut self, timeline_limit: u32) -> Self {
+        self.timeline_limit = timeline_limit;
+        self
+    }
+
+    /// Set the number of events requested for the rooms subscribed with
+    /// [`RoomListService::subscribe_to_rooms`](super::RoomListService::subscribe_to_rooms).
+    ///
+    /// It defaults to 20.
+    pub fn with_room_subscription_timeline_limit(mut self, timeline_limit: u32) -> Self {
+        self.room_subscription_timeline_limit = timeline_limit;
+        self
+    }
+
+    /// The state events requested for the rooms of the list.
+    pub fn required_state(&self) -> &[(StateEventType, String)] {
+        &self.required_state
+    }
+
+    /// The number of events requested for the rooms of the list.
+    pub fn timeline_limit(&self) -> u32 {
+        self.timeline_limit
+    }
+
+    /// The number of events requested for the subscribed rooms.
+    pub fn room_subscription_timeline_limit(&self) -> u32 {
+        self.room_subscription_timeline_limit
+    }
+
+    /// The state events requested for the subscribed rooms: the ones of the
+    /// list, plus the ones only needed to display a room.
+    pub(super) fn room_subscription_required_state(&self) -> Vec<(StateEventType, String)> {
+        let mut required_state = self.required_state.clone();
+
+        for (event_type, state_key) in DEFAULT_ROOM_SUBSCRIPTION_EXTRA_REQUIRED_STATE {
+            let extra = (event_type.clone(), (*state_key).to_owned());
+
+            if !required_state.contains(&extra) {
+                required_state.push(extra);
+            }
+        }
+
+        required_state
+    }
+
+    /// Check that the settings can be used by a
+    /// [`RoomListService`](super::RoomListService).
+    pub fn validate(&self) -> Result<(), InvalidSettingsError> {
+        for (event_type, _) in &self.required_state {
+            match event_type.to_string().as_str() {
+                "" => return Err(InvalidSettingsError::EmptyEventType),
+                "*" => return Err(InvalidSettingsError::WildcardEventType),
+                _ => {}
+            }
+        }
+
+        for timeline_limit in [self.timeline_limit, self.room_subscription_timeline_limit] {
+            if timeline_limit > MAX_TIMELINE_LIMIT {
+                return Err(InvalidSettingsError::TimelineLimitTooLarge(timeline_limit));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for RoomListServiceSettings {
+    fn default() -> Self {
+        Self {
+            required_state: DEFAULT_REQUIRED_STATE
+                .iter()
+                .map(|(event_type, state_key)| (event_type.clone(), (*state_key).to_owned()))
+                .collect(),
+            timeline_limit: DEFAULT_LIST_TIMELINE_LIMIT,
+            room_subscription_timeline_limit: DEFAULT_ROOM_SUBSCRIPTION_TIMELINE_LIMIT,
+        }
+    }
+}
+
+/// The reasons why [`RoomListServiceSettings`] can be invalid.
+#[derive(Debug, Error)]
+pub enum InvalidSettingsError {
+    /// A required state event has an empty event type.
+    #[error("a required state event has an empty event type")]
+    EmptyEventType,
+
+    /// A required state event has the `*` event type, which would request all
+    /// the state of every room.
+    #[error("the `*` event type isn't allowed in the required state")]
+    WildcardEventType,
+
+    /// A timeline limit is larger than [`MAX_TIMELINE_LIMIT`].
+    #[error("the timeline limit {0} is larger than the maximum of {MAX_TIMELINE_LIMIT}")]
+    TimelineLimitTooLarge(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use ruma::events::StateEventType;
+
+    use super::{InvalidSettingsError, MAX_TIMELINE_LIMIT, RoomListServiceSettings};
+
+    #[test]
+    fn test_required_state() {
+        let default_settings = RoomListServiceSettings::default();
+
+        // The default required state is deduplicated.
+        let settings = default_settings.clone().with_required_state(StateEventType::RoomTopic, "");
+        assert_eq!(settings, default_settings);
+
+        let settings = default_settings
+            .clone()
+            .with_required_state(StateEventType::RoomThirdPartyInvite, "*")
+            .with_required_state(StateEventType::RoomThirdPartyInvite, "*");
+        assert_eq!(settings.required_state().len(), default_settings.required_state().len() + 1);
+        assert_eq!(
+            settings.required_state().last(),
+            Some(&(StateEventType::RoomThirdPartyInvite, "*".to_owned()))
+        );
+
+        // The room subscriptions get the extra state too.
+        let room_subscription_required_state = settings.room_subscription_required_state();
+        assert!(
+            room_subscription_required_state
+                .contains(&(StateEventType::RoomThirdPartyInvite, "*".to_owned()))
+        );
+        assert!(
+            room_subscription_required_state
+                .contains(&(StateEventType::RoomPinnedEvents, "".to_owned()))
+        );
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(RoomListServiceSettings::default().validate().is_ok());
+
+        // A timeline limit of 0 is fine.
+        let settings = RoomListServiceSettings::default().with_timeline_limit(0);
+        assert_eq!(settings.timeline_limit(), 0);
+        assert!(settings.validate().is_ok());
+
+        assert_matches!(
+            RoomListServiceSettings::default().with_required_state("*".into(), "").validate(),
+            Err(InvalidSettingsError::WildcardEventType)
+        );
+        assert_matches!(
+            RoomListServiceSettings::default().with_required_state("".into(), "").validate(),
+            Err(InvalidSettingsError::EmptyEventType)
+        );
+        assert_matches!(
+            RoomListServiceSettings::default()
+                .with_timeline_limit(MAX_TIMELINE_LIMIT + 1)
+                .validate(),
+            Err(InvalidSettingsError::TimelineLimitTooLarge(limit)) => {
+                assert_eq!(limit, MAX_TIMELINE_LIMIT + 1);
+            }
+        );
+        assert_matches!(
+            RoomListServiceSettings::default()
+                .with_room_subscription_timeline_limit(MAX_TIMELINE_LIMIT + 1)
+                .validate(),
+            Err(InvalidSettingsError::TimelineLimitTooLarge(_))
+        );
+    }
+}
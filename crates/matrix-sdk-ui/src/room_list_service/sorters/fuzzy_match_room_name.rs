@@ -0,0 +1,143 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{cmp::Ordering, collections::HashMap, sync::Mutex};
+
+use ruma::{OwnedRoomAliasId, OwnedRoomId};
+
+use super::{
+    super::{RoomListItem, filters::FuzzyMatcher},
+    Sorter,
+};
+
+fn cmp(scores: (Option<i64>, Option<i64>)) -> Ordering {
+    let (left_score, right_score) = scores;
+
+    // The best matches come first, and the rooms which don't match come last.
+    right_score.cmp(&left_score)
+}
+
+/// The score of a room, along with the names it has been computed from.
+struct CachedScore {
+    display_name: Option<String>,
+    canonical_alias: Option<OwnedRoomAliasId>,
+    score: Option<i64>,
+}
+
+/// Computes the scores of the rooms, and keeps them as long as the names of the
+/// rooms don't change, since sorting compares the same room many times.
+struct Scorer {
+    matcher: FuzzyMatcher,
+    scores: Mutex<HashMap<OwnedRoomId, CachedScore>>,
+}
+
+impl Scorer {
+    fn score(&self, room: &RoomListItem) -> Option<i64> {
+        let mut scores = self.scores.lock().unwrap();
+
+        if let Some(cached) = scores.get(room.room_id())
+            && cached.display_name == room.cached_display_name
+            && cached.canonical_alias == room.cached_canonical_alias
+        {
+            return cached.score;
+        }
+
+        let score = self.matcher.score_room(room);
+        scores.insert(
+            room.room_id().to_owned(),
+            CachedScore {
+                display_name: room.cached_display_name.clone(),
+                canonical_alias: room.cached_canonical_alias.clone(),
+                score,
+            },
+        );
+
+        score
+    }
+}
+
+/// Create a new sorter that will sort two [`RoomListItem`] by how well they
+/// match `pattern`, as computed by
+/// [`new_filter_fuzzy_match_room_name`](crate::room_list_service::filters::new_filter_fuzzy_match_room_name):
+/// the rooms whose name starts with the pattern come first.
+///
+/// The rooms with the same score are equal, so this sorter is meant to be
+/// combined with other ones by
+/// [`new_sorter_lexicographic`](super::new_sorter_lexicographic), or to be
+/// set with
+/// [`RoomListDynamicEntriesController::set_filter_with_sorter`](super::super::RoomListDynamicEntriesController::set_filter_with_sorter).
+///
+/// The score of a room is only computed again when its name changes.
+pub fn new_sorter(pattern: &str) -> impl Sorter + use<> {
+    let scorer = Scorer {
+        matcher: FuzzyMatcher::new().with_pattern(pattern),
+        scores: Mutex::new(HashMap::new()),
+    };
+
+    move |left, right| -> Ordering { cmp((scorer.score(left), scorer.score(right))) }
+}
+
+#[cfg(test)]
+mod tests {
+    use matrix_sdk::test_utils::logged_in_client_with_server;
+    use matrix_sdk_test::async_test;
+    use ruma::room_id;
+
+    use super::{super::super::filters::new_rooms, *};
+
+    #[test]
+    fn test_cmp() {
+        // The best score comes first.
+        assert_eq!(cmp((Some(1_000), Some(10))), Ordering::Less);
+        assert_eq!(cmp((Some(10), Some(1_000))), Ordering::Greater);
+        assert_eq!(cmp((Some(10), Some(10))), Ordering::Equal);
+
+        // The rooms which don't match come last.
+        assert_eq!(cmp((Some(10), None)), Ordering::Less);
+        assert_eq!(cmp((None, Some(10))), Ordering::Greater);
+    }
+
+    #[async_test]
+    async fn test_with_room_ids() {
+        let (client, server) = logged_in_client_with_server().await;
+        let [room_a, room_b] =
+            new_rooms([room_id!("!a:b.c"), room_id!("!d:e.f")], &client, &server).await;
+
+        // Only `room_b` matches, so it comes first.
+        let sorter = new_sorter("!d");
+        assert_eq!(sorter(&room_a, &room_b), Ordering::Greater);
+        assert_eq!(sorter(&room_b, &room_a), Ordering::Less);
+    }
+
+    #[async_test]
+    async fn test_scores_are_cached_until_the_name_changes() {
+        let (client, server) = logged_in_client_with_server().await;
+        let [mut room_a, room_b] =
+            new_rooms([room_id!("!a:b.c"), room_id!("!d:e.f")], &client, &server).await;
+
+        let scorer = Scorer {
+            matcher: FuzzyMatcher::new().with_pattern("kitchen"),
+            scores: Mutex::new(HashMap::new()),
+        };
+
+        assert_eq!(scorer.score(&room_a), None);
+        assert_eq!(scorer.score(&room_b), None);
+        assert_eq!(scorer.scores.lock().unwrap().len(), 2);
+
+        // The score is computed again when the name changes.
+        room_a.cached_display_name = Some("Kitchen".to_owned());
+        assert!(scorer.score(&room_a).is_some());
+        assert_eq!(scorer.scores.lock().unwrap().len(), 2);
+    }
+}
@@ -14,6 +14,7 @@
 
 //! A collection of room sorters.
 
+mod fuzzy_match_room_name;
 mod latest_event;
 mod lexicographic;
 mod name;
@@ -21,6 +22,7 @@ mod recency;
 
 use std::cmp::Ordering;
 
+pub use fuzzy_match_room_name::new_sorter as new_sorter_fuzzy_match_room_name;
 pub use latest_event::new_sorter as new_sorter_latest_event;
 pub use lexicographic::new_sorter as new_sorter_lexicographic;
 pub use name::new_sorter as new_sorter_name;
@@ -194,8 +194,8 @@ pub const ALL_ROOMS_DEFAULT_GROWING_BATCH_SIZE: u32 = 100;
 
 #[cfg(test)]
 mod tests {
+    use matrix_sdk::sleep::sleep;
     use matrix_sdk_test::async_test;
-    use tokio::time::sleep;
 
     use super::{super::tests::new_room_list, *};
 
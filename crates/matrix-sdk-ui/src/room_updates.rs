@@ -0,0 +1,59 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to observe the room updates of a client, to keep some values
+//! computed from the rooms up to date.
+
+use matrix_sdk_base::sync::RoomUpdates;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use tracing::warn;
+
+/// The room updates returned by [`next_relevant_room_updates`].
+pub(crate) enum RelevantRoomUpdates {
+    /// Some room updates which are relevant to the caller.
+    Updates(RoomUpdates),
+
+    /// Some updates have been missed, so the caller must consider that all
+    /// the rooms may have changed.
+    Lagged,
+}
+
+/// Wait for the next room updates, from
+/// [`Client::subscribe_to_all_room_updates`], for which `is_relevant` returns
+/// `true`. The other updates are skipped.
+///
+/// Returns `None` once the sender of the updates has been closed.
+///
+/// [`Client::subscribe_to_all_room_updates`]: matrix_sdk::Client::subscribe_to_all_room_updates
+pub(crate) async fn next_relevant_room_updates(
+    receiver: &mut Receiver<RoomUpdates>,
+    mut is_relevant: impl FnMut(&RoomUpdates) -> bool,
+) -> Option<RelevantRoomUpdates> {
+    loop {
+        match receiver.recv().await {
+            Ok(updates) => {
+                if is_relevant(&updates) {
+                    return Some(RelevantRoomUpdates::Updates(updates));
+                }
+            }
+
+            Err(RecvError::Lagged(number_of_missed_updates)) => {
+                warn!(number_of_missed_updates, "Lag when receiving the room updates");
+                return Some(RelevantRoomUpdates::Lagged);
+            }
+
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
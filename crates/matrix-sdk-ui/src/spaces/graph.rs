@@ -67,6 +67,22 @@ impl SpaceGraph {
             .map_or(vec![], |node| node.children.iter().map(|id| id.as_ref()).collect())
     }
 
+    /// Returns the descendants of a given node, i.e. its children, their
+    /// children, and so on. If the node does not exist, it returns an empty
+    /// set.
+    pub(super) fn descendants_of(&self, node_id: &RoomId) -> BTreeSet<OwnedRoomId> {
+        let mut descendants = BTreeSet::new();
+        let mut to_visit = self.children_of(node_id);
+
+        while let Some(id) = to_visit.pop() {
+            if descendants.insert(id.to_owned()) {
+                to_visit.extend(self.children_of(id));
+            }
+        }
+
+        descendants
+    }
+
     /// Adds a node to the graph. If the node already exists, it does nothing.
     pub(super) fn add_node(&mut self, node_id: OwnedRoomId) {
         self.nodes.entry(node_id.clone()).or_insert(SpaceGraphNode::new(node_id));
@@ -141,6 +157,28 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_descendants() {
+        let mut graph = SpaceGraph::new();
+
+        let a = room_id!("!a:example.org").to_owned();
+        let b = room_id!("!b:example.org").to_owned();
+        let c = room_id!("!c:example.org").to_owned();
+        let d = room_id!("!d:example.org").to_owned();
+
+        graph.add_edge(a.clone(), b.clone());
+        graph.add_edge(b.clone(), c.clone());
+        graph.add_edge(d.clone(), c.clone());
+        graph.add_edge(c.clone(), a.clone());
+
+        graph.remove_cycles();
+
+        assert_eq!(graph.descendants_of(&a), BTreeSet::from([b.clone(), c.clone()]));
+        assert_eq!(graph.descendants_of(&d), BTreeSet::from([c.clone()]));
+        assert!(graph.descendants_of(&c).is_empty());
+        assert!(graph.descendants_of(room_id!("!unknown:example.org")).is_empty());
+    }
+
     #[test]
     fn test_add_edge_and_root_nodes() {
         let mut graph = SpaceGraph::new();
@@ -27,27 +27,30 @@
 //! - `SpaceRoomList`: A component for retrieving a space's children rooms and
 //!   their details.
 
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 
+use async_stream::stream;
 use eyeball_im::{ObservableVector, VectorSubscriberBatchedStream};
+use futures_core::Stream;
 use futures_util::pin_mut;
 use imbl::Vector;
 use matrix_sdk::{
-    Client, deserialized_responses::SyncOrStrippedState, executor::AbortOnDrop, locks::Mutex,
+    Client, Room, deserialized_responses::SyncOrStrippedState, executor::AbortOnDrop, locks::Mutex,
 };
+use matrix_sdk_base::sync::{RoomUpdates, State, Timeline};
 use matrix_sdk_common::executor::spawn;
 use ruma::{
-    OwnedRoomId,
+    OwnedRoomId, RoomId,
     events::{
-        SyncStateEvent,
+        StateEventType, SyncStateEvent,
         space::{child::SpaceChildEventContent, parent::SpaceParentEventContent},
     },
 };
 use tokio::sync::Mutex as AsyncMutex;
 use tracing::error;
 
-use crate::spaces::graph::SpaceGraph;
 pub use crate::spaces::{room::SpaceRoom, room_list::SpaceRoomList};
+use crate::{room_updates::next_relevant_room_updates, spaces::graph::SpaceGraph};
 
 pub mod graph;
 pub mod room;
@@ -161,6 +164,54 @@ impl SpaceService {
         spaces
     }
 
+    /// Returns the IDs of the descendants of the given space, i.e. its
+    /// children, their children, and so on, as known from the `m.space.child`
+    /// and `m.space.parent` state events of the joined spaces.
+    ///
+    /// They can be used with
+    /// [`new_filter_in_space`](crate::room_list_service::filters::new_filter_in_space)
+    /// to only show the rooms of a space in a room list.
+    pub async fn space_descendants(&self, space_id: &RoomId) -> BTreeSet<OwnedRoomId> {
+        Self::space_descendants_for(&self.client, space_id).await
+    }
+
+    /// Subscribes to the descendants of the given space, see
+    /// [`Self::space_descendants`].
+    ///
+    /// The stream yields the current descendants first, and then the new ones
+    /// every time they change, e.g. when a room is added to or removed from
+    /// the space. They are only computed again when the room updates touch a
+    /// space, contain `m.space.child` or `m.space.parent` state events, or
+    /// when a room is left.
+    pub fn subscribe_to_space_descendants(
+        &self,
+        space_id: OwnedRoomId,
+    ) -> impl Stream<Item = BTreeSet<OwnedRoomId>> + use<> {
+        let client = self.client.clone();
+        let mut all_room_updates_receiver = self.client.subscribe_to_all_room_updates();
+
+        stream! {
+            let mut descendants = Self::space_descendants_for(&client, &space_id).await;
+            yield descendants.clone();
+
+            // When some updates have been missed, the descendants are computed again to be
+            // sure.
+            while next_relevant_room_updates(&mut all_room_updates_receiver, |updates| {
+                may_change_space_graph(&client, updates)
+            })
+            .await
+            .is_some()
+            {
+                let new_descendants = Self::space_descendants_for(&client, &space_id).await;
+
+                if new_descendants != descendants {
+                    descendants = new_descendants;
+                    yield descendants.clone();
+                }
+            }
+        }
+    }
+
     /// Returns a `SpaceRoomList` for the given space ID.
     pub async fn space_room_list(&self, space_id: OwnedRoomId) -> SpaceRoomList {
         SpaceRoomList::new(self.client.clone(), space_id).await
@@ -180,16 +231,49 @@ impl SpaceService {
 
     async fn joined_spaces_for(client: &Client) -> Vec<SpaceRoom> {
         let joined_spaces = client.joined_space_rooms();
+        let graph = Self::space_graph_for(&joined_spaces).await;
+
+        let root_nodes = graph.root_nodes();
 
+        joined_spaces
+            .iter()
+            .filter_map(|room| {
+                let room_id = room.room_id();
+
+                if root_nodes.contains(&room_id) {
+                    Some(SpaceRoom::new_from_known(room, graph.children_of(room_id).len() as u64))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    async fn space_descendants_for(client: &Client, space_id: &RoomId) -> BTreeSet<OwnedRoomId> {
+        // The rooms which aren't spaces can also declare their parents, so all the
+        // joined rooms are part of the graph.
+        Self::space_graph_for(&client.joined_rooms()).await.descendants_of(space_id)
+    }
+
+    /// Build the graph of the given joined rooms.
+    ///
+    /// The spaces are the nodes of the graph, with the edges of their
+    /// `m.space.parent` and `m.space.child` state events. The other rooms only
+    /// add the edges of their `m.space.parent` state events.
+    async fn space_graph_for(joined_rooms: &[Room]) -> SpaceGraph {
         // Build a graph to hold the parent-child relations
         let mut graph = SpaceGraph::new();
 
-        // Iterate over all joined spaces and populate the graph with edges based
+        // Iterate over all joined rooms and populate the graph with edges based
         // on `m.space.parent` and `m.space.child` state events.
-        for space in joined_spaces.iter() {
-            graph.add_node(space.room_id().to_owned());
+        for room in joined_rooms.iter() {
+            let is_space = room.is_space();
 
-            if let Ok(parents) = space.get_state_events_static::<SpaceParentEventContent>().await {
+            if is_space {
+                graph.add_node(room.room_id().to_owned());
+            }
+
+            if let Ok(parents) = room.get_state_events_static::<SpaceParentEventContent>().await {
                 parents.into_iter()
                 .flat_map(|parent_event| match parent_event.deserialize() {
                     Ok(SyncOrStrippedState::Sync(SyncStateEvent::Original(e))) => {
@@ -198,15 +282,19 @@ impl SpaceService {
                     Ok(SyncOrStrippedState::Sync(SyncStateEvent::Redacted(_))) => None,
                     Ok(SyncOrStrippedState::Stripped(e)) => Some(e.state_key),
                     Err(e) => {
-                        error!(room_id = ?space.room_id(), "Could not deserialize m.space.parent: {e}");
+                        error!(room_id = ?room.room_id(), "Could not deserialize m.space.parent: {e}");
                         None
                     }
-                }).for_each(|parent| graph.add_edge(parent, space.room_id().to_owned()));
+                }).for_each(|parent| graph.add_edge(parent, room.room_id().to_owned()));
             } else {
-                error!(room_id = ?space.room_id(), "Could not get m.space.parent events");
+                error!(room_id = ?room.room_id(), "Could not get m.space.parent events");
+            }
+
+            if !is_space {
+                continue;
             }
 
-            if let Ok(children) = space.get_state_events_static::<SpaceChildEventContent>().await {
+            if let Ok(children) = room.get_state_events_static::<SpaceChildEventContent>().await {
                 children.into_iter()
                 .filter_map(|child_event| match child_event.deserialize() {
                     Ok(SyncOrStrippedState::Sync(SyncStateEvent::Original(e))) => {
@@ -215,12 +303,12 @@ impl SpaceService {
                     Ok(SyncOrStrippedState::Sync(SyncStateEvent::Redacted(_))) => None,
                     Ok(SyncOrStrippedState::Stripped(e)) => Some(e.state_key),
                     Err(e) => {
-                        error!(room_id = ?space.room_id(), "Could not deserialize m.space.child: {e}");
+                        error!(room_id = ?room.room_id(), "Could not deserialize m.space.child: {e}");
                         None
                     }
-                }).for_each(|child| graph.add_edge(space.room_id().to_owned(), child));
+                }).for_each(|child| graph.add_edge(room.room_id().to_owned(), child));
             } else {
-                error!(room_id = ?space.room_id(), "Could not get m.space.child events");
+                error!(room_id = ?room.room_id(), "Could not get m.space.child events");
             }
         }
 
@@ -228,21 +316,33 @@ impl SpaceService {
         // enforced backend side.
         graph.remove_cycles();
 
-        let root_nodes = graph.root_nodes();
+        graph
+    }
+}
 
-        joined_spaces
-            .iter()
-            .filter_map(|room| {
-                let room_id = room.room_id();
+/// Whether the room updates may change the graph of the spaces, i.e. if they
+/// touch a space, contain `m.space.child` or `m.space.parent` state events, or
+/// if a room has been left.
+fn may_change_space_graph(client: &Client, updates: &RoomUpdates) -> bool {
+    let is_space_event = |event_type: Option<StateEventType>| {
+        matches!(event_type, Some(StateEventType::SpaceChild | StateEventType::SpaceParent))
+    };
 
-                if root_nodes.contains(&room_id) {
-                    Some(SpaceRoom::new_from_known(room, graph.children_of(room_id).len() as u64))
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
+    let has_space_events = |state: &State, timeline: &Timeline| {
+        let (State::Before(state_events) | State::After(state_events)) = state;
+
+        state_events.iter().any(|event| is_space_event(event.get_field("type").ok().flatten()))
+            || timeline
+                .events
+                .iter()
+                .any(|event| is_space_event(event.raw().get_field("type").ok().flatten()))
+    };
+
+    !updates.left.is_empty()
+        || updates
+            .iter_all_room_ids()
+            .any(|room_id| client.get_room(room_id).is_some_and(|room| room.is_space()))
+        || updates.joined.values().any(|update| has_space_events(&update.state, &update.timeline))
 }
 
 #[cfg(test)]
@@ -491,4 +591,73 @@ mod tests {
             vec![SpaceRoom::new_from_known(&client.get_room(first_space_id).unwrap(), 0)]
         );
     }
+
+    #[async_test]
+    async fn test_space_descendants() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+        let user_id = client.user_id().unwrap();
+        let space_service = SpaceService::new(client.clone());
+        let factory = EventFactory::new();
+
+        server.mock_room_state_encryption().plain().mount().await;
+
+        let space_id = room_id!("!space:example.org");
+        let child_id = room_id!("!child:example.org");
+        let room_with_parent_id = room_id!("!room_with_parent:example.org");
+
+        // A space with one child, declared by the space.
+        server
+            .sync_room(
+                &client,
+                JoinedRoomBuilder::new(space_id)
+                    .add_state_event(factory.create(user_id, RoomVersionId::V1).with_space_type())
+                    .add_state_event(
+                        factory
+                            .space_child(space_id.to_owned(), child_id.to_owned())
+                            .sender(user_id),
+                    ),
+            )
+            .await;
+
+        let descendants_stream = space_service.subscribe_to_space_descendants(space_id.to_owned());
+        pin_mut!(descendants_stream);
+
+        assert_next_eq!(descendants_stream, BTreeSet::from([child_id.to_owned()]));
+
+        // Joining a room which isn't related to any space doesn't yield anything.
+        server
+            .sync_room(
+                &client,
+                JoinedRoomBuilder::new(room_id!("!room:example.org"))
+                    .add_state_event(factory.create(user_id, RoomVersionId::V1)),
+            )
+            .await;
+
+        assert_pending!(descendants_stream);
+
+        // A room which isn't a space, declaring the space as its parent, is a
+        // descendant of the space.
+        server
+            .sync_room(
+                &client,
+                JoinedRoomBuilder::new(room_with_parent_id)
+                    .add_state_event(factory.create(user_id, RoomVersionId::V1))
+                    .add_state_event(
+                        factory
+                            .space_parent(space_id.to_owned(), room_with_parent_id.to_owned())
+                            .sender(user_id),
+                    ),
+            )
+            .await;
+
+        assert_next_eq!(
+            descendants_stream,
+            BTreeSet::from([child_id.to_owned(), room_with_parent_id.to_owned()])
+        );
+        assert_eq!(
+            space_service.space_descendants(space_id).await,
+            BTreeSet::from([child_id.to_owned(), room_with_parent_id.to_owned()])
+        );
+    }
 }
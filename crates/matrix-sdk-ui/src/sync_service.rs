@@ -23,21 +23,31 @@
 //! user should call [`SyncService::start()`] again to restart the room list
 //! sync, if that is not desirable, the offline support for the [`SyncService`]
 //! may be enabled using the [`SyncServiceBuilder::with_offline_mode`] setting.
-
-use std::{sync::Arc, time::Duration};
+//!
+//! Applications knowing when the network is unavailable, e.g. because the
+//! operating system reports it, should tell it to the [`SyncService`] with
+//! [`SyncService::set_network_available`], so that it doesn't retry in vain.
+
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+    time::Duration,
+};
 
 use eyeball::{SharedObservable, Subscriber};
+use futures_core::Stream;
 use futures_util::{
     StreamExt as _,
     future::{Either, select},
     pin_mut,
 };
 use matrix_sdk::{
-    Client,
-    config::RequestConfig,
+    Client, HttpError, RumaApiError,
+    config::{RequestConfig, SyncSettings},
     executor::{JoinHandle, spawn},
-    sleep::sleep,
 };
+use ruma::api::client::error::{ErrorBody, ErrorKind, RetryAfter};
 use thiserror::Error;
 use tokio::sync::{
     Mutex as AsyncMutex, OwnedMutexGuard,
@@ -47,7 +57,7 @@ use tracing::{Instrument, Level, Span, error, info, instrument, trace, warn};
 
 use crate::{
     encryption_sync_service::{self, EncryptionSyncPermit, EncryptionSyncService, WithLocking},
-    room_list_service::{self, RoomListService},
+    room_list_service::{self, RoomListService, RoomListServiceSettings},
 };
 
 /// Current state of the application.
@@ -92,9 +102,254 @@ pub enum State {
     ///
     /// Calling [`SyncService::stop()`] will abort the offline mode and the
     /// [`SyncService`] will go into the [`State::Idle`] mode.
+    ///
+    /// The [`SyncService`] also enters this state, whether the offline mode is
+    /// enabled or not, when the network is reported as unavailable with
+    /// [`SyncService::set_network_available`]. It doesn't check if the server
+    /// is available until the network is reported as available again, and
+    /// then it attempts to sync immediately.
     Offline,
 }
 
+impl State {
+    /// Whether this is the same state as `other`, i.e. the same variant, and
+    /// the same error for [`State::Error`].
+    fn is_same_as(&self, other: &State) -> bool {
+        match (self, other) {
+            (State::Idle, State::Idle)
+            | (State::Running, State::Running)
+            | (State::Terminated, State::Terminated)
+            | (State::Offline, State::Offline) => true,
+            (State::Error(error), State::Error(other_error)) => Arc::ptr_eq(error, other_error),
+            _ => false,
+        }
+    }
+}
+
+/// A subscriber to the [`State`] of a [`SyncService`], as returned by
+/// [`SyncService::state`].
+///
+/// The state is derived from the [`SyncServiceStatus`] of the service, so that
+/// both always agree. This type implements [`Stream`], which only yields the
+/// changes of the state, not the other changes of the status.
+#[derive(Debug)]
+pub struct StateSubscriber {
+    statuses: Subscriber<SyncServiceStatus>,
+    last_state: State,
+}
+
+impl StateSubscriber {
+    fn new(statuses: Subscriber<SyncServiceStatus>) -> Self {
+        let last_state = statuses.get().state;
+        Self { statuses, last_state }
+    }
+
+    /// Get the current state.
+    pub fn get(&self) -> State {
+        self.statuses.get().state
+    }
+}
+
+impl Stream for StateSubscriber {
+    type Item = State;
+
+    fn poll_next(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let Some(status) = ready!(self.statuses.poll_next_unpin(context)) else {
+                return Poll::Ready(None);
+            };
+
+            if !status.state.is_same_as(&self.last_state) {
+                self.last_state = status.state.clone();
+                return Poll::Ready(Some(status.state));
+            }
+        }
+    }
+}
+
+/// The state of the [`SyncService`], with details about the errors it ran into
+/// and its attempts to reach the server, e.g. to show meaningful connectivity
+/// banners.
+///
+/// This can be observed with [`SyncService::status`].
+#[derive(Clone, Debug)]
+pub struct SyncServiceStatus {
+    /// The current state of the [`SyncService`], as observed with
+    /// [`SyncService::state`].
+    pub state: State,
+
+    /// The kind of the last error the [`SyncService`] ran into, either when
+    /// syncing, or when checking whether the server is available in the
+    /// offline mode.
+    ///
+    /// It's reset once the [`SyncService`] is in the [`State::Running`] state
+    /// again.
+    pub last_error: Option<SyncErrorKind>,
+
+    /// The delay before the next attempt to reach the server, in the offline
+    /// mode.
+    ///
+    /// It's only set while the [`SyncService`] is in the [`State::Offline`]
+    /// state and waits before checking again whether the server is available.
+    pub backoff: Option<Duration>,
+}
+
+/// The kind of an error the [`SyncService`] ran into, see
+/// [`SyncServiceStatus::last_error`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncErrorKind {
+    /// The access token has expired or isn't known by the server.
+    ///
+    /// If `soft_logout` is `true`, the user can log in again to the same
+    /// session, otherwise the session is gone.
+    TokenExpired {
+        /// Whether the session can be restored by logging in again.
+        soft_logout: bool,
+    },
+
+    /// The server couldn't be reached, e.g. because of a connection failure or
+    /// a timeout.
+    Network,
+
+    /// The server responded with an error in the 5xx range.
+    Server {
+        /// The HTTP status code of the response.
+        status_code: u16,
+    },
+
+    /// The server asked to send fewer requests.
+    RateLimited {
+        /// How long the server asked to wait before sending another request,
+        /// if specified.
+        retry_after: Option<Duration>,
+    },
+
+    /// Any other error.
+    Other,
+}
+
+impl SyncErrorKind {
+    fn from_error(error: &Error) -> Self {
+        match error {
+            Error::RoomList(room_list_service::Error::SlidingSync(error))
+            | Error::EncryptionSync(encryption_sync_service::Error::SlidingSync(error))
+            | Error::SyncV2(error) => Self::from_sdk_error(error),
+            _ => Self::Other,
+        }
+    }
+
+    fn from_sdk_error(error: &matrix_sdk::Error) -> Self {
+        match error {
+            matrix_sdk::Error::Http(error) => Self::from_http_error(error),
+            _ => Self::Other,
+        }
+    }
+
+    fn from_http_error(error: &HttpError) -> Self {
+        let status_code = match error.as_ruma_api_error() {
+            Some(RumaApiError::ClientApi(error)) => {
+                if let ErrorBody::Standard { kind, .. } = &error.body {
+                    match kind {
+                        ErrorKind::UnknownToken { soft_logout } => {
+                            return Self::TokenExpired { soft_logout: *soft_logout };
+                        }
+                        ErrorKind::LimitExceeded { retry_after } => {
+                            let retry_after =
+                                retry_after.as_ref().and_then(|retry_after| match retry_after {
+                                    RetryAfter::Delay(delay) => Some(*delay),
+                                    RetryAfter::DateTime(_) => None,
+                                });
+
+                            return Self::RateLimited { retry_after };
+                        }
+                        _ => {}
+                    }
+                }
+
+                error.status_code
+            }
+            Some(RumaApiError::Other(error)) => error.status_code,
+            Some(RumaApiError::Uiaa(_)) => return Self::Other,
+            None => {
+                return if matches!(error, HttpError::Reqwest(_)) {
+                    Self::Network
+                } else {
+                    Self::Other
+                };
+            }
+        };
+
+        if status_code.as_u16() == 429 {
+            Self::RateLimited { retry_after: None }
+        } else if status_code.is_server_error() {
+            Self::Server { status_code: status_code.as_u16() }
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// The [`SyncServiceStatus`] of the [`SyncService`], from which its [`State`]
+/// is derived.
+#[derive(Clone)]
+struct ObservableState {
+    status: SharedObservable<SyncServiceStatus>,
+}
+
+impl ObservableState {
+    fn new() -> Self {
+        Self {
+            status: SharedObservable::new(SyncServiceStatus {
+                state: State::Idle,
+                last_error: None,
+                backoff: None,
+            }),
+        }
+    }
+
+    fn get(&self) -> State {
+        self.status.get().state
+    }
+
+    fn set(&self, state: State) {
+        self.status.update(|status| {
+            match &state {
+                State::Running => status.last_error = None,
+                State::Error(error) => status.last_error = Some(SyncErrorKind::from_error(error)),
+                State::Idle | State::Terminated | State::Offline => {}
+            }
+
+            status.backoff = None;
+            status.state = state;
+        });
+    }
+
+    /// Remember the last error, without changing the state.
+    fn set_last_error(&self, error: &Error) {
+        self.status.update(|status| status.last_error = Some(SyncErrorKind::from_error(error)));
+    }
+
+    /// Report that a check of the availability of the server failed, and when
+    /// the next one will happen.
+    fn set_backoff(&self, error: SyncErrorKind, backoff: Duration) {
+        self.status.update(|status| {
+            status.last_error = Some(error);
+            status.backoff = Some(backoff);
+        });
+    }
+}
+
+/// The delay before checking again if the server is available, after a first
+/// failed check in the offline mode.
+///
+/// The delay is doubled after each failed check, up to
+/// [`OFFLINE_CHECK_MAX_DELAY`].
+const OFFLINE_CHECK_INITIAL_DELAY: Duration = Duration::from_millis(100);
+
+/// The maximum delay between two checks of the availability of the server in
+/// the offline mode.
+const OFFLINE_CHECK_MAX_DELAY: Duration = Duration::from_secs(10);
+
 enum MaybeAcquiredPermit {
     Acquired(OwnedMutexGuard<EncryptionSyncPermit>),
     Unacquired(Arc<AsyncMutex<EncryptionSyncPermit>>),
@@ -153,9 +408,15 @@ impl SyncTaskSupervisor {
     /// If the `/_matrix/client/versions` request succeeds, the function exits
     /// without a termination report. If we receive a [`TerminationReport`] from
     /// the user, we exit immediately and return the termination report.
+    ///
+    /// While the network is reported as unavailable, no request is sent: the
+    /// function waits for the network to be reported as available again, and
+    /// exits immediately at this point.
     async fn offline_check(
         client: &Client,
         receiver: &mut Receiver<TerminationReport>,
+        mut network_available: Subscriber<bool>,
+        state: &ObservableState,
     ) -> Option<TerminationReport> {
         info!("Entering the offline mode");
 
@@ -171,7 +432,10 @@ impl SyncTaskSupervisor {
                     receiver.recv().await.unwrap_or_else(TerminationReport::supervisor_error);
 
                 match report.origin {
-                    TerminationOrigin::EncryptionSync | TerminationOrigin::RoomList => {}
+                    TerminationOrigin::EncryptionSync
+                    | TerminationOrigin::RoomList
+                    | TerminationOrigin::SyncV2
+                    | TerminationOrigin::NetworkUnavailable => {}
                     // Since the sync service aren't running anymore, we can only receive a report
                     // from the supervisor. It would have probably made sense to have separate
                     // channels for reports the sync services send and the user can send using the
@@ -182,7 +446,21 @@ impl SyncTaskSupervisor {
         };
 
         let wait_to_be_online = async move {
+            let mut delay = OFFLINE_CHECK_INITIAL_DELAY;
+
             loop {
+                // Don't send any request while the network is known to be unavailable. Once
+                // it's back, let's try to sync immediately.
+                if !network_available.get() {
+                    while let Some(available) = network_available.next().await {
+                        if available {
+                            break;
+                        }
+                    }
+
+                    break;
+                }
+
                 // Encountering network failures when sending a request which has with no retry
                 // limit set in the `RequestConfig` are treated as permanent failures and our
                 // exponential backoff doesn't kick in.
@@ -196,10 +474,31 @@ impl SyncTaskSupervisor {
                 // error in the 5xx range of HTTP errors are considered to be transient.
                 //
                 // Still, as a precaution, we're going to sleep here for a while in the Error
-                // case.
+                // case, for longer and longer.
                 match client.fetch_server_versions(Some(request_config)).await {
                     Ok(_) => break,
-                    Err(_) => sleep(Duration::from_millis(100)).await,
+                    Err(error) => {
+                        let error = SyncErrorKind::from_http_error(&error);
+
+                        // Wait at least as long as the server asked to.
+                        let backoff = match &error {
+                            SyncErrorKind::RateLimited { retry_after: Some(retry_after) } => {
+                                delay.max(*retry_after)
+                            }
+                            _ => delay,
+                        };
+
+                        state.set_backoff(error, backoff);
+
+                        // Don't wait for the whole delay if the availability of the network changes
+                        // in the meantime.
+                        let sleep = client.clock().sleep(backoff);
+                        let network_change = network_available.next();
+                        pin_mut!(sleep, network_change);
+                        select(sleep, network_change).await;
+
+                        delay = (delay * 2).min(OFFLINE_CHECK_MAX_DELAY);
+                    }
                 }
             }
         };
@@ -245,76 +544,91 @@ impl SyncTaskSupervisor {
             MaybeAcquiredPermit::Acquired(encryption_sync_permit.clone().lock_owned().await);
 
         let offline_mode = inner.with_offline_mode;
+        let sync_v2_fallback = inner.with_sync_v2_fallback;
+        let uses_sync_v2 = inner.uses_sync_v2.clone();
+        let network_available = inner.network_available.clone();
         let parent_span = inner.parent_span.clone();
 
         let future = async move {
             loop {
-                let (room_list_task, encryption_sync_task) = Self::spawn_child_tasks(
-                    room_list_service.clone(),
-                    encryption_sync.clone(),
-                    sync_permit_guard,
-                    sender.clone(),
-                    parent_span.clone(),
-                )
-                .await;
-
-                sync_permit_guard = MaybeAcquiredPermit::Unacquired(encryption_sync_permit.clone());
-
-                let report = if let Some(report) = receiver.recv().await {
-                    report
-                } else {
-                    info!("internal channel has been closed?");
-                    // We should still stop the child tasks in the unlikely scenario that our
-                    // receiver died.
-                    TerminationReport::supervisor_error()
-                };
-
-                // If one service failed, make sure to request stopping the other one.
-                let (stop_room_list, stop_encryption) = match &report.origin {
-                    TerminationOrigin::EncryptionSync => (true, false),
-                    TerminationOrigin::RoomList => (false, true),
-                    TerminationOrigin::Supervisor => (true, true),
-                };
+                // If the network is known to be unavailable, wait for it to come back before
+                // syncing.
+                if !network_available.get() {
+                    // Don't hold the permit while waiting.
+                    sync_permit_guard =
+                        MaybeAcquiredPermit::Unacquired(encryption_sync_permit.clone());
+
+                    // The state may have already been set when starting the service.
+                    if !matches!(state.get(), State::Offline) {
+                        state.set(State::Offline);
+                    }
 
-                // Stop both services, and wait for the streams to properly finish: at some
-                // point they'll return `None` and will exit their infinite loops, and their
-                // tasks will gracefully terminate.
+                    let client = room_list_service.client();
 
-                if stop_room_list {
-                    if let Err(err) = room_list_service.stop_sync() {
-                        warn!(?report, "unable to stop room list service: {err:#}");
+                    if let Some(report) = Self::offline_check(
+                        client,
+                        &mut receiver,
+                        network_available.subscribe(),
+                        &state,
+                    )
+                    .await
+                    {
+                        if let Some(error) = report.error {
+                            state.set(State::Error(Arc::new(error)));
+                        } else {
+                            state.set(State::Idle);
+                        }
+                        break;
                     }
 
-                    if report.has_expired() {
-                        room_list_service.expire_sync_session().await;
-                    }
+                    state.set(State::Running);
                 }
 
-                if let Err(err) = room_list_task.await {
-                    error!("when awaiting room list service: {err:#}");
-                }
+                let report = if uses_sync_v2.get() {
+                    Self::run_sync_v2(&room_list_service, sync_permit_guard, &mut receiver)
+                        .instrument(parent_span.clone())
+                        .await
+                } else {
+                    Self::run_sliding_syncs(
+                        &room_list_service,
+                        &encryption_sync,
+                        sync_permit_guard,
+                        &sender,
+                        &mut receiver,
+                        parent_span.clone(),
+                    )
+                    .await
+                };
 
-                if stop_encryption {
-                    if let Err(err) = encryption_sync.stop_sync() {
-                        warn!(?report, "unable to stop encryption sync: {err:#}");
-                    }
+                sync_permit_guard = MaybeAcquiredPermit::Unacquired(encryption_sync_permit.clone());
+
+                if sync_v2_fallback && report.is_sliding_sync_unsupported() {
+                    info!("The server doesn't support sliding sync, falling back to sync v2");
+                    uses_sync_v2.set(true);
 
-                    if report.has_expired() {
-                        encryption_sync.expire_sync_session().await;
+                    if Self::drop_child_task_reports(&mut receiver) {
+                        state.set(State::Idle);
+                        break;
                     }
-                }
 
-                if let Err(err) = encryption_sync_task.await {
-                    error!("when awaiting encryption sync: {err:#}");
+                    continue;
                 }
 
                 if let Some(error) = report.error {
                     if offline_mode {
+                        state.set_last_error(&error);
                         state.set(State::Offline);
 
                         let client = room_list_service.client();
 
-                        if let Some(report) = Self::offline_check(client, &mut receiver).await {
+                        if let Some(report) = Self::offline_check(
+                            client,
+                            &mut receiver,
+                            network_available.subscribe(),
+                            &state,
+                        )
+                        .await
+                        {
                             if let Some(error) = report.error {
                                 state.set(State::Error(Arc::new(error)));
                             } else {
@@ -328,6 +642,14 @@ impl SyncTaskSupervisor {
                         state.set(State::Error(Arc::new(error)));
                         break;
                     }
+                } else if matches!(report.origin, TerminationOrigin::NetworkUnavailable) {
+                    if Self::drop_child_task_reports(&mut receiver) {
+                        state.set(State::Idle);
+                        break;
+                    }
+
+                    // The loop waits for the network to come back before syncing again.
+                    continue;
                 } else if matches!(report.origin, TerminationOrigin::Supervisor) {
                     state.set(State::Idle);
                     break;
@@ -344,6 +666,139 @@ impl SyncTaskSupervisor {
         (task, termination_sender)
     }
 
+    /// Run the sliding syncs of the room list and of the encryption, until one
+    /// of them terminates or a [`TerminationReport`] is received, and stop
+    /// both of them.
+    async fn run_sliding_syncs(
+        room_list_service: &Arc<RoomListService>,
+        encryption_sync: &Arc<EncryptionSyncService>,
+        sync_permit_guard: MaybeAcquiredPermit,
+        sender: &Sender<TerminationReport>,
+        receiver: &mut Receiver<TerminationReport>,
+        parent_span: Span,
+    ) -> TerminationReport {
+        let (room_list_task, encryption_sync_task) = Self::spawn_child_tasks(
+            room_list_service.clone(),
+            encryption_sync.clone(),
+            sync_permit_guard,
+            sender.clone(),
+            parent_span,
+        )
+        .await;
+
+        let report = if let Some(report) = receiver.recv().await {
+            report
+        } else {
+            info!("internal channel has been closed?");
+            // We should still stop the child tasks in the unlikely scenario that our
+            // receiver died.
+            TerminationReport::supervisor_error()
+        };
+
+        // If one service failed, make sure to request stopping the other one.
+        let (stop_room_list, stop_encryption) = match &report.origin {
+            TerminationOrigin::EncryptionSync => (true, false),
+            TerminationOrigin::RoomList => (false, true),
+            TerminationOrigin::Supervisor
+            | TerminationOrigin::NetworkUnavailable
+            | TerminationOrigin::SyncV2 => (true, true),
+        };
+
+        // Stop both services, and wait for the streams to properly finish: at some
+        // point they'll return `None` and will exit their infinite loops, and their
+        // tasks will gracefully terminate.
+
+        if stop_room_list {
+            if let Err(err) = room_list_service.stop_sync() {
+                warn!(?report, "unable to stop room list service: {err:#}");
+            }
+
+            if report.has_expired() {
+                room_list_service.expire_sync_session().await;
+            }
+        }
+
+        if let Err(err) = room_list_task.await {
+            error!("when awaiting room list service: {err:#}");
+        }
+
+        if stop_encryption {
+            if let Err(err) = encryption_sync.stop_sync() {
+                warn!(?report, "unable to stop encryption sync: {err:#}");
+            }
+
+            if report.has_expired() {
+                encryption_sync.expire_sync_session().await;
+            }
+        }
+
+        if let Err(err) = encryption_sync_task.await {
+            error!("when awaiting encryption sync: {err:#}");
+        }
+
+        report
+    }
+
+    /// Run a sync v2 `/sync` loop instead of the sliding syncs, until it fails
+    /// or a [`TerminationReport`] is received from the user.
+    ///
+    /// The `/sync` responses are processed by the [`Client`], so they feed the
+    /// rooms of the [`RoomListService`] and the event cache as the sliding
+    /// syncs would. They also contain the to-device events and the end-to-end
+    /// encryption data, so the encryption sync isn't needed, but the permit
+    /// must be held while they're processed.
+    async fn run_sync_v2(
+        room_list_service: &RoomListService,
+        sync_permit_guard: MaybeAcquiredPermit,
+        receiver: &mut Receiver<TerminationReport>,
+    ) -> TerminationReport {
+        let _sync_permit_guard = sync_permit_guard.acquire().await;
+
+        let sync = async {
+            let sync_stream = room_list_service.client().sync_stream(SyncSettings::default()).await;
+            pin_mut!(sync_stream);
+
+            loop {
+                match sync_stream.next().await {
+                    Some(Ok(_)) => room_list_service.set_synced_with_sync_v2(),
+                    Some(Err(error)) => {
+                        error!("Error while syncing with sync v2 in sync service: {error:#}");
+                        break TerminationReport::sync_v2(Some(error));
+                    }
+                    None => break TerminationReport::sync_v2(None),
+                }
+            }
+        };
+
+        let wait_for_termination_report = async {
+            loop {
+                // No child task is running, but there may be stale reports from the sliding
+                // syncs which ran before.
+                let report =
+                    receiver.recv().await.unwrap_or_else(TerminationReport::supervisor_error);
+
+                match report.origin {
+                    TerminationOrigin::EncryptionSync
+                    | TerminationOrigin::RoomList
+                    | TerminationOrigin::SyncV2 => {}
+                    TerminationOrigin::Supervisor | TerminationOrigin::NetworkUnavailable => {
+                        break report;
+                    }
+                }
+            }
+        };
+
+        pin_mut!(sync, wait_for_termination_report);
+
+        let report = match select(sync, wait_for_termination_report).await {
+            Either::Left((report, _)) | Either::Right((report, _)) => report,
+        };
+
+        room_list_service.set_sync_v2_terminated(report.error.is_some());
+
+        report
+    }
+
     async fn spawn_child_tasks(
         room_list_service: Arc<RoomListService>,
         encryption_sync_service: Arc<EncryptionSyncService>,
@@ -443,6 +898,33 @@ impl SyncTaskSupervisor {
         }
     }
 
+    /// Drop the reports sent by the child tasks when they have been stopped,
+    /// so that they aren't mistaken for the reports of the next child tasks.
+    ///
+    /// Returns `true` if the user has asked to stop the service in the
+    /// meantime.
+    fn drop_child_task_reports(receiver: &mut Receiver<TerminationReport>) -> bool {
+        let mut has_been_stopped = false;
+
+        while let Ok(report) = receiver.try_recv() {
+            if matches!(report.origin, TerminationOrigin::Supervisor) {
+                has_been_stopped = true;
+            }
+        }
+
+        has_been_stopped
+    }
+
+    /// Stop the sync tasks because the network has been reported as
+    /// unavailable, and wait for it to come back.
+    async fn park(&self) {
+        if let Err(err) =
+            self.termination_sender.send(TerminationReport::network_unavailable()).await
+        {
+            error!("Couldn't send the termination report to the supervisor task: {err}");
+        }
+    }
+
     async fn shutdown(self) {
         match self.termination_sender.send(TerminationReport::supervisor()).await {
             Ok(_) => {
@@ -473,8 +955,21 @@ struct SyncServiceInner {
     /// The offline mode is described in the [`State::Offline`] enum variant.
     with_offline_mode: bool,
 
+    /// Is the fallback to sync v2 enabled?
+    ///
+    /// See [`SyncServiceBuilder::with_sync_v2_fallback`].
+    with_sync_v2_fallback: bool,
+
+    /// Has the [`SyncService`] fallen back to sync v2, because the server
+    /// doesn't support sliding sync?
+    uses_sync_v2: SharedObservable<bool>,
+
+    /// Is the network available, as reported by
+    /// [`SyncService::set_network_available`]?
+    network_available: SharedObservable<bool>,
+
     /// What's the state of this sync service?
-    state: SharedObservable<State>,
+    state: ObservableState,
 
     /// The parent tracing span to use for the tasks within this service.
     ///
@@ -504,7 +999,9 @@ impl SyncServiceInner {
 
         self.supervisor =
             Some(SyncTaskSupervisor::new(self, room_list_service, encryption_sync_permit).await);
-        self.state.set(State::Running);
+
+        // The supervisor waits for the network to be available before syncing.
+        self.state.set(if self.network_available.get() { State::Running } else { State::Offline });
     }
 
     async fn stop(&mut self) {
@@ -535,7 +1032,9 @@ impl SyncServiceInner {
 /// synchronization tasks for you.
 ///
 /// **Note**: The [`SyncService`] requires a server with support for [MSC4186],
-/// otherwise it will fail with an 404 `M_UNRECOGNIZED` request error.
+/// otherwise it will fail with an 404 `M_UNRECOGNIZED` request error, unless
+/// the fallback to sync v2 is enabled with
+/// [`SyncServiceBuilder::with_sync_v2_fallback`].
 ///
 /// [MSC4186]: https://github.com/matrix-org/matrix-spec-proposals/pull/4186/
 ///
@@ -588,7 +1087,7 @@ pub struct SyncService {
     /// [`SyncServiceInner`] struct, but it should not be modified in this
     /// struct. It's re-exposed here so we can subscribe to the state without
     /// taking the lock on the `inner` field.
-    state: SharedObservable<State>,
+    state: ObservableState,
 
     /// Global lock to allow using at most one [`EncryptionSyncService`] at all
     /// times.
@@ -612,8 +1111,15 @@ impl SyncService {
     }
 
     /// Returns the state of the sync service.
-    pub fn state(&self) -> Subscriber<State> {
-        self.state.subscribe()
+    pub fn state(&self) -> StateSubscriber {
+        StateSubscriber::new(self.state.status.subscribe())
+    }
+
+    /// Returns the state of the sync service, with details about the last
+    /// error it ran into and the delay before its next attempt to reach the
+    /// server.
+    pub fn status(&self) -> Subscriber<SyncServiceStatus> {
+        self.state.status.subscribe()
     }
 
     /// Start (or restart) the underlying sliding syncs.
@@ -666,6 +1172,32 @@ impl SyncService {
         inner.stop().await;
     }
 
+    /// Tell the [`SyncService`] whether the network is available, e.g. when
+    /// the operating system reports a change of connectivity.
+    ///
+    /// When the network becomes unavailable, the underlying syncs are stopped
+    /// and the service enters the [`State::Offline`] state, instead of failing
+    /// and retrying in vain. When the network becomes available again, the
+    /// underlying syncs are restarted immediately.
+    ///
+    /// The network is assumed to be available until told otherwise.
+    pub async fn set_network_available(&self, available: bool) {
+        let inner = self.inner.lock().await;
+
+        if inner.network_available.set_if_not_eq(available).is_none() {
+            // Nothing has changed.
+            return;
+        }
+
+        // When offline, the supervisor is already waiting for the network to come
+        // back.
+        if !available && matches!(inner.state.get(), State::Running) {
+            if let Some(supervisor) = &inner.supervisor {
+                supervisor.park().await;
+            }
+        }
+    }
+
     /// Force expiring both sessions.
     ///
     /// This ensures that the sync service is stopped before expiring both
@@ -684,6 +1216,14 @@ impl SyncService {
         self.inner.lock().await.encryption_sync_service.expire_sync_session().await;
     }
 
+    /// Whether the [`SyncService`] has fallen back to sync v2, because the
+    /// server doesn't support sliding sync.
+    ///
+    /// See [`SyncServiceBuilder::with_sync_v2_fallback`].
+    pub async fn uses_sync_v2(&self) -> bool {
+        self.inner.lock().await.uses_sync_v2.get()
+    }
+
     /// Attempt to get a permit to use an `EncryptionSyncService` at a given
     /// time.
     ///
@@ -699,6 +1239,8 @@ enum TerminationOrigin {
     EncryptionSync,
     RoomList,
     Supervisor,
+    NetworkUnavailable,
+    SyncV2,
 }
 
 #[derive(Debug)]
@@ -737,13 +1279,36 @@ impl TerminationReport {
         Self { origin: TerminationOrigin::Supervisor, error: None }
     }
 
+    /// Create a new [`TerminationReport`] with `origin` set to
+    /// [`TerminationOrigin::NetworkUnavailable`] and `error` set to `None`.
+    fn network_unavailable() -> Self {
+        Self { origin: TerminationOrigin::NetworkUnavailable, error: None }
+    }
+
+    /// Create a new [`TerminationReport`] with `origin` set to
+    /// [`TerminationOrigin::SyncV2`] and `error` set to [`Error::SyncV2`].
+    fn sync_v2(error: Option<matrix_sdk::Error>) -> Self {
+        Self { origin: TerminationOrigin::SyncV2, error: error.map(Error::SyncV2) }
+    }
+
+    /// Check whether the termination is due to the server not supporting
+    /// sliding sync.
+    fn is_sliding_sync_unsupported(&self) -> bool {
+        match &self.error {
+            Some(Error::RoomList(room_list_service::Error::SlidingSync(error)))
+            | Some(Error::EncryptionSync(encryption_sync_service::Error::SlidingSync(error))) => {
+                error.client_api_error_kind() == Some(&ErrorKind::Unrecognized)
+            }
+            _ => false,
+        }
+    }
+
     /// Check whether the termination is due to an expired sliding sync session.
     fn has_expired(&self) -> bool {
         match &self.error {
             Some(Error::RoomList(room_list_service::Error::SlidingSync(error)))
             | Some(Error::EncryptionSync(encryption_sync_service::Error::SlidingSync(error))) => {
-                error.client_api_error_kind()
-                    == Some(&ruma::api::client::error::ErrorKind::UnknownPos)
+                error.client_api_error_kind() == Some(&ErrorKind::UnknownPos)
             }
             _ => false,
         }
@@ -772,11 +1337,19 @@ pub struct SyncServiceBuilder {
     /// The offline mode is described in the [`State::Offline`] enum variant.
     with_offline_mode: bool,
 
+    /// Is the fallback to sync v2 enabled?
+    ///
+    /// See [`SyncServiceBuilder::with_sync_v2_fallback`].
+    with_sync_v2_fallback: bool,
+
     /// Whether to turn [`SlidingSyncBuilder::share_pos`] on or off.
     ///
     /// [`SlidingSyncBuilder::share_pos`]: matrix_sdk::sliding_sync::SlidingSyncBuilder::share_pos
     with_share_pos: bool,
 
+    /// The settings of the sliding sync of the [`RoomListService`].
+    room_list_settings: RoomListServiceSettings,
+
     /// The parent tracing span to use for the tasks within this service.
     ///
     /// Normally this will be [`Span::none`], but it may be useful to assign a
@@ -791,7 +1364,9 @@ impl SyncServiceBuilder {
             client,
             with_cross_process_lock: false,
             with_offline_mode: false,
+            with_sync_v2_fallback: false,
             with_share_pos: true,
+            room_list_settings: RoomListServiceSettings::default(),
             parent_span: Span::none(),
         }
     }
@@ -820,6 +1395,22 @@ impl SyncServiceBuilder {
         self
     }
 
+    /// Enable the fallback to sync v2 for the [`SyncService`].
+    ///
+    /// When the server doesn't support sliding sync, i.e. when it responds with
+    /// an `M_UNRECOGNIZED` error, the [`SyncService`] then runs a sync v2
+    /// `/sync` loop instead of failing. The `/sync` responses feed the same
+    /// [`RoomListService`] and event cache as sliding sync, so the rooms are
+    /// listed and their timelines are updated as usual.
+    ///
+    /// With sync v2, all the rooms are synced with their full state, and the
+    /// [`RoomListServiceSettings`] and the room subscriptions of the
+    /// [`RoomListService`] have no effect.
+    pub fn with_sync_v2_fallback(mut self) -> Self {
+        self.with_sync_v2_fallback = true;
+        self
+    }
+
     /// Whether to turn [`SlidingSyncBuilder::share_pos`] on or off.
     ///
     /// [`SlidingSyncBuilder::share_pos`]: matrix_sdk::sliding_sync::SlidingSyncBuilder::share_pos
@@ -828,6 +1419,15 @@ impl SyncServiceBuilder {
         self
     }
 
+    /// Set the settings of the sliding sync of the [`RoomListService`], to
+    /// customize its required state and timeline limits.
+    ///
+    /// The settings are validated when calling [`Self::build`].
+    pub fn with_room_list_settings(mut self, settings: RoomListServiceSettings) -> Self {
+        self.room_list_settings = settings;
+        self
+    }
+
     /// Set the parent tracing span to be used for the tasks within this
     /// service.
     pub fn with_parent_span(mut self, parent_span: Span) -> Self {
@@ -845,13 +1445,17 @@ impl SyncServiceBuilder {
             client,
             with_cross_process_lock,
             with_offline_mode,
+            with_sync_v2_fallback,
             with_share_pos,
+            room_list_settings,
             parent_span,
         } = self;
 
         let encryption_sync_permit = Arc::new(AsyncMutex::new(EncryptionSyncPermit::new()));
 
-        let room_list = RoomListService::new_with_share_pos(client.clone(), with_share_pos).await?;
+        let room_list =
+            RoomListService::new_with_settings(client.clone(), with_share_pos, room_list_settings)
+                .await?;
 
         let encryption_sync = Arc::new(
             EncryptionSyncService::new(client, None, WithLocking::from(with_cross_process_lock))
@@ -859,7 +1463,7 @@ impl SyncServiceBuilder {
         );
 
         let room_list_service = Arc::new(room_list);
-        let state = SharedObservable::new(State::Idle);
+        let state = ObservableState::new();
 
         Ok(SyncService {
             state: state.clone(),
@@ -870,6 +1474,9 @@ impl SyncServiceBuilder {
                 encryption_sync_service: encryption_sync,
                 state,
                 with_offline_mode,
+                with_sync_v2_fallback,
+                uses_sync_v2: SharedObservable::new(false),
+                network_available: SharedObservable::new(true),
                 parent_span,
             })),
         })
@@ -887,6 +1494,11 @@ pub enum Error {
     #[error(transparent)]
     EncryptionSync(#[from] encryption_sync_service::Error),
 
+    /// An error received from the sync v2 `/sync` loop, when the
+    /// [`SyncService`] has fallen back to it.
+    #[error(transparent)]
+    SyncV2(matrix_sdk::Error),
+
     /// An error had occurred in the sync task supervisor, likely due to a bug.
     #[error("the supervisor channel has run into an unexpected error")]
     Supervisor,
@@ -12,15 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use matrix_sdk::{Room, executor::spawn};
+use matrix_sdk::{Client, Room, executor::spawn, room_preview::RoomPreview};
 use matrix_sdk_base::{SendOutsideWasm, SyncOutsideWasm};
 use ruma::{events::AnySyncTimelineEvent, room_version_rules::RoomVersionRules};
 use tracing::{Instrument, Span, info_span};
 
 use super::{
-    DateDividerMode, Error, Timeline, TimelineDropHandle, TimelineFocus,
+    DateDividerMode, Error, MessageEffects, ReactionOrdering, Timeline, TimelineDropHandle,
+    TimelineFocus, TimelineItemContent,
     controller::{TimelineController, TimelineSettings},
 };
 use crate::{
@@ -28,7 +29,7 @@ use crate::{
         controller::spawn_crypto_tasks,
         tasks::{
             pinned_events_task, room_event_cache_updates_task, room_send_queue_update_task,
-            thread_updates_task,
+            starred_events_task, thread_updates_task, url_previews_task,
         },
     },
     unable_to_decrypt_hook::UtdHookManager,
@@ -62,6 +63,17 @@ impl TimelineBuilder {
         }
     }
 
+    /// Create a new [`TimelineBuilder`] for a [`TimelineFocus::Preview`]
+    /// timeline, loading the `num_events` most recent events of the room of
+    /// the given preview, obtained with [`Client::get_room_preview`].
+    ///
+    /// Unlike [`TimelineBuilder::new`], the room doesn't need to be known
+    /// locally, see [`Client::get_or_create_room_for_preview`].
+    pub fn for_room_preview(client: &Client, room_preview: &RoomPreview, num_events: u16) -> Self {
+        let room = client.get_or_create_room_for_preview(room_preview);
+        Self::new(&room).with_focus(TimelineFocus::Preview { num_events })
+    }
+
     /// Sets up the initial focus for this timeline.
     ///
     /// This can be changed later on while the timeline is alive.
@@ -88,13 +100,28 @@ impl TimelineBuilder {
         self
     }
 
-    /// Chose when to insert the date separators, either in between each day
-    /// or each month.
+    /// Chose when to insert the date separators, either in between each day,
+    /// each month, or custom groups of dates, if at all.
     pub fn with_date_divider_mode(mut self, mode: DateDividerMode) -> Self {
         self.settings.date_divider_mode = mode;
         self
     }
 
+    /// Group the consecutive messages from the same sender, sent within the
+    /// given time window of each other.
+    ///
+    /// The position of each item in its group is then available with
+    /// [`EventTimelineItem::is_first_of_group`] and
+    /// [`EventTimelineItem::is_last_of_group`]. Date dividers and events
+    /// which aren't messages, like state events, end the groups.
+    ///
+    /// [`EventTimelineItem::is_first_of_group`]: super::EventTimelineItem::is_first_of_group
+    /// [`EventTimelineItem::is_last_of_group`]: super::EventTimelineItem::is_last_of_group
+    pub fn group_consecutive_events(mut self, time_window: Duration) -> Self {
+        self.settings.grouping_time_window = Some(time_window);
+        self
+    }
+
     /// Enable tracking of the fully-read marker and the read receipts on the
     /// timeline.
     pub fn track_read_marker_and_receipts(mut self) -> Self {
@@ -102,6 +129,20 @@ impl TimelineBuilder {
         self
     }
 
+    /// Only compute the read receipts of the timeline items in the window set
+    /// with [`Timeline::load_receipts_for()`], instead of the ones of every
+    /// item on every update.
+    ///
+    /// This is useful for very large rooms, where receipts are received often
+    /// and most of the items aren't displayed. Like
+    /// [`Self::track_read_marker_and_receipts()`], this enables tracking of the
+    /// fully-read marker and the read receipts.
+    pub fn lazy_load_read_receipts(mut self) -> Self {
+        self.settings.track_read_receipts = true;
+        self.settings.lazy_read_receipts = true;
+        self
+    }
+
     /// Use the given filter to choose whether to add events to the timeline.
     ///
     /// # Arguments
@@ -138,6 +179,82 @@ impl TimelineBuilder {
         self
     }
 
+    /// Add a function transforming the content of the event items of the
+    /// timeline, before they're emitted.
+    ///
+    /// This can be used to strip the tracking parameters from the links of the
+    /// messages, or to translate them, for instance. The transformers run in
+    /// the order they're added, on the content of the new items and when an
+    /// item is edited, and can be replaced later with
+    /// [`Timeline::set_content_transformers`].
+    pub fn add_content_transformer<F>(mut self, transformer: F) -> Self
+    where
+        F: Fn(&mut TimelineItemContent) + SendOutsideWasm + SyncOutsideWasm + 'static,
+    {
+        self.settings.content_transformers.push(Arc::new(transformer));
+        self
+    }
+
+    /// Collapse the consecutive membership and profile changes received
+    /// together, e.g. in the same sync response or back-pagination, into a
+    /// single [`VirtualTimelineItem::MembershipSummary`](super::VirtualTimelineItem::MembershipSummary)
+    /// item.
+    ///
+    /// A summary can be expanded back to the original items with
+    /// [`Timeline::expand_membership_summary`].
+    pub fn collapse_membership_changes(mut self) -> Self {
+        self.settings.collapse_membership_changes = true;
+        self
+    }
+
+    /// Keep the reactions of the event items in the given order, see
+    /// [`ReactionsByKeyBySender`](super::ReactionsByKeyBySender).
+    ///
+    /// By default, the keys are kept in no particular order.
+    pub fn with_reaction_ordering(mut self, ordering: ReactionOrdering) -> Self {
+        self.settings.reaction_ordering = Some(ordering);
+        self
+    }
+
+    /// Recognize the messages triggering an effect, like confetti or
+    /// fireworks, with the given mapping from their `msgtype`s to the names of
+    /// the effects.
+    ///
+    /// Their [`Message::effect()`] is then set, so the applications can play
+    /// the effect without looking at the raw `msgtype`s, while the messages are
+    /// still displayed like the other ones. Use [`MessageEffects::default()`]
+    /// to recognize the effects sent by Element.
+    ///
+    /// [`Message::effect()`]: super::Message::effect
+    pub fn with_message_effects(mut self, effects: MessageEffects) -> Self {
+        self.settings.message_effects = Some(Arc::new(effects));
+        self
+    }
+
+    /// Fetch the previews of the links of the messages, and add them to their
+    /// items, see [`EventTimelineItem::url_previews`].
+    ///
+    /// The previews are only fetched in the rooms where
+    /// [`Room::url_previews_enabled()`] is true, since fetching them sends the
+    /// links to the homeserver.
+    ///
+    /// [`EventTimelineItem::url_previews`]: super::EventTimelineItem::url_previews
+    pub fn with_url_previews(mut self) -> Self {
+        self.settings.fetch_url_previews = true;
+        self
+    }
+
+    /// Coalesce the diffs emitted by [`Timeline::subscribe()`] over the given
+    /// time window, instead of emitting them as soon as the timeline changes.
+    ///
+    /// This is useful in very active rooms, where the timeline may change many
+    /// times per second: the diffs received during the window are merged into
+    /// a single, smaller batch, so the UI is updated less often.
+    pub fn coalesce_diffs(mut self, window: Duration) -> Self {
+        self.settings.diffs_coalescing_window = Some(window);
+        self
+    }
+
     /// Whether to add events that failed to deserialize to the timeline.
     ///
     /// Defaults to `true`.
@@ -152,6 +269,7 @@ impl TimelineBuilder {
         fields(
             room_id = ?self.room.room_id(),
             track_read_receipts = self.settings.track_read_receipts,
+            lazy_read_receipts = self.settings.lazy_read_receipts,
         )
     )]
     pub async fn build(self) -> Result<Timeline, Error> {
@@ -170,6 +288,8 @@ impl TimelineBuilder {
             .ok()
             .unwrap_or_default();
 
+        let fetch_url_previews = settings.fetch_url_previews;
+
         let controller = TimelineController::new(
             room.clone(),
             focus.clone(),
@@ -187,6 +307,12 @@ impl TimelineBuilder {
             None
         };
 
+        let starred_events_join_handle = if matches!(focus, TimelineFocus::StarredEvents { .. }) {
+            Some(spawn(starred_events_task(room.starred_event_ids_stream(), controller.clone())))
+        } else {
+            None
+        };
+
         let room_update_join_handle = spawn({
             let span = info_span!(
                 parent: Span::none(),
@@ -258,6 +384,12 @@ impl TimelineBuilder {
             })
         };
 
+        let url_previews_join_handle = if fetch_url_previews {
+            Some(spawn(url_previews_task(controller.clone())))
+        } else {
+            None
+        };
+
         let crypto_drop_handles = spawn_crypto_tasks(room, controller.clone()).await;
 
         let timeline = Timeline {
@@ -268,6 +400,8 @@ impl TimelineBuilder {
                 room_update_join_handle,
                 thread_update_join_handle,
                 pinned_events_join_handle,
+                starred_events_join_handle,
+                url_previews_join_handle,
                 local_echo_listener_handle,
                 _event_cache_drop_handle: event_cache_drop,
             }),
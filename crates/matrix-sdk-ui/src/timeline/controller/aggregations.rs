@@ -44,7 +44,7 @@ use matrix_sdk::deserialized_responses::EncryptionInfo;
 use ruma::{
     MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, OwnedUserId,
     events::{
-        AnySyncTimelineEvent,
+        AnySyncTimelineEvent, location::LocationContent,
         poll::unstable_start::NewUnstablePollStartEventContentWithoutRelation,
         relation::Replacement, room::message::RoomMessageEventContentWithoutRelation,
     },
@@ -55,8 +55,9 @@ use tracing::{info, trace, warn};
 
 use super::{ObservableItemsTransaction, rfind_event_by_item_id};
 use crate::timeline::{
-    EventTimelineItem, MsgLikeContent, MsgLikeKind, PollState, ReactionInfo, ReactionStatus,
-    TimelineEventItemId, TimelineItem, TimelineItemContent,
+    EventTimelineItem, LiveLocationState, MsgLikeContent, MsgLikeKind, PollState, ReactionInfo,
+    ReactionOrdering, ReactionStatus, ReactionsByKeyBySender, TimelineEventItemId, TimelineItem,
+    TimelineItemContent,
 };
 
 #[derive(Clone)]
@@ -112,6 +113,17 @@ pub(crate) enum AggregationKind {
         end_date: MilliSecondsSinceUnixEpoch,
     },
 
+    /// This is a location update of a live location share.
+    BeaconUpdate {
+        /// The new location of the sender.
+        location: LocationContent,
+        /// Timestamp at which the location has been measured.
+        ts: MilliSecondsSinceUnixEpoch,
+    },
+
+    /// This is the marker of the end of a live location share.
+    BeaconStop,
+
     /// This is a reaction to another event.
     Reaction {
         /// The reaction "key" displayed by the client, often an emoji.
@@ -175,6 +187,25 @@ fn poll_state_from_item<'a>(
     }
 }
 
+/// Get the live location state from a given [`TimelineItemContent`].
+fn live_location_state_from_item<'a>(
+    event: &'a mut Cow<'_, EventTimelineItem>,
+) -> Result<&'a mut LiveLocationState, AggregationError> {
+    if matches!(event.content(), TimelineItemContent::LiveLocation(_)) {
+        let state = as_variant!(
+            event.to_mut().content_mut(),
+            TimelineItemContent::LiveLocation(s) => s
+        )
+        .expect("it was a live location share just above");
+        Ok(state)
+    } else {
+        Err(AggregationError::InvalidType {
+            expected: "a live location share".to_owned(),
+            actual: event.content().debug_string().to_owned(),
+        })
+    }
+}
+
 impl Aggregation {
     /// Create a new [`Aggregation`].
     pub fn new(own_id: TimelineEventItemId, kind: AggregationKind) -> Self {
@@ -225,6 +256,43 @@ impl Aggregation {
                 Err(err) => ApplyAggregationResult::Error(err),
             },
 
+            AggregationKind::BeaconUpdate { location, ts } => {
+                // Only clone the item if the location is more recent than the last known one.
+                let is_newer = match event.content() {
+                    TimelineItemContent::LiveLocation(state) => state.is_newer_location(*ts),
+                    _ => true,
+                };
+
+                if !is_newer {
+                    return ApplyAggregationResult::LeftItemIntact;
+                }
+
+                match live_location_state_from_item(event) {
+                    Ok(state) => {
+                        state.update_location(location.clone(), *ts);
+                        ApplyAggregationResult::UpdatedItem
+                    }
+                    Err(err) => ApplyAggregationResult::Error(err),
+                }
+            }
+
+            AggregationKind::BeaconStop => {
+                if matches!(
+                    event.content(),
+                    TimelineItemContent::LiveLocation(state) if state.has_been_stopped
+                ) {
+                    return ApplyAggregationResult::LeftItemIntact;
+                }
+
+                match live_location_state_from_item(event) {
+                    Ok(state) => {
+                        state.stop();
+                        ApplyAggregationResult::UpdatedItem
+                    }
+                    Err(err) => ApplyAggregationResult::Error(err),
+                }
+            }
+
             AggregationKind::Reaction { key, sender, timestamp, reaction_status } => {
                 let Some(reactions) = event.content().reactions() else {
                     // An item that can't hold any reactions.
@@ -307,6 +375,21 @@ impl Aggregation {
                 ApplyAggregationResult::Error(AggregationError::CantUndoRedaction)
             }
 
+            AggregationKind::BeaconUpdate { .. } => {
+                // Only the last location is kept, so the previous one can't be restored:
+                // keep showing the last known location.
+                ApplyAggregationResult::LeftItemIntact
+            }
+
+            AggregationKind::BeaconStop => {
+                let state = match live_location_state_from_item(event) {
+                    Ok(state) => state,
+                    Err(err) => return ApplyAggregationResult::Error(err),
+                };
+                state.has_been_stopped = false;
+                ApplyAggregationResult::UpdatedItem
+            }
+
             AggregationKind::Reaction { key, sender, .. } => {
                 let Some(reactions) = event.content().reactions() else {
                     // An item that can't hold any reactions.
@@ -356,18 +439,48 @@ pub(crate) struct Aggregations {
 
     /// Mapping of a related event identifier to its target.
     inverted_map: HashMap<TimelineEventItemId, TimelineEventItemId>,
+
+    /// How the reactions of the event items are ordered, if they are.
+    ///
+    /// This isn't cleared with the rest of the aggregations.
+    reaction_ordering: Option<ReactionOrdering>,
 }
 
 impl Aggregations {
-    /// Clear all the known aggregations from all the mappings.
+    /// Create a new manager, sorting the reactions of the items with the given
+    /// ordering, if any, every time a reaction is added or removed.
+    pub fn new(reaction_ordering: Option<ReactionOrdering>) -> Self {
+        Self { reaction_ordering, ..Default::default() }
+    }
+
+    /// Clear all the known remote aggregations from all the mappings.
+    ///
+    /// The local aggregations are kept, since they're still being sent and
+    /// won't be received again: they're applied again when the item they
+    /// relate to is added back to the timeline.
     pub fn clear(&mut self) {
-        self.related_events.clear();
-        self.inverted_map.clear();
+        self.related_events.retain(|_, aggregations| {
+            aggregations.retain(|agg| matches!(agg.own_id, TimelineEventItemId::TransactionId(_)));
+            !aggregations.is_empty()
+        });
+        self.inverted_map
+            .retain(|own_id, _| matches!(own_id, TimelineEventItemId::TransactionId(_)));
     }
 
     /// Add a given aggregation that relates to the [`TimelineItemContent`]
     /// identified by the given [`TimelineEventItemId`].
     pub fn add(&mut self, related_to: TimelineEventItemId, aggregation: Aggregation) {
+        // An aggregation may be received again, e.g. when its event is loaded again
+        // after the timeline has been reset; replace it instead of duplicating it.
+        if let Some(previous_target) = self.inverted_map.remove(&aggregation.own_id)
+            && let Some(aggregations) = self.related_events.get_mut(&previous_target)
+        {
+            aggregations.retain(|agg| agg.own_id != aggregation.own_id);
+            if aggregations.is_empty() {
+                self.related_events.remove(&previous_target);
+            }
+        }
+
         // If the aggregation is a redaction, it invalidates all the other aggregations;
         // remove them.
         if matches!(aggregation.kind, AggregationKind::Redaction) {
@@ -439,6 +552,7 @@ impl Aggregations {
             match aggregation.unapply(&mut cowed) {
                 ApplyAggregationResult::UpdatedItem => {
                     trace!("removed aggregation");
+                    self.sort_reactions_after(&aggregation, &mut cowed);
                     items.replace(
                         item_pos,
                         TimelineItem::new(cowed.into_owned(), item.internal_id.to_owned()),
@@ -493,17 +607,25 @@ impl Aggregations {
         };
 
         let mut has_edits = false;
+        let mut has_reactions = false;
 
         for a in aggregations {
             match a.apply(event, rules) {
                 ApplyAggregationResult::Edit => {
                     has_edits = true;
                 }
-                ApplyAggregationResult::UpdatedItem | ApplyAggregationResult::LeftItemIntact => {}
+                ApplyAggregationResult::UpdatedItem => {
+                    has_reactions |= matches!(a.kind, AggregationKind::Reaction { .. });
+                }
+                ApplyAggregationResult::LeftItemIntact => {}
                 ApplyAggregationResult::Error(err) => return Err(err),
             }
         }
 
+        if has_reactions {
+            self.sort_event_reactions(event);
+        }
+
         if has_edits {
             resolve_edits(aggregations, items, event);
         }
@@ -511,6 +633,41 @@ impl Aggregations {
         Ok(())
     }
 
+    /// Sort the reactions of `event` after `aggregation` has been applied or
+    /// unapplied, if it's a reaction and the reactions are ordered for this
+    /// timeline.
+    fn sort_reactions_after(
+        &self,
+        aggregation: &Aggregation,
+        event: &mut Cow<'_, EventTimelineItem>,
+    ) {
+        if matches!(aggregation.kind, AggregationKind::Reaction { .. }) {
+            self.sort_event_reactions(event);
+        }
+    }
+
+    /// Sort the reactions of `event`, if the reactions are ordered for this
+    /// timeline.
+    fn sort_event_reactions(&self, event: &mut Cow<'_, EventTimelineItem>) {
+        let Some(ordering) = self.reaction_ordering else {
+            return;
+        };
+
+        if event.content().reactions().is_some_and(|reactions| !reactions.is_sorted(ordering))
+            && let Some(reactions) = event.to_mut().content_mut().reactions_mut()
+        {
+            reactions.sort(ordering);
+        }
+    }
+
+    /// Sort `reactions` after a reaction has been added or removed, if the
+    /// reactions are ordered for this timeline.
+    pub fn sort_reactions(&self, reactions: &mut ReactionsByKeyBySender) {
+        if let Some(ordering) = self.reaction_ordering {
+            reactions.sort(ordering);
+        }
+    }
+
     /// Mark a target event as being sent (i.e. it transitions from an local
     /// transaction id to its remote event id counterpart), by updating the
     /// internal mappings.
@@ -561,6 +718,8 @@ impl Aggregations {
             match &mut found.kind {
                 AggregationKind::PollResponse { .. }
                 | AggregationKind::PollEnd { .. }
+                | AggregationKind::BeaconUpdate { .. }
+                | AggregationKind::BeaconStop
                 | AggregationKind::Edit(..)
                 | AggregationKind::Redaction => {
                     // Nothing particular to do.
@@ -737,6 +896,22 @@ pub(crate) fn find_item_and_apply_aggregation(
     target: &TimelineEventItemId,
     aggregation: Aggregation,
     rules: &RoomVersionRules,
+) -> Option<EventTimelineItem> {
+    find_item_and_apply_aggregation_then(aggregations, items, target, aggregation, rules, |item| {
+        item
+    })
+}
+
+/// Same as [`find_item_and_apply_aggregation`], but `update` is called on the
+/// item after the aggregation has been applied, before the item is replaced
+/// in the timeline, so that a single update is emitted for both changes.
+pub(crate) fn find_item_and_apply_aggregation_then(
+    aggregations: &Aggregations,
+    items: &mut ObservableItemsTransaction<'_>,
+    target: &TimelineEventItemId,
+    aggregation: Aggregation,
+    rules: &RoomVersionRules,
+    update: impl FnOnce(EventTimelineItem) -> EventTimelineItem,
 ) -> Option<EventTimelineItem> {
     let Some((idx, event_item)) = rfind_event_by_item_id(items, target) else {
         trace!("couldn't find aggregation's target {target:?}");
@@ -747,7 +922,8 @@ pub(crate) fn find_item_and_apply_aggregation(
     match aggregation.apply(&mut cowed, rules) {
         ApplyAggregationResult::UpdatedItem => {
             trace!("applied aggregation");
-            let new_event_item = cowed.into_owned();
+            aggregations.sort_reactions_after(&aggregation, &mut cowed);
+            let new_event_item = update(cowed.into_owned());
             let new_item =
                 TimelineItem::new(new_event_item.clone(), event_item.internal_id.to_owned());
             items.replace(idx, new_item);
@@ -757,7 +933,7 @@ pub(crate) fn find_item_and_apply_aggregation(
             if let Some(aggregations) = aggregations.related_events.get(target)
                 && resolve_edits(aggregations, items, &mut cowed)
             {
-                let new_event_item = cowed.into_owned();
+                let new_event_item = update(cowed.into_owned());
                 let new_item =
                     TimelineItem::new(new_event_item.clone(), event_item.internal_id.to_owned());
                 items.replace(idx, new_item);
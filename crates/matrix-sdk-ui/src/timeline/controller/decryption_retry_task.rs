@@ -29,9 +29,11 @@ use matrix_sdk::{
     event_handler::EventHandlerHandle,
     executor::{JoinHandle, spawn},
 };
+use ruma::OwnedUserId;
 use tokio::sync::{
     RwLock,
     mpsc::{self, Receiver, Sender},
+    oneshot,
 };
 use tokio_stream::{StreamExt as _, wrappers::errors::BroadcastStreamRecvError};
 use tracing::{Instrument as _, debug, error, field, info, info_span, warn};
@@ -54,6 +56,7 @@ pub(in crate::timeline) struct CryptoDropHandles {
     room_keys_received_join_handle: JoinHandle<()>,
     room_key_backup_enabled_join_handle: JoinHandle<()>,
     encryption_changes_handle: JoinHandle<()>,
+    trust_changes_join_handle: JoinHandle<()>,
 }
 
 impl Drop for CryptoDropHandles {
@@ -66,6 +69,7 @@ impl Drop for CryptoDropHandles {
         self.room_keys_received_join_handle.abort();
         self.room_key_backup_enabled_join_handle.abort();
         self.encryption_changes_handle.abort();
+        self.trust_changes_join_handle.abort();
     }
 }
 
@@ -165,6 +169,19 @@ async fn room_key_received_task<S>(
     }
 }
 
+/// The task that refreshes the encryption info of the items, whenever the
+/// devices or identities of their senders change.
+async fn trust_changes_task<S>(senders_stream: S, timeline_controller: TimelineController)
+where
+    S: Stream<Item = BTreeSet<OwnedUserId>>,
+{
+    pin_mut!(senders_stream);
+
+    while let Some(senders) = senders_stream.next().await {
+        timeline_controller.refresh_encryption_info(Some(senders)).await;
+    }
+}
+
 /// Spawn all the crypto-related tasks that are used to handle re-decryption of
 /// messages.
 pub(in crate::timeline) async fn spawn_crypto_tasks(
@@ -209,12 +226,29 @@ pub(in crate::timeline) async fn spawn_crypto_tasks(
         ))
     };
 
+    let trust_changes_join_handle = {
+        let encryption = client.encryption();
+        let logged_in_message = "We should be logged in by now, so we should have access to an \
+                                 `OlmMachine` to be able to listen to this stream";
+
+        let devices = encryption.devices_stream().await.expect(logged_in_message).map(|updates| {
+            updates.new.into_keys().chain(updates.changed.into_keys()).collect::<BTreeSet<_>>()
+        });
+        let identities =
+            encryption.user_identities_stream().await.expect(logged_in_message).map(|updates| {
+                updates.new.into_keys().chain(updates.changed.into_keys()).collect::<BTreeSet<_>>()
+            });
+
+        spawn(trust_changes_task(devices.merge(identities), controller.clone()))
+    };
+
     CryptoDropHandles {
         client,
         event_handler_handles: event_handlers,
         room_key_from_backups_join_handle,
         room_keys_received_join_handle,
         room_key_backup_enabled_join_handle,
+        trust_changes_join_handle,
         encryption_changes_handle: spawn(async move {
             controller.handle_encryption_state_changes().await
         }),
@@ -273,21 +307,63 @@ impl<P: RoomDataProvider, D: Decryptor> DecryptionRetryTask<P, D> {
         session_ids: Option<BTreeSet<String>>,
         settings: TimelineSettings,
     ) {
-        let res =
-            self.sender.send(DecryptionRetryRequest { decryptor, session_ids, settings }).await;
+        let request =
+            DecryptionRetryRequest { decryptor, session_ids, settings, report_sender: None };
+
+        if let Err(error) = self.sender.send(request).await {
+            error!("Failed to send decryption retry request: {error}");
+        }
+    }
+
+    /// Like [`DecryptionRetryTask::decrypt`], but wait for the events to have
+    /// been retried, and report how many of them could be decrypted.
+    pub(crate) async fn decrypt_and_report(
+        &self,
+        decryptor: D,
+        session_ids: Option<BTreeSet<String>>,
+        settings: TimelineSettings,
+    ) -> DecryptionRetryReport {
+        let (report_sender, report_receiver) = oneshot::channel();
+        let request = DecryptionRetryRequest {
+            decryptor,
+            session_ids,
+            settings,
+            report_sender: Some(report_sender),
+        };
 
-        if let Err(error) = res {
+        if let Err(error) = self.sender.send(request).await {
             error!("Failed to send decryption retry request: {error}");
+            return DecryptionRetryReport::default();
         }
+
+        report_receiver.await.unwrap_or_else(|_| {
+            error!("The decryption task stopped before reporting the retry");
+            DecryptionRetryReport::default()
+        })
     }
 }
 
+/// The outcome of an attempt to decrypt the unable-to-decrypt items of a
+/// timeline again.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecryptionRetryReport {
+    /// The number of unable-to-decrypt items whose decryption was retried.
+    pub num_retried: usize,
+
+    /// The number of items which could be decrypted, and aren't
+    /// unable-to-decrypt items anymore.
+    pub num_decrypted: usize,
+}
+
 /// The information sent across the channel to the long-running task requesting
 /// that the supplied set of sessions be retried.
 struct DecryptionRetryRequest<D: Decryptor> {
     decryptor: D,
     session_ids: Option<BTreeSet<String>>,
     settings: TimelineSettings,
+
+    /// Where to send the outcome of the retry, if the requester waits for it.
+    report_sender: Option<oneshot::Sender<DecryptionRetryReport>>,
 }
 
 /// Long-running task that waits for decryption requests to come through the
@@ -322,10 +398,13 @@ async fn decryption_task<P: RoomDataProvider, D: Decryptor>(
             retry_fetch_encryption_info(&mut state, retry_info_indices, &room_data_provider).await;
         }
 
+        let mut report =
+            DecryptionRetryReport { num_retried: retry_decryption_indices.len(), num_decrypted: 0 };
+
         // Retry decrypting any unable-to-decrypt messages
         if !retry_decryption_indices.is_empty() {
             debug!("Retrying decryption");
-            decrypt_by_index(
+            report.num_decrypted = decrypt_by_index(
                 &mut state,
                 &request.settings,
                 &room_data_provider,
@@ -333,7 +412,12 @@ async fn decryption_task<P: RoomDataProvider, D: Decryptor>(
                 should_retry,
                 retry_decryption_indices,
             )
-            .await
+            .await;
+        }
+
+        if let Some(report_sender) = request.report_sender {
+            // The requester may have stopped waiting for the report, so this can fail.
+            let _ = report_sender.send(report);
         }
     }
 
@@ -434,6 +518,8 @@ async fn make_replacement_for<P: RoomDataProvider>(
 
 /// Attempt decryption of the events encrypted with the session IDs in the
 /// supplied decryption `request`.
+///
+/// Returns the number of events which could be decrypted.
 async fn decrypt_by_index<P: RoomDataProvider, D: Decryptor>(
     state: &mut TimelineState<P>,
     settings: &TimelineSettings,
@@ -441,7 +527,7 @@ async fn decrypt_by_index<P: RoomDataProvider, D: Decryptor>(
     decryptor: D,
     should_retry: impl Fn(&str) -> bool,
     retry_indices: Vec<usize>,
-) {
+) -> usize {
     let push_ctx = room_data_provider.push_context().await;
     let push_ctx = push_ctx.as_ref();
     let unable_to_decrypt_hook = state.meta.unable_to_decrypt_hook.clone();
@@ -508,7 +594,7 @@ async fn decrypt_by_index<P: RoomDataProvider, D: Decryptor>(
         ))
     };
 
-    state.retry_event_decryption(retry_one, retry_indices, room_data_provider, settings).await;
+    state.retry_event_decryption(retry_one, retry_indices, room_data_provider, settings).await
 }
 
 #[cfg(test)]
@@ -655,9 +741,12 @@ mod tests {
             read_receipts: Default::default(),
             is_own: false,
             is_highlighted: false,
+            mentions_me: false,
+            url_previews: Vec::new(),
             encryption_info: None,
             original_json: None,
             latest_edit_json: None,
+            encrypted_json: None,
             origin: RemoteEventOrigin::Sync,
         });
 
@@ -696,6 +785,8 @@ mod tests {
             read_receipts: Default::default(),
             is_own: false,
             is_highlighted: false,
+            mentions_me: false,
+            url_previews: Vec::new(),
             encryption_info: Some(Arc::new(EncryptionInfo {
                 sender: owned_user_id!("@u:s.co"),
                 sender_device: None,
@@ -703,11 +794,13 @@ mod tests {
                     curve25519_key: "".to_owned(),
                     sender_claimed_keys: BTreeMap::new(),
                     session_id: Some(session_id.to_owned()),
+                    forwarding_curve25519_key_chain: Vec::new(),
                 },
                 verification_state: VerificationState::Verified,
             })),
             original_json: None,
             latest_edit_json: None,
+            encrypted_json: None,
             origin: RemoteEventOrigin::Sync,
         });
 
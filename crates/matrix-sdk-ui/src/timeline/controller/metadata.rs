@@ -14,7 +14,9 @@
 
 use std::{
     collections::{BTreeSet, HashMap},
+    fmt,
     sync::Arc,
+    time::Duration,
 };
 
 use imbl::Vector;
@@ -39,7 +41,8 @@ use super::{
 };
 use crate::{
     timeline::{
-        InReplyToDetails, TimelineEventItemId,
+        CustomVirtualItem, EventTimelineItem, InReplyToDetails, MessageEffects, ReactionOrdering,
+        TimelineContentTransformerFn, TimelineEventItemId,
         event_item::{
             extract_bundled_edit_event_json, extract_poll_edit_content,
             extract_room_msg_edit_content,
@@ -57,6 +60,42 @@ pub(crate) struct RemoteEventContext<'a> {
     pub bundled_edit_encryption_info: Option<Arc<EncryptionInfo>>,
 }
 
+/// The functions transforming the content of the event items, in the order
+/// they run.
+#[derive(Clone, Default)]
+pub(in crate::timeline) struct ContentTransformers(
+    pub(super) Vec<Arc<TimelineContentTransformerFn>>,
+);
+
+impl ContentTransformers {
+    /// Whether there isn't any transformer to run.
+    pub(in crate::timeline) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Run the transformers on the content of the given event item.
+    pub(in crate::timeline) fn transform(&self, item: EventTimelineItem) -> EventTimelineItem {
+        if self.is_empty() {
+            return item;
+        }
+
+        let mut content = item.content().clone();
+
+        for transformer in &self.0 {
+            transformer(&mut content);
+        }
+
+        item.with_content(content)
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+impl fmt::Debug for ContentTransformers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentTransformers").field("len", &self.0.len()).finish()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(in crate::timeline) struct TimelineMetadata {
     // **** CONSTANT FIELDS ****
@@ -89,6 +128,16 @@ pub(in crate::timeline) struct TimelineMetadata {
     /// The own [`OwnedUserId`] of the client who opened the timeline.
     pub(crate) own_user_id: OwnedUserId,
 
+    /// The display name of the own user in the room, fetched when the timeline
+    /// is initialized, to detect the messages mentioning them by name.
+    pub own_display_name: Option<String>,
+
+    /// The maximum time between two consecutive messages from the same sender
+    /// for them to be grouped, or `None` if the messages aren't grouped.
+    ///
+    /// This value is constant over the lifetime of the metadata.
+    pub grouping_time_window: Option<Duration>,
+
     // **** DYNAMIC FIELDS ****
     /// The next internal identifier for timeline items, used for both local and
     /// remote echoes.
@@ -125,34 +174,102 @@ pub(in crate::timeline) struct TimelineMetadata {
     ///
     /// TODO: move this over to the event cache (see also #3058).
     pub(super) read_receipts: ReadReceipts,
+
+    /// The functions transforming the content of the event items.
+    ///
+    /// This isn't cleared with the rest of the metadata, and can only be
+    /// replaced when the whole timeline is rebuilt.
+    pub(super) content_transformers: ContentTransformers,
+
+    /// Whether runs of membership and profile changes received together are
+    /// collapsed into a
+    /// [`VirtualTimelineItem::MembershipSummary`](crate::timeline::VirtualTimelineItem::MembershipSummary).
+    pub(super) collapse_membership_changes: bool,
+
+    /// The IDs of the events collapsed into each membership summary item, by
+    /// unique ID of the summary.
+    ///
+    /// The events stay in [`ObservableItems::all_remote_events`], without a
+    /// timeline item, until their summary is expanded.
+    ///
+    /// [`ObservableItems::all_remote_events`]: super::ObservableItems::all_remote_events
+    pub(super) membership_summaries: HashMap<TimelineUniqueId, Vec<OwnedEventId>>,
+
+    /// The mapping used to recognize the messages triggering an effect, if
+    /// they are.
+    ///
+    /// This isn't cleared with the rest of the metadata.
+    pub(super) message_effects: Option<Arc<MessageEffects>>,
+
+    /// The custom virtual items inserted by the application.
+    ///
+    /// This isn't cleared with the rest of the metadata, so the items are
+    /// added back when their anchor event is loaded again.
+    pub(super) custom_items: Vec<CustomVirtualItem>,
 }
 
 impl TimelineMetadata {
+    #[allow(clippy::too_many_arguments)]
     pub(in crate::timeline) fn new(
         own_user_id: OwnedUserId,
         room_version_rules: RoomVersionRules,
         internal_id_prefix: Option<String>,
         unable_to_decrypt_hook: Option<Arc<UtdHookManager>>,
         is_room_encrypted: bool,
+        grouping_time_window: Option<Duration>,
+        lazy_read_receipts: bool,
+        content_transformers: Vec<Arc<TimelineContentTransformerFn>>,
+        collapse_membership_changes: bool,
+        reaction_ordering: Option<ReactionOrdering>,
+        message_effects: Option<Arc<MessageEffects>>,
     ) -> Self {
         Self {
             subscriber_skip_count: SkipCount::new(),
             own_user_id,
+            own_display_name: None,
             next_internal_id: Default::default(),
-            aggregations: Default::default(),
+            aggregations: Aggregations::new(reaction_ordering),
             replies: Default::default(),
             fully_read_event: Default::default(),
             // It doesn't make sense to set this to false until we fill the `fully_read_event`
             // field, otherwise we'll keep on exiting early in `Self::update_read_marker`.
             has_up_to_date_read_marker_item: true,
-            read_receipts: Default::default(),
+            read_receipts: ReadReceipts::new(lazy_read_receipts),
             room_version_rules,
             unable_to_decrypt_hook,
             internal_id_prefix,
             is_room_encrypted,
+            grouping_time_window,
+            content_transformers: ContentTransformers(content_transformers),
+            collapse_membership_changes,
+            membership_summaries: Default::default(),
+            message_effects,
+            custom_items: Default::default(),
         }
     }
 
+    /// Whether the content of the event items may be changed after being
+    /// built from their event, by the message effects or the content
+    /// transformers.
+    pub(in crate::timeline) fn transforms_content(&self) -> bool {
+        self.message_effects.is_some() || !self.content_transformers.is_empty()
+    }
+
+    /// Recognize the message effects and run the content transformers on the
+    /// content of the given event item.
+    pub(in crate::timeline) fn transform_content(
+        &self,
+        item: EventTimelineItem,
+    ) -> EventTimelineItem {
+        let item =
+            match self.message_effects.as_ref().and_then(|effects| effects.apply(item.content())) {
+                Some(content) => item.with_content(content),
+                None => item,
+            };
+
+        self.content_transformers.transform(item)
+    }
+
     pub(super) fn clear(&mut self) {
         // Note: we don't clear the next internal id to avoid bad cases of stale unique
         // ids across timeline clears.
@@ -163,6 +280,7 @@ impl TimelineMetadata {
         // before attempting to update it for each new timeline item.
         self.has_up_to_date_read_marker_item = true;
         self.read_receipts.clear();
+        self.membership_summaries.clear();
     }
 
     /// Get the relative positions of two events in the timeline.
@@ -12,19 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::BTreeSet, fmt, sync::Arc};
+use std::{collections::BTreeSet, fmt, ops::Range, sync::Arc, time::Duration};
 
 use as_variant::as_variant;
-use decryption_retry_task::DecryptionRetryTask;
+use decryption_retry_task::{DecryptionRetryTask, retry_fetch_encryption_info};
 use eyeball_im::{VectorDiff, VectorSubscriberStream};
 use eyeball_im_util::vector::{FilterMap, VectorObserverExt};
 use futures_core::Stream;
+use futures_util::StreamExt as _;
 use imbl::Vector;
 #[cfg(test)]
 use matrix_sdk::Result;
 use matrix_sdk::{
     deserialized_responses::TimelineEvent,
     event_cache::{RoomEventCache, RoomPaginationStatus},
+    locks::RwLock as StdRwLock,
+    media::UrlPreview,
     paginators::{PaginationResult, Paginator},
     send_queue::{
         LocalEcho, LocalEchoContent, RoomSendQueueUpdate, SendHandle, SendReactionHandle,
@@ -32,8 +35,11 @@ use matrix_sdk::{
 };
 #[cfg(test)]
 use ruma::events::receipt::ReceiptEventContent;
+#[cfg(feature = "unstable-msc4274")]
+use ruma::events::room::message::GalleryItemType;
 use ruma::{
-    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, TransactionId, UserId,
+    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, OwnedUserId,
+    TransactionId, UInt, UserId,
     api::client::receipt::create_receipt::v3::ReceiptType as SendReceiptType,
     events::{
         AnyMessageLikeEventContent, AnySyncEphemeralRoomEvent, AnySyncMessageLikeEvent,
@@ -51,7 +57,7 @@ use tokio::sync::{RwLock, RwLockWriteGuard};
 use tracing::{debug, error, field::debug, info, instrument, trace, warn};
 
 pub(super) use self::{
-    metadata::{RelativePosition, TimelineMetadata},
+    metadata::{ContentTransformers, RelativePosition, TimelineMetadata},
     observable_items::{
         AllRemoteEvents, ObservableItems, ObservableItemsEntry, ObservableItemsTransaction,
         ObservableItemsTransactionEntry,
@@ -60,22 +66,27 @@ pub(super) use self::{
     state_transaction::TimelineStateTransaction,
 };
 use super::{
-    DateDividerMode, EmbeddedEvent, Error, EventSendState, EventTimelineItem, InReplyToDetails,
-    MediaUploadProgress, PaginationError, Profile, TimelineDetails, TimelineEventItemId,
-    TimelineFocus, TimelineItem, TimelineItemContent, TimelineItemKind, VirtualTimelineItem,
+    CustomVirtualItem, DateDividerMode, EmbeddedEvent, Error, EventSendState, EventTimelineItem,
+    InReplyToDetails, MediaUploadProgress, MessageEffects, PaginationError, Profile,
+    ReactionOrdering, TimelineDetails, TimelineEventItemId, TimelineFocus, TimelineItem,
+    TimelineItemContent, TimelineItemKind, TimelineMemoryReport, TimelinePaginationStatus,
+    VirtualTimelineItem,
     algorithms::{rfind_event_by_id, rfind_event_item},
     event_item::{ReactionStatus, RemoteEventOrigin},
+    futures::AttachmentUploadPhase,
     item::TimelineUniqueId,
+    pagination::PaginationStatusTracker,
     subscriber::TimelineSubscriber,
     traits::RoomDataProvider,
 };
 use crate::{
     timeline::{
-        MsgLikeContent, MsgLikeKind, Room, TimelineEventFilterFn,
+        MsgLikeContent, MsgLikeKind, Room, TimelineContentTransformerFn, TimelineEventFilterFn,
         algorithms::rfind_event_by_item_id,
         date_dividers::DateDividerAdjuster,
         event_item::TimelineItemHandle,
         pinned_events_loader::{PinnedEventsLoader, PinnedEventsLoaderError},
+        starred_events_loader::{StarredEventsLoader, StarredEventsLoaderError},
     },
     unable_to_decrypt_hook::UtdHookManager,
 };
@@ -89,6 +100,7 @@ mod state;
 mod state_transaction;
 
 pub(super) use aggregations::*;
+pub use decryption_retry_task::DecryptionRetryReport;
 pub(super) use decryption_retry_task::{CryptoDropHandles, spawn_crypto_tasks};
 
 /// Data associated to the current timeline focus.
@@ -123,6 +135,17 @@ pub(in crate::timeline) enum TimelineFocusKind<P: RoomDataProvider> {
     PinnedEvents {
         loader: PinnedEventsLoader,
     },
+
+    StarredEvents {
+        loader: StarredEventsLoader,
+    },
+
+    /// The timeline previews the most recent events of a room, and it can
+    /// expand backwards.
+    Preview {
+        /// The paginator instance.
+        paginator: Paginator<P>,
+    },
 }
 
 impl<P: RoomDataProvider> TimelineFocusKind<P> {
@@ -147,7 +170,10 @@ impl<P: RoomDataProvider> TimelineFocusKind<P> {
         match self {
             TimelineFocusKind::Live { hide_threaded_events }
             | TimelineFocusKind::Event { hide_threaded_events, .. } => *hide_threaded_events,
-            TimelineFocusKind::Thread { .. } | TimelineFocusKind::PinnedEvents { .. } => false,
+            TimelineFocusKind::Thread { .. }
+            | TimelineFocusKind::PinnedEvents { .. }
+            | TimelineFocusKind::StarredEvents { .. }
+            | TimelineFocusKind::Preview { .. } => false,
         }
     }
 
@@ -162,7 +188,9 @@ impl<P: RoomDataProvider> TimelineFocusKind<P> {
         match self {
             TimelineFocusKind::Live { .. }
             | TimelineFocusKind::Event { paginator: _, hide_threaded_events: _ }
-            | TimelineFocusKind::PinnedEvents { .. } => None,
+            | TimelineFocusKind::PinnedEvents { .. }
+            | TimelineFocusKind::StarredEvents { .. }
+            | TimelineFocusKind::Preview { .. } => None,
             TimelineFocusKind::Thread { root_event_id } => Some(root_event_id),
         }
     }
@@ -183,11 +211,20 @@ pub(super) struct TimelineController<P: RoomDataProvider = Room> {
     pub(crate) room_data_provider: P,
 
     /// Settings applied to this timeline.
-    pub(super) settings: TimelineSettings,
+    ///
+    /// They're shared with the clones of the controller, so that the event
+    /// filter can be changed after the timeline has been built.
+    settings: Arc<StdRwLock<TimelineSettings>>,
 
     /// Long-running task used to retry decryption of timeline items without
     /// blocking main processing.
     decryption_retry_task: DecryptionRetryTask<P, P>,
+
+    /// The status of the back-paginations of this timeline.
+    back_pagination_status: PaginationStatusTracker,
+
+    /// The status of the forward-paginations of this timeline.
+    forward_pagination_status: PaginationStatusTracker,
 }
 
 #[derive(Clone)]
@@ -195,6 +232,12 @@ pub(super) struct TimelineSettings {
     /// Should the read receipts and read markers be handled?
     pub(super) track_read_receipts: bool,
 
+    /// Should the read receipts of the timeline items only be computed for the
+    /// window of items set with [`Timeline::load_receipts_for()`]?
+    ///
+    /// [`Timeline::load_receipts_for()`]: crate::Timeline::load_receipts_for
+    pub(super) lazy_read_receipts: bool,
+
     /// Event filter that controls what's rendered as a timeline item (and thus
     /// what can carry read receipts).
     pub(super) event_filter: Arc<TimelineEventFilterFn>,
@@ -204,6 +247,32 @@ pub(super) struct TimelineSettings {
 
     /// Should the timeline items be grouped by day or month?
     pub(super) date_divider_mode: DateDividerMode,
+
+    /// The functions transforming the content of the event items, in the
+    /// order they run.
+    pub(super) content_transformers: Vec<Arc<TimelineContentTransformerFn>>,
+
+    /// The maximum time between two consecutive messages from the same sender
+    /// for them to be grouped, if the grouping is enabled.
+    pub(super) grouping_time_window: Option<Duration>,
+
+    /// Should runs of membership and profile changes received together be
+    /// collapsed into a summary item?
+    pub(super) collapse_membership_changes: bool,
+
+    /// Should the previews of the links of the messages be fetched?
+    pub(super) fetch_url_previews: bool,
+
+    /// How the reactions of the event items are ordered, if they are.
+    pub(super) reaction_ordering: Option<ReactionOrdering>,
+
+    /// The mapping used to recognize the messages triggering an effect, if
+    /// they are.
+    pub(super) message_effects: Option<Arc<MessageEffects>>,
+
+    /// The time window over which the diffs are coalesced before being
+    /// emitted by the subscribers of the timeline, if they are.
+    pub(super) diffs_coalescing_window: Option<Duration>,
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -211,7 +280,13 @@ impl fmt::Debug for TimelineSettings {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("TimelineSettings")
             .field("track_read_receipts", &self.track_read_receipts)
+            .field("lazy_read_receipts", &self.lazy_read_receipts)
             .field("add_failed_to_parse", &self.add_failed_to_parse)
+            .field("collapse_membership_changes", &self.collapse_membership_changes)
+            .field("fetch_url_previews", &self.fetch_url_previews)
+            .field("reaction_ordering", &self.reaction_ordering)
+            .field("diffs_coalescing_window", &self.diffs_coalescing_window)
+            .field("message_effects", &self.message_effects)
             .finish_non_exhaustive()
     }
 }
@@ -220,9 +295,17 @@ impl Default for TimelineSettings {
     fn default() -> Self {
         Self {
             track_read_receipts: false,
+            lazy_read_receipts: false,
             event_filter: Arc::new(default_event_filter),
             add_failed_to_parse: true,
             date_divider_mode: DateDividerMode::Daily,
+            content_transformers: Vec::new(),
+            grouping_time_window: None,
+            collapse_membership_changes: false,
+            fetch_url_previews: false,
+            reaction_ordering: None,
+            message_effects: None,
+            diffs_coalescing_window: None,
         }
     }
 }
@@ -340,6 +423,20 @@ impl<P: RoomDataProvider> TimelineController<P> {
                     ),
                 }
             }
+
+            TimelineFocus::StarredEvents { max_events_to_load, max_concurrent_requests } => {
+                TimelineFocusKind::StarredEvents {
+                    loader: StarredEventsLoader::new(
+                        Arc::new(room_data_provider.clone()),
+                        max_events_to_load as usize,
+                        max_concurrent_requests as usize,
+                    ),
+                }
+            }
+
+            TimelineFocus::Preview { .. } => {
+                TimelineFocusKind::Preview { paginator: Paginator::new(room_data_provider.clone()) }
+            }
         };
 
         let focus = Arc::new(focus);
@@ -350,12 +447,33 @@ impl<P: RoomDataProvider> TimelineController<P> {
             internal_id_prefix,
             unable_to_decrypt_hook,
             is_room_encrypted,
+            settings.grouping_time_window,
+            settings.lazy_read_receipts,
+            settings.content_transformers.clone(),
+            settings.collapse_membership_changes,
+            settings.reaction_ordering,
+            settings.message_effects.clone(),
         )));
 
         let decryption_retry_task =
             DecryptionRetryTask::new(state.clone(), room_data_provider.clone());
 
-        Self { state, focus, room_data_provider, settings, decryption_retry_task }
+        // A live timeline is always at the end of the room.
+        let forward_pagination_status = if matches!(*focus, TimelineFocusKind::Live { .. }) {
+            TimelinePaginationStatus::ReachedEnd
+        } else {
+            TimelinePaginationStatus::Idle
+        };
+
+        Self {
+            state,
+            focus,
+            room_data_provider,
+            settings: Arc::new(StdRwLock::new(settings)),
+            decryption_retry_task,
+            back_pagination_status: PaginationStatusTracker::new(TimelinePaginationStatus::Idle),
+            forward_pagination_status: PaginationStatusTracker::new(forward_pagination_status),
+        }
     }
 
     /// Initializes the configured focus with appropriate data.
@@ -369,6 +487,13 @@ impl<P: RoomDataProvider> TimelineController<P> {
         focus: &TimelineFocus,
         room_event_cache: &RoomEventCache,
     ) -> Result<bool, Error> {
+        let own_profile = self
+            .room_data_provider
+            .profile_from_user_id(self.room_data_provider.own_user_id())
+            .await;
+        self.state.write().await.meta.own_display_name =
+            own_profile.and_then(|profile| profile.display_name);
+
         match focus {
             TimelineFocus::Live { .. } => {
                 // Retrieve the cached events, and add them to the timeline.
@@ -388,6 +513,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
                             // Eagerly insert the timeline start item, since pagination claims
                             // we've already hit the timeline start.
                             self.insert_timeline_start_if_missing().await;
+                            self.back_pagination_status.set(TimelinePaginationStatus::ReachedEnd);
                         }
                     }
                     RoomPaginationStatus::Paginating => {}
@@ -410,6 +536,13 @@ impl<P: RoomDataProvider> TimelineController<P> {
 
                 let has_events = !start_from_result.events.is_empty();
 
+                if !start_from_result.has_prev {
+                    self.back_pagination_status.set(TimelinePaginationStatus::ReachedEnd);
+                }
+                if !start_from_result.has_next {
+                    self.forward_pagination_status.set(TimelinePaginationStatus::ReachedEnd);
+                }
+
                 self.replace_with_initial_remote_events(
                     start_from_result.events.into_iter(),
                     RemoteEventOrigin::Pagination,
@@ -476,6 +609,59 @@ impl<P: RoomDataProvider> TimelineController<P> {
 
                 Ok(has_events)
             }
+
+            TimelineFocus::StarredEvents { .. } => {
+                let TimelineFocusKind::StarredEvents { loader } = &*self.focus else {
+                    // Note: this is sync'd with code in the ctor.
+                    unreachable!();
+                };
+
+                let Some(loaded_events) =
+                    loader.load_events().await.map_err(Error::StarredEventsError)?
+                else {
+                    // There wasn't any events.
+                    return Ok(false);
+                };
+
+                let has_events = !loaded_events.is_empty();
+
+                self.replace_with_initial_remote_events(
+                    loaded_events.into_iter(),
+                    RemoteEventOrigin::Pagination,
+                )
+                .await;
+
+                Ok(has_events)
+            }
+
+            TimelineFocus::Preview { num_events } => {
+                let TimelineFocusKind::Preview { paginator } = &*self.focus else {
+                    // Note: this is sync'd with code in the ctor.
+                    unreachable!();
+                };
+
+                // Start a /messages request from the end of the timeline.
+                let PaginationResult { events, hit_end_of_timeline } = paginator
+                    .start_from_end((*num_events).into())
+                    .await
+                    .map_err(PaginationError::Paginator)?;
+
+                let has_events = !events.is_empty();
+
+                if hit_end_of_timeline {
+                    self.back_pagination_status.set(TimelinePaginationStatus::ReachedEnd);
+                }
+                self.forward_pagination_status.set(TimelinePaginationStatus::ReachedEnd);
+
+                // Events are in reverse topological order.
+                self.replace_with_initial_remote_events(
+                    events.into_iter().rev(),
+                    RemoteEventOrigin::Pagination,
+                )
+                .await;
+
+                Ok(has_events)
+            }
         }
     }
 
@@ -520,6 +706,16 @@ impl<P: RoomDataProvider> TimelineController<P> {
         }
     }
 
+    pub(crate) async fn reload_starred_events(
+        &self,
+    ) -> Result<Option<Vec<TimelineEvent>>, StarredEventsLoaderError> {
+        if let TimelineFocusKind::StarredEvents { loader } = &*self.focus {
+            loader.load_events().await
+        } else {
+            Err(StarredEventsLoaderError::TimelineFocusNotStarredEvents)
+        }
+    }
+
     /// Run a lazy backwards pagination (in live mode).
     ///
     /// It adjusts the `count` value of the `Skip` higher-order stream so that
@@ -554,10 +750,12 @@ impl<P: RoomDataProvider> TimelineController<P> {
         let PaginationResult { events, hit_end_of_timeline } = match &*self.focus {
             TimelineFocusKind::Live { .. }
             | TimelineFocusKind::PinnedEvents { .. }
+            | TimelineFocusKind::StarredEvents { .. }
             | TimelineFocusKind::Thread { .. } => {
                 return Err(PaginationError::NotSupported);
             }
-            TimelineFocusKind::Event { paginator, .. } => paginator
+            TimelineFocusKind::Event { paginator, .. }
+            | TimelineFocusKind::Preview { paginator } => paginator
                 .paginate_backward(num_events.into())
                 .await
                 .map_err(PaginationError::Paginator)?,
@@ -585,9 +783,11 @@ impl<P: RoomDataProvider> TimelineController<P> {
         let PaginationResult { events, hit_end_of_timeline } = match &*self.focus {
             TimelineFocusKind::Live { .. }
             | TimelineFocusKind::PinnedEvents { .. }
+            | TimelineFocusKind::StarredEvents { .. }
             | TimelineFocusKind::Thread { .. } => return Err(PaginationError::NotSupported),
 
-            TimelineFocusKind::Event { paginator, .. } => paginator
+            TimelineFocusKind::Event { paginator, .. }
+            | TimelineFocusKind::Preview { paginator } => paginator
                 .paginate_forward(num_events.into())
                 .await
                 .map_err(PaginationError::Paginator)?,
@@ -604,6 +804,94 @@ impl<P: RoomDataProvider> TimelineController<P> {
         Ok(hit_end_of_timeline)
     }
 
+    /// The status of the back-paginations of this timeline.
+    pub(super) fn back_pagination_status(&self) -> &PaginationStatusTracker {
+        &self.back_pagination_status
+    }
+
+    /// The status of the forward-paginations of this timeline.
+    pub(super) fn forward_pagination_status(&self) -> &PaginationStatusTracker {
+        &self.forward_pagination_status
+    }
+
+    /// A snapshot of the settings applied to this timeline.
+    pub(super) fn settings(&self) -> TimelineSettings {
+        self.settings.read().clone()
+    }
+
+    /// Replace the event filter of this timeline, and rebuild the timeline
+    /// items with it.
+    ///
+    /// Only the live and thread timelines are rebuilt, from the events of the
+    /// event cache; the other focus modes apply the new filter to the events
+    /// they receive afterwards.
+    pub(super) async fn set_event_filter(
+        &self,
+        filter: Arc<TimelineEventFilterFn>,
+        room_event_cache: &RoomEventCache,
+    ) -> Result<(), Error> {
+        self.settings.write().event_filter = filter;
+        self.rebuild_from_event_cache(room_event_cache).await
+    }
+
+    /// Replace the content transformers of this timeline, and apply them to
+    /// the timeline items.
+    ///
+    /// The live and thread timelines are rebuilt from the events of the event
+    /// cache, like with [`Self::set_event_filter`]. The items of the other
+    /// focus modes can't be rebuilt from their events, so the new
+    /// transformers run on their current content instead.
+    pub(super) async fn set_content_transformers(
+        &self,
+        transformers: Vec<Arc<TimelineContentTransformerFn>>,
+        room_event_cache: &RoomEventCache,
+    ) -> Result<(), Error> {
+        self.settings.write().content_transformers = transformers.clone();
+
+        let mut state = self.state.write().await;
+        state.meta.content_transformers = ContentTransformers(transformers);
+
+        if self.live_or_thread_focus().is_some() {
+            drop(state);
+            self.rebuild_from_event_cache(room_event_cache).await
+        } else {
+            state.transform_content_of_all_events();
+            Ok(())
+        }
+    }
+
+    /// The focus to initialize this timeline with again, if it's a live or
+    /// thread timeline, which can be rebuilt from the event cache.
+    fn live_or_thread_focus(&self) -> Option<TimelineFocus> {
+        match &*self.focus {
+            TimelineFocusKind::Live { hide_threaded_events } => {
+                Some(TimelineFocus::Live { hide_threaded_events: *hide_threaded_events })
+            }
+            TimelineFocusKind::Thread { root_event_id } => {
+                Some(TimelineFocus::Thread { root_event_id: root_event_id.clone() })
+            }
+            TimelineFocusKind::Event { .. }
+            | TimelineFocusKind::PinnedEvents { .. }
+            | TimelineFocusKind::StarredEvents { .. }
+            | TimelineFocusKind::Preview { .. } => None,
+        }
+    }
+
+    /// Rebuild the items of a live or thread timeline from the events of the
+    /// event cache, after its settings changed.
+    async fn rebuild_from_event_cache(
+        &self,
+        room_event_cache: &RoomEventCache,
+    ) -> Result<(), Error> {
+        let Some(focus) = self.live_or_thread_focus() else {
+            return Ok(());
+        };
+
+        self.init_focus(&focus, room_event_cache).await?;
+
+        Ok(())
+    }
+
     /// Is this timeline receiving events from sync (aka has a live focus)?
     pub(super) fn is_live(&self) -> bool {
         matches!(&*self.focus, TimelineFocusKind::Live { .. })
@@ -627,6 +915,178 @@ impl<P: RoomDataProvider> TimelineController<P> {
         self.state.read().await.items.clone_items()
     }
 
+    /// Count the items held in memory by the timeline.
+    pub(super) async fn memory_report(&self) -> TimelineMemoryReport {
+        let state = self.state.read().await;
+        let items = state.items.clone_items();
+
+        let mut report = TimelineMemoryReport {
+            items: items.len(),
+            remote_events: state.items.all_remote_events().iter().len(),
+            ..Default::default()
+        };
+
+        for event in items.iter().filter_map(|item| item.as_event()) {
+            report.event_items += 1;
+
+            if event.is_local_echo() {
+                report.local_echoes += 1;
+            }
+        }
+
+        report
+    }
+
+    /// The ID of the fully-read event of the room, if it's known.
+    pub(super) async fn fully_read_event_id(&self) -> Option<OwnedEventId> {
+        self.state.read().await.meta.fully_read_event.clone()
+    }
+
+    /// Wait for the timeline to have received the remote event with the given
+    /// ID, be it visible or not.
+    ///
+    /// It never returns if the event isn't received, e.g. if it's filtered out
+    /// before reaching the timeline, so the caller must bound it with a
+    /// timeout.
+    pub(super) async fn wait_for_remote_event(&self, event_id: &EventId) {
+        let mut updates = {
+            let state = self.state.read().await;
+
+            if state.items.all_remote_events().get_by_event_id(event_id).is_some() {
+                return;
+            }
+
+            state.items.subscribe().into_stream()
+        };
+
+        while updates.next().await.is_some() {
+            if self.state.read().await.items.all_remote_events().get_by_event_id(event_id).is_some()
+            {
+                return;
+            }
+        }
+    }
+
+    /// Find the item at which the user stopped reading.
+    ///
+    /// This is the read marker item, or the item of the fully-read event when
+    /// the read marker isn't shown, because everything has been read.
+    ///
+    /// Returns `None` if none of them are in the timeline yet.
+    pub(super) async fn find_read_marker(&self) -> Option<TimelineUniqueId> {
+        let state = self.state.read().await;
+        let fully_read_event = state.meta.fully_read_event.as_deref()?;
+
+        // The read marker, if any, comes after the fully-read event.
+        let item = state.items.iter().rev().find(|item| {
+            item.is_read_marker()
+                || item.as_event().is_some_and(|event| event.event_id() == Some(fully_read_event))
+        })?;
+
+        Some(item.unique_id().clone())
+    }
+
+    /// Replace the membership summary item with the given unique ID by the
+    /// items it collapsed.
+    ///
+    /// Each item is put back after the item of the closest previous remote
+    /// event, and the collapsed events which aren't in the timeline anymore are
+    /// dropped.
+    ///
+    /// Returns whether the summary has been found.
+    pub(super) async fn expand_membership_summary(&self, unique_id: &TimelineUniqueId) -> bool {
+        let mut state = self.state.write().await;
+
+        let Some(index) = state.items.iter().position(|item| item.unique_id() == unique_id) else {
+            return false;
+        };
+
+        let mut txn = state.transaction();
+
+        let Some(VirtualTimelineItem::MembershipSummary(summary)) =
+            txn.items.get(index).and_then(|item| item.as_virtual()).cloned()
+        else {
+            return false;
+        };
+
+        let event_ids = txn.meta.membership_summaries.remove(unique_id).unwrap_or_default();
+
+        txn.items.remove(index);
+
+        let mut next_index = index;
+
+        for event_id in event_ids {
+            let Some(item) = summary
+                .items
+                .iter()
+                .find(|item| item.as_event().and_then(|event| event.event_id()) == Some(&event_id))
+            else {
+                continue;
+            };
+
+            let Some(event_index) = txn.items.position_by_event_id(&event_id) else {
+                trace!(%event_id, "Dropping a collapsed event which isn't in the timeline anymore");
+                continue;
+            };
+
+            // The closest previous event with an item, if any, is the item before this one.
+            let previous_item_index = txn
+                .items
+                .all_remote_events()
+                .range(..event_index)
+                .rev()
+                .find_map(|event_meta| event_meta.timeline_item_index);
+
+            let item_index = previous_item_index.map_or(next_index, |previous| previous + 1);
+            txn.items.insert(item_index, item.clone(), Some(event_index));
+            next_index = item_index + 1;
+        }
+
+        // The expanded items may need other date dividers than the summary.
+        let mut adjuster = DateDividerAdjuster::new(self.settings().date_divider_mode);
+        adjuster.mark_used();
+        txn.adjust_date_dividers(adjuster);
+
+        txn.commit();
+        true
+    }
+
+    /// Register the given custom item, replacing the one with the same ID if
+    /// any, and insert it next to its anchor if it's in the timeline.
+    pub(super) async fn insert_custom_item(&self, item: CustomVirtualItem) {
+        let mut state = self.state.write().await;
+        let mut txn = state.transaction();
+
+        txn.meta.custom_items.retain(|custom| custom.id != item.id);
+        txn.meta.custom_items.push(item);
+
+        txn.commit();
+    }
+
+    /// Forget about the custom item with the given ID, and remove it from the
+    /// timeline.
+    ///
+    /// Returns whether the item was registered.
+    pub(super) async fn remove_custom_item(&self, id: &str) -> bool {
+        let mut state = self.state.write().await;
+
+        if !state.meta.custom_items.iter().any(|custom| custom.id == id) {
+            return false;
+        }
+
+        let mut txn = state.transaction();
+        txn.meta.custom_items.retain(|custom| custom.id != id);
+
+        if let Some(index) =
+            txn.items.iter().position(|item| item.as_custom().is_some_and(|custom| custom.id == id))
+        {
+            txn.items.remove(index);
+        }
+
+        txn.commit();
+        true
+    }
+
     #[cfg(test)]
     pub(super) async fn subscribe_raw(
         &self,
@@ -747,6 +1207,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
                 let reaction_info = reactions.remove_reaction(user_id, key);
 
                 if reaction_info.is_some() {
+                    state.meta.aggregations.sort_reactions(&mut reactions);
                     let new_item = item.with_reactions(reactions);
                     state.items.replace(item_pos, new_item);
                 } else {
@@ -775,6 +1236,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
                                 .entry(key.to_owned())
                                 .or_default()
                                 .insert(user_id.to_owned(), reaction_info);
+                            state.meta.aggregations.sort_reactions(&mut reactions);
                             let new_item = item.with_reactions(reactions);
                             state.items.replace(item_pos, new_item);
                         } else {
@@ -809,7 +1271,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
                 diffs,
                 origin,
                 &self.room_data_provider,
-                &self.settings,
+                &self.settings(),
             )
             .await
     }
@@ -826,7 +1288,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
 
         let mut state = self.state.write().await;
         state
-            .handle_remote_aggregations(diffs, origin, &self.room_data_provider, &self.settings)
+            .handle_remote_aggregations(diffs, origin, &self.room_data_provider, &self.settings())
             .await
     }
 
@@ -834,6 +1296,45 @@ impl<P: RoomDataProvider> TimelineController<P> {
         self.state.write().await.clear();
     }
 
+    /// Apply again the aggregations known by the event cache to the events of
+    /// the timeline, after it's been reset because of a gap.
+    ///
+    /// The events relating to them may not be part of the events received
+    /// after the reset, but they're still in the event cache store.
+    pub(super) async fn reaggregate_after_reset(&self, room_event_cache: &RoomEventCache) {
+        let event_ids = self
+            .state
+            .read()
+            .await
+            .items
+            .all_remote_events()
+            .iter()
+            .filter(|event_meta| event_meta.visible)
+            .map(|event_meta| event_meta.event_id.clone())
+            .collect::<Vec<_>>();
+
+        let mut related_events = Vector::new();
+        for event_id in event_ids {
+            if let Some((_original, related)) =
+                room_event_cache.find_event_with_relations(&event_id, None).await
+            {
+                related_events.extend(related);
+            }
+        }
+
+        if related_events.is_empty() {
+            return;
+        }
+
+        trace!(num_related_events = related_events.len(), "re-aggregating after a reset");
+
+        self.handle_remote_aggregations(
+            vec![VectorDiff::Append { values: related_events }],
+            RemoteEventOrigin::Cache,
+        )
+        .await;
+    }
+
     /// Replaces the content of the current timeline with initial events.
     ///
     /// Also sets up read receipts and the read marker for a live timeline of a
@@ -851,7 +1352,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
     {
         let mut state = self.state.write().await;
 
-        let track_read_markers = self.settings.track_read_receipts;
+        let track_read_markers = self.settings().track_read_receipts;
         if track_read_markers {
             state.populate_initial_user_receipt(&self.room_data_provider, ReceiptType::Read).await;
             state
@@ -870,7 +1371,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
                     events,
                     origin,
                     &self.room_data_provider,
-                    &self.settings,
+                    &self.settings(),
                 )
                 .await;
         }
@@ -906,7 +1407,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
         let sender = self.room_data_provider.own_user_id().to_owned();
         let profile = self.room_data_provider.profile_from_user_id(&sender).await;
 
-        let date_divider_mode = self.settings.date_divider_mode.clone();
+        let date_divider_mode = self.settings().date_divider_mode;
 
         let mut state = self.state.write().await;
         state
@@ -919,6 +1420,16 @@ impl<P: RoomDataProvider> TimelineController<P> {
     /// If the corresponding local timeline item is missing, a warning is
     /// raised.
     #[instrument(skip(self))]
+    /// Get the size of the thumbnail of the media with the given index, in the
+    /// local echo with the given transaction ID, or 0 if it has none.
+    async fn local_echo_thumbnail_len(&self, txn_id: &TransactionId, index: u64) -> usize {
+        let state = self.state.read().await;
+
+        rfind_event_item(&state.items, |it| it.transaction_id() == Some(txn_id))
+            .and_then(|(_, item)| thumbnail_size(item.content().as_message()?.msgtype(), index))
+            .map_or(0, |size| u64::from(size) as usize)
+    }
+
     pub(super) async fn update_event_send_state(
         &self,
         txn_id: &TransactionId,
@@ -950,8 +1461,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
                 txn.items.remove(idx);
 
                 // Adjust the date dividers, if needs be.
-                let mut adjuster =
-                    DateDividerAdjuster::new(self.settings.date_divider_mode.clone());
+                let mut adjuster = DateDividerAdjuster::new(self.settings().date_divider_mode);
                 adjuster.run(&mut txn.items, &mut txn.meta);
             }
 
@@ -1026,7 +1536,7 @@ impl<P: RoomDataProvider> TimelineController<P> {
 
             // A read marker or a date divider may have been inserted before the local echo.
             // Ensure both are up to date.
-            let mut adjuster = DateDividerAdjuster::new(self.settings.date_divider_mode.clone());
+            let mut adjuster = DateDividerAdjuster::new(self.settings().date_divider_mode);
             adjuster.run(&mut txn.items, &mut txn.meta);
 
             txn.meta.update_read_marker(&mut txn.items);
@@ -1125,10 +1635,41 @@ impl<P: RoomDataProvider> TimelineController<P> {
 
     pub(crate) async fn retry_event_decryption_inner(&self, session_ids: Option<BTreeSet<String>>) {
         self.decryption_retry_task
-            .decrypt(self.room_data_provider.clone(), session_ids, self.settings.clone())
+            .decrypt(self.room_data_provider.clone(), session_ids, self.settings())
             .await;
     }
 
+    /// Fetch the encryption info of the decrypted items sent by the given
+    /// users again, so their shields reflect the current trust state of the
+    /// senders' devices and identities.
+    ///
+    /// If `senders` is `None`, or contains our own user, the encryption info of
+    /// all the decrypted items is fetched again, since a change of our own
+    /// identity can change the verification state of everyone else.
+    pub(super) async fn refresh_encryption_info(&self, senders: Option<BTreeSet<OwnedUserId>>) {
+        let own_user_id = self.room_data_provider.own_user_id();
+        let senders = senders.filter(|senders| !senders.contains(own_user_id));
+
+        let mut state = self.state.write().await;
+
+        let indices = state
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, item)| {
+                let event = item.as_event()?;
+                let should_refresh = event.encryption_info().is_some()
+                    && senders.as_ref().is_none_or(|senders| senders.contains(event.sender()));
+                should_refresh.then_some(idx)
+            })
+            .collect::<Vec<_>>();
+
+        if !indices.is_empty() {
+            debug!(num_items = indices.len(), "Refreshing the encryption info of items");
+            retry_fetch_encryption_info(&mut state, indices, &self.room_data_provider).await;
+        }
+    }
+
     pub(super) async fn set_sender_profiles_pending(&self) {
         self.set_non_ready_sender_profiles(TimelineDetails::Pending).await;
     }
@@ -1234,6 +1775,15 @@ impl<P: RoomDataProvider> TimelineController<P> {
         trace!("Done forcing update of sender profiles");
     }
 
+    /// Set the window of timeline items whose read receipts are computed, if
+    /// they are lazily loaded.
+    pub(super) async fn load_read_receipts_for(&self, range: Range<usize>) {
+        let mut state = self.state.write().await;
+        let mut txn = state.transaction();
+        txn.set_read_receipts_window(range);
+        txn.commit();
+    }
+
     #[cfg(test)]
     pub(super) async fn handle_read_receipts(&self, receipt_event_content: ReceiptEventContent) {
         let own_user_id = self.room_data_provider.own_user_id();
@@ -1393,10 +1943,13 @@ impl<P: RoomDataProvider> TimelineController<P> {
             }
 
             RoomSendQueueUpdate::MediaUpload { related_to, index, progress, .. } => {
+                let thumbnail_len = self.local_echo_thumbnail_len(&related_to, index).await;
+                let phase = AttachmentUploadPhase::from_upload_progress(progress, thumbnail_len);
+
                 self.update_event_send_state(
                     &related_to,
                     EventSendState::NotSentYet {
-                        progress: Some(MediaUploadProgress { index, progress }),
+                        progress: Some(MediaUploadProgress { index, progress, phase }),
                     },
                 )
                 .await;
@@ -1518,6 +2071,21 @@ impl TimelineController {
         Ok(())
     }
 
+    /// Add the given previews of the links of a message to its item.
+    pub(super) async fn add_url_previews(&self, event_id: &EventId, previews: Vec<UrlPreview>) {
+        let mut state = self.state.write().await;
+
+        let Some((index, item)) = rfind_event_by_id(&state.items, event_id) else {
+            debug!("Can't find the item to add URL previews to");
+            return;
+        };
+
+        let internal_id = item.internal_id.to_owned();
+        let mut item = item.clone();
+        item.add_url_previews(previews);
+        state.items.replace(index, TimelineItem::new(item, internal_id));
+    }
+
     /// Returns the thread that should be used for a read receipt based on the
     /// current focus of the timeline and the receipt type.
     ///
@@ -1625,6 +2193,14 @@ impl TimelineController {
         self.retry_event_decryption_inner(session_ids).await
     }
 
+    /// Retry the decryption of all the unable-to-decrypt items, and wait for
+    /// it to be done.
+    pub(super) async fn retry_decryption_for_all_utds(&self) -> DecryptionRetryReport {
+        self.decryption_retry_task
+            .decrypt_and_report(self.room_data_provider.clone(), None, self.settings())
+            .await
+    }
+
     /// Combine the global (event cache) pagination status with the local state
     /// of the timeline.
     ///
@@ -1709,3 +2285,28 @@ async fn fetch_replied_to_event<P: RoomDataProvider>(
 
     Ok(res)
 }
+
+/// Get the size of the thumbnail of the media with the given index in a
+/// message, if it has one.
+///
+/// The index is only relevant for galleries.
+#[cfg_attr(not(feature = "unstable-msc4274"), allow(unused_variables))]
+fn thumbnail_size(msgtype: &MessageType, index: u64) -> Option<UInt> {
+    let thumbnail_info = match msgtype {
+        MessageType::Image(content) => content.info.as_ref()?.thumbnail_info.as_ref(),
+        MessageType::Video(content) => content.info.as_ref()?.thumbnail_info.as_ref(),
+        MessageType::File(content) => content.info.as_ref()?.thumbnail_info.as_ref(),
+        #[cfg(feature = "unstable-msc4274")]
+        MessageType::Gallery(gallery) => {
+            match gallery.itemtypes.get(usize::try_from(index).ok()?)? {
+                GalleryItemType::Image(content) => content.info.as_ref()?.thumbnail_info.as_ref(),
+                GalleryItemType::Video(content) => content.info.as_ref()?.thumbnail_info.as_ref(),
+                GalleryItemType::File(content) => content.info.as_ref()?.thumbnail_info.as_ref(),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    thumbnail_info?.size
+}
@@ -740,6 +740,7 @@ mod observable_items_tests {
                         msgtype: MessageType::Text(TextMessageEventContent::plain("hello")),
                         edited: false,
                         mentions: None,
+                        effect: None,
                     }),
                     reactions: Default::default(),
                     thread_root: None,
@@ -752,9 +753,12 @@ mod observable_items_tests {
                     read_receipts: Default::default(),
                     is_own: false,
                     is_highlighted: false,
+                    mentions_me: false,
+                    url_previews: Vec::new(),
                     encryption_info: None,
                     original_json: None,
                     latest_edit_json: None,
+                    encrypted_json: None,
                     origin: RemoteEventOrigin::Sync,
                 }),
                 false,
@@ -774,6 +778,7 @@ mod observable_items_tests {
                         msgtype: MessageType::Text(TextMessageEventContent::plain("hello")),
                         edited: false,
                         mentions: None,
+                        effect: None,
                     }),
                     reactions: Default::default(),
                     thread_root: None,
@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
 use futures_core::Stream;
 use indexmap::IndexMap;
@@ -46,13 +50,34 @@ pub(super) struct ReadReceipts {
 
     /// A sender to notify of changes to the receipts of our own user.
     own_user_read_receipts_changed_sender: watch::Sender<()>,
+
+    /// The events whose timeline items hold their read receipts, if the read
+    /// receipts are lazily loaded.
+    ///
+    /// If this is `None`, every timeline item holds its read receipts.
+    lazy_loaded_events: Option<HashSet<OwnedEventId>>,
 }
 
 impl ReadReceipts {
+    /// Create the caches, with the read receipts of the timeline items lazily
+    /// loaded or not.
+    pub(super) fn new(lazy_load: bool) -> Self {
+        Self { lazy_loaded_events: lazy_load.then(HashSet::new), ..Default::default() }
+    }
+
     /// Empty the caches.
     pub(super) fn clear(&mut self) {
         self.by_event.clear();
         self.latest_by_user.clear();
+
+        if let Some(events) = &mut self.lazy_loaded_events {
+            events.clear();
+        }
+    }
+
+    /// Whether the timeline item of the given event holds its read receipts.
+    fn are_loaded_for(&self, event_id: &EventId) -> bool {
+        self.lazy_loaded_events.as_ref().is_none_or(|events| events.contains(event_id))
     }
 
     /// Subscribe to changes in the read receipts of our own user.
@@ -227,10 +252,14 @@ impl ReadReceipts {
             return;
         }
 
+        // Only the items whose read receipts are loaded must be updated.
+        let old_item_event_id = old_item_event_id.filter(|event_id| self.are_loaded_for(event_id));
+        let new_item_event_id = new_item_event_id.filter(|event_id| self.are_loaded_for(event_id));
+
         let timeline_update = ReadReceiptTimelineUpdate {
-            old_item_pos,
+            old_item_pos: old_item_pos.filter(|_| old_item_event_id.is_some()),
             old_event_id: old_item_event_id,
-            new_item_pos,
+            new_item_pos: new_item_pos.filter(|_| new_item_event_id.is_some()),
             new_event_id: new_item_event_id,
         };
 
@@ -271,6 +300,10 @@ impl ReadReceipts {
     ///
     /// This includes all the receipts on the event as well as all the receipts
     /// on the following events that are filtered out (not visible).
+    ///
+    /// If the read receipts of the given event aren't loaded, this is empty,
+    /// but the receipts of the hidden events are still taken from the
+    /// previous timeline item.
     #[instrument(skip(self, timeline_items, at_end))]
     pub(super) fn compute_event_receipts(
         &self,
@@ -278,7 +311,13 @@ impl ReadReceipts {
         timeline_items: &mut ObservableItemsTransaction<'_>,
         at_end: bool,
     ) -> IndexMap<OwnedUserId, Receipt> {
-        let mut all_receipts = self.get_event_receipts(event_id).cloned().unwrap_or_default();
+        let is_loaded = self.are_loaded_for(event_id);
+
+        let mut all_receipts = if is_loaded {
+            self.get_event_receipts(event_id).cloned().unwrap_or_default()
+        } else {
+            IndexMap::new()
+        };
 
         if at_end {
             // No need to search for extra receipts, there are no events after.
@@ -348,7 +387,10 @@ impl ReadReceipts {
             }
         }
 
-        all_receipts.extend(hidden);
+        if is_loaded {
+            all_receipts.extend(hidden);
+        }
+
         trace!(
             "computed receipts: {}",
             all_receipts.iter().map(|(u, _)| u.as_str()).collect::<Vec<_>>().join(", ")
@@ -515,6 +557,13 @@ impl<P: RoomDataProvider> TimelineStateTransaction<'_, P> {
                 }
 
                 for (user_id, receipt) in receipts {
+                    // Our receipts in the threads mark them as read on their root items.
+                    if user_id == own_user_id
+                        && let ReceiptThread::Thread(thread_root) = &receipt.thread
+                    {
+                        self.maybe_mark_thread_as_read(thread_root, &event_id);
+                    }
+
                     if matches!(own_receipt_thread, ReceiptThread::Unthreaded | ReceiptThread::Main)
                     {
                         // If the own receipt thread is unthreaded or main, we maintain maximal
@@ -638,6 +687,11 @@ impl<P: RoomDataProvider> TimelineStateTransaction<'_, P> {
             return;
         };
 
+        if !self.meta.read_receipts.are_loaded_for(&prev_event_meta.event_id) {
+            trace!("The read receipts of the previous visible event aren't loaded, exiting");
+            return;
+        }
+
         let Some((prev_item_pos, prev_event_item)) =
             rfind_event_by_id(&self.items, &prev_event_meta.event_id)
         else {
@@ -669,6 +723,71 @@ impl<P: RoomDataProvider> TimelineStateTransaction<'_, P> {
         remote_prev_event_item.read_receipts = read_receipts;
         self.items.replace(prev_item_pos, TimelineItem::new(prev_event_item, prev_event_item_id));
     }
+
+    /// Set the window of timeline items which hold their read receipts, if
+    /// they are lazily loaded.
+    ///
+    /// The read receipts of the event items in the given range are computed,
+    /// and the ones of the items which aren't in the range anymore are
+    /// removed.
+    pub(super) fn set_read_receipts_window(&mut self, range: Range<usize>) {
+        let Some(loaded_events) = &self.meta.read_receipts.lazy_loaded_events else {
+            trace!("read receipts aren't lazily loaded, nothing to do");
+            return;
+        };
+
+        let window = range
+            .filter_map(|idx| Some(self.items.get(idx)?.as_event()?.as_remote()?.event_id.clone()))
+            .collect::<Vec<_>>();
+        let new_loaded_events = window.iter().cloned().collect::<HashSet<_>>();
+
+        let unloaded = loaded_events.difference(&new_loaded_events).cloned().collect::<Vec<_>>();
+        // Keep the order of the items, so they're updated from the oldest to the
+        // newest.
+        let loaded = window
+            .into_iter()
+            .filter(|event_id| !loaded_events.contains(event_id))
+            .collect::<Vec<_>>();
+
+        self.meta.read_receipts.lazy_loaded_events = Some(new_loaded_events);
+
+        for event_id in unloaded {
+            self.replace_read_receipts_of(&event_id, IndexMap::new());
+        }
+
+        for event_id in loaded {
+            let read_receipts =
+                self.meta.read_receipts.compute_event_receipts(&event_id, &mut self.items, false);
+            self.replace_read_receipts_of(&event_id, read_receipts);
+        }
+    }
+
+    /// Replace the read receipts of the timeline item of the given event.
+    fn replace_read_receipts_of(
+        &mut self,
+        event_id: &EventId,
+        read_receipts: IndexMap<OwnedUserId, Receipt>,
+    ) {
+        let Some((item_pos, event_item)) = rfind_event_by_id(&self.items, event_id) else {
+            trace!(%event_id, "the event item isn't in the timeline anymore");
+            return;
+        };
+
+        let event_item_id = event_item.internal_id.to_owned();
+        let mut event_item = event_item.clone();
+
+        let Some(remote_event_item) = event_item.as_remote_mut() else {
+            warn!("loading read receipts for a local item, this should not be possible");
+            return;
+        };
+
+        if read_receipts.is_empty() && remote_event_item.read_receipts.is_empty() {
+            return;
+        }
+
+        remote_event_item.read_receipts = read_receipts;
+        self.items.replace(item_pos, TimelineItem::new(event_item, event_item_id));
+    }
 }
 
 impl<P: RoomDataProvider> TimelineState<P> {
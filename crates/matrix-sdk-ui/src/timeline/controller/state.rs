@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{future::Future, sync::Arc};
+use std::{future::Future, sync::Arc, time::Duration};
 
 use eyeball_im::VectorDiff;
 use matrix_sdk::{deserialized_responses::TimelineEvent, send_queue::SendHandle};
@@ -28,7 +28,7 @@ use tracing::{instrument, trace, warn};
 
 use super::{
     super::{
-        Profile, TimelineItem,
+        MessageEffects, Profile, ReactionOrdering, TimelineContentTransformerFn, TimelineItem,
         date_dividers::DateDividerAdjuster,
         event_handler::{
             Flow, TimelineAction, TimelineEventContext, TimelineEventHandler, TimelineItemPosition,
@@ -51,6 +51,7 @@ pub(in crate::timeline) struct TimelineState<P: RoomDataProvider> {
 }
 
 impl<P: RoomDataProvider> TimelineState<P> {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         focus: Arc<TimelineFocusKind<P>>,
         own_user_id: OwnedUserId,
@@ -58,6 +59,12 @@ impl<P: RoomDataProvider> TimelineState<P> {
         internal_id_prefix: Option<String>,
         unable_to_decrypt_hook: Option<Arc<UtdHookManager>>,
         is_room_encrypted: bool,
+        grouping_time_window: Option<Duration>,
+        lazy_read_receipts: bool,
+        content_transformers: Vec<Arc<TimelineContentTransformerFn>>,
+        collapse_membership_changes: bool,
+        reaction_ordering: Option<ReactionOrdering>,
+        message_effects: Option<Arc<MessageEffects>>,
     ) -> Self {
         Self {
             items: ObservableItems::new(),
@@ -67,6 +74,12 @@ impl<P: RoomDataProvider> TimelineState<P> {
                 internal_id_prefix,
                 unable_to_decrypt_hook,
                 is_room_encrypted,
+                grouping_time_window,
+                lazy_read_receipts,
+                content_transformers,
+                collapse_membership_changes,
+                reaction_ordering,
+                message_effects,
             ),
             focus,
         }
@@ -172,7 +185,10 @@ impl<P: RoomDataProvider> TimelineState<P> {
             TimelineFocusKind::Thread { root_event_id, .. } => {
                 thread_root.as_ref().is_some_and(|r| r == root_event_id)
             }
-            TimelineFocusKind::Event { .. } | TimelineFocusKind::PinnedEvents { .. } => {
+            TimelineFocusKind::Event { .. }
+            | TimelineFocusKind::PinnedEvents { .. }
+            | TimelineFocusKind::StarredEvents { .. }
+            | TimelineFocusKind::Preview { .. } => {
                 // Don't add new items to these timelines; aggregations are added independently
                 // of the `should_add_new_items` value.
                 false
@@ -202,13 +218,16 @@ impl<P: RoomDataProvider> TimelineState<P> {
         txn.commit();
     }
 
+    /// Replace the items at the given indices with their decrypted version,
+    /// and return the number of items which could be decrypted.
     pub(super) async fn retry_event_decryption<Fut>(
         &mut self,
         retry_one: impl Fn(Arc<TimelineItem>) -> Fut,
         retry_indices: Vec<usize>,
         room_data_provider: &P,
         settings: &TimelineSettings,
-    ) where
+    ) -> usize
+    where
         Fut: Future<Output = Option<TimelineEvent>>,
     {
         let mut txn = self.transaction();
@@ -220,12 +239,15 @@ impl<P: RoomDataProvider> TimelineState<P> {
         // before the event being edited, if both were UTD. Keep track of
         // index change as UTDs are removed instead of updated.
         let mut offset = 0;
+        let mut num_decrypted = 0;
         for idx in retry_indices {
             let idx = idx - offset;
             let Some(event) = retry_one(txn.items[idx].clone()).await else {
                 continue;
             };
 
+            num_decrypted += 1;
+
             let removed_item = txn
                 .handle_remote_event(
                     event,
@@ -246,6 +268,8 @@ impl<P: RoomDataProvider> TimelineState<P> {
         txn.adjust_date_dividers(date_divider_adjuster);
 
         txn.commit();
+
+        num_decrypted
     }
 
     #[cfg(test)]
@@ -300,6 +324,12 @@ impl<P: RoomDataProvider> TimelineState<P> {
         txn.commit();
     }
 
+    pub(super) fn transform_content_of_all_events(&mut self) {
+        let mut txn = self.transaction();
+        txn.transform_content_of_all_events();
+        txn.commit();
+    }
+
     pub(super) fn transaction(&mut self) -> TimelineStateTransaction<'_, P> {
         TimelineStateTransaction::new(&mut self.items, &mut self.meta, &*self.focus)
     }
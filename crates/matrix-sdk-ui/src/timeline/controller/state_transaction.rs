@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
 use eyeball_im::VectorDiff;
 use itertools::Itertools as _;
@@ -21,7 +25,12 @@ use matrix_sdk::deserialized_responses::{
 };
 use ruma::{
     EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, OwnedUserId, UserId,
-    events::AnySyncTimelineEvent, push::Action, serde::Raw,
+    events::{
+        AnySyncTimelineEvent,
+        receipt::{ReceiptThread, ReceiptType},
+    },
+    push::Action,
+    serde::Raw,
 };
 use tracing::{debug, instrument, trace, warn};
 
@@ -37,7 +46,10 @@ use super::{
     metadata::EventMeta,
 };
 use crate::timeline::{
-    EmbeddedEvent, ThreadSummary, TimelineDetails, VirtualTimelineItem,
+    CustomItemAnchor, EmbeddedEvent, EventTimelineItem, MembershipSummary, MsgLikeContent,
+    ThreadSummary, TimelineDetails, TimelineEventItemId, TimelineItem, TimelineItemContent,
+    TimelineItemKind, VirtualTimelineItem,
+    algorithms::rfind_event_by_id,
     controller::TimelineFocusKind,
     event_handler::{FailedToParseEvent, RemovedItem, TimelineAction},
 };
@@ -60,6 +72,10 @@ pub(in crate::timeline) struct TimelineStateTransaction<'a, P: RoomDataProvider>
 
     /// The kind of focus of this timeline.
     pub focus: &'a TimelineFocusKind<P>,
+
+    /// The IDs of the remote events which got a new item during this
+    /// transaction.
+    added_event_ids: HashSet<OwnedEventId>,
 }
 
 impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
@@ -79,6 +95,7 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
             previous_meta,
             meta,
             focus,
+            added_event_ids: HashSet::new(),
         }
     }
 
@@ -92,6 +109,7 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
     ) {
         let mut date_divider_adjuster =
             DateDividerAdjuster::new(settings.date_divider_mode.clone());
+        let mut has_been_reset = false;
 
         for diff in diffs {
             match diff {
@@ -169,6 +187,10 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
                 }
 
                 VectorDiff::Clear => {
+                    // A clear coming from a sync, while there were events before, means that
+                    // the event cache has been reset because of a gap.
+                    has_been_reset |= matches!(origin, RemoteEventOrigin::Sync)
+                        && self.items.all_remote_events().last_index().is_some();
                     self.clear();
                 }
 
@@ -176,6 +198,12 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
             }
         }
 
+        if has_been_reset {
+            self.insert_timeline_reset_item();
+        } else {
+            self.remove_filled_timeline_reset_item();
+        }
+
         self.adjust_date_dividers(date_divider_adjuster);
         self.check_invariants();
     }
@@ -214,6 +242,7 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
         .await
         {
             let encryption_info = event.kind.encryption_info().cloned();
+            let encrypted_event = event.kind.encrypted_raw().cloned();
 
             let sender_profile = room_data_provider.profile_from_user_id(&sender).await;
 
@@ -228,6 +257,7 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
                     event_id: event_id.clone(),
                     raw_event: event.raw().clone(),
                     encryption_info,
+                    encrypted_event,
                     txn_id,
                     position,
                 },
@@ -410,10 +440,15 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
                 room_data_provider.is_pinned_event(event.event_id())
             }
 
-            TimelineFocusKind::Event { hide_threaded_events, paginator: _ } => {
+            TimelineFocusKind::StarredEvents { loader } => {
+                // Only add the loaded starred events for the starred events timeline.
+                loader.is_starred_event(event.event_id())
+            }
+
+            TimelineFocusKind::Event { .. } | TimelineFocusKind::Preview { .. } => {
                 // If the timeline's filtering out in-thread events, don't add items for
                 // threaded events.
-                if thread_root.is_some() && *hide_threaded_events {
+                if thread_root.is_some() && self.focus.hide_threaded_events() {
                     return false;
                 }
 
@@ -554,6 +589,39 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
         }
     }
 
+    /// Mark the thread as read on its root item, if our read receipt in the
+    /// thread has moved to its latest reply.
+    pub(super) fn maybe_mark_thread_as_read(&mut self, thread_root: &EventId, event_id: &EventId) {
+        let Some((index, item)) = rfind_event_by_id(&self.items, thread_root) else {
+            return;
+        };
+
+        let mut content = item.content().clone();
+
+        let TimelineItemContent::MsgLike(MsgLikeContent { thread_summary: Some(summary), .. }) =
+            &mut content
+        else {
+            return;
+        };
+
+        let is_latest_reply = matches!(
+            &summary.latest_event,
+            TimelineDetails::Ready(latest_event)
+                if matches!(&latest_event.identifier, TimelineEventItemId::EventId(id) if id == event_id)
+        );
+
+        if !summary.is_unread || !is_latest_reply {
+            return;
+        }
+
+        trace!(%thread_root, "marking the thread as read");
+        summary.is_unread = false;
+
+        let internal_id = item.internal_id.to_owned();
+        let new_item = item.with_content(content);
+        self.items.replace(index, TimelineItem::new(new_item, internal_id));
+    }
+
     // Attempt to load a thread's latest reply as an embedded timeline item, either
     // using the event cache or the storage.
     async fn fetch_latest_thread_reply(
@@ -592,21 +660,39 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
         let is_highlighted =
             event.push_actions().is_some_and(|actions| actions.iter().any(Action::is_highlight));
 
-        let thread_summary = if let ThreadSummaryStatus::Some(summary) = event.thread_summary {
-            let latest_reply_item = if let Some(latest_reply) = summary.latest_reply {
-                self.fetch_latest_thread_reply(&latest_reply, room_data_provider).await
+        let thread_summary = if let ThreadSummaryStatus::Some(summary) = &event.thread_summary {
+            let latest_reply_item = if let Some(latest_reply) = &summary.latest_reply {
+                self.fetch_latest_thread_reply(latest_reply, room_data_provider).await
             } else {
                 None
             };
+
+            let is_unread = if let Some(latest_reply) = &summary.latest_reply
+                && let Some(thread_root) = event.event_id()
+            {
+                is_thread_unread(
+                    &thread_root,
+                    latest_reply,
+                    latest_reply_item.as_deref(),
+                    room_data_provider,
+                )
+                .await
+            } else {
+                false
+            };
+
             Some(ThreadSummary {
                 latest_event: TimelineDetails::from_initial_value(latest_reply_item),
                 num_replies: summary.num_replies,
+                num_participants: summary.num_participants,
+                is_unread,
             })
         } else {
             None
         };
 
         let encryption_info = event.kind.encryption_info().cloned();
+        let encrypted_event = event.kind.encrypted_raw().cloned();
 
         let bundled_edit_encryption_info = event.kind.unsigned_encryption_map().and_then(|map| {
             map.get(&UnsignedEventLocation::RelationsReplace)?.encryption_info().cloned()
@@ -704,6 +790,7 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
                     event_id: event_id.clone(),
                     raw_event: raw,
                     encryption_info,
+                    encrypted_event,
                     txn_id,
                     position,
                 },
@@ -720,6 +807,10 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
 
         let mut item_removed = false;
 
+        if item_added && !matches!(position, TimelineItemPosition::UpdateAt { .. }) {
+            self.added_event_ids.insert(event_id);
+        }
+
         if !item_added {
             trace!("No new item added");
 
@@ -763,6 +854,38 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
         }
     }
 
+    /// Let the observers know why the timeline has been emptied, by inserting
+    /// a [`VirtualTimelineItem::TimelineReset`] before the first event received
+    /// after the reset.
+    fn insert_timeline_reset_item(&mut self) {
+        let index = self
+            .items
+            .iter_remotes_region()
+            .find_map(|(index, item)| item.as_event().map(|_| index))
+            .unwrap_or_else(|| self.items.first_remotes_region_index());
+
+        let item = self.meta.new_timeline_item(VirtualTimelineItem::TimelineReset);
+        self.items.insert(index, item, None);
+    }
+
+    /// Remove the [`VirtualTimelineItem::TimelineReset`] once events have been
+    /// back-paginated before it: the gap it marks has been filled, so the
+    /// events before and after it follow each other again.
+    fn remove_filled_timeline_reset_item(&mut self) {
+        let mut has_events_before = false;
+
+        let Some(reset_index) = self.items.iter_remotes_region().find_map(|(index, item)| {
+            has_events_before |= item.as_event().is_some();
+            item.is_timeline_reset().then_some(index)
+        }) else {
+            return;
+        };
+
+        if has_events_before {
+            self.items.remove(reset_index);
+        }
+    }
+
     pub(super) fn clear(&mut self) {
         // By first checking if there are any local echoes first, we do a bit
         // more work in case some are found, but it should be worth it because
@@ -775,9 +898,11 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
                 if entry.is_remote_event()
                     || entry.as_virtual().is_some_and(|vitem| match vitem {
                         VirtualTimelineItem::DateDivider(_) => false,
-                        VirtualTimelineItem::ReadMarker | VirtualTimelineItem::TimelineStart => {
-                            true
-                        }
+                        VirtualTimelineItem::ReadMarker
+                        | VirtualTimelineItem::TimelineStart
+                        | VirtualTimelineItem::TimelineReset
+                        | VirtualTimelineItem::MembershipSummary(_)
+                        | VirtualTimelineItem::Custom(_) => true,
                     })
                 {
                     ObservableItemsTransactionEntry::remove(entry);
@@ -816,7 +941,11 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
         self.meta.update_read_marker(&mut self.items);
     }
 
-    pub(super) fn commit(self) {
+    pub(super) fn commit(mut self) {
+        self.collapse_membership_changes();
+        self.adjust_custom_items();
+        self.adjust_groups();
+
         // Update the `subscriber_skip_count` value.
         let previous_number_of_items = self.number_of_items_when_transaction_started;
         let next_number_of_items = self.items.len();
@@ -893,6 +1022,195 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
         }
     }
 
+    /// Replace the runs of consecutive membership and profile changes added
+    /// during this transaction with a summary item, if the collapsing is
+    /// enabled for this timeline.
+    ///
+    /// Items which were already in the timeline are never collapsed, so a
+    /// summary which has been expanded stays so.
+    fn collapse_membership_changes(&mut self) {
+        if !self.meta.collapse_membership_changes || self.added_event_ids.is_empty() {
+            return;
+        }
+
+        let is_collapsible = |item: &TimelineItem| {
+            item.as_event().is_some_and(|event| {
+                matches!(
+                    event.content(),
+                    TimelineItemContent::MembershipChange(_)
+                        | TimelineItemContent::ProfileChange(_)
+                ) && event
+                    .event_id()
+                    .is_some_and(|event_id| self.added_event_ids.contains(event_id))
+            })
+        };
+
+        let mut runs = Vec::new();
+        let mut run_start = None;
+
+        for (index, item) in self.items.iter_all_regions() {
+            match (is_collapsible(item), run_start) {
+                (true, None) => run_start = Some(index),
+                (false, Some(start)) => {
+                    runs.push(start..index);
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(start) = run_start {
+            runs.push(start..self.items.len());
+        }
+
+        // Replace the runs from the last one, so the indices of the others stay valid.
+        for run in runs.into_iter().rev().filter(|run| run.len() > 1) {
+            let mut items =
+                run.clone().rev().map(|index| self.items.remove(index)).collect::<Vec<_>>();
+            items.reverse();
+
+            let Some(timestamp) = items[0].as_event().map(|event| event.timestamp()) else {
+                continue;
+            };
+
+            trace!(count = items.len(), "Collapsing membership changes");
+
+            let event_ids = items
+                .iter()
+                .filter_map(|item| item.as_event()?.event_id().map(ToOwned::to_owned))
+                .collect();
+
+            let summary = self.meta.new_timeline_item(VirtualTimelineItem::MembershipSummary(
+                MembershipSummary { timestamp, items },
+            ));
+            self.meta.membership_summaries.insert(summary.unique_id().clone(), event_ids);
+            self.items.insert(run.start, summary, None);
+        }
+    }
+
+    /// Keep the custom virtual items next to their anchor event.
+    ///
+    /// An item whose anchor isn't in the timeline is removed, and it's inserted
+    /// back when its anchor is.
+    fn adjust_custom_items(&mut self) {
+        if self.meta.custom_items.is_empty() {
+            return;
+        }
+
+        // Only the read marker and the other custom items can be between an item and
+        // its anchor.
+        let is_transparent =
+            |item: &Arc<TimelineItem>| item.is_read_marker() || item.as_custom().is_some();
+
+        for custom in self.meta.custom_items.clone() {
+            let current_index = self.items.iter_all_regions().find_map(|(index, item)| {
+                item.as_custom().is_some_and(|item| item.id == custom.id).then_some(index)
+            });
+            let anchor_index =
+                rfind_event_by_id(&self.items, custom.anchor.event_id()).map(|(index, _)| index);
+
+            let Some(mut anchor_index) = anchor_index else {
+                if let Some(index) = current_index {
+                    trace!(id = %custom.id, "Removing custom item whose anchor is gone");
+                    self.items.remove(index);
+                }
+                continue;
+            };
+
+            if let Some(index) = current_index {
+                let is_in_place = match &custom.anchor {
+                    CustomItemAnchor::Before(_) => {
+                        index < anchor_index
+                            && (index + 1..anchor_index).all(|i| is_transparent(&self.items[i]))
+                    }
+                    CustomItemAnchor::After(_) => {
+                        index > anchor_index
+                            && (anchor_index + 1..index).all(|i| is_transparent(&self.items[i]))
+                    }
+                };
+
+                if is_in_place {
+                    if self.items[index].as_custom() != Some(&custom) {
+                        let item = self.items[index].with_kind(VirtualTimelineItem::Custom(custom));
+                        self.items.replace(index, item);
+                    }
+                    continue;
+                }
+
+                self.items.remove(index);
+                if index < anchor_index {
+                    anchor_index -= 1;
+                }
+            }
+
+            let index = match &custom.anchor {
+                CustomItemAnchor::Before(_) => anchor_index,
+                CustomItemAnchor::After(_) => {
+                    let mut index = anchor_index + 1;
+                    while self.items.get(index).is_some_and(|item| item.as_custom().is_some()) {
+                        index += 1;
+                    }
+                    index
+                }
+            };
+
+            self.items.insert(index, TimelineItem::custom(custom), None);
+        }
+    }
+
+    /// Update the position of the event items in their group of consecutive
+    /// messages, if the grouping is enabled for this timeline.
+    ///
+    /// Only the items whose position changed are replaced.
+    fn adjust_groups(&mut self) {
+        let Some(time_window) = self.meta.grouping_time_window else {
+            return;
+        };
+
+        // Whether each item is the first and the last of its group, by index.
+        let mut positions = vec![(true, true); self.items.len()];
+        let mut prev_event: Option<(usize, &EventTimelineItem)> = None;
+
+        for (index, item) in self.items.iter_all_regions() {
+            match item.kind() {
+                TimelineItemKind::Event(event) => {
+                    if let Some((prev_index, prev)) = prev_event
+                        && can_be_grouped(prev, event, time_window)
+                    {
+                        positions[prev_index].1 = false;
+                        positions[index].0 = false;
+                    }
+
+                    prev_event = Some((index, event));
+                }
+
+                // The read marker doesn't split the groups, contrary to the other virtual
+                // items.
+                TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker) => {}
+                TimelineItemKind::Virtual(_) => prev_event = None,
+            }
+        }
+
+        for (index, (is_first, is_last)) in positions.into_iter().enumerate() {
+            let item = &self.items[index];
+
+            let Some(event) = item.as_event() else {
+                continue;
+            };
+
+            if event.is_first_of_group == is_first && event.is_last_of_group == is_last {
+                continue;
+            }
+
+            let mut event = event.clone();
+            event.is_first_of_group = is_first;
+            event.is_last_of_group = is_last;
+
+            let item = item.with_kind(event);
+            self.items.replace(index, item);
+        }
+    }
+
     pub(super) fn adjust_date_dividers(&mut self, mut adjuster: DateDividerAdjuster) {
         adjuster.run(&mut self.items, &mut self.meta);
     }
@@ -918,4 +1236,69 @@ impl<'a, P: RoomDataProvider> TimelineStateTransaction<'a, P> {
             }
         }
     }
+
+    /// Run the content transformers on the current content of all the event
+    /// items, replacing each of them once.
+    pub(super) fn transform_content_of_all_events(&mut self) {
+        if self.meta.content_transformers.is_empty() {
+            return;
+        }
+
+        for idx in 0..self.items.len() {
+            let item = &self.items[idx];
+
+            if let Some(event) = item.as_event() {
+                let transformed_event = self.meta.content_transformers.transform(event.clone());
+                let item = item.with_kind(transformed_event);
+                self.items.replace(idx, item);
+            }
+        }
+    }
+}
+
+/// Whether the latest reply of a thread hasn't been read by the current user.
+///
+/// The thread is read if the latest reply has been sent by the current user, or
+/// if their (public or private) read receipt in the thread is on it.
+async fn is_thread_unread<P: RoomDataProvider>(
+    thread_root: &EventId,
+    latest_reply: &EventId,
+    latest_reply_item: Option<&EmbeddedEvent>,
+    room_data_provider: &P,
+) -> bool {
+    let own_user_id = room_data_provider.own_user_id();
+
+    if latest_reply_item.is_some_and(|item| item.sender == own_user_id) {
+        return false;
+    }
+
+    let thread = ReceiptThread::Thread(thread_root.to_owned());
+
+    for receipt_type in [ReceiptType::Read, ReceiptType::ReadPrivate] {
+        if let Some((event_id, _)) =
+            room_data_provider.load_user_receipt(receipt_type, thread.clone(), own_user_id).await
+            && event_id == latest_reply
+        {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Whether two consecutive event items belong to the same group: they're both
+/// messages from the same sender, sent within the given time window.
+fn can_be_grouped(
+    prev: &EventTimelineItem,
+    next: &EventTimelineItem,
+    time_window: Duration,
+) -> bool {
+    let is_message =
+        |event: &EventTimelineItem| matches!(event.content(), TimelineItemContent::MsgLike(_));
+    let elapsed = u64::from(next.timestamp().0).saturating_sub(u64::from(prev.timestamp().0));
+
+    prev.sender() == next.sender()
+        && is_message(prev)
+        && is_message(next)
+        && u128::from(elapsed) <= time_window.as_millis()
 }
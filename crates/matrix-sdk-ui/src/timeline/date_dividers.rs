@@ -126,6 +126,13 @@ impl DateDividerAdjuster {
         // non-decreasing order of the indices), so we must record the insert
         // position for an operation related to the previous item.
 
+        if matches!(self.mode, DateDividerMode::None) {
+            // The mode never changes for a given timeline, so there's no date divider to
+            // remove either.
+            self.consumed = true;
+            return;
+        }
+
         let mut prev_item: Option<PrevItemDesc<'_>> = None;
         let mut latest_event_ts = None;
 
@@ -143,8 +150,9 @@ impl DateDividerAdjuster {
                     }
                 }
 
-                TimelineItemKind::Event(event) => {
-                    let ts = event.timestamp();
+                TimelineItemKind::Event(_)
+                | TimelineItemKind::Virtual(VirtualTimelineItem::MembershipSummary(_)) => {
+                    let Some(ts) = dated_item_timestamp(item) else { continue };
 
                     self.handle_event(i, ts, prev_item, latest_event_ts);
 
@@ -154,7 +162,9 @@ impl DateDividerAdjuster {
                 }
 
                 TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker)
-                | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineStart) => {
+                | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineStart)
+                | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineReset)
+                | TimelineItemKind::Virtual(VirtualTimelineItem::Custom(_)) => {
                     // Nothing to do.
                 }
             }
@@ -184,7 +194,7 @@ impl DateDividerAdjuster {
                 }
             }
 
-            if item.is_event() {
+            if dated_item_timestamp(item).is_some() {
                 // Stop as soon as we run into the first (trailing) event.
                 break;
             }
@@ -232,9 +242,12 @@ impl DateDividerAdjuster {
         };
 
         match prev_item.kind() {
-            TimelineItemKind::Event(event) => {
-                // This date divider is preceded by an event.
-                if self.is_same_date_divider_group_as(event.timestamp(), ts) {
+            TimelineItemKind::Event(_)
+            | TimelineItemKind::Virtual(VirtualTimelineItem::MembershipSummary(_)) => {
+                // This date divider is preceded by an event, or a summary of events.
+                if dated_item_timestamp(prev_item)
+                    .is_some_and(|prev_ts| self.is_same_date_divider_group_as(prev_ts, ts))
+                {
                     // The event has the same date as the date divider: remove the current date
                     // divider.
                     trace!("removing date divider following event with same timestamp @ {i}");
@@ -251,7 +264,9 @@ impl DateDividerAdjuster {
             }
 
             TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker)
-            | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineStart) => {
+            | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineStart)
+            | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineReset)
+            | TimelineItemKind::Virtual(VirtualTimelineItem::Custom(_)) => {
                 // Nothing to do.
             }
         }
@@ -276,10 +291,11 @@ impl DateDividerAdjuster {
         };
 
         match item.kind() {
-            TimelineItemKind::Event(prev_event) => {
-                // The event is preceded by another event. If they're not the same date,
-                // insert a date divider.
-                let prev_ts = prev_event.timestamp();
+            TimelineItemKind::Event(_)
+            | TimelineItemKind::Virtual(VirtualTimelineItem::MembershipSummary(_)) => {
+                // The event is preceded by another event, or a summary of events. If they're
+                // not the same date, insert a date divider.
+                let Some(prev_ts) = dated_item_timestamp(item) else { return };
 
                 if !self.is_same_date_divider_group_as(prev_ts, ts) {
                     trace!(
@@ -291,14 +307,12 @@ impl DateDividerAdjuster {
             }
 
             TimelineItemKind::Virtual(VirtualTimelineItem::DateDivider(prev_ts)) => {
-                let event_date = timestamp_to_date(ts);
-
                 // The event is preceded by a date divider.
-                if timestamp_to_date(*prev_ts) != event_date {
+                if !self.is_same_date_divider_group_as(*prev_ts, ts) {
                     // The date divider is wrong. Should we replace it with the correct value, or
                     // remove it entirely?
                     if let Some(last_event_ts) = latest_event_ts
-                        && timestamp_to_date(last_event_ts) == event_date
+                        && self.is_same_date_divider_group_as(last_event_ts, ts)
                     {
                         // There's a previous event with the same date: remove the divider.
                         trace!(
@@ -317,7 +331,9 @@ impl DateDividerAdjuster {
             }
 
             TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker)
-            | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineStart) => {
+            | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineStart)
+            | TimelineItemKind::Virtual(VirtualTimelineItem::TimelineReset)
+            | TimelineItemKind::Virtual(VirtualTimelineItem::Custom(_)) => {
                 // Nothing to do.
             }
         }
@@ -410,7 +426,9 @@ impl DateDividerAdjuster {
         {
             let mut i = items.first_remotes_region_index();
             while let Some(item) = items.get(i) {
-                if let Some(virt) = item.as_virtual() {
+                if let Some(virt) = item.as_virtual()
+                    && !matches!(virt, VirtualTimelineItem::MembershipSummary(_))
+                {
                     if matches!(virt, VirtualTimelineItem::DateDivider(_)) {
                         // We found a date divider among the first virtual items: stop here.
                         break;
@@ -452,9 +470,7 @@ impl DateDividerAdjuster {
             let mut prev_date_divider_ts = None;
 
             for (i, item) in items.iter_remotes_and_locals_regions() {
-                if let Some(ev) = item.as_event() {
-                    let ts = ev.timestamp();
-
+                if let Some(ts) = dated_item_timestamp(item) {
                     // We have the same date as the previous event we've seen.
                     if let Some(prev_ts) = prev_event_ts
                         && !self.is_same_date_divider_group_as(prev_ts, ts)
@@ -523,10 +539,24 @@ impl DateDividerAdjuster {
             DateDividerMode::Monthly => {
                 timestamp_to_date(lhs).is_same_month_as(timestamp_to_date(rhs))
             }
+            DateDividerMode::None => true,
+            DateDividerMode::Custom(bucket) => bucket(lhs) == bucket(rhs),
         }
     }
 }
 
+/// The timestamp of an item which must be preceded by a date divider, that is
+/// an event or a summary of collapsed events.
+fn dated_item_timestamp(item: &TimelineItem) -> Option<MilliSecondsSinceUnixEpoch> {
+    match item.kind() {
+        TimelineItemKind::Event(event) => Some(event.timestamp()),
+        TimelineItemKind::Virtual(VirtualTimelineItem::MembershipSummary(summary)) => {
+            Some(summary.timestamp())
+        }
+        TimelineItemKind::Virtual(_) => None,
+    }
+}
+
 #[derive(Debug)]
 enum DateDividerOperation {
     Insert(usize, MilliSecondsSinceUnixEpoch),
@@ -653,6 +683,8 @@ enum DateDividerInsertError {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use assert_matches2::assert_let;
     use ruma::{
         MilliSecondsSinceUnixEpoch, owned_event_id, owned_user_id,
@@ -675,9 +707,12 @@ mod tests {
             read_receipts: Default::default(),
             is_own: false,
             is_highlighted: false,
+            mentions_me: false,
+            url_previews: Vec::new(),
             encryption_info: None,
             original_json: None,
             latest_edit_json: None,
+            encrypted_json: None,
             origin: crate::timeline::event_item::RemoteEventOrigin::Sync,
         });
         EventTimelineItem::new(
@@ -691,7 +726,19 @@ mod tests {
     }
 
     fn test_metadata() -> TimelineMetadata {
-        TimelineMetadata::new(owned_user_id!("@a:b.c"), RoomVersionRules::V11, None, None, false)
+        TimelineMetadata::new(
+            owned_user_id!("@a:b.c"),
+            RoomVersionRules::V11,
+            None,
+            None,
+            false,
+            None,
+            false,
+            Vec::new(),
+            false,
+            None,
+            None,
+        )
     }
 
     #[test]
@@ -978,4 +1025,68 @@ mod tests {
         assert!(iter.next().unwrap().is_remote_event());
         assert!(iter.next().is_none());
     }
+
+    #[test]
+    fn test_no_divider_mode() {
+        let mut items = ObservableItems::new();
+        let mut txn = items.transaction();
+
+        let mut meta = test_metadata();
+
+        txn.push_back(
+            meta.new_timeline_item(event_with_ts(MilliSecondsSinceUnixEpoch(uint!(86_400_000)))),
+            None,
+        );
+        txn.push_back(
+            // One month later.
+            meta.new_timeline_item(event_with_ts(MilliSecondsSinceUnixEpoch(uint!(2_764_800_000)))),
+            None,
+        );
+
+        let mut adjuster = DateDividerAdjuster::new(DateDividerMode::None);
+        adjuster.run(&mut txn, &mut meta);
+
+        txn.commit();
+
+        let mut iter = items.iter();
+
+        assert!(iter.next().unwrap().is_remote_event());
+        assert!(iter.next().unwrap().is_remote_event());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_custom_divider_mode() {
+        let mut items = ObservableItems::new();
+        let mut txn = items.transaction();
+
+        let mut meta = test_metadata();
+
+        // Group the events by periods of 10 seconds.
+        let mode = DateDividerMode::Custom(Arc::new(|ts| u64::from(ts.0) / 10_000));
+
+        for ts in [uint!(1_000), uint!(9_000), uint!(12_000), uint!(35_000), uint!(39_000)] {
+            txn.push_back(
+                meta.new_timeline_item(event_with_ts(MilliSecondsSinceUnixEpoch(ts))),
+                None,
+            );
+        }
+
+        let mut adjuster = DateDividerAdjuster::new(mode);
+        adjuster.run(&mut txn, &mut meta);
+
+        txn.commit();
+
+        let mut iter = items.iter();
+
+        assert!(iter.next().unwrap().is_date_divider());
+        assert!(iter.next().unwrap().is_remote_event());
+        assert!(iter.next().unwrap().is_remote_event());
+        assert!(iter.next().unwrap().is_date_divider());
+        assert!(iter.next().unwrap().is_remote_event());
+        assert!(iter.next().unwrap().is_date_divider());
+        assert!(iter.next().unwrap().is_remote_event());
+        assert!(iter.next().unwrap().is_remote_event());
+        assert!(iter.next().is_none());
+    }
 }
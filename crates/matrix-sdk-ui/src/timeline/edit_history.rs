@@ -0,0 +1,167 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use matrix_sdk::{
+    deserialized_responses::TimelineEvent,
+    room::{IncludeRelations, RelationsOptions},
+};
+use ruma::{
+    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId,
+    events::{
+        AnySyncMessageLikeEvent, AnySyncTimelineEvent, SyncMessageLikeEvent,
+        relation::RelationType,
+        room::message::{MessageType, OriginalSyncRoomMessageEvent, Relation},
+    },
+};
+use tracing::{instrument, warn};
+
+use super::{EditHistoryError, Error};
+
+/// A version of the content of an event, as it was originally sent or as it
+/// has been edited.
+#[derive(Clone, Debug)]
+pub struct EditHistoryEntry {
+    /// The ID of the original event for the first entry, or of the edit for
+    /// the others.
+    pub event_id: OwnedEventId,
+
+    /// When this version has been sent.
+    pub timestamp: MilliSecondsSinceUnixEpoch,
+
+    /// The content of this version.
+    pub content: MessageType,
+}
+
+impl EditHistoryEntry {
+    /// The plain text body of this version.
+    pub fn body(&self) -> &str {
+        self.content.body()
+    }
+}
+
+impl super::Timeline {
+    /// Get the edit history of the room message with the given event ID.
+    ///
+    /// The edits known by the event cache are combined with the ones returned
+    /// by the `/relations` endpoint, which is paginated until its end.
+    ///
+    /// Returns the original content first, followed by every edit sent by the
+    /// original sender, from the oldest to the most recent one. Edits sent by
+    /// anyone else are ignored, as they're not valid.
+    #[instrument(skip(self), fields(room_id = ?self.room().room_id()))]
+    pub async fn edit_history(&self, event_id: &EventId) -> Result<Vec<EditHistoryEntry>, Error> {
+        let (original, mut candidates) = match self
+            .event_cache
+            .find_event_with_relations(event_id, Some(vec![RelationType::Replacement]))
+            .await
+        {
+            Some(found) => found,
+            None => (
+                self.room()
+                    .load_or_fetch_event(event_id, None)
+                    .await
+                    .map_err(EditHistoryError::from)?,
+                Vec::new(),
+            ),
+        };
+
+        let Some(original) = as_original_room_message(&original) else {
+            return Err(EditHistoryError::NotRoomMessage.into());
+        };
+
+        let mut from = None;
+
+        loop {
+            let relations = self
+                .room()
+                .relations(
+                    event_id.to_owned(),
+                    RelationsOptions {
+                        from,
+                        include_relations: IncludeRelations::RelationsOfType(
+                            RelationType::Replacement,
+                        ),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map_err(EditHistoryError::from)?;
+
+            candidates.extend(relations.chunk);
+
+            match relations.next_batch_token {
+                Some(token) => from = Some(token),
+                None => break,
+            }
+        }
+
+        // The same edit may be known by the event cache and returned by the server,
+        // so deduplicate them by event ID.
+        let mut edits = BTreeMap::new();
+
+        for candidate in &candidates {
+            let Some(edit) = as_original_room_message(candidate) else {
+                continue;
+            };
+
+            if edit.sender != original.sender {
+                warn!(edit_id = ?edit.event_id, "ignoring an edit from another sender");
+                continue;
+            }
+
+            let Some(Relation::Replacement(replacement)) = edit.content.relates_to else {
+                continue;
+            };
+
+            if *replacement.event_id != *event_id {
+                continue;
+            }
+
+            edits.insert(
+                edit.event_id.clone(),
+                EditHistoryEntry {
+                    event_id: edit.event_id,
+                    timestamp: edit.origin_server_ts,
+                    content: replacement.new_content.msgtype,
+                },
+            );
+        }
+
+        let mut edits = edits.into_values().collect::<Vec<_>>();
+        edits.sort_by_key(|edit| edit.timestamp);
+
+        let mut history = Vec::with_capacity(edits.len() + 1);
+        history.push(EditHistoryEntry {
+            event_id: original.event_id,
+            timestamp: original.origin_server_ts,
+            content: original.content.msgtype,
+        });
+        history.extend(edits);
+
+        Ok(history)
+    }
+}
+
+/// Deserialize the event as a room message which hasn't been redacted, if it
+/// is one.
+fn as_original_room_message(event: &TimelineEvent) -> Option<OriginalSyncRoomMessageEvent> {
+    match event.raw().deserialize().ok()? {
+        AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::RoomMessage(
+            SyncMessageLikeEvent::Original(event),
+        )) => Some(event),
+        _ => None,
+    }
+}
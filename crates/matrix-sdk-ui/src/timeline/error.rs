@@ -18,7 +18,10 @@ use matrix_sdk::{
 };
 use thiserror::Error;
 
-use crate::timeline::{TimelineEventItemId, pinned_events_loader::PinnedEventsLoaderError};
+use crate::timeline::{
+    TimelineEventItemId, pinned_events_loader::PinnedEventsLoaderError,
+    starred_events_loader::StarredEventsLoaderError,
+};
 
 /// Errors specific to the timeline.
 #[derive(Error, Debug)]
@@ -64,6 +67,10 @@ pub enum Error {
     #[error(transparent)]
     PinnedEventsError(#[from] PinnedEventsLoaderError),
 
+    /// An error happened while loading the starred events.
+    #[error(transparent)]
+    StarredEventsError(#[from] StarredEventsLoaderError),
+
     /// An error happened while operating the room's send queue.
     #[error(transparent)]
     SendQueueError(#[from] RoomSendQueueError),
@@ -79,6 +86,18 @@ pub enum Error {
     /// An error happened while attempting to redact an event.
     #[error(transparent)]
     RedactError(#[from] RedactError),
+
+    /// An error happened while retrieving the edit history of an event.
+    #[error(transparent)]
+    EditHistoryError(#[from] EditHistoryError),
+
+    /// An error happened while forwarding an event to another room.
+    #[error(transparent)]
+    ForwardError(#[from] ForwardError),
+
+    /// An error happened while reporting an event or the room.
+    #[error(transparent)]
+    ReportError(#[from] ReportError),
 }
 
 #[derive(Error, Debug)]
@@ -96,6 +115,45 @@ pub enum EditError {
     RoomError(#[from] matrix_sdk::room::edit::EditError),
 }
 
+#[derive(Error, Debug)]
+pub enum EditHistoryError {
+    /// The event isn't a room message, so it can't have an edit history.
+    #[error("the event isn't a room message")]
+    NotRoomMessage,
+
+    /// The event or its edits couldn't be retrieved.
+    #[error(transparent)]
+    Sdk(#[from] matrix_sdk::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum ForwardError {
+    /// The event isn't a room message, so it can't be forwarded.
+    #[error("the event isn't a room message")]
+    NotRoomMessage,
+
+    /// The encryption state of the target room couldn't be read, or the media
+    /// of the event couldn't be uploaded again.
+    #[error(transparent)]
+    Sdk(#[from] matrix_sdk::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum ReportError {
+    /// The item to report wasn't found in the timeline.
+    #[error("Event to report wasn't found for item id {0:?}")]
+    ItemNotFound(TimelineEventItemId),
+
+    /// The item is a local echo, which can't be reported since it hasn't been
+    /// sent yet.
+    #[error("a local echo can't be reported")]
+    LocalEcho,
+
+    /// The report couldn't be sent.
+    #[error(transparent)]
+    Sdk(#[from] matrix_sdk::Error),
+}
+
 #[derive(Error, Debug)]
 pub enum RedactError {
     /// Local event to redact wasn't found for transaction id
@@ -28,6 +28,7 @@ use ruma::{
         AnyMessageLikeEventContent, AnySyncMessageLikeEvent, AnySyncStateEvent,
         AnySyncTimelineEvent, FullStateEventContent, MessageLikeEventContent, MessageLikeEventType,
         StateEventType, SyncStateEvent,
+        location::LocationContent,
         poll::unstable_start::{
             NewUnstablePollStartEventContentWithoutRelation, UnstablePollStartEventContent,
         },
@@ -42,12 +43,14 @@ use ruma::{
 use tracing::{debug, error, field::debug, instrument, trace, warn};
 
 use super::{
-    EmbeddedEvent, EncryptedMessage, EventTimelineItem, InReplyToDetails, MsgLikeContent,
-    MsgLikeKind, OtherState, ReactionStatus, Sticker, ThreadSummary, TimelineDetails, TimelineItem,
-    TimelineItemContent,
+    EmbeddedEvent, EncryptedMessage, EventTimelineItem, InReplyToDetails, LiveLocationState,
+    MsgLikeContent, MsgLikeKind, OtherState, ReactionStatus, Sticker, ThreadSummary,
+    TimelineDetails, TimelineItem, TimelineItemContent,
+    algorithms::rfind_event_item,
     controller::{
         Aggregation, AggregationKind, ObservableItemsTransaction, PendingEditKind,
         TimelineMetadata, TimelineStateTransaction, find_item_and_apply_aggregation,
+        find_item_and_apply_aggregation_then,
     },
     date_dividers::DateDividerAdjuster,
     event_item::{
@@ -86,6 +89,9 @@ pub(super) enum Flow {
         position: TimelineItemPosition,
         /// Information about the encryption for this event.
         encryption_info: Option<Arc<EncryptionInfo>>,
+        /// The encrypted event, as received from the server, if this event was
+        /// encrypted and could be decrypted.
+        encrypted_event: Option<Raw<AnySyncTimelineEvent>>,
     },
 }
 
@@ -142,6 +148,9 @@ pub(super) enum HandleAggregationKind {
 
     /// Ending a related poll.
     PollEnd,
+
+    /// Updating the location of a related live location share.
+    BeaconUpdate { location: LocationContent, ts: MilliSecondsSinceUnixEpoch },
 }
 
 /// An action that we want to cause on the timeline.
@@ -280,6 +289,9 @@ impl TimelineAction {
                         ))
                     }
                 },
+                AnySyncStateEvent::BeaconInfo(SyncStateEvent::Original(ev)) => Self::add_item(
+                    TimelineItemContent::LiveLocation(LiveLocationState::new(ev.content)),
+                ),
                 ev => Self::add_item(TimelineItemContent::OtherState(OtherState {
                     state_key: ev.state_key().to_owned(),
                     content: AnyOtherFullStateEventContent::with_event_content(ev.content()),
@@ -336,6 +348,11 @@ impl TimelineAction {
                 kind: HandleAggregationKind::PollEnd,
             },
 
+            AnyMessageLikeEventContent::Beacon(c) => Self::HandleAggregation {
+                related_event: c.relates_to.event_id,
+                kind: HandleAggregationKind::BeaconUpdate { location: c.location, ts: c.ts },
+            },
+
             AnyMessageLikeEventContent::CallInvite(_) => {
                 Self::add_item(TimelineItemContent::CallInvite)
             }
@@ -517,7 +534,15 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
 
         match timeline_action {
             TimelineAction::AddItem { content } => {
-                if self.ctx.should_add_new_items {
+                let is_live_location_stop = matches!(
+                    &content,
+                    TimelineItemContent::LiveLocation(state) if !state.beacon_info.live
+                );
+
+                if is_live_location_stop && self.handle_live_location_stop() {
+                    // The stopped share is already in the timeline, and has
+                    // been updated in place.
+                } else if self.ctx.should_add_new_items {
                     self.add_item(content);
                     added_item = true;
                 }
@@ -548,6 +573,9 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                 HandleAggregationKind::PollEnd => {
                     self.handle_poll_end(related_event);
                 }
+                HandleAggregationKind::BeaconUpdate { location, ts } => {
+                    self.handle_beacon_update(related_event, location, ts);
+                }
             },
         }
 
@@ -572,18 +600,31 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
 
         self.meta.aggregations.add(target.clone(), aggregation.clone());
 
-        if let Some(new_item) = find_item_and_apply_aggregation(
-            &self.meta.aggregations,
+        let meta = &*self.meta;
+        if let Some(new_item) = find_item_and_apply_aggregation_then(
+            &meta.aggregations,
             self.items,
             &target,
             aggregation,
-            &self.meta.room_version_rules,
+            &meta.room_version_rules,
+            |item| Self::refresh_edited_item(meta, item),
         ) {
             // Update all events that replied to this message with the edited content.
             Self::maybe_update_responses(self.meta, self.items, &edited_event_id, &new_item);
         }
     }
 
+    /// Update an item whose content has just been replaced by an edit:
+    /// recognize the message effects and run the content transformers on the
+    /// new content, check again whether it mentions the user, and drop the
+    /// previews of the links which have been removed.
+    fn refresh_edited_item(meta: &TimelineMetadata, item: EventTimelineItem) -> EventTimelineItem {
+        let mut item = meta.transform_content(item);
+        item.update_mentions_me(&meta.own_user_id, meta.own_display_name.as_deref());
+        item.retain_url_previews();
+        item
+    }
+
     /// Apply a reaction to a *remote* event.
     ///
     /// Reactions to local events are applied in
@@ -660,6 +701,68 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
         );
     }
 
+    fn handle_beacon_update(
+        &mut self,
+        beacon_info_event_id: OwnedEventId,
+        location: LocationContent,
+        ts: MilliSecondsSinceUnixEpoch,
+    ) {
+        let target = TimelineEventItemId::EventId(beacon_info_event_id);
+        let aggregation = Aggregation::new(
+            self.ctx.flow.timeline_item_id(),
+            AggregationKind::BeaconUpdate { location, ts },
+        );
+        self.meta.aggregations.add(target.clone(), aggregation.clone());
+        find_item_and_apply_aggregation(
+            &self.meta.aggregations,
+            self.items,
+            &target,
+            aggregation,
+            &self.meta.room_version_rules,
+        );
+    }
+
+    /// Stop the most recent live location share of the sender, upon receiving
+    /// the `m.beacon_info` state event which stops it.
+    ///
+    /// The stop event doesn't refer to the event which started the share, so
+    /// the share can only be found if it's been received before, i.e. when the
+    /// stop event is added at the end of the timeline.
+    ///
+    /// Returns whether the share has been found.
+    fn handle_live_location_stop(&mut self) -> bool {
+        if !matches!(self.ctx.flow, Flow::Remote { position: TimelineItemPosition::End { .. }, .. })
+        {
+            return false;
+        }
+
+        let sender = &self.ctx.sender;
+        let Some(beacon_info_event_id) = rfind_event_item(self.items, |item| {
+            item.sender() == sender
+                && matches!(
+                    item.content(),
+                    TimelineItemContent::LiveLocation(state) if state.beacon_info.live
+                )
+        })
+        .and_then(|(_, item)| item.event_id().map(ToOwned::to_owned)) else {
+            return false;
+        };
+
+        let target = TimelineEventItemId::EventId(beacon_info_event_id);
+        let aggregation =
+            Aggregation::new(self.ctx.flow.timeline_item_id(), AggregationKind::BeaconStop);
+        self.meta.aggregations.add(target.clone(), aggregation.clone());
+        find_item_and_apply_aggregation(
+            &self.meta.aggregations,
+            self.items,
+            &target,
+            aggregation,
+            &self.meta.room_version_rules,
+        );
+
+        true
+    }
+
     /// Looks for the redacted event in all the timeline event items, and
     /// redacts it.
     ///
@@ -739,7 +842,15 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
             }
             .into(),
 
-            Flow::Remote { event_id, raw_event, position, txn_id, encryption_info, .. } => {
+            Flow::Remote {
+                event_id,
+                raw_event,
+                position,
+                txn_id,
+                encryption_info,
+                encrypted_event,
+                ..
+            } => {
                 let origin = match *position {
                     TimelineItemPosition::Start { origin }
                     | TimelineItemPosition::End { origin }
@@ -761,9 +872,13 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
                     read_receipts: self.ctx.read_receipts.clone(),
                     is_own: self.ctx.sender == self.meta.own_user_id,
                     is_highlighted: self.ctx.is_highlighted,
+                    // Computed once the aggregations, which may edit the content, are applied.
+                    mentions_me: false,
+                    url_previews: Vec::new(),
                     encryption_info: encryption_info.clone(),
                     original_json: Some(raw_event.clone()),
                     latest_edit_json: None,
+                    encrypted_json: encrypted_event.clone(),
                     origin,
                 }
                 .into()
@@ -791,7 +906,8 @@ impl<'a, 'o> TimelineEventHandler<'a, 'o> {
         ) {
             warn!("discarding aggregations: {err}");
         }
-        let item = cowed.into_owned();
+        let mut item = self.meta.transform_content(cowed.into_owned());
+        item.update_mentions_me(&self.meta.own_user_id, self.meta.own_display_name.as_deref());
 
         match &self.ctx.flow {
             Flow::Local { .. } => {
@@ -0,0 +1,117 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module handles the messages which trigger an effect, like confetti or
+//! fireworks, when they are displayed.
+
+use std::collections::HashMap;
+
+use super::{MsgLikeKind, TimelineItemContent};
+
+/// The mapping from the `msgtype`s of the messages to the names of the
+/// effects they trigger.
+///
+/// The default mapping recognizes the effects sent by Element: `confetti`,
+/// `fireworks`, `space_invaders`, and the `io.element.effect.*` ones, named
+/// after the suffix of their `msgtype`.
+#[derive(Clone, Debug)]
+pub struct MessageEffects {
+    names_by_msgtype: HashMap<String, String>,
+    msgtype_prefixes: Vec<String>,
+}
+
+impl MessageEffects {
+    /// Create a mapping which doesn't recognize any effect.
+    pub fn empty() -> Self {
+        Self { names_by_msgtype: HashMap::new(), msgtype_prefixes: Vec::new() }
+    }
+
+    /// Recognize the messages with the given `msgtype` as the effect with the
+    /// given name.
+    pub fn with_effect(mut self, msgtype: impl Into<String>, name: impl Into<String>) -> Self {
+        self.names_by_msgtype.insert(msgtype.into(), name.into());
+        self
+    }
+
+    /// Recognize the messages whose `msgtype` starts with the given prefix as
+    /// effects, named after the rest of their `msgtype`.
+    pub fn with_msgtype_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.msgtype_prefixes.push(prefix.into());
+        self
+    }
+
+    /// The name of the effect triggered by the given `msgtype`, if any.
+    pub fn effect_name<'a>(&'a self, msgtype: &'a str) -> Option<&'a str> {
+        if let Some(name) = self.names_by_msgtype.get(msgtype) {
+            return Some(name.as_str());
+        }
+
+        self.msgtype_prefixes
+            .iter()
+            .filter_map(|prefix| msgtype.strip_prefix(prefix.as_str()))
+            .find(|name| !name.is_empty())
+    }
+
+    /// The given content with the [`Message::effect()`] of its message set,
+    /// or `None` if it's not a message or its effect didn't change.
+    ///
+    /// [`Message::effect()`]: super::Message::effect
+    pub(in crate::timeline) fn apply(
+        &self,
+        content: &TimelineItemContent,
+    ) -> Option<TimelineItemContent> {
+        let TimelineItemContent::MsgLike(msglike) = content else { return None };
+        let MsgLikeKind::Message(message) = &msglike.kind else { return None };
+
+        // The effect of an edited message may have changed with its `msgtype`.
+        let effect = self.effect_name(message.msgtype().msgtype());
+        if message.effect() == effect {
+            return None;
+        }
+
+        let mut message = message.clone();
+        message.effect = effect.map(ToOwned::to_owned);
+
+        Some(TimelineItemContent::MsgLike(msglike.with_kind(MsgLikeKind::Message(message))))
+    }
+}
+
+impl Default for MessageEffects {
+    fn default() -> Self {
+        Self::empty()
+            .with_effect("nic.custom.confetti", "confetti")
+            .with_effect("nic.custom.fireworks", "fireworks")
+            .with_effect("io.element.effects.space_invaders", "space_invaders")
+            .with_msgtype_prefix("io.element.effect.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MessageEffects;
+
+    #[test]
+    fn test_effect_name() {
+        let effects = MessageEffects::default();
+
+        assert_eq!(effects.effect_name("nic.custom.confetti"), Some("confetti"));
+        assert_eq!(effects.effect_name("io.element.effect.snowfall"), Some("snowfall"));
+        assert_eq!(effects.effect_name("io.element.effect."), None);
+        assert_eq!(effects.effect_name("m.text"), None);
+
+        let effects = MessageEffects::empty().with_effect("org.example.balloons", "balloons");
+        assert_eq!(effects.effect_name("org.example.balloons"), Some("balloons"));
+        assert_eq!(effects.effect_name("nic.custom.confetti"), None);
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module handles rendering of MSC4274 galleries in the timeline.
+
+use ruma::events::room::{MediaSource, message::GalleryItemType};
+
+/// The type of a [`GalleryMedia`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GalleryMediaKind {
+    /// An image.
+    Image,
+    /// A video.
+    Video,
+    /// An audio file.
+    Audio,
+    /// Any other file.
+    File,
+}
+
+/// One of the media of a gallery message.
+///
+/// It's built from the item types of the gallery, whose media types differ in
+/// where they store their metadata, so that all of them can be shown the same
+/// way.
+#[derive(Clone, Debug)]
+pub struct GalleryMedia {
+    pub(in crate::timeline) kind: GalleryMediaKind,
+    pub(in crate::timeline) source: MediaSource,
+    pub(in crate::timeline) thumbnail_source: Option<MediaSource>,
+    pub(in crate::timeline) filename: String,
+    pub(in crate::timeline) caption: Option<String>,
+    pub(in crate::timeline) mimetype: Option<String>,
+}
+
+impl GalleryMedia {
+    /// Create a `GalleryMedia` from an item of a gallery.
+    ///
+    /// Returns `None` for the item types which aren't media.
+    pub(in crate::timeline) fn from_item_type(item_type: &GalleryItemType) -> Option<Self> {
+        // Some variants look really similar below, but the `content` and `info` are all
+        // different types…
+        let (kind, source, thumbnail_source, mimetype, filename, caption) = match item_type {
+            GalleryItemType::Image(content) => (
+                GalleryMediaKind::Image,
+                &content.source,
+                content.info.as_ref().and_then(|info| info.thumbnail_source.clone()),
+                content.info.as_ref().and_then(|info| info.mimetype.clone()),
+                content.filename(),
+                content.caption(),
+            ),
+            GalleryItemType::Video(content) => (
+                GalleryMediaKind::Video,
+                &content.source,
+                content.info.as_ref().and_then(|info| info.thumbnail_source.clone()),
+                content.info.as_ref().and_then(|info| info.mimetype.clone()),
+                content.filename(),
+                content.caption(),
+            ),
+            GalleryItemType::Audio(content) => (
+                GalleryMediaKind::Audio,
+                &content.source,
+                None,
+                content.info.as_ref().and_then(|info| info.mimetype.clone()),
+                content.filename(),
+                content.caption(),
+            ),
+            GalleryItemType::File(content) => (
+                GalleryMediaKind::File,
+                &content.source,
+                content.info.as_ref().and_then(|info| info.thumbnail_source.clone()),
+                content.info.as_ref().and_then(|info| info.mimetype.clone()),
+                content.filename(),
+                content.caption(),
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            kind,
+            source: source.clone(),
+            thumbnail_source,
+            filename: filename.to_owned(),
+            caption: caption.map(ToOwned::to_owned),
+            mimetype,
+        })
+    }
+
+    /// The type of this media.
+    pub fn kind(&self) -> GalleryMediaKind {
+        self.kind
+    }
+
+    /// The source of the media itself.
+    pub fn source(&self) -> &MediaSource {
+        &self.source
+    }
+
+    /// The source of the thumbnail of the media, if any.
+    pub fn thumbnail_source(&self) -> Option<&MediaSource> {
+        self.thumbnail_source.as_ref()
+    }
+
+    /// The name of the file of the media.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// The caption of this media, if any, which is distinct from the caption
+    /// of the whole gallery.
+    pub fn caption(&self) -> Option<&str> {
+        self.caption.as_deref()
+    }
+
+    /// The mime type of the media, if known.
+    pub fn mimetype(&self) -> Option<&str> {
+        self.mimetype.as_deref()
+    }
+}
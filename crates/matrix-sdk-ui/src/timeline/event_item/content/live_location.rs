@@ -0,0 +1,82 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module handles rendering of MSC3489 live location shares in the
+//! timeline.
+
+use matrix_sdk::live_location_share::LastLocation;
+use ruma::{
+    MilliSecondsSinceUnixEpoch,
+    events::{beacon_info::BeaconInfoEventContent, location::LocationContent},
+};
+
+/// Holds the state of a live location share.
+///
+/// This struct should be created for each `m.beacon_info` state event starting
+/// a share, and then updated whenever handling an `m.beacon` event that relates
+/// to it, or the `m.beacon_info` state event which stops it.
+#[derive(Clone, Debug)]
+pub struct LiveLocationState {
+    pub(in crate::timeline) beacon_info: BeaconInfoEventContent,
+    pub(in crate::timeline) last_location: Option<LastLocation>,
+    pub(in crate::timeline) has_been_stopped: bool,
+}
+
+impl LiveLocationState {
+    pub(crate) fn new(beacon_info: BeaconInfoEventContent) -> Self {
+        Self { beacon_info, last_location: None, has_been_stopped: false }
+    }
+
+    /// Whether the given location is more recent than the last known one.
+    pub(crate) fn is_newer_location(&self, ts: MilliSecondsSinceUnixEpoch) -> bool {
+        self.last_location.as_ref().is_none_or(|last| last.ts < ts)
+    }
+
+    /// Update the last known location, if the given one is more recent.
+    pub(crate) fn update_location(
+        &mut self,
+        location: LocationContent,
+        ts: MilliSecondsSinceUnixEpoch,
+    ) {
+        if self.is_newer_location(ts) {
+            self.last_location = Some(LastLocation { location, ts });
+        }
+    }
+
+    /// Mark the share as stopped by its sender.
+    pub(crate) fn stop(&mut self) {
+        self.has_been_stopped = true;
+    }
+
+    /// The content of the `m.beacon_info` state event which started the share.
+    pub fn beacon_info(&self) -> &BeaconInfoEventContent {
+        &self.beacon_info
+    }
+
+    /// The description of the share, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.beacon_info.description.as_deref()
+    }
+
+    /// The most recent location received for this share, if any.
+    pub fn last_location(&self) -> Option<&LastLocation> {
+        self.last_location.as_ref()
+    }
+
+    /// Whether the share is still live, i.e. it hasn't been stopped by its
+    /// sender and its timeout hasn't expired yet.
+    pub fn is_live(&self) -> bool {
+        !self.has_been_stopped && self.beacon_info.is_live()
+    }
+}
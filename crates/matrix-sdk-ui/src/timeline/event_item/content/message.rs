@@ -14,10 +14,11 @@
 
 //! Timeline item content bits for `m.room.message` events.
 
-use std::fmt;
+use std::{collections::BTreeSet, fmt};
 
+use as_variant::as_variant;
 use ruma::{
-    OwnedEventId,
+    MatrixToUri, MatrixUri, OwnedEventId, OwnedUserId, UserId,
     events::{
         AnySyncMessageLikeEvent, AnySyncTimelineEvent, BundledMessageLikeRelations, Mentions,
         poll::unstable_start::{
@@ -28,11 +29,14 @@ use ruma::{
             MessageType, Relation, RoomMessageEventContentWithoutRelation, SyncRoomMessageEvent,
         },
     },
-    html::RemoveReplyFallback,
+    html::{Html, NodeRef, RemoveReplyFallback},
+    matrix_uri::MatrixId,
     serde::Raw,
 };
 use tracing::{error, trace};
 
+#[cfg(feature = "unstable-msc4274")]
+use super::GalleryMedia;
 use crate::DEFAULT_SANITIZER_MODE;
 
 /// An `m.room.message` event or extensible event, including edits.
@@ -41,6 +45,7 @@ pub struct Message {
     pub(in crate::timeline) msgtype: MessageType,
     pub(in crate::timeline) edited: bool,
     pub(in crate::timeline) mentions: Option<Mentions>,
+    pub(in crate::timeline) effect: Option<String>,
 }
 
 impl Message {
@@ -53,7 +58,7 @@ impl Message {
     ) -> Self {
         msgtype.sanitize(DEFAULT_SANITIZER_MODE, remove_reply_fallback);
 
-        let mut ret = Self { msgtype, edited: false, mentions };
+        let mut ret = Self { msgtype, edited: false, mentions, effect: None };
 
         if let Some(edit) = edit {
             ret.apply_edit(edit);
@@ -77,6 +82,12 @@ impl Message {
         &self.msgtype
     }
 
+    /// Get the `msgtype`-specific data of this message mutably, to change it
+    /// from a content transformer.
+    pub fn msgtype_mut(&mut self) -> &mut MessageType {
+        &mut self.msgtype
+    }
+
     /// Get a reference to the message body.
     ///
     /// Shorthand for `.msgtype().body()`.
@@ -94,6 +105,195 @@ impl Message {
     pub fn mentions(&self) -> Option<&Mentions> {
         self.mentions.as_ref()
     }
+
+    /// Get the name of the effect this message triggers, like `confetti`, if
+    /// its `msgtype` is one of the
+    /// [`MessageEffects`](crate::timeline::MessageEffects) of the timeline.
+    ///
+    /// Effects are only recognized when the timeline has been built with
+    /// [`TimelineBuilder::with_message_effects()`](crate::timeline::TimelineBuilder::with_message_effects).
+    pub fn effect(&self) -> Option<&str> {
+        self.effect.as_deref()
+    }
+
+    /// Whether this message is a voice message, i.e. an audio message with the
+    /// MSC3245 `m.voice` marker.
+    pub fn is_voice_message(&self) -> bool {
+        matches!(&self.msgtype, MessageType::Audio(content) if content.voice.is_some())
+    }
+
+    /// Get the waveform of this message, if it's an audio message with one.
+    ///
+    /// The amplitudes are between 0 and 1024.
+    pub fn waveform(&self) -> Option<Vec<u16>> {
+        let MessageType::Audio(content) = &self.msgtype else {
+            return None;
+        };
+
+        let waveform = &content.audio.as_ref()?.waveform;
+        Some(
+            waveform
+                .iter()
+                .map(|amplitude| u16::try_from(amplitude.get()).unwrap_or(u16::MAX))
+                .collect(),
+        )
+    }
+
+    /// Get the users and the room mentioned by this message.
+    ///
+    /// These are its [`Self::mentions()`] if it has any. Otherwise, they're
+    /// detected in its body, as they were sent by clients not supporting
+    /// intentional mentions: the users from the links to them in the formatted
+    /// body and the user IDs written in the plain body, and the room from
+    /// `@room` in the plain body.
+    pub fn parsed_mentions(&self) -> Mentions {
+        if let Some(mentions) = &self.mentions {
+            return mentions.clone();
+        }
+
+        let formatted = match &self.msgtype {
+            MessageType::Text(content) => content.formatted.as_ref(),
+            MessageType::Emote(content) => content.formatted.as_ref(),
+            MessageType::Notice(content) => content.formatted.as_ref(),
+            _ => None,
+        };
+
+        let body = self.msgtype.body();
+
+        let mut user_ids =
+            formatted.map(|formatted| linked_user_ids(&formatted.body)).unwrap_or_default();
+        user_ids.extend(written_user_ids(body));
+
+        let mut mentions = Mentions::with_user_ids(user_ids);
+        mentions.room = body.contains("@room");
+
+        mentions
+    }
+
+    /// Whether this message mentions the given user, or the whole room.
+    ///
+    /// In addition to the [`Self::parsed_mentions()`], a message without
+    /// intentional mentions mentions the user when its plain body contains
+    /// their `display_name` as a whole word, ignoring the case, like the legacy
+    /// push rules do.
+    pub fn mentions_user(&self, user_id: &UserId, display_name: Option<&str>) -> bool {
+        let mentions = self.parsed_mentions();
+
+        if mentions.room || mentions.user_ids.contains(user_id) {
+            return true;
+        }
+
+        self.mentions.is_none()
+            && display_name.is_some_and(|name| contains_word(self.msgtype.body(), name))
+    }
+
+    /// Get the web links in the plain body of this message, without duplicates
+    /// and in the order they appear.
+    ///
+    /// Only the `http` and `https` URLs of text, emote and notice messages are
+    /// returned.
+    pub fn links(&self) -> Vec<&str> {
+        if !matches!(
+            self.msgtype,
+            MessageType::Text(_) | MessageType::Emote(_) | MessageType::Notice(_)
+        ) {
+            return Vec::new();
+        }
+
+        let mut links = Vec::new();
+
+        for word in self.msgtype.body().split_whitespace() {
+            // Links are often followed by punctuation, or surrounded by brackets.
+            let link = word
+                .trim_start_matches(['(', '<', '[', '"', '\''])
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '>', ']', '"', '\'']);
+
+            if (link.starts_with("https://") || link.starts_with("http://"))
+                && !links.contains(&link)
+            {
+                links.push(link);
+            }
+        }
+
+        links
+    }
+
+    /// Get the media of this message, in their order in the gallery, if it's
+    /// a gallery.
+    ///
+    /// The items of the gallery which aren't media are skipped.
+    #[cfg(feature = "unstable-msc4274")]
+    pub fn gallery_media(&self) -> Option<Vec<GalleryMedia>> {
+        let MessageType::Gallery(gallery) = &self.msgtype else {
+            return None;
+        };
+
+        Some(gallery.itemtypes.iter().filter_map(GalleryMedia::from_item_type).collect())
+    }
+}
+
+/// Get the IDs of the users linked to in the given HTML, with `matrix.to` or
+/// `matrix:` URIs.
+fn linked_user_ids(html: &str) -> BTreeSet<OwnedUserId> {
+    fn collect(node: NodeRef<'_>, user_ids: &mut BTreeSet<OwnedUserId>) {
+        if let Some(element) = node.as_element()
+            && &*element.name.local == "a"
+            && let Some(href) = element.attrs.iter().find(|attr| &*attr.name.local == "href")
+        {
+            let id = match MatrixToUri::parse(&href.value) {
+                Ok(uri) => Some(uri.id().clone()),
+                Err(_) => MatrixUri::parse(&href.value).ok().map(|uri| uri.id().clone()),
+            };
+
+            if let Some(user_id) = id.and_then(|id| as_variant!(id, MatrixId::User)) {
+                user_ids.insert(user_id);
+            }
+        }
+
+        for child in node.children() {
+            collect(child, user_ids);
+        }
+    }
+
+    let html = Html::parse(html);
+    let mut user_ids = BTreeSet::new();
+
+    for node in html.children() {
+        collect(node, &mut user_ids);
+    }
+
+    user_ids
+}
+
+/// Get the IDs of the users written in the given plain text.
+fn written_user_ids(text: &str) -> impl Iterator<Item = OwnedUserId> + '_ {
+    text.split_whitespace().filter_map(|word| {
+        // User IDs are often followed by punctuation, or surrounded by brackets.
+        let word = word
+            .trim_start_matches(['(', '<', '[', '"', '\''])
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '>', ']', '"', '\'']);
+
+        UserId::parse(word).ok()
+    })
+}
+
+/// Whether the given text contains the given word, with no letter or digit
+/// right before or after it, ignoring the case.
+fn contains_word(text: &str, word: &str) -> bool {
+    let word = word.trim().to_lowercase();
+
+    if word.is_empty() {
+        return false;
+    }
+
+    let text = text.to_lowercase();
+
+    text.match_indices(&word).any(|(start, matched)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + matched.len()..].chars().next();
+
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
 }
 
 /// Extracts the raw json of the edit event part of bundled relations.
@@ -170,9 +370,12 @@ pub(crate) fn extract_poll_edit_content(
 #[cfg(not(tarpaulin_include))]
 impl fmt::Debug for Message {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self { msgtype: _, edited, mentions: _ } = self;
+        let Self { msgtype: _, edited, mentions: _, effect } = self;
         // since timeline items are logged, don't include all fields here so
         // people don't leak personal data in bug reports
-        f.debug_struct("Message").field("edited", edited).finish_non_exhaustive()
+        f.debug_struct("Message")
+            .field("edited", edited)
+            .field("effect", effect)
+            .finish_non_exhaustive()
     }
 }
@@ -60,6 +60,10 @@ use ruma::{
 };
 use tracing::warn;
 
+mod effect;
+#[cfg(feature = "unstable-msc4274")]
+mod gallery;
+mod live_location;
 mod message;
 mod msg_like;
 pub(crate) mod pinned_events;
@@ -68,10 +72,14 @@ mod reply;
 
 pub use pinned_events::RoomPinnedEventsChange;
 
+#[cfg(feature = "unstable-msc4274")]
+pub use self::gallery::{GalleryMedia, GalleryMediaKind};
 pub(in crate::timeline) use self::message::{
     extract_bundled_edit_event_json, extract_poll_edit_content, extract_room_msg_edit_content,
 };
 pub use self::{
+    effect::MessageEffects,
+    live_location::LiveLocationState,
     message::Message,
     msg_like::{MsgLikeContent, MsgLikeKind, ThreadSummary},
     polls::{PollResult, PollState},
@@ -119,6 +127,10 @@ pub enum TimelineItemContent {
 
     /// An `m.rtc.notification` event
     RtcNotification,
+
+    /// A live location share, started by an `m.beacon_info` state event, and
+    /// aggregating the `m.beacon` events which relate to it.
+    LiveLocation(LiveLocationState),
 }
 
 impl TimelineItemContent {
@@ -357,6 +369,17 @@ impl TimelineItemContent {
         matches!(self, Self::MsgLike(MsgLikeContent { kind: MsgLikeKind::Message(_), .. }))
     }
 
+    /// Check whether this item's content is a
+    /// [`Message`][MsgLikeKind::Message] which mentions the given user, or the
+    /// whole room, see [`Message::mentions_user()`].
+    pub(in crate::timeline) fn mentions_user(
+        &self,
+        user_id: &UserId,
+        display_name: Option<&str>,
+    ) -> bool {
+        self.as_message().is_some_and(|message| message.mentions_user(user_id, display_name))
+    }
+
     /// If `self` is of the [`MsgLike`][Self::MsgLike] variant, return the
     /// inner [`PollState`].
     pub fn as_poll(&self) -> Option<&PollState> {
@@ -448,6 +471,7 @@ impl TimelineItemContent {
             | TimelineItemContent::FailedToParseState { .. } => "an event that couldn't be parsed",
             TimelineItemContent::CallInvite => "a call invite",
             TimelineItemContent::RtcNotification => "a call notification",
+            TimelineItemContent::LiveLocation(_) => "a live location share",
         }
     }
 
@@ -518,7 +542,7 @@ impl TimelineItemContent {
 
     pub(in crate::timeline) fn redact(&self, rules: &RedactionRules) -> Self {
         match self {
-            Self::MsgLike(_) | Self::CallInvite | Self::RtcNotification => {
+            Self::MsgLike(_) | Self::CallInvite | Self::RtcNotification | Self::LiveLocation(_) => {
                 TimelineItemContent::MsgLike(MsgLikeContent::redacted())
             }
             Self::MembershipChange(ev) => Self::MembershipChange(ev.redact(rules)),
@@ -550,7 +574,8 @@ impl TimelineItemContent {
             | TimelineItemContent::FailedToParseMessageLike { .. }
             | TimelineItemContent::FailedToParseState { .. }
             | TimelineItemContent::CallInvite
-            | TimelineItemContent::RtcNotification => {
+            | TimelineItemContent::RtcNotification
+            | TimelineItemContent::LiveLocation(_) => {
                 // No reactions for these kind of items.
                 None
             }
@@ -575,7 +600,8 @@ impl TimelineItemContent {
             | TimelineItemContent::FailedToParseMessageLike { .. }
             | TimelineItemContent::FailedToParseState { .. }
             | TimelineItemContent::CallInvite
-            | TimelineItemContent::RtcNotification => {
+            | TimelineItemContent::RtcNotification
+            | TimelineItemContent::LiveLocation(_) => {
                 // No reactions for these kind of items.
                 None
             }
@@ -48,6 +48,18 @@ pub struct ThreadSummary {
     /// thread-focused timeline with the same timeline filter may result in
     /// *fewer* events than this number.
     pub num_replies: u32,
+
+    /// The number of distinct users who replied in the thread, if known.
+    ///
+    /// It's unknown for the summaries bundled by the server, until the event
+    /// cache has seen the replies.
+    pub num_participants: Option<u32>,
+
+    /// Whether the latest reply hasn't been read by the current user,
+    /// according to their read receipt in the thread.
+    ///
+    /// Our own replies are considered read.
+    pub is_unread: bool,
 }
 
 /// A special kind of [`super::TimelineItemContent`] that groups together
@@ -22,6 +22,7 @@ use matrix_sdk::{
 use ruma::{EventId, OwnedEventId, OwnedTransactionId};
 
 use super::TimelineEventItemId;
+use crate::timeline::futures::AttachmentUploadPhase;
 
 /// An item for an event that was created locally and not yet echoed back by
 /// the homeserver.
@@ -105,4 +106,12 @@ pub struct MediaUploadProgress {
     /// thumbnail. For gallery uploads, the progress is reported per indexed
     /// gallery item.
     pub progress: AbstractProgress,
+
+    /// What's being uploaded, or [`AttachmentUploadPhase::Sending`] once the
+    /// media have been uploaded and the event is being sent.
+    ///
+    /// Like the progress, it's only updated as the media are uploaded if
+    /// [`matrix_sdk::send_queue::SendQueue::enable_upload_progress`] has been
+    /// called.
+    pub phase: AttachmentUploadPhase,
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+    cmp::Ordering,
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -22,6 +23,7 @@ use indexmap::IndexMap;
 use matrix_sdk::{
     Client, Error,
     deserialized_responses::{EncryptionInfo, ShieldState},
+    media::UrlPreview,
     send_queue::{SendHandle, SendReactionHandle},
 };
 use matrix_sdk_base::{
@@ -42,15 +44,19 @@ use unicode_segmentation::UnicodeSegmentation;
 mod content;
 mod local;
 mod remote;
+mod source;
 
+#[cfg(feature = "unstable-msc4274")]
+pub use self::content::{GalleryMedia, GalleryMediaKind};
 pub use self::{
     content::{
         AnyOtherFullStateEventContent, EmbeddedEvent, EncryptedMessage, InReplyToDetails,
-        MemberProfileChange, MembershipChange, Message, MsgLikeContent, MsgLikeKind, OtherState,
-        PollResult, PollState, RoomMembershipChange, RoomPinnedEventsChange, Sticker,
-        ThreadSummary, TimelineItemContent,
+        LiveLocationState, MemberProfileChange, MembershipChange, Message, MessageEffects,
+        MsgLikeContent, MsgLikeKind, OtherState, PollResult, PollState, RoomMembershipChange,
+        RoomPinnedEventsChange, Sticker, ThreadSummary, TimelineItemContent,
     },
     local::{EventSendState, MediaUploadProgress},
+    source::EventItemSource,
 };
 pub(super) use self::{
     content::{
@@ -81,6 +87,12 @@ pub struct EventTimelineItem {
     ///
     /// May be false when we don't know about the room encryption status yet.
     pub(super) is_room_encrypted: bool,
+    /// Whether the item starts a group of consecutive messages from the same
+    /// sender.
+    pub(super) is_first_of_group: bool,
+    /// Whether the item ends a group of consecutive messages from the same
+    /// sender.
+    pub(super) is_last_of_group: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -120,7 +132,16 @@ impl EventTimelineItem {
         kind: EventTimelineItemKind,
         is_room_encrypted: bool,
     ) -> Self {
-        Self { sender, sender_profile, timestamp, content, kind, is_room_encrypted }
+        Self {
+            sender,
+            sender_profile,
+            timestamp,
+            content,
+            kind,
+            is_room_encrypted,
+            is_first_of_group: true,
+            is_last_of_group: true,
+        }
     }
 
     /// If the supplied low-level [`TimelineEvent`] is suitable for use as the
@@ -146,6 +167,7 @@ impl EventTimelineItem {
 
         let raw_sync_event = latest_event.event().raw().clone();
         let encryption_info = latest_event.event().encryption_info().cloned();
+        let encrypted_json = latest_event.event().encrypted_raw().cloned();
 
         let Ok(event) = raw_sync_event.deserialize() else {
             warn!("Unable to deserialize latest_event as an AnySyncTimelineEvent!");
@@ -183,20 +205,37 @@ impl EventTimelineItem {
         // Probably the origin of the event doesn't matter for the preview.
         let origin = RemoteEventOrigin::Sync;
 
+        let room = client.get_room(room_id);
+
+        let mentions_me = match (is_own, client.user_id()) {
+            (false, Some(own_user_id)) => {
+                let own_profile = match &room {
+                    Some(room) => room.profile_from_user_id(own_user_id).await,
+                    None => None,
+                };
+                let own_display_name = own_profile.and_then(|profile| profile.display_name);
+
+                content.mentions_user(own_user_id, own_display_name.as_deref())
+            }
+            _ => false,
+        };
+
         let kind = RemoteEventTimelineItem {
             event_id,
             transaction_id: None,
             read_receipts,
             is_own,
             is_highlighted,
+            mentions_me,
+            url_previews: Vec::new(),
             encryption_info,
             original_json: Some(raw_sync_event),
             latest_edit_json,
+            encrypted_json,
             origin,
         }
         .into();
 
-        let room = client.get_room(room_id);
         let sender_profile = if let Some(room) = room {
             let mut profile = room.profile_from_latest_event(&latest_event);
 
@@ -210,7 +249,16 @@ impl EventTimelineItem {
             TimelineDetails::Unavailable
         };
 
-        Some(Self { sender, sender_profile, timestamp, content, kind, is_room_encrypted: false })
+        Some(Self {
+            sender,
+            sender_profile,
+            timestamp,
+            content,
+            kind,
+            is_room_encrypted: false,
+            is_first_of_group: true,
+            is_last_of_group: true,
+        })
     }
 
     /// Check whether this item is a local echo.
@@ -320,6 +368,17 @@ impl EventTimelineItem {
         &mut self.content
     }
 
+    /// Get the summary of the thread this item is the root of, if any.
+    ///
+    /// The summary is kept up to date as the replies to the thread and our
+    /// read receipts in it are received, so the number of replies, the latest
+    /// one and whether it's unread can be shown directly.
+    ///
+    /// Shorthand for `item.content().thread_summary()`.
+    pub fn thread_summary(&self) -> Option<ThreadSummary> {
+        self.content.thread_summary()
+    }
+
     /// Get the read receipts of this item.
     ///
     /// The key is the ID of a room member and the value are details about the
@@ -343,6 +402,28 @@ impl EventTimelineItem {
         self.timestamp
     }
 
+    /// Whether this item is the first of a group of consecutive messages from
+    /// the same sender, e.g. to show the name of the sender above it.
+    ///
+    /// Always `true`, unless the grouping has been enabled with
+    /// [`TimelineBuilder::group_consecutive_events`].
+    ///
+    /// [`TimelineBuilder::group_consecutive_events`]: crate::timeline::TimelineBuilder::group_consecutive_events
+    pub fn is_first_of_group(&self) -> bool {
+        self.is_first_of_group
+    }
+
+    /// Whether this item is the last of a group of consecutive messages from
+    /// the same sender, e.g. to show the avatar of the sender next to it.
+    ///
+    /// Always `true`, unless the grouping has been enabled with
+    /// [`TimelineBuilder::group_consecutive_events`].
+    ///
+    /// [`TimelineBuilder::group_consecutive_events`]: crate::timeline::TimelineBuilder::group_consecutive_events
+    pub fn is_last_of_group(&self) -> bool {
+        self.is_last_of_group
+    }
+
     /// Whether this timeline item was sent by the logged-in user themselves.
     pub fn is_own(&self) -> bool {
         match &self.kind {
@@ -395,6 +476,76 @@ impl EventTimelineItem {
         }
     }
 
+    /// Whether the event mentions the logged-in user, or the whole room.
+    ///
+    /// The mentions are the ones detected by [`Message::mentions_user()`], with
+    /// the display name of the user in the room when the timeline was built,
+    /// and the user's own messages never mention them.
+    pub fn mentions_me(&self) -> bool {
+        match &self.kind {
+            EventTimelineItemKind::Local(_) => false,
+            EventTimelineItemKind::Remote(remote_event) => remote_event.mentions_me,
+        }
+    }
+
+    /// Update whether the event mentions the logged-in user, from its current
+    /// content.
+    pub(super) fn update_mentions_me(
+        &mut self,
+        own_user_id: &UserId,
+        own_display_name: Option<&str>,
+    ) {
+        let mentions_me =
+            !self.is_own() && self.content.mentions_user(own_user_id, own_display_name);
+
+        if let EventTimelineItemKind::Remote(remote_event) = &mut self.kind {
+            remote_event.mentions_me = mentions_me;
+        }
+    }
+
+    /// Get the previews of the links of the message, in the order of
+    /// [`Message::links()`].
+    ///
+    /// They're only fetched when the timeline has been built with
+    /// [`TimelineBuilder::with_url_previews()`](crate::timeline::TimelineBuilder::with_url_previews),
+    /// and the links without anything to preview don't have one.
+    pub fn url_previews(&self) -> &[UrlPreview] {
+        match &self.kind {
+            EventTimelineItemKind::Local(_) => &[],
+            EventTimelineItemKind::Remote(remote_event) => &remote_event.url_previews,
+        }
+    }
+
+    /// Add the given previews to the ones of the links of the message.
+    pub(super) fn add_url_previews(&mut self, previews: Vec<UrlPreview>) {
+        let links = self.links();
+
+        if let EventTimelineItemKind::Remote(remote_event) = &mut self.kind {
+            remote_event.url_previews.extend(previews);
+            remote_event.url_previews.sort_by_key(|preview| {
+                links.iter().position(|link| *link == preview.url).unwrap_or(usize::MAX)
+            });
+        }
+    }
+
+    /// Remove the previews of the links which aren't in the message anymore,
+    /// e.g. after it's been edited.
+    pub(super) fn retain_url_previews(&mut self) {
+        let links = self.links();
+
+        if let EventTimelineItemKind::Remote(remote_event) = &mut self.kind {
+            remote_event.url_previews.retain(|preview| links.contains(&preview.url));
+        }
+    }
+
+    /// The links of the message, if this is one.
+    fn links(&self) -> Vec<String> {
+        self.content
+            .as_message()
+            .map(|message| message.links().into_iter().map(ToOwned::to_owned).collect())
+            .unwrap_or_default()
+    }
+
     /// Get the encryption information for the event, if any.
     pub fn encryption_info(&self) -> Option<&EncryptionInfo> {
         match &self.kind {
@@ -405,6 +556,10 @@ impl EventTimelineItem {
 
     /// Gets the [`ShieldState`] which can be used to decorate messages in the
     /// recommended way.
+    ///
+    /// The timeline keeps the encryption info of its items up to date when the
+    /// devices or identities of their senders change, so the item is replaced
+    /// whenever its shield may have changed.
     pub fn get_shield(&self, strict: bool) -> Option<ShieldState> {
         if !self.is_room_encrypted || self.is_local_echo() {
             return None;
@@ -462,6 +617,24 @@ impl EventTimelineItem {
         }
     }
 
+    /// Get the source of the event, with its JSON and its encryption
+    /// metadata, for "view source" and debugging UIs.
+    ///
+    /// Returns `None` if this event hasn't been echoed back by the server
+    /// yet, or if it has been redacted.
+    pub fn source(&self) -> Option<EventItemSource<'_>> {
+        let EventTimelineItemKind::Remote(remote_event) = &self.kind else {
+            return None;
+        };
+
+        Some(EventItemSource {
+            json: remote_event.original_json.as_ref()?,
+            encrypted_json: remote_event.encrypted_json.as_ref(),
+            latest_edit_json: remote_event.latest_edit_json.as_ref(),
+            encryption_info: remote_event.encryption_info.as_deref(),
+        })
+    }
+
     /// Shorthand for
     /// `item.latest_edit_json().or_else(|| item.original_json())`.
     pub fn latest_json(&self) -> Option<&Raw<AnySyncTimelineEvent>> {
@@ -548,6 +721,8 @@ impl EventTimelineItem {
             content,
             kind,
             is_room_encrypted: self.is_room_encrypted,
+            is_first_of_group: self.is_first_of_group,
+            is_last_of_group: self.is_last_of_group,
         }
     }
 
@@ -616,7 +791,8 @@ impl EventTimelineItem {
             | TimelineItemContent::FailedToParseMessageLike { .. }
             | TimelineItemContent::FailedToParseState { .. }
             | TimelineItemContent::CallInvite
-            | TimelineItemContent::RtcNotification => None,
+            | TimelineItemContent::RtcNotification
+            | TimelineItemContent::LiveLocation(_) => None,
         };
 
         if let Some(body) = body {
@@ -742,10 +918,45 @@ pub struct ReactionInfo {
     pub status: ReactionStatus,
 }
 
+/// How the reactions of the event items are ordered, when set with
+/// [`TimelineBuilder::with_reaction_ordering()`](super::TimelineBuilder::with_reaction_ordering).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReactionOrdering {
+    /// The keys with the most reactions first.
+    ///
+    /// The keys with the same number of reactions are ordered by their first
+    /// reaction, the oldest first, so they don't move when they get new
+    /// reactions.
+    ByCount,
+
+    /// The keys which received a reaction most recently first.
+    ByRecency,
+}
+
+/// A summary of the reactions with the same key, returned by
+/// [`ReactionsByKeyBySender::summaries()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReactionSummary {
+    /// The key of the reactions.
+    pub key: String,
+    /// The number of users who reacted with this key.
+    pub count: usize,
+    /// When the first reaction with this key was sent.
+    pub first_reaction_timestamp: MilliSecondsSinceUnixEpoch,
+    /// When the most recent reaction with this key was sent.
+    pub latest_reaction_timestamp: MilliSecondsSinceUnixEpoch,
+    /// Whether the current user reacted with this key.
+    pub reacted_by_me: bool,
+}
+
 /// Reactions grouped by key first, then by sender.
 ///
 /// This representation makes sure that a given sender has sent at most one
 /// reaction for an event.
+///
+/// When the timeline has a [`ReactionOrdering`], the keys are kept in that
+/// order, and the senders of each key are ordered by the time of their
+/// reaction, the oldest first.
 #[derive(Debug, Clone, Default)]
 pub struct ReactionsByKeyBySender(IndexMap<String, IndexMap<OwnedUserId, ReactionInfo>>);
 
@@ -785,6 +996,73 @@ impl ReactionsByKeyBySender {
         }
         None
     }
+
+    /// Get a summary of the reactions of each key, in the order of the keys.
+    pub fn summaries(&self, own_user_id: &UserId) -> Vec<ReactionSummary> {
+        self.0
+            .iter()
+            .map(|(key, by_sender)| {
+                let (first_reaction_timestamp, latest_reaction_timestamp) =
+                    reaction_timestamps_range(by_sender);
+
+                ReactionSummary {
+                    key: key.clone(),
+                    count: by_sender.len(),
+                    first_reaction_timestamp,
+                    latest_reaction_timestamp,
+                    reacted_by_me: by_sender.contains_key(own_user_id),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the keys and the senders are in the order of the given
+    /// [`ReactionOrdering`].
+    pub(crate) fn is_sorted(&self, ordering: ReactionOrdering) -> bool {
+        let values = self.0.values();
+        values.clone().zip(values.skip(1)).all(|(a, b)| compare_keys(a, b, ordering).is_le())
+            && self.0.values().all(|by_sender| {
+                let timestamps = by_sender.values().map(|info| info.timestamp);
+                timestamps.clone().zip(timestamps.skip(1)).all(|(a, b)| a <= b)
+            })
+    }
+
+    /// Sort the keys and the senders in the order of the given
+    /// [`ReactionOrdering`].
+    pub(crate) fn sort(&mut self, ordering: ReactionOrdering) {
+        for by_sender in self.0.values_mut() {
+            by_sender.sort_by(|_, a, _, b| a.timestamp.cmp(&b.timestamp));
+        }
+
+        self.0.sort_by(|_, a, _, b| compare_keys(a, b, ordering));
+    }
+}
+
+/// The timestamps of the first and the most recent reactions of a key.
+fn reaction_timestamps_range(
+    by_sender: &IndexMap<OwnedUserId, ReactionInfo>,
+) -> (MilliSecondsSinceUnixEpoch, MilliSecondsSinceUnixEpoch) {
+    let timestamps = by_sender.values().map(|info| info.timestamp);
+    // A key always has at least one reaction.
+    let first = timestamps.clone().min().unwrap_or(MilliSecondsSinceUnixEpoch(0u32.into()));
+    let latest = timestamps.max().unwrap_or(first);
+    (first, latest)
+}
+
+/// Compare the reactions of two keys, according to the given
+/// [`ReactionOrdering`].
+fn compare_keys(
+    a: &IndexMap<OwnedUserId, ReactionInfo>,
+    b: &IndexMap<OwnedUserId, ReactionInfo>,
+    ordering: ReactionOrdering,
+) -> Ordering {
+    let (a_first, a_latest) = reaction_timestamps_range(a);
+    let (b_first, b_latest) = reaction_timestamps_range(b);
+
+    match ordering {
+        ReactionOrdering::ByCount => b.len().cmp(&a.len()).then(a_first.cmp(&b_first)),
+        ReactionOrdering::ByRecency => b_latest.cmp(&a_latest),
+    }
 }
 
 #[cfg(test)]
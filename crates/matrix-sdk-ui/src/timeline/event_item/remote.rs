@@ -15,7 +15,7 @@
 use std::{fmt, sync::Arc};
 
 use indexmap::IndexMap;
-use matrix_sdk::deserialized_responses::EncryptionInfo;
+use matrix_sdk::{deserialized_responses::EncryptionInfo, media::UrlPreview};
 use ruma::{
     OwnedEventId, OwnedTransactionId, OwnedUserId,
     events::{AnySyncTimelineEvent, receipt::Receipt},
@@ -45,6 +45,12 @@ pub(in crate::timeline) struct RemoteEventTimelineItem {
     /// Whether the item should be highlighted in the timeline.
     pub is_highlighted: bool,
 
+    /// Whether the event mentions the logged-in user, or the whole room.
+    pub mentions_me: bool,
+
+    /// The previews of the links of the message, if they've been fetched.
+    pub url_previews: Vec<UrlPreview>,
+
     /// Encryption information.
     pub encryption_info: Option<Arc<EncryptionInfo>>,
 
@@ -63,6 +69,10 @@ pub(in crate::timeline) struct RemoteEventTimelineItem {
     /// JSON of the latest edit to this item.
     pub latest_edit_json: Option<Raw<AnySyncTimelineEvent>>,
 
+    /// JSON of the encrypted event, as received from the server, if it could
+    /// be decrypted.
+    pub encrypted_json: Option<Raw<AnySyncTimelineEvent>>,
+
     /// Where we got this event from: A sync response or pagination.
     pub origin: RemoteEventOrigin,
 }
@@ -70,7 +80,13 @@ pub(in crate::timeline) struct RemoteEventTimelineItem {
 impl RemoteEventTimelineItem {
     /// Clone the current event item, and redacts its fields.
     pub fn redact(&self) -> Self {
-        Self { original_json: None, latest_edit_json: None, ..self.clone() }
+        Self {
+            original_json: None,
+            latest_edit_json: None,
+            encrypted_json: None,
+            url_previews: Vec::new(),
+            ..self.clone()
+        }
     }
 }
 
@@ -99,7 +115,10 @@ impl fmt::Debug for RemoteEventTimelineItem {
             encryption_info,
             original_json: _,
             latest_edit_json: _,
+            encrypted_json: _,
             is_highlighted,
+            mentions_me,
+            url_previews,
             origin,
         } = self;
 
@@ -109,6 +128,8 @@ impl fmt::Debug for RemoteEventTimelineItem {
             .field("read_receipts", read_receipts)
             .field("is_own", is_own)
             .field("is_highlighted", is_highlighted)
+            .field("mentions_me", mentions_me)
+            .field("url_previews", url_previews)
             .field("encryption_info", encryption_info)
             .field("origin", origin)
             .finish_non_exhaustive()
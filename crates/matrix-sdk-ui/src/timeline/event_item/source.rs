@@ -0,0 +1,170 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::deserialized_responses::{AlgorithmInfo, EncryptionInfo};
+use ruma::{DeviceId, EventEncryptionAlgorithm, events::AnySyncTimelineEvent, serde::Raw};
+
+/// The source of an event item received from the homeserver, for "view
+/// source" and debugging UIs.
+///
+/// It's obtained with
+/// [`EventTimelineItem::source()`](super::EventTimelineItem::source).
+#[derive(Clone, Copy, Debug)]
+pub struct EventItemSource<'a> {
+    pub(super) json: &'a Raw<AnySyncTimelineEvent>,
+    pub(super) encrypted_json: Option<&'a Raw<AnySyncTimelineEvent>>,
+    pub(super) latest_edit_json: Option<&'a Raw<AnySyncTimelineEvent>>,
+    pub(super) encryption_info: Option<&'a EncryptionInfo>,
+}
+
+impl<'a> EventItemSource<'a> {
+    /// The JSON of the event, which is the decrypted payload if the event was
+    /// encrypted and could be decrypted.
+    pub fn json(&self) -> &'a Raw<AnySyncTimelineEvent> {
+        self.json
+    }
+
+    /// The JSON of the encrypted event, as it was received from the
+    /// homeserver, if the event was encrypted and could be decrypted.
+    ///
+    /// The encrypted events aren't persisted in the event cache, so it's
+    /// unknown for the events which have been loaded from the store.
+    pub fn encrypted_json(&self) -> Option<&'a Raw<AnySyncTimelineEvent>> {
+        self.encrypted_json
+    }
+
+    /// The JSON of the latest edit of the event, if any.
+    pub fn latest_edit_json(&self) -> Option<&'a Raw<AnySyncTimelineEvent>> {
+        self.latest_edit_json
+    }
+
+    /// The encryption information of the event, if it was encrypted.
+    pub fn encryption_info(&self) -> Option<&'a EncryptionInfo> {
+        self.encryption_info
+    }
+
+    /// The algorithm which was used to encrypt the event, if it was encrypted.
+    pub fn algorithm(&self) -> Option<EventEncryptionAlgorithm> {
+        Some(match self.encryption_info?.algorithm_info {
+            AlgorithmInfo::MegolmV1AesSha2 { .. } => EventEncryptionAlgorithm::MegolmV1AesSha2,
+            AlgorithmInfo::OlmV1Curve25519AesSha2 { .. } => {
+                EventEncryptionAlgorithm::OlmV1Curve25519AesSha2
+            }
+        })
+    }
+
+    /// The Curve25519 key of the device which sent the event, encoded as
+    /// base64, if it was encrypted.
+    ///
+    /// For a Megolm session, it's the key of the device which created the
+    /// session.
+    pub fn sender_key(&self) -> Option<&'a str> {
+        match &self.encryption_info?.algorithm_info {
+            AlgorithmInfo::MegolmV1AesSha2 { curve25519_key, .. } => Some(curve25519_key.as_str()),
+            AlgorithmInfo::OlmV1Curve25519AesSha2 { curve25519_public_key_base64 } => {
+                Some(curve25519_public_key_base64.as_str())
+            }
+        }
+    }
+
+    /// The ID of the device which sent the event, if it was encrypted and the
+    /// device is known.
+    pub fn sender_device(&self) -> Option<&'a DeviceId> {
+        self.encryption_info?.sender_device.as_deref()
+    }
+
+    /// The ID of the Megolm session which was used to encrypt the event, if
+    /// known.
+    pub fn session_id(&self) -> Option<&'a str> {
+        self.encryption_info?.session_id()
+    }
+
+    /// The Curve25519 keys, encoded as base64, of the devices through which
+    /// the Megolm session was forwarded to us, if the event was encrypted.
+    ///
+    /// Empty if the session was received from the device which created it.
+    pub fn forwarding_curve25519_key_chain(&self) -> &'a [String] {
+        match self.encryption_info.map(|info| &info.algorithm_info) {
+            Some(AlgorithmInfo::MegolmV1AesSha2 { forwarding_curve25519_key_chain, .. }) => {
+                forwarding_curve25519_key_chain
+            }
+            Some(AlgorithmInfo::OlmV1Curve25519AesSha2 { .. }) | None => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use matrix_sdk::deserialized_responses::{AlgorithmInfo, EncryptionInfo, VerificationState};
+    use matrix_sdk_test::{ALICE, event_factory::EventFactory};
+    use ruma::{EventEncryptionAlgorithm, device_id, event_id};
+
+    use super::EventItemSource;
+
+    #[test]
+    fn test_source_of_an_encrypted_event() {
+        let f = EventFactory::new().sender(*ALICE);
+        let json =
+            f.text_msg("It's a secret to everybody").event_id(event_id!("$1")).into_raw_sync();
+        let encrypted_json = f.text_msg("<encrypted>").event_id(event_id!("$1")).into_raw_sync();
+        let encryption_info = EncryptionInfo {
+            sender: ALICE.to_owned(),
+            sender_device: Some(device_id!("ALICEDEVICE").to_owned()),
+            algorithm_info: AlgorithmInfo::MegolmV1AesSha2 {
+                curve25519_key: "sender_key".to_owned(),
+                sender_claimed_keys: BTreeMap::new(),
+                session_id: Some("session_id".to_owned()),
+                forwarding_curve25519_key_chain: vec!["forwarder_key".to_owned()],
+            },
+            verification_state: VerificationState::Verified,
+        };
+
+        let source = EventItemSource {
+            json: &json,
+            encrypted_json: Some(&encrypted_json),
+            latest_edit_json: None,
+            encryption_info: Some(&encryption_info),
+        };
+
+        assert_eq!(source.json().json().get(), json.json().get());
+        assert_eq!(source.encrypted_json().unwrap().json().get(), encrypted_json.json().get());
+        assert!(source.latest_edit_json().is_none());
+        assert_eq!(source.algorithm(), Some(EventEncryptionAlgorithm::MegolmV1AesSha2));
+        assert_eq!(source.sender_key(), Some("sender_key"));
+        assert_eq!(source.sender_device(), Some(device_id!("ALICEDEVICE")));
+        assert_eq!(source.session_id(), Some("session_id"));
+        assert_eq!(source.forwarding_curve25519_key_chain(), ["forwarder_key".to_owned()]);
+    }
+
+    #[test]
+    fn test_source_of_an_unencrypted_event() {
+        let json = EventFactory::new().sender(*ALICE).text_msg("Hello").into_raw_sync();
+
+        let source = EventItemSource {
+            json: &json,
+            encrypted_json: None,
+            latest_edit_json: None,
+            encryption_info: None,
+        };
+
+        assert!(source.encrypted_json().is_none());
+        assert!(source.algorithm().is_none());
+        assert!(source.sender_key().is_none());
+        assert!(source.sender_device().is_none());
+        assert!(source.session_id().is_none());
+        assert!(source.forwarding_curve25519_key_chain().is_empty());
+    }
+}
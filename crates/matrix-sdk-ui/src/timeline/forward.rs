@@ -0,0 +1,154 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::{
+    Room,
+    media::{MediaFormat, MediaRequestParameters},
+    send_queue::SendHandle,
+};
+use mime::Mime;
+use ruma::{
+    EventId,
+    events::{
+        Mentions,
+        room::{
+            MediaSource,
+            message::{MessageType, RoomMessageEventContent},
+        },
+    },
+};
+use tracing::{debug, instrument};
+
+use super::{Error, ForwardError, TimelineEventItemId};
+
+impl super::Timeline {
+    /// Forward the room message with the given event ID to another room.
+    ///
+    /// The latest version of the content of the message is sent through the
+    /// send queue of the target room, with its caption and formatting, but
+    /// without its relations or mentions.
+    ///
+    /// The media of a media message is reused, unless it's encrypted and the
+    /// target room isn't: in that case, it's decrypted and uploaded again, so
+    /// its decryption key isn't leaked. Its thumbnail is dropped then, since
+    /// the server can generate one for the unencrypted media.
+    #[instrument(
+        skip_all,
+        fields(room_id = ?self.room().room_id(), target_room_id = ?target_room.room_id())
+    )]
+    pub async fn forward(
+        &self,
+        event_id: &EventId,
+        target_room: &Room,
+    ) -> Result<SendHandle, Error> {
+        let Some(item) = self.item_by_event_id(event_id).await else {
+            return Err(Error::EventNotInTimeline(TimelineEventItemId::EventId(
+                event_id.to_owned(),
+            )));
+        };
+
+        let Some(message) = item.content().as_message() else {
+            return Err(ForwardError::NotRoomMessage.into());
+        };
+
+        let mut msgtype = message.msgtype().clone();
+
+        let target_is_encrypted =
+            target_room.latest_encryption_state().await.map_err(ForwardError::from)?.is_encrypted();
+
+        if !target_is_encrypted {
+            reupload_encrypted_media(target_room, &mut msgtype).await?;
+        }
+
+        let content = RoomMessageEventContent::new(msgtype).add_mentions(Mentions::new());
+
+        Ok(target_room.send_queue().send(content.into()).await?)
+    }
+}
+
+/// Replace the encrypted media of the message, if any, by its decrypted data
+/// uploaded again, and drop its thumbnail.
+async fn reupload_encrypted_media(room: &Room, msgtype: &mut MessageType) -> Result<(), Error> {
+    // Some variants look really similar below, but the `content` and `info` are all
+    // different types…
+    match msgtype {
+        MessageType::Image(content) => {
+            let mimetype = content.info.as_ref().and_then(|info| info.mimetype.as_deref());
+            if let Some(source) = decrypt_and_upload(room, &content.source, mimetype).await? {
+                content.source = source;
+                if let Some(info) = &mut content.info {
+                    info.thumbnail_source = None;
+                    info.thumbnail_info = None;
+                }
+            }
+        }
+        MessageType::Video(content) => {
+            let mimetype = content.info.as_ref().and_then(|info| info.mimetype.as_deref());
+            if let Some(source) = decrypt_and_upload(room, &content.source, mimetype).await? {
+                content.source = source;
+                if let Some(info) = &mut content.info {
+                    info.thumbnail_source = None;
+                    info.thumbnail_info = None;
+                }
+            }
+        }
+        MessageType::Audio(content) => {
+            let mimetype = content.info.as_ref().and_then(|info| info.mimetype.as_deref());
+            if let Some(source) = decrypt_and_upload(room, &content.source, mimetype).await? {
+                content.source = source;
+            }
+        }
+        MessageType::File(content) => {
+            let mimetype = content.info.as_ref().and_then(|info| info.mimetype.as_deref());
+            if let Some(source) = decrypt_and_upload(room, &content.source, mimetype).await? {
+                content.source = source;
+                if let Some(info) = &mut content.info {
+                    info.thumbnail_source = None;
+                    info.thumbnail_info = None;
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Decrypt the media with the given source, and upload it again without
+/// encryption.
+///
+/// Returns the source of the uploaded media, or `None` if the media wasn't
+/// encrypted.
+async fn decrypt_and_upload(
+    room: &Room,
+    source: &MediaSource,
+    mimetype: Option<&str>,
+) -> Result<Option<MediaSource>, ForwardError> {
+    if matches!(source, MediaSource::Plain(_)) {
+        return Ok(None);
+    }
+
+    debug!("uploading the decrypted media again for an unencrypted room");
+
+    let media = room.client().media();
+    let request = MediaRequestParameters { source: source.clone(), format: MediaFormat::File };
+    let data = media.get_media_content(&request, true).await?;
+
+    let mimetype = mimetype
+        .and_then(|mimetype| mimetype.parse::<Mime>().ok())
+        .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+    let response = media.upload(&mimetype, data, None).await?;
+
+    Ok(Some(MediaSource::Plain(response.content_uri)))
+}
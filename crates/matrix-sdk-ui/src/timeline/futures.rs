@@ -1,9 +1,14 @@
 use std::future::IntoFuture;
 
-use eyeball::SharedObservable;
-use matrix_sdk::TransmissionProgress;
+use eyeball::{SharedObservable, Subscriber};
+use matrix_sdk::{
+    TransmissionProgress,
+    executor::{AbortOnDrop, spawn},
+    send_queue::{AbstractProgress, RoomSendQueueError, RoomSendQueueUpdate, SendHandle},
+};
 use matrix_sdk_base::boxed_into_future;
 use mime::Mime;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
 use tracing::{Instrument as _, Span};
 
 use super::{AttachmentSource, Error, Timeline};
@@ -56,7 +61,7 @@ impl<'a> SendAttachment<'a> {
 }
 
 impl<'a> IntoFuture for SendAttachment<'a> {
-    type Output = Result<(), Error>;
+    type Output = Result<SendAttachmentHandle, Error>;
     boxed_into_future!(extra_bounds: 'a);
 
     fn into_future(self) -> Self::IntoFuture {
@@ -73,6 +78,10 @@ impl<'a> IntoFuture for SendAttachment<'a> {
         let fut = async move {
             let (data, filename) = source.try_into_bytes_and_filename()?;
 
+            let thumbnail_len =
+                config.thumbnail.as_ref().map_or(0, |thumbnail| thumbnail.data.len());
+            let total = thumbnail_len + data.len();
+
             let reply = timeline.infer_reply(config.in_reply_to).await;
             let sdk_config = matrix_sdk::attachment::AttachmentConfig {
                 txn_id: config.txn_id,
@@ -85,12 +94,34 @@ impl<'a> IntoFuture for SendAttachment<'a> {
             };
 
             if use_send_queue {
-                timeline
-                    .room()
-                    .send_queue()
+                let send_queue = timeline.room().send_queue();
+
+                // Subscribe before queuing the upload, so as not to miss its first updates.
+                let (_, updates) = send_queue.subscribe().await?;
+
+                let send_handle = send_queue
                     .send_attachment(filename, mime_type, data, sdk_config)
                     .await
                     .map_err(|_| Error::FailedSendingAttachment)?;
+
+                let progress = SharedObservable::new(AttachmentUploadProgress {
+                    phase: AttachmentUploadPhase::Pending,
+                    current: 0,
+                    total,
+                });
+
+                let progress_task = spawn(track_upload_progress(
+                    updates,
+                    send_handle.clone(),
+                    thumbnail_len,
+                    progress.clone(),
+                ));
+
+                Ok(SendAttachmentHandle {
+                    send_handle: Some(send_handle),
+                    progress,
+                    _progress_task: Some(AbortOnDrop::new(progress_task)),
+                })
             } else {
                 timeline
                     .room()
@@ -99,15 +130,170 @@ impl<'a> IntoFuture for SendAttachment<'a> {
                     .store_in_cache()
                     .await
                     .map_err(|_| Error::FailedSendingAttachment)?;
-            }
 
-            Ok(())
+                // The attachment has been sent by now.
+                Ok(SendAttachmentHandle {
+                    send_handle: None,
+                    progress: SharedObservable::new(AttachmentUploadProgress {
+                        phase: AttachmentUploadPhase::Sent,
+                        current: total,
+                        total,
+                    }),
+                    _progress_task: None,
+                })
+            }
         };
 
         Box::pin(fut.instrument(tracing_span))
     }
 }
 
+/// The step an attachment sent with [`Timeline::send_attachment`] is at.
+///
+/// It's also reported by the local echoes of media, in
+/// [`MediaUploadProgress::phase`](crate::timeline::MediaUploadProgress::phase).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum AttachmentUploadPhase {
+    /// The upload hasn't started yet, e.g. because other requests of the send
+    /// queue are sent before it.
+    Pending,
+
+    /// The thumbnail of the attachment is being uploaded.
+    Thumbnail,
+
+    /// The file of the attachment is being uploaded, after its thumbnail if
+    /// any.
+    File,
+
+    /// The media have been uploaded, and the event referencing them is being
+    /// sent.
+    Sending,
+
+    /// The event has been sent.
+    Sent,
+}
+
+impl AttachmentUploadPhase {
+    /// Deduce the phase of an upload from its progress reported by the send
+    /// queue.
+    ///
+    /// The thumbnail is uploaded before the file, so the phase is deduced from
+    /// the number of bytes uploaded so far, and from the size of the thumbnail.
+    pub(crate) fn from_upload_progress(progress: AbstractProgress, thumbnail_len: usize) -> Self {
+        if progress.current >= progress.total {
+            Self::Sending
+        } else if progress.current < thumbnail_len {
+            Self::Thumbnail
+        } else {
+            Self::File
+        }
+    }
+}
+
+/// The progress of an attachment sent with [`Timeline::send_attachment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttachmentUploadProgress {
+    /// What's being uploaded.
+    pub phase: AttachmentUploadPhase,
+
+    /// The number of bytes uploaded so far, across the thumbnail and the file.
+    pub current: usize,
+
+    /// The total number of bytes to upload, across the thumbnail and the file.
+    pub total: usize,
+}
+
+/// A handle on an attachment sent with [`Timeline::send_attachment`], to
+/// follow the progress of its upload or to cancel it.
+///
+/// Without [`SendAttachment::use_send_queue`], the attachment has already been
+/// sent once this handle is returned.
+pub struct SendAttachmentHandle {
+    /// The handle on the request of the send queue, if it's been used.
+    send_handle: Option<SendHandle>,
+
+    progress: SharedObservable<AttachmentUploadProgress>,
+
+    /// Task updating the progress from the updates of the send queue.
+    _progress_task: Option<AbortOnDrop<()>>,
+}
+
+impl SendAttachmentHandle {
+    /// Get the current progress of the upload.
+    ///
+    /// The bytes are only reported as they're uploaded if
+    /// [`matrix_sdk::send_queue::SendQueue::enable_upload_progress`] has been
+    /// called; the phase is always updated.
+    pub fn progress(&self) -> AttachmentUploadProgress {
+        self.progress.get()
+    }
+
+    /// Subscribe to the progress of the upload.
+    pub fn subscribe_to_progress(&self) -> Subscriber<AttachmentUploadProgress> {
+        self.progress.subscribe()
+    }
+
+    /// Cancel the upload and the sending of the attachment, and remove its
+    /// local echo from the timeline.
+    ///
+    /// Returns `true` if it's been cancelled, `false` if it's too late because
+    /// the event has been sent already.
+    pub async fn cancel(&self) -> Result<bool, Error> {
+        let Some(send_handle) = &self.send_handle else {
+            return Ok(false);
+        };
+
+        Ok(send_handle.abort().await.map_err(RoomSendQueueError::from)?)
+    }
+}
+
+/// Update the progress of an attachment from the updates of the send queue,
+/// until its event has been sent or cancelled.
+async fn track_upload_progress(
+    mut updates: Receiver<RoomSendQueueUpdate>,
+    send_handle: SendHandle,
+    thumbnail_len: usize,
+    progress: SharedObservable<AttachmentUploadProgress>,
+) {
+    let transaction_id = send_handle.transaction_id();
+
+    loop {
+        match updates.recv().await {
+            Ok(RoomSendQueueUpdate::MediaUpload { related_to, progress: upload, .. })
+                if *related_to == *transaction_id =>
+            {
+                progress.set(AttachmentUploadProgress {
+                    phase: AttachmentUploadPhase::from_upload_progress(upload, thumbnail_len),
+                    current: upload.current,
+                    total: upload.total,
+                });
+            }
+
+            Ok(RoomSendQueueUpdate::SentEvent { transaction_id: sent, .. })
+                if *sent == *transaction_id =>
+            {
+                let total = progress.get().total;
+                progress.set(AttachmentUploadProgress {
+                    phase: AttachmentUploadPhase::Sent,
+                    current: total,
+                    total,
+                });
+                break;
+            }
+
+            Ok(RoomSendQueueUpdate::CancelledLocalEvent { transaction_id: cancelled })
+                if *cancelled == *transaction_id =>
+            {
+                break;
+            }
+
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
 #[cfg(feature = "unstable-msc4274")]
 pub use galleries::*;
 
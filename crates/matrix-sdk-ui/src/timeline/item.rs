@@ -16,7 +16,7 @@ use std::{ops::Deref, sync::Arc};
 
 use as_variant::as_variant;
 
-use super::{EventTimelineItem, VirtualTimelineItem};
+use super::{CustomVirtualItem, EventTimelineItem, VirtualTimelineItem};
 
 /// Opaque unique identifier for a timeline item.
 ///
@@ -94,6 +94,21 @@ impl TimelineItem {
         })
     }
 
+    /// Create the item for a custom virtual item, whose unique ID is derived
+    /// from its own identifier, so it's stable when the item is moved.
+    pub(crate) fn custom(item: CustomVirtualItem) -> Arc<TimelineItem> {
+        let internal_id = TimelineUniqueId(format!("__custom_{}", item.id));
+        Arc::new(Self {
+            kind: TimelineItemKind::Virtual(VirtualTimelineItem::Custom(item)),
+            internal_id,
+        })
+    }
+
+    /// Get the inner [`CustomVirtualItem`], if this is one.
+    pub(crate) fn as_custom(&self) -> Option<&CustomVirtualItem> {
+        as_variant!(&self.kind, TimelineItemKind::Virtual(VirtualTimelineItem::Custom(item)) => item)
+    }
+
     pub(crate) fn is_local_echo(&self) -> bool {
         matches!(&self.kind, TimelineItemKind::Event(ev) if ev.is_local_echo())
     }
@@ -119,6 +134,11 @@ impl TimelineItem {
     pub fn is_timeline_start(&self) -> bool {
         matches!(self.kind, TimelineItemKind::Virtual(VirtualTimelineItem::TimelineStart))
     }
+
+    /// Check whether this item is a (virtual) timeline reset item.
+    pub fn is_timeline_reset(&self) -> bool {
+        matches!(self.kind, TimelineItemKind::Virtual(VirtualTimelineItem::TimelineReset))
+    }
 }
 
 impl Deref for TimelineItem {
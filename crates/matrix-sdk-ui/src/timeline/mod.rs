@@ -16,7 +16,7 @@
 //!
 //! See [`Timeline`] for details.
 
-use std::{fs, path::PathBuf, sync::Arc};
+use std::{fmt, fs, ops::Range, path::PathBuf, sync::Arc, time::Duration};
 
 use algorithms::rfind_event_by_item_id;
 use event_item::TimelineItemHandle;
@@ -26,22 +26,23 @@ use futures::SendGallery;
 use futures_core::Stream;
 use imbl::Vector;
 use matrix_sdk::{
-    Result,
-    attachment::{AttachmentInfo, Thumbnail},
+    ComposerDraft, Result,
+    attachment::{AttachmentInfo, BaseAudioInfo, Thumbnail},
     deserialized_responses::TimelineEvent,
     event_cache::{EventCacheDropHandles, RoomEventCache},
     executor::JoinHandle,
     room::{
-        Receipts, Room,
+        Receipts, ReportedContentScore, Room,
         edit::EditedContent,
         reply::{EnforceThread, Reply},
     },
     send_queue::{RoomSendQueueError, SendHandle},
 };
+use matrix_sdk_base::{RoomInfoNotableUpdateReasons, SendOutsideWasm, SyncOutsideWasm};
 use mime::Mime;
 use pinned_events_loader::PinnedEventsRoom;
 use ruma::{
-    EventId, OwnedEventId, OwnedTransactionId, UserId,
+    EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, UInt, UserId,
     api::client::receipt::create_receipt::v3::ReceiptType,
     events::{
         AnyMessageLikeEventContent, AnySyncTimelineEvent, Mentions,
@@ -58,7 +59,7 @@ use ruma::{
     },
     room_version_rules::RoomVersionRules,
 };
-use subscriber::TimelineWithDropHandle;
+use subscriber::{TimelineWithDropHandle, coalesce_diffs};
 use thiserror::Error;
 use tracing::{instrument, trace, warn};
 
@@ -71,15 +72,18 @@ mod algorithms;
 mod builder;
 mod controller;
 mod date_dividers;
+mod edit_history;
 mod error;
 mod event_handler;
 mod event_item;
 pub mod event_type_filter;
+mod forward;
 pub mod futures;
 mod item;
 mod latest_event;
 mod pagination;
 mod pinned_events_loader;
+mod starred_events_loader;
 mod subscriber;
 mod tasks;
 #[cfg(test)]
@@ -88,23 +92,28 @@ mod to_device;
 mod traits;
 mod virtual_item;
 
+#[cfg(feature = "unstable-msc4274")]
+pub use self::event_item::{GalleryMedia, GalleryMediaKind};
 pub use self::{
     builder::TimelineBuilder,
-    controller::default_event_filter,
+    controller::{DecryptionRetryReport, default_event_filter},
+    edit_history::EditHistoryEntry,
     error::*,
     event_item::{
         AnyOtherFullStateEventContent, EmbeddedEvent, EncryptedMessage, EventItemOrigin,
-        EventSendState, EventTimelineItem, InReplyToDetails, MediaUploadProgress,
-        MemberProfileChange, MembershipChange, Message, MsgLikeContent, MsgLikeKind, OtherState,
-        PollResult, PollState, Profile, ReactionInfo, ReactionStatus, ReactionsByKeyBySender,
+        EventItemSource, EventSendState, EventTimelineItem, InReplyToDetails, LiveLocationState,
+        MediaUploadProgress, MemberProfileChange, MembershipChange, Message, MessageEffects,
+        MsgLikeContent, MsgLikeKind, OtherState, PollResult, PollState, Profile, ReactionInfo,
+        ReactionOrdering, ReactionStatus, ReactionSummary, ReactionsByKeyBySender,
         RoomMembershipChange, RoomPinnedEventsChange, Sticker, ThreadSummary, TimelineDetails,
         TimelineEventItemId, TimelineItemContent,
     },
     event_type_filter::TimelineEventTypeFilter,
     item::{TimelineItem, TimelineItemKind, TimelineUniqueId},
     latest_event::LatestEventValue,
+    pagination::TimelinePaginationStatus,
     traits::RoomExt,
-    virtual_item::VirtualTimelineItem,
+    virtual_item::{CustomItemAnchor, CustomVirtualItem, MembershipSummary, VirtualTimelineItem},
 };
 
 /// A high-level view into a regular¹ room's contents.
@@ -125,6 +134,24 @@ pub struct Timeline {
     drop_handle: Arc<TimelineDropHandle>,
 }
 
+/// A summary of the items held in memory by a [`Timeline`], as returned by
+/// [`Timeline::memory_report`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimelineMemoryReport {
+    /// The number of items of the timeline, including the virtual items.
+    pub items: usize,
+
+    /// The number of items which are events, including the local echoes.
+    pub event_items: usize,
+
+    /// The number of local echoes, i.e. the events being sent.
+    pub local_echoes: usize,
+
+    /// The number of remote events known by the timeline, including the ones
+    /// without an item, e.g. the reactions or the filtered out events.
+    pub remote_events: usize,
+}
+
 /// What should the timeline focus on?
 #[derive(Clone, Debug, PartialEq)]
 pub enum TimelineFocus {
@@ -154,6 +181,25 @@ pub enum TimelineFocus {
 
     /// Only show pinned events.
     PinnedEvents { max_events_to_load: u16, max_concurrent_requests: u16 },
+
+    /// Only show the events starred by the user with
+    /// [`Room::star_event()`](matrix_sdk::Room::star_event), from the most
+    /// recently starred ones.
+    StarredEvents { max_events_to_load: u16, max_concurrent_requests: u16 },
+
+    /// Preview the most recent events of a room the user isn't a member of,
+    /// for instance an invite or a room they've left, if its history is
+    /// world-readable.
+    ///
+    /// The timeline is loaded with `/messages` and can be paginated backwards,
+    /// but it doesn't receive events from the sync. Use
+    /// [`TimelineBuilder::for_room_preview`] to build it from a
+    /// [`RoomPreview`](matrix_sdk::room_preview::RoomPreview), when the room
+    /// isn't known locally.
+    Preview {
+        /// The number of events to load initially.
+        num_events: u16,
+    },
 }
 
 impl TimelineFocus {
@@ -163,18 +209,55 @@ impl TimelineFocus {
             TimelineFocus::Event { target, .. } => format!("permalink:{target}"),
             TimelineFocus::Thread { root_event_id, .. } => format!("thread:{root_event_id}"),
             TimelineFocus::PinnedEvents { .. } => "pinned-events".to_owned(),
+            TimelineFocus::StarredEvents { .. } => "starred-events".to_owned(),
+            TimelineFocus::Preview { .. } => "preview".to_owned(),
         }
     }
 }
 
 /// Changes how dividers get inserted, either in between each day or in between
-/// each month
-#[derive(Debug, Clone)]
+/// each month, in between custom groups of dates, or not at all.
+#[derive(Clone)]
 pub enum DateDividerMode {
     Daily,
     Monthly,
+
+    /// Don't insert any date divider.
+    None,
+
+    /// Insert a date divider in between two events whose timestamps are put in
+    /// different buckets by the given function.
+    ///
+    /// The function must return the same value for all the timestamps of a
+    /// bucket, for instance the number of the week they're in.
+    Custom(Arc<DateDividerBucketFn>),
+}
+
+#[cfg(not(tarpaulin_include))]
+impl fmt::Debug for DateDividerMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => f.write_str("Daily"),
+            Self::Monthly => f.write_str("Monthly"),
+            Self::None => f.write_str("None"),
+            Self::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+        }
+    }
 }
 
+/// The filename of the voice messages sent with
+/// [`Timeline::send_voice_message()`].
+const VOICE_MESSAGE_FILENAME: &str = "voice-message.ogg";
+
+/// The mime type of the voice messages sent with
+/// [`Timeline::send_voice_message()`].
+const VOICE_MESSAGE_MIME_TYPE: &str = "audio/ogg";
+
+/// The maximum number of times the `m.room.pinned_events` state event is sent
+/// by [`Timeline::pin_event()`] and [`Timeline::unpin_event()`], when it keeps
+/// being overwritten by concurrent changes.
+const MAX_PINNED_EVENTS_ATTEMPTS: usize = 3;
+
 /// Configuration for sending an attachment.
 ///
 /// Like [`matrix_sdk::attachment::AttachmentConfig`], but instead of the
@@ -202,6 +285,38 @@ impl Timeline {
         self.controller.clear().await;
     }
 
+    /// Replace the filter choosing which events are added to the timeline, as
+    /// set with [`TimelineBuilder::event_filter`].
+    ///
+    /// The items of a live or thread timeline are rebuilt from the events of
+    /// the event cache, so that the events hidden by the previous filter can
+    /// be shown; with the other focus modes, the new filter only applies to
+    /// the events received afterwards.
+    pub async fn set_event_filter<F>(&self, filter: F) -> Result<(), Error>
+    where
+        F: Fn(&AnySyncTimelineEvent, &RoomVersionRules) -> bool
+            + SendOutsideWasm
+            + SyncOutsideWasm
+            + 'static,
+    {
+        self.controller.set_event_filter(Arc::new(filter), &self.event_cache).await
+    }
+
+    /// Replace the functions transforming the content of the event items, as
+    /// added with [`TimelineBuilder::add_content_transformer`].
+    ///
+    /// Like with [`Self::set_event_filter`], the items of a live or thread
+    /// timeline are rebuilt from the events of the event cache, so that only
+    /// the new transformers apply to them. With the other focus modes, the
+    /// items can't be rebuilt, so the new transformers run on their current
+    /// content, which has already been transformed by the previous ones.
+    pub async fn set_content_transformers(
+        &self,
+        transformers: Vec<Arc<TimelineContentTransformerFn>>,
+    ) -> Result<(), Error> {
+        self.controller.set_content_transformers(transformers, &self.event_cache).await
+    }
+
     /// Retry decryption of previously un-decryptable events given a list of
     /// session IDs whose keys have been imported.
     ///
@@ -235,6 +350,27 @@ impl Timeline {
             .await;
     }
 
+    /// Retry the decryption of every unable-to-decrypt item of the timeline,
+    /// for instance after keys have been imported or the key backup has been
+    /// restored.
+    ///
+    /// Unlike [`Timeline::retry_decryption`], this waits for all the items to
+    /// have been retried, and reports how many of them could be decrypted.
+    #[instrument(skip(self))]
+    pub async fn retry_decryption_for_all_utds(&self) -> DecryptionRetryReport {
+        self.controller.retry_decryption_for_all_utds().await
+    }
+
+    /// Get a summary of the items held in memory by this timeline.
+    ///
+    /// It completes [`Client::memory_report`], which doesn't include the
+    /// timelines since they are owned by the application.
+    ///
+    /// [`Client::memory_report`]: matrix_sdk::Client::memory_report
+    pub async fn memory_report(&self) -> TimelineMemoryReport {
+        self.controller.memory_report().await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn retry_decryption_for_all_events(&self) {
         self.controller.retry_event_decryption(None).await;
@@ -267,13 +403,16 @@ impl Timeline {
     /// timeline items.
     ///
     /// The stream produces `Vec<VectorDiff<_>>`, which means multiple updates
-    /// at once. There are no delays, it consumes as many updates as possible
-    /// and batches them.
+    /// at once. By default, there are no delays, it consumes as many updates as
+    /// possible and batches them. With
+    /// [`TimelineBuilder::coalesce_diffs()`], the updates received over the
+    /// configured time window are merged into a single batch.
     pub async fn subscribe(
         &self,
     ) -> (Vector<Arc<TimelineItem>>, impl Stream<Item = Vec<VectorDiff<Arc<TimelineItem>>>> + use<>)
     {
         let (items, stream) = self.controller.subscribe().await;
+        let stream = coalesce_diffs(stream, self.controller.settings().diffs_coalescing_window);
         let stream = TimelineWithDropHandle::new(stream, self.drop_handle.clone());
         (items, stream)
     }
@@ -559,6 +698,47 @@ impl Timeline {
         SendAttachment::new(self, source.into(), mime_type, config)
     }
 
+    /// Sends a voice message to the room.
+    ///
+    /// The recording is sent as an `m.audio` message with the `m.voice` marker
+    /// and the audio details holding its duration and waveform, as defined by
+    /// MSC3245 and MSC3246, so other clients render it as a voice message.
+    ///
+    /// The message is sent through the send queue, so its local echo shows up
+    /// in the timeline right away, with the waveform available from
+    /// [`Message::waveform()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `audio` - The bytes of the recording, which is expected to be encoded
+    ///   with Opus in an Ogg container.
+    ///
+    /// * `waveform` - The amplitudes of the recording, between 0 and 1024.
+    ///
+    /// * `duration` - The duration of the recording.
+    #[instrument(skip_all)]
+    pub fn send_voice_message(
+        &self,
+        audio: Vec<u8>,
+        waveform: Vec<u16>,
+        duration: Duration,
+    ) -> SendAttachment<'_> {
+        let info = AttachmentInfo::Voice {
+            audio_info: BaseAudioInfo {
+                duration: Some(duration),
+                size: UInt::new(audio.len() as u64),
+            },
+            waveform: Some(waveform),
+        };
+        let config = AttachmentConfig { info: Some(info), ..Default::default() };
+        let source =
+            AttachmentSource::Data { bytes: audio, filename: VOICE_MESSAGE_FILENAME.to_owned() };
+        let mime_type =
+            VOICE_MESSAGE_MIME_TYPE.parse().expect("the voice message mime type should be valid");
+
+        SendAttachment::new(self, source, mime_type, config).use_send_queue()
+    }
+
     /// Sends a media gallery to the room.
     ///
     /// If the encryption feature is enabled, this method will transparently
@@ -607,6 +787,40 @@ impl Timeline {
         Ok(())
     }
 
+    /// Report an event given its [`TimelineEventItemId`] as inappropriate to
+    /// the homeserver's administrator, with an optional score and reason.
+    ///
+    /// Only remote events can be reported.
+    pub async fn report(
+        &self,
+        item_id: &TimelineEventItemId,
+        score: Option<ReportedContentScore>,
+        reason: Option<String>,
+    ) -> Result<(), Error> {
+        let items = self.items().await;
+        let Some((_pos, event)) = rfind_event_by_item_id(&items, item_id) else {
+            return Err(ReportError::ItemNotFound(item_id.clone()).into());
+        };
+
+        let Some(event_id) = event.event_id() else {
+            return Err(ReportError::LocalEcho.into());
+        };
+
+        self.room()
+            .report_content(event_id.to_owned(), score, reason)
+            .await
+            .map_err(ReportError::from)?;
+
+        Ok(())
+    }
+
+    /// Report the room of this timeline as inappropriate to the homeserver's
+    /// administrator.
+    pub async fn report_room(&self, reason: String) -> Result<(), Error> {
+        self.room().report_room(reason).await.map_err(ReportError::from)?;
+        Ok(())
+    }
+
     /// Fetch unavailable details about the event with the given ID.
     ///
     /// This method only works for IDs of remote [`EventTimelineItem`]s,
@@ -679,6 +893,49 @@ impl Timeline {
         self.controller.latest_user_read_receipt_timeline_event_id(user_id).await
     }
 
+    /// Compute the read receipts of the items in the given range of indices
+    /// in [`Self::items()`], if the timeline was built with
+    /// [`TimelineBuilder::lazy_load_read_receipts()`].
+    ///
+    /// The range replaces the previous one: the items which aren't in it
+    /// anymore lose their read receipts, until they're requested again. The
+    /// window follows its items, so it doesn't need to be set again when items
+    /// are added before or after it.
+    ///
+    /// Does nothing if the read receipts aren't lazily loaded.
+    #[instrument(skip(self))]
+    pub async fn load_receipts_for(&self, range: Range<usize>) {
+        self.controller.load_read_receipts_for(range).await
+    }
+
+    /// Show again the items collapsed into the
+    /// [`VirtualTimelineItem::MembershipSummary`] with the given unique ID, if
+    /// the timeline was built with
+    /// [`TimelineBuilder::collapse_membership_changes()`].
+    ///
+    /// Returns `false` if there's no such summary in the timeline.
+    pub async fn expand_membership_summary(&self, unique_id: &TimelineUniqueId) -> bool {
+        self.controller.expand_membership_summary(unique_id).await
+    }
+
+    /// Insert a [`VirtualTimelineItem::Custom`] item next to its anchor event,
+    /// replacing the custom item with the same ID if any.
+    ///
+    /// The item stays next to its anchor while the timeline changes. It's only
+    /// shown while its anchor event is in the timeline, and comes back when
+    /// the anchor is loaded again, e.g. after a back-pagination, until it's
+    /// removed with [`Timeline::remove_custom_item()`].
+    pub async fn insert_custom_item(&self, item: CustomVirtualItem) {
+        self.controller.insert_custom_item(item).await
+    }
+
+    /// Remove the custom item with the given ID from the timeline.
+    ///
+    /// Returns `false` if there was no such item.
+    pub async fn remove_custom_item(&self, id: &str) -> bool {
+        self.controller.remove_custom_item(id).await
+    }
+
     /// Subscribe to changes in the read receipts of our own user.
     pub async fn subscribe_own_user_read_receipts_changed(&self) -> impl Stream<Item = ()> + use<> {
         self.controller.subscribe_own_user_read_receipts_changed().await
@@ -808,53 +1065,101 @@ impl Timeline {
     /// Adds a new pinned event by sending an updated `m.room.pinned_events`
     /// event containing the new event id.
     ///
-    /// This method will first try to get the pinned events from the current
-    /// room's state and if it fails to do so it'll try to load them from the
-    /// homeserver.
+    /// See [`Self::unpin_event()`] for how the list of pinned events is
+    /// updated.
     ///
     /// Returns `true` if we pinned the event, `false` if the event was already
     /// pinned.
     pub async fn pin_event(&self, event_id: &EventId) -> Result<bool> {
-        let mut pinned_event_ids = if let Some(event_ids) = self.room().pinned_event_ids() {
-            event_ids
-        } else {
-            self.room().load_pinned_events().await?.unwrap_or_default()
-        };
-        let event_id = event_id.to_owned();
-        if pinned_event_ids.contains(&event_id) {
-            Ok(false)
-        } else {
-            pinned_event_ids.push(event_id);
-            let content = RoomPinnedEventsEventContent::new(pinned_event_ids);
-            self.room().send_state_event(content).await?;
-            Ok(true)
-        }
+        self.update_pinned_events(event_id, true).await
     }
 
     /// Removes a pinned event by sending an updated `m.room.pinned_events`
     /// event without the event id we want to remove.
     ///
-    /// This method will first try to get the pinned events from the current
-    /// room's state and if it fails to do so it'll try to load them from the
-    /// homeserver.
+    /// The pinned events are loaded from the homeserver, falling back to the
+    /// current room's state if there are none. Once the new list is sent, it's
+    /// loaded again to check that it hasn't been overwritten by a concurrent
+    /// change, in which case the change is applied again to the new list. After
+    /// a few attempts, [`matrix_sdk::Error::PinningConflict`] is returned.
+    ///
+    /// The room's pinned events are updated right away, so the timelines
+    /// focused on the pinned events don't wait for the state event to be
+    /// received via sync.
     ///
     /// Returns `true` if we unpinned the event, `false` if the event wasn't
     /// pinned before.
     pub async fn unpin_event(&self, event_id: &EventId) -> Result<bool> {
-        let mut pinned_event_ids = if let Some(event_ids) = self.room().pinned_event_ids() {
-            event_ids
-        } else {
-            self.room().load_pinned_events().await?.unwrap_or_default()
+        self.update_pinned_events(event_id, false).await
+    }
+
+    /// Pin or unpin the given event, see [`Self::unpin_event()`].
+    async fn update_pinned_events(&self, event_id: &EventId, pin: bool) -> Result<bool> {
+        let room = self.room();
+        let is_done = |pinned_event_ids: &[OwnedEventId]| {
+            pinned_event_ids.iter().any(|id| id == event_id) == pin
         };
-        let event_id = event_id.to_owned();
-        if let Some(idx) = pinned_event_ids.iter().position(|e| *e == *event_id) {
-            pinned_event_ids.remove(idx);
-            let content = RoomPinnedEventsEventContent::new(pinned_event_ids);
-            self.room().send_state_event(content).await?;
-            Ok(true)
-        } else {
-            Ok(false)
+
+        let mut pinned_event_ids = match room.load_pinned_events().await? {
+            Some(event_ids) => event_ids,
+            None => room.pinned_event_ids().unwrap_or_default(),
+        };
+
+        if is_done(&pinned_event_ids) {
+            return Ok(false);
+        }
+
+        for _ in 0..MAX_PINNED_EVENTS_ATTEMPTS {
+            if pin {
+                pinned_event_ids.push(event_id.to_owned());
+            } else {
+                pinned_event_ids.retain(|id| id != event_id);
+            }
+
+            let content = RoomPinnedEventsEventContent::new(pinned_event_ids.clone());
+            room.send_state_event(content).await?;
+
+            let mut room_info = room.clone_info();
+            room_info.set_pinned_event_ids(pinned_event_ids);
+            room.set_room_info(room_info, RoomInfoNotableUpdateReasons::empty());
+
+            // Another client may have sent its own list in the meantime, based on the
+            // previous one.
+            match room.load_pinned_events().await? {
+                Some(event_ids) if !is_done(&event_ids) => {
+                    warn!("the pinned events were changed concurrently, sending them again");
+                    pinned_event_ids = event_ids;
+                }
+                _ => return Ok(true),
+            }
         }
+
+        warn!("giving up on updating the pinned events after too many concurrent changes");
+        Err(matrix_sdk::Error::PinningConflict)
+    }
+
+    /// Store the draft of the composer of this timeline in the state store.
+    ///
+    /// The draft is saved for the room, or for the thread if this timeline is
+    /// focused on one, so that every timeline of the room or the thread can
+    /// restore it, even after a restart.
+    pub async fn save_draft(&self, draft: ComposerDraft) -> Result<()> {
+        let thread_root = self.controller.thread_root();
+        self.room().save_composer_draft(draft, thread_root.as_deref()).await
+    }
+
+    /// Retrieve the draft of the composer of this timeline, as saved with
+    /// [`Self::save_draft`].
+    pub async fn load_draft(&self) -> Result<Option<ComposerDraft>> {
+        let thread_root = self.controller.thread_root();
+        self.room().load_composer_draft(thread_root.as_deref()).await
+    }
+
+    /// Remove the draft of the composer of this timeline from the state store,
+    /// typically once the message has been sent.
+    pub async fn clear_draft(&self) -> Result<()> {
+        let thread_root = self.controller.thread_root();
+        self.room().clear_composer_draft(thread_root.as_deref()).await
     }
 
     /// Create a [`EmbeddedEvent`] from an arbitrary event, be it in the
@@ -898,7 +1203,9 @@ impl Timeline {
 struct TimelineDropHandle {
     room_update_join_handle: JoinHandle<()>,
     pinned_events_join_handle: Option<JoinHandle<()>>,
+    starred_events_join_handle: Option<JoinHandle<()>>,
     thread_update_join_handle: Option<JoinHandle<()>>,
+    url_previews_join_handle: Option<JoinHandle<()>>,
     local_echo_listener_handle: JoinHandle<()>,
     _event_cache_drop_handle: Arc<EventCacheDropHandles>,
     _crypto_drop_handles: CryptoDropHandles,
@@ -910,10 +1217,18 @@ impl Drop for TimelineDropHandle {
             handle.abort();
         }
 
+        if let Some(handle) = self.starred_events_join_handle.take() {
+            handle.abort();
+        }
+
         if let Some(handle) = self.thread_update_join_handle.take() {
             handle.abort();
         }
 
+        if let Some(handle) = self.url_previews_join_handle.take() {
+            handle.abort();
+        }
+
         self.local_echo_listener_handle.abort();
         self.room_update_join_handle.abort();
     }
@@ -925,6 +1240,16 @@ pub type TimelineEventFilterFn =
 #[cfg(target_family = "wasm")]
 pub type TimelineEventFilterFn = dyn Fn(&AnySyncTimelineEvent, &RoomVersionRules) -> bool;
 
+#[cfg(not(target_family = "wasm"))]
+pub type TimelineContentTransformerFn = dyn Fn(&mut TimelineItemContent) + Send + Sync;
+#[cfg(target_family = "wasm")]
+pub type TimelineContentTransformerFn = dyn Fn(&mut TimelineItemContent);
+
+#[cfg(not(target_family = "wasm"))]
+pub type DateDividerBucketFn = dyn Fn(MilliSecondsSinceUnixEpoch) -> u64 + Send + Sync;
+#[cfg(target_family = "wasm")]
+pub type DateDividerBucketFn = dyn Fn(MilliSecondsSinceUnixEpoch) -> u64;
+
 /// A source for sending an attachment.
 ///
 /// The [`AttachmentSource::File`] variant can be constructed from any type that
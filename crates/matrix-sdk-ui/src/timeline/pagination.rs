@@ -12,21 +12,144 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{sync::Arc, time::Duration};
+
 use async_rx::StreamExt as _;
 use async_stream::stream;
+use eyeball::{SharedObservable, Subscriber};
 use futures_core::Stream;
 use futures_util::{StreamExt as _, pin_mut};
-use matrix_sdk::event_cache::{self, EventCacheError, RoomPaginationStatus};
+use matrix_sdk::{
+    event_cache::{self, EventCacheError, RoomPaginationStatus},
+    locks::Mutex,
+    timeout::timeout,
+};
 use tracing::{instrument, warn};
 
-use super::Error;
+use super::{Error, TimelineUniqueId};
+
+/// How long [`Timeline::paginate_backwards_to_read_marker()`] waits for the
+/// timeline to receive the fully-read event, once it's been loaded by the
+/// event cache.
+///
+/// [`Timeline::paginate_backwards_to_read_marker()`]: super::Timeline::paginate_backwards_to_read_marker
+const FULLY_READ_EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The status of the paginations of a timeline, in one direction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum TimelinePaginationStatus {
+    /// No pagination is running, and more events may be loaded.
+    Idle,
+
+    /// A pagination is running.
+    Paginating,
+
+    /// The start of the timeline, when paginating backwards, or its end, when
+    /// paginating forwards, has been reached: there are no more events to
+    /// load in this direction.
+    ReachedEnd,
+
+    /// The last pagination failed. Another one may be attempted.
+    Failed {
+        /// The error which made the pagination fail.
+        error: String,
+    },
+}
+
+impl TimelinePaginationStatus {
+    /// The status after a pagination returned the given result.
+    fn from_result(result: &Result<bool, Error>) -> Self {
+        match result {
+            Ok(true) => Self::ReachedEnd,
+            Ok(false) => Self::Idle,
+            Err(error) => Self::Failed { error: error.to_string() },
+        }
+    }
+}
+
+/// The status of the paginations of a timeline in one direction.
+///
+/// Several paginations may run concurrently, so the running ones are counted:
+/// the status stays [`TimelinePaginationStatus::Paginating`] until all of them
+/// are done.
+#[derive(Clone, Debug)]
+pub(super) struct PaginationStatusTracker {
+    status: SharedObservable<TimelinePaginationStatus>,
+    num_running_paginations: Arc<Mutex<usize>>,
+}
+
+impl PaginationStatusTracker {
+    pub(super) fn new(status: TimelinePaginationStatus) -> Self {
+        Self { status: SharedObservable::new(status), num_running_paginations: Default::default() }
+    }
+
+    /// Set the status, e.g. when the end of the timeline has been reached
+    /// while initializing it.
+    pub(super) fn set(&self, status: TimelinePaginationStatus) {
+        self.status.set(status);
+    }
+
+    pub(super) fn subscribe(&self) -> Subscriber<TimelinePaginationStatus> {
+        self.status.subscribe()
+    }
+
+    /// Mark a pagination as running, until the returned value is finished or
+    /// dropped.
+    fn start(&self) -> RunningPagination<'_> {
+        *self.num_running_paginations.lock() += 1;
+        self.status.set(TimelinePaginationStatus::Paginating);
+
+        RunningPagination { tracker: self, status: None }
+    }
+}
+
+/// A pagination counted by a [`PaginationStatusTracker`].
+struct RunningPagination<'a> {
+    tracker: &'a PaginationStatusTracker,
+
+    /// The status after this pagination, if it's done.
+    status: Option<TimelinePaginationStatus>,
+}
+
+impl RunningPagination<'_> {
+    /// Mark the pagination as done, with the given result.
+    fn finish(mut self, result: &Result<bool, Error>) {
+        self.status = Some(TimelinePaginationStatus::from_result(result));
+    }
+}
+
+impl Drop for RunningPagination<'_> {
+    fn drop(&mut self) {
+        let mut num_running_paginations = self.tracker.num_running_paginations.lock();
+        *num_running_paginations -= 1;
+
+        // The last pagination to finish decides of the status. A pagination which has
+        // been cancelled didn't change anything.
+        if *num_running_paginations == 0 {
+            self.tracker.status.set(self.status.take().unwrap_or(TimelinePaginationStatus::Idle));
+        }
+    }
+}
 
 impl super::Timeline {
     /// Add more events to the start of the timeline.
     ///
     /// Returns whether we hit the start of the timeline.
+    ///
+    /// The progress of the paginations can be observed with
+    /// [`Self::back_pagination_status()`].
     #[instrument(skip_all, fields(room_id = ?self.room().room_id()))]
-    pub async fn paginate_backwards(&self, mut num_events: u16) -> Result<bool, Error> {
+    pub async fn paginate_backwards(&self, num_events: u16) -> Result<bool, Error> {
+        let pagination = self.controller.back_pagination_status().start();
+
+        let result = self.run_paginate_backwards(num_events).await;
+
+        pagination.finish(&result);
+        result
+    }
+
+    async fn run_paginate_backwards(&self, mut num_events: u16) -> Result<bool, Error> {
         if self.controller.is_live() {
             match self.controller.live_lazy_paginate_backwards(num_events).await {
                 Some(needed_num_events) => {
@@ -57,12 +180,103 @@ impl super::Timeline {
     /// Add more events to the end of the timeline.
     ///
     /// Returns whether we hit the end of the timeline.
+    ///
+    /// The progress of the paginations can be observed with
+    /// [`Self::forward_pagination_status()`].
     #[instrument(skip_all, fields(room_id = ?self.room().room_id()))]
     pub async fn paginate_forwards(&self, num_events: u16) -> Result<bool, Error> {
         if self.controller.is_live() {
-            Ok(true)
-        } else {
-            Ok(self.controller.focused_paginate_forwards(num_events).await?)
+            return Ok(true);
+        }
+
+        let pagination = self.controller.forward_pagination_status().start();
+
+        let result =
+            self.controller.focused_paginate_forwards(num_events).await.map_err(Error::from);
+
+        pagination.finish(&result);
+        result
+    }
+
+    /// Subscribe to the status of the back-paginations of this timeline.
+    ///
+    /// Contrary to [`Self::live_back_pagination_status()`], this works in all
+    /// the focus modes, and only reflects the paginations started with
+    /// [`Self::paginate_backwards()`] on this timeline.
+    pub fn back_pagination_status(
+        &self,
+    ) -> (TimelinePaginationStatus, impl Stream<Item = TimelinePaginationStatus> + use<>) {
+        subscribe_to_status(self.controller.back_pagination_status())
+    }
+
+    /// Subscribe to the status of the forward-paginations of this timeline.
+    ///
+    /// A live timeline is always at the end of the room, so its status is
+    /// always [`TimelinePaginationStatus::ReachedEnd`].
+    pub fn forward_pagination_status(
+        &self,
+    ) -> (TimelinePaginationStatus, impl Stream<Item = TimelinePaginationStatus> + use<>) {
+        subscribe_to_status(self.controller.forward_pagination_status())
+    }
+
+    /// Back-paginate until the read marker is in the timeline, to jump to the
+    /// first unread message.
+    ///
+    /// Returns the unique ID of the read marker item, or of the item of the
+    /// fully-read event if the read marker isn't shown because everything
+    /// has been read. Returns `None` if the fully-read event isn't known, or
+    /// if the start of the timeline has been reached without finding it.
+    ///
+    /// The fully-read event is only known if the timeline tracks the read
+    /// marker, see
+    /// [`TimelineBuilder::track_read_marker_and_receipts()`](super::TimelineBuilder::track_read_marker_and_receipts):
+    /// otherwise, this returns `None` without paginating.
+    ///
+    /// Each back-pagination asks for `num_events` events.
+    #[instrument(skip_all, fields(room_id = ?self.room().room_id()))]
+    pub async fn paginate_backwards_to_read_marker(
+        &self,
+        num_events: u16,
+    ) -> Result<Option<TimelineUniqueId>, Error> {
+        let Some(fully_read_event_id) = self.controller.fully_read_event_id().await else {
+            return Ok(None);
+        };
+
+        loop {
+            if let Some(unique_id) = self.controller.find_read_marker().await {
+                return Ok(Some(unique_id));
+            }
+
+            let hit_start = self.paginate_backwards(num_events).await?;
+
+            // In live mode, the timeline receives the paginated events from the event cache
+            // in the background, so wait for the fully-read event if it's been loaded.
+            if self.controller.is_live()
+                && self
+                    .event_cache
+                    .rfind_map_event_in_memory_by(|event| {
+                        (event.event_id().as_deref() == Some(&*fully_read_event_id)).then_some(())
+                    })
+                    .await
+                    .is_some()
+            {
+                if timeout(
+                    self.controller.wait_for_remote_event(&fully_read_event_id),
+                    FULLY_READ_EVENT_TIMEOUT,
+                )
+                .await
+                .is_err()
+                {
+                    warn!("The fully-read event hasn't been received by the timeline");
+                }
+
+                return Ok(self.controller.find_read_marker().await);
+            }
+
+            if hit_start {
+                // The fully-read event might have been in the last batch.
+                return Ok(self.controller.find_read_marker().await);
+            }
         }
     }
 
@@ -143,3 +357,46 @@ impl super::Timeline {
         Some((current_value, stream))
     }
 }
+
+fn subscribe_to_status(
+    status: &PaginationStatusTracker,
+) -> (TimelinePaginationStatus, impl Stream<Item = TimelinePaginationStatus> + use<>) {
+    let mut subscriber = status.subscribe();
+    (subscriber.next_now(), subscriber)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::{Error, PaginationStatusTracker, TimelinePaginationStatus};
+
+    #[test]
+    fn test_concurrent_paginations() {
+        let tracker = PaginationStatusTracker::new(TimelinePaginationStatus::Idle);
+        let subscriber = tracker.subscribe();
+
+        let first = tracker.start();
+        let second = tracker.start();
+        assert_eq!(subscriber.get(), TimelinePaginationStatus::Paginating);
+
+        // The status doesn't change while another pagination is still running.
+        first.finish(&Ok(false));
+        assert_eq!(subscriber.get(), TimelinePaginationStatus::Paginating);
+
+        second.finish(&Ok(true));
+        assert_eq!(subscriber.get(), TimelinePaginationStatus::ReachedEnd);
+
+        // A failure is reported once all the paginations are done.
+        let first = tracker.start();
+        let second = tracker.start();
+        second.finish(&Err(Error::UnsupportedEvent));
+        assert_eq!(subscriber.get(), TimelinePaginationStatus::Paginating);
+        first.finish(&Err(Error::UnsupportedEvent));
+        assert_matches!(subscriber.get(), TimelinePaginationStatus::Failed { .. });
+
+        // A cancelled pagination leaves the paginations idle.
+        drop(tracker.start());
+        assert_eq!(subscriber.get(), TimelinePaginationStatus::Idle);
+    }
+}
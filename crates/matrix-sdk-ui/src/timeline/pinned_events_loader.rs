@@ -87,50 +87,17 @@ impl PinnedEventsLoader {
             return Ok(Some(Vec::new()));
         }
 
-        let request_config = Some(RequestConfig::default().retry_limit(3));
-
-        let mut loaded_events: Vec<TimelineEvent> =
-            stream::iter(pinned_event_ids.clone().into_iter().map(|event_id| {
-                let provider = self.room.clone();
-                let relations_filter =
-                    Some(vec![RelationType::Annotation, RelationType::Replacement]);
-                async move {
-                    match provider
-                        .load_event_with_relations(&event_id, request_config, relations_filter)
-                        .await
-                    {
-                        Ok((event, related_events)) => {
-                            let mut events = vec![event];
-                            events.extend(related_events);
-                            Some(events)
-                        }
-                        Err(err) => {
-                            warn!("error when loading pinned event: {err}");
-                            None
-                        }
-                    }
-                }
-            }))
-            .buffer_unordered(self.max_concurrent_requests)
-            // Get only the `Some<Vec<_>>` results
-            .flat_map(stream::iter)
-            // Flatten the `Vec`s into a single one containing all their items
-            .flat_map(stream::iter)
-            .collect()
-            .await;
+        let loaded_events = load_events_with_relations(
+            &self.room,
+            pinned_event_ids.clone(),
+            self.max_concurrent_requests,
+        )
+        .await;
 
         if loaded_events.is_empty() {
             return Err(PinnedEventsLoaderError::TimelineReloadFailed);
         }
 
-        // Sort using chronological ordering (oldest -> newest)
-        loaded_events.sort_by_key(|item| {
-            item.raw()
-                .deserialize()
-                .map(|e| e.origin_server_ts())
-                .unwrap_or_else(|_| MilliSecondsSinceUnixEpoch::now())
-        });
-
         // We've successfully loaded *some* pinned events, so we can update the list of
         // previously seen pinned events.
         *self.previous_pinned_event_ids.lock().await = pinned_event_ids;
@@ -139,6 +106,59 @@ impl PinnedEventsLoader {
     }
 }
 
+/// Load the events with the given IDs and their related events, using the cache
+/// first and then requesting the events from the homeserver if they couldn't be
+/// found, with at most `max_concurrent_requests` concurrent requests.
+///
+/// The events which couldn't be loaded are skipped, and the others are sorted
+/// in chronological order.
+pub(super) async fn load_events_with_relations<R: PinnedEventsRoom + ?Sized>(
+    room: &Arc<R>,
+    event_ids: Vec<OwnedEventId>,
+    max_concurrent_requests: usize,
+) -> Vec<TimelineEvent> {
+    let request_config = Some(RequestConfig::default().retry_limit(3));
+
+    let mut loaded_events: Vec<TimelineEvent> =
+        stream::iter(event_ids.into_iter().map(|event_id| {
+            let provider = room.clone();
+            let relations_filter = Some(vec![RelationType::Annotation, RelationType::Replacement]);
+            async move {
+                match provider
+                    .load_event_with_relations(&event_id, request_config, relations_filter)
+                    .await
+                {
+                    Ok((event, related_events)) => {
+                        let mut events = vec![event];
+                        events.extend(related_events);
+                        Some(events)
+                    }
+                    Err(err) => {
+                        warn!("error when loading event {event_id}: {err}");
+                        None
+                    }
+                }
+            }
+        }))
+        .buffer_unordered(max_concurrent_requests)
+        // Get only the `Some<Vec<_>>` results
+        .flat_map(stream::iter)
+        // Flatten the `Vec`s into a single one containing all their items
+        .flat_map(stream::iter)
+        .collect()
+        .await;
+
+    // Sort using chronological ordering (oldest -> newest)
+    loaded_events.sort_by_key(|item| {
+        item.raw()
+            .deserialize()
+            .map(|e| e.origin_server_ts())
+            .unwrap_or_else(|_| MilliSecondsSinceUnixEpoch::now())
+    });
+
+    loaded_events
+}
+
 pub trait PinnedEventsRoom: SendOutsideWasm + SyncOutsideWasm {
     /// Load a single room event using the cache or network and any events
     /// related to it, if they are cached.
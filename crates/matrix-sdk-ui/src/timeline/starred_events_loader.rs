@@ -0,0 +1,145 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::Formatter,
+    sync::{Arc, Mutex},
+};
+
+use matrix_sdk::{BoxFuture, Room};
+use matrix_sdk_base::deserialized_responses::TimelineEvent;
+use ruma::{EventId, OwnedEventId};
+use thiserror::Error;
+
+use super::pinned_events_loader::{PinnedEventsRoom, load_events_with_relations};
+
+/// Utility to load the events starred by the user in a room.
+pub struct StarredEventsLoader {
+    /// Backend to load starred events.
+    room: Arc<dyn StarredEventsRoom>,
+
+    /// The list of starred event ids we've loaded previously.
+    ///
+    /// Starts as an empty vector and is updated when the starred events are
+    /// loaded. It's a blocking lock, because it's read when deciding whether
+    /// an event should be added to the timeline.
+    previous_starred_event_ids: Mutex<Vec<OwnedEventId>>,
+
+    /// Maximum number of starred events to load, starting from the most
+    /// recently starred ones.
+    max_events_to_load: usize,
+
+    /// Number of requests to load starred events that can run concurrently.
+    max_concurrent_requests: usize,
+}
+
+impl StarredEventsLoader {
+    /// Creates a new `StarredEventsLoader` instance.
+    pub fn new(
+        room: Arc<dyn StarredEventsRoom>,
+        max_events_to_load: usize,
+        max_concurrent_requests: usize,
+    ) -> Self {
+        Self {
+            room,
+            max_events_to_load,
+            max_concurrent_requests,
+            previous_starred_event_ids: Default::default(),
+        }
+    }
+
+    /// Loads the starred events in this room, like
+    /// [`PinnedEventsLoader::load_events()`](super::pinned_events_loader::PinnedEventsLoader::load_events).
+    ///
+    /// Returns `None` if the list of starred events hasn't changed since the
+    /// previous time we loaded them.
+    pub async fn load_events(
+        &self,
+    ) -> Result<Option<Vec<TimelineEvent>>, StarredEventsLoaderError> {
+        let starred_event_ids: Vec<OwnedEventId> = self
+            .room
+            .starred_event_ids()
+            .await?
+            .into_iter()
+            .rev()
+            .take(self.max_events_to_load)
+            .rev()
+            .collect();
+
+        // Check if the list of starred events has changed since the last time.
+        if starred_event_ids == *self.previous_starred_event_ids.lock().unwrap() {
+            return Ok(None);
+        }
+
+        if starred_event_ids.is_empty() {
+            self.previous_starred_event_ids.lock().unwrap().clear();
+            return Ok(Some(Vec::new()));
+        }
+
+        let loaded_events = load_events_with_relations(
+            &self.room,
+            starred_event_ids.clone(),
+            self.max_concurrent_requests,
+        )
+        .await;
+
+        if loaded_events.is_empty() {
+            return Err(StarredEventsLoaderError::TimelineReloadFailed);
+        }
+
+        *self.previous_starred_event_ids.lock().unwrap() = starred_event_ids;
+
+        Ok(Some(loaded_events))
+    }
+
+    /// Whether the given event is one of the starred events which have been
+    /// loaded.
+    pub fn is_starred_event(&self, event_id: &EventId) -> bool {
+        self.previous_starred_event_ids.lock().unwrap().iter().any(|id| id == event_id)
+    }
+}
+
+pub trait StarredEventsRoom: PinnedEventsRoom {
+    /// Get the IDs of the events starred by the user in the room, from the
+    /// first one to be starred to the most recent one.
+    fn starred_event_ids(&self) -> BoxFuture<'_, Result<Vec<OwnedEventId>, matrix_sdk::Error>>;
+}
+
+impl StarredEventsRoom for Room {
+    fn starred_event_ids(&self) -> BoxFuture<'_, Result<Vec<OwnedEventId>, matrix_sdk::Error>> {
+        Box::pin(Room::starred_event_ids(self))
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+impl std::fmt::Debug for StarredEventsLoader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StarredEventsLoader")
+            .field("max_events_to_load", &self.max_events_to_load)
+            .finish()
+    }
+}
+
+/// Errors related to `StarredEventsLoader` usage.
+#[derive(Error, Debug)]
+pub enum StarredEventsLoaderError {
+    #[error("Timeline focus is not starred events.")]
+    TimelineFocusNotStarredEvents,
+
+    #[error("Could not load the starred events: {0}")]
+    StarredEventIds(#[from] matrix_sdk::Error),
+
+    #[error("Could not load starred events.")]
+    TimelineReloadFailed,
+}
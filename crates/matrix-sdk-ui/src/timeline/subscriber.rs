@@ -16,13 +16,21 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
+use async_stream::stream;
 use eyeball::Subscriber;
 use eyeball_im::{VectorDiff, VectorSubscriberBatchedStream};
 use eyeball_im_util::vector::{Skip, VectorObserverExt};
 use futures_core::Stream;
+use futures_util::{
+    StreamExt,
+    future::{Either, select},
+    pin_mut,
+};
 use imbl::Vector;
+use matrix_sdk::sleep::sleep;
 use pin_project_lite::pin_project;
 
 use super::{TimelineDropHandle, controller::ObservableItems, item::TimelineItem};
@@ -106,6 +114,96 @@ impl Stream for TimelineSubscriber {
     }
 }
 
+/// Coalesce the batches of diffs of the given stream over the given time
+/// window, if any.
+///
+/// When a batch is received, the batches received during the following
+/// `window` are appended to it, and the result is compacted with
+/// [`compact_diffs`] before being emitted. Without a window, the batches are
+/// emitted as they are, and the given stream is returned untouched.
+///
+/// The returned stream is [`Unpin`] as long as the given stream is.
+pub(super) fn coalesce_diffs<S, T>(
+    stream: S,
+    window: Option<Duration>,
+) -> impl Stream<Item = Vec<VectorDiff<T>>>
+where
+    S: Stream<Item = Vec<VectorDiff<T>>>,
+{
+    let Some(window) = window else {
+        return Either::Left(stream);
+    };
+
+    Either::Right(Box::pin(stream! {
+        let stream = stream.fuse();
+        pin_mut!(stream);
+
+        while let Some(mut diffs) = stream.next().await {
+            let deadline = sleep(window);
+            pin_mut!(deadline);
+
+            loop {
+                match select(stream.next(), deadline.as_mut()).await {
+                    Either::Left((Some(next_diffs), _)) => diffs.extend(next_diffs),
+                    Either::Left((None, _)) | Either::Right(_) => break,
+                }
+            }
+
+            let diffs = compact_diffs(diffs);
+
+            if !diffs.is_empty() {
+                yield diffs;
+            }
+        }
+    }))
+}
+
+/// Remove the diffs which are made useless by the ones following them.
+///
+/// The result has the same effect as the given diffs when applied to a
+/// vector, with fewer diffs:
+///
+/// - the diffs before a `Clear` or a `Reset` are dropped,
+/// - a value set or inserted and then set again is only set or inserted once,
+///   with its latest value,
+/// - a value set and then removed is only removed,
+/// - a value inserted and then removed is neither inserted nor removed.
+fn compact_diffs<T>(diffs: Vec<VectorDiff<T>>) -> Vec<VectorDiff<T>> {
+    let start = diffs
+        .iter()
+        .rposition(|diff| matches!(diff, VectorDiff::Clear | VectorDiff::Reset { .. }))
+        .unwrap_or(0);
+
+    let mut compacted = Vec::with_capacity(diffs.len() - start);
+
+    for diff in diffs.into_iter().skip(start) {
+        match diff {
+            VectorDiff::Set { index, value } => match compacted.last_mut() {
+                Some(
+                    VectorDiff::Set { index: last_index, value: last_value }
+                    | VectorDiff::Insert { index: last_index, value: last_value },
+                ) if *last_index == index => *last_value = value,
+                _ => compacted.push(VectorDiff::Set { index, value }),
+            },
+
+            VectorDiff::Remove { index } => match compacted.last() {
+                Some(VectorDiff::Insert { index: last_index, .. }) if *last_index == index => {
+                    compacted.pop();
+                }
+                Some(VectorDiff::Set { index: last_index, .. }) if *last_index == index => {
+                    compacted.pop();
+                    compacted.push(VectorDiff::Remove { index });
+                }
+                _ => compacted.push(VectorDiff::Remove { index }),
+            },
+
+            diff => compacted.push(diff),
+        }
+    }
+
+    compacted
+}
+
 pub mod skip {
     use eyeball::{SharedObservable, Subscriber};
 
@@ -455,3 +553,94 @@ pub mod skip {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+    use eyeball_im::VectorDiff;
+    use futures_util::{StreamExt, stream};
+    use imbl::vector;
+    use matrix_sdk_test::async_test;
+
+    use super::{coalesce_diffs, compact_diffs};
+
+    #[test]
+    fn test_compact_diffs() {
+        // The diffs before a reset are dropped.
+        let diffs = vec![
+            VectorDiff::PushBack { value: 'a' },
+            VectorDiff::Set { index: 0, value: 'b' },
+            VectorDiff::Reset { values: vector!['c'] },
+            VectorDiff::PushBack { value: 'd' },
+        ];
+        assert_eq!(
+            compact_diffs(diffs),
+            vec![VectorDiff::Reset { values: vector!['c'] }, VectorDiff::PushBack { value: 'd' }]
+        );
+
+        // A value set or inserted several times is only set or inserted once.
+        let diffs = vec![
+            VectorDiff::Set { index: 1, value: 'a' },
+            VectorDiff::Set { index: 1, value: 'b' },
+            VectorDiff::Insert { index: 2, value: 'c' },
+            VectorDiff::Set { index: 2, value: 'd' },
+            VectorDiff::Set { index: 3, value: 'e' },
+        ];
+        assert_eq!(
+            compact_diffs(diffs),
+            vec![
+                VectorDiff::Set { index: 1, value: 'b' },
+                VectorDiff::Insert { index: 2, value: 'd' },
+                VectorDiff::Set { index: 3, value: 'e' },
+            ]
+        );
+
+        // A value set and then removed is only removed, and a value inserted and
+        // then removed is never there.
+        let diffs = vec![
+            VectorDiff::Set { index: 0, value: 'a' },
+            VectorDiff::Remove { index: 0 },
+            VectorDiff::Insert { index: 1, value: 'b' },
+            VectorDiff::Remove { index: 1 },
+            VectorDiff::Remove { index: 2 },
+        ];
+        assert_eq!(
+            compact_diffs(diffs),
+            vec![VectorDiff::Remove { index: 0 }, VectorDiff::Remove { index: 2 }]
+        );
+    }
+
+    #[async_test]
+    async fn test_coalesce_diffs() {
+        let batches = || {
+            stream::iter(vec![
+                vec![VectorDiff::PushBack { value: 'a' }],
+                vec![VectorDiff::Set { index: 0, value: 'b' }],
+                vec![VectorDiff::PushBack { value: 'c' }],
+            ])
+        };
+
+        // Without a window, the batches are emitted as they are.
+        let coalesced = coalesce_diffs(batches(), None).collect::<Vec<_>>().await;
+        assert_eq!(coalesced.len(), 3);
+
+        // With a window, they are merged into a single batch.
+        let coalesced =
+            coalesce_diffs(batches(), Some(Duration::from_millis(50))).collect::<Vec<_>>().await;
+        assert_eq!(
+            coalesced,
+            vec![vec![
+                VectorDiff::PushBack { value: 'a' },
+                VectorDiff::Set { index: 0, value: 'b' },
+                VectorDiff::PushBack { value: 'c' },
+            ]]
+        );
+
+        // The coalesced stream can be polled without being pinned first.
+        let mut coalesced = coalesce_diffs(batches(), Some(Duration::from_millis(50)));
+        assert_matches!(coalesced.next().await, Some(diffs) => assert_eq!(diffs.len(), 3));
+        assert!(coalesced.next().await.is_none());
+    }
+}
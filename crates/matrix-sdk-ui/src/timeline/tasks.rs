@@ -14,10 +14,12 @@
 
 //! Long-lived tasks for the timeline.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
+use eyeball_im::VectorDiff;
 use futures_core::Stream;
 use futures_util::pin_mut;
+use imbl::Vector;
 use matrix_sdk::{
     event_cache::{
         EventsOrigin, RoomEventCache, RoomEventCacheSubscriber, RoomEventCacheUpdate,
@@ -32,6 +34,9 @@ use tracing::{instrument, trace, warn};
 
 use crate::timeline::{TimelineController, TimelineFocus, event_item::RemoteEventOrigin};
 
+/// The maximum number of links of a message whose previews are fetched.
+const MAX_URL_PREVIEWS_PER_MESSAGE: usize = 3;
+
 /// Long-lived task, in the pinned events focus mode, that updates the timeline
 /// after any changes in the pinned events.
 #[instrument(
@@ -74,6 +79,48 @@ pub(in crate::timeline) async fn pinned_events_task<S>(
     }
 }
 
+/// Long-lived task, in the starred events focus mode, that updates the
+/// timeline after any changes in the starred events.
+#[instrument(
+    skip_all,
+    fields(
+        room_id = %timeline_controller.room().room_id(),
+    )
+)]
+pub(in crate::timeline) async fn starred_events_task<S>(
+    starred_event_ids_stream: S,
+    timeline_controller: TimelineController,
+) where
+    S: Stream<Item = Vec<OwnedEventId>>,
+{
+    pin_mut!(starred_event_ids_stream);
+
+    while starred_event_ids_stream.next().await.is_some() {
+        trace!("received a starred events update");
+
+        match timeline_controller.reload_starred_events().await {
+            Ok(Some(events)) => {
+                trace!("successfully reloaded starred events");
+                timeline_controller
+                    .replace_with_initial_remote_events(
+                        events.into_iter(),
+                        RemoteEventOrigin::Pagination,
+                    )
+                    .await;
+            }
+
+            Ok(None) => {
+                // The list of starred events hasn't changed since the previous
+                // time.
+            }
+
+            Err(err) => {
+                warn!("Failed to reload starred events: {err}");
+            }
+        }
+    }
+}
+
 /// For a thread-focused timeline, a long-lived task that will listen to the
 /// underlying thread updates.
 pub(in crate::timeline) async fn thread_updates_task(
@@ -181,7 +228,14 @@ pub(in crate::timeline) async fn room_event_cache_updates_task(
                 let has_diffs = !diffs.is_empty();
 
                 if matches!(timeline_focus, TimelineFocus::Live { .. }) {
+                    let has_been_reset = matches!(origin, RemoteEventOrigin::Sync)
+                        && diffs.iter().any(|diff| matches!(diff, VectorDiff::Clear));
+
                     timeline_controller.handle_remote_events_with_diffs(diffs, origin).await;
+
+                    if has_been_reset {
+                        timeline_controller.reaggregate_after_reset(&room_event_cache).await;
+                    }
                 } else {
                     // Only handle the remote aggregation for a non-live timeline.
                     timeline_controller.handle_remote_aggregations(diffs, origin).await;
@@ -237,3 +291,112 @@ pub(in crate::timeline) async fn room_send_queue_update_task(
         }
     }
 }
+
+/// Long-lived task that fetches the previews of the links of the messages in
+/// the timeline, and adds them to their items.
+#[instrument(
+    skip_all,
+    fields(
+        room_id = %timeline_controller.room().room_id(),
+    )
+)]
+pub(in crate::timeline) async fn url_previews_task(timeline_controller: TimelineController) {
+    // Only observe the remote messages with links that haven't been previewed yet.
+    let (initial_items, stream) = timeline_controller
+        .subscribe_filter_map(|item| {
+            let event = item.as_event().filter(|event| event.is_remote_event())?;
+            let event_id = event.event_id()?.to_owned();
+
+            let links = event
+                .content()
+                .as_message()?
+                .links()
+                .into_iter()
+                .take(MAX_URL_PREVIEWS_PER_MESSAGE)
+                .filter(|link| !event.url_previews().iter().any(|preview| preview.url == *link))
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+
+            (!links.is_empty()).then_some((event_id, links))
+        })
+        .await;
+
+    pin_mut!(stream);
+
+    let room = timeline_controller.room().clone();
+
+    // The event IDs of the observed items, in the same order, so that the links of
+    // the items which aren't observed anymore can be forgotten.
+    let mut observed_event_ids =
+        initial_items.iter().map(|(event_id, _)| event_id.clone()).collect::<Vector<_>>();
+    let mut pending = initial_items.into_iter().collect::<Vec<_>>();
+
+    // The links of the observed items that have already been looked at, so that
+    // they're not fetched again when there's nothing to preview, or the preview
+    // failed. They're forgotten once their item isn't observed anymore, or the
+    // timeline is reset, so the failed previews can be tried again later.
+    let mut attempted_links = HashMap::<OwnedEventId, HashSet<String>>::new();
+
+    loop {
+        for (event_id, links) in pending.drain(..) {
+            // The user may change their mind at any time, so check this every time.
+            match room.url_previews_enabled().await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(err) => {
+                    warn!("Failed to check whether URL previews are enabled: {err}");
+                    continue;
+                }
+            }
+
+            let mut previews = Vec::new();
+
+            for link in links {
+                if !attempted_links.entry(event_id.clone()).or_default().insert(link.clone()) {
+                    continue;
+                }
+
+                match room.client().media().get_url_preview(&link).await {
+                    Ok(preview) => {
+                        if !preview.is_empty() {
+                            previews.push(preview);
+                        }
+                    }
+                    Err(err) => warn!(%event_id, "Failed to fetch the preview of a link: {err}"),
+                }
+            }
+
+            if !previews.is_empty() {
+                timeline_controller.add_url_previews(&event_id, previews).await;
+            }
+        }
+
+        let Some(diff) = stream.next().await else {
+            break;
+        };
+
+        diff.clone().map(|(event_id, _)| event_id).apply(&mut observed_event_ids);
+
+        match diff {
+            VectorDiff::Append { values } => pending.extend(values),
+            VectorDiff::PushFront { value }
+            | VectorDiff::PushBack { value }
+            | VectorDiff::Insert { value, .. }
+            | VectorDiff::Set { value, .. } => pending.push(value),
+            VectorDiff::Reset { values } => {
+                attempted_links.clear();
+                pending.extend(values);
+            }
+            VectorDiff::Clear => attempted_links.clear(),
+            VectorDiff::PopFront
+            | VectorDiff::PopBack
+            | VectorDiff::Remove { .. }
+            | VectorDiff::Truncate { .. } => {
+                let observed = observed_event_ids.iter().collect::<HashSet<_>>();
+                attempted_links.retain(|event_id, _| observed.contains(event_id));
+            }
+        }
+    }
+
+    trace!("URL previews task finished.");
+}
@@ -12,11 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{sync::Arc, time::Duration};
+
 use assert_matches::assert_matches;
 use assert_matches2::assert_let;
 use eyeball_im::VectorDiff;
 use futures_util::StreamExt;
-use imbl::vector;
+use imbl::{Vector, vector};
 use matrix_sdk_test::{
     ALICE, BOB, CAROL, async_test,
     event_factory::{EventFactory, PreviousMembership},
@@ -29,18 +31,18 @@ use ruma::{
         room::{
             ImageInfo,
             member::{MembershipState, RedactedRoomMemberEventContent},
-            message::MessageType,
+            message::{MessageType, RoomMessageEventContent},
             topic::RedactedRoomTopicEventContent,
         },
     },
     mxc_uri, owned_event_id, owned_mxc_uri, user_id,
 };
-use stream_assert::assert_next_matches;
+use stream_assert::{assert_next_matches, assert_pending};
 
 use super::TestTimeline;
 use crate::timeline::{
-    MembershipChange, MsgLikeContent, MsgLikeKind, TimelineDetails, TimelineItemContent,
-    TimelineItemKind, VirtualTimelineItem,
+    MembershipChange, MessageEffects, MsgLikeContent, MsgLikeKind, TimelineContentTransformerFn,
+    TimelineDetails, TimelineItem, TimelineItemContent, TimelineItemKind, VirtualTimelineItem,
     controller::TimelineSettings,
     event_item::{AnyOtherFullStateEventContent, RemoteEventOrigin},
     tests::{ReadReceiptMap, TestRoomDataProvider, TestTimelineBuilder},
@@ -506,3 +508,248 @@ async fn test_replace_with_initial_events_when_batched() {
         assert_matches!(value.as_virtual(), Some(VirtualTimelineItem::DateDivider(_)));
     });
 }
+
+#[async_test]
+async fn test_group_consecutive_messages() {
+    let timeline = TestTimelineBuilder::new()
+        .settings(TimelineSettings {
+            grouping_time_window: Some(Duration::from_secs(5 * 60)),
+            ..Default::default()
+        })
+        .build();
+
+    let f = &timeline.factory;
+    timeline.handle_live_event(f.text_msg("A").sender(*ALICE).server_ts(0)).await;
+    timeline.handle_live_event(f.text_msg("B").sender(*ALICE).server_ts(1_000)).await;
+    timeline.handle_live_event(f.text_msg("C").sender(*BOB).server_ts(2_000)).await;
+    // More than 5 minutes after the previous message.
+    timeline.handle_live_event(f.text_msg("D").sender(*BOB).server_ts(10 * 60 * 1_000)).await;
+
+    let positions = |items: &Vector<Arc<TimelineItem>>| {
+        items
+            .iter()
+            .filter_map(|item| item.as_event())
+            .map(|event| (event.is_first_of_group(), event.is_last_of_group()))
+            .collect::<Vec<_>>()
+    };
+
+    let items = timeline.controller.items().await;
+    assert_eq!(positions(&items), [(true, false), (false, true), (true, true), (true, true)]);
+
+    // A new message from Bob joins the group of the previous one.
+    timeline.handle_live_event(f.text_msg("E").sender(*BOB).server_ts(10 * 60 * 1_000 + 1)).await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(
+        positions(&items),
+        [(true, false), (false, true), (true, true), (true, false), (false, true)]
+    );
+
+    // A state event ends the group.
+    timeline
+        .handle_live_event(f.room_name("Tea time").sender(*BOB).server_ts(10 * 60 * 1_000 + 2))
+        .await;
+    timeline.handle_live_event(f.text_msg("F").sender(*BOB).server_ts(10 * 60 * 1_000 + 3)).await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(positions(&items)[4..], [(false, true), (true, true), (true, true)]);
+}
+
+#[async_test]
+async fn test_content_transformers() {
+    let shout: Arc<TimelineContentTransformerFn> = Arc::new(|content: &mut TimelineItemContent| {
+        if let TimelineItemContent::MsgLike(MsgLikeContent {
+            kind: MsgLikeKind::Message(message),
+            ..
+        }) = content
+            && let MessageType::Text(text) = message.msgtype_mut()
+        {
+            text.body = text.body.to_uppercase();
+        }
+    });
+
+    let timeline = TestTimelineBuilder::new()
+        .settings(TimelineSettings { content_transformers: vec![shout], ..Default::default() })
+        .build();
+
+    let body = |items: &Vector<Arc<TimelineItem>>| {
+        items[1].as_event().unwrap().content().as_message().unwrap().body().to_owned()
+    };
+
+    let f = &timeline.factory;
+    let event_id = event_id!("$original");
+    timeline.handle_live_event(f.text_msg("hello").sender(*ALICE).event_id(event_id)).await;
+
+    // The content of a new item is transformed.
+    assert_eq!(body(&timeline.controller.items().await), "HELLO");
+
+    let mut stream = timeline.subscribe().await;
+
+    // The content of an edited item is transformed too, with a single update.
+    timeline
+        .handle_live_event(
+            f.text_msg(" * hello there")
+                .sender(*ALICE)
+                .edit(event_id, MessageType::text_plain("hello there").into()),
+        )
+        .await;
+
+    assert_eq!(body(&timeline.controller.items().await), "HELLO THERE");
+    assert_next_matches!(stream, VectorDiff::Set { index: 1, .. });
+    assert_pending!(stream);
+}
+
+#[async_test]
+async fn test_message_effects() {
+    let timeline = TestTimelineBuilder::new()
+        .settings(TimelineSettings {
+            message_effects: Some(Arc::new(MessageEffects::default())),
+            ..Default::default()
+        })
+        .build();
+
+    let f = &timeline.factory;
+    let confetti = |body: &str| {
+        MessageType::new("nic.custom.confetti", body.to_owned(), Default::default()).unwrap()
+    };
+
+    let event_id = event_id!("$confetti");
+    timeline
+        .handle_live_event(
+            f.event(RoomMessageEventContent::new(confetti("Congrats!")))
+                .sender(*ALICE)
+                .event_id(event_id),
+        )
+        .await;
+    timeline.handle_live_event(f.text_msg("Thanks!").sender(*BOB)).await;
+
+    let items = timeline.controller.items().await;
+    let message = items[1].as_event().unwrap().content().as_message().unwrap();
+    assert_eq!(message.effect(), Some("confetti"));
+    assert_eq!(message.body(), "Congrats!");
+
+    // Other messages don't have an effect.
+    assert_eq!(items[2].as_event().unwrap().content().as_message().unwrap().effect(), None);
+
+    // An edited effect is still an effect.
+    timeline
+        .handle_live_event(
+            f.text_msg(" * Congrats again!")
+                .sender(*ALICE)
+                .edit(event_id, confetti("Congrats again!").into()),
+        )
+        .await;
+
+    let items = timeline.controller.items().await;
+    let message = items[1].as_event().unwrap().content().as_message().unwrap();
+    assert_eq!(message.effect(), Some("confetti"));
+    assert_eq!(message.body(), "Congrats again!");
+    assert!(message.is_edited());
+
+    // An effect edited into a regular message loses its effect.
+    timeline
+        .handle_live_event(
+            f.text_msg(" * Congrats")
+                .sender(*ALICE)
+                .edit(event_id, MessageType::text_plain("Congrats").into()),
+        )
+        .await;
+
+    let items = timeline.controller.items().await;
+    let message = items[1].as_event().unwrap().content().as_message().unwrap();
+    assert_eq!(message.effect(), None);
+    assert_eq!(message.body(), "Congrats");
+}
+
+#[async_test]
+async fn test_collapse_membership_changes() {
+    let timeline = TestTimelineBuilder::new()
+        .settings(TimelineSettings { collapse_membership_changes: true, ..Default::default() })
+        .build();
+
+    let f = &timeline.factory;
+    timeline
+        .handle_event_update(
+            vec![VectorDiff::Append {
+                values: vector![
+                    f.text_msg("hello").sender(*ALICE).into_event(),
+                    f.member(&BOB).membership(MembershipState::Join).into_event(),
+                    f.member(&CAROL).membership(MembershipState::Join).into_event(),
+                    f.member(&BOB)
+                        .membership(MembershipState::Leave)
+                        .previous(MembershipState::Join)
+                        .into_event(),
+                    f.member(&BOB)
+                        .membership(MembershipState::Join)
+                        .previous(MembershipState::Leave)
+                        .into_event(),
+                    f.member(&ALICE)
+                        .membership(MembershipState::Join)
+                        .display_name("Alice")
+                        .previous(PreviousMembership::new(MembershipState::Join))
+                        .into_event(),
+                ],
+            }],
+            RemoteEventOrigin::Sync,
+        )
+        .await;
+
+    // The membership and profile changes received together are collapsed.
+    let items = timeline.controller.items().await;
+    assert_eq!(items.len(), 3);
+    assert!(items[0].is_date_divider());
+    assert!(items[1].as_event().unwrap().content().is_message());
+    assert_let!(Some(VirtualTimelineItem::MembershipSummary(summary)) = items[2].as_virtual());
+    assert_eq!(summary.items().len(), 5);
+    // Bob joined twice, but is only counted once.
+    assert_eq!(summary.joined_count(), 2);
+    assert_eq!(summary.left_count(), 1);
+    assert_eq!(summary.profile_changes_count(), 1);
+
+    // A single membership change isn't collapsed.
+    timeline
+        .handle_live_event(f.member(user_id!("@dan:example.org")).membership(MembershipState::Join))
+        .await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(items.len(), 4);
+    assert_let!(TimelineItemContent::MembershipChange(_) = items[3].as_event().unwrap().content());
+
+    // Expanding the summary shows its items again, in place.
+    assert!(timeline.controller.expand_membership_summary(items[2].unique_id()).await);
+    assert!(!timeline.controller.expand_membership_summary(items[2].unique_id()).await);
+
+    let items = timeline.controller.items().await;
+    assert_eq!(items.len(), 8);
+    assert!(items[0].is_date_divider());
+    assert_eq!(items[2].as_event().unwrap().sender(), *BOB);
+    assert_eq!(items[6].as_event().unwrap().sender(), *ALICE);
+    assert_eq!(items[7].as_event().unwrap().sender(), user_id!("@dan:example.org"));
+}
+
+#[async_test]
+async fn test_message_links() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let f = &timeline.factory;
+
+    timeline
+        .handle_live_event(
+            f.text_msg(
+                "See https://matrix.org, (https://example.org/a?b=c) and https://matrix.org again!",
+            )
+            .sender(&BOB),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    let message = item.content().as_message().unwrap();
+    assert_eq!(message.links(), ["https://matrix.org", "https://example.org/a?b=c"]);
+    assert!(item.url_previews().is_empty());
+
+    // Only the links with an HTTP scheme are kept.
+    timeline.handle_live_event(f.text_msg("mxc://matrix.org/abc or ftp://x.y").sender(&BOB)).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(item.content().as_message().unwrap().links().is_empty());
+}
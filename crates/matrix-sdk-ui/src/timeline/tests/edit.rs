@@ -167,6 +167,7 @@ async fn test_edit_updates_encryption_info() {
             curve25519_key: "123".to_owned(),
             sender_claimed_keys: BTreeMap::new(),
             session_id: Some("mysessionid6333".to_owned()),
+            forwarding_curve25519_key_chain: Vec::new(),
         },
         verification_state: VerificationState::Verified,
     });
@@ -176,6 +177,7 @@ async fn test_edit_updates_encryption_info() {
             event: original_event,
             encryption_info: encryption_info.clone(),
             unsigned_encryption_info: None,
+            encrypted_event: None,
         },
         None,
     );
@@ -207,6 +209,7 @@ async fn test_edit_updates_encryption_info() {
             event: edit_event,
             encryption_info: encryption_info.clone(),
             unsigned_encryption_info: None,
+            encrypted_event: None,
         },
         None,
     );
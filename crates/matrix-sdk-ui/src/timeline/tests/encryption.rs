@@ -36,7 +36,7 @@ use matrix_sdk::{
 use matrix_sdk_base::deserialized_responses::{TimelineEvent, UnableToDecryptReason};
 use matrix_sdk_test::{ALICE, BOB, async_test};
 use ruma::{
-    assign, event_id,
+    EventEncryptionAlgorithm, assign, event_id,
     events::room::encrypted::{
         EncryptedEventScheme, MegolmV1AesSha2ContentInit, Relation, Replacement,
         RoomEncryptedEventContent,
@@ -52,7 +52,8 @@ use tokio::time::sleep;
 use super::TestTimeline;
 use crate::{
     timeline::{
-        EncryptedMessage, MsgLikeContent, MsgLikeKind, TimelineDetails, TimelineItemContent,
+        DecryptionRetryReport, EncryptedMessage, MsgLikeContent, MsgLikeKind, TimelineDetails,
+        TimelineItemContent,
         tests::{TestDecryptor, TestRoomDataProvider, TestTimelineBuilder},
     },
     unable_to_decrypt_hook::{UnableToDecryptHook, UnableToDecryptInfo, UtdHookManager},
@@ -177,6 +178,22 @@ async fn test_retry_message_decryption() {
     assert_eq!(message.body(), "It's a secret to everybody");
     assert!(!event.is_highlighted());
 
+    // The source of the item keeps the encrypted event, next to the decrypted
+    // payload.
+    let source = event.source().unwrap();
+    let encrypted_json = source.encrypted_json().unwrap();
+    assert_eq!(
+        encrypted_json.get_field::<String>("type").unwrap().as_deref(),
+        Some("m.room.encrypted")
+    );
+    assert_eq!(
+        source.json().get_field::<String>("type").unwrap().as_deref(),
+        Some("m.room.message")
+    );
+    assert_eq!(source.algorithm(), Some(EventEncryptionAlgorithm::MegolmV1AesSha2));
+    assert_eq!(source.session_id(), Some(SESSION_ID));
+    assert!(source.sender_key().is_some());
+
     // The message should not be re-reported as a late decryption.
     {
         let utds = hook.utds.lock().unwrap();
@@ -188,6 +205,81 @@ async fn test_retry_message_decryption() {
     }
 }
 
+#[async_test]
+async fn test_retry_decryption_for_all_utds() {
+    const SESSION_ID: &str = "gM8i47Xhu0q52xLfgUXzanCMpLinoyVyH7R58cBuVBU";
+    const SESSION_KEY: &[u8] = b"\
+        -----BEGIN MEGOLM SESSION DATA-----\n\
+        ASKcWoiAVUM97482UAi83Avce62hSLce7i5JhsqoF6xeAAAACqt2Cg3nyJPRWTTMXxXH7TXnkfdlmBXbQtq5\
+        bpHo3LRijcq2Gc6TXilESCmJN14pIsfKRJrWjZ0squ/XsoTFytuVLWwkNaW3QF6obeg2IoVtJXLMPdw3b2vO\
+        vgwGY3OMP0XafH13j1vcb6YLzvgLkZQLnYvd47hv3yK/9GmKS9tokuaQ7dCVYckYcIOS09EDTs70YdxUd5WG\
+        rQynATCLFP1p/NAGv70r9MK7Cy/mNpjD0r4qC7UEDIoi1kOWzHgnLo19wtvwsb8Fg8ATxcs3Wmtj8hIUYpDx\
+        ia4sM10zbytUuaPUAfCDf42IyxdmOnGe1CueXhgI71y+RW0s0argNqUt7jB70JT0o9CyX6UBGRaqLk2MPY9T\
+        hUu5J8X3UgIa6rcbWigzohzWm9rdbEHFrSWqjpfQYMaAKQQgETrjSy4XTrp2RhC2oNqG/hylI4ab+F4X6fpH\
+        DYP1NqNMP5g36xNu7LhDnrUB5qsPjYOmWORxGLfudpF3oLYCSlr3DgHqEIB6HjQblLZ3KQuPBse3zxyROTnS\
+        AhdPH4a/z1wioFtKNVph3hecsiKEdqnz4Y2coSIdhz58mJ9JWNQoFAENE5CSsoEZAGvafYZVpW4C75YY2zq1\
+        wIeiFi1dT43/jLAUGkslsi1VvnyfUu8qO404RxYO3XHoGLMFoFLOO+lZ+VGci2Vz10AhxJhEBHxRKxw4k2uB\
+        HztoSJUr/2Y\n\
+        -----END MEGOLM SESSION DATA-----";
+
+    let own_user_id = user_id!("@example:morheus.localhost");
+    let olm_machine = OlmMachine::new(own_user_id, "SomeDeviceId".into()).await;
+
+    let timeline = TestTimelineBuilder::new()
+        .provider(TestRoomDataProvider::default().with_decryptor(TestDecryptor::new(
+            room_id!("!DovneieKSTkdHKpIXy:morpheus.localhost"),
+            &olm_machine,
+        )))
+        .build();
+
+    let f = &timeline.factory;
+    timeline
+        .handle_live_event(
+            f.event(RoomEncryptedEventContent::new(
+                EncryptedEventScheme::MegolmV1AesSha2(
+                    MegolmV1AesSha2ContentInit {
+                        ciphertext: "\
+                            AwgAEtABPRMavuZMDJrPo6pGQP4qVmpcuapuXtzKXJyi3YpEsjSWdzuRKIgJzD4P\
+                            cSqJM1A8kzxecTQNJsC5q22+KSFEPxPnI4ltpm7GFowSoPSW9+bFdnlfUzEP1jPq\
+                            YevHAsMJp2fRKkzQQbPordrUk1gNqEpGl4BYFeRqKl9GPdKFwy45huvQCLNNueql\
+                            CFZVoYMuhxrfyMiJJAVNTofkr2um2mKjDTlajHtr39pTG8k0eOjSXkLOSdZvNOMz\
+                            hGhSaFNeERSA2G2YbeknOvU7MvjiO0AKuxaAe1CaVhAI14FCgzrJ8g0y5nly+n7x\
+                            QzL2G2Dn8EoXM5Iqj8W99iokQoVsSrUEnaQ1WnSIfewvDDt4LCaD/w7PGETMCQ"
+                            .to_owned(),
+                        sender_key: "DeHIg4gwhClxzFYcmNntPNF9YtsdZbmMy8+3kzCMXHA".to_owned(),
+                        device_id: "NLAZCWIOCO".into(),
+                        session_id: SESSION_ID.into(),
+                    }
+                    .into(),
+                ),
+                None,
+            ))
+            .sender(&BOB)
+            .into_utd_sync_timeline_event(),
+        )
+        .await;
+
+    // Without the key, the UTD is retried but can't be decrypted.
+    let report = timeline.controller.retry_decryption_for_all_utds().await;
+    assert_eq!(report, DecryptionRetryReport { num_retried: 1, num_decrypted: 0 });
+
+    let exported_keys = decrypt_room_key_export(Cursor::new(SESSION_KEY), "1234").unwrap();
+    olm_machine.store().import_exported_room_keys(exported_keys, |_, _| {}).await.unwrap();
+
+    // Once the key has been imported, it's decrypted, and the report reflects it
+    // as soon as the retry is done.
+    let report = timeline.controller.retry_decryption_for_all_utds().await;
+    assert_eq!(report, DecryptionRetryReport { num_retried: 1, num_decrypted: 1 });
+
+    let items = timeline.controller.items().await;
+    assert_let!(Some(message) = items[1].as_event().unwrap().content().as_message());
+    assert_eq!(message.body(), "It's a secret to everybody");
+
+    // There's nothing left to retry.
+    let report = timeline.controller.retry_decryption_for_all_utds().await;
+    assert_eq!(report, DecryptionRetryReport::default());
+}
+
 // There has been a regression when the `retry_event_decryption` function
 // changed from failing with an Error to instead return a new type of timeline
 // event in UTD. The regression caused the timeline to consider any
@@ -653,6 +745,7 @@ async fn test_retry_fetching_encryption_info() {
                 VerificationState::Unverified(VerificationLevel::UnsignedDevice),
             ),
             unsigned_encryption_info: None,
+            encrypted_event: None,
         },
         None,
     );
@@ -664,6 +757,7 @@ async fn test_retry_fetching_encryption_info() {
                 VerificationState::Unverified(VerificationLevel::UnsignedDevice),
             ),
             unsigned_encryption_info: None,
+            encrypted_event: None,
         },
         None,
     );
@@ -709,6 +803,134 @@ async fn test_retry_fetching_encryption_info() {
     assert_pending!(stream);
 }
 
+#[async_test]
+async fn test_refresh_encryption_info_when_trust_changes() {
+    const BOB_SESSION_ID: &str = "bob_session_id";
+    const CARL_SESSION_ID: &str = "carl_session_id";
+    let carl = user_id!("@carl:s.co");
+
+    // Given the room says both sessions are now verified…
+    let provider = TestRoomDataProvider::default()
+        .with_encryption_info(
+            BOB_SESSION_ID,
+            make_encryption_info(BOB_SESSION_ID, VerificationState::Verified),
+        )
+        .with_encryption_info(
+            CARL_SESSION_ID,
+            make_encryption_info(CARL_SESSION_ID, VerificationState::Verified),
+        );
+
+    let timeline = TestTimelineBuilder::new().provider(provider).build();
+
+    let f = &timeline.factory;
+    let mut stream = timeline.subscribe_events().await;
+
+    // …but the timeline contains events from Bob and Carl which are unverified.
+    for (sender, session_id) in [(*BOB, BOB_SESSION_ID), (carl, CARL_SESSION_ID)] {
+        timeline
+            .handle_live_event(TimelineEvent::from_decrypted(
+                DecryptedRoomEvent {
+                    event: f.text_msg("foo").sender(sender).into_raw(),
+                    encryption_info: make_encryption_info(
+                        session_id,
+                        VerificationState::Unverified(VerificationLevel::UnsignedDevice),
+                    ),
+                    unsigned_encryption_info: None,
+                    encrypted_event: None,
+                },
+                None,
+            ))
+            .await;
+
+        assert_next_matches!(stream, VectorDiff::PushBack { .. });
+    }
+
+    // When Bob's devices or identity change, only his event is updated.
+    timeline.controller.refresh_encryption_info(Some(iter::once(BOB.to_owned()).collect())).await;
+
+    let event = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_eq!(event.sender(), *BOB);
+    let encryption_info = event.encryption_info().unwrap();
+    assert_matches!(encryption_info.verification_state, VerificationState::Verified);
+    assert_pending!(stream);
+
+    // When our own identity changes, the events of everyone are updated.
+    timeline.controller.refresh_encryption_info(Some(iter::once(ALICE.to_owned()).collect())).await;
+
+    let event = assert_next_matches!(stream, VectorDiff::Set { index: 1, value } => value);
+    assert_eq!(event.sender(), carl);
+    let encryption_info = event.encryption_info().unwrap();
+    assert_matches!(encryption_info.verification_state, VerificationState::Verified);
+    assert_pending!(stream);
+}
+
+#[async_test]
+async fn test_source_of_an_event_decrypted_on_arrival() {
+    const SESSION_ID: &str = "gM8i47Xhu0q52xLfgUXzanCMpLinoyVyH7R58cBuVBU";
+
+    let timeline = TestTimeline::new();
+    let f = &timeline.factory;
+    let mut stream = timeline.subscribe_events().await;
+
+    let event_id = event_id!("$decrypted");
+    let encrypted_event = f
+        .event(RoomEncryptedEventContent::new(
+            EncryptedEventScheme::MegolmV1AesSha2(
+                MegolmV1AesSha2ContentInit {
+                    ciphertext: "AwgAEnACgAkLmt6qF84IK++J7UDH2Za1YVchHyprqTqsg".to_owned(),
+                    sender_key: "DeHIg4gwhClxzFYcmNntPNF9YtsdZbmMy8+3kzCMXHA".to_owned(),
+                    device_id: "NLAZCWIOCO".into(),
+                    session_id: SESSION_ID.into(),
+                }
+                .into(),
+            ),
+            None,
+        ))
+        .sender(&BOB)
+        .event_id(event_id)
+        .into_raw_sync();
+
+    let mut encryption_info = make_encryption_info(SESSION_ID, VerificationState::Verified);
+    assert_let!(
+        AlgorithmInfo::MegolmV1AesSha2 { forwarding_curve25519_key_chain, .. } =
+            &mut Arc::make_mut(&mut encryption_info).algorithm_info
+    );
+    forwarding_curve25519_key_chain.push("forwarder_key".to_owned());
+
+    // The event could be decrypted as soon as it was received.
+    timeline
+        .handle_live_event(TimelineEvent::from_decrypted(
+            DecryptedRoomEvent {
+                event: f
+                    .text_msg("It's a secret to everybody")
+                    .sender(&BOB)
+                    .event_id(event_id)
+                    .into_raw(),
+                encryption_info,
+                unsigned_encryption_info: None,
+                encrypted_event: Some(encrypted_event),
+            },
+            None,
+        ))
+        .await;
+
+    let event = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+
+    // Its source keeps the encrypted event, next to the decrypted payload, and the
+    // forwarding chain of the room key.
+    let source = event.source().unwrap();
+    assert_eq!(
+        source.encrypted_json().unwrap().get_field::<String>("type").unwrap().as_deref(),
+        Some("m.room.encrypted")
+    );
+    assert_eq!(
+        source.json().get_field::<String>("type").unwrap().as_deref(),
+        Some("m.room.message")
+    );
+    assert_eq!(source.session_id(), Some(SESSION_ID));
+    assert_eq!(source.forwarding_curve25519_key_chain(), ["forwarder_key".to_owned()]);
+}
+
 fn make_encryption_info(
     session_id: &str,
     verification_state: VerificationState,
@@ -720,6 +942,7 @@ fn make_encryption_info(
             curve25519_key: Default::default(),
             sender_claimed_keys: Default::default(),
             session_id: Some(session_id.to_owned()),
+            forwarding_curve25519_key_chain: Vec::new(),
         },
         verification_state,
     })
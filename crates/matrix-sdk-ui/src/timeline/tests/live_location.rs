@@ -0,0 +1,157 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use assert_matches2::assert_let;
+use eyeball_im::VectorDiff;
+use matrix_sdk_test::{ALICE, BOB, async_test};
+use ruma::{
+    MilliSecondsSinceUnixEpoch, event_id, events::beacon_info::BeaconInfoEventContent, uint,
+};
+use stream_assert::{assert_next_matches, assert_pending};
+
+use super::TestTimeline;
+use crate::timeline::TimelineItemContent;
+
+#[async_test]
+async fn test_beacons_update_the_live_location_share() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let f = &timeline.factory;
+
+    let beacon_info_id = event_id!("$beacon_info");
+    timeline
+        .handle_live_event(
+            f.event(BeaconInfoEventContent::new(
+                Some("Hiking".to_owned()),
+                Duration::from_secs(3600),
+                true,
+                None,
+            ))
+            .sender(&ALICE)
+            .state_key(ALICE.as_str())
+            .event_id(beacon_info_id),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert!(state.is_live());
+    assert_eq!(state.description(), Some("Hiking"));
+    assert!(state.last_location().is_none());
+
+    // The first beacon updates the share in place.
+    timeline
+        .handle_live_event(
+            f.beacon(
+                beacon_info_id.to_owned(),
+                48.85,
+                2.35,
+                10,
+                Some(MilliSecondsSinceUnixEpoch(uint!(2000))),
+            )
+            .sender(&ALICE),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    let last_location = state.last_location().unwrap();
+    assert_eq!(last_location.location.uri, "geo:48.85,2.35;u=10");
+    assert_eq!(last_location.ts, MilliSecondsSinceUnixEpoch(uint!(2000)));
+
+    // A beacon older than the last known location is ignored.
+    timeline
+        .handle_live_event(
+            f.beacon(
+                beacon_info_id.to_owned(),
+                48.84,
+                2.34,
+                10,
+                Some(MilliSecondsSinceUnixEpoch(uint!(1000))),
+            )
+            .sender(&ALICE),
+        )
+        .await;
+
+    assert_pending!(stream);
+
+    // A more recent one replaces the last known location.
+    timeline
+        .handle_live_event(
+            f.beacon(
+                beacon_info_id.to_owned(),
+                48.86,
+                2.36,
+                5,
+                Some(MilliSecondsSinceUnixEpoch(uint!(3000))),
+            )
+            .sender(&ALICE),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    let last_location = state.last_location().unwrap();
+    assert_eq!(last_location.location.uri, "geo:48.86,2.36;u=5");
+    assert!(state.is_live());
+
+    assert_pending!(stream);
+}
+
+#[async_test]
+async fn test_stopping_a_live_location_share() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let f = &timeline.factory;
+
+    let beacon_info = |live| {
+        f.event(BeaconInfoEventContent::new(None, Duration::from_secs(3600), live, None))
+            .sender(&ALICE)
+            .state_key(ALICE.as_str())
+    };
+
+    timeline.handle_live_event(beacon_info(true).event_id(event_id!("$start"))).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert!(state.is_live());
+
+    // Bob stopping a share which isn't in the timeline adds an item for the ended
+    // share, and leaves Alice's one untouched.
+    timeline
+        .handle_live_event(
+            f.event(BeaconInfoEventContent::new(None, Duration::from_secs(3600), false, None))
+                .sender(&BOB)
+                .state_key(BOB.as_str())
+                .event_id(event_id!("$bob_stop")),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert_eq!(item.sender(), *BOB);
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert!(!state.is_live());
+
+    // Alice stopping her share updates it in place, instead of adding a new item.
+    timeline.handle_live_event(beacon_info(false).event_id(event_id!("$stop"))).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_eq!(item.event_id(), Some(event_id!("$start")));
+    assert_let!(TimelineItemContent::LiveLocation(state) = item.content());
+    assert!(!state.is_live());
+
+    assert_pending!(stream);
+}
@@ -0,0 +1,183 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use eyeball_im::VectorDiff;
+use matrix_sdk_test::{ALICE, BOB, CAROL, async_test};
+use ruma::{
+    event_id,
+    events::{Mentions, room::message::RoomMessageEventContentWithoutRelation},
+};
+use stream_assert::{assert_next_matches, assert_pending};
+
+use super::TestTimeline;
+
+#[async_test]
+async fn test_intentional_mentions() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let f = &timeline.factory;
+
+    timeline
+        .handle_live_event(
+            f.text_msg("hey")
+                .sender(&BOB)
+                .mentions(Mentions::with_user_ids([ALICE.to_owned(), CAROL.to_owned()])),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    let mentions = item.content().as_message().unwrap().parsed_mentions();
+    assert_eq!(mentions.user_ids.len(), 2);
+    assert!(!mentions.room);
+    assert!(item.mentions_me());
+
+    // The intentional mentions take precedence over the body.
+    timeline
+        .handle_live_event(
+            f.text_msg("hey @room")
+                .sender(&BOB)
+                .mentions(Mentions::with_user_ids([CAROL.to_owned()])),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(!item.content().as_message().unwrap().parsed_mentions().room);
+    assert!(!item.mentions_me());
+
+    // The user's own messages never mention them.
+    timeline
+        .handle_live_event(
+            f.text_msg("hey me")
+                .sender(&ALICE)
+                .mentions(Mentions::with_user_ids([ALICE.to_owned()])),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(!item.mentions_me());
+
+    assert_pending!(stream);
+}
+
+#[async_test]
+async fn test_legacy_mentions() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let f = &timeline.factory;
+
+    // Without intentional mentions, the users are found in the links of the body.
+    timeline
+        .handle_live_event(
+            f.text_html(
+                "Alice: hi",
+                "<a href=\"https://matrix.to/#/@alice:server.name\">Alice</a>: hi",
+            )
+            .sender(&BOB),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    let mentions = item.content().as_message().unwrap().parsed_mentions();
+    assert!(mentions.user_ids.contains(*ALICE));
+    assert!(!mentions.room);
+    assert!(item.mentions_me());
+
+    // Whatever the attributes of the links.
+    timeline
+        .handle_live_event(
+            f.text_html(
+                "Carol: hi",
+                "<a title='Carol' href='matrix:u/carol:other.server'>Carol</a>: hi",
+            )
+            .sender(&BOB),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    let mentions = item.content().as_message().unwrap().parsed_mentions();
+    assert_eq!(mentions.user_ids.into_iter().collect::<Vec<_>>(), [CAROL.to_owned()]);
+    assert!(!item.mentions_me());
+
+    // And the user IDs written in the plain body.
+    timeline.handle_live_event(f.text_msg("hi @alice:server.name!").sender(&BOB)).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(item.content().as_message().unwrap().parsed_mentions().user_ids.contains(*ALICE));
+    assert!(item.mentions_me());
+
+    // And the room mention in the plain body.
+    timeline.handle_live_event(f.text_msg("hey @room").sender(&BOB)).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(item.content().as_message().unwrap().parsed_mentions().room);
+    assert!(item.mentions_me());
+
+    assert_pending!(stream);
+}
+
+#[async_test]
+async fn test_display_name_mentions() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let f = &timeline.factory;
+
+    timeline.handle_live_event(f.text_msg("Hey alice, how are you?").sender(&BOB)).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    let message = item.content().as_message().unwrap();
+    assert!(message.mentions_user(*ALICE, Some("Alice")));
+    // Only whole words match.
+    assert!(!message.mentions_user(*ALICE, Some("Ali")));
+    assert!(!message.mentions_user(*ALICE, None));
+
+    // The intentional mentions take precedence over the body.
+    timeline
+        .handle_live_event(
+            f.text_msg("Hey Alice")
+                .sender(&BOB)
+                .mentions(Mentions::with_user_ids([CAROL.to_owned()])),
+        )
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(!item.content().as_message().unwrap().mentions_user(*ALICE, Some("Alice")));
+
+    assert_pending!(stream);
+}
+
+#[async_test]
+async fn test_edit_updates_mentions_me() {
+    let timeline = TestTimeline::new();
+    let mut stream = timeline.subscribe_events().await;
+    let f = &timeline.factory;
+
+    let event_id = event_id!("$original");
+    timeline.handle_live_event(f.text_msg("hey").sender(&BOB).event_id(event_id)).await;
+
+    let item = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(!item.mentions_me());
+
+    let mut new_content = RoomMessageEventContentWithoutRelation::text_plain("hey Alice");
+    new_content.mentions = Some(Mentions::with_user_ids([ALICE.to_owned()]));
+
+    timeline
+        .handle_live_event(f.text_msg("* hey Alice").sender(&BOB).edit(event_id, new_content))
+        .await;
+
+    let item = assert_next_matches!(stream, VectorDiff::Set { index: 0, value } => value);
+    assert_eq!(item.content().as_message().unwrap().body(), "hey Alice");
+    assert!(item.mentions_me());
+
+    assert_pending!(stream);
+}
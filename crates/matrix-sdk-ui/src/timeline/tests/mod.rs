@@ -64,7 +64,10 @@ use super::{
     event_item::RemoteEventOrigin, traits::RoomDataProvider,
 };
 use crate::{
-    timeline::{pinned_events_loader::PinnedEventsRoom, traits::Decryptor},
+    timeline::{
+        pinned_events_loader::PinnedEventsRoom, starred_events_loader::StarredEventsRoom,
+        traits::Decryptor,
+    },
     unable_to_decrypt_hook::UtdHookManager,
 };
 
@@ -74,6 +77,8 @@ mod edit;
 mod encryption;
 mod event_filter;
 mod invalid;
+mod live_location;
+mod mentions;
 mod polls;
 mod reactions;
 mod read_receipts;
@@ -354,6 +359,12 @@ impl PinnedEventsRoom for TestRoomDataProvider {
     }
 }
 
+impl StarredEventsRoom for TestRoomDataProvider {
+    fn starred_event_ids(&self) -> BoxFuture<'_, Result<Vec<OwnedEventId>, matrix_sdk::Error>> {
+        unimplemented!();
+    }
+}
+
 impl RoomDataProvider for TestRoomDataProvider {
     fn own_user_id(&self) -> &UserId {
         &ALICE
@@ -19,18 +19,19 @@ use eyeball_im::VectorDiff;
 use futures_core::Stream;
 use futures_util::{FutureExt as _, StreamExt as _};
 use imbl::vector;
-use matrix_sdk::assert_next_matches_with_timeout;
-use matrix_sdk_test::{ALICE, BOB, async_test};
+use matrix_sdk::{assert_next_matches_with_timeout, timeout::timeout};
+use matrix_sdk_test::{ALICE, BOB, CAROL, async_test};
 use ruma::{
     EventId, MilliSecondsSinceUnixEpoch, OwnedEventId, event_id,
     events::AnyMessageLikeEventContent, server_name, uint,
 };
 use stream_assert::{assert_next_matches, assert_pending};
-use tokio::time::timeout;
 
 use crate::timeline::{
-    ReactionStatus, TimelineEventItemId, TimelineItem, event_item::RemoteEventOrigin,
-    tests::TestTimeline,
+    ReactionOrdering, ReactionStatus, ReactionSummary, TimelineEventItemId, TimelineItem,
+    controller::TimelineSettings,
+    event_item::RemoteEventOrigin,
+    tests::{TestTimeline, TestTimelineBuilder},
 };
 
 const REACTION_KEY: &str = "👍";
@@ -45,7 +46,7 @@ macro_rules! assert_item_update {
         // Expect an event timeline item update, with a short timeout.
         assert_let!(
             Ok(Some(VectorDiff::Set { index: i, value: event })) =
-                timeout(Duration::from_secs(1), $stream.next()).await
+                timeout($stream.next(), Duration::from_secs(1)).await
         );
 
         // Expect at the right position.
@@ -319,3 +320,178 @@ async fn test_reinserted_item_keeps_reactions() {
     // No other updates.
     assert_pending!(stream);
 }
+
+#[async_test]
+async fn test_reactions_are_kept_in_order() {
+    let timeline = TestTimelineBuilder::new()
+        .settings(TimelineSettings {
+            reaction_ordering: Some(ReactionOrdering::ByCount),
+            ..Default::default()
+        })
+        .build();
+    let f = &timeline.factory;
+
+    let event_id = event_id!("$target");
+    timeline.handle_live_event(f.text_msg("hey").sender(&BOB).event_id(event_id)).await;
+
+    timeline.handle_live_event(f.reaction(event_id, "👍").sender(&BOB).server_ts(1_000)).await;
+    timeline.handle_live_event(f.reaction(event_id, "🎉").sender(&CAROL).server_ts(3_000)).await;
+    timeline.handle_live_event(f.reaction(event_id, "🎉").sender(&ALICE).server_ts(2_000)).await;
+
+    let items = timeline.controller.items().await;
+    let item = items.last().unwrap().as_event().unwrap();
+    let reactions = item.content().reactions().unwrap();
+
+    // The key with the most reactions comes first, and its senders are ordered by
+    // the time of their reaction.
+    assert_eq!(reactions.keys().collect::<Vec<_>>(), ["🎉", "👍"]);
+    assert_eq!(reactions["🎉"].keys().collect::<Vec<_>>(), [*ALICE, *CAROL]);
+
+    assert_eq!(
+        reactions.summaries(&ALICE),
+        [
+            ReactionSummary {
+                key: "🎉".to_owned(),
+                count: 2,
+                first_reaction_timestamp: MilliSecondsSinceUnixEpoch(uint!(2_000)),
+                latest_reaction_timestamp: MilliSecondsSinceUnixEpoch(uint!(3_000)),
+                reacted_by_me: true,
+            },
+            ReactionSummary {
+                key: "👍".to_owned(),
+                count: 1,
+                first_reaction_timestamp: MilliSecondsSinceUnixEpoch(uint!(1_000)),
+                latest_reaction_timestamp: MilliSecondsSinceUnixEpoch(uint!(1_000)),
+                reacted_by_me: false,
+            },
+        ]
+    );
+}
+
+#[async_test]
+async fn test_reactions_are_kept_in_order_by_recency() {
+    let timeline = TestTimelineBuilder::new()
+        .settings(TimelineSettings {
+            reaction_ordering: Some(ReactionOrdering::ByRecency),
+            ..Default::default()
+        })
+        .build();
+    let f = &timeline.factory;
+
+    let event_id = event_id!("$target");
+    let reaction_id = event_id!("$reaction");
+    timeline.handle_live_event(f.text_msg("hey").sender(&BOB).event_id(event_id)).await;
+
+    timeline.handle_live_event(f.reaction(event_id, "👍").sender(&BOB).server_ts(1_000)).await;
+    timeline.handle_live_event(f.reaction(event_id, "🎉").sender(&CAROL).server_ts(2_000)).await;
+
+    // The key which received a reaction most recently comes first.
+    let items = timeline.controller.items().await;
+    let reactions = items.last().unwrap().as_event().unwrap().content().reactions().unwrap();
+    assert_eq!(reactions.keys().collect::<Vec<_>>(), ["🎉", "👍"]);
+
+    timeline
+        .handle_live_event(
+            f.reaction(event_id, "👍").sender(&ALICE).event_id(reaction_id).server_ts(3_000),
+        )
+        .await;
+
+    let items = timeline.controller.items().await;
+    let reactions = items.last().unwrap().as_event().unwrap().content().reactions().unwrap();
+    assert_eq!(reactions.keys().collect::<Vec<_>>(), ["👍", "🎉"]);
+    assert_eq!(reactions["👍"].keys().collect::<Vec<_>>(), [*BOB, *ALICE]);
+
+    // When the most recent reaction is redacted, its key moves back.
+    timeline.handle_live_event(f.redaction(reaction_id).sender(&ALICE)).await;
+
+    let items = timeline.controller.items().await;
+    let reactions = items.last().unwrap().as_event().unwrap().content().reactions().unwrap();
+    assert_eq!(reactions.keys().collect::<Vec<_>>(), ["🎉", "👍"]);
+    assert_eq!(reactions["👍"].keys().collect::<Vec<_>>(), [*BOB]);
+}
+
+#[async_test]
+async fn test_local_reaction_is_kept_after_a_reset() {
+    let timeline = TestTimeline::new();
+    let f = &timeline.factory;
+
+    let event_id = event_id!("$target");
+    let target_event = f.text_msg("hey").sender(&BOB).event_id(event_id).into_event();
+
+    timeline
+        .handle_event_update(
+            vec![VectorDiff::Append { values: vector![target_event.clone()] }],
+            RemoteEventOrigin::Sync,
+        )
+        .await;
+
+    // Alice reacts to the event, and the reaction is still being sent…
+    timeline
+        .toggle_reaction_local(&TimelineEventItemId::EventId(event_id.to_owned()), REACTION_KEY)
+        .await
+        .unwrap();
+
+    // …when the timeline is reset after a gap, and the event is received again.
+    timeline
+        .handle_event_update(
+            vec![VectorDiff::Clear, VectorDiff::Append { values: vector![target_event] }],
+            RemoteEventOrigin::Sync,
+        )
+        .await;
+
+    // The local echo of the reaction is still there.
+    let items = timeline.controller.items().await;
+    let item = items.last().unwrap().as_event().unwrap();
+    let reactions = item.content().reactions().cloned().unwrap_or_default();
+    let reaction = reactions.get(REACTION_KEY).unwrap().get(*ALICE).unwrap();
+    assert_matches!(reaction.status, ReactionStatus::LocalToRemote(_));
+}
+
+#[async_test]
+async fn test_reaction_received_again_is_not_duplicated() {
+    let timeline = TestTimeline::new();
+    let f = &timeline.factory;
+
+    let event_id = event_id!("$target");
+    let reaction_id = event_id!("$reaction");
+    let target_event = f.text_msg("hey").sender(&BOB).event_id(event_id).into_event();
+    let reaction_event =
+        f.reaction(event_id, REACTION_KEY).sender(&ALICE).event_id(reaction_id).into_event();
+
+    timeline
+        .handle_event_update(
+            vec![VectorDiff::Append {
+                values: vector![target_event.clone(), reaction_event.clone()],
+            }],
+            RemoteEventOrigin::Sync,
+        )
+        .await;
+
+    // The reaction is received again, as when re-aggregating the events after a
+    // reset.
+    timeline
+        .controller
+        .handle_remote_aggregations(
+            vec![VectorDiff::Append { values: vector![reaction_event] }],
+            RemoteEventOrigin::Cache,
+        )
+        .await;
+
+    // Then it's redacted.
+    timeline.handle_live_event(f.redaction(reaction_id).sender(&ALICE)).await;
+
+    // When the target is removed and reinserted, the reaction doesn't come back.
+    timeline
+        .handle_event_update(
+            vec![
+                VectorDiff::Remove { index: 0 },
+                VectorDiff::Insert { index: 0, value: target_event },
+            ],
+            RemoteEventOrigin::Sync,
+        )
+        .await;
+
+    let items = timeline.controller.items().await;
+    let item = items.last().unwrap().as_event().unwrap();
+    assert!(item.content().reactions().cloned().unwrap_or_default().is_empty());
+}
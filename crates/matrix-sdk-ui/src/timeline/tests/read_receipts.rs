@@ -899,3 +899,60 @@ async fn test_unthreaded_client_updates_threaded_read_receipts() {
 
     assert_pending!(stream);
 }
+
+#[async_test]
+async fn test_lazy_loaded_read_receipts() {
+    let timeline = TestTimelineBuilder::new()
+        .settings(TimelineSettings {
+            track_read_receipts: true,
+            lazy_read_receipts: true,
+            ..Default::default()
+        })
+        .build();
+    let mut stream = timeline.subscribe().await;
+
+    let f = &timeline.factory;
+    timeline.handle_live_event(f.text_msg("A").sender(*BOB)).await;
+    timeline.handle_live_event(f.text_msg("B").sender(*CAROL)).await;
+
+    // No item holds read receipts until they're requested.
+    let item_a = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(item_a.as_event().unwrap().read_receipts().is_empty());
+    let _date_divider = assert_next_matches!(stream, VectorDiff::PushFront { value } => value);
+    let item_b = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(item_b.as_event().unwrap().read_receipts().is_empty());
+    assert_pending!(stream);
+
+    // Requesting the receipts of the first event computes them.
+    timeline.controller.load_read_receipts_for(1..2).await;
+
+    let item_a = assert_next_matches!(stream, VectorDiff::Set { index: 1, value } => value);
+    let event_a = item_a.as_event().unwrap();
+    assert_eq!(event_a.read_receipts().len(), 1);
+    assert!(event_a.read_receipts().get(*BOB).is_some());
+    assert_pending!(stream);
+
+    // Bob's receipt moves out of the window, to an item which doesn't hold its
+    // receipts.
+    timeline.handle_live_event(f.text_msg("C").sender(*BOB)).await;
+
+    let item_a = assert_next_matches!(stream, VectorDiff::Set { index: 1, value } => value);
+    assert!(item_a.as_event().unwrap().read_receipts().is_empty());
+    let item_c = assert_next_matches!(stream, VectorDiff::PushBack { value } => value);
+    assert!(item_c.as_event().unwrap().read_receipts().is_empty());
+    assert_pending!(stream);
+
+    // Moving the window computes the receipts of the items entering it.
+    timeline.controller.load_read_receipts_for(2..4).await;
+
+    let item_b = assert_next_matches!(stream, VectorDiff::Set { index: 2, value } => value);
+    let event_b = item_b.as_event().unwrap();
+    assert_eq!(event_b.read_receipts().len(), 1);
+    assert!(event_b.read_receipts().get(*CAROL).is_some());
+
+    let item_c = assert_next_matches!(stream, VectorDiff::Set { index: 3, value } => value);
+    let event_c = item_c.as_event().unwrap();
+    assert_eq!(event_c.read_receipts().len(), 1);
+    assert!(event_c.read_receipts().get(*BOB).is_some());
+    assert_pending!(stream);
+}
@@ -12,11 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use assert_matches::assert_matches;
 use assert_matches2::assert_let;
 use chrono::{Datelike, TimeZone, Utc};
 use eyeball_im::VectorDiff;
 use futures_util::{FutureExt, StreamExt as _};
+use imbl::{Vector, vector};
 use matrix_sdk_test::{ALICE, BOB, async_test};
 use ruma::{
     event_id,
@@ -25,7 +28,10 @@ use ruma::{
 use stream_assert::assert_next_matches;
 
 use super::TestTimeline;
-use crate::timeline::{VirtualTimelineItem, traits::RoomDataProvider as _};
+use crate::timeline::{
+    CustomItemAnchor, CustomVirtualItem, TimelineItem, VirtualTimelineItem,
+    event_item::RemoteEventOrigin, traits::RoomDataProvider as _,
+};
 
 #[async_test]
 async fn test_date_divider() {
@@ -229,3 +235,137 @@ async fn test_update_read_marker() {
 
     assert!(stream.next().now_or_never().is_none());
 }
+
+#[async_test]
+async fn test_custom_items_stay_next_to_their_anchor() {
+    let timeline = TestTimeline::new();
+    let f = &timeline.factory;
+
+    timeline.handle_live_event(f.text_msg("A").sender(&ALICE).event_id(event_id!("$a"))).await;
+    timeline.handle_live_event(f.text_msg("B").sender(&BOB).event_id(event_id!("$b"))).await;
+
+    timeline
+        .controller
+        .insert_custom_item(CustomVirtualItem::new(
+            "after_a",
+            CustomItemAnchor::After(event_id!("$a").to_owned()),
+            "{}",
+        ))
+        .await;
+
+    // The anchor of this one isn't in the timeline yet.
+    timeline
+        .controller
+        .insert_custom_item(CustomVirtualItem::new(
+            "before_c",
+            CustomItemAnchor::Before(event_id!("$c").to_owned()),
+            "{}",
+        ))
+        .await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(describe_items(&items), ["divider", "$a", "after_a", "$b"]);
+
+    // Once the anchor is received, the other item is inserted, and the first one
+    // doesn't move.
+    timeline.handle_live_event(f.text_msg("C").sender(&BOB).event_id(event_id!("$c"))).await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(describe_items(&items), ["divider", "$a", "after_a", "$b", "before_c", "$c"]);
+
+    // Inserting an item with the same ID replaces it, keeping its unique ID.
+    let unique_id = items[2].unique_id().clone();
+    timeline
+        .controller
+        .insert_custom_item(CustomVirtualItem::new(
+            "after_a",
+            CustomItemAnchor::After(event_id!("$a").to_owned()),
+            "{\"updated\":true}",
+        ))
+        .await;
+
+    let items = timeline.controller.items().await;
+    assert_let!(Some(VirtualTimelineItem::Custom(custom)) = items[2].as_virtual());
+    assert_eq!(custom.content(), "{\"updated\":true}");
+    assert_eq!(*items[2].unique_id(), unique_id);
+
+    assert!(timeline.controller.remove_custom_item("after_a").await);
+    assert!(!timeline.controller.remove_custom_item("after_a").await);
+
+    let items = timeline.controller.items().await;
+    assert_eq!(describe_items(&items), ["divider", "$a", "$b", "before_c", "$c"]);
+}
+
+#[async_test]
+async fn test_timeline_reset_item() {
+    let timeline = TestTimeline::new();
+    let f = &timeline.factory;
+
+    // A clear received while the timeline is still empty doesn't add any item.
+    timeline
+        .handle_event_update(
+            vec![
+                VectorDiff::Clear,
+                VectorDiff::Append {
+                    values: vector![
+                        f.text_msg("A").sender(&BOB).event_id(event_id!("$a")).into_event()
+                    ],
+                },
+            ],
+            RemoteEventOrigin::Sync,
+        )
+        .await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(describe_items(&items), ["divider", "$a"]);
+
+    // After a gap, the reset item is inserted before the new events.
+    timeline
+        .handle_event_update(
+            vec![
+                VectorDiff::Clear,
+                VectorDiff::Append {
+                    values: vector![
+                        f.text_msg("C").sender(&BOB).event_id(event_id!("$c")).into_event()
+                    ],
+                },
+            ],
+            RemoteEventOrigin::Sync,
+        )
+        .await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(describe_items(&items), ["reset", "divider", "$c"]);
+    assert!(items[0].is_timeline_reset());
+
+    // Once events have been loaded by back-paginating, the gap is filled and the
+    // reset item is removed.
+    timeline
+        .handle_back_paginated_event(
+            f.text_msg("B").sender(&BOB).event_id(event_id!("$b")).into_raw_timeline(),
+        )
+        .await;
+
+    let items = timeline.controller.items().await;
+    assert_eq!(describe_items(&items), ["divider", "$b", "$c"]);
+
+    // A clear which doesn't come from a sync, e.g. when the event cache is cleared,
+    // doesn't add any item.
+    timeline.handle_event_update(vec![VectorDiff::Clear], RemoteEventOrigin::Cache).await;
+
+    assert!(timeline.controller.items().await.is_empty());
+}
+
+/// The event IDs of the event items, the IDs of the custom items, `divider`
+/// for the date dividers, and `reset` for the timeline reset items.
+fn describe_items(items: &Vector<Arc<TimelineItem>>) -> Vec<String> {
+    items
+        .iter()
+        .map(|item| match item.as_virtual() {
+            Some(VirtualTimelineItem::Custom(custom)) => custom.id().to_owned(),
+            Some(VirtualTimelineItem::DateDivider(_)) => "divider".to_owned(),
+            Some(VirtualTimelineItem::TimelineReset) => "reset".to_owned(),
+            _ => item.as_event().unwrap().event_id().unwrap().to_string(),
+        })
+        .collect()
+}
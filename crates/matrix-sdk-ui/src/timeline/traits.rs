@@ -40,6 +40,7 @@ use tracing::error;
 use super::{EventTimelineItem, Profile, RedactError, TimelineBuilder};
 use crate::timeline::{
     self, Timeline, latest_event::LatestEventValue, pinned_events_loader::PinnedEventsRoom,
+    starred_events_loader::StarredEventsRoom,
 };
 
 pub trait RoomExt {
@@ -101,7 +102,7 @@ impl RoomExt for Room {
 }
 
 pub(super) trait RoomDataProvider:
-    Clone + Decryptor + PaginableRoom + PaginableThread + PinnedEventsRoom + 'static
+    Clone + Decryptor + PaginableRoom + PaginableThread + PinnedEventsRoom + StarredEventsRoom + 'static
 {
     fn own_user_id(&self) -> &UserId;
     fn room_version_rules(&self) -> RoomVersionRules;
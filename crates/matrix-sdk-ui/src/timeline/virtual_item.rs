@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ruma::MilliSecondsSinceUnixEpoch;
+use std::{collections::HashSet, sync::Arc};
+
+use ruma::{EventId, MilliSecondsSinceUnixEpoch, OwnedEventId};
+
+use super::{MembershipChange, TimelineItem, TimelineItemContent};
 
 /// A [`TimelineItem`](super::TimelineItem) that doesn't correspond to an event.
 #[derive(Clone, Debug)]
@@ -30,4 +34,162 @@ pub enum VirtualTimelineItem {
     /// The timeline start, that is, an indication that we've seen all the
     /// events for that timeline.
     TimelineStart,
+
+    /// An indication that the timeline has been reset, because a gap in the
+    /// history has been detected, e.g. after a limited sync.
+    ///
+    /// The items that were before it have been removed, and the events after
+    /// it may not follow the ones that were displayed before. It's placed
+    /// before the first event received after the reset, and removed once
+    /// events have been loaded again before it by back-paginating, since the
+    /// gap has then been filled.
+    TimelineReset,
+
+    /// Consecutive membership and profile changes received together, whose
+    /// event items have been collapsed into this one.
+    ///
+    /// They can be shown again with
+    /// [`Timeline::expand_membership_summary()`](super::Timeline::expand_membership_summary).
+    MembershipSummary(MembershipSummary),
+
+    /// An item inserted by the application with
+    /// [`Timeline::insert_custom_item()`](super::Timeline::insert_custom_item),
+    /// which is kept next to its anchor event.
+    Custom(CustomVirtualItem),
+}
+
+/// Where a [`CustomVirtualItem`] is placed in the timeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CustomItemAnchor {
+    /// Right before the event with the given ID.
+    Before(OwnedEventId),
+
+    /// Right after the event with the given ID.
+    After(OwnedEventId),
+}
+
+impl CustomItemAnchor {
+    /// The ID of the event the item is placed next to.
+    pub fn event_id(&self) -> &EventId {
+        match self {
+            Self::Before(event_id) | Self::After(event_id) => event_id,
+        }
+    }
+}
+
+/// A virtual item inserted by the application, for instance to mark where the
+/// history imported from another platform starts.
+///
+/// It's only in the timeline while its anchor event is: it's removed when the
+/// anchor event is, and added back when it's loaded again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomVirtualItem {
+    pub(in crate::timeline) id: String,
+    pub(in crate::timeline) anchor: CustomItemAnchor,
+    pub(in crate::timeline) content: String,
+}
+
+impl CustomVirtualItem {
+    /// Create a new `CustomVirtualItem`.
+    ///
+    /// The `id` must be unique among the custom items of the timeline, and the
+    /// `content` is only meaningful to the application, e.g. serialized JSON.
+    pub fn new(
+        id: impl Into<String>,
+        anchor: CustomItemAnchor,
+        content: impl Into<String>,
+    ) -> Self {
+        Self { id: id.into(), anchor, content: content.into() }
+    }
+
+    /// The identifier of this item, chosen by the application.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Where this item is placed.
+    pub fn anchor(&self) -> &CustomItemAnchor {
+        &self.anchor
+    }
+
+    /// The content of this item, chosen by the application.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+}
+
+/// The membership and profile changes collapsed into a
+/// [`VirtualTimelineItem::MembershipSummary`].
+#[derive(Clone, Debug)]
+pub struct MembershipSummary {
+    pub(in crate::timeline) timestamp: MilliSecondsSinceUnixEpoch,
+    pub(in crate::timeline) items: Vec<Arc<TimelineItem>>,
+}
+
+impl MembershipSummary {
+    /// The timestamp of the first collapsed event.
+    pub fn timestamp(&self) -> MilliSecondsSinceUnixEpoch {
+        self.timestamp
+    }
+
+    /// The collapsed event items, in the timeline order.
+    pub fn items(&self) -> &[Arc<TimelineItem>] {
+        &self.items
+    }
+
+    /// The number of distinct users who joined the room.
+    ///
+    /// A user who joined several times is only counted once.
+    pub fn joined_count(&self) -> usize {
+        self.count_membership_changes(|change| {
+            matches!(
+                change,
+                MembershipChange::Joined
+                    | MembershipChange::InvitationAccepted
+                    | MembershipChange::KnockAccepted
+            )
+        })
+    }
+
+    /// The number of distinct users who left the room, or were removed from
+    /// it.
+    ///
+    /// A user who left several times is only counted once.
+    pub fn left_count(&self) -> usize {
+        self.count_membership_changes(|change| {
+            matches!(
+                change,
+                MembershipChange::Left
+                    | MembershipChange::Kicked
+                    | MembershipChange::Banned
+                    | MembershipChange::KickedAndBanned
+            )
+        })
+    }
+
+    /// The number of distinct users who changed their display name or avatar.
+    pub fn profile_changes_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter_map(|item| match item.as_event()?.content() {
+                TimelineItemContent::ProfileChange(profile) => Some(profile.user_id()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    fn count_membership_changes(&self, filter: impl Fn(MembershipChange) -> bool) -> usize {
+        self.items
+            .iter()
+            .filter_map(|item| match item.as_event()?.content() {
+                TimelineItemContent::MembershipChange(membership) => {
+                    membership.change().filter(|change| filter(*change))?;
+                    Some(membership.user_id())
+                }
+                _ => None,
+            })
+            .collect::<HashSet<_>>()
+            .len()
+    }
 }
@@ -1,14 +1,17 @@
 use std::{collections::BTreeMap, ops::Not, sync::Arc};
 
 use assert_matches::assert_matches;
-use eyeball_im::VectorDiff;
-use futures_util::{FutureExt, StreamExt, pin_mut};
+use eyeball_im::{Vector, VectorDiff};
+use futures_util::{FutureExt, Stream, StreamExt, pin_mut};
 use matrix_sdk::{
     Client, RoomDisplayName,
     config::RequestConfig,
     test_utils::{
         logged_in_client_with_server,
-        mocks::{MatrixMockServer, RoomMessagesResponseTemplate},
+        mocks::{
+            MatrixMockServer, RoomMessagesResponseTemplate,
+            sliding_sync::{SlidingSyncResponseBuilder, SlidingSyncRoomBuilder},
+        },
         set_client_session, test_client_builder,
     },
 };
@@ -19,22 +22,31 @@ use matrix_sdk_test::{
 use matrix_sdk_ui::{
     RoomListService,
     room_list_service::{
-        ALL_ROOMS_LIST_NAME as ALL_ROOMS, Error, RoomListLoadingState, State, SyncIndicator,
-        filters::{new_filter_fuzzy_match_room_name, new_filter_non_left, new_filter_none},
+        ALL_ROOMS_LIST_NAME as ALL_ROOMS, Error, RoomListItem, RoomListLoadingState, State,
+        SyncIndicator,
+        filters::{
+            RoomListSection, new_filter_fuzzy_match_room_name, new_filter_non_left, new_filter_none,
+        },
     },
     timeline::{RoomExt as _, TimelineItemKind, VirtualTimelineItem},
 };
 use ruma::{
     api::client::room::create_room::v3::Request as CreateRoomRequest,
     event_id,
-    events::room::message::RoomMessageEventContent,
+    events::room::{member::MembershipState, message::RoomMessageEventContent},
     mxc_uri, room_id,
+    serde::Raw,
     time::{Duration, Instant},
 };
 use serde_json::json;
 use stream_assert::{assert_next_matches, assert_pending};
 use tempfile::TempDir;
-use tokio::{spawn, sync::Barrier, task::yield_now, time::sleep};
+use tokio::{
+    spawn,
+    sync::Barrier,
+    task::yield_now,
+    time::{sleep, timeout},
+};
 use wiremock::{
     Mock, MockServer, ResponseTemplate,
     matchers::{header, method, path},
@@ -1759,6 +1771,219 @@ async fn test_dynamic_entries_stream() -> Result<(), Error> {
     Ok(())
 }
 
+#[async_test]
+async fn test_windowed_entries_stream() -> Result<(), Error> {
+    let (_client, server, room_list) = new_room_list_service().await?;
+
+    let sync = room_list.sync();
+    pin_mut!(sync);
+
+    let all_rooms = room_list.all_rooms().await?;
+
+    let (windowed_entries_stream, windowed_entries) =
+        all_rooms.windowed_entries_with_dynamic_adapters(1);
+    pin_mut!(windowed_entries_stream);
+
+    sync_then_assert_request_and_fake_response! {
+        [server, room_list, sync]
+        states = Init => SettingUp,
+        assert request >= {
+            "lists": {
+                ALL_ROOMS: {
+                    "ranges": [[0, 19]],
+                },
+            },
+        },
+        respond with = {
+            "pos": "0",
+            "lists": {
+                ALL_ROOMS: {
+                    "count": 5,
+                },
+            },
+            "rooms": {
+                "!r0:bar.org": {
+                    "initial": true,
+                    "bump_stamp": 1,
+                },
+                "!r1:bar.org": {
+                    "initial": true,
+                    "bump_stamp": 2,
+                },
+                "!r2:bar.org": {
+                    "initial": true,
+                    "bump_stamp": 3,
+                },
+                "!r3:bar.org": {
+                    "initial": true,
+                    "bump_stamp": 4,
+                },
+                "!r4:bar.org": {
+                    "initial": true,
+                    "bump_stamp": 5,
+                }
+            },
+        },
+    };
+
+    // The stream is pending because there is no filter set yet.
+    assert_pending!(windowed_entries_stream);
+
+    let mut entries = Vector::new();
+    let mut apply_pending_diffs = |entries: &mut Vector<_>| {
+        while let Some(Some(diffs)) = windowed_entries_stream.next().now_or_never() {
+            for diff in diffs {
+                diff.apply(entries);
+            }
+        }
+    };
+    let room_ids = |entries: &Vector<RoomListItem>| -> Vec<String> {
+        entries.iter().map(|room| room.room_id().to_string()).collect()
+    };
+
+    windowed_entries.set_filter(Box::new(new_filter_non_left()));
+
+    // The window is empty until a visible range is set.
+    apply_pending_diffs(&mut entries);
+    assert!(entries.is_empty());
+    assert_eq!(windowed_entries.window(), 0..0);
+
+    // The rooms are sorted by recency. The visible room is yielded with the
+    // margin around it.
+    windowed_entries.set_visible_range(1..2);
+    assert_eq!(windowed_entries.window(), 0..3);
+
+    apply_pending_diffs(&mut entries);
+    assert_eq!(room_ids(&entries), ["!r4:bar.org", "!r3:bar.org", "!r2:bar.org"]);
+
+    // Scroll to the end of the room list.
+    windowed_entries.set_visible_range(3..5);
+    assert_eq!(windowed_entries.window(), 2..6);
+
+    apply_pending_diffs(&mut entries);
+    assert_eq!(room_ids(&entries), ["!r2:bar.org", "!r1:bar.org", "!r0:bar.org"]);
+
+    Ok(())
+}
+
+/// Apply the diffs of `stream` to `entries` until the rooms of `entries` are
+/// `expected_room_ids`.
+async fn wait_for_section_rooms(
+    stream: &mut (impl Stream<Item = Vec<VectorDiff<RoomListItem>>> + Unpin),
+    entries: &mut Vector<RoomListItem>,
+    expected_room_ids: &[&str],
+) {
+    while entries.iter().map(|room| room.room_id().as_str()).ne(expected_room_ids.iter().copied()) {
+        let diffs = timeout(Duration::from_secs(1), stream.next())
+            .await
+            .expect("the section should have been updated")
+            .expect("the stream of the section should be open");
+
+        for diff in diffs {
+            diff.apply(entries);
+        }
+    }
+}
+
+#[async_test]
+async fn test_sections_entries_stream() -> Result<(), Error> {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+    let own_user_id = client.user_id().unwrap().to_owned();
+    let room_list = RoomListService::new(client).await?;
+
+    let all_rooms = room_list.all_rooms().await?;
+    let sections = all_rooms.sections();
+
+    let (invites_stream, _) = sections.section_entries(RoomListSection::Invites, 5);
+    let (knocks_stream, _) = sections.section_entries(RoomListSection::Knocks, 5);
+    let (favourites_stream, _) = sections.section_entries(RoomListSection::Favourites, 5);
+    let (rooms_stream, rooms_controller) = sections.section_entries(RoomListSection::Rooms, 1);
+    pin_mut!(invites_stream, knocks_stream, favourites_stream, rooms_stream);
+
+    let mut invites = Vector::new();
+    let mut knocks = Vector::new();
+    let mut favourites = Vector::new();
+    let mut rooms = Vector::new();
+
+    // The sections are empty until a filter is set.
+    let diffs = invites_stream.next().await.unwrap();
+    assert_matches!(&diffs[..], [VectorDiff::Reset { values }] => assert!(values.is_empty()));
+
+    sections.set_filter(Box::new(new_filter_non_left()));
+
+    let f = EventFactory::new().sender(&ALICE);
+    let favourite_tag = Raw::new(&json!({
+        "type": "m.tag",
+        "content": { "tags": { "m.favourite": {} } },
+    }))
+    .unwrap()
+    .cast_unchecked();
+
+    server
+        .mock_sliding_sync()
+        .ok_with(
+            SlidingSyncResponseBuilder::new("0")
+                .list(ALL_ROOMS, 5)
+                .room(
+                    room_id!("!r0:bar.org"),
+                    SlidingSyncRoomBuilder::new().initial().bump_stamp(1).name("Room #0"),
+                )
+                .room(
+                    room_id!("!r1:bar.org"),
+                    SlidingSyncRoomBuilder::new().initial().bump_stamp(2).name("Room #1"),
+                )
+                .room(
+                    room_id!("!r2:bar.org"),
+                    SlidingSyncRoomBuilder::new().initial().bump_stamp(3).invite_state(
+                        f.member(&own_user_id).membership(MembershipState::Invite).into_raw(),
+                    ),
+                )
+                .room(
+                    room_id!("!r3:bar.org"),
+                    SlidingSyncRoomBuilder::new().initial().bump_stamp(4).invite_state(
+                        EventFactory::new()
+                            .member(&own_user_id)
+                            .membership(MembershipState::Knock)
+                            .into_raw(),
+                    ),
+                )
+                .room(
+                    room_id!("!r4:bar.org"),
+                    SlidingSyncRoomBuilder::new().initial().bump_stamp(5).name("Room #4"),
+                )
+                .room_account_data(room_id!("!r1:bar.org"), favourite_tag),
+        )
+        .mock_once()
+        .mount()
+        .await;
+
+    let sync = room_list.sync();
+    pin_mut!(sync);
+    assert_matches!(sync.next().await, Some(Ok(())));
+
+    // Each room is in exactly one section, and the knocked room has its own.
+    wait_for_section_rooms(&mut invites_stream, &mut invites, &["!r2:bar.org"]).await;
+    wait_for_section_rooms(&mut knocks_stream, &mut knocks, &["!r3:bar.org"]).await;
+    wait_for_section_rooms(&mut favourites_stream, &mut favourites, &["!r1:bar.org"]).await;
+
+    // The sections are paginated independently from the others.
+    wait_for_section_rooms(&mut rooms_stream, &mut rooms, &["!r4:bar.org"]).await;
+
+    rooms_controller.add_one_page();
+    wait_for_section_rooms(&mut rooms_stream, &mut rooms, &["!r4:bar.org", "!r0:bar.org"]).await;
+
+    // Changing the filter updates all the sections.
+    sections.set_filter(Box::new(new_filter_none()));
+
+    wait_for_section_rooms(&mut invites_stream, &mut invites, &[]).await;
+    wait_for_section_rooms(&mut knocks_stream, &mut knocks, &[]).await;
+    wait_for_section_rooms(&mut favourites_stream, &mut favourites, &[]).await;
+    wait_for_section_rooms(&mut rooms_stream, &mut rooms, &[]).await;
+
+    Ok(())
+}
+
 #[async_test]
 async fn test_room_sorting() -> Result<(), Error> {
     let (_client, server, room_list) = new_room_list_service().await?;
@@ -3086,3 +3311,44 @@ async fn test_thread_subscriptions_extension_enabled_only_if_server_advertises_i
         },
     };
 }
+
+#[async_test]
+async fn test_sync_with_matrix_mock_server() -> Result<(), Error> {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+    let room_list = RoomListService::new(client).await?;
+
+    let room_id = room_id!("!r0:bar.org");
+    let f = EventFactory::new().room(room_id).sender(&ALICE);
+
+    server
+        .mock_sliding_sync()
+        .match_no_pos()
+        .match_list_range(ALL_ROOMS, 0, 19)
+        .match_extension_enabled("account_data")
+        .ok_with(
+            SlidingSyncResponseBuilder::new("0")
+                .list(ALL_ROOMS, 1)
+                .room(
+                    room_id,
+                    SlidingSyncRoomBuilder::new()
+                        .initial()
+                        .bump_stamp(1)
+                        .required_state(f.room_name("Room #0"))
+                        .timeline_event(f.text_msg("hello").event_id(event_id!("$x0:bar.org"))),
+                )
+                .typing(room_id, [ALICE.to_owned()]),
+        )
+        .mock_once()
+        .mount()
+        .await;
+
+    let sync = room_list.sync();
+    pin_mut!(sync);
+    assert_matches!(sync.next().await, Some(Ok(())));
+
+    let room = room_list.room(room_id)?;
+    assert_eq!(room.cached_display_name(), Some(RoomDisplayName::Named("Room #0".to_owned())));
+
+    Ok(())
+}
@@ -19,11 +19,15 @@ use std::{
 
 use assert_matches::assert_matches;
 use matrix_sdk::{
-    assert_next_matches_with_timeout,
+    assert_next_matches_with_timeout, assert_next_with_timeout,
     test_utils::{logged_in_client_with_server, mocks::MatrixMockServer},
 };
-use matrix_sdk_test::async_test;
-use matrix_sdk_ui::sync_service::{State, SyncService};
+use matrix_sdk_test::{JoinedRoomBuilder, async_test};
+use matrix_sdk_ui::{
+    room_list_service::{RoomListLoadingState, State as RoomListState},
+    sync_service::{State, SyncErrorKind, SyncService, SyncServiceStatus},
+};
+use ruma::room_id;
 use serde_json::json;
 use stream_assert::{assert_next_matches, assert_pending};
 use wiremock::{Match as _, Mock, MockGuard, MockServer, Request, ResponseTemplate};
@@ -280,3 +284,217 @@ async fn test_sync_service_offline_mode_restarting() {
     assert_next_matches_with_timeout!(states, 2000, State::Running);
     assert_next_matches_with_timeout!(states, 2000, State::Offline);
 }
+
+#[async_test]
+async fn test_sync_service_status_with_expired_token() {
+    let mock_server = MatrixMockServer::new().await;
+    let client = mock_server.client_builder().build().await;
+
+    let sync_service = SyncService::builder(client).build().await.unwrap();
+    let mut statuses = sync_service.status();
+
+    Mock::given(SlidingSyncMatcher)
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "errcode": "M_UNKNOWN_TOKEN",
+            "error": "Invalid access token passed.",
+            "soft_logout": true,
+        })))
+        .mount(mock_server.server())
+        .await;
+
+    sync_service.start().await;
+    assert_next_matches!(
+        statuses,
+        SyncServiceStatus { state: State::Running, last_error: None, backoff: None }
+    );
+
+    let status = assert_next_with_timeout!(statuses, 2000);
+    assert_matches!(status.state, State::Error(_));
+    assert_eq!(status.last_error, Some(SyncErrorKind::TokenExpired { soft_logout: true }));
+    assert_eq!(status.backoff, None);
+}
+
+#[async_test]
+async fn test_sync_service_status_in_offline_mode() {
+    let mock_server = MatrixMockServer::new().await;
+    let client = mock_server.client_builder().build().await;
+
+    let sync_service = SyncService::builder(client).with_offline_mode().build().await.unwrap();
+    let mut statuses = sync_service.status();
+
+    Mock::given(SlidingSyncMatcher)
+        .respond_with(ResponseTemplate::new(404))
+        .mount(mock_server.server())
+        .await;
+
+    {
+        let _versions_guard = mock_server
+            .mock_versions()
+            .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+                "errcode": "M_LIMIT_EXCEEDED",
+                "error": "Too many requests",
+                "retry_after_ms": 1,
+            })))
+            .mount_as_scoped()
+            .await;
+
+        sync_service.start().await;
+
+        // Wait for a check of the availability of the server to fail.
+        let status = loop {
+            let status = assert_next_with_timeout!(statuses, 2000);
+
+            if status.backoff.is_some() {
+                break status;
+            }
+        };
+
+        assert_matches!(status.state, State::Offline);
+        assert_eq!(
+            status.last_error,
+            Some(SyncErrorKind::RateLimited { retry_after: Some(Duration::from_millis(1)) })
+        );
+        assert_matches!(status.backoff, Some(backoff) => {
+            assert!(backoff >= Duration::from_millis(100));
+        });
+    }
+
+    mock_server.mock_versions().ok().mount().await;
+
+    // Once the server is available again, the error and the backoff are reset.
+    let status = loop {
+        let status = assert_next_with_timeout!(statuses, 2000);
+
+        if matches!(status.state, State::Running) {
+            break status;
+        }
+    };
+
+    assert_eq!(status.last_error, None);
+    assert_eq!(status.backoff, None);
+}
+
+#[async_test]
+async fn test_sync_service_network_unavailable() {
+    let (client, server) = logged_in_client_with_server().await;
+
+    let encryption_pos = Arc::new(Mutex::new(0));
+    let room_pos = Arc::new(Mutex::new(0));
+    let _guard = setup_mocking_sliding_sync_server(&server, encryption_pos, room_pos).await;
+
+    // The offline mode doesn't need to be enabled.
+    let sync_service = SyncService::builder(client).build().await.unwrap();
+    let mut states = sync_service.state();
+
+    sync_service.start().await;
+    assert_next_matches!(states, State::Running);
+
+    // Reporting the same availability does nothing.
+    sync_service.set_network_available(true).await;
+    assert_pending!(states);
+
+    // When the network is unavailable, the syncs are stopped.
+    sync_service.set_network_available(false).await;
+    assert_next_matches_with_timeout!(states, 2000, State::Offline);
+    assert!(sync_service.is_supervisor_running().await);
+    assert!(sync_service.try_get_encryption_sync_permit().is_some());
+
+    // When it's back, the syncs are restarted immediately.
+    sync_service.set_network_available(true).await;
+    assert_next_matches_with_timeout!(states, 1000, State::Running);
+
+    // The syncs keep on running until the service is stopped: the next state is
+    // `Idle`, not an error.
+    sync_service.stop().await;
+    assert_next_matches_with_timeout!(states, 2000, State::Idle);
+}
+
+#[async_test]
+async fn test_sync_service_start_while_network_unavailable() {
+    let mock_server = MatrixMockServer::new().await;
+    let client = mock_server.client_builder().build().await;
+
+    let sync_service = SyncService::builder(client).build().await.unwrap();
+    let mut states = sync_service.state();
+
+    sync_service.set_network_available(false).await;
+    assert_pending!(states);
+
+    // No request is sent while the network is unavailable.
+    Mock::given(SlidingSyncMatcher)
+        .respond_with(ResponseTemplate::new(404))
+        .expect(0)
+        .mount(mock_server.server())
+        .await;
+
+    sync_service.start().await;
+    assert_next_matches!(states, State::Offline);
+
+    // The service stays offline until it's stopped: the next state is `Idle`, not
+    // `Running`.
+    sync_service.stop().await;
+    assert_next_matches_with_timeout!(states, 2000, State::Idle);
+}
+
+#[async_test]
+async fn test_sync_service_sync_v2_fallback() {
+    let mock_server = MatrixMockServer::new().await;
+    let client = mock_server.client_builder().build().await;
+    let room_id = room_id!("!r0:bar.org");
+
+    let sync_service =
+        SyncService::builder(client.clone()).with_sync_v2_fallback().build().await.unwrap();
+    let mut states = sync_service.state();
+    let mut room_list_states = sync_service.room_list_service().state();
+    let room_list = sync_service.room_list_service().all_rooms().await.unwrap();
+    let mut loading_states = room_list.loading_state();
+    assert_matches!(loading_states.get(), RoomListLoadingState::NotLoaded);
+
+    // The server doesn't support sliding sync.
+    Mock::given(SlidingSyncMatcher)
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "errcode": "M_UNRECOGNIZED",
+            "error": "Unrecognized request",
+        })))
+        .expect(1..)
+        .mount(mock_server.server())
+        .await;
+    mock_server
+        .mock_sync()
+        .ok(|builder| {
+            builder.add_joined_room(JoinedRoomBuilder::new(room_id));
+        })
+        .expect(1..)
+        .mount()
+        .await;
+
+    sync_service.start().await;
+    assert_next_matches!(states, State::Running);
+
+    // The room list is fed by sync v2.
+    loop {
+        if matches!(assert_next_with_timeout!(room_list_states, 2000), RoomListState::Running) {
+            break;
+        }
+    }
+
+    assert!(client.get_room(room_id).is_some());
+    assert!(sync_service.uses_sync_v2().await);
+
+    // The room list is loaded with the rooms received by sync v2.
+    loop {
+        if matches!(
+            assert_next_with_timeout!(loading_states, 2000),
+            RoomListLoadingState::Loaded { maximum_number_of_rooms: Some(1) }
+        ) {
+            break;
+        }
+    }
+
+    // The sync service doesn't fail.
+    assert_pending!(states);
+
+    sync_service.stop().await;
+    assert_next_matches_with_timeout!(states, 2000, State::Idle);
+    assert_matches!(room_list_states.get(), RoomListState::Terminated { .. });
+}
@@ -22,7 +22,9 @@ use futures_util::{FutureExt, StreamExt};
 use matrix_sdk::{
     Client,
     room::edit::EditedContent,
-    test_utils::mocks::{MatrixMockServer, RoomMessagesResponseTemplate},
+    test_utils::mocks::{
+        MatrixMockServer, RoomMessagesResponseTemplate, RoomRelationsResponseTemplate,
+    },
 };
 use matrix_sdk_test::{ALICE, BOB, JoinedRoomBuilder, async_test, event_factory::EventFactory};
 use matrix_sdk_ui::{
@@ -1091,3 +1093,94 @@ async fn test_send_edit_non_existing_item() {
         .unwrap();
     assert_matches!(error, Error::EventNotInTimeline(_));
 }
+
+#[async_test]
+async fn test_edit_history() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+
+    server.mock_room_state_encryption().plain().mount().await;
+
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    let f = EventFactory::new().room(room_id).sender(&ALICE);
+    let event_id = event_id!("$original");
+
+    // The original event and its first edit are received from the sync.
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id)
+                .add_timeline_event(f.text_msg("hello").event_id(event_id).server_ts(1000))
+                .add_timeline_event(
+                    f.text_msg("* hello, world")
+                        .edit(
+                            event_id,
+                            RoomMessageEventContentWithoutRelation::text_plain("hello, world"),
+                        )
+                        .event_id(event_id!("$edit1"))
+                        .server_ts(2000),
+                ),
+        )
+        .await;
+
+    assert_let!(Some(_) = timeline_stream.next().await);
+
+    // The server knows about the first edit too, about an invalid edit from
+    // someone else, and about a more recent edit in a second batch.
+    let batch1 = vec![
+        f.text_msg("* hello, bob")
+            .edit(event_id, RoomMessageEventContentWithoutRelation::text_plain("hello, bob"))
+            .sender(&BOB)
+            .event_id(event_id!("$edit_bob"))
+            .server_ts(2500)
+            .into_raw(),
+        f.text_msg("* hello, world")
+            .edit(event_id, RoomMessageEventContentWithoutRelation::text_plain("hello, world"))
+            .event_id(event_id!("$edit1"))
+            .server_ts(2000)
+            .into_raw(),
+    ];
+
+    let batch2 = vec![
+        f.text_msg("* hello, world!")
+            .edit(event_id, RoomMessageEventContentWithoutRelation::text_plain("hello, world!"))
+            .event_id(event_id!("$edit2"))
+            .server_ts(3000)
+            .into_raw(),
+    ];
+
+    server
+        .mock_room_relations()
+        .match_target_event(event_id.to_owned())
+        .ok(RoomRelationsResponseTemplate::default().events(batch1).next_batch("next_batch"))
+        .mock_once()
+        .mount()
+        .await;
+
+    server
+        .mock_room_relations()
+        .match_target_event(event_id.to_owned())
+        .match_from("next_batch")
+        .ok(RoomRelationsResponseTemplate::default().events(batch2))
+        .mock_once()
+        .mount()
+        .await;
+
+    let history = timeline.edit_history(event_id).await.unwrap();
+
+    assert_eq!(history.len(), 3);
+
+    assert_eq!(history[0].event_id, event_id);
+    assert_eq!(history[0].body(), "hello");
+
+    assert_eq!(history[1].event_id, event_id!("$edit1"));
+    assert_eq!(history[1].body(), "hello, world");
+
+    assert_eq!(history[2].event_id, event_id!("$edit2"));
+    assert_eq!(history[2].body(), "hello, world!");
+}
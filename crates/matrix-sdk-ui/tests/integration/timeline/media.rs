@@ -27,22 +27,27 @@ use matrix_sdk_test::{
     ALICE, JoinedRoomBuilder, TestResult, async_test, event_factory::EventFactory,
 };
 use matrix_sdk_ui::timeline::{
-    AttachmentConfig, AttachmentSource, EventSendState, MediaUploadProgress, RoomExt, TimelineFocus,
+    AttachmentConfig, AttachmentSource, Error, EventSendState, MediaUploadProgress, RoomExt,
+    TimelineBuilder, TimelineFocus, futures::AttachmentUploadPhase,
 };
 #[cfg(feature = "unstable-msc4274")]
-use matrix_sdk_ui::timeline::{GalleryConfig, GalleryItemInfo};
+use matrix_sdk_ui::timeline::{GalleryConfig, GalleryItemInfo, GalleryMediaKind};
 #[cfg(feature = "unstable-msc4274")]
 use ruma::events::room::message::GalleryItemType;
 #[cfg(feature = "unstable-msc4274")]
 use ruma::owned_mxc_uri;
 use ruma::{
     event_id,
-    events::room::{MediaSource, message::MessageType},
-    room_id,
+    events::room::{
+        MediaSource,
+        message::{FormattedBody, ImageMessageEventContent, MessageType, RoomMessageEventContent},
+    },
+    mxc_uri, room_id,
 };
 use serde_json::json;
 use stream_assert::assert_pending;
 use tempfile::TempDir;
+use tokio::time::sleep;
 use wiremock::ResponseTemplate;
 
 fn create_temporary_file(filename: &str) -> anyhow::Result<(TempDir, PathBuf)> {
@@ -150,11 +155,12 @@ async fn test_send_attachment_from_file() -> TestResult {
         assert_let!(Some(msg) = item.content().as_message());
         assert_let!(
             Some(EventSendState::NotSentYet {
-                progress: Some(MediaUploadProgress { index, progress })
+                progress: Some(MediaUploadProgress { index, progress, phase })
             }) = item.send_state()
         );
         assert_eq!(*index, 0);
         assert_eq!(progress.current, progress.total);
+        assert_eq!(*phase, AttachmentUploadPhase::Sending);
         assert_eq!(get_filename_and_caption(msg.msgtype()), ("test.bin", Some("caption")));
 
         // The URI still refers to the local cache.
@@ -174,7 +180,7 @@ async fn test_send_attachment_from_file() -> TestResult {
         assert_let!(Some(msg) = item.content().as_message());
         assert_let!(
             Some(EventSendState::NotSentYet {
-                progress: Some(MediaUploadProgress { index, progress })
+                progress: Some(MediaUploadProgress { index, progress, .. })
             }) = item.send_state()
         );
         assert_eq!(*index, final_index);
@@ -291,7 +297,7 @@ async fn test_send_attachment_from_bytes() -> TestResult {
 
             assert_let!(Some(EventSendState::NotSentYet { progress }) = item.send_state());
 
-            assert_let!(Some(MediaUploadProgress { index, progress }) = progress);
+            assert_let!(Some(MediaUploadProgress { index, progress, phase }) = progress);
 
             // We're only uploading a single file.
             assert_eq!(*index, 0);
@@ -300,6 +306,13 @@ async fn test_send_attachment_from_bytes() -> TestResult {
             assert!(progress.current <= progress.total);
             assert_eq!(progress.total, size);
 
+            // There's no thumbnail, so the file is uploaded until the event is sent.
+            if progress.current < progress.total {
+                assert_eq!(*phase, AttachmentUploadPhase::File);
+            } else {
+                assert_eq!(*phase, AttachmentUploadPhase::Sending);
+            }
+
             // The progress only increases.
             if let Some(prev_progress) = prev_progress {
                 assert!(progress.current >= prev_progress.current);
@@ -334,6 +347,201 @@ async fn test_send_attachment_from_bytes() -> TestResult {
     Ok(())
 }
 
+#[async_test]
+async fn test_send_voice_message() -> TestResult {
+    let mock = MatrixMockServer::new().await;
+    let client = mock.client_builder().build().await;
+
+    mock.mock_authenticated_media_config().ok_default().mount().await;
+    mock.mock_room_state_encryption().plain().mount().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = mock.sync_joined_room(&client, room_id).await;
+    let timeline = room.timeline().await?;
+
+    let (items, mut timeline_stream) =
+        timeline.subscribe_filter_map(|item| item.as_event().cloned()).await;
+    assert!(items.is_empty());
+
+    mock.mock_upload()
+        .expect_mime_type("audio/ogg")
+        .ok(mxc_uri!("mxc://sdk.rs/voice"))
+        .mock_once()
+        .mount()
+        .await;
+
+    // The event has the voice marker and the audio details.
+    mock.mock_room_send()
+        .body_matches_partial_json(json!({
+            "msgtype": "m.audio",
+            "org.matrix.msc1767.audio": {
+                "duration": 3000,
+                "waveform": [0, 512, 1024],
+            },
+            "org.matrix.msc3245.voice": {},
+        }))
+        .ok(event_id!("$voice"))
+        .mock_once()
+        .mount()
+        .await;
+
+    timeline
+        .send_voice_message(b"ogg opus".to_vec(), vec![0, 512, 1024], Duration::from_secs(3))
+        .await?;
+
+    // The local echo is a voice message, with the waveform.
+    assert_let_timeout!(Some(VectorDiff::PushBack { value: item }) = timeline_stream.next());
+    assert_matches!(item.send_state(), Some(EventSendState::NotSentYet { .. }));
+    assert_let!(Some(msg) = item.content().as_message());
+    assert!(msg.is_voice_message());
+    assert_eq!(msg.waveform(), Some(vec![0, 512, 1024]));
+    assert_let!(MessageType::Audio(audio) = msg.msgtype());
+    assert_eq!(audio.filename(), "voice-message.ogg");
+
+    // Eventually, the event is sent.
+    loop {
+        assert_let_timeout!(
+            Duration::from_secs(3),
+            Some(VectorDiff::Set { index: 0, value: item }) = timeline_stream.next()
+        );
+        assert!(item.content().as_message().unwrap().is_voice_message());
+
+        if let Some(EventSendState::Sent { event_id }) = item.send_state() {
+            assert_eq!(event_id, event_id!("$voice"));
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[async_test]
+async fn test_forward_media_message() -> TestResult {
+    let mock = MatrixMockServer::new().await;
+    let client = mock.client_builder().build().await;
+
+    mock.mock_room_state_encryption().plain().mount().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = mock.sync_joined_room(&client, room_id).await;
+    let timeline = room.timeline().await?;
+
+    let target_room = mock.sync_joined_room(&client, room_id!("!target:example.org")).await;
+    let target_timeline = target_room.timeline().await?;
+    let (_, mut target_stream) =
+        target_timeline.subscribe_filter_map(|item| item.as_event().cloned()).await;
+
+    let mut image = ImageMessageEventContent::plain(
+        "A cat".to_owned(),
+        mxc_uri!("mxc://sdk.rs/cat").to_owned(),
+    );
+    image.filename = Some("cat.png".to_owned());
+    image.formatted = Some(FormattedBody::html("A <b>cat</b>"));
+
+    let event_id = event_id!("$image");
+    let f = EventFactory::new();
+    mock.sync_room(
+        &client,
+        JoinedRoomBuilder::new(room_id).add_timeline_event(
+            f.event(RoomMessageEventContent::new(MessageType::Image(image)))
+                .sender(&ALICE)
+                .event_id(event_id),
+        ),
+    )
+    .await;
+
+    // An event which isn't in the timeline can't be forwarded.
+    assert_matches!(
+        timeline.forward(event_id!("$unknown"), &target_room).await,
+        Err(Error::EventNotInTimeline(_))
+    );
+
+    // The media of an unencrypted event is reused, with the caption.
+    mock.mock_room_send()
+        .body_matches_partial_json(json!({
+            "msgtype": "m.image",
+            "body": "A cat",
+            "filename": "cat.png",
+            "formatted_body": "A <b>cat</b>",
+            "url": "mxc://sdk.rs/cat",
+            "m.mentions": {},
+        }))
+        .ok(event_id!("$forwarded"))
+        .mock_once()
+        .mount()
+        .await;
+
+    timeline.forward(event_id, &target_room).await?;
+
+    assert_let_timeout!(Some(VectorDiff::PushBack { value: item }) = target_stream.next());
+    assert_let!(Some(msg) = item.content().as_message());
+    assert_eq!(get_filename_and_caption(msg.msgtype()), ("cat.png", Some("A cat")));
+    assert_let!(MessageType::Image(image) = msg.msgtype());
+    assert_eq!(image.formatted.as_ref().unwrap().body, "A <b>cat</b>");
+    assert_let!(MediaSource::Plain(uri) = &image.source);
+    assert_eq!(*uri, "mxc://sdk.rs/cat");
+
+    assert_let_timeout!(Some(VectorDiff::Set { index: 0, value: item }) = target_stream.next());
+    assert_matches!(item.send_state(), Some(EventSendState::Sent { event_id }) => {
+        assert_eq!(event_id, event_id!("$forwarded"));
+    });
+
+    Ok(())
+}
+
+#[async_test]
+async fn test_cancel_attachment_upload() -> TestResult {
+    let mock = MatrixMockServer::new().await;
+    let client = mock.client_builder().build().await;
+
+    mock.mock_authenticated_media_config().ok_default().mount().await;
+    mock.mock_room_state_encryption().plain().mount().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = mock.sync_joined_room(&client, room_id).await;
+    let timeline = room.timeline().await?;
+
+    let (items, mut timeline_stream) =
+        timeline.subscribe_filter_map(|item| item.as_event().cloned()).await;
+    assert!(items.is_empty());
+
+    // The upload takes a while, so it can be cancelled in the meantime.
+    mock.mock_upload()
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(10)).set_body_json(
+            json!({
+              "content_uri": "mxc://sdk.rs/media"
+            }),
+        ))
+        .mount()
+        .await;
+
+    let source =
+        AttachmentSource::Data { bytes: b"hello world".to_vec(), filename: "test.bin".to_owned() };
+    let handle = timeline
+        .send_attachment(source, mime::TEXT_PLAIN, AttachmentConfig::default())
+        .use_send_queue()
+        .await?;
+
+    // The upload hasn't completed yet.
+    let progress = handle.progress();
+    assert_ne!(progress.phase, AttachmentUploadPhase::Sent);
+    assert_eq!(progress.total, 11);
+
+    // The local echo appears…
+    assert_let_timeout!(Some(VectorDiff::PushBack { value: item }) = timeline_stream.next());
+    assert!(item.is_local_echo());
+
+    // …and disappears once the upload is cancelled.
+    assert!(handle.cancel().await?);
+    assert_let_timeout!(Some(VectorDiff::Remove { index: 0 }) = timeline_stream.next());
+
+    // It can't be cancelled twice.
+    assert!(!handle.cancel().await?);
+
+    assert_pending!(timeline_stream);
+    Ok(())
+}
+
 #[cfg(feature = "unstable-msc4274")]
 #[async_test]
 async fn test_send_gallery_from_bytes() -> TestResult {
@@ -371,6 +579,15 @@ async fn test_send_gallery_from_bytes() -> TestResult {
     assert_let!(Some(msg) = item.content().as_message());
     assert_eq!(msg.body(), "check out my favourite gifs");
 
+    // The gallery exposes its media in order.
+    assert_let!(Some(media) = msg.gallery_media());
+    assert_eq!(media.len(), 1);
+    assert_eq!(media[0].kind(), GalleryMediaKind::Image);
+    assert_eq!(media[0].filename(), "rickroll.gif");
+    assert_let!(MediaSource::Plain(uri) = media[0].source());
+    assert_eq!(uri.as_str(), "mxc://sdk.rs/rickroll");
+    assert!(media[0].thumbnail_source().is_none());
+
     // No other updates.
     assert_pending!(timeline_stream);
 
@@ -419,6 +636,7 @@ async fn test_send_gallery_from_bytes() -> TestResult {
         // Item has filename and caption
         assert_eq!(filename, file.filename());
         assert_eq!(Some("item caption"), file.caption());
+        assert_eq!(msg.gallery_media().unwrap()[0].caption(), Some("item caption"));
 
         // The URI refers to the local cache.
         assert_let!(MediaSource::Plain(uri) = &file.source);
@@ -433,7 +651,7 @@ async fn test_send_gallery_from_bytes() -> TestResult {
         );
         assert_let!(
             Some(EventSendState::NotSentYet {
-                progress: Some(MediaUploadProgress { index, progress })
+                progress: Some(MediaUploadProgress { index, progress, phase })
             }) = item.send_state()
         );
         assert_let!(Some(msg) = item.content().as_message());
@@ -441,6 +659,7 @@ async fn test_send_gallery_from_bytes() -> TestResult {
         // The upload has finished.
         assert_eq!(*index, 0);
         assert_eq!(progress.current, progress.total);
+        assert_eq!(*phase, AttachmentUploadPhase::Sending);
 
         // Body matches gallery caption.
         assert_eq!(msg.body(), "caption");
@@ -470,7 +689,7 @@ async fn test_send_gallery_from_bytes() -> TestResult {
         assert_let!(Some(msg) = item.content().as_message());
         assert_let!(
             Some(EventSendState::NotSentYet {
-                progress: Some(MediaUploadProgress { index, progress })
+                progress: Some(MediaUploadProgress { index, progress, .. })
             }) = item.send_state()
         );
         assert_eq!(*index, final_index);
@@ -560,3 +779,65 @@ async fn test_react_to_local_media() -> TestResult {
     assert_pending!(timeline_stream);
     Ok(())
 }
+
+#[async_test]
+async fn test_url_previews() -> TestResult {
+    let mock = MatrixMockServer::new().await;
+    let client = mock.client_builder().no_server_versions().build().await;
+
+    mock.mock_versions().ok_custom(&["v1.11"], &Default::default()).mount().await;
+    mock.mock_room_state_encryption().plain().mount().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = mock.sync_joined_room(&client, room_id).await;
+    let timeline = TimelineBuilder::new(&room).with_url_previews().build().await?;
+
+    let (items, mut timeline_stream) =
+        timeline.subscribe_filter_map(|item| item.as_event().cloned()).await;
+    assert!(items.is_empty());
+
+    mock.mock_authenticated_media_preview()
+        .match_url("https://matrix.org")
+        .ok(json!({
+            "og:title": "Matrix.org",
+            "og:description": "An open network for secure, decentralised communication",
+        }))
+        .mock_once()
+        .mount()
+        .await;
+
+    // The preview of this link fails, so it's only tried once.
+    mock.mock_authenticated_media_preview()
+        .match_url("https://example.org")
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "errcode": "M_NOT_FOUND",
+            "error": "Not found",
+        })))
+        .mock_once()
+        .mount()
+        .await;
+
+    let f = EventFactory::new();
+    mock.sync_room(
+        &client,
+        JoinedRoomBuilder::new(room_id).add_timeline_event(
+            f.text_msg("Have a look at https://matrix.org, and https://example.org")
+                .sender(&ALICE)
+                .event_id(event_id!("$event")),
+        ),
+    )
+    .await;
+
+    assert_let_timeout!(Some(VectorDiff::PushBack { value: item }) = timeline_stream.next());
+    assert!(item.url_previews().is_empty());
+
+    // The preview of the first link is added to the item.
+    assert_let_timeout!(Some(VectorDiff::Set { index: 0, value: item }) = timeline_stream.next());
+    assert_let!([preview] = item.url_previews());
+    assert_eq!(preview.url, "https://matrix.org");
+    assert_eq!(preview.title.as_deref(), Some("Matrix.org"));
+
+    sleep(Duration::from_millis(100)).await;
+    assert_pending!(timeline_stream);
+    Ok(())
+}
@@ -20,6 +20,7 @@ use eyeball_im::VectorDiff;
 use futures_util::StreamExt;
 use matrix_sdk::{
     linked_chunk::{ChunkIdentifier, LinkedChunkId, Position, Update},
+    room::ReportedContentScore,
     test_utils::mocks::MatrixMockServer,
 };
 use matrix_sdk_test::{
@@ -29,9 +30,9 @@ use matrix_sdk_test::{
 use matrix_sdk_ui::{
     Timeline,
     timeline::{
-        AnyOtherFullStateEventContent, Error, EventSendState, RedactError, RoomExt,
+        AnyOtherFullStateEventContent, Error, EventSendState, RedactError, ReportError, RoomExt,
         TimelineBuilder, TimelineEventItemId, TimelineFocus, TimelineItemContent,
-        VirtualTimelineItem,
+        TimelineMemoryReport, VirtualTimelineItem,
     },
 };
 use ruma::{
@@ -47,7 +48,7 @@ use sliding_sync::assert_timeline_stream;
 use stream_assert::assert_pending;
 use wiremock::{
     Mock, ResponseTemplate,
-    matchers::{header, method, path_regex},
+    matchers::{body_json, header, method, path_regex},
 };
 
 mod decryption;
@@ -57,12 +58,14 @@ mod focus_event;
 mod media;
 mod pagination;
 mod pinned_event;
+mod preview;
 mod profiles;
 mod queue;
 mod reactions;
 mod read_receipts;
 mod redecryption;
 mod replies;
+mod starred_event;
 mod subscribe;
 mod thread;
 
@@ -216,6 +219,47 @@ async fn test_reaction() {
     assert_pending!(timeline_stream);
 }
 
+#[async_test]
+async fn test_memory_report() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+
+    server.mock_room_state_encryption().plain().mount().await;
+
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    assert_eq!(timeline.memory_report().await, TimelineMemoryReport::default());
+
+    let f = EventFactory::new().sender(&ALICE);
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id)
+                .add_timeline_event(f.text_msg("hello").event_id(event_id!("$1")))
+                .add_timeline_event(f.text_msg("world").event_id(event_id!("$2")))
+                .add_timeline_event(f.reaction(event_id!("$1"), "👍").event_id(event_id!("$3"))),
+        )
+        .await;
+
+    assert_let!(Some(_) = timeline_stream.next().await);
+
+    // The reaction is a remote event without an item, and the date divider is an
+    // item which isn't an event.
+    assert_eq!(
+        timeline.memory_report().await,
+        TimelineMemoryReport {
+            items: timeline.items().await.len(),
+            event_items: 2,
+            local_echoes: 0,
+            remote_events: 3,
+        }
+    );
+}
+
 #[async_test]
 async fn test_redacted_message() {
     let server = MatrixMockServer::new().await;
@@ -413,6 +457,53 @@ async fn test_redact_nonexisting_item() {
     assert_matches!(error, Some(Error::RedactError(RedactError::ItemNotFound(_))));
 }
 
+#[async_test]
+async fn test_report_event() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+
+    server.mock_room_state_encryption().plain().mount().await;
+
+    let timeline = room.timeline().await.unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    let f = EventFactory::new();
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id).add_timeline_event(
+                f.text_msg("buy my bitcoins bro")
+                    .sender(user_id!("@a:b.com"))
+                    .event_id(event_id!("$offensive_event")),
+            ),
+        )
+        .await;
+
+    assert_let!(Some(_) = timeline_stream.next().await);
+
+    Mock::given(method("POST"))
+        .and(path_regex(r"^/_matrix/client/.*/rooms/.*/report/\$offensive_event"))
+        .and(body_json(json!({ "reason": "spam", "score": -80 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .mount(server.server())
+        .await;
+
+    let item_id = TimelineEventItemId::EventId(owned_event_id!("$offensive_event"));
+    let score = ReportedContentScore::new(-80).unwrap();
+    timeline.report(&item_id, Some(score), Some("spam".to_owned())).await.unwrap();
+
+    // An event which isn't in the timeline can't be reported.
+    let error = timeline
+        .report(&TimelineEventItemId::EventId(owned_event_id!("$123:example.com")), None, None)
+        .await
+        .err();
+    assert_matches!(error, Some(Error::ReportError(ReportError::ItemNotFound(_))));
+}
+
 #[async_test]
 async fn test_read_marker() {
     let server = MatrixMockServer::new().await;
@@ -626,6 +717,61 @@ async fn test_pin_event_is_returning_false_because_is_already_pinned() {
     assert!(!timeline.pin_event(event_id).await.unwrap());
 }
 
+#[async_test]
+async fn test_pin_event_is_sent_again_after_a_concurrent_change() {
+    let mut setup = PinningTestSetup::new().await;
+    let timeline = setup.timeline().await;
+
+    setup.mock_sync(false).await;
+    assert!(!timeline.items().await.is_empty());
+
+    // The list is empty when it's first loaded, but another client pins an event
+    // before we send ours, so it's loaded again with the other event only.
+    for pinned in [json!([]), json!(["$other"]), json!(["$other", "$a"])] {
+        Mock::given(method("GET"))
+            .and(path_regex(r"^/_matrix/client/v3/rooms/.*/state/m.room.pinned_events/.*"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "pinned": pinned })))
+            .up_to_n_times(1)
+            .mount(setup.server.server())
+            .await;
+    }
+
+    // So our list is sent twice.
+    setup.server.mock_set_room_pinned_events().ok(owned_event_id!("$42")).expect(2).mount().await;
+
+    let event_id = setup.event_id();
+    assert!(timeline.pin_event(event_id).await.unwrap());
+
+    // The room's pinned events are updated without waiting for the sync.
+    let room = setup.client.get_room(setup.room_id).unwrap();
+    assert_eq!(
+        room.pinned_event_ids().unwrap(),
+        [owned_event_id!("$other"), owned_event_id!("$a")]
+    );
+}
+
+#[async_test]
+async fn test_pin_event_gives_up_after_too_many_concurrent_changes() {
+    let mut setup = PinningTestSetup::new().await;
+    let timeline = setup.timeline().await;
+
+    setup.mock_sync(false).await;
+    assert!(!timeline.items().await.is_empty());
+
+    // Another client keeps overwriting our list with its own.
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/_matrix/client/v3/rooms/.*/state/m.room.pinned_events/.*"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "pinned": ["$other"] })))
+        .mount(setup.server.server())
+        .await;
+
+    // So our list is sent a few times, before giving up.
+    setup.server.mock_set_room_pinned_events().ok(owned_event_id!("$42")).expect(3).mount().await;
+
+    let event_id = setup.event_id();
+    assert_matches!(timeline.pin_event(event_id).await, Err(matrix_sdk::Error::PinningConflict));
+}
+
 #[async_test]
 async fn test_pin_event_is_returning_an_error() {
     let mut setup = PinningTestSetup::new().await;
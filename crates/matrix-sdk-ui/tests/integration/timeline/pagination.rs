@@ -31,13 +31,16 @@ use matrix_sdk::{
     },
 };
 use matrix_sdk_test::{
-    ALICE, BOB, JoinedRoomBuilder, StateTestEvent, SyncResponseBuilder, async_test,
-    event_factory::EventFactory, mocks::mock_encryption_state,
+    ALICE, BOB, JoinedRoomBuilder, RoomAccountDataTestEvent, StateTestEvent, SyncResponseBuilder,
+    async_test, event_factory::EventFactory, mocks::mock_encryption_state,
+};
+use matrix_sdk_ui::timeline::{
+    AnyOtherFullStateEventContent, RoomExt, TimelineItemContent, TimelinePaginationStatus,
+    VirtualTimelineItem,
 };
-use matrix_sdk_ui::timeline::{AnyOtherFullStateEventContent, RoomExt, TimelineItemContent};
 use once_cell::sync::Lazy;
 use ruma::{
-    EventId,
+    EventId, event_id,
     events::{FullStateEventContent, room::message::MessageType},
     room_id,
 };
@@ -683,8 +686,11 @@ async fn test_timeline_reset_while_paginating() {
     assert!(hit_start);
 
     // No events in back-pagination responses, start of timeline + date divider +
-    // all events from the previous syncs are present.
-    assert_eq!(timeline.items().await.len(), 4);
+    // all events from the previous syncs are present. The reset item of the second
+    // limited sync has been removed, since the gap has been back-paginated.
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 4);
+    assert!(!items.iter().any(|item| item.is_timeline_reset()));
 
     // Make sure both pagination mocks were called.
     server.verify().await;
@@ -1433,3 +1439,128 @@ async fn test_timeline_start_properly_inserted_when_created() {
     assert!(items[0].is_timeline_start());
     assert_pending!(stream2);
 }
+
+#[async_test]
+async fn test_paginate_backwards_to_read_marker() {
+    let room_id = room_id!("!foo:bar.baz");
+
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    client.event_cache().subscribe().unwrap();
+
+    let f = EventFactory::new().room(room_id).sender(*BOB);
+
+    // The fully-read event is two batches away from the latest event.
+    let room = server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id)
+                .add_account_data(RoomAccountDataTestEvent::Custom(json!({
+                    "type": "m.fully_read",
+                    "content": { "event_id": "$1" },
+                })))
+                .add_timeline_event(f.text_msg("3").event_id(event_id!("$3")))
+                .set_timeline_prev_batch("prev1"),
+        )
+        .await;
+
+    server.mock_room_state_encryption().plain().mount().await;
+
+    server
+        .mock_room_messages()
+        .match_from("prev1")
+        .ok(RoomMessagesResponseTemplate::default()
+            .events(vec![f.text_msg("2").event_id(event_id!("$2"))])
+            .end_token("prev2"))
+        .mock_once()
+        .mount()
+        .await;
+
+    server
+        .mock_room_messages()
+        .match_from("prev2")
+        .ok(RoomMessagesResponseTemplate::default()
+            .events(vec![f.text_msg("1").event_id(event_id!("$1"))]))
+        .mock_once()
+        .mount()
+        .await;
+
+    let timeline = room.timeline_builder().track_read_marker_and_receipts().build().await.unwrap();
+
+    let unique_id = timeline.paginate_backwards_to_read_marker(1).await.unwrap().unwrap();
+
+    // The read marker is right after the fully-read event.
+    let items = timeline.items().await;
+    let index = items.iter().position(|item| *item.unique_id() == unique_id).unwrap();
+    assert_matches!(items[index].as_virtual(), Some(VirtualTimelineItem::ReadMarker));
+    assert_eq!(items[index - 1].as_event().unwrap().event_id(), Some(event_id!("$1")));
+    assert_eq!(items[index + 1].as_event().unwrap().event_id(), Some(event_id!("$2")));
+}
+
+#[async_test]
+async fn test_pagination_status() {
+    let room_id = room_id!("!foo:bar.baz");
+
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    client.event_cache().subscribe().unwrap();
+
+    let f = EventFactory::new().room(room_id).sender(*BOB);
+
+    let room = server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id)
+                .add_timeline_event(f.text_msg("2").event_id(event_id!("$2")))
+                .set_timeline_prev_batch("prev1"),
+        )
+        .await;
+
+    server.mock_room_state_encryption().plain().mount().await;
+
+    let timeline = room.timeline().await.unwrap();
+
+    // A live timeline is always at the end of the room.
+    let (forward_status, _) = timeline.forward_pagination_status();
+    assert_eq!(forward_status, TimelinePaginationStatus::ReachedEnd);
+
+    let (back_status, mut back_status_stream) = timeline.back_pagination_status();
+    assert_eq!(back_status, TimelinePaginationStatus::Idle);
+    assert_pending!(back_status_stream);
+
+    // A failed pagination is reported as such…
+    server
+        .mock_room_messages()
+        .match_from("prev1")
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errcode": "M_UNKNOWN",
+            "error": "Something went wrong",
+        })))
+        .mock_once()
+        .mount()
+        .await;
+
+    timeline.paginate_backwards(10).await.unwrap_err();
+
+    assert_let_timeout!(Some(back_status) = back_status_stream.next());
+    assert_matches!(back_status, TimelinePaginationStatus::Failed { .. });
+
+    // …and the next successful one replaces the error.
+    server
+        .mock_room_messages()
+        .match_from("prev1")
+        .ok(RoomMessagesResponseTemplate::default()
+            .events(vec![f.text_msg("1").event_id(event_id!("$1"))]))
+        .mock_once()
+        .mount()
+        .await;
+
+    let hit_start = timeline.paginate_backwards(10).await.unwrap();
+    assert!(hit_start);
+
+    assert_let_timeout!(Some(back_status) = back_status_stream.next());
+    assert_eq!(back_status, TimelinePaginationStatus::ReachedEnd);
+    assert_pending!(back_status_stream);
+}
@@ -0,0 +1,120 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use matrix_sdk::test_utils::mocks::{MatrixMockServer, RoomMessagesResponseTemplate};
+use matrix_sdk_base::RoomState;
+use matrix_sdk_test::{ALICE, BOB, LeftRoomBuilder, async_test, event_factory::EventFactory};
+use matrix_sdk_ui::timeline::{TimelineBuilder, TimelineFocus};
+use ruma::room_id;
+
+#[async_test]
+async fn test_preview_timeline() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+    let room_id = room_id!("!test:localhost");
+
+    let room = server.sync_room(&client, LeftRoomBuilder::new(room_id)).await;
+    server.mock_room_state_encryption().plain().mount().await;
+
+    let f = EventFactory::new().room(room_id);
+
+    // The most recent events are loaded first, in reverse topological order.
+    server
+        .mock_room_messages()
+        .ok(RoomMessagesResponseTemplate::default()
+            .events(vec![
+                f.text_msg("it doesn't even matter").sender(*BOB),
+                f.text_msg("in the end").sender(*ALICE),
+            ])
+            .end_token("prev"))
+        .mock_once()
+        .mount()
+        .await;
+
+    let timeline = TimelineBuilder::new(&room)
+        .with_focus(TimelineFocus::Preview { num_events: 20 })
+        .build()
+        .await
+        .unwrap();
+
+    let items = timeline.items().await;
+
+    assert_eq!(items.len(), 2 + 1); // event items + a date divider
+    assert!(items[0].is_date_divider());
+    assert_eq!(items[1].as_event().unwrap().content().as_message().unwrap().body(), "in the end");
+    assert_eq!(
+        items[2].as_event().unwrap().content().as_message().unwrap().body(),
+        "it doesn't even matter"
+    );
+
+    // There's nothing after the most recent events.
+    assert!(timeline.paginate_forwards(20).await.unwrap());
+
+    // But the older events can be loaded.
+    server
+        .mock_room_messages()
+        .match_from("prev")
+        .ok(RoomMessagesResponseTemplate::default()
+            .events(vec![f.text_msg("i tried so hard").sender(*ALICE)]))
+        .mock_once()
+        .mount()
+        .await;
+
+    let hit_start = timeline.paginate_backwards(20).await.unwrap();
+    assert!(hit_start);
+
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 3 + 1); // event items + a date divider
+    assert!(items[0].is_date_divider());
+    assert_eq!(
+        items[1].as_event().unwrap().content().as_message().unwrap().body(),
+        "i tried so hard"
+    );
+}
+
+#[async_test]
+async fn test_preview_timeline_for_unknown_room() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+    let room_id = room_id!("!test:localhost");
+
+    // The room isn't known locally, only its preview is.
+    server.mock_room_summary().ok(room_id).mock_once().mount().await;
+    server.mock_room_state_encryption().plain().mount().await;
+
+    let room_preview = client.get_room_preview(room_id.into(), Vec::new()).await.unwrap();
+    assert!(client.get_room(room_id).is_none());
+
+    let f = EventFactory::new().room(room_id);
+
+    server
+        .mock_room_messages()
+        .ok(RoomMessagesResponseTemplate::default()
+            .events(vec![f.text_msg("in the end").sender(*ALICE)])
+            .end_token("prev"))
+        .mock_once()
+        .mount()
+        .await;
+
+    let timeline =
+        TimelineBuilder::for_room_preview(&client, &room_preview, 20).build().await.unwrap();
+
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 1 + 1); // event item + a date divider
+    assert!(items[0].is_date_divider());
+    assert_eq!(items[1].as_event().unwrap().content().as_message().unwrap().body(), "in the end");
+
+    // The room has been created in memory for the timeline, without a membership.
+    assert_eq!(client.get_room(room_id).unwrap().state(), RoomState::Left);
+}
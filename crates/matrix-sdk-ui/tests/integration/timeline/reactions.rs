@@ -18,7 +18,7 @@ use assert_matches2::{assert_let, assert_matches};
 use eyeball_im::VectorDiff;
 use futures_util::StreamExt as _;
 use matrix_sdk::{assert_let_timeout, test_utils::mocks::MatrixMockServer};
-use matrix_sdk_test::{ALICE, JoinedRoomBuilder, async_test, event_factory::EventFactory};
+use matrix_sdk_test::{ALICE, BOB, JoinedRoomBuilder, async_test, event_factory::EventFactory};
 use matrix_sdk_ui::timeline::{EventSendState, ReactionStatus, RoomExt as _};
 use ruma::{event_id, events::room::message::RoomMessageEventContent, room_id};
 use serde_json::json;
@@ -441,3 +441,62 @@ async fn test_local_reaction_to_local_echo() {
     tokio::time::sleep(Duration::from_millis(150)).await;
     assert_pending!(stream);
 }
+
+#[async_test]
+async fn test_reactions_are_aggregated_again_after_a_reset() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+
+    server.mock_room_state_encryption().plain().mount().await;
+
+    let timeline = room.timeline().await.unwrap();
+    let (initial_items, mut stream) = timeline.subscribe().await;
+
+    assert!(initial_items.is_empty());
+
+    let f = EventFactory::new();
+    let event_id = event_id!("$1");
+
+    // A reaction is received before the event it relates to…
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id).add_timeline_event(
+                f.reaction(event_id, "👍").sender(&BOB).event_id(event_id!("$2")),
+            ),
+        )
+        .await;
+
+    // …which is received in a limited sync, after a gap.
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id)
+                .add_timeline_event(f.text_msg("hello").sender(&ALICE).event_id(event_id))
+                .set_timeline_prev_batch("prev-batch-token")
+                .set_timeline_limited(),
+        )
+        .await;
+
+    // The timeline is reset, and the reset item is inserted before the event.
+    assert_let_timeout!(Some(timeline_updates) = stream.next());
+    assert_matches!(&timeline_updates[0], VectorDiff::Clear);
+
+    let items = timeline.items().await;
+    assert!(items[0].is_timeline_reset());
+    assert!(items[1].is_date_divider());
+    assert_eq!(items[2].as_event().unwrap().event_id(), Some(event_id));
+
+    // The reaction, which isn't part of the events received after the reset, is
+    // aggregated again from the event cache.
+    assert_let_timeout!(Some(timeline_updates) = stream.next());
+    assert_eq!(timeline_updates.len(), 1);
+
+    assert_let!(VectorDiff::Set { index: 2, value: item } = &timeline_updates[0]);
+    let reactions = item.as_event().unwrap().content().reactions().cloned().unwrap_or_default();
+    assert_eq!(reactions.len(), 1);
+    reactions.get("👍").unwrap().get(*BOB).unwrap();
+}
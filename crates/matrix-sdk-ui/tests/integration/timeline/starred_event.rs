@@ -0,0 +1,107 @@
+use assert_matches2::assert_let;
+use eyeball_im::VectorDiff;
+use futures_util::StreamExt as _;
+use matrix_sdk::test_utils::mocks::MatrixMockServer;
+use matrix_sdk_base::deserialized_responses::TimelineEvent;
+use matrix_sdk_test::{
+    BOB, JoinedRoomBuilder, RoomAccountDataTestEvent, async_test, event_factory::EventFactory,
+};
+use matrix_sdk_ui::timeline::{TimelineBuilder, TimelineFocus};
+use ruma::{MilliSecondsSinceUnixEpoch, event_id, room_id};
+use serde_json::json;
+use stream_assert::assert_pending;
+
+#[async_test]
+async fn test_new_starred_event_ids_reload_the_timeline() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+    let room_id = room_id!("!test:localhost");
+
+    let f = EventFactory::new().room(room_id).sender(*BOB);
+
+    for (event_id, body) in [(event_id!("$1"), "in the end"), (event_id!("$2"), "it doesn't")] {
+        server
+            .mock_room_event()
+            .room(room_id.to_owned())
+            .match_event_id()
+            .ok(TimelineEvent::from_plaintext(
+                f.text_msg(body)
+                    .event_id(event_id)
+                    .server_ts(MilliSecondsSinceUnixEpoch::now())
+                    .into_raw_sync(),
+            ))
+            .mount()
+            .await;
+    }
+
+    let starred_events = |event_ids: &[&str]| {
+        JoinedRoomBuilder::new(room_id).add_account_data(RoomAccountDataTestEvent::Custom(json!({
+            "type": "org.matrix.room.starred_events",
+            "content": { "event_ids": event_ids },
+        })))
+    };
+
+    let room = server.sync_room(&client, starred_events(&["$1"])).await;
+
+    let timeline = TimelineBuilder::new(&room)
+        .with_focus(TimelineFocus::StarredEvents {
+            max_events_to_load: 100,
+            max_concurrent_requests: 10,
+        })
+        .build()
+        .await
+        .unwrap();
+
+    let (items, mut timeline_stream) = timeline.subscribe().await;
+
+    assert_eq!(items.len(), 1 + 1); // event item + a date divider
+    assert!(items[0].is_date_divider());
+    assert_eq!(items[1].as_event().unwrap().content().as_message().unwrap().body(), "in the end");
+    assert_pending!(timeline_stream);
+
+    // Starring another event reloads the timeline.
+    server.sync_room(&client, starred_events(&["$1", "$2"])).await;
+
+    assert_let!(Some(timeline_updates) = timeline_stream.next().await);
+    assert_eq!(timeline_updates.len(), 4);
+
+    assert_let!(VectorDiff::Clear = &timeline_updates[0]);
+
+    assert_let!(VectorDiff::PushBack { value } = &timeline_updates[1]);
+    assert_eq!(value.as_event().unwrap().event_id().unwrap(), event_id!("$1"));
+
+    assert_let!(VectorDiff::PushBack { value } = &timeline_updates[2]);
+    assert_eq!(value.as_event().unwrap().event_id().unwrap(), event_id!("$2"));
+
+    assert_let!(VectorDiff::PushFront { value } = &timeline_updates[3]);
+    assert!(value.is_date_divider());
+
+    // Removing all the stars empties the timeline.
+    server.sync_room(&client, starred_events(&[])).await;
+
+    assert_let!(Some(timeline_updates) = timeline_stream.next().await);
+    assert_eq!(timeline_updates.len(), 1);
+    assert_let!(VectorDiff::Clear = &timeline_updates[0]);
+
+    assert_pending!(timeline_stream);
+}
+
+#[async_test]
+async fn test_starred_timeline_without_starred_events_is_empty() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+    let room_id = room_id!("!test:localhost");
+
+    let room = server.sync_joined_room(&client, room_id).await;
+
+    let timeline = TimelineBuilder::new(&room)
+        .with_focus(TimelineFocus::StarredEvents {
+            max_events_to_load: 100,
+            max_concurrent_requests: 10,
+        })
+        .build()
+        .await
+        .unwrap();
+
+    assert!(timeline.items().await.is_empty());
+}
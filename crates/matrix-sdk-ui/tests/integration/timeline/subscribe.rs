@@ -19,20 +19,24 @@ use assert_matches2::assert_let;
 use eyeball_im::VectorDiff;
 use futures_util::StreamExt;
 use matrix_sdk::{
+    assert_let_timeout,
     config::{SyncSettings, SyncToken},
-    test_utils::logged_in_client_with_server,
+    test_utils::{logged_in_client_with_server, mocks::MatrixMockServer},
 };
 use matrix_sdk_common::executor::spawn;
 use matrix_sdk_test::{
     ALICE, BOB, JoinedRoomBuilder, SyncResponseBuilder, async_test, event_factory::EventFactory,
     mocks::mock_encryption_state,
 };
-use matrix_sdk_ui::timeline::{RoomExt, TimelineDetails};
+use matrix_sdk_ui::timeline::{RoomExt, TimelineDetails, TimelineItemContent};
 use ruma::{
     event_id,
-    events::room::{
-        member::MembershipState,
-        message::{MessageType, RoomMessageEventContent},
+    events::{
+        AnySyncTimelineEvent,
+        room::{
+            member::MembershipState,
+            message::{MessageType, RoomMessageEventContent},
+        },
     },
     room_id, user_id,
 };
@@ -174,6 +178,74 @@ async fn test_event_filter() {
     assert_pending!(timeline_stream);
 }
 
+#[async_test]
+async fn test_set_event_filter_rebuilds_the_timeline() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+    server.mock_room_state_encryption().plain().mount().await;
+
+    // Only show the message-like events at first.
+    let timeline = room
+        .timeline_builder()
+        .event_filter(|event, _| matches!(event, AnySyncTimelineEvent::MessageLike(_)))
+        .build()
+        .await
+        .unwrap();
+    let (_, mut timeline_stream) = timeline.subscribe().await;
+
+    let f = EventFactory::new().room(room_id);
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id)
+                .add_timeline_event(f.text_msg("hello").sender(&ALICE))
+                .add_timeline_event(
+                    f.member(&BOB)
+                        .membership(MembershipState::Join)
+                        .previous(MembershipState::Invite),
+                ),
+        )
+        .await;
+
+    // Wait for the timeline to see the sync response.
+    assert_let_timeout!(Some(_) = timeline_stream.next());
+
+    // The message and its date divider.
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_date_divider());
+    assert_eq!(items[1].as_event().unwrap().sender(), *ALICE);
+
+    // When all the events are shown, the membership change appears, as it's
+    // reloaded from the event cache.
+    timeline.set_event_filter(|_, _| true).await.unwrap();
+
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 3);
+    assert!(items[0].is_date_divider());
+    assert_eq!(items[1].as_event().unwrap().sender(), *ALICE);
+    assert_matches!(
+        items[2].as_event().unwrap().content(),
+        TimelineItemContent::MembershipChange(_)
+    );
+
+    // Hiding the messages rebuilds the timeline too.
+    timeline
+        .set_event_filter(|event, _| matches!(event, AnySyncTimelineEvent::State(_)))
+        .await
+        .unwrap();
+
+    let items = timeline.items().await;
+    assert_eq!(items.len(), 2);
+    assert_matches!(
+        items[1].as_event().unwrap().content(),
+        TimelineItemContent::MembershipChange(_)
+    );
+}
+
 #[async_test]
 async fn test_timeline_is_reset_when_a_user_is_ignored_or_unignored() {
     let room_id = room_id!("!a98sd12bjh:example.org");
@@ -18,7 +18,7 @@ use assert_matches2::{assert_let, assert_matches};
 use eyeball_im::VectorDiff;
 use futures_util::StreamExt as _;
 use matrix_sdk::{
-    Client, ThreadingSupport, assert_let_timeout,
+    Client, ComposerDraft, ComposerDraftType, ThreadingSupport, assert_let_timeout,
     test_utils::mocks::{MatrixMockServer, RoomRelationsResponseTemplate},
 };
 use matrix_sdk_test::{
@@ -385,8 +385,15 @@ async fn test_new_thread_reply_causes_thread_summary_update() {
     assert_eq!(latest_event.sender, *BOB);
     assert!(latest_event.sender_profile.is_unavailable());
 
-    // The thread summary contains the number of replies.
+    // The thread summary contains the number of replies and participants.
     assert_eq!(summary.num_replies, 1);
+    assert_eq!(summary.num_participants, Some(1));
+
+    // The reply has been sent by someone else, and we haven't read it yet.
+    assert!(summary.is_unread);
+
+    // The summary can also be read from the item directly.
+    assert_eq!(event_item.thread_summary().unwrap().num_replies, 1);
 
     assert_pending!(stream);
 
@@ -443,8 +450,64 @@ async fn test_new_thread_reply_causes_thread_summary_update() {
     assert_eq!(latest_event.sender, *BOB);
     assert!(latest_event.sender_profile.is_unavailable());
 
-    // The number of replies has been updated.
+    // The number of replies has been updated, but Bob is still the only
+    // participant.
+    assert_eq!(summary.num_replies, 2);
+    assert_eq!(summary.num_participants, Some(1));
+    assert!(summary.is_unread);
+
+    assert_pending!(stream);
+
+    // Our own read receipt on an older reply of the thread doesn't mark it as read.
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id).add_receipt(
+                f.read_receipts()
+                    .add(
+                        reply_event_id,
+                        client.user_id().unwrap(),
+                        ReceiptType::Read,
+                        ReceiptThread::Thread(thread_event_id.to_owned()),
+                    )
+                    .into_event(),
+            ),
+        )
+        .await;
+
+    yield_now().await;
+    assert_pending!(stream);
+
+    // Once our own read receipt in the thread reaches the latest reply, the thread
+    // isn't unread anymore.
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id).add_receipt(
+                f.read_receipts()
+                    .add(
+                        another_reply_event_id,
+                        client.user_id().unwrap(),
+                        ReceiptType::Read,
+                        ReceiptThread::Thread(thread_event_id.to_owned()),
+                    )
+                    .into_event(),
+            ),
+        )
+        .await;
+
+    assert_let_timeout!(Some(timeline_updates) = stream.next());
+    assert_eq!(timeline_updates.len(), 1);
+
+    assert_let!(VectorDiff::Set { index: 1, value } = &timeline_updates[0]);
+    let event_item = value.as_event().unwrap();
+    assert_eq!(event_item.event_id().unwrap(), thread_event_id);
+
+    assert_let!(Some(summary) = event_item.content().thread_summary());
     assert_eq!(summary.num_replies, 2);
+    assert!(summary.is_unread.not());
+
+    assert_pending!(stream);
 }
 
 #[async_test]
@@ -1491,3 +1554,63 @@ async fn test_send_read_receipts() {
     let did_send = timeline.mark_as_read(SendReceiptType::Read).await.unwrap();
     assert!(did_send.not());
 }
+
+#[async_test]
+async fn test_thread_draft_is_separate_from_the_room_draft() {
+    let server = MatrixMockServer::new().await;
+    let client = client_with_threading_support(&server).await;
+
+    let room_id = room_id!("!a:b.c");
+    let thread_root_event_id = owned_event_id!("$root");
+
+    let room = server.sync_joined_room(&client, room_id).await;
+
+    let room_timeline = room.timeline().await.unwrap();
+    let thread_timeline = TimelineBuilder::new(&room)
+        .with_focus(TimelineFocus::Thread { root_event_id: thread_root_event_id.clone() })
+        .build()
+        .await
+        .unwrap();
+
+    // At first, there are no drafts.
+    assert!(room_timeline.load_draft().await.unwrap().is_none());
+    assert!(thread_timeline.load_draft().await.unwrap().is_none());
+
+    let room_draft = ComposerDraft {
+        plain_text: "Hello, room!".to_owned(),
+        html_text: None,
+        draft_type: ComposerDraftType::NewMessage,
+    };
+    let thread_draft = ComposerDraft {
+        plain_text: "Hello, thread!".to_owned(),
+        html_text: Some("<b>Hello, thread!</b>".to_owned()),
+        draft_type: ComposerDraftType::Reply { event_id: thread_root_event_id.clone() },
+    };
+
+    // Each timeline saves its own draft.
+    room_timeline.save_draft(room_draft.clone()).await.unwrap();
+    thread_timeline.save_draft(thread_draft.clone()).await.unwrap();
+
+    assert_eq!(room_timeline.load_draft().await.unwrap(), Some(room_draft.clone()));
+    assert_eq!(thread_timeline.load_draft().await.unwrap(), Some(thread_draft.clone()));
+
+    // Another timeline on the same thread restores the thread draft.
+    let other_thread_timeline = TimelineBuilder::new(&room)
+        .with_focus(TimelineFocus::Thread { root_event_id: thread_root_event_id })
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(other_thread_timeline.load_draft().await.unwrap(), Some(thread_draft));
+
+    // Clearing the thread draft doesn't touch the room draft.
+    thread_timeline.clear_draft().await.unwrap();
+
+    assert!(thread_timeline.load_draft().await.unwrap().is_none());
+    assert!(other_thread_timeline.load_draft().await.unwrap().is_none());
+    assert_eq!(room_timeline.load_draft().await.unwrap(), Some(room_draft));
+
+    // And the room draft can be cleared too.
+    room_timeline.clear_draft().await.unwrap();
+
+    assert!(room_timeline.load_draft().await.unwrap().is_none());
+}
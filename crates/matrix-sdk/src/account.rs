@@ -14,6 +14,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
 use futures_core::Stream;
 use futures_util::{StreamExt, stream};
 #[cfg(feature = "experimental-element-recent-emojis")]
@@ -24,6 +26,7 @@ use js_int::uint;
 use matrix_sdk_base::recent_emojis::RecentEmojisContent;
 use matrix_sdk_base::{
     StateStoreDataKey, StateStoreDataValue,
+    breadcrumbs::BreadcrumbsEventContent,
     media::{MediaFormat, MediaRequestParameters},
     store::StateStoreExt,
 };
@@ -37,7 +40,7 @@ use ruma::{
             add_3pid, change_password, deactivate, delete_3pid, get_3pids,
             request_3pid_management_token_via_email, request_3pid_management_token_via_msisdn,
         },
-        config::{get_global_account_data, set_global_account_data},
+        config::{get_global_account_data, set_global_account_data, set_room_account_data},
         error::ErrorKind,
         profile::{
             get_avatar_url, get_display_name, get_profile, set_avatar_url, set_display_name,
@@ -46,8 +49,9 @@ use ruma::{
     },
     assign,
     events::{
-        AnyGlobalAccountDataEventContent, GlobalAccountDataEvent, GlobalAccountDataEventContent,
-        GlobalAccountDataEventType, StaticEventContent,
+        AnyGlobalAccountDataEventContent, AnyRoomAccountDataEventContent, GlobalAccountDataEvent,
+        GlobalAccountDataEventContent, GlobalAccountDataEventType, RoomAccountDataEventType,
+        StaticEventContent,
         ignored_user_list::{IgnoredUser, IgnoredUserListEventContent},
         media_preview_config::{
             InviteAvatars, MediaPreviewConfigEventContent, MediaPreviews,
@@ -60,7 +64,7 @@ use ruma::{
     serde::Raw,
     thirdparty::Medium,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::error;
 
 use crate::{Client, Error, Result, config::RequestConfig};
@@ -80,7 +84,7 @@ pub struct Account {
 
 impl Account {
     /// The maximum number of visited room identifiers to keep in the state
-    /// store.
+    /// store, and in the breadcrumbs.
     const VISITED_ROOMS_LIMIT: usize = 20;
 
     pub(crate) fn new(client: Client) -> Self {
@@ -854,6 +858,172 @@ impl Account {
         Ok(self.client.send(request).await?)
     }
 
+    /// Export the account data of the user, from storage, to a bundle that can
+    /// be serialized to JSON.
+    ///
+    /// Only the event types listed in the `filter` are exported, or all of
+    /// them with [`AccountDataFilter::all`]. The room account data is exported
+    /// for all the rooms known by the client.
+    ///
+    /// The account data managed by the server, like `m.push_rules` and
+    /// `m.fully_read`, is never exported, since it can't be imported back with
+    /// the account data API.
+    ///
+    /// The bundle can be imported later, possibly on another account, with
+    /// [`Account::import_account_data`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use matrix_sdk::Client;
+    /// # async {
+    /// # let client = Client::new("http://localhost:8080".parse()?).await?;
+    /// # let account = client.account();
+    /// use matrix_sdk::AccountDataFilter;
+    ///
+    /// let bundle =
+    ///     account.export_account_data(&AccountDataFilter::default()).await?;
+    /// let json = serde_json::to_string(&bundle)?;
+    /// # anyhow::Ok(()) };
+    /// ```
+    pub async fn export_account_data(
+        &self,
+        filter: &AccountDataFilter,
+    ) -> Result<AccountDataBundle> {
+        let mut bundle = AccountDataBundle::default();
+
+        if filter.all {
+            for event in self.client.state_store().get_account_data_events().await? {
+                let (event_type, content) = get_raw_type_and_content(event)?;
+                bundle.global.insert(event_type, content);
+            }
+        } else {
+            for event_type in &filter.global {
+                if let Some(content) = self.account_data_raw(event_type.clone()).await? {
+                    bundle.global.insert(event_type.to_string(), content);
+                }
+            }
+        }
+
+        bundle.global.retain(|event_type, _| !is_server_managed_global_account_data(event_type));
+
+        if filter.all || !filter.room.is_empty() {
+            for room in self.client.rooms() {
+                let mut room_account_data = BTreeMap::new();
+
+                if filter.all {
+                    for event in self
+                        .client
+                        .state_store()
+                        .get_room_account_data_events(room.room_id())
+                        .await?
+                    {
+                        let (event_type, content) = get_raw_type_and_content(event)?;
+                        room_account_data.insert(event_type, content);
+                    }
+                } else {
+                    for event_type in &filter.room {
+                        if let Some(content) =
+                            get_raw_content(room.account_data(event_type.clone()).await?)?
+                        {
+                            room_account_data.insert(event_type.to_string(), content);
+                        }
+                    }
+                }
+
+                room_account_data
+                    .retain(|event_type, _| !is_server_managed_room_account_data(event_type));
+
+                if !room_account_data.is_empty() {
+                    bundle.rooms.insert(room.room_id().to_owned(), room_account_data);
+                }
+            }
+        }
+
+        Ok(bundle)
+    }
+
+    /// Import a bundle of account data, previously exported with
+    /// [`Account::export_account_data`], on the homeserver.
+    ///
+    /// Only the event types listed in the `filter` are imported, which allows
+    /// to import the bundle selectively, or all of them with
+    /// [`AccountDataFilter::all`]. The room account data is imported even if
+    /// the user isn't a member of the room yet.
+    ///
+    /// The imported account data replaces any existing account data of the
+    /// same type. The account data managed by the server, like `m.push_rules`
+    /// and `m.fully_read`, is skipped.
+    ///
+    /// The import doesn't stop at the first event rejected by the homeserver:
+    /// the returned [`AccountDataImportReport`] lists the events that were
+    /// imported, skipped, or that failed to be imported.
+    pub async fn import_account_data(
+        &self,
+        bundle: &AccountDataBundle,
+        filter: &AccountDataFilter,
+    ) -> Result<AccountDataImportReport> {
+        let own_user = self.client.user_id().ok_or(Error::AuthenticationRequired)?;
+        let mut report = AccountDataImportReport::default();
+
+        for (event_type, content) in &bundle.global {
+            let event_type = GlobalAccountDataEventType::from(event_type.as_str());
+
+            if !filter.all && !filter.global.contains(&event_type) {
+                continue;
+            }
+
+            let entry =
+                AccountDataImportEntry { room_id: None, event_type: event_type.to_string() };
+
+            if is_server_managed_global_account_data(&entry.event_type) {
+                report.skipped.push(entry);
+                continue;
+            }
+
+            match self.set_account_data_raw(event_type, content.clone()).await {
+                Ok(_) => report.imported.push(entry),
+                Err(error) => report.failed.push(AccountDataImportFailure { entry, error }),
+            }
+        }
+
+        for (room_id, room_account_data) in &bundle.rooms {
+            for (event_type, content) in room_account_data {
+                let event_type = RoomAccountDataEventType::from(event_type.as_str());
+
+                if !filter.all && !filter.room.contains(&event_type) {
+                    continue;
+                }
+
+                let entry = AccountDataImportEntry {
+                    room_id: Some(room_id.clone()),
+                    event_type: event_type.to_string(),
+                };
+
+                if is_server_managed_room_account_data(&entry.event_type) {
+                    report.skipped.push(entry);
+                    continue;
+                }
+
+                let request = set_room_account_data::v3::Request::new_raw(
+                    own_user.to_owned(),
+                    room_id.clone(),
+                    event_type,
+                    content.clone(),
+                );
+
+                match self.client.send(request).await {
+                    Ok(_) => report.imported.push(entry),
+                    Err(error) => {
+                        report.failed.push(AccountDataImportFailure { entry, error: error.into() })
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
     /// Marks the room identified by `room_id` as a "direct chat" with each
     /// user in `user_ids`.
     ///
@@ -1015,6 +1185,67 @@ impl Account {
         Ok(())
     }
 
+    /// Get the breadcrumbs, i.e. the rooms the user visited recently on any of
+    /// their devices, the most recent first, from storage.
+    ///
+    /// See [`Client::track_room_visit`] to update them.
+    pub async fn get_breadcrumbs(&self) -> Result<Vec<OwnedRoomId>> {
+        Ok(self
+            .account_data::<BreadcrumbsEventContent>()
+            .await?
+            .map(|raw| raw.deserialize())
+            .transpose()?
+            .map(|content| content.recent_rooms)
+            .unwrap_or_default())
+    }
+
+    /// Observe the breadcrumbs, i.e. the rooms the user visited recently on
+    /// any of their devices.
+    ///
+    /// Returns the current breadcrumbs, along with a stream of the updated
+    /// breadcrumbs, received from the sync.
+    pub async fn observe_breadcrumbs(
+        &self,
+    ) -> Result<(Vec<OwnedRoomId>, impl Stream<Item = Vec<OwnedRoomId>> + use<>)> {
+        let observer =
+            self.client.observe_events::<GlobalAccountDataEvent<BreadcrumbsEventContent>, ()>();
+
+        let mut subscriber = observer.subscribe();
+
+        let stream = async_stream::stream! {
+            // The observer needs to be alive for the subscriber to be alive, so let's move it
+            // in the stream.
+            let _observer = observer;
+
+            while let Some((event, ())) = subscriber.next().await {
+                yield event.content.recent_rooms;
+            }
+        };
+
+        // Get the initial value after creating the observer, to make sure we don't miss
+        // an update received in between.
+        let initial_value = self.get_breadcrumbs().await?;
+
+        Ok((initial_value, stream))
+    }
+
+    /// Move the given room to the front of the breadcrumbs, and upload them to
+    /// the server.
+    pub(crate) async fn track_breadcrumb(&self, room_id: &RoomId) -> Result<()> {
+        let mut content = self
+            .account_data::<BreadcrumbsEventContent>()
+            .await?
+            .map(|raw| raw.deserialize())
+            .transpose()?
+            .unwrap_or_default();
+
+        if content.track_visit(room_id.to_owned(), Self::VISITED_ROOMS_LIMIT) {
+            self.set_account_data(content).await?;
+        }
+
+        Ok(())
+    }
+
     /// Observes the media preview configuration.
     ///
     /// This value is linked to the [MSC 4278](https://github.com/matrix-org/matrix-spec-proposals/pull/4278) which is still in an unstable state.
@@ -1244,25 +1475,180 @@ impl Account {
                 .transpose()?
         };
 
-        if let Some(content) = content {
-            // Sort by count, descending. For items with the same count, since they were
-            // previously ordered by recency in the list, more recent emojis will be
-            // returned first.
-            let sorted_emojis = content
-                .recent_emoji
-                .into_iter()
-                // Items with higher counts should be first
-                .sorted_by(|(_, count_a), (_, count_b)| count_b.cmp(count_a))
-                // Make sure we take only up to MAX_RECENT_EMOJI_COUNT
-                .take(MAX_RECENT_EMOJI_COUNT)
-                .collect();
-            Ok(sorted_emojis)
-        } else {
-            Ok(Vec::new())
+        Ok(content.map(sort_recent_emojis).unwrap_or_default())
+    }
+
+    /// Observes the list of recently used emojis from the
+    /// `io.element.recent_emoji` global account data.
+    ///
+    /// This will return the initial list, as stored locally, and a stream that
+    /// will yield a new list every time the account data is updated by a sync,
+    /// for instance when another client of the same user records a new emoji.
+    ///
+    /// Both the initial value and the values yielded by the stream are sorted
+    /// the same way as the result of [`Account::get_recent_emojis`].
+    #[cfg(feature = "experimental-element-recent-emojis")]
+    pub async fn observe_recent_emojis(
+        &self,
+    ) -> Result<(Vec<(String, UInt)>, impl Stream<Item = Vec<(String, UInt)>> + use<>)> {
+        let observer =
+            self.client.observe_events::<GlobalAccountDataEvent<RecentEmojisContent>, ()>();
+
+        let mut subscriber = observer.subscribe();
+
+        let stream = async_stream::stream! {
+            // The observer needs to be alive for the subscriber to be alive, so let's move it
+            // in the stream.
+            let _observer = observer;
+
+            while let Some((event, ())) = subscriber.next().await {
+                yield sort_recent_emojis(event.content);
+            }
+        };
+
+        // Get the initial value after creating the observer, to make sure we don't miss
+        // an update received in between.
+        let initial_value = self.get_recent_emojis(false).await?;
+
+        Ok((initial_value, stream))
+    }
+}
+
+/// Sort the recently used emojis by count, descending.
+///
+/// For items with the same count, since they were previously ordered by recency
+/// in the list, more recent emojis will be returned first.
+#[cfg(feature = "experimental-element-recent-emojis")]
+fn sort_recent_emojis(content: RecentEmojisContent) -> Vec<(String, UInt)> {
+    content
+        .recent_emoji
+        .into_iter()
+        // Items with higher counts should be first
+        .sorted_by(|(_, count_a), (_, count_b)| count_b.cmp(count_a))
+        // Make sure we take only up to MAX_RECENT_EMOJI_COUNT
+        .take(MAX_RECENT_EMOJI_COUNT)
+        .collect()
+}
+
+/// A bundle of account data of the user, as exported by
+/// [`Account::export_account_data`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AccountDataBundle {
+    /// The content of the global account data, by event type.
+    #[serde(default)]
+    pub global: BTreeMap<String, Raw<AnyGlobalAccountDataEventContent>>,
+
+    /// The content of the room account data, by room and event type.
+    #[serde(default)]
+    pub rooms: BTreeMap<OwnedRoomId, BTreeMap<String, Raw<AnyRoomAccountDataEventContent>>>,
+}
+
+/// The account data event types to export with
+/// [`Account::export_account_data`], or to import with
+/// [`Account::import_account_data`].
+#[derive(Clone, Debug)]
+pub struct AccountDataFilter {
+    /// Whether all the event types match, in which case `global` and `room`
+    /// are ignored.
+    pub all: bool,
+
+    /// The global account data event types.
+    pub global: Vec<GlobalAccountDataEventType>,
+
+    /// The room account data event types.
+    pub room: Vec<RoomAccountDataEventType>,
+}
+
+impl AccountDataFilter {
+    /// A filter that doesn't match any event type.
+    pub fn empty() -> Self {
+        Self { all: false, global: Vec::new(), room: Vec::new() }
+    }
+
+    /// A filter that matches all the event types, including the secrets stored
+    /// in the account data.
+    pub fn all() -> Self {
+        Self { all: true, ..Self::empty() }
+    }
+}
+
+impl Default for AccountDataFilter {
+    /// The user settings that are worth keeping when migrating to another
+    /// account: ignored users, direct rooms, room tags and unread markers.
+    ///
+    /// Secrets, like the cross-signing keys, are not part of the default
+    /// filter.
+    fn default() -> Self {
+        Self {
+            all: false,
+            global: vec![
+                GlobalAccountDataEventType::IgnoredUserList,
+                GlobalAccountDataEventType::Direct,
+            ],
+            room: vec![RoomAccountDataEventType::Tag, RoomAccountDataEventType::MarkedUnread],
         }
     }
 }
 
+/// The outcome of [`Account::import_account_data`].
+#[derive(Debug, Default)]
+pub struct AccountDataImportReport {
+    /// The account data events that were imported.
+    pub imported: Vec<AccountDataImportEntry>,
+
+    /// The account data events that were skipped, because they are managed by
+    /// the server.
+    pub skipped: Vec<AccountDataImportEntry>,
+
+    /// The account data events that the homeserver rejected.
+    pub failed: Vec<AccountDataImportFailure>,
+}
+
+/// An account data event of an [`AccountDataBundle`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountDataImportEntry {
+    /// The room of the event, or `None` for global account data.
+    pub room_id: Option<OwnedRoomId>,
+
+    /// The type of the event.
+    pub event_type: String,
+}
+
+/// An account data event that failed to be imported.
+#[derive(Debug)]
+pub struct AccountDataImportFailure {
+    /// The event that failed to be imported.
+    pub entry: AccountDataImportEntry,
+
+    /// The error returned when importing the event.
+    pub error: Error,
+}
+
+/// Whether the global account data of the given type is managed by the
+/// server, and must be changed through a dedicated API.
+fn is_server_managed_global_account_data(event_type: &str) -> bool {
+    event_type == GlobalAccountDataEventType::PushRules.to_string()
+}
+
+/// Whether the room account data of the given type is managed by the server,
+/// and must be changed through a dedicated API.
+fn is_server_managed_room_account_data(event_type: &str) -> bool {
+    event_type == RoomAccountDataEventType::FullyRead.to_string()
+}
+
+fn get_raw_type_and_content<Ev, C>(raw: Raw<Ev>) -> Result<(String, Raw<C>)> {
+    #[derive(Deserialize)]
+    #[serde(bound = "C: Sized")] // Replace default Deserialize bound
+    struct GetRawTypeAndContent<C> {
+        #[serde(rename = "type")]
+        event_type: String,
+        content: Raw<C>,
+    }
+
+    let event = raw.deserialize_as_unchecked::<GetRawTypeAndContent<C>>()?;
+    Ok((event.event_type, event.content))
+}
+
 fn get_raw_content<Ev, C>(raw: Option<Raw<Ev>>) -> Result<Option<Raw<C>>> {
     #[derive(Deserialize)]
     #[serde(bound = "C: Sized")] // Replace default Deserialize bound
@@ -1279,9 +1665,22 @@ fn get_raw_content<Ev, C>(raw: Option<Raw<Ev>>) -> Result<Option<Raw<C>>> {
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
-    use matrix_sdk_test::async_test;
+    use futures_util::{StreamExt as _, pin_mut};
+    use matrix_sdk_base::breadcrumbs::BreadcrumbsEventContent;
+    use matrix_sdk_test::{
+        JoinedRoomBuilder, RoomAccountDataTestEvent, async_test, event_factory::EventFactory,
+    };
+    use ruma::{
+        events::{GlobalAccountDataEventType, RoomAccountDataEventType},
+        owned_room_id, owned_user_id, room_id,
+    };
+    use serde_json::json;
 
-    use crate::{Error, test_utils::client::MockClientBuilder};
+    use super::{AccountDataBundle, AccountDataFilter, AccountDataImportEntry};
+    use crate::{
+        Error,
+        test_utils::{client::MockClientBuilder, mocks::MatrixMockServer},
+    };
 
     #[async_test]
     async fn test_dont_ignore_oneself() {
@@ -1293,11 +1692,194 @@ mod tests {
             Err(Error::CantIgnoreLoggedInUser)
         );
     }
+
+    #[async_test]
+    async fn test_export_and_import_account_data() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+        let room_id = room_id!("!test:localhost");
+
+        server
+            .mock_sync()
+            .ok_and_run(&client, |builder| {
+                builder
+                    .add_global_account_data(
+                        EventFactory::new()
+                            .ignored_user_list([owned_user_id!("@spammer:example.org")]),
+                    )
+                    .add_joined_room(
+                        JoinedRoomBuilder::new(room_id)
+                            .add_account_data(RoomAccountDataTestEvent::Tags),
+                    );
+            })
+            .await;
+
+        let bundle =
+            client.account().export_account_data(&AccountDataFilter::default()).await.unwrap();
+        assert!(bundle.global.contains_key("m.ignored_user_list"));
+        assert!(bundle.rooms[room_id].contains_key("m.tag"));
+
+        // The bundle survives a round-trip through JSON.
+        let bundle: AccountDataBundle =
+            serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+
+        // Only the ignored users are imported.
+        server
+            .mock_set_global_account_data(GlobalAccountDataEventType::IgnoredUserList)
+            .ok()
+            .mock_once()
+            .mount()
+            .await;
+        server.mock_set_room_account_data(RoomAccountDataEventType::Tag).ok().never().mount().await;
+
+        let filter = AccountDataFilter {
+            global: vec![GlobalAccountDataEventType::IgnoredUserList],
+            ..AccountDataFilter::empty()
+        };
+        let report = client.account().import_account_data(&bundle, &filter).await.unwrap();
+        assert_eq!(report.imported.len(), 1);
+        assert_eq!(report.imported[0].event_type, "m.ignored_user_list");
+        assert!(report.skipped.is_empty());
+        assert!(report.failed.is_empty());
+    }
+
+    #[async_test]
+    async fn test_export_all_account_data() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+        let room_id = room_id!("!test:localhost");
+
+        server
+            .mock_sync()
+            .ok_and_run(&client, |builder| {
+                builder
+                    .add_global_account_data(
+                        EventFactory::new()
+                            .ignored_user_list([owned_user_id!("@spammer:example.org")]),
+                    )
+                    .add_global_account_data(EventFactory::new().global_account_data(
+                        BreadcrumbsEventContent::new(vec![owned_room_id!("!visited:localhost")]),
+                    ))
+                    .add_joined_room(
+                        JoinedRoomBuilder::new(room_id)
+                            .add_account_data(RoomAccountDataTestEvent::Tags)
+                            .add_account_data(RoomAccountDataTestEvent::FullyRead),
+                    );
+            })
+            .await;
+
+        let bundle = client.account().export_account_data(&AccountDataFilter::all()).await.unwrap();
+
+        // The custom account data is exported too.
+        assert!(bundle.global.contains_key("m.ignored_user_list"));
+        assert!(bundle.global.contains_key("im.vector.setting.breadcrumbs"));
+        assert!(bundle.rooms[room_id].contains_key("m.tag"));
+
+        // The account data managed by the server isn't exported.
+        assert!(!bundle.rooms[room_id].contains_key("m.fully_read"));
+    }
+
+    #[async_test]
+    async fn test_import_account_data_with_push_rules() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+
+        let bundle: AccountDataBundle = serde_json::from_value(json!({
+            "global": {
+                "m.push_rules": { "global": {} },
+                "m.ignored_user_list": { "ignored_users": { "@spammer:example.org": {} } },
+            },
+            "rooms": {
+                "!test:localhost": {
+                    "m.tag": { "tags": { "u.work": {} } },
+                },
+            },
+        }))
+        .unwrap();
+
+        // The push rules can't be set through the account data API, so they are
+        // skipped.
+        server
+            .mock_set_global_account_data(GlobalAccountDataEventType::PushRules)
+            .ok()
+            .never()
+            .mount()
+            .await;
+        server
+            .mock_set_global_account_data(GlobalAccountDataEventType::IgnoredUserList)
+            .ok()
+            .mock_once()
+            .mount()
+            .await;
+        // A rejected event doesn't stop the import.
+        server.mock_set_room_account_data(RoomAccountDataEventType::Tag).error500().mount().await;
+
+        let report =
+            client.account().import_account_data(&bundle, &AccountDataFilter::all()).await.unwrap();
+
+        assert_eq!(
+            report.imported,
+            vec![AccountDataImportEntry {
+                room_id: None,
+                event_type: "m.ignored_user_list".to_owned()
+            }]
+        );
+        assert_eq!(
+            report.skipped,
+            vec![AccountDataImportEntry { room_id: None, event_type: "m.push_rules".to_owned() }]
+        );
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(
+            report.failed[0].entry,
+            AccountDataImportEntry {
+                room_id: Some(owned_room_id!("!test:localhost")),
+                event_type: "m.tag".to_owned()
+            }
+        );
+    }
+
+    #[async_test]
+    async fn test_track_room_visit() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+        let room = server.sync_joined_room(&client, room_id!("!visited:localhost")).await;
+
+        let (initial, stream) = client.account().observe_breadcrumbs().await.unwrap();
+        assert!(initial.is_empty());
+        pin_mut!(stream);
+
+        server
+            .mock_set_global_account_data("im.vector.setting.breadcrumbs".into())
+            .body_matches_partial_json(json!({ "recent_rooms": ["!visited:localhost"] }))
+            .ok()
+            .mock_once()
+            .mount()
+            .await;
+
+        client.track_room_visit(&room).await.unwrap();
+
+        // The server sends the updated breadcrumbs back.
+        server
+            .mock_sync()
+            .ok_and_run(&client, |builder| {
+                let content =
+                    BreadcrumbsEventContent::new(vec![owned_room_id!("!visited:localhost")]);
+                builder.add_global_account_data(EventFactory::new().global_account_data(content));
+            })
+            .await;
+
+        assert_eq!(stream.next().await.unwrap(), vec![owned_room_id!("!visited:localhost")]);
+        assert_eq!(
+            client.account().get_breadcrumbs().await.unwrap(),
+            vec![owned_room_id!("!visited:localhost")]
+        );
+    }
 }
 
 #[cfg(test)]
 #[cfg(feature = "experimental-element-recent-emojis")]
 mod test_recent_emojis {
+    use futures_util::{StreamExt as _, pin_mut};
     use js_int::{UInt, uint};
     use matrix_sdk_base::recent_emojis::RecentEmojisContent;
     use matrix_sdk_test::{async_test, event_factory::EventFactory};
@@ -1439,4 +2021,34 @@ mod test_recent_emojis {
 
         client.account().add_recent_emoji(":D").await.expect("adding emoji");
     }
+
+    #[async_test]
+    async fn test_observe_recent_emojis() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+
+        let (initial, stream) =
+            client.account().observe_recent_emojis().await.expect("observing recent emojis");
+        assert!(initial.is_empty());
+        pin_mut!(stream);
+
+        // Another client of the same user updates the list.
+        server
+            .mock_sync()
+            .ok(|builder| {
+                let content = RecentEmojisContent::new(vec![
+                    ("🎉".to_owned(), uint!(2)),
+                    ("😁".to_owned(), uint!(10)),
+                ]);
+                builder.add_global_account_data(EventFactory::new().global_account_data(content));
+            })
+            .named("Sync")
+            .mount()
+            .await;
+
+        client.sync_once(SyncSettings::default()).await.expect("sync failed");
+
+        let updated = stream.next().await.expect("the stream should yield the new list");
+        assert_eq!(updated, vec![("😁".to_owned(), uint!(10)), ("🎉".to_owned(), uint!(2))]);
+    }
 }
@@ -0,0 +1,262 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Accounting of the data exchanged with the homeserver.
+//!
+//! Every request sent by a [`Client`] is counted by its [`BandwidthCounter`],
+//! grouped by [`RequestCategory`], so an application can show how much data
+//! has been used, and adapt its behavior on metered connections.
+//!
+//! Only the bodies of the requests and of the responses are counted, for every
+//! attempt, including the retries. The headers, and the overhead of the
+//! transport, are not.
+//!
+//! [`Client`]: crate::Client
+
+use std::sync::{Arc, Mutex};
+
+/// The category of a request, for the purpose of bandwidth accounting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestCategory {
+    /// The requests to the `/sync` endpoints, including sliding sync.
+    Sync,
+
+    /// The uploads of media.
+    MediaUpload,
+
+    /// The downloads of media, and of their thumbnails.
+    MediaDownload,
+
+    /// The end-to-end encryption requests: keys, to-device messages and key
+    /// backups.
+    Crypto,
+
+    /// All the other requests.
+    Other,
+}
+
+impl RequestCategory {
+    /// Find the category of a request, from its path.
+    pub(crate) fn from_path(path: &str) -> Self {
+        if path.contains("/media/") {
+            if path.contains("/upload") {
+                Self::MediaUpload
+            } else if path.contains("/download/") || path.contains("/thumbnail/") {
+                Self::MediaDownload
+            } else {
+                Self::Other
+            }
+        } else if path.contains("/_matrix/client/") && path.ends_with("/sync") {
+            Self::Sync
+        } else if path.contains("/keys/")
+            || path.contains("/sendToDevice/")
+            || path.contains("/room_keys/")
+            || path.contains("dehydrated_device")
+        {
+            Self::Crypto
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// The number of bytes exchanged with the homeserver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BandwidthUsage {
+    /// The number of bytes sent to the homeserver.
+    pub sent: u64,
+
+    /// The number of bytes received from the homeserver.
+    pub received: u64,
+}
+
+impl BandwidthUsage {
+    /// The number of bytes sent and received.
+    pub fn total(&self) -> u64 {
+        self.sent.saturating_add(self.received)
+    }
+
+    fn add(&mut self, other: BandwidthUsage) {
+        self.sent = self.sent.saturating_add(other.sent);
+        self.received = self.received.saturating_add(other.received);
+    }
+}
+
+/// The bandwidth used by a client, grouped by [`RequestCategory`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BandwidthReport {
+    /// See [`RequestCategory::Sync`].
+    pub sync: BandwidthUsage,
+
+    /// See [`RequestCategory::MediaUpload`].
+    pub media_upload: BandwidthUsage,
+
+    /// See [`RequestCategory::MediaDownload`].
+    pub media_download: BandwidthUsage,
+
+    /// See [`RequestCategory::Crypto`].
+    pub crypto: BandwidthUsage,
+
+    /// See [`RequestCategory::Other`].
+    pub other: BandwidthUsage,
+}
+
+impl BandwidthReport {
+    /// The bandwidth used by the requests of the given category.
+    pub fn get(&self, category: RequestCategory) -> BandwidthUsage {
+        match category {
+            RequestCategory::Sync => self.sync,
+            RequestCategory::MediaUpload => self.media_upload,
+            RequestCategory::MediaDownload => self.media_download,
+            RequestCategory::Crypto => self.crypto,
+            RequestCategory::Other => self.other,
+        }
+    }
+
+    /// The bandwidth used by all the requests, whatever their category.
+    pub fn total(&self) -> BandwidthUsage {
+        let mut total = BandwidthUsage::default();
+        for usage in [self.sync, self.media_upload, self.media_download, self.crypto, self.other] {
+            total.add(usage);
+        }
+        total
+    }
+
+    fn get_mut(&mut self, category: RequestCategory) -> &mut BandwidthUsage {
+        match category {
+            RequestCategory::Sync => &mut self.sync,
+            RequestCategory::MediaUpload => &mut self.media_upload,
+            RequestCategory::MediaDownload => &mut self.media_download,
+            RequestCategory::Crypto => &mut self.crypto,
+            RequestCategory::Other => &mut self.other,
+        }
+    }
+}
+
+/// Counts the bytes exchanged by a client with the homeserver.
+///
+/// Get it with [`Client::bandwidth`]. Cloning it returns a handle to the same
+/// counters.
+///
+/// It keeps two sets of counters: the totals since the client has been
+/// created, and a set which can be reset, e.g. at the start of every billing
+/// period of a metered connection.
+///
+/// [`Client::bandwidth`]: crate::Client::bandwidth
+#[derive(Clone, Debug, Default)]
+pub struct BandwidthCounter {
+    inner: Arc<Mutex<BandwidthCounterInner>>,
+}
+
+#[derive(Debug, Default)]
+struct BandwidthCounterInner {
+    /// The bandwidth used since the client has been created.
+    session: BandwidthReport,
+
+    /// The bandwidth used since the last call to [`BandwidthCounter::reset`].
+    since_reset: BandwidthReport,
+}
+
+impl BandwidthCounter {
+    /// The bandwidth used since the client has been created.
+    pub fn session_totals(&self) -> BandwidthReport {
+        self.inner.lock().unwrap().session
+    }
+
+    /// The bandwidth used since the last call to [`Self::reset`], or since the
+    /// client has been created if it has never been reset.
+    pub fn since_reset(&self) -> BandwidthReport {
+        self.inner.lock().unwrap().since_reset
+    }
+
+    /// Reset the resettable counters, and return their values before the
+    /// reset.
+    ///
+    /// The totals of the session are left untouched.
+    pub fn reset(&self) -> BandwidthReport {
+        std::mem::take(&mut self.inner.lock().unwrap().since_reset)
+    }
+
+    /// Count the bytes sent and received by a request of the given category.
+    pub(crate) fn record(&self, category: RequestCategory, sent: usize, received: usize) {
+        let usage = BandwidthUsage {
+            sent: sent.try_into().unwrap_or(u64::MAX),
+            received: received.try_into().unwrap_or(u64::MAX),
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.session.get_mut(category).add(usage);
+        inner.since_reset.get_mut(category).add(usage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BandwidthCounter, BandwidthUsage, RequestCategory};
+
+    #[test]
+    fn test_request_category_from_path() {
+        assert_eq!(RequestCategory::from_path("/_matrix/client/v3/sync"), RequestCategory::Sync);
+        assert_eq!(
+            RequestCategory::from_path(
+                "/_matrix/client/unstable/org.matrix.simplified_msc3575/sync"
+            ),
+            RequestCategory::Sync
+        );
+        assert_eq!(
+            RequestCategory::from_path("/_matrix/media/v3/upload"),
+            RequestCategory::MediaUpload
+        );
+        assert_eq!(
+            RequestCategory::from_path("/_matrix/client/v1/media/download/example.org/abcd"),
+            RequestCategory::MediaDownload
+        );
+        assert_eq!(
+            RequestCategory::from_path("/_matrix/client/v1/media/thumbnail/example.org/abcd"),
+            RequestCategory::MediaDownload
+        );
+        assert_eq!(
+            RequestCategory::from_path("/_matrix/client/v3/keys/upload"),
+            RequestCategory::Crypto
+        );
+        assert_eq!(
+            RequestCategory::from_path("/_matrix/client/v3/sendToDevice/m.room.encrypted/1"),
+            RequestCategory::Crypto
+        );
+        assert_eq!(
+            RequestCategory::from_path("/_matrix/client/v3/account/whoami"),
+            RequestCategory::Other
+        );
+    }
+
+    #[test]
+    fn test_bandwidth_counter_reset() {
+        let counter = BandwidthCounter::default();
+
+        counter.record(RequestCategory::Sync, 10, 1000);
+        counter.record(RequestCategory::MediaUpload, 500, 50);
+
+        let report = counter.reset();
+        assert_eq!(report.sync, BandwidthUsage { sent: 10, received: 1000 });
+        assert_eq!(report.media_upload, BandwidthUsage { sent: 500, received: 50 });
+        assert_eq!(report.total(), BandwidthUsage { sent: 510, received: 1050 });
+
+        counter.record(RequestCategory::Sync, 20, 2000);
+
+        // Only the resettable counters have been reset.
+        assert_eq!(counter.since_reset().sync, BandwidthUsage { sent: 20, received: 2000 });
+        assert_eq!(counter.session_totals().sync, BandwidthUsage { sent: 30, received: 3000 });
+        assert_eq!(counter.session_totals().get(RequestCategory::MediaUpload).total(), 550);
+    }
+}
@@ -26,7 +26,12 @@ use std::{collections::BTreeSet, fmt, sync::Arc};
 use homeserver_config::*;
 #[cfg(feature = "e2e-encryption")]
 use matrix_sdk_base::crypto::DecryptionSettings;
-use matrix_sdk_base::{BaseClient, ThreadingSupport, store::StoreConfig};
+use matrix_sdk_base::{
+    BaseClient, ThreadingSupport,
+    clock::{Clock, SystemClock},
+    invite_policy::InvitePolicy,
+    store::StoreConfig,
+};
 #[cfg(feature = "sqlite")]
 use matrix_sdk_sqlite::SqliteStoreConfig;
 use ruma::{
@@ -48,6 +53,8 @@ use crate::crypto::{CollectStrategy, TrustRequirement};
 use crate::encryption::EncryptionSettings;
 #[cfg(not(target_family = "wasm"))]
 use crate::http_client::HttpSettings;
+#[cfg(not(target_family = "wasm"))]
+use crate::sync_recording::{SyncRecorder, SyncRecording, SyncReplayer};
 use crate::{
     HttpError, IdParseError,
     authentication::{AuthCtx, oauth::OAuthCtx},
@@ -122,6 +129,12 @@ pub struct ClientBuilder {
     enable_share_history_on_invite: bool,
     cross_process_store_locks_holder_name: String,
     threading_support: ThreadingSupport,
+    invite_policy: InvitePolicy,
+    clock: Arc<dyn Clock>,
+    #[cfg(not(target_family = "wasm"))]
+    sync_recorder: Option<SyncRecorder>,
+    #[cfg(not(target_family = "wasm"))]
+    sync_recording: Option<SyncRecording>,
     #[cfg(feature = "experimental-search")]
     search_index_store_kind: SearchIndexStoreKind,
 }
@@ -155,6 +168,12 @@ impl ClientBuilder {
             cross_process_store_locks_holder_name:
                 Self::DEFAULT_CROSS_PROCESS_STORE_LOCKS_HOLDER_NAME.to_owned(),
             threading_support: ThreadingSupport::Disabled,
+            invite_policy: Default::default(),
+            clock: Arc::new(SystemClock),
+            #[cfg(not(target_family = "wasm"))]
+            sync_recorder: None,
+            #[cfg(not(target_family = "wasm"))]
+            sync_recording: None,
             #[cfg(feature = "experimental-search")]
             search_index_store_kind: SearchIndexStoreKind::InMemory,
         }
@@ -501,6 +520,53 @@ impl ClientBuilder {
         self
     }
 
+    /// Set the clock used to read the time and to wait, e.g. before retrying
+    /// a request or between two syncs.
+    ///
+    /// Tests can use a [`MockClock`] to fast-forward time instead of sleeping
+    /// in real time. Defaults to the [`SystemClock`].
+    ///
+    /// [`MockClock`]: matrix_sdk_base::clock::MockClock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record all the sync responses received by the client with the given
+    /// [`SyncRecorder`], so they can be replayed later.
+    ///
+    /// See the [`sync_recording`](crate::sync_recording) module.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn record_sync_responses(mut self, recorder: SyncRecorder) -> Self {
+        self.sync_recorder = Some(recorder);
+        self
+    }
+
+    /// Make the client receive the responses of the given [`SyncRecording`]
+    /// instead of the ones of the homeserver, in the recorded order.
+    ///
+    /// Only the sync requests are replayed; the other requests are still sent
+    /// to the homeserver. Once all the recorded responses of an endpoint have
+    /// been replayed, the next sync requests to this endpoint never resolve,
+    /// like a sync without any new data.
+    ///
+    /// See the [`sync_recording`](crate::sync_recording) module.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn replay_sync_responses(mut self, recording: SyncRecording) -> Self {
+        self.sync_recording = Some(recording);
+        self
+    }
+
+    /// Set the policy deciding which invites are quarantined instead of being
+    /// turned into invited rooms.
+    ///
+    /// Quarantined invites don't show up in the list of rooms; they can be
+    /// reviewed with [`Client::quarantined_invites`].
+    pub fn with_invite_policy(mut self, invite_policy: InvitePolicy) -> Self {
+        self.invite_policy = invite_policy;
+        self
+    }
+
     /// The base directory in which each room's index directory will be stored.
     #[cfg(feature = "experimental-search")]
     pub fn search_index_store(mut self, kind: SearchIndexStoreKind) -> Self {
@@ -540,13 +606,14 @@ impl ClientBuilder {
         let base_client = if let Some(base_client) = self.base_client {
             base_client
         } else {
-            #[allow(unused_mut)]
             let mut client = BaseClient::new(
                 build_store_config(self.store_config, &self.cross_process_store_locks_holder_name)
                     .await?,
                 self.threading_support,
             );
 
+            client.set_invite_policy(self.invite_policy);
+
             #[cfg(feature = "e2e-encryption")]
             {
                 client.room_key_recipient_strategy = self.room_key_recipient_strategy;
@@ -556,7 +623,15 @@ impl ClientBuilder {
             client
         };
 
-        let http_client = HttpClient::new(inner_http_client.clone(), self.request_config);
+        #[cfg_attr(target_family = "wasm", allow(unused_mut))]
+        let mut http_client =
+            HttpClient::new(inner_http_client.clone(), self.request_config).with_clock(self.clock);
+
+        #[cfg(not(target_family = "wasm"))]
+        {
+            http_client.sync_recorder = self.sync_recorder;
+            http_client.sync_replayer = self.sync_recording.map(|r| Arc::new(SyncReplayer::new(r)));
+        }
 
         #[allow(unused_variables)]
         let HomeserverDiscoveryResult { server, homeserver, supported_versions, well_known } =
@@ -33,7 +33,9 @@ use matrix_sdk_base::crypto::{DecryptionSettings, store::LockableCryptoStore};
 use matrix_sdk_base::{
     BaseClient, RoomInfoNotableUpdate, RoomState, RoomStateFilter, SendOutsideWasm, SessionMeta,
     StateStoreDataKey, StateStoreDataValue, SyncOutsideWasm, ThreadingSupport,
+    clock::Clock,
     event_cache::store::EventCacheStoreLock,
+    invite_policy::QuarantinedInvite,
     media::store::MediaStoreLock,
     store::{DynStateStore, RoomLoadSettings, ServerInfo, WellKnownResponse},
     sync::{Notification, RoomUpdates},
@@ -61,7 +63,7 @@ use ruma::{
             filter::{FilterDefinition, create_filter::v3::Request as FilterUploadRequest},
             knock::knock_room,
             media,
-            membership::{join_room_by_id, join_room_by_id_or_alias},
+            membership::{join_room_by_id, join_room_by_id_or_alias, leave_room},
             room::create_room,
             session::login::v3::DiscoveryInfo,
             sync::sync_events,
@@ -89,7 +91,9 @@ use crate::{
         AuthCtx, AuthData, ReloadSessionCallback, SaveSessionCallback, matrix::MatrixAuth,
         oauth::OAuth,
     },
+    bandwidth::BandwidthCounter,
     client::thread_subscriptions::ThreadSubscriptionCatchup,
+    client_settings::ClientSettings,
     config::{RequestConfig, SyncToken},
     deduplicating_handler::DeduplicatingHandler,
     error::HttpResult,
@@ -101,6 +105,7 @@ use crate::{
     http_client::HttpClient,
     latest_events::LatestEvents,
     media::MediaError,
+    memory_report::MemoryReport,
     notification_settings::NotificationSettings,
     room::RoomMember,
     room_preview::RoomPreview,
@@ -187,6 +192,17 @@ pub(crate) struct ClientLocks {
     /// explanation.
     pub(crate) mark_as_dm_lock: Mutex<()>,
 
+    /// Lock ensuring that only a single setting of a [`ClientSettings`]
+    /// namespace is updated at once, so that concurrent updates don't
+    /// overwrite each other.
+    ///
+    /// [`ClientSettings`]: crate::client_settings::ClientSettings
+    pub(crate) client_settings_lock: Mutex<()>,
+
+    /// Lock ensuring that the starred events of a room are updated one at a
+    /// time, so that concurrent updates don't overwrite each other.
+    pub(crate) starred_events_lock: Mutex<()>,
+
     /// Lock ensuring that only a single secret store is getting opened at the
     /// same time.
     ///
@@ -234,6 +250,10 @@ pub(crate) struct ClientLocks {
     /// internal implementation detail, see [`Self::send_single_receipt`].
     pub(crate) read_receipt_deduplicated_handler: DeduplicatingHandler<(String, OwnedEventId)>,
 
+    /// Handler to ensure that only one preview request is running at a time,
+    /// given a URL.
+    pub(crate) url_preview_deduplicated_handler: DeduplicatingHandler<String>,
+
     #[cfg(feature = "e2e-encryption")]
     pub(crate) cross_process_crypto_store_lock: OnceCell<CrossProcessLock<LockableCryptoStore>>,
 
@@ -761,6 +781,55 @@ impl Client {
         Account::new(self.clone())
     }
 
+    /// Record that the user opened the given room, by moving it to the front of
+    /// the `im.vector.setting.breadcrumbs` account data.
+    ///
+    /// The breadcrumbs are shared with the other clients of the user, and can
+    /// be observed with [`Account::observe_breadcrumbs`] to show the recent
+    /// conversations.
+    pub async fn track_room_visit(&self, room: &Room) -> Result<()> {
+        self.account().track_breadcrumb(room.room_id()).await
+    }
+
+    /// Get the clock used by this client to read the time and to wait.
+    ///
+    /// See [`ClientBuilder::with_clock`].
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.inner.http_client.clock.clone()
+    }
+
+    /// Get the counter of the bytes exchanged by this client with the
+    /// homeserver, grouped by category of request.
+    pub fn bandwidth(&self) -> BandwidthCounter {
+        self.inner.http_client.bandwidth.clone()
+    }
+
+    /// Get the settings of an application in the given namespace, which are
+    /// synchronized across all the devices of the user.
+    ///
+    /// The namespace is used as the type of the account data event where the
+    /// settings are stored, so it should use the Java package naming
+    /// convention, like `com.example.myapp.settings`.
+    pub fn settings(&self, namespace: &str) -> ClientSettings {
+        ClientSettings::new(self.clone(), namespace)
+    }
+
+    /// Summarize the content of the main in-memory caches of this client, to
+    /// diagnose the memory growth on large accounts and tune the cache limits.
+    ///
+    /// See [`MemoryReport`] for what is included.
+    pub async fn memory_report(&self) -> MemoryReport {
+        MemoryReport {
+            rooms: self.base_client().rooms().len(),
+            event_cache: self.event_cache().memory_report().await,
+            #[cfg(feature = "e2e-encryption")]
+            olm_sessions: match self.olm_machine().await.as_ref() {
+                Some(olm_machine) => Some(olm_machine.store().session_cache_stats().await),
+                None => None,
+            },
+        }
+    }
+
     /// Get the encryption manager of the client.
     #[cfg(feature = "e2e-encryption")]
     pub fn encryption(&self) -> Encryption {
@@ -1306,6 +1375,22 @@ impl Client {
         RoomPreview::from_remote_room(self, room_id, room_or_alias_id, via).await
     }
 
+    /// Get the room of a [`RoomPreview`], or create it in memory if it isn't
+    /// known locally.
+    ///
+    /// A created room takes the state of the preview, or
+    /// [`RoomState::Left`] if the user has no membership in it. It allows to
+    /// use the APIs which don't require the user to be a member of the room,
+    /// like loading its world-readable history with [`Room::messages`], and
+    /// it isn't saved to the store by this method.
+    pub fn get_or_create_room_for_preview(&self, room_preview: &RoomPreview) -> Room {
+        let room = self.base_client().get_or_create_room(
+            &room_preview.room_id,
+            room_preview.state.unwrap_or(RoomState::Left),
+        );
+        Room::new(self.clone(), room)
+    }
+
     /// Resolve a room alias to a room id and a list of servers which know
     /// about it.
     ///
@@ -1641,6 +1726,36 @@ impl Client {
         self.finish_join_room(&response.room_id, pre_join_info).await
     }
 
+    /// Get the invites that have been quarantined because they didn't satisfy
+    /// the [`InvitePolicy`] set with [`ClientBuilder::with_invite_policy`].
+    ///
+    /// [`InvitePolicy`]: matrix_sdk_base::invite_policy::InvitePolicy
+    pub async fn quarantined_invites(&self) -> Result<Vec<QuarantinedInvite>> {
+        Ok(self.base_client().quarantined_invites().await?)
+    }
+
+    /// Let a quarantined invite through, as if it satisfied the invite policy.
+    ///
+    /// Returns the invited room, or `None` if there was no quarantined invite
+    /// for this room.
+    pub async fn release_quarantined_invite(&self, room_id: &RoomId) -> Result<Option<Room>> {
+        Ok(self
+            .base_client()
+            .release_quarantined_invite(room_id)
+            .await?
+            .map(|room| Room::new(self.clone(), room)))
+    }
+
+    /// Reject a quarantined invite.
+    ///
+    /// This leaves the room on the homeserver, and removes the invite from the
+    /// quarantined invites.
+    pub async fn reject_quarantined_invite(&self, room_id: &RoomId) -> Result<()> {
+        self.send(leave_room::v3::Request::new(room_id.to_owned())).await?;
+        self.base_client().remove_quarantined_invite(room_id).await?;
+        Ok(())
+    }
+
     /// Search the homeserver's directory of public rooms.
     ///
     /// Sends a request to "_matrix/client/r0/publicRooms", returns
@@ -2369,6 +2484,18 @@ impl Client {
     pub async fn sync_once(
         &self,
         sync_settings: crate::config::SyncSettings,
+    ) -> Result<SyncResponse> {
+        let future = self.sync_once_impl(sync_settings);
+
+        #[cfg(feature = "opentelemetry")]
+        let future = matrix_sdk_common::telemetry::instrument_sync_iteration("sync", future);
+
+        future.await
+    }
+
+    async fn sync_once_impl(
+        &self,
+        sync_settings: crate::config::SyncSettings,
     ) -> Result<SyncResponse> {
         // The sync might not return for quite a while due to the timeout.
         // We'll see if there's anything crypto related to send out before we
@@ -2708,7 +2835,7 @@ impl Client {
                     .instrument(parent_span.clone())
                     .await;
 
-                Client::delay_sync(&mut last_sync_time).await
+                self.delay_sync(&mut last_sync_time).await
             }
         })
     }
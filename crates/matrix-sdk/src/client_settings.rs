@@ -0,0 +1,408 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Settings of an application, synchronized across the devices of the user.
+//!
+//! The settings of a [`ClientSettings`] namespace are stored in a single global
+//! account data event, whose type is the namespace. Every setting is stored
+//! along with the time of its last update, so that concurrent updates from
+//! different devices are resolved by keeping the last write.
+
+use std::collections::BTreeMap;
+
+use async_stream::stream;
+use futures_core::Stream;
+use futures_util::StreamExt;
+use ruma::{
+    MilliSecondsSinceUnixEpoch, OwnedDeviceId,
+    events::{
+        AnyGlobalAccountDataEvent, AnyGlobalAccountDataEventContent, GlobalAccountDataEventType,
+    },
+    serde::Raw,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value as JsonValue;
+use tracing::warn;
+
+use crate::{Client, Result};
+
+/// A namespace of settings of an application, stored in the account data of
+/// the user, and synchronized across all their devices.
+///
+/// Get one with [`Client::settings`].
+#[derive(Debug, Clone)]
+pub struct ClientSettings {
+    client: Client,
+    event_type: GlobalAccountDataEventType,
+}
+
+impl ClientSettings {
+    pub(crate) fn new(client: Client, namespace: &str) -> Self {
+        Self { client, event_type: namespace.into() }
+    }
+
+    /// The namespace of these settings, i.e. the type of the account data
+    /// event where they are stored.
+    pub fn namespace(&self) -> String {
+        self.event_type.to_string()
+    }
+
+    /// Get the value of a setting, from storage.
+    ///
+    /// Returns `None` if the setting has never been set, or has been removed.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let content = self.stored_content().await?;
+
+        Ok(content
+            .settings
+            .get(key)
+            .and_then(|entry| entry.value.clone())
+            .map(serde_json::from_value)
+            .transpose()?)
+    }
+
+    /// Get all the settings that have a value, from storage.
+    pub async fn get_all(&self) -> Result<BTreeMap<String, JsonValue>> {
+        Ok(self.stored_content().await?.values())
+    }
+
+    /// Set the value of a setting.
+    ///
+    /// The settings are fetched from the server first, so that the updates
+    /// made on other devices since the last sync are not overwritten. If
+    /// another device updated the same setting more recently, its value is
+    /// kept.
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        self.update(key, Some(serde_json::to_value(value)?), MilliSecondsSinceUnixEpoch::now())
+            .await
+    }
+
+    /// Remove a setting.
+    ///
+    /// The removal is kept in the account data, so that it wins over older
+    /// updates of other devices.
+    pub async fn remove(&self, key: &str) -> Result<()> {
+        self.update(key, None, MilliSecondsSinceUnixEpoch::now()).await
+    }
+
+    /// Subscribe to the changes of these settings, coming from the sync.
+    ///
+    /// Every time the account data event of this namespace is received, the
+    /// stream yields the settings whose value changed, with their new value,
+    /// which is `None` if the setting has been removed.
+    pub async fn subscribe(&self) -> Result<impl Stream<Item = Vec<ClientSettingChange>> + use<>> {
+        // Subscribe before reading the stored settings, so that no update received in
+        // between is missed.
+        let observer = self.client.observe_events::<Raw<AnyGlobalAccountDataEvent>, ()>();
+        let mut subscriber = observer.subscribe();
+        let mut previous = self.stored_content().await?.values();
+        let event_type = self.event_type.to_string();
+
+        Ok(stream! {
+            // Keep the event handler alive as long as the stream.
+            let _observer = observer;
+
+            while let Some((event, ())) = subscriber.next().await {
+                if event.get_field::<String>("type").ok().flatten().as_deref()
+                    != Some(event_type.as_str())
+                {
+                    continue;
+                }
+
+                let content = match event.get_field::<SettingsContent>("content") {
+                    Ok(content) => content.unwrap_or_default(),
+                    Err(error) => {
+                        warn!("Failed to deserialize the client settings: {error}");
+                        continue;
+                    }
+                };
+
+                let current = content.values();
+                let changes = diff(&previous, &current);
+                previous = current;
+
+                if !changes.is_empty() {
+                    yield changes;
+                }
+            }
+        })
+    }
+
+    async fn stored_content(&self) -> Result<SettingsContent> {
+        let raw = self.client.account().account_data_raw(self.event_type.clone()).await?;
+        Ok(raw.map(|raw| raw.deserialize_as_unchecked()).transpose()?.unwrap_or_default())
+    }
+
+    async fn update(
+        &self,
+        key: &str,
+        value: Option<JsonValue>,
+        updated_at: MilliSecondsSinceUnixEpoch,
+    ) -> Result<()> {
+        let account = self.client.account();
+        let entry = SettingEntry {
+            value,
+            updated_at,
+            device_id: self.client.device_id().map(ToOwned::to_owned),
+        };
+
+        // Another update of this client could otherwise upload its settings between
+        // our fetch and our upload, and lose our setting, or the other way around.
+        let _guard = self.client.locks().client_settings_lock.lock().await;
+
+        // Merge our setting into the settings of the server, rather than into the
+        // stored ones, which might miss the updates of other devices.
+        let mut content: SettingsContent = account
+            .fetch_account_data(self.event_type.clone())
+            .await?
+            .map(|raw| raw.deserialize_as_unchecked())
+            .transpose()?
+            .unwrap_or_default();
+
+        if !content.merge_entry(key.to_owned(), entry) {
+            // Another device updated this setting more recently, its value wins.
+            return Ok(());
+        }
+
+        let raw = Raw::new(&content)?.cast_unchecked::<AnyGlobalAccountDataEventContent>();
+        account.set_account_data_raw(self.event_type.clone(), raw).await?;
+
+        Ok(())
+    }
+}
+
+/// A change of a setting of a [`ClientSettings`] namespace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientSettingChange {
+    /// The key of the setting.
+    pub key: String,
+
+    /// The new value of the setting, or `None` if it has been removed.
+    pub value: Option<JsonValue>,
+}
+
+/// The content of the account data event of a [`ClientSettings`] namespace.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct SettingsContent {
+    #[serde(default)]
+    settings: BTreeMap<String, SettingEntry>,
+}
+
+impl SettingsContent {
+    /// Insert the entry, unless the existing entry for the same key is more
+    /// recent.
+    ///
+    /// Returns whether the entry has been inserted.
+    fn merge_entry(&mut self, key: String, entry: SettingEntry) -> bool {
+        match self.settings.get(&key) {
+            Some(existing) if existing.is_more_recent_than(&entry) => false,
+            _ => {
+                self.settings.insert(key, entry);
+                true
+            }
+        }
+    }
+
+    /// The settings that have a value.
+    fn values(&self) -> BTreeMap<String, JsonValue> {
+        self.settings
+            .iter()
+            .filter_map(|(key, entry)| Some((key.clone(), entry.value.clone()?)))
+            .collect()
+    }
+}
+
+/// A setting, along with the information needed to resolve conflicts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SettingEntry {
+    /// The value of the setting, or `None` if it has been removed.
+    value: Option<JsonValue>,
+
+    /// When the setting has been updated.
+    updated_at: MilliSecondsSinceUnixEpoch,
+
+    /// The device that updated the setting, used to break ties.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device_id: Option<OwnedDeviceId>,
+}
+
+impl SettingEntry {
+    fn is_more_recent_than(&self, other: &Self) -> bool {
+        (self.updated_at, &self.device_id) > (other.updated_at, &other.device_id)
+    }
+}
+
+/// Compute the changes between two sets of setting values.
+fn diff(
+    previous: &BTreeMap<String, JsonValue>,
+    current: &BTreeMap<String, JsonValue>,
+) -> Vec<ClientSettingChange> {
+    let removed = previous
+        .keys()
+        .filter(|key| !current.contains_key(*key))
+        .map(|key| ClientSettingChange { key: key.clone(), value: None });
+
+    let updated = current
+        .iter()
+        .filter(|(key, value)| previous.get(*key) != Some(*value))
+        .map(|(key, value)| ClientSettingChange { key: key.clone(), value: Some(value.clone()) });
+
+    removed.chain(updated).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    use matrix_sdk_test::async_test;
+    use ruma::{MilliSecondsSinceUnixEpoch, owned_device_id, uint};
+    use serde_json::{Value as JsonValue, json};
+    use wiremock::{
+        Mock, Request, ResponseTemplate,
+        matchers::{method, path_regex},
+    };
+
+    use super::{ClientSettingChange, SettingEntry, SettingsContent, diff};
+    use crate::test_utils::mocks::MatrixMockServer;
+
+    /// Mount an account data endpoint for the `app.settings` namespace, that
+    /// keeps the uploaded content and returns it when it's fetched.
+    async fn mount_account_data(server: &MatrixMockServer) -> Arc<Mutex<JsonValue>> {
+        let stored = Arc::new(Mutex::new(json!({ "settings": {} })));
+        let path = r"^/_matrix/client/v3/user/.*/account_data/app\.settings$";
+
+        Mock::given(method("GET"))
+            .and(path_regex(path))
+            .respond_with({
+                let stored = stored.clone();
+                move |_: &Request| {
+                    ResponseTemplate::new(200).set_body_json(stored.lock().unwrap().clone())
+                }
+            })
+            .mount(server.server())
+            .await;
+
+        Mock::given(method("PUT"))
+            .and(path_regex(path))
+            .respond_with({
+                let stored = stored.clone();
+                move |request: &Request| {
+                    *stored.lock().unwrap() = request.body_json().unwrap();
+                    ResponseTemplate::new(200).set_body_json(json!({}))
+                }
+            })
+            .mount(server.server())
+            .await;
+
+        stored
+    }
+
+    fn entry(value: Option<serde_json::Value>, ts: u32, device: &str) -> SettingEntry {
+        SettingEntry {
+            value,
+            updated_at: MilliSecondsSinceUnixEpoch(ts.into()),
+            device_id: Some(device.into()),
+        }
+    }
+
+    #[test]
+    fn test_last_write_wins() {
+        let mut content = SettingsContent::default();
+
+        assert!(content.merge_entry("theme".to_owned(), entry(Some(json!("dark")), 2, "A")));
+        // An older write is ignored.
+        assert!(!content.merge_entry("theme".to_owned(), entry(Some(json!("light")), 1, "B")));
+        assert_eq!(content.values()["theme"], json!("dark"));
+
+        // A newer removal wins.
+        content.merge_entry("theme".to_owned(), entry(None, 3, "B"));
+        assert!(content.values().is_empty());
+
+        // Ties are broken by the device ID.
+        content.merge_entry("theme".to_owned(), entry(Some(json!("light")), 3, "C"));
+        assert_eq!(content.values()["theme"], json!("light"));
+        assert_eq!(content.settings["theme"].device_id, Some(owned_device_id!("C")));
+        assert_eq!(content.settings["theme"].updated_at, MilliSecondsSinceUnixEpoch(uint!(3)));
+    }
+
+    #[async_test]
+    async fn test_concurrent_writers_on_the_same_device() {
+        let server = MatrixMockServer::new().await;
+        let client = server.client_builder().build().await;
+        let stored = mount_account_data(&server).await;
+
+        let settings = client.settings("app.settings");
+        let (theme, font_size) =
+            futures_util::join!(settings.set("theme", &"dark"), settings.set("font_size", &12));
+        theme.unwrap();
+        font_size.unwrap();
+
+        // None of the writes has overwritten the other one.
+        let stored = stored.lock().unwrap();
+        assert_eq!(stored["settings"]["theme"]["value"], json!("dark"));
+        assert_eq!(stored["settings"]["font_size"]["value"], json!(12));
+    }
+
+    #[async_test]
+    async fn test_concurrent_writers_on_different_devices() {
+        let server = MatrixMockServer::new().await;
+        let alice = server.client_builder().build().await;
+        let bob = server.client_builder().build().await;
+        let stored = mount_account_data(&server).await;
+
+        let at = |ts: u32| MilliSecondsSinceUnixEpoch(ts.into());
+
+        // Bob writes last, but his write reaches the server first.
+        bob.settings("app.settings").update("theme", Some(json!("light")), at(3)).await.unwrap();
+        alice.settings("app.settings").update("theme", Some(json!("dark")), at(2)).await.unwrap();
+
+        // The last write wins, and the older one has been rejected.
+        {
+            let stored = stored.lock().unwrap();
+            assert_eq!(stored["settings"]["theme"]["value"], json!("light"));
+            assert_eq!(stored["settings"]["theme"]["updated_at"], json!(3));
+        }
+
+        // A write more recent than the one on the server wins.
+        alice.settings("app.settings").update("theme", Some(json!("dark")), at(4)).await.unwrap();
+
+        let stored = stored.lock().unwrap();
+        assert_eq!(stored["settings"]["theme"]["value"], json!("dark"));
+        assert_eq!(stored["settings"]["theme"]["updated_at"], json!(4));
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = BTreeMap::from([
+            ("theme".to_owned(), json!("dark")),
+            ("ordering".to_owned(), json!("recency")),
+        ]);
+        let current = BTreeMap::from([
+            ("theme".to_owned(), json!("light")),
+            ("font_size".to_owned(), json!(12)),
+        ]);
+
+        assert_eq!(
+            diff(&previous, &current),
+            vec![
+                ClientSettingChange { key: "ordering".to_owned(), value: None },
+                ClientSettingChange { key: "font_size".to_owned(), value: Some(json!(12)) },
+                ClientSettingChange { key: "theme".to_owned(), value: Some(json!("light")) },
+            ]
+        );
+    }
+}
@@ -384,6 +384,11 @@ pub enum Error {
     #[error("a concurrent request failed; see logs for details")]
     ConcurrentRequestFailed,
 
+    /// The pinned events of a room were changed concurrently by another
+    /// client too many times in a row, so they couldn't be updated.
+    #[error("the pinned events were changed concurrently too many times")]
+    PinningConflict,
+
     /// An other error was raised.
     ///
     /// This might happen because encryption was enabled on the base-crate
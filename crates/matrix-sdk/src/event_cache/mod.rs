@@ -79,7 +79,9 @@ mod room;
 mod search;
 
 pub use pagination::{RoomPagination, RoomPaginationStatus};
-pub use room::{RoomEventCache, RoomEventCacheSubscriber, ThreadEventCacheUpdate};
+pub use room::{
+    RoomEventCache, RoomEventCacheMemoryReport, RoomEventCacheSubscriber, ThreadEventCacheUpdate,
+};
 
 /// An error observed in the [`EventCache`].
 #[derive(thiserror::Error, Debug)]
@@ -424,6 +426,20 @@ impl EventCache {
         Ok((room, drop_handles))
     }
 
+    /// Summarize what the event caches of all the rooms hold in memory.
+    ///
+    /// Only the rooms whose event cache has been loaded are included.
+    pub async fn memory_report(&self) -> Vec<RoomEventCacheMemoryReport> {
+        let rooms: Vec<_> = self.inner.by_room.read().await.values().cloned().collect();
+
+        let mut reports = Vec::with_capacity(rooms.len());
+        for room in rooms {
+            reports.push(room.memory_report().await);
+        }
+
+        reports
+    }
+
     /// Cleanly clear all the rooms' event caches.
     ///
     /// This will notify any live observers that the room has been cleared.
@@ -406,6 +406,36 @@ impl RoomEventCache {
     pub async fn debug_string(&self) -> Vec<String> {
         self.inner.state.read().await.room_linked_chunk().debug_string()
     }
+
+    /// Summarize what this room's event cache holds in memory.
+    ///
+    /// Only the chunks loaded in memory are taken into account; the events
+    /// which are only in the event cache store don't count.
+    pub async fn memory_report(&self) -> RoomEventCacheMemoryReport {
+        self.inner.state.read().await.memory_report()
+    }
+}
+
+/// A summary of what a [`RoomEventCache`] holds in memory, as returned by
+/// [`RoomEventCache::memory_report`].
+#[derive(Clone, Debug)]
+pub struct RoomEventCacheMemoryReport {
+    /// The room this report is about.
+    pub room_id: OwnedRoomId,
+
+    /// The number of chunks (of events or gaps) of the room's linked chunk
+    /// loaded in memory.
+    pub chunks: usize,
+
+    /// The number of events of the room's linked chunk loaded in memory.
+    pub events: usize,
+
+    /// The number of threads of the room known by the event cache.
+    pub threads: usize,
+
+    /// The number of events loaded in memory, across all the threads of the
+    /// room.
+    pub thread_events: usize,
 }
 
 /// The (non-cloneable) details of the `RoomEventCache`.
@@ -626,7 +656,7 @@ mod private {
     };
     use matrix_sdk_common::executor::spawn;
     use ruma::{
-        EventId, OwnedEventId, OwnedRoomId,
+        EventId, OwnedEventId, OwnedRoomId, OwnedUserId,
         events::{
             AnySyncMessageLikeEvent, AnySyncTimelineEvent, MessageLikeEventType,
             relation::RelationType, room::redaction::SyncRoomRedactionEvent,
@@ -639,7 +669,7 @@ mod private {
 
     use super::{
         super::{EventCacheError, deduplicator::DeduplicationOutcome},
-        EventLocation, LoadMoreEventsBackwardsOutcome,
+        EventLocation, LoadMoreEventsBackwardsOutcome, RoomEventCacheMemoryReport,
         events::EventLinkedChunk,
         sort_positions_descending,
     };
@@ -1321,6 +1351,17 @@ mod private {
             &self.room_linked_chunk
         }
 
+        /// Summarize what this state holds in memory.
+        pub fn memory_report(&self) -> RoomEventCacheMemoryReport {
+            RoomEventCacheMemoryReport {
+                room_id: self.room.clone(),
+                chunks: self.room_linked_chunk.chunks().count(),
+                events: self.room_linked_chunk.events().count(),
+                threads: self.threads.len(),
+                thread_events: self.threads.values().map(ThreadEventCache::num_loaded_events).sum(),
+            }
+        }
+
         //// Find a single event in this room, starting from the most recent event.
         ///
         /// **Warning**! It looks into the loaded events from the in-memory
@@ -1540,7 +1581,7 @@ mod private {
                 // that field can only be present on room messages, we don't have to
                 // worry about filtering out aggregation events (like
                 // reactions/edits/etc.). Pretty neat, huh?
-                let num_replies = {
+                let (num_replies, num_participants) = {
                     let store_guard = &*self.store.lock().await?;
                     let related_thread_events = store_guard
                         .find_event_relations(
@@ -1549,14 +1590,29 @@ mod private {
                             Some(&[RelationType::Thread]),
                         )
                         .await?;
-                    related_thread_events.len().try_into().unwrap_or(u32::MAX)
+
+                    let participants = related_thread_events
+                        .iter()
+                        .filter_map(|(event, _)| {
+                            event.raw().get_field::<OwnedUserId>("sender").ok().flatten()
+                        })
+                        .collect::<HashSet<_>>();
+
+                    (
+                        related_thread_events.len().try_into().unwrap_or(u32::MAX),
+                        participants.len().try_into().unwrap_or(u32::MAX),
+                    )
                 };
 
                 if let Some(last_event_id) = last_event_id {
                     latest_reply = Some(last_event_id);
                 }
 
-                let new_summary = ThreadSummary { num_replies, latest_reply };
+                let new_summary = ThreadSummary {
+                    num_replies,
+                    latest_reply,
+                    num_participants: Some(num_participants),
+                };
 
                 if prev_summary == Some(&new_summary) {
                     trace!(%thread_root, "thread summary is already up-to-date");
@@ -87,6 +87,11 @@ impl ThreadEventCache {
         (events, recv)
     }
 
+    /// The number of events of this thread loaded in memory.
+    pub fn num_loaded_events(&self) -> usize {
+        self.chunk.events().count()
+    }
+
     /// Clear a thread, after a gappy sync for instance.
     pub fn clear(&mut self) {
         self.chunk.reset();
@@ -27,6 +27,7 @@ use bytes::{Bytes, BytesMut};
 use bytesize::ByteSize;
 use eyeball::SharedObservable;
 use http::Method;
+use matrix_sdk_common::clock::{Clock, SystemClock};
 use ruma::api::{
     AuthScheme, OutgoingRequest, SendAccessToken, SupportedVersions,
     error::{FromHttpResponseError, IntoHttpError},
@@ -34,7 +35,9 @@ use ruma::api::{
 use tokio::sync::{Semaphore, SemaphorePermit};
 use tracing::{debug, field::debug, instrument, trace};
 
-use crate::{config::RequestConfig, error::HttpError};
+#[cfg(not(target_family = "wasm"))]
+use crate::sync_recording::{SyncEndpoint, SyncRecorder, SyncReplayer};
+use crate::{bandwidth::BandwidthCounter, config::RequestConfig, error::HttpError};
 
 #[cfg(not(target_family = "wasm"))]
 mod native;
@@ -76,6 +79,12 @@ pub(crate) struct HttpClient {
     pub(crate) request_config: RequestConfig,
     concurrent_request_semaphore: MaybeSemaphore,
     next_request_id: Arc<AtomicU64>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) bandwidth: BandwidthCounter,
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) sync_recorder: Option<SyncRecorder>,
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) sync_replayer: Option<Arc<SyncReplayer>>,
 }
 
 impl HttpClient {
@@ -87,9 +96,21 @@ impl HttpClient {
                 request_config.max_concurrent_requests,
             ),
             next_request_id: AtomicU64::new(0).into(),
+            clock: Arc::new(SystemClock),
+            bandwidth: BandwidthCounter::default(),
+            #[cfg(not(target_family = "wasm"))]
+            sync_recorder: None,
+            #[cfg(not(target_family = "wasm"))]
+            sync_replayer: None,
         }
     }
 
+    /// Use the given clock to wait between the retries of a request.
+    pub(crate) fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     fn get_request_id(&self) -> String {
         let request_id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
         format!("REQ-{request_id}")
@@ -208,6 +229,13 @@ impl HttpClient {
             request
         };
 
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(replayer) = &self.sync_replayer
+            && let Some(endpoint) = SyncEndpoint::from_path(request.uri().path())
+        {
+            return Box::pin(replay_sync_response::<R>(replayer, endpoint)).await;
+        }
+
         // will be automatically dropped at the end of this function
         let _handle = self.concurrent_request_semaphore.acquire().await;
 
@@ -235,6 +263,34 @@ pub struct TransmissionProgress {
     pub total: usize,
 }
 
+/// Respond to a sync request with the next recorded response, instead of
+/// sending it.
+#[cfg(not(target_family = "wasm"))]
+async fn replay_sync_response<R>(
+    replayer: &SyncReplayer,
+    endpoint: SyncEndpoint,
+) -> Result<R::IncomingResponse, HttpError>
+where
+    R: OutgoingRequest,
+    HttpError: From<FromHttpResponseError<R::EndpointError>>,
+{
+    use ruma::api::IncomingResponse;
+
+    let Some(body) = replayer.next(endpoint) else {
+        debug!(?endpoint, "No more recorded sync responses to replay");
+        return std::future::pending().await;
+    };
+
+    debug!(?endpoint, "Replaying a recorded sync response");
+    let response = http::Response::builder()
+        .status(http::StatusCode::OK)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .expect("Can't construct a response using the given body");
+
+    Ok(R::IncomingResponse::try_from_http_response(response)?)
+}
+
 async fn response_to_http_response(
     mut response: reqwest::Response,
 ) -> Result<http::Response<Bytes>, reqwest::Error> {
@@ -265,8 +321,10 @@ mod tests {
         time::Duration,
     };
 
-    use matrix_sdk_common::executor::spawn;
+    use matrix_sdk_common::{clock::MockClock, executor::spawn};
     use matrix_sdk_test::{async_test, test_json};
+    use serde_json::json;
+    use tokio::task::yield_now;
     use wiremock::{
         Mock, Request, ResponseTemplate,
         matchers::{method, path},
@@ -361,4 +419,63 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 254, "Not all requests passed through");
         bg_task.abort();
     }
+
+    #[async_test]
+    async fn test_retries_wait_for_the_clock() {
+        let clock = MockClock::new();
+        let (client_builder, server) = test_client_builder_with_server().await;
+        let client = client_builder
+            .request_config(RequestConfig::default().retry_limit(3))
+            .with_clock(Arc::new(clock.clone()))
+            .build()
+            .await
+            .unwrap();
+
+        set_client_session(&client).await;
+
+        let counter = Arc::new(AtomicU8::new(0));
+        let inner_counter = counter.clone();
+
+        Mock::given(method("GET"))
+            .and(path("/_matrix/client/versions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&*test_json::VERSIONS))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("_matrix/client/r0/account/whoami"))
+            .respond_with(move |_req: &Request| {
+                // The first request fails with a transient error, the next ones succeed.
+                if inner_counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    ResponseTemplate::new(500)
+                } else {
+                    ResponseTemplate::new(200)
+                        .set_body_json(json!({ "user_id": "@example:localhost" }))
+                }
+            })
+            .mount(&server)
+            .await;
+
+        let task = spawn(async move { client.whoami().await });
+
+        // Wait for the first request to fail.
+        while counter.load(Ordering::SeqCst) == 0 {
+            yield_now().await;
+        }
+
+        // The request isn't retried as long as the clock doesn't move.
+        for _ in 0..100 {
+            yield_now().await;
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert!(!task.is_finished());
+
+        // Move the clock forward until the backoff is over.
+        while !task.is_finished() {
+            clock.advance(Duration::from_millis(100));
+            yield_now().await;
+        }
+        task.await.unwrap().expect("the retried request should succeed");
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
 }
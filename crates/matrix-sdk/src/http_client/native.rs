@@ -30,8 +30,10 @@ use tracing::{debug, info, warn};
 
 use super::{DEFAULT_REQUEST_TIMEOUT, HttpClient, TransmissionProgress, response_to_http_response};
 use crate::{
+    bandwidth::RequestCategory,
     config::RequestConfig,
     error::{HttpError, RetryKind},
+    sync_recording::SyncEndpoint,
 };
 
 impl HttpClient {
@@ -79,11 +81,34 @@ impl HttpClient {
                 let before = ruma::time::Instant::now();
 
                 let response =
-                    send_request(&self.inner, &request, config.timeout, send_progress).await?;
+                    send_request(&self.inner, &request, config.timeout, send_progress).await;
 
                 let request_duration = ruma::time::Instant::now().saturating_duration_since(before);
 
+                #[cfg(feature = "opentelemetry")]
+                matrix_sdk_common::telemetry::record_http_request(
+                    request.method().as_str(),
+                    response.as_ref().ok().map(|response| response.status().as_u16()),
+                    request_duration,
+                );
+
+                self.bandwidth.record(
+                    RequestCategory::from_path(request.uri().path()),
+                    request.body().len(),
+                    response.as_ref().map_or(0, |response| response.body().len()),
+                );
+
+                let response = response?;
+
                 let status_code = response.status();
+
+                if let Some(recorder) = &self.sync_recorder
+                    && status_code.is_success()
+                    && let Some(endpoint) = SyncEndpoint::from_path(request.uri().path())
+                {
+                    recorder.record(endpoint, response.body());
+                }
+
                 let response_size = ByteSize(response.body().len().try_into().unwrap_or(u64::MAX));
                 tracing::Span::current()
                     .record("status", status_code.as_u16())
@@ -108,9 +133,11 @@ impl HttpClient {
         };
 
         let has_retry_limit = config.retry_limit.is_some();
+        let clock = self.clock.clone();
 
         send_request
             .retry(backoff)
+            .sleep(move |duration| clock.sleep(duration))
             .adjust(|err, default_timeout| {
                 match err.retry_kind() {
                     RetryKind::Transient { retry_after } => {
@@ -20,7 +20,7 @@ use eyeball::SharedObservable;
 use ruma::api::{IncomingResponse, OutgoingRequest, error::FromHttpResponseError};
 
 use super::{HttpClient, TransmissionProgress, response_to_http_response};
-use crate::{config::RequestConfig, error::HttpError};
+use crate::{bandwidth::RequestCategory, config::RequestConfig, error::HttpError};
 
 impl HttpClient {
     pub(super) async fn send_request<R>(
@@ -35,11 +35,27 @@ impl HttpClient {
     {
         tracing::debug!("Sending request");
 
+        #[cfg(feature = "opentelemetry")]
+        let method = request.method().clone();
+        let category = RequestCategory::from_path(request.uri().path());
+        let request_size = request.body().len();
         let request = reqwest::Request::try_from(request)?;
 
         let before = ruma::time::Instant::now();
 
-        let response = response_to_http_response(self.inner.execute(request).await?).await?;
+        let response = self.inner.execute(request).await;
+        let response = match response {
+            Ok(response) => response_to_http_response(response).await,
+            Err(error) => Err(error),
+        };
+
+        self.bandwidth.record(
+            category,
+            request_size,
+            response.as_ref().map_or(0, |response| response.body().len()),
+        );
+
+        let response = response?;
 
         let request_duration = ruma::time::Instant::now().saturating_duration_since(before);
         let status_code = response.status();
@@ -50,6 +66,13 @@ impl HttpClient {
             .record("response_size", response_size.display().si_short().to_string())
             .record("request_duration", tracing::field::debug(request_duration));
 
+        #[cfg(feature = "opentelemetry")]
+        matrix_sdk_common::telemetry::record_http_request(
+            method.as_str(),
+            Some(status_code.as_u16()),
+            request_duration,
+        );
+
         Ok(R::IncomingResponse::try_from_http_response(response)?)
     }
 }
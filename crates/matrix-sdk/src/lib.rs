@@ -35,7 +35,9 @@ pub use reqwest;
 mod account;
 pub mod attachment;
 pub mod authentication;
+pub mod bandwidth;
 mod client;
+pub mod client_settings;
 pub mod config;
 mod deduplicating_handler;
 #[cfg(feature = "e2e-encryption")]
@@ -46,6 +48,7 @@ pub mod event_handler;
 mod http_client;
 pub mod latest_events;
 pub mod media;
+pub mod memory_report;
 pub mod notification_settings;
 pub mod paginators;
 pub mod pusher;
@@ -61,10 +64,15 @@ pub mod futures {
 }
 pub mod sliding_sync;
 pub mod sync;
+#[cfg(not(target_family = "wasm"))]
+pub mod sync_recording;
 #[cfg(feature = "experimental-widgets")]
 pub mod widget;
 
-pub use account::Account;
+pub use account::{
+    Account, AccountDataBundle, AccountDataFilter, AccountDataImportEntry,
+    AccountDataImportFailure, AccountDataImportReport,
+};
 pub use authentication::{AuthApi, AuthSession, SessionTokens};
 #[cfg(feature = "experimental-search")]
 pub use client::search::SearchIndexStoreKind;
@@ -35,10 +35,14 @@ use ruma::{
     assign,
     events::room::{MediaSource, ThumbnailInfo},
 };
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sha2::{Digest as _, Sha256};
 #[cfg(not(target_family = "wasm"))]
 use tempfile::{Builder as TempFileBuilder, NamedTempFile, TempDir};
 #[cfg(not(target_family = "wasm"))]
 use tokio::{fs::File as TokioFile, io::AsyncWriteExt};
+use tracing::warn;
 
 use crate::{
     Client, Error, Result, TransmissionProgress, attachment::Thumbnail,
@@ -58,6 +62,11 @@ const MIN_UPLOAD_REQUEST_TIMEOUT: Duration = Duration::from_secs(60 * 5);
 // possible would be coming from the user themselves, which we consider a
 // non-threat.
 const LOCAL_MXC_SERVER_NAME: &str = "send-queue.localhost";
+/// The server name used to generate the local MXC URIs under which the URL
+/// previews are cached in the media store.
+const URL_PREVIEW_MXC_SERVER_NAME: &str = "url-preview.localhost";
+/// The duration after which a cached URL preview is fetched again.
+const URL_PREVIEW_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
 
 /// A high-level API to interact with the media API.
 #[derive(Debug, Clone)]
@@ -128,6 +137,82 @@ pub struct PreallocatedMxcUri {
     expire_date: Option<MilliSecondsSinceUnixEpoch>,
 }
 
+/// The preview of a URL, made of the OpenGraph data of the page, as seen by
+/// the homeserver.
+///
+/// See [`Media::get_url_preview()`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UrlPreview {
+    /// The URL which has been previewed.
+    pub url: String,
+    /// The title of the page, from `og:title`.
+    pub title: Option<String>,
+    /// The description of the page, from `og:description`.
+    pub description: Option<String>,
+    /// The name of the website, from `og:site_name`.
+    pub site_name: Option<String>,
+    /// The image of the page, from `og:image`, which the homeserver has
+    /// uploaded to its media repository.
+    pub image: Option<OwnedMxcUri>,
+    /// The width of the image, in pixels.
+    pub image_width: Option<UInt>,
+    /// The height of the image, in pixels.
+    pub image_height: Option<UInt>,
+    /// The size of the image, in bytes.
+    pub image_size: Option<UInt>,
+}
+
+impl UrlPreview {
+    /// Whether there's nothing to show for this preview.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none() && self.image.is_none()
+    }
+
+    /// Parse the OpenGraph data of the given URL, as returned by the
+    /// `/preview_url` endpoint.
+    fn from_data(url: &str, data: JsonValue) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct OpenGraphData {
+            #[serde(rename = "og:title")]
+            title: Option<String>,
+            #[serde(rename = "og:description")]
+            description: Option<String>,
+            #[serde(rename = "og:site_name")]
+            site_name: Option<String>,
+            #[serde(rename = "og:image")]
+            image: Option<OwnedMxcUri>,
+            #[serde(rename = "og:image:width")]
+            image_width: Option<UInt>,
+            #[serde(rename = "og:image:height")]
+            image_height: Option<UInt>,
+            #[serde(rename = "matrix:image:size")]
+            image_size: Option<UInt>,
+        }
+
+        let data: OpenGraphData = serde_json::from_value(data)?;
+
+        Ok(Self {
+            url: url.to_owned(),
+            title: data.title,
+            description: data.description,
+            site_name: data.site_name,
+            image: data.image,
+            image_width: data.image_width,
+            image_height: data.image_height,
+            image_size: data.image_size,
+        })
+    }
+}
+
+/// A URL preview, as cached in the media store by [`Media::get_url_preview()`].
+#[derive(Serialize, Deserialize)]
+struct CachedUrlPreview {
+    /// When the preview has been fetched from the homeserver.
+    fetched_at: MilliSecondsSinceUnixEpoch,
+    /// The OpenGraph data returned by the homeserver.
+    data: JsonValue,
+}
+
 /// An error that happened in the realm of media.
 #[derive(Debug, thiserror::Error)]
 pub enum MediaError {
@@ -713,6 +798,124 @@ impl Media {
         Ok(())
     }
 
+    /// Get the preview of the given URL, i.e. the OpenGraph data of the page,
+    /// from the homeserver.
+    ///
+    /// Concurrent requests for the same URL are deduplicated, and the previews
+    /// are cached in the media store for a day, so that the homeserver is asked
+    /// at most once per URL during that time. Like the other media, the cached
+    /// previews are subject to the [`MediaRetentionPolicy`].
+    ///
+    /// Note that the homeserver fetches the URL itself, so calling this leaks
+    /// the URL to the homeserver, which is a privacy concern in encrypted
+    /// rooms. See
+    /// [`Room::url_previews_enabled()`](crate::Room::url_previews_enabled).
+    pub async fn get_url_preview(&self, url: &str) -> Result<UrlPreview> {
+        let request = Self::make_url_preview_media_request(url);
+
+        if let Some(preview) = self.get_cached_url_preview(url, &request).await? {
+            return Ok(preview);
+        }
+
+        self.client
+            .locks()
+            .url_preview_deduplicated_handler
+            .run(url.to_owned(), async {
+                // A previous request for the same URL may have filled the cache in the
+                // meantime.
+                if self.get_cached_url_preview(url, &request).await?.is_some() {
+                    return Ok(());
+                }
+
+                let supported_versions = self.client.supported_versions().await?;
+
+                let data = if authenticated_media::get_media_preview::v1::Request::is_supported(
+                    &supported_versions,
+                ) {
+                    let request =
+                        authenticated_media::get_media_preview::v1::Request::new(url.to_owned());
+                    self.client.send(request).await?.data
+                } else {
+                    #[allow(deprecated)]
+                    let request = media::get_media_preview::v3::Request::new(url.to_owned());
+                    self.client.send(request).await?.data
+                };
+
+                // The homeserver may return no data at all if there's nothing to preview.
+                let data = match data {
+                    Some(data) => serde_json::from_str(data.get())?,
+                    None => JsonValue::Object(Default::default()),
+                };
+
+                // Make sure the data is valid before caching it.
+                UrlPreview::from_data(url, data.clone())?;
+
+                let cached =
+                    CachedUrlPreview { fetched_at: MilliSecondsSinceUnixEpoch::now(), data };
+
+                self.client
+                    .media_store()
+                    .lock()
+                    .await?
+                    .add_media_content(
+                        &request,
+                        serde_json::to_vec(&cached)?,
+                        IgnoreMediaRetentionPolicy::No,
+                    )
+                    .await?;
+
+                Ok(())
+            })
+            .await?;
+
+        self.get_cached_url_preview(url, &request).await?.ok_or(Error::ConcurrentRequestFailed)
+    }
+
+    /// Get the preview of the given URL from the media store, unless it's
+    /// older than [`URL_PREVIEW_MAX_AGE`].
+    async fn get_cached_url_preview(
+        &self,
+        url: &str,
+        request: &MediaRequestParameters,
+    ) -> Result<Option<UrlPreview>> {
+        let Some(content) =
+            self.client.media_store().lock().await?.get_media_content(request).await?
+        else {
+            return Ok(None);
+        };
+
+        let cached: CachedUrlPreview = match serde_json::from_slice(&content) {
+            Ok(cached) => cached,
+            Err(error) => {
+                warn!("Ignoring the invalid cached preview of a URL: {error}");
+                return Ok(None);
+            }
+        };
+
+        let age = MilliSecondsSinceUnixEpoch::now().0.saturating_sub(cached.fetched_at.0);
+        if Duration::from_millis(age.into()) > URL_PREVIEW_MAX_AGE {
+            return Ok(None);
+        }
+
+        UrlPreview::from_data(url, cached.data).map(Some)
+    }
+
+    /// Create the [`MediaRequestParameters`] under which the preview of the
+    /// given URL is cached in the media store.
+    ///
+    /// This uses a MXC ID that is only locally valid, derived from a hash of
+    /// the URL so it doesn't end up in the key of the media store.
+    fn make_url_preview_media_request(url: &str) -> MediaRequestParameters {
+        let hash = Sha256::digest(url.as_bytes());
+
+        MediaRequestParameters {
+            source: MediaSource::Plain(OwnedMxcUri::from(format!(
+                "mxc://{URL_PREVIEW_MXC_SERVER_NAME}/{hash:x}"
+            ))),
+            format: MediaFormat::File,
+        }
+    }
+
     /// Upload the file bytes in `data` and return the source information.
     pub(crate) async fn upload_plain_media_and_thumbnail(
         &self,
@@ -0,0 +1,57 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Introspection of the main in-memory caches of a [`Client`].
+//!
+//! A [`MemoryReport`] counts the items held by these caches, rather than
+//! measuring their size in bytes: the counts are cheap to compute, and are
+//! enough to find out which cache keeps growing on a large account.
+//!
+//! [`Client`]: crate::Client
+
+#[cfg(feature = "e2e-encryption")]
+pub use matrix_sdk_base::crypto::store::caches::SessionStoreStats;
+
+pub use crate::event_cache::RoomEventCacheMemoryReport;
+
+/// A summary of the main in-memory caches of a client, as returned by
+/// [`Client::memory_report`].
+///
+/// The timelines aren't included, since they are owned by the application.
+/// Their items are counted by `Timeline::memory_report`, in the `matrix-sdk-ui`
+/// crate.
+///
+/// [`Client::memory_report`]: crate::Client::memory_report
+#[derive(Clone, Debug)]
+pub struct MemoryReport {
+    /// The number of rooms, and thus of room infos, held in memory by the
+    /// client.
+    pub rooms: usize,
+
+    /// The in-memory content of the event cache of every room whose event
+    /// cache has been loaded.
+    pub event_cache: Vec<RoomEventCacheMemoryReport>,
+
+    /// The Olm sessions cached in memory, if the Olm machine has been set up.
+    #[cfg(feature = "e2e-encryption")]
+    pub olm_sessions: Option<SessionStoreStats>,
+}
+
+impl MemoryReport {
+    /// The number of events loaded in memory in the event cache, across all
+    /// the rooms and their threads.
+    pub fn event_cache_events(&self) -> usize {
+        self.event_cache.iter().map(|room| room.events + room.thread_events).sum()
+    }
+}
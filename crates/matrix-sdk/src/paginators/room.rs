@@ -219,6 +219,29 @@ impl<PR: PaginableRoom> Paginator<PR> {
         Ok(StartFromResult { events, has_prev, has_next })
     }
 
+    /// Starts the pagination from the end of the timeline, requesting the
+    /// `num_events` most recent events with a backward pagination.
+    ///
+    /// The events are returned in reverse topological order, like for
+    /// [`Paginator::paginate_backward`], and there won't be any events to
+    /// paginate forward afterwards.
+    ///
+    /// Only works for fresh [`Paginator`] objects, which are in the
+    /// [`PaginatorState::Initial`] state.
+    pub async fn start_from_end(
+        &self,
+        num_events: UInt,
+    ) -> Result<PaginationResult, PaginatorError> {
+        self.check_state(PaginatorState::Initial)?;
+
+        // Without a previous-batch token, the backward pagination starts from the end
+        // of the timeline, so there's nothing after it.
+        self.tokens.lock().unwrap().next = PaginationToken::HitEnd;
+        self.state.set(PaginatorState::Idle);
+
+        self.paginate_backward(num_events).await
+    }
+
     /// Runs a backward pagination (requesting `num_events` to the server), from
     /// the current state of the object.
     ///
@@ -557,7 +580,12 @@ mod tests {
                 }
             };
 
-            Ok(Messages { start: opts.from.unwrap(), end, chunk: events, state: Vec::new() })
+            Ok(Messages {
+                start: opts.from.unwrap_or_default(),
+                end,
+                chunk: events,
+                state: Vec::new(),
+            })
         }
     }
 
@@ -716,6 +744,50 @@ mod tests {
         assert!(prev.events.is_empty());
     }
 
+    #[async_test]
+    async fn test_start_from_end() {
+        // Prepare test data.
+        let room = TestRoom::new(false, *ROOM_ID, *USER_ID);
+        let event_factory = &room.event_factory;
+
+        *room.prev_events.lock().await = vec![event_factory.text_msg("latest").into_event()];
+        *room.prev_batch_token.lock().await = Some("prev".to_owned());
+
+        // When I start from the end, I get the most recent events.
+        let paginator = Arc::new(Paginator::new(room.clone()));
+        let result =
+            paginator.start_from_end(uint!(100)).await.expect("start_from_end should work");
+
+        assert!(!result.hit_end_of_timeline);
+        assert_eq!(result.events.len(), 1);
+        assert_event_matches_msg(&result.events[0], "latest");
+
+        // There's nothing to paginate forward, but I can paginate backward.
+        assert!(!paginator.hit_timeline_start());
+        assert!(paginator.hit_timeline_end());
+
+        let next =
+            paginator.paginate_forward(uint!(100)).await.expect("paginate forward should work");
+        assert!(next.hit_end_of_timeline);
+        assert!(next.events.is_empty());
+
+        *room.prev_events.lock().await = vec![event_factory.text_msg("oldest").into_event()];
+        *room.prev_batch_token.lock().await = None;
+
+        let prev =
+            paginator.paginate_backward(uint!(100)).await.expect("paginate backward should work");
+        assert!(prev.hit_end_of_timeline);
+        assert_event_matches_msg(&prev.events[0], "oldest");
+
+        // A paginator can't start from the end twice.
+        assert_invalid_state(
+            paginator.start_from_end(uint!(100)),
+            PaginatorState::Initial,
+            PaginatorState::Idle,
+        )
+        .await;
+    }
+
     #[async_test]
     async fn test_paginate_backward_with_limit() {
         // Prepare test data.
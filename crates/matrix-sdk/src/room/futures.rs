@@ -204,10 +204,16 @@ impl<'a> IntoFuture for SendRawMessageLikeEvent<'a> {
                     let olm = room.client.olm_machine().await;
                     let olm = olm.as_ref().expect("Olm machine wasn't started");
 
-                    content = olm
-                        .encrypt_room_event_raw(room.room_id(), event_type, &content)
-                        .await?
-                        .cast();
+                    let encrypted =
+                        olm.encrypt_room_event_raw(room.room_id(), event_type, &content);
+
+                    #[cfg(feature = "opentelemetry")]
+                    let encrypted = matrix_sdk_common::telemetry::instrument_crypto_operation(
+                        "encrypt_room_event",
+                        encrypted,
+                    );
+
+                    content = encrypted.await?.cast();
                     event_type = "m.room.encrypted";
                 }
             } else {
@@ -166,7 +166,7 @@ use crate::{
     event_handler::{EventHandler, EventHandlerDropGuard, EventHandlerHandle, SyncEvent},
     live_location_share::ObservableLiveLocation,
     media::{MediaFormat, MediaRequestParameters},
-    notification_settings::{IsEncrypted, IsOneToOne, RoomNotificationMode},
+    notification_settings::{IsEncrypted, IsOneToOne, NotificationSettings, RoomNotificationMode},
     room::{
         knock_requests::{KnockRequest, KnockRequestMemberInfo},
         power_levels::{RoomPowerLevelChanges, RoomPowerLevelsExt},
@@ -192,6 +192,8 @@ pub mod calls;
 
 /// Contains all the functionality for modifying the privacy settings in a room.
 pub mod privacy_settings;
+pub mod starred_events;
+pub mod url_previews;
 
 #[cfg(feature = "e2e-encryption")]
 pub(crate) mod shared_room_history;
@@ -1658,14 +1660,19 @@ impl Room {
         let machine = self.client.olm_machine().await;
         let machine = machine.as_ref().ok_or(Error::NoOlmMachine)?;
 
-        match machine
-            .try_decrypt_room_event(
-                event.cast_ref(),
-                self.inner.room_id(),
-                self.client.decryption_settings(),
-            )
-            .await?
-        {
+        let decryption = machine.try_decrypt_room_event(
+            event.cast_ref(),
+            self.inner.room_id(),
+            self.client.decryption_settings(),
+        );
+
+        #[cfg(feature = "opentelemetry")]
+        let decryption = matrix_sdk_common::telemetry::instrument_crypto_operation(
+            "decrypt_room_event",
+            decryption,
+        );
+
+        match decryption.await? {
             RoomEventDecryptionResult::Decrypted(decrypted) => {
                 let push_actions = if let Some(push_ctx) = push_ctx {
                     Some(push_ctx.for_event(&decrypted.event).await)
@@ -1700,14 +1707,19 @@ impl Room {
         let machine = self.client.olm_machine().await;
         let machine = machine.as_ref().ok_or(Error::NoOlmMachine)?;
 
-        match machine
-            .try_decrypt_room_event(
-                event.cast_ref(),
-                self.inner.room_id(),
-                self.client.decryption_settings(),
-            )
-            .await?
-        {
+        let decryption = machine.try_decrypt_room_event(
+            event.cast_ref(),
+            self.inner.room_id(),
+            self.client.decryption_settings(),
+        );
+
+        #[cfg(feature = "opentelemetry")]
+        let decryption = matrix_sdk_common::telemetry::instrument_crypto_operation(
+            "decrypt_room_event",
+            decryption,
+        );
+
+        match decryption.await? {
             RoomEventDecryptionResult::Decrypted(decrypted) => {
                 let push_actions = if let Some(push_ctx) = push_ctx {
                     Some(push_ctx.for_event(&decrypted.event).await)
@@ -3669,6 +3681,51 @@ impl Room {
         mode
     }
 
+    /// Compute the user-defined notification mode and the notification mode
+    /// with the given notification settings, and cache them.
+    ///
+    /// Contrary to [`Self::notification_mode`], it never sends a request to
+    /// the homeserver: the default notification mode is only computed if the
+    /// encryption state of the room is already known. To read the cached
+    /// results, use
+    /// [`matrix_sdk_base::Room::cached_user_defined_notification_mode`] and
+    /// [`matrix_sdk_base::Room::cached_notification_mode`].
+    pub(crate) async fn update_cached_notification_mode_with(
+        &self,
+        notification_settings: &NotificationSettings,
+    ) {
+        if !matches!(self.state(), RoomState::Joined) {
+            self.update_cached_notification_mode(None);
+            return;
+        }
+
+        let mut mode =
+            notification_settings.get_user_defined_room_notification_mode(self.room_id()).await;
+
+        if let Some(mode) = mode {
+            self.update_cached_user_defined_notification_mode(mode);
+        }
+
+        let encryption_state = self.encryption_state();
+
+        if mode.is_none() && !encryption_state.is_unknown() {
+            // From the point of view of notification settings, a `one-to-one` room is one
+            // that involves exactly two people.
+            let is_one_to_one = IsOneToOne::from(self.active_members_count() == 2);
+
+            mode = Some(
+                notification_settings
+                    .get_default_room_notification_mode(
+                        IsEncrypted::from(encryption_state.is_encrypted()),
+                        is_one_to_one,
+                    )
+                    .await,
+            );
+        }
+
+        self.update_cached_notification_mode(mode);
+    }
+
     /// Report an event as inappropriate to the homeserver's administrator.
     ///
     /// # Arguments
@@ -0,0 +1,146 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Facilities to star the events of a room, so the user can find them later.
+
+use async_stream::stream;
+use futures_util::Stream;
+use matrix_sdk_base::StateChanges;
+use ruma::{
+    EventId, OwnedEventId,
+    events::{
+        AnyRoomAccountDataEvent, RoomAccountDataEvent, StaticEventContent, macros::EventContent,
+    },
+    serde::Raw,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{Result, Room};
+
+/// The content of the `org.matrix.room.starred_events` room account data
+/// event, which lists the events the user has starred in a room.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "org.matrix.room.starred_events", kind = RoomAccountData)]
+pub struct StarredEventsEventContent {
+    /// The IDs of the starred events, from the first one to be starred to the
+    /// most recent one.
+    #[serde(default)]
+    pub event_ids: Vec<OwnedEventId>,
+}
+
+impl Room {
+    /// The IDs of the events the user has starred in this room, from the
+    /// first one to be starred to the most recent one.
+    pub async fn starred_event_ids(&self) -> Result<Vec<OwnedEventId>> {
+        if let Some(raw) = self.account_data_static::<StarredEventsEventContent>().await?
+            && let Ok(event) = raw.deserialize()
+        {
+            return Ok(event.content.event_ids);
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Whether the event with the given ID has been starred by the user.
+    pub async fn is_starred_event(&self, event_id: &EventId) -> Result<bool> {
+        Ok(self.starred_event_ids().await?.iter().any(|id| id == event_id))
+    }
+
+    /// Star the event with the given ID.
+    ///
+    /// The starred events are saved in the room's account data, so they're
+    /// shared with the user's other devices. The change is taken into account
+    /// by [`Self::starred_event_ids()`] right away, and by
+    /// [`Self::starred_event_ids_stream()`] once the account data has been
+    /// received back with a sync.
+    ///
+    /// Returns `false` if the event was already starred.
+    pub async fn star_event(&self, event_id: &EventId) -> Result<bool> {
+        self.update_starred_event_ids(|event_ids| {
+            if event_ids.iter().any(|id| id == event_id) {
+                return false;
+            }
+
+            event_ids.push(event_id.to_owned());
+            true
+        })
+        .await
+    }
+
+    /// Remove the star of the event with the given ID.
+    ///
+    /// Like with [`Self::star_event()`], the change is taken into account by
+    /// [`Self::starred_event_ids()`] right away.
+    ///
+    /// Returns `false` if the event wasn't starred.
+    pub async fn unstar_event(&self, event_id: &EventId) -> Result<bool> {
+        self.update_starred_event_ids(|event_ids| {
+            let previous_len = event_ids.len();
+            event_ids.retain(|id| id != event_id);
+            event_ids.len() != previous_len
+        })
+        .await
+    }
+
+    /// Update the starred events with `update`, which returns `false` if it
+    /// didn't change them.
+    async fn update_starred_event_ids(
+        &self,
+        update: impl FnOnce(&mut Vec<OwnedEventId>) -> bool,
+    ) -> Result<bool> {
+        // Two updates in a row would otherwise both start from the same list, and the
+        // last one would drop the change of the first one.
+        let _guard = self.client.locks().starred_events_lock.lock().await;
+
+        let mut event_ids = self.starred_event_ids().await?;
+
+        if !update(&mut event_ids) {
+            return Ok(false);
+        }
+
+        let content = StarredEventsEventContent { event_ids };
+        self.set_account_data(content.clone()).await?;
+
+        // Save the new list right away, so that the next update starts from it, even if
+        // the account data hasn't been received back with a sync yet.
+        let raw_event = Raw::new(&json!({
+            "type": StarredEventsEventContent::TYPE,
+            "content": content,
+        }))?
+        .cast_unchecked::<AnyRoomAccountDataEvent>();
+        let event = raw_event.deserialize()?;
+
+        let mut changes = StateChanges::default();
+        changes.add_room_account_data(self.room_id(), event, raw_event);
+        self.client.state_store().save_changes(&changes).await?;
+
+        Ok(true)
+    }
+
+    /// Get a stream of the IDs of the starred events, which yields the new
+    /// list whenever it's updated by a sync.
+    pub fn starred_event_ids_stream(&self) -> impl Stream<Item = Vec<OwnedEventId>> + use<> {
+        let observer =
+            self.client.observe_room_events::<RoomAccountDataEvent<StarredEventsEventContent>, ()>(
+                self.room_id(),
+            );
+
+        stream! {
+            for await (event, ()) in observer.subscribe() {
+                yield event.content.event_ids;
+            }
+        }
+    }
+}
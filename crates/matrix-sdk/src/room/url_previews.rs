@@ -0,0 +1,60 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Facilities to control whether the URLs sent in a room can be previewed.
+
+use ruma::events::macros::EventContent;
+use serde::{Deserialize, Serialize};
+
+use crate::{Result, Room};
+
+/// The content of the `org.matrix.room.preview_urls` room account data event,
+/// which tells whether the user has disabled the previews of the URLs sent in
+/// a room.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "org.matrix.room.preview_urls", kind = RoomAccountData)]
+pub struct UrlPreviewsEventContent {
+    /// Whether the URL previews are disabled in the room.
+    #[serde(default)]
+    pub disable: bool,
+}
+
+impl Room {
+    /// Whether the URLs sent in this room can be previewed with
+    /// [`Media::get_url_preview()`](crate::media::Media::get_url_preview).
+    ///
+    /// Previewing a URL sends it to the homeserver, so unless the user has
+    /// chosen otherwise with [`Self::set_url_previews_enabled()`], the URL
+    /// previews are only enabled in unencrypted rooms.
+    pub async fn url_previews_enabled(&self) -> Result<bool> {
+        if let Some(raw) = self.account_data_static::<UrlPreviewsEventContent>().await?
+            && let Ok(event) = raw.deserialize()
+        {
+            return Ok(!event.content.disable);
+        }
+
+        Ok(!self.latest_encryption_state().await?.is_encrypted())
+    }
+
+    /// Enable or disable the previews of the URLs sent in this room.
+    ///
+    /// The choice is saved in the room's account data, so it's shared with the
+    /// user's other devices. It's taken into account by
+    /// [`Self::url_previews_enabled()`] once the account data has been received
+    /// back with a sync.
+    pub async fn set_url_previews_enabled(&self, enabled: bool) -> Result<()> {
+        self.set_account_data(UrlPreviewsEventContent { disable: !enabled }).await?;
+        Ok(())
+    }
+}
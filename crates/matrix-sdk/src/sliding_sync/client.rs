@@ -243,13 +243,42 @@ impl SlidingSyncResponseProcessor {
 async fn update_in_memory_caches(client: &Client, response: &SyncResponse) -> Result<()> {
     let _timer = timer!(tracing::Level::TRACE, "update_in_memory_caches");
 
-    for room_id in response.rooms.joined.keys() {
-        let Some(room) = client.get_room(room_id) else {
-            error!(?room_id, "Cannot post process a room in sliding sync because it is missing");
-            continue;
-        };
+    // When the push rules have changed, the notification mode of all the joined
+    // rooms may have changed, not only the ones of the rooms in the response.
+    let push_rules_have_changed = response.account_data.iter().any(|event| {
+        event.get_field::<String>("type").ok().flatten().as_deref() == Some("m.push_rules")
+    });
+
+    let rooms = if push_rules_have_changed {
+        client.joined_rooms()
+    } else {
+        response
+            .rooms
+            .joined
+            .keys()
+            .filter_map(|room_id| {
+                let room = client.get_room(room_id);
+
+                if room.is_none() {
+                    error!(
+                        ?room_id,
+                        "Cannot post process a room in sliding sync because it is missing"
+                    );
+                }
+
+                room
+            })
+            .collect()
+    };
 
-        room.user_defined_notification_mode().await;
+    if rooms.is_empty() {
+        return Ok(());
+    }
+
+    let notification_settings = client.notification_settings().await;
+
+    for room in rooms {
+        room.update_cached_notification_mode_with(&notification_settings).await;
     }
 
     Ok(())
@@ -480,6 +509,7 @@ mod tests {
             room.cached_user_defined_notification_mode(),
             Some(RoomNotificationMode::AllMessages),
         );
+        assert_eq!(room.cached_notification_mode(), Some(RoomNotificationMode::AllMessages));
 
         // Mock a sync response.
         // A `m.push_rules` with `room` is cached during the sync.
@@ -531,6 +561,117 @@ mod tests {
             room.cached_user_defined_notification_mode(),
             Some(RoomNotificationMode::MentionsAndKeywordsOnly),
         );
+        assert_eq!(
+            room.cached_notification_mode(),
+            Some(RoomNotificationMode::MentionsAndKeywordsOnly)
+        );
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn test_cache_notification_mode_of_all_rooms_when_push_rules_change() -> Result<()> {
+        let client = MockClientBuilder::new(None).build().await;
+        let room_id_0 = room_id!("!r0:matrix.org");
+        let room_id_1 = room_id!("!r1:matrix.org");
+
+        let sliding_sync = client
+            .sliding_sync("test")?
+            .with_account_data_extension(
+                assign!(http::request::AccountData::default(), { enabled: Some(true) }),
+            )
+            .add_list(
+                SlidingSyncList::builder("all")
+                    .sync_mode(SlidingSyncMode::new_selective().add_range(0..=10)),
+            )
+            .build()
+            .await?;
+
+        // Mock a sync response with both rooms.
+        {
+            let server_response = assign!(http::Response::new("0".to_owned()), {
+                rooms: BTreeMap::from([
+                    (room_id_0.to_owned(), http::response::Room::default()),
+                    (room_id_1.to_owned(), http::response::Room::default()),
+                ]),
+            });
+
+            let mut pos_guard = sliding_sync.inner.position.clone().lock_owned().await;
+            sliding_sync
+                .handle_response(
+                    server_response,
+                    &mut pos_guard,
+                    RequestedRequiredStates::default(),
+                )
+                .await?;
+        }
+
+        let room_1 = client.get_room(room_id_1).unwrap();
+        let mut room_info_notable_update_stream = client.room_info_notable_update_receiver();
+
+        // Mock a sync response with only the first room, and a `m.push_rules` muting
+        // the second room.
+        {
+            let server_response = assign!(http::Response::new("1".to_owned()), {
+                rooms: BTreeMap::from([(room_id_0.to_owned(), http::response::Room::default())]),
+                extensions: assign!(http::response::Extensions::default(), {
+                    account_data: assign!(http::response::AccountData::default(), {
+                        global: vec![
+                            Raw::from_json_string(
+                                json!({
+                                    "type": "m.push_rules",
+                                    "content": {
+                                        "global": {
+                                            "override": [
+                                                {
+                                                    "actions": [],
+                                                    "rule_id": room_id_1,
+                                                    "default": false,
+                                                    "enabled": true,
+                                                    "conditions": [
+                                                        {
+                                                            "kind": "event_match",
+                                                            "key": "room_id",
+                                                            "pattern": room_id_1,
+                                                        },
+                                                    ],
+                                                },
+                                            ],
+                                        },
+                                    },
+                                })
+                                .to_string(),
+                            ).unwrap()
+                        ]
+                    })
+                })
+            });
+
+            let mut pos_guard = sliding_sync.inner.position.clone().lock_owned().await;
+            sliding_sync
+                .handle_response(
+                    server_response,
+                    &mut pos_guard,
+                    RequestedRequiredStates::default(),
+                )
+                .await?;
+        }
+
+        // The second room has been updated, even if it wasn't in the response.
+        assert_eq!(room_1.cached_notification_mode(), Some(RoomNotificationMode::Mute));
+
+        // And a notable update has been emitted for it.
+        let mut has_received_notification_mode_update = false;
+
+        while let Ok(update) = room_info_notable_update_stream.try_recv() {
+            if update.room_id == room_id_1
+                && update.reasons.contains(RoomInfoNotableUpdateReasons::NOTIFICATION_MODE)
+            {
+                has_received_notification_mode_update = true;
+            }
+        }
+
+        assert!(has_received_notification_mode_update);
 
         Ok(())
     }
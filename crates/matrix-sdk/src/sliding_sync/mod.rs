@@ -666,16 +666,24 @@ impl SlidingSync {
     #[doc(hidden)]
     #[instrument(skip_all, fields(pos, conn_id = self.inner.id))]
     pub async fn sync_once(&self) -> Result<UpdateSummary> {
-        let (request, request_config, position_guard) =
-            self.generate_sync_request(&mut LazyTransactionId::new()).await?;
+        let future = async {
+            let (request, request_config, position_guard) =
+                self.generate_sync_request(&mut LazyTransactionId::new()).await?;
 
-        // Send the request.
-        let summaries = self.send_sync_request(request, request_config, position_guard).await?;
+            // Send the request.
+            let summaries = self.send_sync_request(request, request_config, position_guard).await?;
 
-        // Notify a new sync was received.
-        self.inner.client.inner.sync_beat.notify(usize::MAX);
+            // Notify a new sync was received.
+            self.inner.client.inner.sync_beat.notify(usize::MAX);
 
-        Ok(summaries)
+            Ok(summaries)
+        };
+
+        #[cfg(feature = "opentelemetry")]
+        let future =
+            matrix_sdk_common::telemetry::instrument_sync_iteration("sliding_sync", future);
+
+        future.await
     }
 
     /// Create a _new_ Sliding Sync sync loop.
@@ -26,7 +26,6 @@ use matrix_sdk_base::{
         DebugInvitedRoom, DebugKnockedRoom, DebugListOfProcessedToDeviceEvents,
         DebugListOfRawEventsNoId,
     },
-    sleep::sleep,
     sync::SyncResponse as BaseSyncResponse,
     timer,
 };
@@ -306,10 +305,6 @@ impl Client {
         }
     }
 
-    async fn sleep() {
-        sleep(Duration::from_secs(1)).await;
-    }
-
     pub(crate) async fn sync_loop_helper(
         &self,
         sync_settings: &mut crate::config::SyncSettings,
@@ -328,8 +323,9 @@ impl Client {
         }
     }
 
-    pub(crate) async fn delay_sync(last_sync_time: &mut Option<Instant>) {
-        let now = Instant::now();
+    pub(crate) async fn delay_sync(&self, last_sync_time: &mut Option<Instant>) {
+        let clock = self.clock();
+        let now = clock.now();
 
         // If the last sync happened less than a second ago, sleep for a
         // while to not hammer out requests if the server doesn't respect
@@ -337,7 +333,7 @@ impl Client {
         if let Some(t) = last_sync_time
             && now - *t <= Duration::from_secs(1)
         {
-            Self::sleep().await;
+            clock.sleep(Duration::from_secs(1)).await;
         }
 
         *last_sync_time = Some(now);
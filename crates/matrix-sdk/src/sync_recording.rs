@@ -0,0 +1,484 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Record the sync responses of a session, and replay them later.
+//!
+//! A [`SyncRecorder`] appends every sync response received by a [`Client`] to
+//! a file, after having sanitized it, so it can be attached to a bug report.
+//! A [`SyncRecording`] loads such a file, and makes another [`Client`] receive
+//! the exact same sync responses, in the same order, instead of the ones of the
+//! homeserver. This makes the bugs reported from the field reproducible in
+//! tests.
+//!
+//! Both are set up with the [`ClientBuilder`]:
+//!
+//! ```no_run
+//! # async {
+//! use matrix_sdk::{
+//!     Client,
+//!     sync_recording::{SyncRecorder, SyncRecording},
+//! };
+//!
+//! // On the device of the user.
+//! let client = Client::builder()
+//!     .homeserver_url("https://example.org")
+//!     .record_sync_responses(SyncRecorder::create("sync.jsonl")?)
+//!     .build()
+//!     .await?;
+//!
+//! // In a test.
+//! let client = Client::builder()
+//!     .homeserver_url("https://example.org")
+//!     .replay_sync_responses(SyncRecording::load("sync.jsonl")?)
+//!     .build()
+//!     .await?;
+//! # anyhow::Ok(()) };
+//! ```
+//!
+//! Recording and replaying is only available on native targets.
+//!
+//! [`Client`]: crate::Client
+//! [`ClientBuilder`]: crate::ClientBuilder
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::{Mutex, mpsc},
+};
+
+use bytes::Bytes;
+use ruma::{EventId, RoomAliasId, RoomId, UserId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// The keys whose string values are replaced by [`sanitize_sync_response`].
+const SANITIZED_KEYS: &[&str] = &[
+    // The content of the messages.
+    "body",
+    "formatted_body",
+    "ciphertext",
+    "filename",
+    "reason",
+    // The names and topics of the rooms, and the names of the users.
+    "name",
+    "topic",
+    "displayname",
+];
+
+/// The keys whose MXC URIs are replaced by [`sanitize_sync_response`], since
+/// the media might be unencrypted.
+const SANITIZED_MEDIA_KEYS: &[&str] = &["avatar_url", "url", "thumbnail_url"];
+
+/// The key of the account data, whose strings are all replaced by
+/// [`sanitize_sync_response`], except their event types and the Matrix
+/// identifiers.
+const ACCOUNT_DATA_KEY: &str = "account_data";
+
+/// The value replacing the sanitized strings.
+const SANITIZED_VALUE: &str = "<redacted>";
+
+/// The value replacing the sanitized MXC URIs, which is still a valid MXC URI.
+const SANITIZED_MXC_URI: &str = "mxc://localhost/redacted";
+
+/// The sync endpoint a response has been received from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncEndpoint {
+    /// The `/v3/sync` endpoint.
+    Sync,
+
+    /// The sliding sync endpoint, aka `/v5/sync` or MSC4186.
+    SlidingSync,
+}
+
+impl SyncEndpoint {
+    /// Find the sync endpoint targeted by a request, from its path.
+    pub(crate) fn from_path(path: &str) -> Option<Self> {
+        if !path.ends_with("/sync") {
+            return None;
+        }
+
+        if path.contains("simplified_msc3575") || path.contains("/v5/") {
+            Some(Self::SlidingSync)
+        } else if path.contains("/_matrix/client/") {
+            Some(Self::Sync)
+        } else {
+            None
+        }
+    }
+}
+
+/// A sync response, as stored in a recording.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedSyncResponse {
+    /// The endpoint the response has been received from.
+    pub endpoint: SyncEndpoint,
+
+    /// The (sanitized) body of the response.
+    pub body: JsonValue,
+}
+
+/// Sanitize a sync response, so it can be shared without leaking the content
+/// of the messages.
+///
+/// The bodies of the messages, the ciphertexts of the encrypted events, the
+/// names of the rooms and users, the media URLs, and all the strings of the
+/// account data except the event types and the Matrix identifiers, are
+/// replaced by a placeholder. The structure of the response and the
+/// identifiers are kept, so it can still be deserialized.
+pub fn sanitize_sync_response(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if key == ACCOUNT_DATA_KEY {
+                    sanitize_all_strings(value);
+                } else if value.is_string() && SANITIZED_KEYS.contains(&key.as_str()) {
+                    *value = SANITIZED_VALUE.into();
+                } else if value.is_string() && SANITIZED_MEDIA_KEYS.contains(&key.as_str()) {
+                    *value = SANITIZED_MXC_URI.into();
+                } else {
+                    sanitize_sync_response(value);
+                }
+            }
+        }
+        JsonValue::Array(array) => array.iter_mut().for_each(sanitize_sync_response),
+        _ => {}
+    }
+}
+
+/// Replace all the strings of the value, except the event types and the Matrix
+/// identifiers.
+///
+/// The identifiers are kept because the account data events reference users,
+/// rooms and events, e.g. in `m.direct` or `m.fully_read`, and they can't be
+/// deserialized without them.
+fn sanitize_all_strings(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if key != "type" {
+                    sanitize_all_strings(value);
+                }
+            }
+        }
+        JsonValue::Array(array) => array.iter_mut().for_each(sanitize_all_strings),
+        JsonValue::String(string) if !is_matrix_id(string) => {
+            *string = SANITIZED_VALUE.to_owned();
+        }
+        _ => {}
+    }
+}
+
+/// Whether the given string is a user, room, room alias or event ID.
+fn is_matrix_id(string: &str) -> bool {
+    UserId::parse(string).is_ok()
+        || RoomId::parse(string).is_ok()
+        || RoomAliasId::parse(string).is_ok()
+        || EventId::parse(string).is_ok()
+}
+
+/// Records the sync responses received by a client into a file, one JSON
+/// object per line.
+///
+/// The responses are sanitized and written by a dedicated thread, so that
+/// recording them doesn't block the sync.
+///
+/// Set it up with [`ClientBuilder::record_sync_responses`].
+///
+/// [`ClientBuilder::record_sync_responses`]: crate::ClientBuilder::record_sync_responses
+#[derive(Clone)]
+pub struct SyncRecorder {
+    sender: mpsc::Sender<RecorderCommand>,
+}
+
+/// A command sent to the writer thread of a [`SyncRecorder`].
+enum RecorderCommand {
+    /// Record the raw body of a sync response.
+    Record(SyncEndpoint, Bytes),
+
+    /// Notify the sender once all the previous responses have been written.
+    Flush(oneshot::Sender<()>),
+}
+
+impl fmt::Debug for SyncRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncRecorder").finish_non_exhaustive()
+    }
+}
+
+impl SyncRecorder {
+    /// Create a recorder appending the sync responses to the file at the given
+    /// path, which is created if it doesn't exist.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("sync-recorder".to_owned())
+            .spawn(move || write_responses(BufWriter::new(file), receiver))?;
+
+        Ok(Self { sender })
+    }
+
+    /// Record the raw body of a successful sync response.
+    ///
+    /// Failing to record a response must not break the sync, so errors are
+    /// only logged.
+    pub(crate) fn record(&self, endpoint: SyncEndpoint, body: &Bytes) {
+        if self.sender.send(RecorderCommand::Record(endpoint, body.clone())).is_err() {
+            warn!("Failed to record the sync response: the recorder has stopped");
+        }
+    }
+
+    /// Wait until all the sync responses recorded so far have been written to
+    /// the file.
+    pub async fn flush(&self) {
+        let (sender, receiver) = oneshot::channel();
+
+        if self.sender.send(RecorderCommand::Flush(sender)).is_ok() {
+            // An error means that the writer thread has stopped, so there's nothing
+            // left to wait for.
+            let _ = receiver.await;
+        }
+    }
+}
+
+/// The loop of the writer thread of a [`SyncRecorder`], which stops once all
+/// the recorders have been dropped.
+fn write_responses(mut writer: BufWriter<File>, receiver: mpsc::Receiver<RecorderCommand>) {
+    for command in receiver {
+        match command {
+            RecorderCommand::Record(endpoint, body) => {
+                let mut body = match serde_json::from_slice(&body) {
+                    Ok(body) => body,
+                    Err(error) => {
+                        warn!("Failed to parse the sync response to record it: {error}");
+                        continue;
+                    }
+                };
+                sanitize_sync_response(&mut body);
+
+                if let Err(error) =
+                    write_response(&mut writer, &RecordedSyncResponse { endpoint, body })
+                {
+                    warn!("Failed to record the sync response: {error}");
+                }
+            }
+
+            RecorderCommand::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+fn write_response(writer: &mut BufWriter<File>, response: &RecordedSyncResponse) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, response)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// A sequence of recorded sync responses, to be replayed by a client.
+///
+/// Set it up with [`ClientBuilder::replay_sync_responses`].
+///
+/// [`ClientBuilder::replay_sync_responses`]: crate::ClientBuilder::replay_sync_responses
+#[derive(Clone, Debug, Default)]
+pub struct SyncRecording {
+    responses: Vec<RecordedSyncResponse>,
+}
+
+impl SyncRecording {
+    /// Load a recording written by a [`SyncRecorder`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut responses = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            responses.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(Self { responses })
+    }
+
+    /// Create a recording from the given responses, e.g. built by hand in a
+    /// test.
+    pub fn from_responses(responses: Vec<RecordedSyncResponse>) -> Self {
+        Self { responses }
+    }
+
+    /// The recorded responses, in order.
+    pub fn responses(&self) -> &[RecordedSyncResponse] {
+        &self.responses
+    }
+}
+
+/// Serves the responses of a [`SyncRecording`] in place of the homeserver.
+#[derive(Debug)]
+pub(crate) struct SyncReplayer {
+    sync: Mutex<VecDeque<JsonValue>>,
+    sliding_sync: Mutex<VecDeque<JsonValue>>,
+}
+
+impl SyncReplayer {
+    pub(crate) fn new(recording: SyncRecording) -> Self {
+        let mut sync = VecDeque::new();
+        let mut sliding_sync = VecDeque::new();
+
+        for response in recording.responses {
+            match response.endpoint {
+                SyncEndpoint::Sync => sync.push_back(response.body),
+                SyncEndpoint::SlidingSync => sliding_sync.push_back(response.body),
+            }
+        }
+
+        Self { sync: Mutex::new(sync), sliding_sync: Mutex::new(sliding_sync) }
+    }
+
+    /// The body of the next response to replay for the given endpoint, if any
+    /// is left.
+    pub(crate) fn next(&self, endpoint: SyncEndpoint) -> Option<Bytes> {
+        let queue = match endpoint {
+            SyncEndpoint::Sync => &self.sync,
+            SyncEndpoint::SlidingSync => &self.sliding_sync,
+        };
+
+        let body = queue.lock().unwrap().pop_front()?;
+        Some(serde_json::to_vec(&body).expect("a JSON value can always be serialized").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{SyncEndpoint, sanitize_sync_response};
+
+    #[test]
+    fn test_sync_endpoint_from_path() {
+        assert_eq!(SyncEndpoint::from_path("/_matrix/client/v3/sync"), Some(SyncEndpoint::Sync));
+        assert_eq!(
+            SyncEndpoint::from_path("/_matrix/client/unstable/org.matrix.simplified_msc3575/sync"),
+            Some(SyncEndpoint::SlidingSync)
+        );
+        assert_eq!(SyncEndpoint::from_path("/_matrix/client/v3/account/whoami"), None);
+    }
+
+    #[test]
+    fn test_sanitize_sync_response() {
+        let mut response = json!({
+            "rooms": {
+                "join": {
+                    "!room:example.org": {
+                        "timeline": {
+                            "events": [{
+                                "type": "m.room.message",
+                                "content": {
+                                    "msgtype": "m.text",
+                                    "body": "secret",
+                                    "formatted_body": "<b>secret</b>",
+                                },
+                            }, {
+                                "type": "m.room.encrypted",
+                                "content": {
+                                    "algorithm": "m.megolm.v1.aes-sha2",
+                                    "ciphertext": "AwgAEnAC",
+                                },
+                            }, {
+                                "type": "m.room.member",
+                                "state_key": "@alice:example.org",
+                                "content": {
+                                    "membership": "join",
+                                    "displayname": "Alice",
+                                    "avatar_url": "mxc://example.org/alice",
+                                },
+                            }, {
+                                "type": "m.room.name",
+                                "state_key": "",
+                                "content": { "name": "Secret plans" },
+                            }],
+                        },
+                        "account_data": {
+                            "events": [{
+                                "type": "m.tag",
+                                "content": { "tags": { "u.secret": { "order": 0.5 } } },
+                            }],
+                        },
+                    },
+                },
+            },
+            "account_data": {
+                "events": [{
+                    "type": "m.direct",
+                    "content": { "@bob:example.org": ["!dm:example.org"] },
+                }, {
+                    "type": "m.secret_storage.default_key",
+                    "content": { "key": "secret_key_id" },
+                }],
+            },
+            "to_device": {
+                "events": [{
+                    "type": "m.room.encrypted",
+                    "content": {
+                        "algorithm": "m.olm.v1.curve25519-aes-sha2",
+                        "ciphertext": { "curve_key": { "type": 0, "body": "AwogHmrA" } },
+                    },
+                }],
+            },
+        });
+
+        sanitize_sync_response(&mut response);
+
+        let events = &response["rooms"]["join"]["!room:example.org"]["timeline"]["events"];
+        assert_eq!(events[0]["content"]["msgtype"], "m.text");
+        assert_eq!(events[0]["content"]["body"], "<redacted>");
+        assert_eq!(events[0]["content"]["formatted_body"], "<redacted>");
+        assert_eq!(events[1]["content"]["ciphertext"], "<redacted>");
+        assert_eq!(events[2]["content"]["membership"], "join");
+        assert_eq!(events[2]["content"]["displayname"], "<redacted>");
+        assert_eq!(events[2]["content"]["avatar_url"], "mxc://localhost/redacted");
+        assert_eq!(events[3]["content"]["name"], "<redacted>");
+
+        // The strings of the account data are sanitized, but not the event types nor
+        // the identifiers.
+        let room_account_data =
+            &response["rooms"]["join"]["!room:example.org"]["account_data"]["events"][0];
+        assert_eq!(room_account_data["type"], "m.tag");
+        assert_eq!(room_account_data["content"]["tags"]["u.secret"]["order"], 0.5);
+        let global_account_data = &response["account_data"]["events"][0];
+        assert_eq!(global_account_data["type"], "m.direct");
+        assert_eq!(global_account_data["content"]["@bob:example.org"][0], "!dm:example.org");
+        let global_account_data = &response["account_data"]["events"][1];
+        assert_eq!(global_account_data["content"]["key"], "<redacted>");
+        // The sanitized account data can still be deserialized.
+        serde_json::from_value::<ruma::events::direct::DirectEventContent>(
+            response["account_data"]["events"][0]["content"].clone(),
+        )
+        .unwrap();
+
+        // The structure of the olm ciphertext is kept.
+        let olm_content = &response["to_device"]["events"][0]["content"];
+        assert_eq!(olm_content["ciphertext"]["curve_key"]["type"], 0);
+        assert_eq!(olm_content["ciphertext"]["curve_key"]["body"], "<redacted>");
+    }
+}
@@ -22,15 +22,15 @@ use std::{
 };
 
 use assert_matches2::assert_let;
-use matrix_sdk_test::test_json;
+use matrix_sdk_test::{JoinedRoomBuilder, StateTestEvent, event_factory::EventFactory, test_json};
 use ruma::{
-    CrossSigningKeyId, DeviceId, MilliSecondsSinceUnixEpoch, OneTimeKeyAlgorithm, OwnedDeviceId,
-    OwnedOneTimeKeyId, OwnedUserId, UserId,
+    CrossSigningKeyId, DeviceId, EventId, MilliSecondsSinceUnixEpoch, OneTimeKeyAlgorithm,
+    OwnedDeviceId, OwnedOneTimeKeyId, OwnedUserId, RoomId, UserId,
     api::client::{
         keys::upload_signatures::v3::SignedKeys, to_device::send_event_to_device::v3::Messages,
     },
     encryption::{CrossSigningKey, DeviceKeys, OneTimeKey},
-    events::AnyToDeviceEvent,
+    events::{AnySyncStateEvent, AnyToDeviceEvent, room::message::RoomMessageEventContent},
     owned_device_id, owned_user_id,
     serde::Raw,
     to_device::DeviceIdOrAllDevices,
@@ -42,8 +42,9 @@ use wiremock::{
 };
 
 use crate::{
-    Client,
+    Client, Room,
     crypto::types::events::room::encrypted::EncryptedToDeviceEvent,
+    deserialized_responses::{TimelineEvent, UnableToDecryptInfo, UnableToDecryptReason},
     test_utils::{
         client::MockClientBuilder,
         mocks::{Keys, MatrixMockServer},
@@ -504,6 +505,160 @@ impl MatrixMockServer {
                 .await;
         }
     }
+
+    /// Have the given clients join the same encrypted room, and return their
+    /// own [`Room`] for it, in order.
+    ///
+    /// The clients must have been set up for encryption beforehand, e.g. with
+    /// [`MatrixMockServer::set_up_alice_and_bob_for_encryption`].
+    pub async fn set_up_encrypted_room(
+        &self,
+        room_id: &RoomId,
+        alice: &Client,
+        bob: &Client,
+    ) -> (Room, Room) {
+        let event_factory = EventFactory::new().room(room_id);
+        let members: Vec<Raw<AnySyncStateEvent>> = [alice, bob]
+            .iter()
+            .map(|client| {
+                event_factory
+                    .member(client.user_id().expect("The client should be logged in"))
+                    .into_raw()
+            })
+            .collect();
+
+        for client in [alice, bob] {
+            self.mock_sync()
+                .ok_and_run(client, |builder| {
+                    builder.add_joined_room(
+                        JoinedRoomBuilder::new(room_id)
+                            .add_state_event(StateTestEvent::Create)
+                            .add_state_event(StateTestEvent::Encryption)
+                            .add_state_bulk(members.clone()),
+                    );
+                })
+                .await;
+        }
+
+        let alice_room = alice.get_room(room_id).expect("Alice should know about the room");
+        let bob_room = bob.get_room(room_id).expect("Bob should know about the room");
+
+        (alice_room, bob_room)
+    }
+
+    /// Send an encrypted message from `sender_room`, deliver it to `recipient`
+    /// along with the room keys, and return it as decrypted by the recipient.
+    ///
+    /// This exercises the whole encryption round-trip: the sender claims
+    /// one-time keys and shares the room key over to-device messages, which
+    /// are synced back to the recipient, before the message itself is synced
+    /// back in the room of the recipient.
+    ///
+    /// The room must have been set up with
+    /// [`MatrixMockServer::set_up_encrypted_room`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use matrix_sdk::{
+    ///     assert_decrypted_message_eq,
+    ///     ruma::{events::room::message::RoomMessageEventContent, room_id},
+    ///     test_utils::mocks::MatrixMockServer,
+    /// };
+    ///
+    /// let server = MatrixMockServer::new().await;
+    /// server.mock_crypto_endpoints_preset().await;
+    ///
+    /// let (alice, bob) = server.set_up_alice_and_bob_for_encryption().await;
+    /// let (alice_room, _) = server
+    ///     .set_up_encrypted_room(room_id!("!room:example.org"), &alice, &bob)
+    ///     .await;
+    ///
+    /// let event = server
+    ///     .send_encrypted_and_sync_back(
+    ///         &alice_room,
+    ///         RoomMessageEventContent::text_plain("It's a secret to everybody"),
+    ///         &bob,
+    ///     )
+    ///     .await;
+    ///
+    /// assert_decrypted_message_eq!(event, "It's a secret to everybody");
+    /// # });
+    /// ```
+    pub async fn send_encrypted_and_sync_back(
+        &self,
+        sender_room: &Room,
+        content: RoomMessageEventContent,
+        recipient: &Client,
+    ) -> TimelineEvent {
+        let room_id = sender_room.room_id();
+        let sender = sender_room.own_user_id();
+        let recipient_room =
+            recipient.get_room(room_id).expect("The recipient should know about the room");
+
+        let event_factory = EventFactory::new().room(room_id);
+        let members = vec![
+            event_factory.member(sender).into_raw(),
+            event_factory
+                .member(recipient.user_id().expect("The recipient should be logged in"))
+                .into_raw(),
+        ];
+
+        let to_device_queue = Arc::new(Mutex::new(PendingToDeviceMessages::new()));
+        let to_device_guard =
+            self.capture_put_to_device_traffic(sender, to_device_queue.clone()).await;
+
+        let (event_receiver, send_mock) = self
+            .mock_room_send()
+            .do_not_expect_access_token()
+            .ok_with_capture(EventId::new(sender.server_name()), sender);
+        let send_guard = send_mock.mock_once().mount_as_scoped().await;
+
+        let members_guard = self
+            .mock_get_members()
+            .do_not_expect_access_token()
+            .ok(members)
+            .mount_as_scoped()
+            .await;
+
+        let event_id = sender_room
+            .send(content)
+            .await
+            .expect("The sender should be able to send the encrypted message")
+            .event_id;
+        let event = event_receiver.await.expect("The sent event should have been captured");
+
+        drop((to_device_guard, send_guard, members_guard));
+
+        // Deliver the room key first, so the recipient is able to decrypt the message.
+        self.sync_back_pending_to_device_messages(to_device_queue, recipient).await;
+
+        self.mock_sync()
+            .ok_and_run(recipient, |builder| {
+                builder.add_joined_room(
+                    JoinedRoomBuilder::new(room_id).add_timeline_event(event.clone()),
+                );
+            })
+            .await;
+
+        let _event_guard = self
+            .mock_room_event()
+            .do_not_expect_access_token()
+            .room(room_id)
+            .match_event_id()
+            .ok(TimelineEvent::from_utd(
+                event,
+                UnableToDecryptInfo { session_id: None, reason: UnableToDecryptReason::Unknown },
+            ))
+            .mount_as_scoped()
+            .await;
+
+        recipient_room
+            .event(&event_id, None)
+            .await
+            .expect("The recipient should be able to fetch the sent event")
+    }
 }
 
 /// Intercepts a `/keys/query` request and mock its results as returned by an
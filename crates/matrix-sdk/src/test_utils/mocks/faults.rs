@@ -0,0 +1,221 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to inject network faults in the responses of the mock server, for
+//! the purpose of testing the retry, offline and send queue behaviors under
+//! realistic failure patterns.
+//!
+//! A [`FaultInjector`] is attached to a mocked endpoint with
+//! [`MockEndpoint::with_faults`]. Every request received by this endpoint
+//! consumes the next queued [`Fault`], if any; once the queue is empty, the
+//! endpoint responds normally again.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::json;
+use wiremock::{Request, Respond, ResponseTemplate};
+
+use super::MockEndpoint;
+
+/// A fault that can be injected in the response of a mocked endpoint.
+#[derive(Clone, Debug)]
+pub enum Fault {
+    /// Respond normally, but only after the given delay.
+    Latency(Duration),
+
+    /// Respond with the given HTTP status code, and a non-Matrix body, like a
+    /// reverse proxy would do when the homeserver is unreachable, e.g. with a
+    /// `502 Bad Gateway`.
+    Status(u16),
+
+    /// Respond with a `429 Too Many Requests`, and a `M_LIMIT_EXCEEDED` error
+    /// with the given `retry_after_ms`.
+    RateLimited {
+        /// The time after which the request can be retried.
+        retry_after: Option<Duration>,
+    },
+
+    /// Respond with a successful status code, but a body that isn't valid
+    /// JSON.
+    CorruptBody,
+
+    /// Respond with a successful status code, but close the connection before
+    /// the end of the body: the response announces a longer body than the one
+    /// that is actually sent.
+    DropConnection,
+}
+
+/// A queue of [`Fault`]s to inject in the responses of one or several mocked
+/// endpoints.
+///
+/// Cloning it returns a handle to the same queue, so faults can be queued
+/// after the mock has been mounted.
+#[derive(Clone, Debug, Default)]
+pub struct FaultInjector {
+    inner: Arc<Mutex<FaultInjectorInner>>,
+}
+
+#[derive(Debug, Default)]
+struct FaultInjectorInner {
+    /// The faults to inject in the next responses, in order.
+    pending: VecDeque<Fault>,
+
+    /// The number of faults that have been injected so far.
+    injected: usize,
+}
+
+impl FaultInjector {
+    /// Create a new injector, without any queued fault.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a fault, to be injected after all the already queued ones.
+    pub fn push(&self, fault: Fault) {
+        self.inner.lock().unwrap().pending.push_back(fault);
+    }
+
+    /// Queue a sequence of faults, e.g. several `502` followed by a `429`.
+    pub fn extend(&self, faults: impl IntoIterator<Item = Fault>) {
+        self.inner.lock().unwrap().pending.extend(faults);
+    }
+
+    /// Queue the same fault `times` times in a row.
+    pub fn repeat(&self, fault: Fault, times: usize) {
+        self.extend(std::iter::repeat_n(fault, times));
+    }
+
+    /// Forget about all the queued faults.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().pending.clear();
+    }
+
+    /// The number of faults that are queued, and haven't been injected yet.
+    pub fn pending(&self) -> usize {
+        self.inner.lock().unwrap().pending.len()
+    }
+
+    /// The number of faults that have been injected so far.
+    pub fn injected(&self) -> usize {
+        self.inner.lock().unwrap().injected
+    }
+
+    fn next(&self) -> Option<Fault> {
+        let mut inner = self.inner.lock().unwrap();
+        let fault = inner.pending.pop_front()?;
+        inner.injected += 1;
+        Some(fault)
+    }
+
+    /// Wrap the given responder, so that the queued faults are injected in its
+    /// responses.
+    pub(super) fn wrap<R: Respond>(&self, responder: R) -> FaultyResponder<R> {
+        FaultyResponder { inner: responder, faults: self.clone() }
+    }
+}
+
+/// A [`Respond`] implementation which injects the faults of a
+/// [`FaultInjector`] in the responses of another one.
+pub(super) struct FaultyResponder<R> {
+    inner: R,
+    faults: FaultInjector,
+}
+
+impl<R: Respond> Respond for FaultyResponder<R> {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let Some(fault) = self.faults.next() else {
+            return self.inner.respond(request);
+        };
+
+        match fault {
+            Fault::Latency(delay) => self.inner.respond(request).set_delay(delay),
+
+            Fault::Status(status) => ResponseTemplate::new(status)
+                .set_body_raw("<html><body>Injected fault</body></html>", "text/html"),
+
+            Fault::RateLimited { retry_after } => {
+                let mut body = json!({
+                    "errcode": "M_LIMIT_EXCEEDED",
+                    "error": "Too many requests",
+                });
+                if let Some(retry_after) = retry_after {
+                    body["retry_after_ms"] = json!(retry_after.as_millis());
+                }
+                ResponseTemplate::new(429).set_body_json(body)
+            }
+
+            Fault::CorruptBody => ResponseTemplate::new(200)
+                .set_body_raw("{\"this is not\": json", "application/json"),
+
+            Fault::DropConnection => ResponseTemplate::new(200)
+                .insert_header("content-length", "4096")
+                .set_body_raw("{\"truncated\":", "application/json"),
+        }
+    }
+}
+
+impl<'a, T> MockEndpoint<'a, T> {
+    /// Inject the faults queued in the given [`FaultInjector`] in the
+    /// responses of this endpoint.
+    ///
+    /// Every request consumes one fault; once there are no more queued faults,
+    /// the endpoint responds normally. Faulty responses still count as
+    /// received requests for [`MatrixMock::expect`](super::MatrixMock::expect).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use matrix_sdk::{
+    ///     config::RequestConfig,
+    ///     test_utils::mocks::{
+    ///         MatrixMockServer,
+    ///         faults::{Fault, FaultInjector},
+    ///     },
+    /// };
+    ///
+    /// let server = MatrixMockServer::new().await;
+    /// let client = server
+    ///     .client_builder()
+    ///     .on_builder(|builder| {
+    ///         builder.request_config(RequestConfig::new().retry_limit(3))
+    ///     })
+    ///     .build()
+    ///     .await;
+    ///
+    /// let faults = FaultInjector::new();
+    /// faults.repeat(Fault::RateLimited { retry_after: None }, 2);
+    ///
+    /// server
+    ///     .mock_who_am_i()
+    ///     .with_faults(faults.clone())
+    ///     .ok()
+    ///     .expect(3)
+    ///     .mount()
+    ///     .await;
+    ///
+    /// // The request is retried until the server stops failing.
+    /// client.whoami().await?;
+    /// assert_eq!(faults.injected(), 2);
+    /// # anyhow::Ok(()) });
+    /// ```
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+}
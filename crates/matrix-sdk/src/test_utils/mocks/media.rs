@@ -0,0 +1,211 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to mock the media endpoints, and to assert on the media that has
+//! actually been uploaded, for the purpose of testing attachments and the
+//! send queue.
+
+use std::sync::{Arc, Mutex};
+
+use ruma::{MxcUri, OwnedMxcUri};
+use serde_json::json;
+use wiremock::{Match, Request, ResponseTemplate};
+
+use super::{
+    AuthedMediaDownloadEndpoint, AuthedMediaThumbnailEndpoint, MatrixMock, MediaDownloadEndpoint,
+    MediaThumbnailEndpoint, MockEndpoint, UploadEndpoint,
+};
+
+/// A media file uploaded to the mock server.
+#[derive(Clone, Debug)]
+pub struct UploadedMedia {
+    /// The value of the `Content-Type` header of the upload, if any.
+    pub content_type: Option<String>,
+
+    /// The value of the `filename` query parameter of the upload, if any.
+    pub filename: Option<String>,
+
+    /// The uploaded bytes.
+    pub data: Vec<u8>,
+
+    /// The MXC URI the mock server returned for this upload.
+    pub mxc_uri: OwnedMxcUri,
+}
+
+/// The media files uploaded to an endpoint mocked with
+/// [`MockEndpoint::<UploadEndpoint>::ok_with_capture_all`], in the order in
+/// which they have been uploaded.
+///
+/// Cloning it returns a handle to the same list of uploads.
+#[derive(Clone, Debug, Default)]
+pub struct UploadCapture {
+    uploads: Arc<Mutex<Vec<UploadedMedia>>>,
+}
+
+impl UploadCapture {
+    /// All the media files uploaded so far.
+    pub fn uploads(&self) -> Vec<UploadedMedia> {
+        self.uploads.lock().unwrap().clone()
+    }
+
+    /// The last media file uploaded so far, if any.
+    pub fn last(&self) -> Option<UploadedMedia> {
+        self.uploads.lock().unwrap().last().cloned()
+    }
+}
+
+impl<'a> MockEndpoint<'a, UploadEndpoint> {
+    /// Returns an upload endpoint that emulates success for all the uploads,
+    /// and captures every one of them with its content type and file name.
+    ///
+    /// Every upload gets its own MXC URI on the given server, so the uploads
+    /// of a media and of its thumbnail can be told apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use matrix_sdk::test_utils::mocks::MatrixMockServer;
+    ///
+    /// let server = MatrixMockServer::new().await;
+    /// let (uploads, upload_mock) =
+    ///     server.mock_upload().ok_with_capture_all("localhost");
+    /// upload_mock.expect(2).mount().await;
+    ///
+    /// let client = server.client_builder().build().await;
+    /// client.media().upload(&mime::TEXT_PLAIN, b"hello".to_vec(), None).await?;
+    /// client.media().upload(&mime::IMAGE_PNG, b"world".to_vec(), None).await?;
+    ///
+    /// let uploads = uploads.uploads();
+    /// assert_eq!(uploads.len(), 2);
+    /// assert_eq!(uploads[0].content_type.as_deref(), Some("text/plain"));
+    /// assert_eq!(uploads[0].data, b"hello");
+    /// assert_eq!(uploads[1].content_type.as_deref(), Some("image/png"));
+    /// assert_ne!(uploads[0].mxc_uri, uploads[1].mxc_uri);
+    /// # anyhow::Ok(()) });
+    /// ```
+    pub fn ok_with_capture_all(self, server_name: &str) -> (UploadCapture, MatrixMock<'a>) {
+        let capture = UploadCapture::default();
+        let uploads = capture.uploads.clone();
+        let server_name = server_name.to_owned();
+
+        let mock = self.respond_with(move |request: &Request| {
+            let mut uploads = uploads.lock().unwrap();
+
+            let mxc_uri = OwnedMxcUri::from(format!("mxc://{server_name}/{}", uploads.len()));
+            let content_type = request
+                .headers
+                .get("content-type")
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned);
+            let filename = request
+                .url
+                .query_pairs()
+                .find_map(|(key, value)| (key == "filename").then(|| value.into_owned()));
+
+            uploads.push(UploadedMedia {
+                content_type,
+                filename,
+                data: request.body.clone(),
+                mxc_uri: mxc_uri.clone(),
+            });
+
+            ResponseTemplate::new(200).set_body_json(json!({ "content_uri": mxc_uri }))
+        });
+
+        (capture, mock)
+    }
+}
+
+impl<'a> MockEndpoint<'a, MediaDownloadEndpoint> {
+    /// Limits the scope of this mock to the given media.
+    pub fn match_mxc_uri(self, mxc_uri: &MxcUri) -> Self {
+        Self { mock: self.mock.and(MxcUriMatcher::new(mxc_uri)), ..self }
+    }
+
+    /// Returns a successful response with the given bytes and content type.
+    pub fn ok_with(self, data: Vec<u8>, content_type: &str) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(200).set_body_raw(data, content_type))
+    }
+}
+
+impl<'a> MockEndpoint<'a, AuthedMediaDownloadEndpoint> {
+    /// Limits the scope of this mock to the given media.
+    pub fn match_mxc_uri(self, mxc_uri: &MxcUri) -> Self {
+        Self { mock: self.mock.and(MxcUriMatcher::new(mxc_uri)), ..self }
+    }
+
+    /// Returns a successful response with the given bytes and content type.
+    pub fn ok_with(self, data: Vec<u8>, content_type: &str) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(200).set_body_raw(data, content_type))
+    }
+}
+
+impl<'a> MockEndpoint<'a, MediaThumbnailEndpoint> {
+    /// Limits the scope of this mock to the thumbnails of the given media.
+    pub fn match_mxc_uri(self, mxc_uri: &MxcUri) -> Self {
+        Self { mock: self.mock.and(MxcUriMatcher::new(mxc_uri)), ..self }
+    }
+
+    /// Returns a successful response with the given bytes and content type.
+    pub fn ok_with(self, data: Vec<u8>, content_type: &str) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(200).set_body_raw(data, content_type))
+    }
+}
+
+impl<'a> MockEndpoint<'a, AuthedMediaThumbnailEndpoint> {
+    /// Limits the scope of this mock to the thumbnails of the given media.
+    pub fn match_mxc_uri(self, mxc_uri: &MxcUri) -> Self {
+        Self { mock: self.mock.and(MxcUriMatcher::new(mxc_uri)), ..self }
+    }
+
+    /// Returns a successful response with the given bytes and content type.
+    pub fn ok_with(self, data: Vec<u8>, content_type: &str) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(200).set_body_raw(data, content_type))
+    }
+}
+
+/// Matches the download and thumbnail requests of a single media.
+///
+/// The paths of these requests end with `{server_name}/{media_id}`, optionally
+/// followed by a file name for downloads.
+struct MxcUriMatcher {
+    server_name: String,
+    media_id: String,
+}
+
+impl MxcUriMatcher {
+    fn new(mxc_uri: &MxcUri) -> Self {
+        let (server_name, media_id) = mxc_uri.parts().expect("The MXC URI should be valid");
+        Self { server_name: server_name.to_string(), media_id: media_id.to_owned() }
+    }
+}
+
+impl Match for MxcUriMatcher {
+    fn matches(&self, request: &Request) -> bool {
+        let Some(segments) = request.url.path_segments() else {
+            return false;
+        };
+        let segments: Vec<_> = segments.collect();
+
+        let Some(position) =
+            segments.iter().position(|segment| *segment == "download" || *segment == "thumbnail")
+        else {
+            return false;
+        };
+
+        segments.get(position + 1) == Some(&self.server_name.as_str())
+            && segments.get(position + 2) == Some(&self.media_id.as_str())
+    }
+}
@@ -68,8 +68,12 @@ use wiremock::{
 
 #[cfg(feature = "e2e-encryption")]
 pub mod encryption;
+pub mod faults;
+pub mod media;
 pub mod oauth;
+pub mod sliding_sync;
 
+use self::faults::FaultInjector;
 use super::client::MockClientBuilder;
 use crate::{Client, OwnedServerName, Room, SlidingSyncBuilder, room::IncludeRelations};
 
@@ -1273,6 +1277,16 @@ impl MatrixMockServer {
         self.mock_endpoint(mock, RoomAccountDataEndpoint).expect_default_access_token()
     }
 
+    /// Create a prebuilt mock for the endpoint used to set global account data.
+    pub fn mock_set_global_account_data(
+        &self,
+        data_type: GlobalAccountDataEventType,
+    ) -> MockEndpoint<'_, GlobalAccountDataEndpoint> {
+        let mock = Mock::given(method("PUT"))
+            .and(path_regex(format!("^/_matrix/client/v3/user/.*/account_data/{data_type}")));
+        self.mock_endpoint(mock, GlobalAccountDataEndpoint).expect_default_access_token()
+    }
+
     /// Create a prebuilt mock for the endpoint used to get the media config of
     /// the homeserver that requires authentication.
     pub fn mock_authenticated_media_config(
@@ -1289,6 +1303,15 @@ impl MatrixMockServer {
         self.mock_endpoint(mock, MediaConfigEndpoint)
     }
 
+    /// Create a prebuilt mock for the endpoint used to get the preview of a
+    /// URL, that requires authentication.
+    pub fn mock_authenticated_media_preview(
+        &self,
+    ) -> MockEndpoint<'_, AuthenticatedMediaPreviewEndpoint> {
+        let mock = Mock::given(method("GET")).and(path("/_matrix/client/v1/media/preview_url"));
+        self.mock_endpoint(mock, AuthenticatedMediaPreviewEndpoint).expect_default_access_token()
+    }
+
     /// Create a prebuilt mock for the endpoint used to log into a session.
     pub fn mock_login(&self) -> MockEndpoint<'_, LoginEndpoint> {
         let mock = Mock::given(method("POST")).and(path("/_matrix/client/v3/login"));
@@ -1671,11 +1694,18 @@ pub struct MockEndpoint<'a, T> {
     mock: MockBuilder,
     endpoint: T,
     expected_access_token: ExpectedAccessToken,
+    faults: Option<FaultInjector>,
 }
 
 impl<'a, T> MockEndpoint<'a, T> {
     fn new(server: &'a MockServer, mock: MockBuilder, endpoint: T) -> Self {
-        Self { server, mock, endpoint, expected_access_token: ExpectedAccessToken::None }
+        Self {
+            server,
+            mock,
+            endpoint,
+            expected_access_token: ExpectedAccessToken::None,
+            faults: None,
+        }
     }
 
     /// Expect authentication with the default access token on this endpoint.
@@ -1741,10 +1771,11 @@ impl<'a, T> MockEndpoint<'a, T> {
     /// # anyhow::Ok(()) });
     /// ```
     pub fn respond_with<R: Respond + 'static>(self, func: R) -> MatrixMock<'a> {
-        let mock = self
-            .expected_access_token
-            .maybe_match_authorization_header(self.mock)
-            .respond_with(func);
+        let mock = self.expected_access_token.maybe_match_authorization_header(self.mock);
+        let mock = match self.faults {
+            Some(faults) => mock.respond_with(faults.wrap(func)),
+            None => mock.respond_with(func),
+        };
         MatrixMock { mock, server: self.server }
     }
 
@@ -3685,6 +3716,22 @@ impl<'a> MockEndpoint<'a, RoomAccountDataEndpoint> {
     }
 }
 
+/// A prebuilt mock for `PUT /user/{userId}/account_data/{type}` request.
+pub struct GlobalAccountDataEndpoint;
+
+impl<'a> MockEndpoint<'a, GlobalAccountDataEndpoint> {
+    /// Ensures that the body of the request is a superset of the provided
+    /// `body` parameter.
+    pub fn body_matches_partial_json(self, body: Value) -> Self {
+        Self { mock: self.mock.and(body_partial_json(body)), ..self }
+    }
+
+    /// Returns a successful empty response.
+    pub fn ok(self) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+    }
+}
+
 /// A prebuilt mock for `GET /_matrix/client/v1/media/config` request.
 pub struct AuthenticatedMediaConfigEndpoint;
 
@@ -3716,6 +3763,21 @@ impl<'a> MockEndpoint<'a, MediaConfigEndpoint> {
     }
 }
 
+/// A prebuilt mock for `GET /_matrix/client/v1/media/preview_url` request.
+pub struct AuthenticatedMediaPreviewEndpoint;
+
+impl<'a> MockEndpoint<'a, AuthenticatedMediaPreviewEndpoint> {
+    /// Returns a successful response with the given OpenGraph data.
+    pub fn ok(self, data: Value) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(200).set_body_json(data))
+    }
+
+    /// Only match requests for the given URL.
+    pub fn match_url(self, url: &str) -> Self {
+        Self { mock: self.mock.and(query_param("url", url)), ..self }
+    }
+}
+
 /// A prebuilt mock for `POST /login` requests.
 pub struct LoginEndpoint;
 
@@ -0,0 +1,348 @@
+// Copyright 2025 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers to build simplified sliding sync responses, and to assert the
+//! content of the sliding sync requests, for the purpose of testing the
+//! `RoomListService`, the `SyncService`, or any other sliding sync user.
+
+use std::collections::BTreeMap;
+
+use ruma::{
+    OwnedRoomId, OwnedUserId, RoomId, UInt,
+    events::{
+        AnyGlobalAccountDataEvent, AnyRoomAccountDataEvent, AnyStrippedStateEvent,
+        AnySyncStateEvent, AnySyncTimelineEvent, AnyToDeviceEvent,
+    },
+    serde::Raw,
+};
+use serde_json::{Value, json};
+use wiremock::{
+    ResponseTemplate,
+    matchers::{body_partial_json, query_param, query_param_is_missing},
+};
+
+use super::{MatrixMock, MockEndpoint, SlidingSyncEndpoint};
+
+impl<'a> MockEndpoint<'a, SlidingSyncEndpoint> {
+    /// Ensures the request has been sent with the given `pos`, i.e. it's the
+    /// continuation of the sliding sync session that received this `pos`.
+    pub fn match_pos(self, pos: &str) -> Self {
+        Self { mock: self.mock.and(query_param("pos", pos)), ..self }
+    }
+
+    /// Ensures the request has been sent without a `pos`, i.e. it starts a
+    /// new sliding sync session.
+    pub fn match_no_pos(self) -> Self {
+        Self { mock: self.mock.and(query_param_is_missing("pos")), ..self }
+    }
+
+    /// Ensures the request contains the list named `list_name`, with the given
+    /// inclusive range.
+    pub fn match_list_range(self, list_name: &str, start: u32, end: u32) -> Self {
+        let body = json!({ "lists": { list_name: { "ranges": [[start, end]] } } });
+        Self { mock: self.mock.and(body_partial_json(body)), ..self }
+    }
+
+    /// Ensures the request subscribes to the given room.
+    pub fn match_room_subscription(self, room_id: &RoomId) -> Self {
+        let body = json!({ "room_subscriptions": { room_id: {} } });
+        Self { mock: self.mock.and(body_partial_json(body)), ..self }
+    }
+
+    /// Ensures the request enables the extension named `extension`, e.g.
+    /// `to_device` or `account_data`.
+    pub fn match_extension_enabled(self, extension: &str) -> Self {
+        let body = json!({ "extensions": { extension: { "enabled": true } } });
+        Self { mock: self.mock.and(body_partial_json(body)), ..self }
+    }
+
+    /// Mocks the sliding sync endpoint with the response built by the given
+    /// [`SlidingSyncResponseBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use matrix_sdk::test_utils::mocks::{
+    ///     MatrixMockServer,
+    ///     sliding_sync::{SlidingSyncResponseBuilder, SlidingSyncRoomBuilder},
+    /// };
+    /// use ruma::room_id;
+    ///
+    /// let mock_server = MatrixMockServer::new().await;
+    ///
+    /// mock_server
+    ///     .mock_sliding_sync()
+    ///     .match_list_range("all_rooms", 0, 19)
+    ///     .ok_with(
+    ///         SlidingSyncResponseBuilder::new("0").list("all_rooms", 1).room(
+    ///             room_id!("!r0:matrix.org"),
+    ///             SlidingSyncRoomBuilder::new().name("Room #0").initial(),
+    ///         ),
+    ///     )
+    ///     .mock_once()
+    ///     .mount()
+    ///     .await;
+    /// # anyhow::Ok(()) });
+    /// ```
+    pub fn ok_with(self, response: SlidingSyncResponseBuilder) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(200).set_body_json(response.build_json()))
+    }
+
+    /// Mocks the sliding sync endpoint with an `M_UNKNOWN_POS` error, which
+    /// forces the client to start a new sliding sync session.
+    pub fn error_unknown_pos(self) -> MatrixMock<'a> {
+        self.respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errcode": "M_UNKNOWN_POS",
+            "error": "Unknown position",
+        })))
+    }
+}
+
+/// A builder for a simplified sliding sync response.
+#[derive(Debug, Default)]
+pub struct SlidingSyncResponseBuilder {
+    pos: String,
+    lists: BTreeMap<String, UInt>,
+    rooms: BTreeMap<OwnedRoomId, Value>,
+    to_device: Option<(String, Vec<Raw<AnyToDeviceEvent>>)>,
+    global_account_data: Vec<Raw<AnyGlobalAccountDataEvent>>,
+    room_account_data: BTreeMap<OwnedRoomId, Vec<Raw<AnyRoomAccountDataEvent>>>,
+    typing: BTreeMap<OwnedRoomId, Vec<OwnedUserId>>,
+    receipts: BTreeMap<OwnedRoomId, Value>,
+    device_lists_changed: Vec<OwnedUserId>,
+}
+
+impl SlidingSyncResponseBuilder {
+    /// Create a new, empty response, with the given `pos`.
+    pub fn new(pos: impl Into<String>) -> Self {
+        Self { pos: pos.into(), ..Default::default() }
+    }
+
+    /// Add a list, with the total number of rooms it contains.
+    pub fn list(mut self, name: &str, count: u32) -> Self {
+        self.lists.insert(name.to_owned(), count.into());
+        self
+    }
+
+    /// Add a room, from a list or a room subscription.
+    pub fn room(mut self, room_id: &RoomId, room: SlidingSyncRoomBuilder) -> Self {
+        self.rooms.insert(room_id.to_owned(), room.build_json());
+        self
+    }
+
+    /// Add the `to_device` extension, with the given `next_batch` token and
+    /// events.
+    pub fn to_device(
+        mut self,
+        next_batch: impl Into<String>,
+        events: impl IntoIterator<Item = Raw<AnyToDeviceEvent>>,
+    ) -> Self {
+        self.to_device = Some((next_batch.into(), events.into_iter().collect()));
+        self
+    }
+
+    /// Add a global account data event to the `account_data` extension.
+    pub fn global_account_data(mut self, event: impl Into<Raw<AnyGlobalAccountDataEvent>>) -> Self {
+        self.global_account_data.push(event.into());
+        self
+    }
+
+    /// Add a room account data event to the `account_data` extension.
+    pub fn room_account_data(
+        mut self,
+        room_id: &RoomId,
+        event: impl Into<Raw<AnyRoomAccountDataEvent>>,
+    ) -> Self {
+        self.room_account_data.entry(room_id.to_owned()).or_default().push(event.into());
+        self
+    }
+
+    /// Set the users typing in a room, in the `typing` extension.
+    pub fn typing(
+        mut self,
+        room_id: &RoomId,
+        user_ids: impl IntoIterator<Item = OwnedUserId>,
+    ) -> Self {
+        self.typing.insert(room_id.to_owned(), user_ids.into_iter().collect());
+        self
+    }
+
+    /// Set the content of the `m.receipt` event of a room, in the `receipts`
+    /// extension.
+    pub fn receipts(mut self, room_id: &RoomId, content: Value) -> Self {
+        self.receipts.insert(room_id.to_owned(), content);
+        self
+    }
+
+    /// Add a user whose devices changed, in the `e2ee` extension.
+    pub fn device_list_changed(mut self, user_id: OwnedUserId) -> Self {
+        self.device_lists_changed.push(user_id);
+        self
+    }
+
+    /// Build the JSON body of the response.
+    pub fn build_json(self) -> Value {
+        let lists: BTreeMap<_, _> =
+            self.lists.into_iter().map(|(name, count)| (name, json!({ "count": count }))).collect();
+
+        let mut extensions = serde_json::Map::new();
+
+        if let Some((next_batch, events)) = self.to_device {
+            extensions.insert(
+                "to_device".to_owned(),
+                json!({ "next_batch": next_batch, "events": events }),
+            );
+        }
+
+        if !self.global_account_data.is_empty() || !self.room_account_data.is_empty() {
+            extensions.insert(
+                "account_data".to_owned(),
+                json!({ "global": self.global_account_data, "rooms": self.room_account_data }),
+            );
+        }
+
+        if !self.typing.is_empty() {
+            let rooms: BTreeMap<_, _> = self
+                .typing
+                .into_iter()
+                .map(|(room_id, user_ids)| {
+                    (room_id, json!({ "type": "m.typing", "content": { "user_ids": user_ids } }))
+                })
+                .collect();
+            extensions.insert("typing".to_owned(), json!({ "rooms": rooms }));
+        }
+
+        if !self.receipts.is_empty() {
+            let rooms: BTreeMap<_, _> = self
+                .receipts
+                .into_iter()
+                .map(|(room_id, content)| {
+                    (room_id, json!({ "type": "m.receipt", "content": content }))
+                })
+                .collect();
+            extensions.insert("receipts".to_owned(), json!({ "rooms": rooms }));
+        }
+
+        if !self.device_lists_changed.is_empty() {
+            extensions.insert(
+                "e2ee".to_owned(),
+                json!({ "device_lists": { "changed": self.device_lists_changed, "left": [] } }),
+            );
+        }
+
+        json!({
+            "pos": self.pos,
+            "lists": lists,
+            "rooms": self.rooms,
+            "extensions": extensions,
+        })
+    }
+}
+
+/// A builder for a room in a [`SlidingSyncResponseBuilder`].
+#[derive(Debug, Default)]
+pub struct SlidingSyncRoomBuilder {
+    fields: serde_json::Map<String, Value>,
+    timeline: Vec<Raw<AnySyncTimelineEvent>>,
+    required_state: Vec<Raw<AnySyncStateEvent>>,
+    invite_state: Option<Vec<Raw<AnyStrippedStateEvent>>>,
+}
+
+impl SlidingSyncRoomBuilder {
+    /// Create a new room, without any field.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn field(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.fields.insert(name.to_owned(), value.into());
+        self
+    }
+
+    /// Set the name of the room, as computed by the server.
+    pub fn name(self, name: &str) -> Self {
+        self.field("name", name)
+    }
+
+    /// Mark the room as being sent for the first time in this sliding sync
+    /// session.
+    pub fn initial(self) -> Self {
+        self.field("initial", true)
+    }
+
+    /// Set the bump stamp of the room, used to sort the rooms by recency.
+    pub fn bump_stamp(self, bump_stamp: u32) -> Self {
+        self.field("bump_stamp", bump_stamp)
+    }
+
+    /// Set the number of joined members of the room.
+    pub fn joined_count(self, count: u32) -> Self {
+        self.field("joined_count", count)
+    }
+
+    /// Set the number of invited members of the room.
+    pub fn invited_count(self, count: u32) -> Self {
+        self.field("invited_count", count)
+    }
+
+    /// Set the unread notification and highlight counts of the room.
+    pub fn unread_notifications(self, notification_count: u32, highlight_count: u32) -> Self {
+        self.field("notification_count", notification_count)
+            .field("highlight_count", highlight_count)
+    }
+
+    /// Mark the timeline as limited, i.e. there's a gap before its events,
+    /// that can be filled by paginating from `prev_batch`.
+    pub fn limited(self, prev_batch: &str) -> Self {
+        self.field("limited", true).field("prev_batch", prev_batch)
+    }
+
+    /// Add an event to the timeline of the room.
+    pub fn timeline_event(mut self, event: impl Into<Raw<AnySyncTimelineEvent>>) -> Self {
+        self.timeline.push(event.into());
+        self
+    }
+
+    /// Add a state event to the required state of the room.
+    pub fn required_state(mut self, event: impl Into<Raw<AnySyncStateEvent>>) -> Self {
+        self.required_state.push(event.into());
+        self
+    }
+
+    /// Add a stripped state event to the invite state of the room, which marks
+    /// it as an invite.
+    pub fn invite_state(mut self, event: impl Into<Raw<AnyStrippedStateEvent>>) -> Self {
+        self.invite_state.get_or_insert_with(Vec::new).push(event.into());
+        self
+    }
+
+    fn build_json(self) -> Value {
+        let mut fields = self.fields;
+
+        if !self.timeline.is_empty() {
+            fields.insert("timeline".to_owned(), json!(self.timeline));
+        }
+
+        if !self.required_state.is_empty() {
+            fields.insert("required_state".to_owned(), json!(self.required_state));
+        }
+
+        if let Some(invite_state) = self.invite_state {
+            fields.insert("invite_state".to_owned(), json!(invite_state));
+        }
+
+        Value::Object(fields)
+    }
+}
@@ -6,12 +6,19 @@ use futures_util::FutureExt;
 use matrix_sdk::{
     Client, Error, MemoryStore, SlidingSyncList, StateChanges, StateStore, ThreadingSupport,
     authentication::oauth::{OAuthError, error::OAuthTokenRevocationError},
+    bandwidth::{BandwidthReport, BandwidthUsage},
     config::{RequestConfig, StoreConfig, SyncSettings, SyncToken},
     sleep::sleep,
     store::{RoomLoadSettings, ThreadSubscriptionStatus},
     sync::{RoomUpdate, State},
+    sync_recording::{SyncRecorder, SyncRecording},
     test_utils::{
-        client::mock_matrix_session, mocks::MatrixMockServer, no_retry_test_client_with_server,
+        client::mock_matrix_session,
+        mocks::{
+            MatrixMockServer,
+            faults::{Fault, FaultInjector},
+        },
+        no_retry_test_client_with_server,
     },
 };
 use matrix_sdk_base::{RoomState, sync::RoomUpdates};
@@ -1796,3 +1803,157 @@ async fn test_sync_thread_subscriptions_with_catchup() {
     let sub3 = room1.load_or_fetch_thread_subscription(&thread3).await.unwrap();
     assert_eq!(sub3, Some(matrix_sdk::room::ThreadSubscription { automatic: false }));
 }
+
+#[async_test]
+async fn test_retries_with_injected_faults() {
+    let server = MatrixMockServer::new().await;
+    let client = server
+        .client_builder()
+        .on_builder(|builder| builder.request_config(RequestConfig::new().retry_limit(3)))
+        .build()
+        .await;
+
+    let faults = FaultInjector::new();
+    server.mock_who_am_i().with_faults(faults.clone()).ok().mount().await;
+
+    // Transient errors are retried, until the server responds normally.
+    faults.extend([
+        Fault::Status(502),
+        Fault::RateLimited { retry_after: Some(Duration::from_millis(10)) },
+        Fault::Latency(Duration::from_millis(10)),
+    ]);
+    client.whoami().await.expect("the request should succeed after the retries");
+    assert_eq!(faults.injected(), 3);
+    assert_eq!(faults.pending(), 0);
+
+    // A corrupt response isn't retried.
+    faults.push(Fault::CorruptBody);
+    client.whoami().await.expect_err("a corrupt response should be an error");
+    assert_eq!(faults.injected(), 4);
+
+    // Network failures are retried, up to the retry limit.
+    faults.repeat(Fault::DropConnection, 3);
+    client.whoami().await.expect_err("the request should fail after 3 attempts");
+    assert_eq!(faults.injected(), 7);
+
+    // Once there are no more faults, the endpoint responds normally again.
+    client.whoami().await.expect("the request should succeed");
+    assert_eq!(faults.injected(), 7);
+}
+
+#[async_test]
+async fn test_record_and_replay_sync() {
+    let recording_dir = tempfile::tempdir().unwrap();
+    let recording_path = recording_dir.path().join("sync.jsonl");
+    let room_id = room_id!("!room:example.org");
+
+    // Record a couple of syncs.
+    let server = MatrixMockServer::new().await;
+    let recorder = SyncRecorder::create(&recording_path).unwrap();
+    let client = server
+        .client_builder()
+        .on_builder({
+            let recorder = recorder.clone();
+            |builder| builder.record_sync_responses(recorder)
+        })
+        .build()
+        .await;
+
+    let f = EventFactory::new().room(room_id).sender(user_id!("@alice:example.org"));
+    server
+        .mock_sync()
+        .ok_and_run(&client, |builder| {
+            builder.add_joined_room(
+                JoinedRoomBuilder::new(room_id).add_timeline_event(f.text_msg("secret")),
+            );
+        })
+        .await;
+    server.mock_sync().ok_and_run(&client, |_| {}).await;
+
+    // The responses have been recorded, and sanitized.
+    recorder.flush().await;
+    let recording = SyncRecording::load(&recording_path).unwrap();
+    assert_eq!(recording.responses().len(), 2);
+
+    let events =
+        &recording.responses()[0].body["rooms"]["join"][room_id.as_str()]["timeline"]["events"];
+    assert_eq!(events[0]["content"]["body"], "<redacted>");
+
+    // A new client receives the recorded responses, without any mocked sync.
+    let server = MatrixMockServer::new().await;
+    let client = server
+        .client_builder()
+        .on_builder(|builder| builder.replay_sync_responses(recording))
+        .build()
+        .await;
+    assert!(client.get_room(room_id).is_none());
+
+    client.sync_once(SyncSettings::default()).await.unwrap();
+    assert!(client.get_room(room_id).is_some());
+
+    client.sync_once(SyncSettings::default()).await.unwrap();
+
+    // All the responses have been replayed, so the next sync waits forever.
+    assert!(client.sync_once(SyncSettings::default()).now_or_never().is_none());
+}
+
+#[async_test]
+async fn test_memory_report() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+    client.event_cache().subscribe().unwrap();
+
+    // Nothing is cached at first.
+    let report = client.memory_report().await;
+    assert_eq!(report.rooms, 0);
+    assert!(report.event_cache.is_empty());
+
+    let room_id = room_id!("!room:example.org");
+    let f = EventFactory::new().room(room_id).sender(user_id!("@alice:example.org"));
+
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id).add_timeline_bulk(vec![
+                f.text_msg("hello").into_raw_sync(),
+                f.text_msg("world").into_raw_sync(),
+            ]),
+        )
+        .await;
+    server.sync_joined_room(&client, room_id!("!other:example.org")).await;
+
+    let report = client.memory_report().await;
+    assert_eq!(report.rooms, 2);
+    assert_eq!(report.event_cache_events(), 2);
+
+    let room_report =
+        report.event_cache.iter().find(|room| room.room_id == room_id).expect("room is cached");
+    assert_eq!(room_report.events, 2);
+    assert_eq!(room_report.threads, 0);
+}
+
+#[async_test]
+async fn test_bandwidth_accounting() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let bandwidth = client.bandwidth();
+    assert_eq!(bandwidth.session_totals(), BandwidthReport::default());
+
+    server.mock_who_am_i().ok().mock_once().mount().await;
+    client.whoami().await.unwrap();
+
+    let report = bandwidth.since_reset();
+    assert_eq!(report.sync, BandwidthUsage::default());
+    assert!(report.other.received > 0);
+
+    server.sync_joined_room(&client, room_id!("!room:example.org")).await;
+
+    let report = bandwidth.reset();
+    assert!(report.sync.received > 0);
+    assert!(report.total().received >= report.sync.received + report.other.received);
+
+    // Resetting the counters doesn't reset the totals of the session.
+    assert_eq!(bandwidth.since_reset(), BandwidthReport::default());
+    assert_eq!(bandwidth.session_totals(), report);
+}
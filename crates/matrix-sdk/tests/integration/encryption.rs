@@ -1,6 +1,7 @@
 mod backups;
 mod cross_signing;
 mod recovery;
+mod room_messages;
 mod secret_storage;
 mod shared_history;
 #[cfg(feature = "experimental-encrypted-state-events")]
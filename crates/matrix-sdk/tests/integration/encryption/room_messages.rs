@@ -0,0 +1,32 @@
+use matrix_sdk::{assert_decrypted_message_eq, test_utils::mocks::MatrixMockServer};
+use matrix_sdk_test::async_test;
+use ruma::{events::room::message::RoomMessageEventContent, room_id};
+
+#[async_test]
+async fn test_encrypted_round_trip() {
+    let server = MatrixMockServer::new().await;
+    server.mock_crypto_endpoints_preset().await;
+
+    let (alice, bob) = server.set_up_alice_and_bob_for_encryption().await;
+    let (alice_room, bob_room) =
+        server.set_up_encrypted_room(room_id!("!test:example.org"), &alice, &bob).await;
+
+    let event = server
+        .send_encrypted_and_sync_back(
+            &alice_room,
+            RoomMessageEventContent::text_plain("Hello, Bob"),
+            &bob,
+        )
+        .await;
+    assert_decrypted_message_eq!(event, "Hello, Bob", "Bob should decrypt Alice's message");
+
+    // The other way around works too.
+    let event = server
+        .send_encrypted_and_sync_back(
+            &bob_room,
+            RoomMessageEventContent::text_plain("Hello, Alice"),
+            &alice,
+        )
+        .await;
+    assert_decrypted_message_eq!(event, "Hello, Alice", "Alice should decrypt Bob's message");
+}
@@ -1,3 +1,4 @@
+use futures_util::future::join;
 use matrix_sdk::{
     media::{MediaFormat, MediaRequestParameters, MediaThumbnailSettings},
     test_utils::mocks::MatrixMockServer,
@@ -9,6 +10,7 @@ use ruma::{
     events::room::{ImageInfo, MediaSource, message::ImageMessageEventContent},
     mxc_uri, owned_mxc_uri, uint,
 };
+use serde_json::json;
 
 #[async_test]
 async fn test_get_media_content_no_auth() {
@@ -321,3 +323,105 @@ async fn test_async_media_upload() {
         .await
         .unwrap();
 }
+
+#[async_test]
+async fn test_capture_uploads_and_download_them() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().no_server_versions().build().await;
+
+    server.mock_versions().ok_custom(&["v1.11"], &Default::default()).mount().await;
+
+    let (uploads, upload_mock) = server.mock_upload().ok_with_capture_all("localhost");
+    upload_mock.expect(2).named("upload").mount().await;
+
+    let text_uri = client
+        .media()
+        .upload(&mime::TEXT_PLAIN, b"hello".to_vec(), None)
+        .await
+        .unwrap()
+        .content_uri;
+    let image_uri = client
+        .media()
+        .upload(&mime::IMAGE_JPEG, b"binaryjpeg".to_vec(), None)
+        .await
+        .unwrap()
+        .content_uri;
+
+    // Every upload has been captured, with its own MXC URI.
+    let uploads = uploads.uploads();
+    assert_eq!(uploads.len(), 2);
+
+    assert_eq!(uploads[0].mxc_uri, text_uri);
+    assert_eq!(uploads[0].content_type.as_deref(), Some("text/plain"));
+    assert_eq!(uploads[0].data, b"hello");
+
+    assert_eq!(uploads[1].mxc_uri, image_uri);
+    assert_eq!(uploads[1].content_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(uploads[1].data, b"binaryjpeg");
+
+    // The uploads can be served back individually.
+    for upload in &uploads {
+        server
+            .mock_authed_media_download()
+            .match_mxc_uri(&upload.mxc_uri)
+            .ok_with(upload.data.clone(), upload.content_type.as_deref().unwrap())
+            .expect(1)
+            .mount()
+            .await;
+    }
+
+    let request =
+        MediaRequestParameters { source: MediaSource::Plain(image_uri), format: MediaFormat::File };
+    let content = client.media().get_media_content(&request, false).await.unwrap();
+    assert_eq!(content, b"binaryjpeg");
+
+    let request =
+        MediaRequestParameters { source: MediaSource::Plain(text_uri), format: MediaFormat::File };
+    let content = client.media().get_media_content(&request, false).await.unwrap();
+    assert_eq!(content, b"hello");
+}
+
+#[async_test]
+async fn test_get_url_preview() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().no_server_versions().build().await;
+
+    server.mock_versions().ok_custom(&["v1.11"], &Default::default()).mount().await;
+
+    let url = "https://matrix.org";
+
+    server
+        .mock_authenticated_media_preview()
+        .match_url(url)
+        .ok(json!({
+            "og:title": "Matrix.org",
+            "og:description": "An open network for secure, decentralised communication",
+            "og:image": "mxc://example.com/ascERGshawAWawugaAcauga",
+            "og:image:width": 640,
+            "matrix:image:size": 102400,
+        }))
+        // Concurrent and subsequent requests for the same URL only hit the server once.
+        .expect(1)
+        .mount()
+        .await;
+
+    let media = client.media();
+    let (first, second) = join(media.get_url_preview(url), media.get_url_preview(url)).await;
+    let preview = first.unwrap();
+    assert_eq!(second.unwrap(), preview);
+
+    assert_eq!(preview.url, url);
+    assert_eq!(preview.title.as_deref(), Some("Matrix.org"));
+    assert_eq!(
+        preview.description.as_deref(),
+        Some("An open network for secure, decentralised communication")
+    );
+    assert_eq!(preview.image, Some(owned_mxc_uri!("mxc://example.com/ascERGshawAWawugaAcauga")));
+    assert_eq!(preview.image_width, Some(uint!(640)));
+    assert_eq!(preview.image_height, None);
+    assert_eq!(preview.image_size, Some(uint!(102400)));
+    assert!(!preview.is_empty());
+
+    // The preview is now cached.
+    assert_eq!(media.get_url_preview(url).await.unwrap(), preview);
+}
@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, iter, time::Duration};
 
 use assert_matches2::{assert_let, assert_matches};
+use futures_util::join;
 use js_int::uint;
 use matrix_sdk::{
     RoomDisplayName, RoomMemberships,
@@ -9,14 +10,14 @@ use matrix_sdk::{
     test_utils::mocks::MatrixMockServer,
 };
 use matrix_sdk_test::{
-    BOB, DEFAULT_TEST_ROOM_ID, JoinedRoomBuilder, LeftRoomBuilder, StateTestEvent,
-    SyncResponseBuilder, async_test, bulk_room_members, event_factory::EventFactory,
-    sync_state_event, test_json,
+    BOB, DEFAULT_TEST_ROOM_ID, JoinedRoomBuilder, LeftRoomBuilder, RoomAccountDataTestEvent,
+    StateTestEvent, SyncResponseBuilder, async_test, bulk_room_members,
+    event_factory::EventFactory, sync_state_event, test_json,
 };
 use ruma::{
     event_id,
     events::{
-        AnySyncStateEvent, AnySyncTimelineEvent, StateEventType,
+        AnySyncStateEvent, AnySyncTimelineEvent, RoomAccountDataEventType, StateEventType,
         direct::DirectUserIdentifier,
         room::{avatar, member::MembershipState, message::RoomMessageEventContent},
     },
@@ -903,3 +904,125 @@ async fn test_room_avatar() {
     assert_eq!(avatar_info.mimetype.as_deref(), Some("image/png"));
     assert_eq!(avatar_info.size, Some(uint!(5243)));
 }
+
+#[async_test]
+async fn test_url_previews_enabled() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+
+    // URL previews are enabled by default in unencrypted rooms.
+    server.mock_room_state_encryption().plain().mount().await;
+    assert!(room.url_previews_enabled().await.unwrap());
+
+    server
+        .mock_set_room_account_data(RoomAccountDataEventType::from("org.matrix.room.preview_urls"))
+        .ok()
+        .expect(1)
+        .mount()
+        .await;
+    room.set_url_previews_enabled(false).await.unwrap();
+
+    // The choice of the user is taken into account once it's received back.
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id).add_account_data(RoomAccountDataTestEvent::Custom(
+                json!({
+                    "type": "org.matrix.room.preview_urls",
+                    "content": { "disable": true },
+                }),
+            )),
+        )
+        .await;
+    assert!(!room.url_previews_enabled().await.unwrap());
+}
+
+#[async_test]
+async fn test_starred_events() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+    let event_id = event_id!("$starred");
+
+    assert!(room.starred_event_ids().await.unwrap().is_empty());
+
+    server
+        .mock_set_room_account_data(RoomAccountDataEventType::from(
+            "org.matrix.room.starred_events",
+        ))
+        .ok()
+        .expect(1)
+        .mount()
+        .await;
+    assert!(room.star_event(event_id).await.unwrap());
+
+    // The starred events are updated once they're received back.
+    server
+        .sync_room(
+            &client,
+            JoinedRoomBuilder::new(room_id).add_account_data(RoomAccountDataTestEvent::Custom(
+                json!({
+                    "type": "org.matrix.room.starred_events",
+                    "content": { "event_ids": [event_id] },
+                }),
+            )),
+        )
+        .await;
+    assert_eq!(room.starred_event_ids().await.unwrap(), [event_id.to_owned()]);
+    assert!(room.is_starred_event(event_id).await.unwrap());
+
+    // Starring the event again, or removing the star of another event, doesn't send
+    // any request.
+    assert!(!room.star_event(event_id).await.unwrap());
+    assert!(!room.unstar_event(event_id!("$other")).await.unwrap());
+}
+
+#[async_test]
+async fn test_star_events_back_to_back() {
+    let server = MatrixMockServer::new().await;
+    let client = server.client_builder().build().await;
+
+    let room_id = room_id!("!a98sd12bjh:example.org");
+    let room = server.sync_joined_room(&client, room_id).await;
+    let event_type = RoomAccountDataEventType::from("org.matrix.room.starred_events");
+
+    server
+        .mock_set_room_account_data(event_type.clone())
+        .body_matches_partial_json(json!({ "event_ids": ["$first"] }))
+        .ok()
+        .mock_once()
+        .mount()
+        .await;
+    server
+        .mock_set_room_account_data(event_type.clone())
+        .body_matches_partial_json(json!({ "event_ids": ["$first", "$second"] }))
+        .ok()
+        .mock_once()
+        .mount()
+        .await;
+    server
+        .mock_set_room_account_data(event_type)
+        .body_matches_partial_json(json!({ "event_ids": ["$second"] }))
+        .ok()
+        .mock_once()
+        .mount()
+        .await;
+
+    // Without any sync in between, the second star doesn't overwrite the first one.
+    let (first, second) =
+        join!(room.star_event(event_id!("$first")), room.star_event(event_id!("$second")));
+    assert!(first.unwrap());
+    assert!(second.unwrap());
+    assert_eq!(
+        room.starred_event_ids().await.unwrap(),
+        [event_id!("$first").to_owned(), event_id!("$second").to_owned()]
+    );
+
+    assert!(room.unstar_event(event_id!("$first")).await.unwrap());
+    assert_eq!(room.starred_event_ids().await.unwrap(), [event_id!("$second").to_owned()]);
+}
@@ -0,0 +1,474 @@
+//! The keybindings and the colors of multiverse, which can be customised in
+//! the `multiverse.toml` file of the session path, e.g.:
+//!
+//! ```toml
+//! [keys]
+//! quit = "ctrl-c"
+//! next_room = ["ctrl-j", "alt-down"]
+//!
+//! [theme]
+//! header_bg = "#1e1e2e"
+//! mention = "red"
+//! ```
+//!
+//! The actions which aren't mentioned keep their default keys, and the colors
+//! accept the names and the `#rrggbb` values of [`Color`].
+
+use std::{collections::HashMap, fmt, fs, io, path::Path, str::FromStr, sync::OnceLock};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, eyre},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::{Color, palette::tailwind};
+use serde::Deserialize;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// The name of the configuration file, in the session path.
+const CONFIG_FILE_NAME: &str = "multiverse.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Load the configuration file of the session path, if there's one.
+///
+/// Must be called before the first call to [`theme`] or [`keymap`], which
+/// otherwise use the defaults.
+pub fn init(session_path: &Path) -> Result<()> {
+    let path = session_path.join(CONFIG_FILE_NAME);
+
+    let config = match fs::read_to_string(&path) {
+        Ok(content) => {
+            Config::from_toml(&content).wrap_err_with(|| format!("invalid {}", path.display()))?
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Config::default(),
+        Err(err) => return Err(err).wrap_err_with(|| format!("couldn't read {}", path.display())),
+    };
+
+    CONFIG.set(config).map_err(|_| eyre!("the configuration was already loaded"))
+}
+
+fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// The colors of the user interface.
+pub fn theme() -> &'static Theme {
+    &config().theme
+}
+
+/// The keys triggering each action.
+pub fn keymap() -> &'static Keymap {
+    &config().keymap
+}
+
+#[derive(Default)]
+struct Config {
+    theme: Theme,
+    keymap: Keymap,
+}
+
+impl Config {
+    fn from_toml(content: &str) -> Result<Self> {
+        let file: ConfigFile = toml::from_str(content)?;
+
+        Ok(Self { theme: Theme::new(file.theme)?, keymap: Keymap::new(file.keys)? })
+    }
+}
+
+/// The content of the configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    keys: HashMap<Action, Keys>,
+    theme: ThemeFile,
+}
+
+/// One key, or several keys, triggering an action.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    header_bg: Option<String>,
+    normal_row: Option<String>,
+    alt_row: Option<String>,
+    selected_fg: Option<String>,
+    text: Option<String>,
+    mention: Option<String>,
+}
+
+/// The colors of the user interface.
+pub struct Theme {
+    /// The background of the headers and of the selected tabs.
+    pub header_bg: Color,
+    /// The background of the even rows of the lists.
+    pub normal_row: Color,
+    /// The background of the odd rows of the lists.
+    pub alt_row: Color,
+    /// The foreground of the selected rows.
+    pub selected_fg: Color,
+    /// The foreground of the text.
+    pub text: Color,
+    /// The foreground of the rooms and messages mentioning us.
+    pub mention: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            header_bg: tailwind::BLUE.c950,
+            normal_row: tailwind::SLATE.c950,
+            alt_row: tailwind::SLATE.c900,
+            selected_fg: tailwind::BLUE.c300,
+            text: tailwind::SLATE.c200,
+            mention: tailwind::AMBER.c400,
+        }
+    }
+}
+
+impl Theme {
+    fn new(file: ThemeFile) -> Result<Self> {
+        fn parse(color: Option<String>, default: Color) -> Result<Color> {
+            match color {
+                Some(color) => {
+                    Color::from_str(&color).map_err(|_| eyre!("unknown color `{color}`"))
+                }
+                None => Ok(default),
+            }
+        }
+
+        let default = Self::default();
+
+        Ok(Self {
+            header_bg: parse(file.header_bg, default.header_bg)?,
+            normal_row: parse(file.normal_row, default.normal_row)?,
+            alt_row: parse(file.alt_row, default.alt_row)?,
+            selected_fg: parse(file.selected_fg, default.selected_fg)?,
+            text: parse(file.text, default.text)?,
+            mention: parse(file.mention, default.mention)?,
+        })
+    }
+}
+
+/// The actions which can be bound to keys, in the order of the help screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    Help,
+    StatusLog,
+    Devices,
+    NextSpace,
+    NextAccount,
+    EncryptionSettings,
+    LinkedChunksDetails,
+    EventsDetails,
+    ReadReceiptsDetails,
+    MembersDetails,
+    InvitesDetails,
+    PinnedEventsDetails,
+    ReactionsDetails,
+    SettingsDetails,
+    SendQueueDetails,
+    ToggleTiling,
+    MarkAsRead,
+    CycleNotificationMode,
+    GoToNotifiedRoom,
+    Quit,
+    NextRoom,
+    PreviousRoom,
+    BackPaginate,
+    ForwardPaginate,
+    React,
+    ShowRawJson,
+    NextItem,
+    PreviousItem,
+    OpenThread,
+    ThreadSubscriptionStatus,
+    WriteMessage,
+    CommandLine,
+    Reply,
+    Edit,
+    Redact,
+    Leave,
+    TogglePin,
+    EndPoll,
+    CreateRoom,
+    Search,
+    FindInRoom,
+    FilterRooms,
+}
+
+impl Action {
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Help => &["F1"],
+            Action::StatusLog => &["F4"],
+            Action::Devices => &["F5"],
+            Action::NextSpace => &["F2"],
+            Action::NextAccount => &["F3"],
+            Action::EncryptionSettings => &["F10"],
+            Action::LinkedChunksDetails => &["Alt-l"],
+            Action::EventsDetails => &["Alt-e"],
+            Action::ReadReceiptsDetails => &["Alt-r"],
+            Action::MembersDetails => &["u"],
+            Action::InvitesDetails => &["Alt-i"],
+            Action::PinnedEventsDetails => &["Alt-p"],
+            Action::ReactionsDetails => &["Alt-a"],
+            Action::SettingsDetails => &["Alt-o"],
+            Action::SendQueueDetails => &["Alt-q"],
+            Action::ToggleTiling => &["Alt-t"],
+            Action::MarkAsRead => &["Alt-m"],
+            Action::CycleNotificationMode => &["Alt-n"],
+            Action::GoToNotifiedRoom => &["Alt-g"],
+            Action::Quit => &["Ctrl-q"],
+            Action::NextRoom => &["Ctrl-j", "Ctrl-Down"],
+            Action::PreviousRoom => &["Ctrl-k", "Ctrl-Up"],
+            Action::BackPaginate => &["PageUp"],
+            Action::ForwardPaginate => &["PageDown"],
+            Action::React => &["Ctrl-l"],
+            Action::ShowRawJson => &["J"],
+            Action::NextItem => &["Down", "Ctrl-n"],
+            Action::PreviousItem => &["Up", "Ctrl-p"],
+            Action::OpenThread => &["Ctrl-t"],
+            Action::ThreadSubscriptionStatus => &["Alt-s"],
+            Action::WriteMessage => &["i"],
+            Action::CommandLine => &[":"],
+            Action::Reply => &["R"],
+            Action::Edit => &["E"],
+            Action::Redact => &["D"],
+            Action::Leave => &["L"],
+            Action::TogglePin => &["P"],
+            Action::EndPoll => &["X"],
+            Action::CreateRoom => &["Ctrl-r"],
+            Action::Search => &["Ctrl-s"],
+            Action::FindInRoom => &["Ctrl-f"],
+            Action::FilterRooms => &["/"],
+        }
+    }
+
+    /// What the action does, for the help screen.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Help => "Open Help",
+            Action::StatusLog => "Open the log of the status messages and errors",
+            Action::Devices => "Manage the devices of the account: rename, sign out or verify them",
+            Action::NextSpace => "Restrict the room list to the next space, or show all the rooms",
+            Action::NextAccount => "Switch to the next account, when there are several of them",
+            Action::EncryptionSettings => "Open the encryption settings",
+            Action::LinkedChunksDetails => "Open the linked chunk details view",
+            Action::EventsDetails => "Open the events details view",
+            Action::ReadReceiptsDetails => "Open the read receipt details view",
+            Action::MembersDetails => "Open the room members details view",
+            Action::InvitesDetails => "Open the pending invites details view",
+            Action::PinnedEventsDetails => "Open the pinned events details view",
+            Action::ReactionsDetails => "Open the reactions details view of the focused item",
+            Action::SettingsDetails => "Open the room settings editor",
+            Action::SendQueueDetails => {
+                "Open the send queue details view, to retry, edit or abort requests"
+            }
+            Action::ToggleTiling => "Switch the detail view tiling direction",
+            Action::MarkAsRead => "Mark the currently selected room as read",
+            Action::CycleNotificationMode => {
+                "Cycle the notification mode of the selected room: all, mentions or mute"
+            }
+            Action::GoToNotifiedRoom => "Go to the room of the notification being shown",
+            Action::Quit => "Quit Multiverse",
+            Action::NextRoom => "Switch to the next room in the list",
+            Action::PreviousRoom => "Switch to the previous room in the list",
+            Action::BackPaginate => "Backpaginate the currently selected room",
+            Action::ForwardPaginate => {
+                "Paginate forwards in a timeline focused on an event, back to the live one"
+            }
+            Action::React => "React to the focused item, or to the last message",
+            Action::ShowRawJson => "Show the raw JSON of the focused item",
+            Action::NextItem => "Focus on the next item in the timeline view",
+            Action::PreviousItem => "Focus on the previous item in the timeline view",
+            Action::OpenThread => "Open a thread on the focused timeline item",
+            Action::ThreadSubscriptionStatus => "Show the subscription status of the thread",
+            Action::WriteMessage => "Write a message in the selected room",
+            Action::CommandLine => {
+                "Write a command, e.g. /me, /voice <path>, /export <path> [--all] or /logout"
+            }
+            Action::Reply => "Reply to the focused timeline item",
+            Action::Edit => "Edit the focused message, if it's ours",
+            Action::Redact => "Redact the focused timeline item",
+            Action::Leave => "Leave the selected room",
+            Action::TogglePin => "Pin or unpin the focused timeline item",
+            Action::EndPoll => "End the focused poll, if it's ours",
+            Action::CreateRoom => "Create a new room",
+            Action::Search => "Search",
+            Action::FindInRoom => {
+                "Find messages in the selected room; Enter/Up and Down jump between the matches, \
+                 Tab searches the server too"
+            }
+            Action::FilterRooms => "Filter the room list by room name",
+        }
+    }
+}
+
+/// A key, with the modifiers which must be held while pressing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Create the binding of the key, where Shift is part of the character:
+    /// `R` rather than `Shift-r`.
+    fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            code => code,
+        };
+
+        Self { code, modifiers }
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = color_eyre::Report;
+
+    /// Parse a key like `Ctrl-j`, `Alt-Enter`, `F2` or `R`; the names are
+    /// case-insensitive, except for the characters.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = s;
+
+        while let Some((modifier, rest)) = key.split_once('-')
+            && !rest.is_empty()
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(eyre!("unknown modifier `{modifier}` in `{s}`")),
+            };
+            key = rest;
+        }
+
+        let name = key.to_ascii_lowercase();
+
+        let code = if let Some(number) = name.strip_prefix('f')
+            && let Ok(number) = number.parse()
+        {
+            KeyCode::F(number)
+        } else {
+            match name.as_str() {
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                _ => {
+                    let mut chars = key.chars();
+
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => KeyCode::Char(c),
+                        _ => return Err(eyre!("unknown key `{key}` in `{s}`")),
+                    }
+                }
+            }
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift-")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(number) => write!(f, "F{number}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// The dispatch table from the keys to the actions.
+pub struct Keymap {
+    actions: HashMap<KeyBinding, Action>,
+    keys: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(HashMap::new()).expect("the default keys should be valid")
+    }
+}
+
+impl Keymap {
+    /// Bind the keys of the configuration file, and the default keys of the
+    /// other actions.
+    fn new(mut configured: HashMap<Action, Keys>) -> Result<Self> {
+        let mut actions = HashMap::new();
+        let mut keys = HashMap::new();
+
+        for action in Action::iter() {
+            let names = match configured.remove(&action) {
+                Some(Keys::One(name)) => vec![name],
+                Some(Keys::Many(names)) => names,
+                None => action.default_keys().iter().map(|name| (*name).to_owned()).collect(),
+            };
+
+            let bindings =
+                names.iter().map(|name| name.parse::<KeyBinding>()).collect::<Result<Vec<_>>>()?;
+
+            for binding in &bindings {
+                if let Some(other) = actions.insert(*binding, action) {
+                    return Err(eyre!("{binding} is bound to both {other} and {action}"));
+                }
+            }
+
+            keys.insert(action, bindings);
+        }
+
+        Ok(Self { actions, keys })
+    }
+
+    /// The action triggered by the key press, if any.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.actions.get(&KeyBinding::new(event.code, event.modifiers)).copied()
+    }
+
+    /// The keys triggering the action, for the help screen, e.g. `Ctrl-j /
+    /// Ctrl-Down`.
+    pub fn keys(&self, action: Action) -> String {
+        self.keys
+            .get(&action)
+            .map(|bindings| {
+                bindings.iter().map(ToString::to_string).collect::<Vec<_>>().join(" / ")
+            })
+            .unwrap_or_default()
+    }
+}
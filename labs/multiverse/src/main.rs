@@ -9,20 +9,21 @@ use std::{
 };
 
 use clap::Parser;
-use color_eyre::Result;
+use color_eyre::{Result, eyre::WrapErr};
 use crossterm::{
-    event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
-    },
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
 };
 use futures_util::{StreamExt as _, pin_mut};
 use imbl::Vector;
 use layout::Flex;
 use matrix_sdk::{
-    AuthSession, Client, Room, SearchIndexStoreKind, SqliteCryptoStore, SqliteEventCacheStore,
-    SqliteStateStore, ThreadingSupport,
-    authentication::matrix::MatrixSession,
+    AuthSession, Client, Room, SearchIndexStoreKind, SessionChange, SqliteCryptoStore,
+    SqliteEventCacheStore, SqliteStateStore, ThreadingSupport,
+    authentication::{
+        matrix::MatrixSession,
+        oauth::{ClientId, OAuthSession, UserSession},
+    },
     config::StoreConfig,
     deserialized_responses::TimelineEvent,
     encryption::{BackupDownloadStrategy, EncryptionSettings},
@@ -30,6 +31,7 @@ use matrix_sdk::{
     ruma::{
         OwnedEventId, OwnedRoomId, api::client::room::create_room::v3::Request as CreateRoomRequest,
     },
+    sync_recording::{SyncRecorder, SyncRecording},
 };
 use matrix_sdk_common::locks::Mutex;
 use matrix_sdk_ui::{
@@ -38,7 +40,8 @@ use matrix_sdk_ui::{
     sync_service::SyncService,
     timeline::{RoomExt as _, TimelineFocus, TimelineItem},
 };
-use ratatui::{prelude::*, style::palette::tailwind, widgets::*};
+use ratatui::{prelude::*, widgets::*};
+use serde::{Deserialize, Serialize};
 use throbber_widgets_tui::{Throbber, ThrobberState};
 use tokio::{spawn, task::JoinHandle};
 use tracing::{debug, error, warn};
@@ -47,22 +50,27 @@ use widgets::{
     recovery::create_centered_throbber_area, room_view::RoomView, settings::SettingsView,
 };
 
-use crate::widgets::{
-    create_room::CreateRoomView,
-    help::HelpView,
-    room_list::{ExtraRoomInfo, RoomInfos, RoomList, Rooms},
-    search::SearchingView,
-    status::Status,
+use crate::{
+    config::{Action, keymap},
+    widgets::{
+        create_room::CreateRoomView,
+        devices::{DevicesOutcome, DevicesView},
+        help::HelpView,
+        notifications::{NotificationPopup, listen_to_notifications},
+        room_filter::RoomFilterView,
+        room_list::{EntriesController, ExtraRoomInfo, RoomInfos, RoomList, Rooms},
+        search::SearchingView,
+        status::Status,
+        status_log::StatusLogView,
+        sync_indicator::SyncIndicator,
+        verification::{VerificationRequests, VerificationView, listen_to_verification_requests},
+    },
 };
 
+mod config;
+mod oauth;
 mod widgets;
 
-const HEADER_BG: Color = tailwind::BLUE.c950;
-const NORMAL_ROW_COLOR: Color = tailwind::SLATE.c950;
-const ALT_ROW_COLOR: Color = tailwind::SLATE.c900;
-const SELECTED_STYLE_FG: Color = tailwind::BLUE.c300;
-const TEXT_COLOR: Color = tailwind::SLATE.c200;
-
 type Timelines = Arc<Mutex<HashMap<OwnedRoomId, Timeline>>>;
 
 #[derive(Debug, Parser)]
@@ -74,9 +82,40 @@ struct Cli {
     #[clap(default_value = "/tmp/")]
     session_path: PathBuf,
 
+    /// Log in to another account too, with its own homeserver and session
+    /// path, e.g. `--account example.org /tmp/bob`; can be repeated.
+    #[clap(long = "account", num_args = 2, value_names = ["SERVER_NAME", "SESSION_PATH"])]
+    other_accounts: Vec<String>,
+
     /// Set the proxy that should be used for the connection.
     #[clap(short, long, env = "PROXY")]
     proxy: Option<Url>,
+
+    /// Record the (sanitized) sync responses of the first account into the
+    /// given file, to attach it to a bug report.
+    #[clap(long, conflicts_with = "replay_sync")]
+    record_sync: Option<PathBuf>,
+
+    /// Replay the sync responses recorded in the given file, instead of
+    /// receiving the ones of the homeserver, for the first account.
+    #[clap(long)]
+    replay_sync: Option<PathBuf>,
+}
+
+impl Cli {
+    /// The homeservers and the session paths of all the accounts, the first
+    /// one being the one given by the positional arguments.
+    fn accounts(&self) -> Vec<(String, PathBuf)> {
+        let mut accounts = vec![(self.server_name.clone(), self.session_path.clone())];
+
+        accounts.extend(
+            self.other_accounts
+                .chunks_exact(2)
+                .map(|account| (account[0].clone(), PathBuf::from(&account[1]))),
+        );
+
+        accounts
+    }
 }
 
 #[derive(Default)]
@@ -89,11 +128,19 @@ pub enum GlobalMode {
     /// Mode where we have opened the settings screen.
     Settings { view: SettingsView },
     /// Mode where we are shutting our tasks down and exiting multiverse.
-    Exiting { shutdown_task: JoinHandle<()> },
+    Exiting { shutdown_task: JoinHandle<Result<()>> },
     /// Mode where we have opened create room screen
     CreateRoom { view: CreateRoomView },
     /// Mode where we have opened create room screen
     Searching { view: SearchingView },
+    /// Mode where we are filtering the room list by room name.
+    FilteringRooms { view: RoomFilterView },
+    /// Mode where we are going through an incoming verification request.
+    Verifying { view: VerificationView },
+    /// Mode where we have opened the log of the status messages.
+    StatusLog { view: StatusLogView },
+    /// Mode where we have opened the device manager.
+    Devices { view: DevicesView },
 }
 
 /// Helper function to create a centered rect using up certain percentage of the
@@ -118,15 +165,41 @@ async fn main() -> Result<()> {
         .init();
 
     color_eyre::install()?;
+    config::init(&cli.session_path)?;
 
-    let client = configure_client(cli).await?;
+    let accounts = cli.accounts();
+    let mut clients = Vec::with_capacity(accounts.len());
 
-    let event_cache = client.event_cache();
-    event_cache.subscribe()?;
+    for (index, (server_name, session_path)) in accounts.into_iter().enumerate() {
+        if index > 0 {
+            println!("\nSetting up the account of {server_name}, in {}…", session_path.display());
+        }
+
+        // The sync responses are only recorded or replayed for the first account.
+        let (record_sync, replay_sync) = if index == 0 {
+            (cli.record_sync.as_deref(), cli.replay_sync.as_deref())
+        } else {
+            (None, None)
+        };
+
+        let client = configure_client(
+            &server_name,
+            &session_path,
+            cli.proxy.as_ref(),
+            record_sync,
+            replay_sync,
+        )
+        .await?;
+
+        let event_cache = client.event_cache();
+        event_cache.subscribe()?;
+
+        clients.push((client, session_path));
+    }
 
     let terminal = ratatui::init();
     execute!(stdout(), EnableMouseCapture)?;
-    let mut app = App::new(client).await?;
+    let mut app = App::new(clients).await?;
 
     app.run(terminal).await
 }
@@ -147,13 +220,18 @@ pub struct AppState {
     throbber_state: ThrobberState,
 }
 
-struct App {
-    /// Reference to the main SDK client.
+/// One of the accounts multiverse is logged in to, with its own sync and its
+/// own views.
+struct Account {
+    /// Reference to the SDK client of the account.
     client: Client,
 
     /// The sync service used for synchronizing events.
     sync_service: Arc<SyncService>,
 
+    /// The state of the sync, shown in the header.
+    sync_indicator: SyncIndicator,
+
     /// Timelines data structures for each room.
     timelines: Timelines,
 
@@ -167,23 +245,28 @@ struct App {
     /// Task listening to room list service changes, and spawning timelines.
     listen_task: JoinHandle<()>,
 
-    /// The status widget at the bottom of the screen.
-    status: Status,
+    /// The incoming verification requests, waiting to be shown.
+    verification_requests: VerificationRequests,
 
-    state: AppState,
+    /// The popup showing the messages mentioning us in the other rooms.
+    notification_popup: NotificationPopup,
 
-    last_tick: Instant,
+    /// The path where the session and its stores are saved.
+    session_path: PathBuf,
 }
 
-impl App {
-    const TICK_RATE: Duration = Duration::from_millis(250);
-
-    async fn new(client: Client) -> Result<Self> {
-        let sync_service = Arc::new(SyncService::builder(client.clone()).build().await?);
+impl Account {
+    async fn new(client: Client, session_path: PathBuf, status: &Status) -> Result<Self> {
+        // In offline mode, the sync service waits for the homeserver to be reachable
+        // again and restarts, instead of stopping on the first network error.
+        let sync_service =
+            Arc::new(SyncService::builder(client.clone()).with_offline_mode().build().await?);
+        let sync_indicator = SyncIndicator::new(&sync_service);
 
         let rooms = Rooms::default();
         let room_infos = RoomInfos::default();
         let timelines = Timelines::default();
+        let entries_controller = EntriesController::default();
 
         let room_list_service = sync_service.room_list_service();
         let all_rooms = room_list_service.all_rooms().await?;
@@ -192,29 +275,44 @@ impl App {
             rooms.clone(),
             room_infos.clone(),
             timelines.clone(),
+            entries_controller.clone(),
             all_rooms,
         ));
 
+        let verification_requests = listen_to_verification_requests(&client);
+        let notification_popup = NotificationPopup::new(listen_to_notifications(&client).await);
+
         // This will sync (with encryption) until an error happens or the program is
         // stopped.
         sync_service.start().await;
 
-        let status = Status::new();
-        let room_list =
-            RoomList::new(client.clone(), rooms, room_infos, sync_service.clone(), status.handle());
-
-        let room_view = RoomView::new(client.clone(), timelines.clone(), status.handle());
+        let room_list = RoomList::new(
+            client.clone(),
+            rooms,
+            room_infos,
+            sync_service.clone(),
+            entries_controller,
+            status.handle(),
+        );
+
+        let room_view = RoomView::new(
+            client.clone(),
+            timelines.clone(),
+            status.handle(),
+            session_path.join("media"),
+        );
 
         Ok(Self {
             sync_service,
+            sync_indicator,
             timelines,
             room_list,
             room_view,
             client,
             listen_task,
-            status,
-            state: AppState::default(),
-            last_tick: Instant::now(),
+            verification_requests,
+            notification_popup,
+            session_path,
         })
     }
 
@@ -222,10 +320,12 @@ impl App {
         rooms: Rooms,
         room_infos: RoomInfos,
         timelines: Timelines,
+        shared_entries_controller: EntriesController,
         all_rooms: room_list_service::RoomList,
     ) {
         let (stream, entries_controller) = all_rooms.entries_with_dynamic_adapters(50_000);
         entries_controller.set_filter(Box::new(new_filter_non_left()));
+        let _ = shared_entries_controller.set(entries_controller);
 
         pin_mut!(stream);
 
@@ -259,9 +359,18 @@ impl App {
                         warn!("couldn't figure whether a room is a DM or not: {err}");
                     })
                     .ok();
+                let read_receipts = room.read_receipts();
+                let notification_mode = room.notification_mode().await;
                 room_infos.lock().insert(
                     room.room_id().to_owned(),
-                    ExtraRoomInfo { raw_name, display_name, is_dm },
+                    ExtraRoomInfo {
+                        raw_name,
+                        display_name,
+                        is_dm,
+                        num_unread: read_receipts.num_unread,
+                        num_mentions: read_receipts.num_mentions,
+                        notification_mode,
+                    },
                 );
             }
 
@@ -313,46 +422,163 @@ impl App {
         }
     }
 
+    /// Stop the sync and the tasks of the account, then log out and delete
+    /// the session if `log_out` is set.
+    fn shut_down(&self, log_out: bool) -> JoinHandle<Result<()>> {
+        let client = self.client.clone();
+        let session_path = self.session_path.clone();
+        let sync_service = self.sync_service.clone();
+        let timelines = self.timelines.clone();
+        let listen_task = self.listen_task.abort_handle();
+
+        spawn(async move {
+            sync_service.stop().await;
+
+            listen_task.abort();
+
+            for timeline in timelines.lock().values() {
+                timeline.task.abort();
+            }
+
+            if log_out { log_out_and_delete_session(&client, &session_path).await } else { Ok(()) }
+        })
+    }
+}
+
+struct App {
+    /// The accounts multiverse is logged in to; only one of them is shown at a
+    /// time.
+    accounts: Vec<Account>,
+
+    /// The index of the account being shown, in `accounts`.
+    active_account: usize,
+
+    /// The status widget at the bottom of the screen.
+    status: Status,
+
+    state: AppState,
+
+    last_tick: Instant,
+}
+
+impl App {
+    const TICK_RATE: Duration = Duration::from_millis(250);
+
+    async fn new(clients: Vec<(Client, PathBuf)>) -> Result<Self> {
+        // There's always the account given by the positional arguments.
+        let status = Status::new(clients[0].0.clock());
+
+        let mut accounts = Vec::with_capacity(clients.len());
+        for (client, session_path) in clients {
+            accounts.push(Account::new(client, session_path, &status).await?);
+        }
+
+        Ok(Self {
+            accounts,
+            active_account: 0,
+            status,
+            state: AppState::default(),
+            last_tick: Instant::now(),
+        })
+    }
+
+    /// The account being shown.
+    fn account(&self) -> &Account {
+        &self.accounts[self.active_account]
+    }
+
+    fn account_mut(&mut self) -> &mut Account {
+        &mut self.accounts[self.active_account]
+    }
+
+    /// Show the next account, or the first one after the last one.
+    fn switch_to_next_account(&mut self) {
+        // The previews of the images of the previous account must not stay on screen.
+        self.account_mut().room_view.hide_image_previews();
+
+        self.active_account = (self.active_account + 1) % self.accounts.len();
+
+        let message = match self.account().client.user_id() {
+            Some(user_id) => format!("switched to {user_id}"),
+            None => "switched to the next account".to_owned(),
+        };
+        self.status.handle().set_message(message);
+    }
+
     fn set_global_mode(&mut self, mode: GlobalMode) {
         self.state.global_mode = mode;
     }
 
     async fn handle_global_event(&mut self, event: Event) -> Result<bool> {
-        use KeyCode::*;
+        if let Event::Mouse(mouse) = event
+            && self.account().room_list.contains(mouse.column, mouse.row)
+        {
+            let account = self.account_mut();
+
+            if account.room_list.handle_mouse_event(mouse).await {
+                let room_id = account.room_list.get_selected_room_id();
+                account.room_view.set_selected_room(room_id).await;
+            }
+
+            return Ok(false);
+        }
 
-        match event {
-            Event::Key(KeyEvent { code: F(1), modifiers: KeyModifiers::NONE, .. }) => {
-                self.set_global_mode(GlobalMode::Help)
+        let action = match &event {
+            // The characters of the text being written must not trigger the shortcuts.
+            Event::Key(key)
+                if matches!(key.code, KeyCode::Char(_))
+                    && (key.modifiers - KeyModifiers::SHIFT).is_empty()
+                    && self.account().room_view.is_editing_text() =>
+            {
+                None
+            }
+            Event::Key(key) => keymap().action(key),
+            _ => None,
+        };
+
+        match action {
+            Some(Action::Help) => self.set_global_mode(GlobalMode::Help),
+            Some(Action::StatusLog) => {
+                self.set_global_mode(GlobalMode::StatusLog { view: StatusLogView::new() })
+            }
+            Some(Action::Devices) => {
+                let view = DevicesView::new(self.account().client.clone(), self.status.handle());
+                self.set_global_mode(GlobalMode::Devices { view })
             }
 
-            Event::Key(KeyEvent { code: F(10), modifiers: KeyModifiers::NONE, .. }) => self
-                .set_global_mode(GlobalMode::Settings {
-                    view: SettingsView::new(self.client.clone(), self.sync_service.clone()),
-                }),
+            Some(Action::EncryptionSettings) => {
+                let account = self.account();
+                let view = SettingsView::new(account.client.clone(), account.sync_service.clone());
+                self.set_global_mode(GlobalMode::Settings { view })
+            }
 
-            Event::Key(KeyEvent {
-                code: Char('j') | Down,
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            }) => {
-                self.room_list.next_room().await;
-                let room_id = self.room_list.get_selected_room_id();
-                self.room_view.set_selected_room(room_id);
+            Some(Action::NextRoom) => {
+                let account = self.account_mut();
+                account.room_list.next_room().await;
+                let room_id = account.room_list.get_selected_room_id();
+                account.room_view.set_selected_room(room_id).await;
             }
 
-            Event::Key(KeyEvent {
-                code: Char('k') | Up, modifiers: KeyModifiers::CONTROL, ..
-            }) => {
-                self.room_list.previous_room().await;
-                let room_id = self.room_list.get_selected_room_id();
-                self.room_view.set_selected_room(room_id);
+            Some(Action::PreviousRoom) => {
+                let account = self.account_mut();
+                account.room_list.previous_room().await;
+                let room_id = account.room_list.get_selected_room_id();
+                account.room_view.set_selected_room(room_id).await;
             }
 
-            Event::Key(KeyEvent { code: Char('m'), modifiers: KeyModifiers::ALT, .. }) => {
-                self.room_view.mark_as_read().await
+            Some(Action::NextSpace) => self.account_mut().room_list.switch_to_next_space(),
+
+            Some(Action::NextAccount) => self.switch_to_next_account(),
+
+            Some(Action::GoToNotifiedRoom) => self.go_to_notified_room().await,
+
+            Some(Action::MarkAsRead) => self.account_mut().room_view.mark_as_read().await,
+
+            Some(Action::CycleNotificationMode) => {
+                self.account().room_list.cycle_notification_mode()
             }
 
-            Event::Key(KeyEvent { code: Char('q'), modifiers: KeyModifiers::CONTROL, .. }) => {
+            Some(Action::Quit) => {
                 if !matches!(self.state.global_mode, GlobalMode::Default) {
                     self.set_global_mode(GlobalMode::Default);
                 } else {
@@ -360,15 +586,20 @@ impl App {
                 }
             }
 
-            Event::Key(KeyEvent { modifiers: KeyModifiers::CONTROL, code: Char('r'), .. }) => {
+            Some(Action::CreateRoom) => {
                 self.set_global_mode(GlobalMode::CreateRoom { view: CreateRoomView::new() })
             }
 
-            Event::Key(KeyEvent { modifiers: KeyModifiers::CONTROL, code: Char('s'), .. }) => {
+            Some(Action::Search) => {
                 self.set_global_mode(GlobalMode::Searching { view: SearchingView::new() })
             }
 
-            _ => self.room_view.handle_event(event).await,
+            Some(Action::FilterRooms) => {
+                self.set_global_mode(GlobalMode::FilteringRooms { view: RoomFilterView::new() })
+            }
+
+            // The other actions belong to the room view.
+            _ => self.account_mut().room_view.handle_event(event).await,
         }
 
         Ok(false)
@@ -377,11 +608,40 @@ impl App {
     fn on_tick(&mut self) {
         self.state.throbber_state.calc_next();
 
+        for account in &mut self.accounts {
+            account.room_list.on_tick();
+        }
+
+        let account = self.account_mut();
+        account.room_view.on_tick();
+        account.notification_popup.on_tick(account.room_view.room_id());
+
         match &mut self.state.global_mode {
+            // Show the next incoming verification request of any account, if any, once the
+            // user isn't busy with another popup. The other side may have given up in the
+            // meantime.
+            GlobalMode::Default => {
+                let request = self
+                    .accounts
+                    .iter()
+                    .find_map(|account| account.verification_requests.lock().pop_front());
+
+                if let Some(request) = request
+                    && !request.is_cancelled()
+                    && !request.is_done()
+                {
+                    self.set_global_mode(GlobalMode::Verifying {
+                        view: VerificationView::new(request),
+                    });
+                }
+            }
             GlobalMode::Help
-            | GlobalMode::Default
             | GlobalMode::CreateRoom { .. }
             | GlobalMode::Searching { .. }
+            | GlobalMode::FilteringRooms { .. }
+            | GlobalMode::Verifying { .. }
+            | GlobalMode::StatusLog { .. }
+            | GlobalMode::Devices { .. }
             | GlobalMode::Exiting { .. } => {}
             GlobalMode::Settings { view } => {
                 view.on_tick();
@@ -389,33 +649,103 @@ impl App {
         }
     }
 
+    /// Select the room of the notification being shown, if any.
+    async fn go_to_notified_room(&mut self) {
+        let account = self.account_mut();
+
+        let Some(notification) = account.notification_popup.take() else {
+            return;
+        };
+
+        if account.room_list.select_room(&notification.room_id).await {
+            account.room_view.set_selected_room(Some(notification.room_id)).await;
+        } else {
+            self.status
+                .handle()
+                .set_message(format!("{} isn't in the filtered room list", notification.room_id));
+        }
+    }
+
+    /// Stop the sync and the tasks of all the accounts, then quit.
+    ///
+    /// When `log_out` is set, also log out of the account being shown and
+    /// delete its session and its stores, so no stale credentials are left
+    /// around. They're kept if the homeserver couldn't log us out, to be able
+    /// to try again.
+    fn exit(&mut self, log_out: bool) {
+        let tasks = self
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(index, account)| account.shut_down(log_out && index == self.active_account))
+            .collect::<Vec<_>>();
+
+        let shutdown_task = spawn(async move {
+            for task in tasks {
+                task.await??;
+            }
+
+            Ok(())
+        });
+
+        self.set_global_mode(GlobalMode::Exiting { shutdown_task });
+    }
+
+    /// Update the room list after the room view has created, joined or left a
+    /// room.
+    async fn apply_room_view_changes(&mut self) {
+        if self.account_mut().room_view.take_logout_request() {
+            self.exit(true);
+            return;
+        }
+
+        let account = self.account_mut();
+
+        if account.room_view.take_left_room().is_some() {
+            account.room_list.refresh_filter();
+        }
+
+        // Select the room created or found by a command, once it has appeared in the
+        // room list.
+        if let Some(room_id) = account.room_view.room_to_select().map(ToOwned::to_owned)
+            && account.room_list.select_room(&room_id).await
+        {
+            account.room_view.clear_room_to_select();
+            account.room_view.set_selected_room(Some(room_id)).await;
+        }
+    }
+
     async fn render_loop(&mut self, mut terminal: Terminal<impl Backend>) -> Result<()> {
         use KeyCode::*;
 
         loop {
             terminal.draw(|f| f.render_widget(&mut *self, f.area()))?;
 
+            // The previews of the images are drawn over the frame, with the graphics
+            // protocol of the terminal; the screen is redrawn from scratch when they
+            // move, to remove them from their previous positions.
+            // The previews of an account which isn't shown anymore must be removed too.
+            if self.accounts.iter().any(|account| account.room_view.image_previews_have_changed()) {
+                terminal.clear()?;
+                terminal.draw(|f| f.render_widget(&mut *self, f.area()))?;
+
+                for account in &mut self.accounts {
+                    account.room_view.flush_image_previews(&mut stdout())?;
+                }
+            }
+
             if event::poll(Duration::from_millis(100))? {
                 let event = event::read()?;
 
                 match &mut self.state.global_mode {
                     GlobalMode::Default => {
                         if self.handle_global_event(event).await? {
-                            let sync_service = self.sync_service.clone();
-                            let timelines = self.timelines.clone();
-                            let listen_task = self.listen_task.abort_handle();
-
-                            let shutdown_task = spawn(async move {
-                                sync_service.stop().await;
-
-                                listen_task.abort();
-
-                                for timeline in timelines.lock().values() {
-                                    timeline.task.abort();
-                                }
-                            });
+                            // Keep the messages being written for the next start.
+                            for account in &mut self.accounts {
+                                account.room_view.save_draft().await;
+                            }
 
-                            self.set_global_mode(GlobalMode::Exiting { shutdown_task });
+                            self.exit(false);
                         }
                     }
                     GlobalMode::Help => {
@@ -433,6 +763,26 @@ impl App {
                             self.set_global_mode(GlobalMode::Default);
                         }
                     }
+                    GlobalMode::StatusLog { view } => {
+                        if let Event::Key(key) = event
+                            && view.handle_key_press(key)
+                        {
+                            self.set_global_mode(GlobalMode::Default);
+                        }
+                    }
+                    GlobalMode::Devices { view } => {
+                        if let Event::Key(key) = event {
+                            match view.handle_key_press(key).await {
+                                DevicesOutcome::Continue => {}
+                                DevicesOutcome::Close => self.set_global_mode(GlobalMode::Default),
+                                DevicesOutcome::Verify(request) => {
+                                    self.set_global_mode(GlobalMode::Verifying {
+                                        view: VerificationView::new(request),
+                                    });
+                                }
+                            }
+                        }
+                    }
                     GlobalMode::CreateRoom { view } => {
                         if let Event::Key(key) = event
                             && let KeyModifiers::NONE = key.modifiers
@@ -442,7 +792,7 @@ impl App {
                                     if let Some(room_name) = view.get_text() {
                                         let mut request = CreateRoomRequest::new();
                                         request.name = Some(room_name);
-                                        if let Err(err) = self
+                                        if let Err(err) = self.accounts[self.active_account]
                                             .sync_service
                                             .room_list_service()
                                             .client()
@@ -450,6 +800,9 @@ impl App {
                                             .await
                                         {
                                             error!("error while creating room: {err:?}");
+                                            self.status.handle().set_error(format!(
+                                                "couldn't create the room: {err}"
+                                            ));
                                         }
                                     }
                                     self.set_global_mode(GlobalMode::Default);
@@ -463,13 +816,15 @@ impl App {
                         if let Event::Key(key) = event {
                             match key.code {
                                 Enter => {
+                                    let account = &self.accounts[self.active_account];
+
                                     if let Some(query) = view.get_text() {
-                                        if let Some(room) = self.room_view.room() {
+                                        if let Some(room) = account.room_view.room() {
                                             if let Some(results) =
                                                 room.search(&query, 100, None).await
                                             {
                                                 let results = get_events_from_event_ids(
-                                                    &self.client,
+                                                    &account.client,
                                                     &room,
                                                     results,
                                                 )
@@ -491,19 +846,58 @@ impl App {
                             }
                         }
                     }
+                    GlobalMode::FilteringRooms { view } => {
+                        if let Event::Key(key) = event {
+                            match key.code {
+                                // Enter keeps the filter, Esc removes it; both open the
+                                // selected room, i.e. the first match.
+                                Enter | Esc => {
+                                    let account = &mut self.accounts[self.active_account];
+
+                                    if key.code == Esc {
+                                        account.room_list.set_name_filter("");
+                                    }
+
+                                    account.room_list.subscribe_to_selected_room().await;
+                                    let room_id = account.room_list.get_selected_room_id();
+                                    account.room_view.set_selected_room(room_id).await;
+                                    self.set_global_mode(GlobalMode::Default);
+                                }
+                                _ => {
+                                    view.handle_key_press(key);
+                                    self.accounts[self.active_account]
+                                        .room_list
+                                        .set_name_filter(&view.get_text());
+                                }
+                            }
+                        }
+                    }
+                    GlobalMode::Verifying { view } => {
+                        if let Event::Key(key) = event
+                            && view.handle_key_press(key).await
+                        {
+                            self.set_global_mode(GlobalMode::Default);
+                        }
+                    }
                     GlobalMode::Exiting { .. } => {}
                 }
             }
 
-            match &self.state.global_mode {
+            self.apply_room_view_changes().await;
+
+            match &mut self.state.global_mode {
                 GlobalMode::Default
                 | GlobalMode::Help
                 | GlobalMode::CreateRoom { .. }
                 | GlobalMode::Searching { .. }
+                | GlobalMode::FilteringRooms { .. }
+                | GlobalMode::Verifying { .. }
+                | GlobalMode::StatusLog { .. }
+                | GlobalMode::Devices { .. }
                 | GlobalMode::Settings { .. } => {}
                 GlobalMode::Exiting { shutdown_task } => {
                     if shutdown_task.is_finished() {
-                        break;
+                        return shutdown_task.await?;
                     }
                 }
             }
@@ -513,18 +907,17 @@ impl App {
                 self.last_tick = Instant::now();
             }
         }
-
-        Ok(())
     }
 
     async fn run(&mut self, terminal: Terminal<impl Backend>) -> Result<()> {
-        self.render_loop(terminal).await?;
+        let result = self.render_loop(terminal).await;
 
-        // At this point the user has exited the loop, so shut down the application.
+        // At this point the user has exited the loop, so shut down the application,
+        // before reporting a failure to log out, if any.
         ratatui::restore();
         execute!(stdout(), DisableMouseCapture)?;
 
-        Ok(())
+        result
     }
 }
 
@@ -543,8 +936,20 @@ impl Widget for &mut App {
         let [room_list_area, room_view_area] = horizontal.areas(rest_area);
 
         self.render_title(header_area, buf);
-        self.room_list.render(room_list_area, buf);
-        self.room_view.render(room_view_area, buf);
+
+        let account = &mut self.accounts[self.active_account];
+        account.room_list.render(room_list_area, buf);
+        account.room_view.render(room_view_area, buf);
+
+        // The popups can't cover the previews of the images, so hide them.
+        if !matches!(self.state.global_mode, GlobalMode::Default)
+            || account.notification_popup.is_visible()
+        {
+            account.room_view.hide_image_previews();
+        }
+
+        account.notification_popup.render(room_view_area, buf);
+
         self.status.render(status_area, buf, &mut self.state);
 
         match &mut self.state.global_mode {
@@ -570,23 +975,50 @@ impl Widget for &mut App {
             GlobalMode::Searching { view } => {
                 view.render(room_view_area, buf);
             }
+            GlobalMode::FilteringRooms { view } => {
+                view.render(room_list_area, buf);
+            }
+            GlobalMode::Verifying { view } => {
+                view.render(area, buf);
+            }
+            GlobalMode::StatusLog { view } => {
+                view.render(area, buf, &self.status.log());
+            }
+            GlobalMode::Devices { view } => {
+                view.render(area, buf);
+            }
         }
     }
 }
 
 impl App {
-    /// Render the top square (title of the program).
+    /// Render the top square (title of the program), with the account being
+    /// shown when there are several of them, and the state of its sync.
     fn render_title(&self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("Multiverse").bold().centered().render(area, buf);
+        let title = match self.account().client.user_id() {
+            Some(user_id) if self.accounts.len() > 1 => format!(
+                "Multiverse — {user_id} ({}/{})",
+                self.active_account + 1,
+                self.accounts.len()
+            ),
+            _ => "Multiverse".to_owned(),
+        };
+
+        Paragraph::new(title).bold().centered().render(area, buf);
+        self.account().sync_indicator.render(area, buf);
     }
 }
 
 /// Configure the client so it's ready for sync'ing.
 ///
 /// Will log in or reuse a previous session.
-async fn configure_client(cli: Cli) -> Result<Client> {
-    let Cli { server_name, session_path, proxy } = cli;
-
+async fn configure_client(
+    server_name: &str,
+    session_path: &Path,
+    proxy: Option<&Url>,
+    record_sync: Option<&Path>,
+    replay_sync: Option<&Path>,
+) -> Result<Client> {
     let mut client_builder = Client::builder()
         .store_config(
             StoreConfig::new("multiverse".to_owned())
@@ -596,7 +1028,8 @@ async fn configure_client(cli: Cli) -> Result<Client> {
                     SqliteEventCacheStore::open(session_path.join("cache"), None).await?,
                 ),
         )
-        .server_name_or_homeserver_url(&server_name)
+        .server_name_or_homeserver_url(server_name)
+        .handle_refresh_tokens()
         .with_encryption_settings(EncryptionSettings {
             auto_enable_cross_signing: true,
             backup_download_strategy: BackupDownloadStrategy::AfterDecryptionFailure,
@@ -607,35 +1040,127 @@ async fn configure_client(cli: Cli) -> Result<Client> {
         .search_index_store(SearchIndexStoreKind::Directory(session_path.join("indexData")));
 
     if let Some(proxy_url) = proxy {
-        client_builder = client_builder.proxy(proxy_url).disable_ssl_verification();
+        client_builder = client_builder.proxy(proxy_url.clone()).disable_ssl_verification();
+    }
+
+    if let Some(path) = record_sync {
+        client_builder = client_builder.record_sync_responses(SyncRecorder::create(path)?);
+    }
+
+    if let Some(path) = replay_sync {
+        client_builder = client_builder.replay_sync_responses(SyncRecording::load(path)?);
     }
 
     let client = client_builder.build().await?;
 
     // Try reading a session, otherwise create a new one.
-    log_in_or_restore_session(&client, &session_path).await?;
+    log_in_or_restore_session(&client, session_path).await?;
 
     Ok(client)
 }
 
+/// The session saved in `session.json`, to restore it on the next start.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredSession {
+    /// A session logged in with OAuth 2.0, which also needs the ID multiverse
+    /// was registered with.
+    OAuth { client_id: ClientId, user_session: UserSession },
+
+    /// A session logged in with a password.
+    Matrix(MatrixSession),
+}
+
+impl From<StoredSession> for AuthSession {
+    fn from(session: StoredSession) -> Self {
+        match session {
+            StoredSession::OAuth { client_id, user_session } => {
+                OAuthSession { client_id, user: user_session }.into()
+            }
+            StoredSession::Matrix(session) => session.into(),
+        }
+    }
+}
+
 async fn log_in_or_restore_session(client: &Client, session_path: &Path) -> Result<()> {
     let session_path = session_path.join("session.json");
 
     if let Ok(serialized) = std::fs::read_to_string(&session_path) {
-        let session: MatrixSession = serde_json::from_str(&serialized)?;
+        let session: StoredSession = serde_json::from_str(&serialized)?;
         client.restore_session(session).await?;
     } else {
-        login_with_password(client).await?;
+        // Some homeservers only allow logging in with OAuth 2.0, so prefer it when it's
+        // supported.
+        match client.oauth().server_metadata().await {
+            Ok(_) => oauth::login_with_oauth(client).await?,
+            Err(error) if error.is_not_supported() => login_with_password(client).await?,
+            Err(error) => return Err(error.into()),
+        }
 
         // Immediately save the session to disk.
-        if let Some(session) = client.session() {
-            let AuthSession::Matrix(session) = session else {
-                panic!("unexpected OAuth 2.0 session")
-            };
-            let serialized = serde_json::to_string(&session)?;
-            std::fs::write(session_path, serialized)?;
+        save_session(client, &session_path)?;
+        println!("saved session");
+    }
+
+    // The OAuth 2.0 access tokens expire, so save the new ones when they're
+    // refreshed.
+    let client = client.clone();
+    spawn(async move {
+        let mut session_changes = client.subscribe_to_session_changes();
+
+        while let Ok(change) = session_changes.recv().await {
+            if let SessionChange::TokensRefreshed = change
+                && let Err(err) = save_session(&client, &session_path)
+            {
+                error!("couldn't save the refreshed session: {err:?}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Write the session of the client to the given file.
+fn save_session(client: &Client, session_file: &Path) -> Result<()> {
+    let session = match client.session() {
+        Some(AuthSession::Matrix(session)) => StoredSession::Matrix(session),
+        Some(AuthSession::OAuth(session)) => {
+            let OAuthSession { client_id, user } = *session;
+            StoredSession::OAuth { client_id, user_session: user }
+        }
+        None => return Ok(()),
+    };
+
+    std::fs::write(session_file, serde_json::to_string(&session)?)?;
+
+    Ok(())
+}
+
+/// Log out, then delete the session and everything stored for it, except the
+/// logs.
+async fn log_out_and_delete_session(client: &Client, session_path: &Path) -> Result<()> {
+    client.logout().await.wrap_err("couldn't log out, the session was kept")?;
 
-            println!("saved session");
+    let session_file = session_path.join("session.json");
+
+    match std::fs::remove_file(&session_file) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err)
+                .wrap_err_with(|| format!("couldn't delete {}", session_file.display()));
+        }
+    }
+
+    for store in ["crypto", "state", "cache", "indexData", "media"] {
+        let path = session_path.join(store);
+
+        match std::fs::remove_dir_all(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("couldn't delete {}", path.display()));
+            }
         }
     }
 
@@ -0,0 +1,96 @@
+//! Logging in with the OAuth 2.0 authorization code flow, for the homeservers
+//! which don't allow logging in with a password.
+
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    process::{Command, Stdio},
+};
+
+use color_eyre::Result;
+use matrix_sdk::{
+    Client,
+    authentication::oauth::{
+        OAuthAuthorizationData, UrlOrQuery,
+        registration::{ApplicationType, ClientMetadata, Localized, OAuthGrantType},
+    },
+    reqwest::Url,
+    ruma::serde::Raw,
+    utils::local_server::LocalServerBuilder,
+};
+
+/// Register multiverse with the authorization server of the homeserver, and
+/// log in through the browser, until it succeeds.
+pub async fn login_with_oauth(client: &Client) -> Result<()> {
+    println!("Logging in with OAuth 2.0…");
+
+    let oauth = client.oauth();
+
+    loop {
+        // The authorization server redirects the browser to this local server once the
+        // user has allowed multiverse to access their account.
+        let (redirect_uri, redirect_handle) = LocalServerBuilder::new().spawn().await?;
+
+        let OAuthAuthorizationData { url, .. } =
+            oauth.login(redirect_uri, None, Some(client_metadata().into()), None).build().await?;
+
+        println!("\nPlease log in at: {url}\n");
+        open_in_browser(&url);
+
+        let query = redirect_handle.await.map(|query| query.0).unwrap_or_default();
+
+        match oauth.finish_login(UrlOrQuery::Query(query)).await {
+            Ok(()) => {
+                if let Some(user_id) = client.user_id() {
+                    println!("Logged in as {user_id}");
+                }
+                break;
+            }
+            Err(error) => {
+                println!("Error logging in: {error}");
+                println!("Please try again\n");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The metadata shown to the user by the authorization server, when asking
+/// them to allow multiverse to access their account.
+fn client_metadata() -> Raw<ClientMetadata> {
+    // Native clients can register the loopback interfaces, and use any port in the
+    // redirect URI.
+    let ipv4_localhost_uri = Url::parse(&format!("http://{}/", Ipv4Addr::LOCALHOST))
+        .expect("the IPv4 redirect URI should be valid");
+    let ipv6_localhost_uri = Url::parse(&format!("http://[{}]/", Ipv6Addr::LOCALHOST))
+        .expect("the IPv6 redirect URI should be valid");
+    let client_uri = Localized::new(
+        Url::parse("https://github.com/matrix-org/matrix-rust-sdk")
+            .expect("the client URI should be valid"),
+        None,
+    );
+
+    let metadata = ClientMetadata {
+        client_name: Some(Localized::new("multiverse".to_owned(), [])),
+        policy_uri: Some(client_uri.clone()),
+        tos_uri: Some(client_uri.clone()),
+        ..ClientMetadata::new(
+            ApplicationType::Native,
+            vec![OAuthGrantType::AuthorizationCode {
+                redirect_uris: vec![ipv4_localhost_uri, ipv6_localhost_uri],
+            }],
+            client_uri,
+        )
+    };
+
+    Raw::new(&metadata).expect("the client metadata should serialize")
+}
+
+/// Try to open the URL in the default browser; it's been printed anyways, in
+/// case there's none.
+fn open_in_browser(url: &Url) {
+    let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+
+    let _ =
+        Command::new(opener).arg(url.as_str()).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+}
@@ -0,0 +1,410 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use matrix_sdk::{
+    Client,
+    encryption::verification::VerificationRequest,
+    locks::Mutex,
+    ruma::{
+        MilliSecondsSinceUnixEpoch, OwnedDeviceId,
+        api::client::uiaa::{AuthData, Password},
+    },
+};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, Wrap},
+};
+use style::palette::tailwind;
+use tokio::{spawn, task::JoinHandle};
+
+use crate::{
+    config::theme,
+    popup_area,
+    widgets::{
+        popup_input::{PopupInput, PopupInputBuilder},
+        room_view::export::format_timestamp,
+        status::StatusHandle,
+    },
+};
+
+/// A device of the account, as listed by the server, with its verification
+/// state as known by the crypto store.
+struct DeviceRow {
+    device_id: OwnedDeviceId,
+    display_name: Option<String>,
+    last_seen_ip: Option<String>,
+    last_seen_ts: Option<MilliSecondsSinceUnixEpoch>,
+
+    /// Whether the device is verified, or `None` if its keys are unknown.
+    is_verified: Option<bool>,
+
+    /// Whether it's the device multiverse is running as.
+    is_current: bool,
+}
+
+/// The devices of the account, or `None` while they're loading.
+type Devices = Arc<Mutex<Option<Result<Vec<DeviceRow>, String>>>>;
+
+enum Mode {
+    Browsing,
+
+    /// Typing the new name of a device.
+    Renaming {
+        device_id: OwnedDeviceId,
+        input: PopupInput,
+    },
+
+    /// Typing the password to sign a device out, as required by the server.
+    SigningOut {
+        device_id: OwnedDeviceId,
+        session: Option<String>,
+        input: PopupInput,
+    },
+}
+
+/// What must be done after a key press in the [`DevicesView`].
+pub enum DevicesOutcome {
+    /// Keep the device manager open.
+    Continue,
+
+    /// Close the device manager.
+    Close,
+
+    /// Close the device manager, and show the verification which has been
+    /// requested.
+    Verify(VerificationRequest),
+}
+
+/// A popup listing the devices of the account, to rename them, sign them out,
+/// or verify them.
+pub struct DevicesView {
+    client: Client,
+    status_handle: StatusHandle,
+
+    devices: Devices,
+
+    /// Task loading the devices, restarted after every change.
+    task: JoinHandle<()>,
+
+    list_state: ListState,
+    mode: Mode,
+}
+
+impl DevicesView {
+    pub fn new(client: Client, status_handle: StatusHandle) -> Self {
+        let devices = Devices::default();
+        let task = spawn(load_devices(client.clone(), devices.clone()));
+
+        Self {
+            client,
+            status_handle,
+            devices,
+            task,
+            list_state: ListState::default().with_selected(Some(0)),
+            mode: Mode::Browsing,
+        }
+    }
+
+    /// Load the devices again, once one has been renamed or signed out.
+    fn reload(&mut self) {
+        self.task.abort();
+        self.task = spawn(load_devices(self.client.clone(), self.devices.clone()));
+    }
+
+    /// The ID of the selected device, and whether it's the current one.
+    fn selected_device(&self) -> Option<(OwnedDeviceId, bool)> {
+        let index = self.list_state.selected()?;
+        let devices = self.devices.lock();
+        let device = devices.as_ref()?.as_ref().ok()?.get(index)?;
+        Some((device.device_id.clone(), device.is_current))
+    }
+
+    pub async fn handle_key_press(&mut self, key: KeyEvent) -> DevicesOutcome {
+        use KeyCode::*;
+
+        match &mut self.mode {
+            Mode::Browsing => match key.code {
+                Char('j') | Down => self.list_state.select_next(),
+                Char('k') | Up => self.list_state.select_previous(),
+                Char('r') => self.start_renaming(),
+                Char('x') => self.sign_out(None).await,
+                Char('v') => {
+                    if let Some(request) = self.request_verification().await {
+                        return DevicesOutcome::Verify(request);
+                    }
+                }
+                Char('q') | Esc => return DevicesOutcome::Close,
+                _ => {}
+            },
+
+            Mode::Renaming { device_id, input } => match key.code {
+                Esc => self.mode = Mode::Browsing,
+                Enter => {
+                    let device_id = device_id.clone();
+                    let name = input.get_input();
+                    self.mode = Mode::Browsing;
+
+                    match self.client.rename_device(&device_id, name.trim()).await {
+                        Ok(_) => {
+                            self.status_handle.set_message(format!("renamed {device_id}"));
+                            self.reload();
+                        }
+                        Err(err) => self
+                            .status_handle
+                            .set_error(format!("couldn't rename {device_id}: {err}")),
+                    }
+                }
+                _ => input.handle_key_press(key),
+            },
+
+            Mode::SigningOut { session, input, .. } => match key.code {
+                Esc => self.mode = Mode::Browsing,
+                Enter => {
+                    let session = session.take();
+                    let password = input.get_input();
+                    self.sign_out(Some((session, password))).await;
+                }
+                _ => input.handle_key_press(key),
+            },
+        }
+
+        DevicesOutcome::Continue
+    }
+
+    fn start_renaming(&mut self) {
+        let Some((device_id, _)) = self.selected_device() else {
+            return;
+        };
+
+        let display_name = self.devices.lock().as_ref().and_then(|devices| {
+            devices
+                .as_ref()
+                .ok()?
+                .iter()
+                .find(|device| device.device_id == device_id)?
+                .display_name
+                .clone()
+        });
+
+        let mut input = PopupInputBuilder::new("Rename the device", "(Enter the new name)")
+            .width_constraint(Constraint::Percentage(50))
+            .build();
+
+        if let Some(display_name) = display_name {
+            input.set_input(&display_name);
+        }
+
+        self.mode = Mode::Renaming { device_id, input };
+    }
+
+    /// Sign the selected device out, or the one whose password is being
+    /// typed.
+    ///
+    /// The first attempt is made without authentication; if the server asks
+    /// for it, the password is prompted, and sent along with the session of
+    /// the interactive authentication.
+    async fn sign_out(&mut self, auth: Option<(Option<String>, String)>) {
+        let device_id = match &self.mode {
+            Mode::SigningOut { device_id, .. } => device_id.clone(),
+            Mode::Browsing | Mode::Renaming { .. } => {
+                let Some((device_id, is_current)) = self.selected_device() else {
+                    return;
+                };
+
+                if is_current {
+                    self.status_handle
+                        .set_message("use /logout to sign out of the current session".to_owned());
+                    return;
+                }
+
+                device_id
+            }
+        };
+
+        let auth_data = auth.and_then(|(session, password)| {
+            let user_id = self.client.user_id()?;
+            let mut password = Password::new(user_id.to_owned().into(), password);
+            password.session = session;
+            Some(AuthData::Password(password))
+        });
+
+        self.mode = Mode::Browsing;
+
+        match self.client.delete_devices(&[device_id.clone()], auth_data).await {
+            Ok(_) => {
+                self.status_handle.set_message(format!("signed {device_id} out"));
+                self.reload();
+            }
+            Err(err) => {
+                if let Some(info) = err.as_uiaa_response() {
+                    if info.auth_error.is_some() {
+                        self.status_handle.set_error("wrong password".to_owned());
+                    }
+
+                    let input = PopupInputBuilder::new(
+                        "Sign the device out",
+                        "(Enter your password to confirm)",
+                    )
+                    .width_constraint(Constraint::Percentage(50))
+                    .masked()
+                    .build();
+
+                    self.mode =
+                        Mode::SigningOut { device_id, session: info.session.clone(), input };
+                } else {
+                    self.status_handle.set_error(format!("couldn't sign {device_id} out: {err}"));
+                }
+            }
+        }
+    }
+
+    /// Request the verification of the selected device.
+    async fn request_verification(&self) -> Option<VerificationRequest> {
+        let (device_id, is_current) = self.selected_device()?;
+
+        if is_current {
+            self.status_handle.set_message("the current session can't verify itself".to_owned());
+            return None;
+        }
+
+        let user_id = self.client.user_id()?;
+
+        let device = match self.client.encryption().get_device(user_id, &device_id).await {
+            Ok(Some(device)) => device,
+            Ok(None) => {
+                self.status_handle.set_error(format!("the keys of {device_id} aren't known yet"));
+                return None;
+            }
+            Err(err) => {
+                self.status_handle.set_error(format!("couldn't get {device_id}: {err}"));
+                return None;
+            }
+        };
+
+        match device.request_verification().await {
+            Ok(request) => Some(request),
+            Err(err) => {
+                self.status_handle.set_error(format!("couldn't request the verification: {err}"));
+                None
+            }
+        }
+    }
+}
+
+impl Drop for DevicesView {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Load the devices of the account, the current one first, then by the time
+/// they've been seen for the last time, the most recent first.
+async fn load_devices(client: Client, devices: Devices) {
+    let result = async {
+        let response = client.devices().await.map_err(|err| err.to_string())?;
+        let user_id = client.user_id().ok_or("the client isn't logged in")?;
+        let own_devices =
+            client.encryption().get_user_devices(user_id).await.map_err(|err| err.to_string())?;
+        let current_device_id = client.device_id();
+
+        let mut rows = response
+            .devices
+            .into_iter()
+            .map(|device| DeviceRow {
+                is_verified: own_devices.get(&device.device_id).map(|own| own.is_verified()),
+                is_current: Some(&*device.device_id) == current_device_id,
+                device_id: device.device_id,
+                display_name: device.display_name,
+                last_seen_ip: device.last_seen_ip,
+                last_seen_ts: device.last_seen_ts,
+            })
+            .collect::<Vec<_>>();
+
+        rows.sort_by(|a, b| {
+            b.is_current.cmp(&a.is_current).then(b.last_seen_ts.cmp(&a.last_seen_ts))
+        });
+
+        Ok::<_, String>(rows)
+    };
+
+    let result = result.await;
+    *devices.lock() = Some(result);
+}
+
+impl Widget for &mut DevicesView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(" Devices ")
+            .title_bottom(" r: rename, x: sign out, v: verify, q: close ")
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1));
+        let area = popup_area(area, 70, 60);
+        Clear.render(area, buf);
+
+        let devices = self.devices.lock();
+
+        let devices = match &*devices {
+            Some(Ok(devices)) => devices,
+            Some(Err(err)) => {
+                Paragraph::new(format!("Couldn't load the devices: {err}"))
+                    .fg(theme().text)
+                    .wrap(Wrap { trim: false })
+                    .block(block)
+                    .render(area, buf);
+                return;
+            }
+            None => {
+                Paragraph::new("Loading the devices…")
+                    .fg(theme().text)
+                    .block(block)
+                    .render(area, buf);
+                return;
+            }
+        };
+
+        let items = devices.iter().map(|device| {
+            let (verification, color) = match device.is_verified {
+                Some(true) => ("verified", tailwind::GREEN.c400),
+                Some(false) => ("not verified", tailwind::RED.c400),
+                None => ("unknown keys", tailwind::AMBER.c400),
+            };
+
+            let mut title = vec![
+                Span::from(device.display_name.clone().unwrap_or_else(|| "(no name)".to_owned()))
+                    .bold(),
+                Span::from(format!(" {} ", device.device_id)),
+                Span::from(verification).fg(color),
+            ];
+
+            if device.is_current {
+                title.push(Span::from(" (this session)").fg(tailwind::CYAN.c400));
+            }
+
+            let last_seen = match (&device.last_seen_ts, &device.last_seen_ip) {
+                (Some(ts), Some(ip)) => {
+                    format!("    last seen {} from {ip}", format_timestamp(*ts))
+                }
+                (Some(ts), None) => format!("    last seen {}", format_timestamp(*ts)),
+                (None, Some(ip)) => format!("    last seen from {ip}"),
+                (None, None) => "    never seen".to_owned(),
+            };
+
+            ListItem::new(vec![Line::from(title), Line::from(last_seen).fg(tailwind::SLATE.c400)])
+        });
+
+        let list = List::new(items)
+            .block(block)
+            .fg(theme().text)
+            .highlight_symbol(">")
+            .highlight_style(theme().selected_fg);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+
+        match &mut self.mode {
+            Mode::Browsing => {}
+            Mode::Renaming { input, .. } | Mode::SigningOut { input, .. } => {
+                input.render(area, buf)
+            }
+        }
+    }
+}
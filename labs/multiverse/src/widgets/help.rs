@@ -2,8 +2,12 @@ use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Cell, Clear, Padding, Row, Table, TableState},
 };
+use strum::IntoEnumIterator;
 
-use crate::popup_area;
+use crate::{
+    config::{Action, keymap},
+    popup_area,
+};
 
 #[derive(Default)]
 pub struct HelpView {}
@@ -21,52 +25,37 @@ impl Widget for &mut HelpView {
         let area = popup_area(area, 50, 50);
         Clear.render(area, buf);
 
-        let rows = vec![
-            Row::new(vec![Cell::from("F1"), Cell::from("Open Help")]),
-            Row::new(vec![Cell::from("F10"), Cell::from("Open the encryption settings")]),
-            Row::new(vec![Cell::from("Alt-l"), Cell::from("Open the linked chunk details view")]),
-            Row::new(vec![Cell::from("Alt-e"), Cell::from("Open the events details view")]),
-            Row::new(vec![Cell::from("Alt-r"), Cell::from("Open the read receipt details view")]),
-            Row::new(vec![
-                Cell::from("Alt-t"),
-                Cell::from("Switch the detail view tiling direction"),
-            ]),
-            Row::new(vec![
-                Cell::from("Alt-m"),
-                Cell::from("Mark the currently selected room as read"),
-            ]),
-            Row::new(vec![Cell::from("Ctrl-q"), Cell::from("Quit Multiverse")]),
-            Row::new(vec![
-                Cell::from("Ctrl-j / Ctrl-down"),
-                Cell::from("Switch to the next room in the list"),
-            ]),
-            Row::new(vec![
-                Cell::from("Ctrl-k / Ctrl-up"),
-                Cell::from("Switch to the previous room in the list"),
-            ]),
-            Row::new(vec![
-                Cell::from("Page-Up"),
-                Cell::from("Backpaginate the currently selected room"),
-            ]),
+        let keymap = keymap();
+
+        // The keys of the actions come from the configuration, the other ones are
+        // fixed.
+        let mut rows = Action::iter()
+            .map(|action| {
+                Row::new(vec![Cell::from(keymap.keys(action)), Cell::from(action.description())])
+            })
+            .collect::<Vec<_>>();
+
+        rows.extend([
+            Row::new(vec![Cell::from("1-9"), Cell::from("Vote for an answer of the focused poll")]),
+            Row::new(vec![Cell::from("Enter"), Cell::from("Send the message being written")]),
             Row::new(vec![
-                Cell::from("Ctrl-l"),
-                Cell::from("Like the last message in the selected room"),
+                Cell::from("Alt-Enter"),
+                Cell::from("Start a new line in the message being written"),
             ]),
             Row::new(vec![
-                Cell::from("Ctrl-n"),
-                Cell::from("Focus on the next item in the timeline view"),
+                Cell::from("Tab"),
+                Cell::from("Complete the command name or user ID being written"),
             ]),
+            Row::new(vec![Cell::from("Esc"), Cell::from("Cancel the message being written")]),
             Row::new(vec![
-                Cell::from("Ctrl-p"),
-                Cell::from("Focus on the previous item in the timeline view"),
+                Cell::from("Click"),
+                Cell::from("Select a room, a timeline item or a details tab"),
             ]),
             Row::new(vec![
-                Cell::from("Ctrl-t"),
-                Cell::from("Open a thread on the focused timeline item"),
+                Cell::from("Wheel"),
+                Cell::from("Move through the room list or the timeline"),
             ]),
-            Row::new(vec![Cell::from("Ctrl-r"), Cell::from("Create a new room")]),
-            Row::new(vec![Cell::from("Ctrl-s"), Cell::from("Search")]),
-        ];
+        ]);
         let widths = [Constraint::Length(5), Constraint::Length(5)];
 
         let help_table = Table::new(rows, widths)
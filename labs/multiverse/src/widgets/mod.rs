@@ -2,14 +2,20 @@ use itertools::Itertools;
 use ratatui::{prelude::*, widgets::WidgetRef};
 
 pub mod create_room;
+pub mod devices;
 pub mod help;
+pub mod notifications;
 pub mod popup_input;
 pub mod recovery;
+pub mod room_filter;
 pub mod room_list;
 pub mod room_view;
 pub mod search;
 pub mod settings;
 pub mod status;
+pub mod status_log;
+pub mod sync_indicator;
+pub mod verification;
 
 /// A hyperlink widget that renders a hyperlink in the terminal using [OSC 8].
 ///
@@ -0,0 +1,169 @@
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use matrix_sdk::{
+    Client, Room,
+    deserialized_responses::RawAnySyncOrStrippedTimelineEvent,
+    ruma::{
+        OwnedRoomId, RoomId,
+        events::{AnySyncMessageLikeEvent, AnySyncTimelineEvent, SyncMessageLikeEvent},
+    },
+    sync::Notification,
+};
+use matrix_sdk_common::locks::Mutex;
+use ratatui::{prelude::*, widgets::*};
+use style::palette::tailwind;
+
+/// How long a notification stays on screen.
+const POPUP_DURATION: Duration = Duration::from_secs(6);
+
+/// A message mentioning us, according to the push rules.
+pub struct MentionNotification {
+    pub room_id: OwnedRoomId,
+    room_name: String,
+    sender: String,
+    body: String,
+}
+
+/// The incoming mentions, waiting to be shown.
+pub type Notifications = Arc<Mutex<VecDeque<MentionNotification>>>;
+
+/// Collect the messages whose push actions highlight them, i.e. which mention
+/// us, into the returned queue.
+pub async fn listen_to_notifications(client: &Client) -> Notifications {
+    let notifications = Notifications::default();
+
+    let n = notifications.clone();
+    client
+        .register_notification_handler(move |notification: Notification, room: Room, _| {
+            let notifications = n.clone();
+            async move {
+                if let Some(mention) = mention_from_notification(notification, &room).await {
+                    notifications.lock().push_back(mention);
+                }
+            }
+        })
+        .await;
+
+    notifications
+}
+
+async fn mention_from_notification(
+    notification: Notification,
+    room: &Room,
+) -> Option<MentionNotification> {
+    if !notification.actions.iter().any(|action| action.is_highlight()) {
+        return None;
+    }
+
+    let RawAnySyncOrStrippedTimelineEvent::Sync(raw) = notification.event else {
+        return None;
+    };
+
+    let AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::RoomMessage(
+        SyncMessageLikeEvent::Original(event),
+    )) = raw.deserialize().ok()?
+    else {
+        return None;
+    };
+
+    if event.sender == room.own_user_id() {
+        return None;
+    }
+
+    let sender = match room.get_member_no_sync(&event.sender).await {
+        Ok(Some(member)) => member.name().to_owned(),
+        _ => event.sender.to_string(),
+    };
+
+    let room_name = match room.cached_display_name() {
+        Some(name) => name.to_string(),
+        None => room.room_id().to_string(),
+    };
+
+    Some(MentionNotification {
+        room_id: room.room_id().to_owned(),
+        room_name,
+        sender,
+        body: event.content.body().to_owned(),
+    })
+}
+
+/// A popup showing the incoming mentions one at a time, for a few seconds
+/// each.
+pub struct NotificationPopup {
+    notifications: Notifications,
+
+    /// The notification being shown, and when it started to be shown.
+    current: Option<(MentionNotification, Instant)>,
+}
+
+impl NotificationPopup {
+    pub fn new(notifications: Notifications) -> Self {
+        Self { notifications, current: None }
+    }
+
+    /// Is a notification being shown?
+    pub fn is_visible(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Hide the notification once it has been shown long enough, and show the
+    /// next one, skipping the mentions in the room which is already selected.
+    pub fn on_tick(&mut self, selected_room: Option<&RoomId>) {
+        if self.current.as_ref().is_some_and(|(_, shown_at)| shown_at.elapsed() >= POPUP_DURATION) {
+            self.current = None;
+        }
+
+        if self.current.is_some() {
+            return;
+        }
+
+        let mut notifications = self.notifications.lock();
+
+        while let Some(notification) = notifications.pop_front() {
+            if Some(&*notification.room_id) != selected_room {
+                self.current = Some((notification, Instant::now()));
+                break;
+            }
+        }
+    }
+
+    /// Hide the notification being shown, and return it, e.g. to jump to its
+    /// room.
+    pub fn take(&mut self) -> Option<MentionNotification> {
+        self.current.take().map(|(notification, _)| notification)
+    }
+}
+
+impl Widget for &NotificationPopup {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some((notification, _)) = &self.current else {
+            return;
+        };
+
+        // Show the popup in the top-right corner of the area.
+        let vertical = Layout::vertical([Constraint::Length(4)]).flex(layout::Flex::Start);
+        let horizontal = Layout::horizontal([Constraint::Length(60)]).flex(layout::Flex::End);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Clear.render(area, buf);
+
+        let text = vec![
+            Line::from(format!("{} in {}", notification.sender, notification.room_name)).bold(),
+            Line::from(notification.body.clone()),
+        ];
+
+        Paragraph::new(text)
+            .block(
+                Block::bordered()
+                    .title(" Mentioned (Alt-g: go to the room) ")
+                    .bg(tailwind::AMBER.c900)
+                    .fg(tailwind::GRAY.c50),
+            )
+            .render(area, buf);
+    }
+}
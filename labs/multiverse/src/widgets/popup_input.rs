@@ -32,6 +32,9 @@ pub(crate) struct PopupInputBuilder {
 
     /// Foreground color
     fg: Option<Color>,
+
+    /// Whether the input is hidden, e.g. for a password
+    masked: bool,
 }
 
 impl PopupInputBuilder {
@@ -76,6 +79,12 @@ impl PopupInputBuilder {
         self
     }
 
+    /// Hide the input text behind bullets
+    pub fn masked(&mut self) -> &mut Self {
+        self.masked = true;
+        self
+    }
+
     /// Build the [`PopupInput`] from this builder
     pub fn build(&self) -> PopupInput {
         let mut ret = PopupInput {
@@ -86,6 +95,10 @@ impl PopupInputBuilder {
 
         ret.textarea.set_placeholder_text(self.placeholder_text.clone());
 
+        if self.masked {
+            ret.textarea.set_mask_char('\u{2022}'); //U+2022 BULLET (•)
+        }
+
         let border_set = self.border_set.unwrap_or_default();
         let borders = self.borders.unwrap_or_default();
         let bg = self.bg.unwrap_or(tailwind::BLUE.c400);
@@ -126,6 +139,13 @@ impl PopupInput {
     pub fn get_input(&self) -> String {
         self.textarea.lines().join("\n")
     }
+
+    /// Replace the input text, e.g. to edit an existing value.
+    pub fn set_input(&mut self, text: &str) {
+        self.textarea.select_all();
+        self.textarea.cut();
+        self.textarea.insert_str(text);
+    }
 }
 
 impl Widget for &mut PopupInput {
@@ -7,14 +7,15 @@ use std::{
 };
 
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
-use futures_util::FutureExt as _;
+use futures_util::{FutureExt as _, StreamExt as _};
 use layout::Flex;
 use matrix_sdk::{
     Client,
     encryption::{
         backups::BackupState,
-        recovery::{RecoveryError, RecoveryState},
+        recovery::{EnableProgress, RecoveryError, RecoveryState},
     },
+    locks::Mutex,
 };
 use matrix_sdk_common::executor::spawn;
 use ratatui::{
@@ -23,6 +24,7 @@ use ratatui::{
 };
 use throbber_widgets_tui::{Throbber, ThrobberState};
 use tokio::task::JoinHandle;
+use tui_textarea::TextArea;
 
 use super::{ShouldExit, create_centered_throbber_area};
 
@@ -52,8 +54,15 @@ impl Drop for BackupInfo {
 enum Mode {
     #[default]
     Default,
+    /// Waiting for the user to choose the passphrase which can be used
+    /// instead of the recovery key, if any.
+    ChoosingPassphrase {
+        text_area: TextArea<'static>,
+    },
     Enabling {
         enable_task: JoinHandle<Result<String, RecoveryError>>,
+        /// How far the enabling task went, updated by the task itself.
+        progress: Arc<Mutex<EnableProgress>>,
         throbber_state: ThrobberState,
     },
     Disabling {
@@ -120,9 +129,16 @@ impl DefaultRecoveryView {
         let client = self.client.clone();
 
         if matches!(self.recovery_state, RecoveryState::Disabled) {
-            let enable_task = spawn(async move { client.encryption().recovery().enable().await });
+            let mut text_area = TextArea::default();
+
+            text_area.set_cursor_line_style(Style::default());
+            text_area.set_mask_char('\u{2022}'); //U+2022 BULLET (•)
+            text_area.set_placeholder_text("Leave empty to only use the recovery key");
+
+            text_area.set_style(Style::default().fg(Color::LightGreen));
+            text_area.set_block(Block::default());
 
-            self.mode = Mode::Enabling { enable_task, throbber_state: ThrobberState::default() };
+            self.mode = Mode::ChoosingPassphrase { text_area };
         } else {
             let disable_task = spawn(async move {
                 // TODO: Handle errors here?
@@ -134,6 +150,43 @@ impl DefaultRecoveryView {
         }
     }
 
+    /// Enable recovery in the background, with the passphrase if it isn't
+    /// empty, and wait for all the room keys to be backed up.
+    fn enable_recovery(&mut self, passphrase: String) {
+        let client = self.client.clone();
+        let progress = Arc::new(Mutex::new(EnableProgress::Starting));
+
+        let enable_task = spawn({
+            let progress = progress.clone();
+
+            async move {
+                let recovery = client.encryption().recovery();
+                let mut enable = recovery.enable().wait_for_backups_to_upload();
+
+                if !passphrase.is_empty() {
+                    enable = enable.with_passphrase(&passphrase);
+                }
+
+                let mut updates = enable.subscribe_to_progress();
+                let progress_task = spawn(async move {
+                    while let Some(update) = updates.next().await {
+                        if let Ok(update) = update {
+                            *progress.lock() = update;
+                        }
+                    }
+                });
+
+                let result = enable.await;
+                progress_task.abort();
+
+                result
+            }
+        });
+
+        self.mode =
+            Mode::Enabling { enable_task, progress, throbber_state: ThrobberState::default() };
+    }
+
     async fn handle_backup_action(&mut self) {
         let backup_state = self.backup_info.backup_state;
         let backup_exists = self.backup_info.backup_exists.load(Ordering::SeqCst);
@@ -161,7 +214,7 @@ impl DefaultRecoveryView {
             return No;
         }
 
-        match self.mode {
+        match &mut self.mode {
             Mode::Default => match key.code {
                 KeyCode::Esc | KeyCode::Char('q') => Yes,
                 KeyCode::Char('j') | KeyCode::Down => {
@@ -184,6 +237,21 @@ impl DefaultRecoveryView {
                 }
                 _ => No,
             },
+            Mode::ChoosingPassphrase { text_area } => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::Default;
+                    No
+                }
+                KeyCode::Enter => {
+                    let passphrase = text_area.lines().join("");
+                    self.enable_recovery(passphrase);
+                    No
+                }
+                _ => {
+                    text_area.input(key);
+                    No
+                }
+            },
             Mode::Enabling { .. } | Mode::Disabling { .. } => No,
             Mode::Done { .. } => {
                 self.mode = Mode::Default;
@@ -199,13 +267,13 @@ impl DefaultRecoveryView {
             Enabling { throbber_state, .. } | Disabling { throbber_state, .. } => {
                 throbber_state.calc_next()
             }
-            Default | Done { .. } => {}
+            Default | ChoosingPassphrase { .. } | Done { .. } => {}
         }
     }
 
     pub fn is_idle(&self) -> bool {
         match self.mode {
-            Mode::Default => true,
+            Mode::Default | Mode::ChoosingPassphrase { .. } => true,
             Mode::Enabling { .. } | Mode::Disabling { .. } | Mode::Done { .. } => false,
         }
     }
@@ -220,7 +288,7 @@ impl DefaultRecoveryView {
         self.backup_info.backup_state = backup_state;
 
         match &mut self.mode {
-            Default => {}
+            Default | ChoosingPassphrase { .. } => {}
             // Check if the task enabling recovery is done, if so, let's go into the `Done` mode.
             Enabling { enable_task, .. } => {
                 if enable_task.is_finished() {
@@ -256,9 +324,10 @@ impl Widget for &mut DefaultRecoveryView {
 
         let style = match &self.mode {
             Mode::Default => Style::default(),
-            Mode::Enabling { .. } | Mode::Done { .. } | Mode::Disabling { .. } => {
-                Style::default().dim()
-            }
+            Mode::ChoosingPassphrase { .. }
+            | Mode::Enabling { .. }
+            | Mode::Done { .. }
+            | Mode::Disabling { .. } => Style::default().dim(),
         };
 
         let recovery_item = match self.recovery_state {
@@ -294,16 +363,48 @@ impl Widget for &mut DefaultRecoveryView {
             .highlight_symbol("> ")
             .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
 
-        StatefulWidget::render(list, area, buf, &mut self.state);
+        let [list_area, _, details_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Length(1), Constraint::Min(0)])
+                .areas(area);
+
+        StatefulWidget::render(list, list_area, buf, &mut self.state);
 
         match &mut self.mode {
-            Mode::Default => {}
-            Mode::Enabling { throbber_state, .. } => {
+            Mode::Default => {
+                Paragraph::new(describe_backup(backup_state, backup_exists))
+                    .dim()
+                    .render(details_area, buf);
+            }
+            Mode::ChoosingPassphrase { text_area } => {
+                let [hint_area, input_area] =
+                    Layout::vertical([Constraint::Length(2), Constraint::Length(1)])
+                        .areas(details_area);
+                let [left, right] =
+                    Layout::horizontal([Constraint::Length(14), Constraint::Length(50)])
+                        .areas(input_area);
+
+                Paragraph::new(
+                    "Choose a passphrase to recover with, besides the recovery key, and press \
+                     Enter.",
+                )
+                .render(hint_area, buf);
+                Paragraph::new("Passphrase:").render(left, buf);
+                text_area.render(right, buf);
+            }
+            Mode::Enabling { progress, throbber_state, .. } => {
                 let throbber = Throbber::default()
                     .label("Enabling recovery")
                     .throbber_set(throbber_widgets_tui::BRAILLE_EIGHT_DOUBLE);
                 let centered_area = create_centered_throbber_area(area);
                 StatefulWidget::render(throbber, centered_area, buf, throbber_state);
+
+                // Tell what's being done below the throbber.
+                let progress_area =
+                    Rect { x: area.x, y: centered_area.y + 2, width: area.width, height: 1 }
+                        .intersection(area);
+                Paragraph::new(describe_progress(&progress.lock()))
+                    .centered()
+                    .render(progress_area, buf);
             }
             Mode::Disabling { throbber_state, .. } => {
                 let throbber = Throbber::default()
@@ -346,3 +447,35 @@ impl Widget for &mut DefaultRecoveryView {
         }
     }
 }
+
+/// What the task enabling recovery is doing.
+fn describe_progress(progress: &EnableProgress) -> String {
+    match progress {
+        EnableProgress::Starting => "Starting".to_owned(),
+        EnableProgress::CreatingBackup => "Creating the key backup".to_owned(),
+        EnableProgress::CreatingRecoveryKey => "Creating the recovery key".to_owned(),
+        EnableProgress::BackingUp(counts) => {
+            format!("Backing up the room keys: {}/{}", counts.backed_up, counts.total)
+        }
+        EnableProgress::RoomKeyUploadError => {
+            "Some room keys couldn't be backed up, they will be later".to_owned()
+        }
+        EnableProgress::Done { .. } => "Done".to_owned(),
+    }
+}
+
+/// A sentence about the state of the key backup, shown below the menu.
+fn describe_backup(backup_state: BackupState, backup_exists: bool) -> &'static str {
+    match (backup_state, backup_exists) {
+        (BackupState::Unknown, true) => {
+            "A key backup exists on the server, enter the recovery key to use it."
+        }
+        (BackupState::Unknown, false) => "There's no key backup on the server.",
+        (BackupState::Creating, _) => "The key backup is being created.",
+        (BackupState::Enabling, _) => "The key backup is being enabled.",
+        (BackupState::Resuming, _) => "The key backup is being resumed.",
+        (BackupState::Enabled, _) => "The room keys are backed up on the server.",
+        (BackupState::Downloading, _) => "The room keys are being downloaded from the backup.",
+        (BackupState::Disabling, _) => "The key backup is being disabled.",
+    }
+}
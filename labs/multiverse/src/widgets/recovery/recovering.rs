@@ -1,15 +1,18 @@
+use std::{pin::pin, sync::Arc};
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use futures_util::FutureExt as _;
+use futures_util::{FutureExt as _, StreamExt as _};
 use matrix_sdk::{
     Client,
     encryption::{CrossSigningResetAuthType, recovery::RecoveryError},
+    locks::Mutex,
     reqwest::Url,
     ruma::api::client::uiaa::{AuthData, Password},
 };
 use matrix_sdk_common::executor::spawn;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Paragraph},
+    widgets::{Block, Gauge, Paragraph},
 };
 use throbber_widgets_tui::{Throbber, ThrobberState};
 use tokio::{
@@ -39,6 +42,17 @@ enum ResetMessage {
     MatrixAuth { password_sender: UnboundedSender<String> },
 }
 
+/// How far the download of the room keys from the backup went.
+#[derive(Debug, Default)]
+struct DownloadProgress {
+    rooms_done: usize,
+    rooms_total: usize,
+    /// The number of room keys which have been imported.
+    keys: usize,
+    /// The number of rooms whose keys couldn't be downloaded.
+    failures: usize,
+}
+
 #[derive(Debug)]
 pub struct RecoveringView {
     client: Client,
@@ -62,6 +76,14 @@ enum Mode {
     Done {
         result: Result<(), RecoveryError>,
     },
+    /// Downloading the room keys of all the joined rooms from the backup, to
+    /// decrypt their history right away; the mode is kept once the task is
+    /// finished, to show how many keys have been imported.
+    Downloading {
+        download_task: JoinHandle<()>,
+        progress: Arc<Mutex<DownloadProgress>>,
+        throbber_state: ThrobberState,
+    },
 }
 
 impl RecoveringView {
@@ -123,7 +145,7 @@ impl RecoveringView {
                     }
                 }
             },
-            Inputting { .. } | Done { .. } => {}
+            Inputting { .. } | Done { .. } | Downloading { .. } => {}
         }
     }
 
@@ -133,13 +155,17 @@ impl RecoveringView {
         match &mut self.mode {
             Recovering { throbber_state, .. } => throbber_state.calc_next(),
             Resetting { throbber_state, .. } => throbber_state.calc_next(),
+            Downloading { throbber_state, .. } => throbber_state.calc_next(),
             Inputting { .. } | Done { .. } => {}
         }
     }
 
     pub fn is_idle(&self) -> bool {
         match self.mode {
-            Mode::Recovering { .. } | Mode::Resetting { .. } | Mode::Done { .. } => false,
+            Mode::Recovering { .. }
+            | Mode::Resetting { .. }
+            | Mode::Done { .. }
+            | Mode::Downloading { .. } => false,
             Mode::Inputting { .. } => true,
         }
     }
@@ -194,6 +220,45 @@ impl RecoveringView {
             Mode::Resetting { reset_state, reset_task, throbber_state: ThrobberState::default() };
     }
 
+    /// Download the room keys of the joined rooms from the backup, one room
+    /// after the other.
+    fn download_room_keys(&mut self) {
+        let client = self.client.clone();
+        let progress = Arc::new(Mutex::new(DownloadProgress::default()));
+
+        let download_task = spawn({
+            let progress = progress.clone();
+
+            async move {
+                let backups = client.encryption().backups();
+                let rooms = client.joined_rooms();
+                progress.lock().rooms_total = rooms.len();
+
+                for room in rooms {
+                    let mut imported_keys = pin!(backups.room_keys_for_room_stream(room.room_id()));
+                    let result = backups.download_room_keys_for_room(room.room_id()).await;
+
+                    // The keys are announced on the stream as soon as they're imported, so
+                    // they're all there once the download is done.
+                    let mut keys = 0;
+                    while let Some(Some(Ok(imported))) = imported_keys.next().now_or_never() {
+                        keys += imported.values().map(|sessions| sessions.len()).sum::<usize>();
+                    }
+
+                    let mut progress = progress.lock();
+                    progress.rooms_done += 1;
+                    progress.keys += keys;
+                    if result.is_err() {
+                        progress.failures += 1;
+                    }
+                }
+            }
+        });
+
+        self.mode =
+            Mode::Downloading { download_task, progress, throbber_state: ThrobberState::default() };
+    }
+
     pub fn handle_key(&mut self, key: KeyEvent) -> ShouldExit {
         use KeyCode::*;
         use Mode::*;
@@ -262,7 +327,29 @@ impl RecoveringView {
                     }
                 }
             }
+            Done { result: Ok(()) } if key.code == Char('d') => {
+                self.download_room_keys();
+                No
+            }
             Done { .. } => OnlySubScreen,
+            Downloading { download_task, .. } => {
+                if download_task.is_finished() {
+                    OnlySubScreen
+                } else if key.code == Esc {
+                    download_task.abort();
+                    OnlySubScreen
+                } else {
+                    No
+                }
+            }
+        }
+    }
+}
+
+impl Drop for RecoveringView {
+    fn drop(&mut self) {
+        if let Mode::Downloading { download_task, .. } = &self.mode {
+            download_task.abort();
         }
     }
 }
@@ -362,9 +449,12 @@ impl Widget for &mut RecoveringView {
 
                 match result {
                     Ok(_) => {
-                        Paragraph::new("Done recovering\n\nPress any key to continue")
-                            .centered()
-                            .render(middle, buf);
+                        Paragraph::new(
+                            "Done recovering\n\nPress d to download the room keys from the \
+                             backup, or any other key to continue",
+                        )
+                        .centered()
+                        .render(middle, buf);
                     }
                     Err(error) => {
                         Paragraph::new(format!(
@@ -375,6 +465,58 @@ impl Widget for &mut RecoveringView {
                     }
                 }
             }
+
+            Downloading { download_task, progress, throbber_state } => {
+                let progress = progress.lock();
+
+                if download_task.is_finished() {
+                    let constraints =
+                        [Constraint::Fill(1), Constraint::Min(3), Constraint::Fill(1)];
+                    let [_top, middle, _bottom] = Layout::vertical(constraints).areas(area);
+
+                    let failures = if progress.failures > 0 {
+                        format!(", {} rooms failed", progress.failures)
+                    } else {
+                        String::new()
+                    };
+
+                    Paragraph::new(format!(
+                        "Downloaded {} room keys for {} rooms{failures}\n\nPress any key to \
+                         continue",
+                        progress.keys, progress.rooms_done
+                    ))
+                    .centered()
+                    .render(middle, buf);
+                } else {
+                    let throbber = Throbber::default()
+                        .label("Downloading the room keys")
+                        .throbber_set(throbber_widgets_tui::BRAILLE_EIGHT_DOUBLE);
+                    let centered_area = create_centered_throbber_area(area);
+                    StatefulWidget::render(throbber, centered_area, buf, throbber_state);
+
+                    // Show how many rooms are done below the throbber.
+                    let gauge_area = Rect {
+                        x: area.x + area.width / 4,
+                        y: centered_area.y + 2,
+                        width: area.width / 2,
+                        height: 1,
+                    }
+                    .intersection(area);
+                    let ratio = if progress.rooms_total == 0 {
+                        0.0
+                    } else {
+                        progress.rooms_done as f64 / progress.rooms_total as f64
+                    };
+
+                    Gauge::default()
+                        .ratio(ratio)
+                        .label(format!(
+                            "{}/{} rooms, {} keys",
+                            progress.rooms_done, progress.rooms_total, progress.keys
+                        ))
+                        .render(gauge_area, buf);
+                }
+            }
         }
     }
 }
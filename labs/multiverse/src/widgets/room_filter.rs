@@ -0,0 +1,34 @@
+use crossterm::event::KeyEvent;
+use ratatui::prelude::*;
+
+use crate::widgets::popup_input::{PopupInput, PopupInputBuilder};
+
+/// A popup to filter the room list by room name.
+pub struct RoomFilterView {
+    input: PopupInput,
+}
+
+impl RoomFilterView {
+    pub fn new() -> Self {
+        Self {
+            input: PopupInputBuilder::new("Filter rooms", "(Enter part of a room name)")
+                .width_constraint(Constraint::Percentage(90))
+                .build(),
+        }
+    }
+
+    /// The pattern the room names must match.
+    pub fn get_text(&self) -> String {
+        self.input.get_input()
+    }
+
+    pub fn handle_key_press(&mut self, key: KeyEvent) {
+        self.input.handle_key_press(key);
+    }
+}
+
+impl Widget for &mut RoomFilterView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.input.render(area, buf);
+    }
+}
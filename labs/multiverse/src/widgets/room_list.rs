@@ -1,14 +1,30 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, OnceLock},
+};
 
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 use imbl::Vector;
-use matrix_sdk::{Client, Room, locks::Mutex, ruma::OwnedRoomId};
-use matrix_sdk_ui::sync_service::SyncService;
+use matrix_sdk::{
+    Client, Room,
+    locks::Mutex,
+    notification_settings::RoomNotificationMode,
+    ruma::{OwnedRoomId, RoomId},
+};
+use matrix_sdk_ui::{
+    room_list_service::{
+        LatestEventPreviewContent, RoomListDynamicEntriesController, RoomListItem,
+        filters::{
+            BoxedFilterFn, new_filter_all, new_filter_fuzzy_match_room_name, new_filter_non_left,
+        },
+    },
+    spaces::{SpaceService, room_list::SpaceRoomListPaginationState},
+    sync_service::SyncService,
+};
 use ratatui::{prelude::*, widgets::*};
+use tokio::{spawn, task::JoinHandle};
 
-use crate::{
-    ALT_ROW_COLOR, HEADER_BG, NORMAL_ROW_COLOR, SELECTED_STYLE_FG, TEXT_COLOR,
-    widgets::status::StatusHandle,
-};
+use crate::{config::theme, widgets::status::StatusHandle};
 
 /// Extra room information, like its display name, etc.
 #[derive(Clone)]
@@ -21,11 +37,33 @@ pub struct ExtraRoomInfo {
 
     /// Is the room a DM?
     pub is_dm: Option<bool>,
+
+    /// The number of unread messages, according to the read receipts.
+    pub num_unread: u64,
+
+    /// The number of unread mentions of the current user.
+    pub num_mentions: u64,
+
+    /// The notification mode of the room, or `None` if it isn't joined.
+    pub notification_mode: Option<RoomNotificationMode>,
 }
 
 pub type Rooms = Arc<Mutex<Vector<matrix_sdk_ui::room_list_service::RoomListItem>>>;
 pub type RoomInfos = Arc<Mutex<HashMap<OwnedRoomId, ExtraRoomInfo>>>;
 
+/// The controller of the room list entries, set by the task listening to the
+/// room list once it has subscribed to it.
+pub type EntriesController = Arc<OnceLock<RoomListDynamicEntriesController>>;
+
+/// A space the room list is restricted to.
+struct SpaceFilter {
+    id: OwnedRoomId,
+    name: String,
+
+    /// The rooms belonging to the space, according to its hierarchy.
+    room_ids: Arc<HashSet<OwnedRoomId>>,
+}
+
 pub struct RoomList {
     pub state: ListState,
 
@@ -43,6 +81,29 @@ pub struct RoomList {
 
     /// The sync service used for synchronizing events.
     sync_service: Arc<SyncService>,
+
+    /// The controller used to filter the room list.
+    entries_controller: EntriesController,
+
+    /// The pattern the names of the rooms are filtered with.
+    name_filter: String,
+
+    /// The area of the list in the last frame, to find the room under the
+    /// mouse.
+    area: Rect,
+
+    /// The service used to find the joined spaces and their rooms.
+    space_service: Arc<SpaceService>,
+
+    /// The space the rooms are filtered with, or `None` to show all the rooms.
+    space_filter: Option<SpaceFilter>,
+
+    /// The space loaded by the `space_task`, to be applied on the next tick;
+    /// `Some(None)` switches back to all the rooms.
+    loaded_space_filter: Arc<Mutex<Option<Option<SpaceFilter>>>>,
+
+    /// Task loading the rooms of the next space.
+    space_task: Option<JoinHandle<()>>,
 }
 
 impl RoomList {
@@ -52,9 +113,11 @@ impl RoomList {
 
         room_infos: RoomInfos,
         sync_service: Arc<SyncService>,
+        entries_controller: EntriesController,
         status_handle: StatusHandle,
     ) -> Self {
         Self {
+            space_service: Arc::new(SpaceService::new(client.clone())),
             client,
             state: Default::default(),
             rooms,
@@ -62,6 +125,145 @@ impl RoomList {
             room_infos,
             current_room_subscription: None,
             sync_service,
+            entries_controller,
+            name_filter: String::new(),
+            area: Rect::default(),
+            space_filter: None,
+            loaded_space_filter: Default::default(),
+            space_task: None,
+        }
+    }
+
+    /// Restrict the room list to the rooms of the next joined space, after the
+    /// current one, or show all the rooms again after the last space.
+    ///
+    /// The rooms of the space are loaded from its hierarchy in the background,
+    /// and the filter is applied by [`Self::on_tick`] once they're known.
+    pub fn switch_to_next_space(&mut self) {
+        if let Some(task) = self.space_task.take() {
+            task.abort();
+        }
+
+        let space_service = self.space_service.clone();
+        let current_space_id = self.space_filter.as_ref().map(|space| space.id.clone());
+        let loaded_space_filter = self.loaded_space_filter.clone();
+        let status_handle = self.status_handle.clone();
+
+        self.space_task = Some(spawn(async move {
+            let spaces = space_service.joined_spaces().await;
+
+            if spaces.is_empty() {
+                status_handle.set_message("no joined spaces".to_owned());
+                return;
+            }
+
+            // Start from the first space when all the rooms are shown, or when the
+            // current space has been left.
+            let next_space = match current_space_id {
+                Some(current) => spaces
+                    .iter()
+                    .position(|space| space.room_id == current)
+                    .map_or(spaces.first(), |index| spaces.get(index + 1)),
+                None => spaces.first(),
+            };
+
+            let Some(space) = next_space else {
+                *loaded_space_filter.lock() = Some(None);
+                return;
+            };
+
+            let name = space.name.clone().unwrap_or_else(|| space.room_id.to_string());
+            status_handle.set_message(format!("loading the rooms of {name}…"));
+
+            let space_room_list = space_service.space_room_list(space.room_id.clone()).await;
+
+            while !matches!(
+                space_room_list.pagination_state(),
+                SpaceRoomListPaginationState::Idle { end_reached: true }
+            ) {
+                if let Err(err) = space_room_list.paginate().await {
+                    status_handle.set_error(format!("couldn't load the rooms of {name}: {err}"));
+                    return;
+                }
+            }
+
+            let room_ids = space_room_list.rooms().into_iter().map(|room| room.room_id).collect();
+
+            *loaded_space_filter.lock() = Some(Some(SpaceFilter {
+                id: space.room_id.clone(),
+                name,
+                room_ids: Arc::new(room_ids),
+            }));
+        }));
+    }
+
+    /// Apply the space filter loaded in the background, if any.
+    pub fn on_tick(&mut self) {
+        let Some(space_filter) = self.loaded_space_filter.lock().take() else {
+            return;
+        };
+
+        self.space_filter = space_filter;
+        self.refresh_filter();
+    }
+
+    /// Only show the non-left rooms whose name fuzzy-matches the given
+    /// pattern, or all the non-left rooms if the pattern is empty.
+    ///
+    /// The selection is moved to the first match.
+    pub fn set_name_filter(&mut self, pattern: &str) {
+        self.name_filter = pattern.to_owned();
+
+        if self.apply_filter() {
+            // The filtered entries will be received later, but the first match will
+            // always be the first entry.
+            self.state.select(Some(0));
+        }
+    }
+
+    /// Filter the rooms again, e.g. to remove a room we've just left, whose
+    /// entry would only be updated by the next sync otherwise.
+    ///
+    /// The selection is cleared, since the selected room may have been
+    /// removed.
+    pub fn refresh_filter(&mut self) {
+        if self.apply_filter() {
+            self.state.select(None);
+            self.current_room_subscription.take();
+        }
+    }
+
+    /// Set the filter of the room list, and return whether it has been set.
+    fn apply_filter(&self) -> bool {
+        let Some(entries_controller) = self.entries_controller.get() else {
+            self.status_handle.set_message("the room list isn't ready yet".to_owned());
+            return false;
+        };
+
+        let mut filters: Vec<BoxedFilterFn> = vec![Box::new(new_filter_non_left())];
+
+        if !self.name_filter.is_empty() {
+            filters.push(Box::new(new_filter_fuzzy_match_room_name(&self.name_filter)));
+        }
+
+        if let Some(space) = &self.space_filter {
+            let room_ids = space.room_ids.clone();
+            filters.push(Box::new(move |room: &RoomListItem| room_ids.contains(room.room_id())));
+        }
+
+        entries_controller.set_filter(Box::new(new_filter_all(filters)));
+
+        true
+    }
+
+    /// Subscribe to the room which is currently selected, e.g. after the
+    /// selection has been moved by a filter.
+    pub async fn subscribe_to_selected_room(&mut self) {
+        let num_items = self.rooms.lock().len();
+
+        match self.state.selected() {
+            Some(index) if index < num_items => self.subscribe_to_room(index).await,
+            _ => self.state.select(None),
         }
     }
 
@@ -109,6 +311,52 @@ impl RoomList {
         }
     }
 
+    /// Select the room with the given ID, if it's in the list.
+    ///
+    /// Returns whether the room has been found.
+    pub async fn select_room(&mut self, room_id: &RoomId) -> bool {
+        let index = self.rooms.lock().iter().position(|room| room.room_id() == room_id);
+
+        let Some(index) = index else {
+            return false;
+        };
+
+        self.state.select(Some(index));
+        self.subscribe_to_room(index).await;
+
+        true
+    }
+
+    /// Is the given position of the screen in the list?
+    pub fn contains(&self, column: u16, row: u16) -> bool {
+        self.area.contains(Position::new(column, row))
+    }
+
+    /// Select the clicked room, or the next or previous room with the scroll
+    /// wheel.
+    ///
+    /// Returns whether the selected room has changed.
+    pub async fn handle_mouse_event(&mut self, event: MouseEvent) -> bool {
+        let previous = self.state.selected();
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) if self.contains(event.column, event.row) => {
+                // Each room takes a single line.
+                let index = self.state.offset() + usize::from(event.row - self.area.y);
+
+                if index < self.rooms.lock().len() && previous != Some(index) {
+                    self.state.select(Some(index));
+                    self.subscribe_to_room(index).await;
+                }
+            }
+            MouseEventKind::ScrollDown => self.next_room().await,
+            MouseEventKind::ScrollUp => self.previous_room().await,
+            _ => {}
+        }
+
+        self.state.selected() != previous
+    }
+
     /// Returns the [`OwnedRoomId`] of the `nth` room within the [`RoomList`].
     pub fn get_room_id_of_entry(&self, nth: usize) -> Option<OwnedRoomId> {
         self.rooms.lock().get(nth).cloned().map(|room| room.room_id().to_owned())
@@ -120,6 +368,53 @@ impl RoomList {
         self.get_room_id_of_entry(selected)
     }
 
+    /// Switch the selected room to the next notification mode, from all the
+    /// messages to the mentions only, then to mute, and back.
+    ///
+    /// The mode is set in the background; the room list shows the new one
+    /// once it's been saved in the push rules.
+    pub fn cycle_notification_mode(&self) {
+        let Some(room_id) = self.get_selected_room_id() else {
+            self.status_handle.set_message("no room selected".to_owned());
+            return;
+        };
+
+        let current_mode =
+            self.room_infos.lock().get(&room_id).and_then(|info| info.notification_mode);
+
+        let next_mode = match current_mode {
+            Some(RoomNotificationMode::AllMessages) | None => {
+                RoomNotificationMode::MentionsAndKeywordsOnly
+            }
+            Some(RoomNotificationMode::MentionsAndKeywordsOnly) => RoomNotificationMode::Mute,
+            Some(RoomNotificationMode::Mute) => RoomNotificationMode::AllMessages,
+        };
+
+        let client = self.client.clone();
+        let room_infos = self.room_infos.clone();
+        let status_handle = self.status_handle.clone();
+
+        spawn(async move {
+            let settings = client.notification_settings().await;
+
+            match settings.set_room_notification_mode(&room_id, next_mode).await {
+                Ok(()) => {
+                    if let Some(info) = room_infos.lock().get_mut(&room_id) {
+                        info.notification_mode = Some(next_mode);
+                    }
+
+                    status_handle.set_message(format!(
+                        "notifications: {}",
+                        describe_notification_mode(next_mode)
+                    ));
+                }
+                Err(err) => {
+                    status_handle.set_error(format!("couldn't change the notification mode: {err}"))
+                }
+            }
+        });
+    }
+
     /// Subscribe to room that is shown at the given `index`.
     async fn subscribe_to_room(&mut self, index: usize) {
         // Cancel the subscription to the previous room, if any.
@@ -142,15 +437,20 @@ impl Widget for &mut RoomList {
     {
         // We create two blocks, one is for the header (outer) and the other is for list
         // (inner).
+        let title = match &self.space_filter {
+            Some(space) => format!("Room list — {}", space.name),
+            None => "Room list — All rooms".to_owned(),
+        };
+
         let outer_block = Block::default()
             .borders(Borders::RIGHT)
             .border_set(symbols::border::THICK)
-            .fg(TEXT_COLOR)
-            .bg(HEADER_BG)
-            .title("Room list")
+            .fg(theme().text)
+            .bg(theme().header_bg)
+            .title(title)
             .title_alignment(Alignment::Center);
         let inner_block =
-            Block::default().borders(Borders::NONE).fg(TEXT_COLOR).bg(NORMAL_ROW_COLOR);
+            Block::default().borders(Borders::NONE).fg(theme().text).bg(theme().normal_row);
 
         // We get the inner area from outer_block. We'll use this area later to render
         // the table.
@@ -159,6 +459,7 @@ impl Widget for &mut RoomList {
 
         // We can render the header in outer_area.
         outer_block.render(outer_area, buf);
+        self.area = inner_area;
 
         // Don't keep this lock too long by cloning the content. RAM's free these days,
         // right?
@@ -172,22 +473,37 @@ impl Widget for &mut RoomList {
             .enumerate()
             .map(|(i, room)| {
                 let bg_color = match i % 2 {
-                    0 => NORMAL_ROW_COLOR,
-                    _ => ALT_ROW_COLOR,
+                    0 => theme().normal_row,
+                    _ => theme().alt_row,
                 };
 
-                let line = {
+                let (line, color) = {
                     let room_id = room.room_id();
                     let room_info = room_info.remove(room_id);
 
-                    let (raw, display, is_dm) = if let Some(info) = room_info {
-                        (info.raw_name, info.display_name, info.is_dm)
-                    } else {
-                        (None, None, None)
-                    };
+                    let (raw, display, is_dm, num_unread, num_mentions, notification_mode) =
+                        if let Some(info) = room_info {
+                            (
+                                info.raw_name,
+                                info.display_name,
+                                info.is_dm,
+                                info.num_unread,
+                                info.num_mentions,
+                                info.notification_mode,
+                            )
+                        } else {
+                            (None, None, None, 0, 0, None)
+                        };
 
                     let dm_marker = if is_dm.unwrap_or(false) { "🤫" } else { "" };
 
+                    // The default mode, notifying all the messages, isn't shown.
+                    let notification_marker = match notification_mode {
+                        Some(RoomNotificationMode::MentionsAndKeywordsOnly) => " @",
+                        Some(RoomNotificationMode::Mute) => " 🔇",
+                        Some(RoomNotificationMode::AllMessages) | None => "",
+                    };
+
                     let room_name = if let Some(n) = display {
                         format!("{n} ({room_id})")
                     } else if let Some(n) = raw {
@@ -196,10 +512,41 @@ impl Widget for &mut RoomList {
                         room_id.to_string()
                     };
 
-                    format!("#{i}{dm_marker} {room_name}")
+                    let badge = match (num_unread, num_mentions) {
+                        (0, 0) => String::new(),
+                        (unread, 0) => format!(" [{unread}]"),
+                        (unread, mentions) => format!(" [{unread}, @{mentions}]"),
+                    };
+
+                    let preview = match room.latest_event_preview() {
+                        Some(preview) => {
+                            let sender = preview
+                                .sender_display_name()
+                                .map(ToOwned::to_owned)
+                                .unwrap_or_else(|| preview.sender().to_string());
+
+                            match preview.content() {
+                                LatestEventPreviewContent::Text(body) => {
+                                    format!(" — {sender}: {body}")
+                                }
+                                LatestEventPreviewContent::UnableToDecrypt => {
+                                    format!(" — {sender}: 🔒")
+                                }
+                            }
+                        }
+                        None => String::new(),
+                    };
+
+                    // Rooms where we've been mentioned stand out.
+                    let color = if num_mentions > 0 { theme().mention } else { theme().text };
+
+                    (
+                        format!("#{i}{dm_marker} {room_name}{badge}{notification_marker}{preview}"),
+                        color,
+                    )
                 };
 
-                let line = Line::styled(line, TEXT_COLOR);
+                let line = Line::styled(line, color);
                 ListItem::new(line).bg(bg_color)
             })
             .collect();
@@ -211,7 +558,7 @@ impl Widget for &mut RoomList {
                 Style::default()
                     .add_modifier(Modifier::BOLD)
                     .add_modifier(Modifier::REVERSED)
-                    .fg(SELECTED_STYLE_FG),
+                    .fg(theme().selected_fg),
             )
             .highlight_symbol(">")
             .highlight_spacing(HighlightSpacing::Always);
@@ -219,3 +566,12 @@ impl Widget for &mut RoomList {
         StatefulWidget::render(items, inner_area, buf, &mut self.state);
     }
 }
+
+/// The notification mode, as shown in the status messages.
+fn describe_notification_mode(mode: RoomNotificationMode) -> &'static str {
+    match mode {
+        RoomNotificationMode::AllMessages => "all messages",
+        RoomNotificationMode::MentionsAndKeywordsOnly => "mentions and keywords only",
+        RoomNotificationMode::Mute => "muted",
+    }
+}
@@ -0,0 +1,382 @@
+use std::{
+    fs,
+    io::{self, Cursor},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use image::{ImageFormat, ImageReader};
+use matrix_sdk::{
+    attachment::{AttachmentInfo, BaseAudioInfo, BaseFileInfo, BaseImageInfo, Thumbnail},
+    ruma::UInt,
+};
+use matrix_sdk_ui::{
+    Timeline,
+    timeline::{AttachmentConfig, AttachmentSource},
+};
+use mime_guess::{Mime, mime};
+use tokio::spawn;
+use tracing::warn;
+
+use crate::widgets::status::StatusHandle;
+
+/// The maximum width of the thumbnails generated for the images.
+const THUMBNAIL_MAX_WIDTH: u32 = 800;
+
+/// The maximum height of the thumbnails generated for the images.
+const THUMBNAIL_MAX_HEIGHT: u32 = 600;
+
+/// Upload the file at the given path to the room of the timeline, showing the
+/// progress of the upload in the status bar.
+///
+/// Images are sent with their dimensions and a thumbnail, which is generated
+/// from the PNG, GIF and JPEG images larger than the thumbnail size, unless
+/// another thumbnail is given.
+pub fn send_attachment(
+    timeline: Arc<Timeline>,
+    path: &Path,
+    thumbnail_path: Option<&Path>,
+    status_handle: StatusHandle,
+) {
+    let (source, mime_type, config) = match load_attachment(path, thumbnail_path) {
+        Ok(attachment) => attachment,
+        Err(err) => {
+            status_handle.set_error(format!("error when reading the attachment: {err}"));
+            return;
+        }
+    };
+
+    upload(timeline, source, mime_type, config, status_handle);
+}
+
+/// Upload the audio file at the given path as a voice message, with its
+/// duration and waveform.
+///
+/// They're read from the file for uncompressed WAV files; only the duration
+/// can be found for Ogg files, and it must be given for the other formats.
+pub fn send_voice_message(
+    timeline: Arc<Timeline>,
+    path: &Path,
+    duration: Option<Duration>,
+    status_handle: StatusHandle,
+) {
+    let (source, mime_type, config) = match load_voice_message(path, duration) {
+        Ok(voice_message) => voice_message,
+        Err(err) => {
+            status_handle.set_error(format!("error when reading the voice message: {err}"));
+            return;
+        }
+    };
+
+    upload(timeline, source, mime_type, config, status_handle);
+}
+
+/// Send the attachment in the background, showing the progress of the upload
+/// in the status bar.
+fn upload(
+    timeline: Arc<Timeline>,
+    source: AttachmentSource,
+    mime_type: Mime,
+    config: AttachmentConfig,
+    status_handle: StatusHandle,
+) {
+    spawn(async move {
+        let send = timeline.send_attachment(source, mime_type, config);
+
+        let mut progress = send.subscribe_to_send_progress();
+        let s = status_handle.clone();
+        let progress_task = spawn(async move {
+            while let Some(progress) = progress.next().await {
+                s.set_message(format!("uploading: {}/{} bytes", progress.current, progress.total));
+            }
+        });
+
+        let result = send.await;
+        progress_task.abort();
+
+        match result {
+            Ok(_) => status_handle.set_message("attachment sent!".to_owned()),
+            Err(err) => status_handle.set_error(format!("error when sending attachment: {err}")),
+        }
+    });
+}
+
+/// Read the attachment at the given path, and guess its type and metadata.
+fn load_attachment(
+    path: &Path,
+    thumbnail_path: Option<&Path>,
+) -> io::Result<(AttachmentSource, Mime, AttachmentConfig)> {
+    let filename = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))?
+        .to_owned();
+    let bytes = fs::read(path)?;
+    let mime_type = mime_guess::from_path(path).first_or_octet_stream();
+    let size = UInt::new(bytes.len() as u64);
+
+    let info = if mime_type.type_() == mime::IMAGE {
+        let (width, height) = image_dimensions(&bytes).unzip();
+        AttachmentInfo::Image(BaseImageInfo {
+            height: height.map(UInt::from),
+            width: width.map(UInt::from),
+            size,
+            ..Default::default()
+        })
+    } else {
+        AttachmentInfo::File(BaseFileInfo { size })
+    };
+
+    let thumbnail = match thumbnail_path {
+        Some(thumbnail_path) => Some(load_thumbnail(thumbnail_path)?),
+        None if mime_type.type_() == mime::IMAGE => generate_thumbnail(&bytes),
+        None => None,
+    };
+
+    let config = AttachmentConfig { info: Some(info), thumbnail, ..Default::default() };
+
+    Ok((AttachmentSource::Data { bytes, filename }, mime_type, config))
+}
+
+/// Read the voice message at the given path, which must be an audio file.
+fn load_voice_message(
+    path: &Path,
+    duration: Option<Duration>,
+) -> io::Result<(AttachmentSource, Mime, AttachmentConfig)> {
+    let filename = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))?
+        .to_owned();
+    let mime_type = mime_guess::from_path(path).first_or_octet_stream();
+
+    if mime_type.type_() != mime::AUDIO {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "not an audio file"));
+    }
+
+    let bytes = fs::read(path)?;
+
+    let (found_duration, waveform) = match wav_details(&bytes) {
+        Some((duration, waveform)) => (Some(duration), Some(waveform)),
+        None => (ogg_duration(&bytes), None),
+    };
+
+    let Some(duration) = duration.or(found_duration) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the duration of the voice message can't be found, please give it with --duration",
+        ));
+    };
+
+    // The voice message is only marked as such when there's a waveform, which may
+    // be empty.
+    let info = AttachmentInfo::Voice {
+        audio_info: BaseAudioInfo { duration: Some(duration), size: UInt::new(bytes.len() as u64) },
+        waveform: Some(waveform.unwrap_or_default()),
+    };
+
+    let config = AttachmentConfig { info: Some(info), ..Default::default() };
+
+    Ok((AttachmentSource::Data { bytes, filename }, mime_type, config))
+}
+
+/// The number of amplitudes in the waveform of a voice message.
+const WAVEFORM_LEN: usize = 100;
+
+/// The highest amplitude of a waveform, as defined by MSC3246.
+const MAX_AMPLITUDE: u16 = 1024;
+
+/// Find the duration of a WAV file, and its waveform if it's 16-bit PCM, from
+/// its `fmt ` and `data` chunks.
+fn wav_details(data: &[u8]) -> Option<(Duration, Vec<u16>)> {
+    let le_u16 = |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?));
+    let le_u32 = |at: usize| Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?));
+
+    if !data.starts_with(b"RIFF") || data.get(8..12)? != b"WAVE" {
+        return None;
+    }
+
+    // The format, number of channels, byte rate and bits per sample, from the
+    // `fmt ` chunk which comes before the `data` chunk.
+    let mut format = None;
+    let mut at = 12;
+
+    loop {
+        let id = data.get(at..at + 4)?;
+        let size = usize::try_from(le_u32(at + 4)?).ok()?;
+        let body = at + 8;
+
+        if id == b"fmt " {
+            format =
+                Some((le_u16(body)?, le_u16(body + 2)?, le_u32(body + 8)?, le_u16(body + 14)?));
+        } else if id == b"data" {
+            let (format, channels, byte_rate, bits_per_sample) = format?;
+            if byte_rate == 0 || channels == 0 {
+                return None;
+            }
+
+            let samples = data.get(body..)?;
+            let samples = &samples[..size.min(samples.len())];
+            let duration = Duration::from_secs_f64(samples.len() as f64 / f64::from(byte_rate));
+
+            // Only the PCM format with 16-bit samples is decoded, for the first channel.
+            let waveform = if format == 1 && bits_per_sample == 16 {
+                let frame_len = 2 * usize::from(channels);
+                let peaks = samples
+                    .chunks_exact(frame_len)
+                    .map(|frame| i16::from_le_bytes([frame[0], frame[1]]).unsigned_abs())
+                    .collect::<Vec<_>>();
+                let bucket_len = peaks.len().div_ceil(WAVEFORM_LEN).max(1);
+
+                peaks
+                    .chunks(bucket_len)
+                    .map(|bucket| {
+                        let peak = bucket.iter().copied().max().unwrap_or(0);
+                        // The peaks go up to 2^15.
+                        ((u32::from(peak) * u32::from(MAX_AMPLITUDE)) >> 15) as u16
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            return Some((duration, waveform));
+        }
+
+        // The chunks are padded to an even size.
+        at = body + size + size % 2;
+    }
+}
+
+/// Find the duration of an Ogg Opus or Ogg Vorbis file, from the granule
+/// position of its last page.
+fn ogg_duration(data: &[u8]) -> Option<Duration> {
+    let le_u32 = |at: usize| Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?));
+
+    if !data.starts_with(b"OggS") {
+        return None;
+    }
+
+    // The granule position counts the samples at 48 kHz for Opus, and at the rate
+    // given in the identification header for Vorbis.
+    let sample_rate = if find(data, b"OpusHead").is_some() {
+        48_000
+    } else {
+        le_u32(find(data, b"\x01vorbis")? + 12)?
+    };
+
+    let last_page = data.windows(4).rposition(|window| window == b"OggS")?;
+    let granule_position =
+        u64::from_le_bytes(data.get(last_page + 6..last_page + 14)?.try_into().ok()?);
+
+    (sample_rate > 0)
+        .then(|| Duration::from_secs_f64(granule_position as f64 / f64::from(sample_rate)))
+}
+
+/// The position of the first occurrence of `needle` in `data`.
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Read a thumbnail, which must be an image whose dimensions can be found by
+/// [`image_dimensions`].
+fn load_thumbnail(path: &Path) -> io::Result<Thumbnail> {
+    let data = fs::read(path)?;
+    let content_type = mime_guess::from_path(path).first_or_octet_stream();
+
+    let Some((width, height)) = image_dimensions(&data) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the thumbnail must be a PNG, GIF or JPEG image",
+        ));
+    };
+
+    let size = UInt::new(data.len() as u64)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the thumbnail is too big"))?;
+
+    Ok(Thumbnail { data, content_type, height: height.into(), width: width.into(), size })
+}
+
+/// Generate the thumbnail of an image, scaled down to fit in
+/// [`THUMBNAIL_MAX_WIDTH`] x [`THUMBNAIL_MAX_HEIGHT`] while keeping its aspect
+/// ratio.
+///
+/// Returns `None` if the image can't be decoded, or if it's already small
+/// enough to be its own thumbnail.
+fn generate_thumbnail(data: &[u8]) -> Option<Thumbnail> {
+    let image = match ImageReader::new(Cursor::new(data)).with_guessed_format().ok()?.decode() {
+        Ok(image) => image,
+        Err(err) => {
+            warn!("couldn't decode the image to generate its thumbnail: {err}");
+            return None;
+        }
+    };
+
+    if image.width() <= THUMBNAIL_MAX_WIDTH && image.height() <= THUMBNAIL_MAX_HEIGHT {
+        return None;
+    }
+
+    let thumbnail = image.thumbnail(THUMBNAIL_MAX_WIDTH, THUMBNAIL_MAX_HEIGHT);
+
+    // The JPEG encoder doesn't support transparency.
+    let (format, content_type) = if thumbnail.color().has_alpha() {
+        (ImageFormat::Png, mime::IMAGE_PNG)
+    } else {
+        (ImageFormat::Jpeg, mime::IMAGE_JPEG)
+    };
+
+    let mut data = Vec::new();
+    if let Err(err) = thumbnail.write_to(&mut Cursor::new(&mut data), format) {
+        warn!("couldn't encode the thumbnail of the image: {err}");
+        return None;
+    }
+
+    Some(Thumbnail {
+        size: UInt::new(data.len() as u64)?,
+        data,
+        content_type,
+        height: thumbnail.height().into(),
+        width: thumbnail.width().into(),
+    })
+}
+
+/// Find the `(width, height)` of an image from its header, for the PNG, GIF
+/// and JPEG formats.
+fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let be_u16 = |at: usize| Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?));
+    let be_u32 = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    let le_u16 = |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?));
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // The IHDR chunk always comes first.
+        return Some((be_u32(16)?, be_u32(20)?));
+    }
+
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some((le_u16(6)?.into(), le_u16(8)?.into()));
+    }
+
+    if data.starts_with(&[0xff, 0xd8]) {
+        // Walk the segments until the start of frame, which contains the
+        // dimensions.
+        let mut at = 2;
+        loop {
+            if *data.get(at)? != 0xff {
+                return None;
+            }
+
+            let marker = *data.get(at + 1)?;
+            let is_start_of_frame =
+                matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+
+            if is_start_of_frame {
+                return Some((be_u16(at + 7)?.into(), be_u16(at + 5)?.into()));
+            }
+
+            at += 2 + usize::from(be_u16(at + 2)?);
+        }
+    }
+
+    None
+}
@@ -6,7 +6,7 @@ use ratatui::{
     widgets::{Paragraph, Wrap},
 };
 
-use crate::TEXT_COLOR;
+use crate::config::theme;
 
 pub struct EventsView<'a> {
     room: Option<&'a Room>,
@@ -41,12 +41,12 @@ impl Widget for &mut EventsView<'_> {
                 let events = Itertools::intersperse(events, separator);
                 let lines: Vec<_> = [Line::from("")].into_iter().chain(events).collect();
 
-                Paragraph::new(lines).fg(TEXT_COLOR).wrap(Wrap { trim: false }).render(area, buf);
+                Paragraph::new(lines).fg(theme().text).wrap(Wrap { trim: false }).render(area, buf);
             }
 
             None => {
                 Paragraph::new("(room disappeared in the room list service)")
-                    .fg(TEXT_COLOR)
+                    .fg(theme().text)
                     .wrap(Wrap { trim: false })
                     .render(area, buf);
             }
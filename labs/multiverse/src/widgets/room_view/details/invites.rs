@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use matrix_sdk::{Client, Room, locks::Mutex, ruma::OwnedRoomId};
+use ratatui::{
+    prelude::*,
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+};
+use style::palette::tailwind;
+use tokio::{spawn, task::JoinHandle};
+use tracing::warn;
+
+use crate::{config::theme, widgets::status::StatusHandle};
+
+/// A room we've been invited to, as displayed in the list.
+struct Invite {
+    room: Room,
+    name: String,
+
+    /// The name of the user who invited us, if known.
+    inviter: Option<String>,
+
+    /// The reason given with the invite, if any.
+    reason: Option<String>,
+}
+
+/// The list of the rooms we've been invited to, with keybindings to accept or
+/// decline the invites.
+#[derive(Default)]
+pub struct InvitesView {
+    /// The rooms whose invites are listed, to find out when the list must be
+    /// reloaded.
+    room_ids: Vec<OwnedRoomId>,
+
+    invites: Arc<Mutex<Vec<Invite>>>,
+
+    /// Task loading the details of the invites.
+    task: Option<JoinHandle<()>>,
+
+    list_state: ListState,
+}
+
+impl InvitesView {
+    /// Handle a key press, and return whether it has been used by the invites
+    /// list.
+    pub fn handle_key_press(&mut self, key: KeyEvent, status_handle: &StatusHandle) -> bool {
+        use KeyCode::*;
+
+        match key.code {
+            Char('j') => self.list_state.select_next(),
+            Char('k') => self.list_state.select_previous(),
+            Char('a') => self.answer_selected_invite(true, status_handle),
+            Char('d') => self.answer_selected_invite(false, status_handle),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Accept or decline the selected invite, in the background.
+    fn answer_selected_invite(&self, accept: bool, status_handle: &StatusHandle) {
+        let room = self
+            .list_state
+            .selected()
+            .and_then(|index| self.invites.lock().get(index).map(|invite| invite.room.clone()));
+
+        let Some(room) = room else {
+            status_handle.set_message("no selected invite".to_owned());
+            return;
+        };
+
+        let status_handle = status_handle.clone();
+
+        // Once the room isn't an invite anymore, the list will be reloaded.
+        spawn(async move {
+            let (result, verb, participle) = if accept {
+                (room.join().await, "accept", "accepted")
+            } else {
+                (room.leave().await, "decline", "declined")
+            };
+
+            match result {
+                Ok(()) => {
+                    status_handle.set_message(format!("invite to {} {participle}", room.room_id()))
+                }
+                Err(err) => status_handle.set_error(format!("couldn't {verb} the invite: {err}")),
+            }
+        });
+    }
+
+    /// Load the details of the invites, if the rooms we're invited to have
+    /// changed since they've been loaded.
+    fn load(&mut self, client: &Client) {
+        let rooms = client.invited_rooms();
+        let room_ids = rooms.iter().map(|room| room.room_id().to_owned()).collect::<Vec<_>>();
+
+        if room_ids == self.room_ids {
+            return;
+        }
+
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        self.room_ids = room_ids;
+
+        let invites = self.invites.clone();
+
+        self.task = Some(spawn(async move {
+            let mut new_invites = Vec::with_capacity(rooms.len());
+
+            for room in rooms {
+                let name = match room.display_name().await {
+                    Ok(name) => name.to_string(),
+                    Err(_) => room.room_id().to_string(),
+                };
+
+                let (inviter, reason) = match room.invite_details().await {
+                    Ok(details) => (
+                        details.inviter.map(|inviter| inviter.name().to_owned()),
+                        details.invitee.event().reason().map(ToOwned::to_owned),
+                    ),
+                    Err(err) => {
+                        warn!(
+                            "couldn't load the details of the invite to {}: {err}",
+                            room.room_id()
+                        );
+                        (None, None)
+                    }
+                };
+
+                new_invites.push(Invite { room, name, inviter, reason });
+            }
+
+            *invites.lock() = new_invites;
+        }));
+    }
+
+    pub fn render(&mut self, room: Option<&Room>, area: Rect, buf: &mut Buffer) {
+        let Some(room) = room else {
+            Paragraph::new("(room disappeared in the room list service)")
+                .fg(theme().text)
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+            return;
+        };
+
+        self.load(&room.client());
+
+        let invites = self.invites.lock();
+
+        if invites.is_empty() {
+            Paragraph::new("No pending invites.").fg(theme().text).render(area, buf);
+            return;
+        }
+
+        let items = invites.iter().map(|invite| {
+            let mut lines = vec![Line::from(invite.name.clone()).bold()];
+
+            let inviter = invite.inviter.as_deref().unwrap_or("someone");
+            lines.push(Line::from(format!("  invited by {inviter}")).fg(tailwind::GRAY.c400));
+
+            if let Some(reason) = &invite.reason {
+                lines.push(Line::from(format!("  reason: {reason}")).italic());
+            }
+
+            ListItem::new(lines)
+        });
+
+        let list = List::new(items)
+            .fg(theme().text)
+            .highlight_symbol(">")
+            .highlight_style(theme().selected_fg);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+    }
+}
+
+impl Drop for InvitesView {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
@@ -1,48 +1,291 @@
-use matrix_sdk::Room;
-use matrix_sdk_common::executor::Handle;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use matrix_sdk::{
+    Room,
+    linked_chunk::{ChunkContent, ChunkIdentifier, LinkedChunkId, RawChunk},
+    locks::Mutex,
+};
+use matrix_sdk_base::event_cache::{Event, Gap};
 use ratatui::{
     prelude::*,
-    widgets::{Paragraph, Wrap},
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
 };
+use style::palette::tailwind;
+use tokio::{spawn, sync::broadcast::error::RecvError, task::JoinHandle};
+
+use crate::{config::theme, widgets::status::StatusHandle};
+
+/// The number of events to back-paginate when resolving a gap.
+const GAP_RESOLUTION_BATCH_SIZE: u16 = 20;
+
+/// An interactive view of the linked chunk of the room, as it's persisted in
+/// the event cache store: the chunks can be expanded to show their events,
+/// and the gaps resolved on demand.
+#[derive(Default)]
+pub struct LinkedChunkView {
+    /// The room whose linked chunk is shown.
+    room: Option<Room>,
+
+    /// The chunks of the linked chunk, from the oldest to the most recent one,
+    /// or `None` while they're loading.
+    chunks: Arc<Mutex<Option<Vec<RawChunk<Event, Gap>>>>>,
+
+    /// The error which happened when loading the chunks, if any.
+    error: Arc<Mutex<Option<String>>>,
+
+    /// Task reloading the chunks every time the event cache of the room is
+    /// updated.
+    task: Option<JoinHandle<()>>,
 
-use crate::TEXT_COLOR;
+    /// The chunks whose events are shown.
+    expanded: HashSet<ChunkIdentifier>,
 
-pub struct LinkedChunkView<'a> {
-    room: Option<&'a Room>,
+    list_state: ListState,
 }
 
-impl<'a> LinkedChunkView<'a> {
-    pub fn new(room: Option<&'a Room>) -> Self {
-        Self { room }
+impl LinkedChunkView {
+    /// Handle a key press, and return whether it has been used by the linked
+    /// chunk view.
+    pub fn handle_key_press(&mut self, key: KeyEvent, status_handle: &StatusHandle) -> bool {
+        use KeyCode::*;
+
+        match key.code {
+            Char('j') => self.list_state.select_next(),
+            Char('k') => self.list_state.select_previous(),
+            Enter | Char(' ') => self.toggle_selected_chunk(),
+            Char('r') => self.resolve_selected_gap(status_handle),
+            _ => return false,
+        }
+
+        true
     }
-}
 
-impl Widget for &mut LinkedChunkView<'_> {
-    fn render(self, area: Rect, buf: &mut Buffer)
-    where
-        Self: Sized,
-    {
-        match self.room {
-            Some(room) => {
-                let lines = tokio::task::block_in_place(|| {
-                    Handle::current().block_on(async {
-                        let (cache, _drop_guards) =
-                            room.event_cache().await.expect("no event cache for that room");
-                        cache.debug_string().await
-                    })
-                });
-
-                let lines: Vec<Line<'_>> = lines.into_iter().map(Line::from).collect();
-
-                Paragraph::new(lines).fg(TEXT_COLOR).wrap(Wrap { trim: false }).render(area, buf);
+    fn selected_chunk(&self) -> Option<(ChunkIdentifier, bool)> {
+        let index = self.list_state.selected()?;
+        let chunks = self.chunks.lock();
+        let chunk = chunks.as_ref()?.get(index)?;
+        Some((chunk.identifier, matches!(chunk.content, ChunkContent::Gap(_))))
+    }
+
+    /// Show or hide the events of the selected chunk.
+    fn toggle_selected_chunk(&mut self) {
+        let Some((identifier, _)) = self.selected_chunk() else {
+            return;
+        };
+
+        if !self.expanded.remove(&identifier) {
+            self.expanded.insert(identifier);
+        }
+    }
+
+    /// Back-paginate in the event cache of the room, in the background.
+    ///
+    /// The event cache always resolves the most recent gap first, so the
+    /// selected gap is only resolved once the ones after it are.
+    fn resolve_selected_gap(&self, status_handle: &StatusHandle) {
+        let Some(room) = self.room.clone() else {
+            return;
+        };
+
+        if !matches!(self.selected_chunk(), Some((_, true))) {
+            status_handle.set_message("the selected chunk isn't a gap".to_owned());
+            return;
+        }
+
+        let status_handle = status_handle.clone();
+
+        // Once the events are in the event cache, the chunks are reloaded.
+        spawn(async move {
+            let outcome = match room.event_cache().await {
+                Ok((cache, _drop_handles)) => {
+                    cache.pagination().run_backwards_once(GAP_RESOLUTION_BATCH_SIZE).await
+                }
+                Err(err) => Err(err),
+            };
+
+            match outcome {
+                Ok(outcome) if outcome.reached_start => {
+                    status_handle.set_message("reached the start of the room".to_owned())
+                }
+                Ok(outcome) => status_handle
+                    .set_message(format!("back-paginated {} events", outcome.events.len())),
+                Err(err) => status_handle.set_error(format!("couldn't resolve the gap: {err}")),
+            }
+        });
+    }
+
+    /// Start loading the chunks of the room, if it isn't the room whose chunks
+    /// are already shown.
+    fn load(&mut self, room: &Room) {
+        if self.room.as_ref().is_some_and(|current| current.room_id() == room.room_id()) {
+            return;
+        }
+
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        self.room = Some(room.clone());
+        self.list_state = ListState::default();
+        self.chunks = Default::default();
+        self.error = Default::default();
+        self.expanded.clear();
+
+        let room = room.clone();
+        let chunks = self.chunks.clone();
+        let error = self.error.clone();
+
+        self.task = Some(spawn(async move {
+            let (cache, _drop_handles) = match room.event_cache().await {
+                Ok(cache) => cache,
+                Err(err) => {
+                    *error.lock() = Some(err.to_string());
+                    return;
+                }
+            };
+
+            let (_, mut updates) = cache.subscribe().await;
+
+            loop {
+                match load_chunks(&room).await {
+                    Ok(loaded) => *chunks.lock() = Some(loaded),
+                    Err(err) => *error.lock() = Some(err),
+                }
+
+                // Missed updates only mean the chunks must be reloaded too.
+                if let Err(RecvError::Closed) = updates.recv().await {
+                    break;
+                }
             }
+        }));
+    }
+
+    pub fn render(&mut self, room: Option<&Room>, area: Rect, buf: &mut Buffer) {
+        let Some(room) = room else {
+            Paragraph::new("(room disappeared in the room list service)")
+                .fg(theme().text)
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+            return;
+        };
+
+        self.load(room);
+
+        let chunks = self.chunks.lock();
+
+        let Some(chunks) = &*chunks else {
+            let text = match &*self.error.lock() {
+                Some(err) => format!("Couldn't load the linked chunk: {err}"),
+                None => "Loading the linked chunk…".to_owned(),
+            };
+
+            Paragraph::new(text).fg(theme().text).wrap(Wrap { trim: false }).render(area, buf);
+            return;
+        };
+
+        if chunks.is_empty() {
+            Paragraph::new("The linked chunk is empty.").fg(theme().text).render(area, buf);
+            return;
+        }
 
-            None => {
-                Paragraph::new("(room disappeared in the room list service)")
-                    .fg(TEXT_COLOR)
-                    .wrap(Wrap { trim: false })
-                    .render(area, buf);
+        let list_items = chunks.iter().map(|chunk| {
+            let mut lines = vec![format_chunk(chunk)];
+
+            if self.expanded.contains(&chunk.identifier) {
+                match &chunk.content {
+                    ChunkContent::Gap(gap) => {
+                        lines.push(Line::from(format!("    prev_token: {}", gap.prev_token)));
+                    }
+                    ChunkContent::Items(events) => {
+                        lines.extend(events.iter().map(format_event));
+                    }
+                }
             }
+
+            ListItem::new(lines)
+        });
+
+        let list = List::new(list_items)
+            .fg(theme().text)
+            .highlight_symbol(">")
+            .highlight_style(theme().selected_fg);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+    }
+}
+
+impl Drop for LinkedChunkView {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
         }
     }
 }
+
+/// Load the chunks of the room from the event cache store, in order.
+async fn load_chunks(room: &Room) -> Result<Vec<RawChunk<Event, Gap>>, String> {
+    let store = room.client().event_cache_store().lock().await.map_err(|err| err.to_string())?;
+
+    let chunks = store
+        .load_all_chunks(LinkedChunkId::Room(room.room_id()))
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(sort_chunks(chunks))
+}
+
+/// Sort the chunks by following their links from the first one; the chunks
+/// which can't be reached that way, meaning the linked chunk is broken, come
+/// last.
+fn sort_chunks(chunks: Vec<RawChunk<Event, Gap>>) -> Vec<RawChunk<Event, Gap>> {
+    let first = chunks.iter().find(|chunk| chunk.previous.is_none()).map(|chunk| chunk.identifier);
+    let mut by_identifier =
+        chunks.into_iter().map(|chunk| (chunk.identifier, chunk)).collect::<HashMap<_, _>>();
+
+    let mut sorted = Vec::with_capacity(by_identifier.len());
+    let mut next = first;
+
+    while let Some(chunk) = next.and_then(|identifier| by_identifier.remove(&identifier)) {
+        next = chunk.next;
+        sorted.push(chunk);
+    }
+
+    let mut unreachable = by_identifier.into_values().collect::<Vec<_>>();
+    unreachable.sort_by_key(|chunk| chunk.identifier);
+    sorted.extend(unreachable);
+
+    sorted
+}
+
+/// Format the metadata of a chunk.
+fn format_chunk(chunk: &RawChunk<Event, Gap>) -> Line<'static> {
+    let link = |identifier: Option<ChunkIdentifier>| {
+        identifier.map_or_else(|| "-".to_owned(), |identifier| format!("#{}", identifier.index()))
+    };
+
+    let (kind, color) = match &chunk.content {
+        ChunkContent::Gap(_) => ("gap".to_owned(), tailwind::AMBER.c400),
+        ChunkContent::Items(events) => (format!("{} events", events.len()), tailwind::CYAN.c400),
+    };
+
+    Line::from(vec![
+        Span::from(format!("chunk #{} ", chunk.identifier.index())).bold(),
+        Span::from(kind).fg(color),
+        Span::from(format!(" (prev: {}, next: {})", link(chunk.previous), link(chunk.next))),
+    ])
+}
+
+/// Format an event of an expanded chunk, with its ID, type and sender.
+fn format_event(event: &Event) -> Line<'static> {
+    let field = |name: &str| event.raw().get_field::<String>(name).ok().flatten();
+
+    let event_id = event.event_id().map_or_else(|| "(no event ID)".to_owned(), |id| id.to_string());
+    let event_type = field("type").unwrap_or_else(|| "?".to_owned());
+    let sender = field("sender").unwrap_or_else(|| "?".to_owned());
+
+    Line::from(format!("    {event_id} {event_type} from {sender}"))
+}
@@ -0,0 +1,351 @@
+use std::{cmp::Reverse, sync::Arc};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use matrix_sdk::{
+    Room, RoomMemberships,
+    locks::Mutex,
+    ruma::{
+        OwnedUserId, UserId,
+        events::room::{member::MembershipState, power_levels::UserPowerLevel},
+    },
+};
+use ratatui::{
+    prelude::*,
+    widgets::{LineGauge, List, ListItem, ListState, Paragraph, Wrap},
+};
+use style::palette::tailwind;
+use tokio::{spawn, task::JoinHandle};
+use tracing::warn;
+
+use crate::{
+    config::theme,
+    widgets::{
+        popup_input::{PopupInput, PopupInputBuilder},
+        status::StatusHandle,
+    },
+};
+
+/// A member of the room, as displayed in the list.
+struct Member {
+    name: String,
+    user_id: OwnedUserId,
+    power_level: UserPowerLevel,
+    membership: MembershipState,
+}
+
+impl Member {
+    /// The value used to sort the members: the most powerful ones come first.
+    fn power_level_key(&self) -> i64 {
+        match self.power_level {
+            UserPowerLevel::Int(power_level) => power_level.into(),
+            _ => i64::MAX,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Members {
+    members: Vec<Member>,
+
+    /// Are the members still being fetched from the homeserver? Until then,
+    /// only the members known thanks to lazy loading are listed.
+    is_loading: bool,
+}
+
+/// An action on a member of the room.
+enum MemberAction {
+    Invite,
+    Kick,
+    Ban,
+}
+
+impl MemberAction {
+    /// Execute the action on the given user, after checking that our power
+    /// level allows it.
+    async fn run(&self, room: &Room, user_id: &UserId) -> Result<(), String> {
+        let own_member = match room.get_member_no_sync(room.own_user_id()).await {
+            Ok(Some(member)) => member,
+            Ok(None) => return Err("missing own room member".to_owned()),
+            Err(err) => return Err(format!("error when loading member: {err}")),
+        };
+
+        let allowed = match self {
+            Self::Invite => own_member.can_invite(),
+            Self::Kick => own_member.can_kick(),
+            Self::Ban => own_member.can_ban(),
+        };
+
+        if !allowed {
+            return Err(format!("our power level doesn't allow to {} members", self.verb()));
+        }
+
+        let result = match self {
+            Self::Invite => room.invite_user_by_id(user_id).await,
+            Self::Kick => room.kick_user(user_id, None).await,
+            Self::Ban => room.ban_user(user_id, None).await,
+        };
+
+        result.map_err(|err| format!("couldn't {} {user_id}: {err}", self.verb()))
+    }
+
+    fn verb(&self) -> &'static str {
+        match self {
+            Self::Invite => "invite",
+            Self::Kick => "kick",
+            Self::Ban => "ban",
+        }
+    }
+}
+
+/// The list of the members of the room, with their power levels and
+/// memberships.
+#[derive(Default)]
+pub struct MembersView {
+    /// The room whose members are listed.
+    room: Option<Room>,
+
+    members: Arc<Mutex<Members>>,
+
+    /// Task loading the members of the room.
+    task: Option<JoinHandle<()>>,
+
+    list_state: ListState,
+
+    /// The input for the ID of the user to invite, if the user is inviting
+    /// someone.
+    invite_input: Option<PopupInput>,
+}
+
+impl MembersView {
+    /// Is the user writing the ID of a user to invite?
+    pub fn is_editing_text(&self) -> bool {
+        self.invite_input.is_some()
+    }
+
+    /// Handle a key press, and return whether it has been used by the members
+    /// list.
+    pub fn handle_key_press(&mut self, key: KeyEvent, status_handle: &StatusHandle) -> bool {
+        use KeyCode::*;
+
+        if let Some(input) = &mut self.invite_input {
+            match key.code {
+                Enter => {
+                    let user_id = input.get_input();
+                    self.invite_input = None;
+                    self.run_action(MemberAction::Invite, user_id.trim(), status_handle);
+                }
+                Esc => self.invite_input = None,
+                _ => input.handle_key_press(key),
+            }
+
+            return true;
+        }
+
+        match key.code {
+            Char('j') => self.list_state.select_next(),
+            Char('k') => self.list_state.select_previous(),
+
+            Char('a') => {
+                self.invite_input = Some(
+                    PopupInputBuilder::new(
+                        "Invite a user (Enter: invite, Esc: cancel)",
+                        "@user:server",
+                    )
+                    .build(),
+                );
+            }
+
+            Char('K') => self.run_action_on_selected_member(MemberAction::Kick, status_handle),
+            Char('B') => self.run_action_on_selected_member(MemberAction::Ban, status_handle),
+
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn run_action_on_selected_member(&self, action: MemberAction, status_handle: &StatusHandle) {
+        let user_id = self.list_state.selected().and_then(|index| {
+            self.members.lock().members.get(index).map(|member| member.user_id.to_string())
+        });
+
+        match user_id {
+            Some(user_id) => self.run_action(action, &user_id, status_handle),
+            None => status_handle.set_message("no selected member".to_owned()),
+        }
+    }
+
+    /// Run the action in the background, and reload the members once it's
+    /// done.
+    fn run_action(&self, action: MemberAction, user_id: &str, status_handle: &StatusHandle) {
+        let Some(room) = self.room.clone() else {
+            return;
+        };
+
+        // Allow to only give the localpart of a user of our own homeserver.
+        let user_id =
+            match UserId::parse_with_server_name(user_id, room.own_user_id().server_name()) {
+                Ok(user_id) => user_id,
+                Err(err) => {
+                    status_handle.set_message(format!("{user_id} isn't a valid user ID: {err}"));
+                    return;
+                }
+            };
+
+        let members = self.members.clone();
+        let status_handle = status_handle.clone();
+
+        spawn(async move {
+            match action.run(&room, &user_id).await {
+                Ok(()) => {
+                    status_handle.set_message(format!("{user_id}: {} done!", action.verb()));
+
+                    let new_members = load_members(&room).await;
+                    members.lock().members = new_members;
+                }
+                Err(err) => status_handle.set_message(err),
+            }
+        });
+    }
+
+    /// Start loading the members of the room, if it isn't the room whose
+    /// members are already listed.
+    fn load(&mut self, room: &Room) {
+        if self.room.as_ref().is_some_and(|current| current.room_id() == room.room_id()) {
+            return;
+        }
+
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        self.room = Some(room.clone());
+        self.list_state = ListState::default();
+        *self.members.lock() = Members { members: Vec::new(), is_loading: true };
+
+        let room = room.clone();
+        let members = self.members.clone();
+
+        self.task = Some(spawn(async move {
+            // Show the members we already know about first, the full list of a big room can
+            // take a while to be fetched.
+            let is_synced = room.are_members_synced();
+            let known_members = load_members(&room).await;
+            members.lock().members = known_members;
+
+            if !is_synced {
+                if let Err(err) = room.sync_members().await {
+                    warn!("couldn't fetch the members of the room: {err}");
+                }
+
+                let all_members = load_members(&room).await;
+                members.lock().members = all_members;
+            }
+
+            members.lock().is_loading = false;
+        }));
+    }
+
+    pub fn render(&mut self, room: Option<&Room>, area: Rect, buf: &mut Buffer) {
+        let Some(room) = room else {
+            Paragraph::new("(room disappeared in the room list service)")
+                .fg(theme().text)
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+            return;
+        };
+
+        self.load(room);
+
+        let members = self.members.lock();
+
+        let [progress_area, list_area] = if members.is_loading {
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area)
+        } else {
+            [Rect::default(), area]
+        };
+
+        if members.is_loading {
+            // The members which left or have been banned aren't part of the count.
+            let total = room.active_members_count();
+            let known = members
+                .members
+                .iter()
+                .filter(|member| RoomMemberships::ACTIVE.matches(&member.membership))
+                .count() as u64;
+            let ratio = if total == 0 { 0.0 } else { (known as f64 / total as f64).min(1.0) };
+
+            LineGauge::default()
+                .filled_style(tailwind::BLUE.c400)
+                .label(format!("loading the members: {known}/{total}"))
+                .ratio(ratio)
+                .render(progress_area, buf);
+        }
+
+        let items = members.members.iter().map(|member| {
+            let power_level = match member.power_level {
+                UserPowerLevel::Int(power_level) => power_level.to_string(),
+                _ => "creator".to_owned(),
+            };
+
+            let mut line = Line::from(vec![
+                Span::from(format!("{power_level:>7} ")).fg(tailwind::AMBER.c400),
+                Span::from(member.name.clone()).bold(),
+                Span::from(format!(" ({})", member.user_id)),
+            ]);
+
+            if member.membership != MembershipState::Join {
+                line.push_span(Span::from(format!(" [{}]", member.membership)).italic());
+            }
+
+            ListItem::new(line)
+        });
+
+        let list = List::new(items)
+            .fg(theme().text)
+            .highlight_symbol(">")
+            .highlight_style(theme().selected_fg);
+
+        StatefulWidget::render(list, list_area, buf, &mut self.list_state);
+
+        if let Some(input) = &mut self.invite_input {
+            input.render(area, buf);
+        }
+    }
+}
+
+impl Drop for MembersView {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Load the members of the room from the store, sorted by power level, then
+/// by name.
+async fn load_members(room: &Room) -> Vec<Member> {
+    let room_members = match room.members_no_sync(RoomMemberships::empty()).await {
+        Ok(room_members) => room_members,
+        Err(err) => {
+            warn!("couldn't load the members of the room: {err}");
+            return Vec::new();
+        }
+    };
+
+    let mut members = room_members
+        .into_iter()
+        .map(|member| Member {
+            name: member.name().to_owned(),
+            user_id: member.user_id().to_owned(),
+            power_level: member.power_level(),
+            membership: member.membership().clone(),
+        })
+        .collect::<Vec<_>>();
+
+    members.sort_by_cached_key(|member| {
+        (Reverse(member.power_level_key()), member.name.to_lowercase())
+    });
+
+    members
+}
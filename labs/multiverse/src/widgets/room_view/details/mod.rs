@@ -3,13 +3,23 @@ use ratatui::{prelude::*, widgets::*};
 use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
 use style::palette::tailwind;
 
-use self::{events::EventsView, linked_chunk::LinkedChunkView, read_receipts::ReadReceipts};
+use self::{
+    events::EventsView, invites::InvitesView, linked_chunk::LinkedChunkView, members::MembersView,
+    pinned_events::PinnedEventsView, reactions::ReactionsView, read_receipts::ReadReceipts,
+    send_queue::SendQueueView, settings::RoomSettingsView,
+};
 use super::DetailsState;
-use crate::widgets::recovery::ShouldExit;
+use crate::widgets::{recovery::ShouldExit, status::StatusHandle};
 
 mod events;
+mod invites;
 mod linked_chunk;
+mod members;
+mod pinned_events;
+mod reactions;
 mod read_receipts;
+mod send_queue;
+mod settings;
 
 #[derive(Clone, Copy, Default, Display, FromRepr, EnumIter)]
 enum SelectedTab {
@@ -22,6 +32,24 @@ enum SelectedTab {
 
     /// Show the linked chunks that are used to display the timeline.
     LinkedChunks,
+
+    /// Show the members of the room.
+    Members,
+
+    /// Show the rooms we've been invited to.
+    Invites,
+
+    /// Show the pinned events of the room.
+    PinnedEvents,
+
+    /// Show who reacted with what to the selected event.
+    Reactions,
+
+    /// Edit the settings of the room.
+    Settings,
+
+    /// Show the requests waiting in the send queue of the room.
+    SendQueue,
 }
 
 impl SelectedTab {
@@ -71,6 +99,12 @@ impl SelectedTab {
             Self::Events => tailwind::BLUE,
             Self::ReadReceipts => tailwind::EMERALD,
             Self::LinkedChunks => tailwind::INDIGO,
+            Self::Members => tailwind::AMBER,
+            Self::Invites => tailwind::ROSE,
+            Self::PinnedEvents => tailwind::TEAL,
+            Self::Reactions => tailwind::FUCHSIA,
+            Self::Settings => tailwind::SKY,
+            Self::SendQueue => tailwind::ORANGE,
         }
     }
 }
@@ -89,9 +123,18 @@ impl<'a> StatefulWidget for &'a SelectedTab {
             SelectedTab::ReadReceipts => {
                 ReadReceipts::new(state).render(area, buf);
             }
-            SelectedTab::LinkedChunks => {
-                LinkedChunkView::new(state.selected_room).render(area, buf)
+            SelectedTab::Reactions => {
+                ReactionsView::new(state).render(area, buf);
             }
+            // The linked chunks, the members, the invites, the pinned events, the settings
+            // and the send queue are rendered by the `RoomDetails`, which keeps their
+            // state around.
+            SelectedTab::LinkedChunks
+            | SelectedTab::Members
+            | SelectedTab::Invites
+            | SelectedTab::PinnedEvents
+            | SelectedTab::Settings
+            | SelectedTab::SendQueue => {}
         }
     }
 }
@@ -99,34 +142,109 @@ impl<'a> StatefulWidget for &'a SelectedTab {
 #[derive(Default)]
 pub struct RoomDetails {
     selected_tab: SelectedTab,
+
+    /// The area of the tab titles in the last frame, to find the clicked tab.
+    tabs_area: Rect,
+
+    linked_chunk: LinkedChunkView,
+    members: MembersView,
+    invites: InvitesView,
+    pinned_events: PinnedEventsView,
+    settings: RoomSettingsView,
+    send_queue: SendQueueView,
 }
 
 impl RoomDetails {
     /// Create a new [`RoomDetails`] struct with the [`SelectedTab::Events`] as
     /// the selected tab.
     pub fn with_events_as_selected() -> Self {
-        Self { selected_tab: SelectedTab::Events }
+        Self { selected_tab: SelectedTab::Events, ..Default::default() }
     }
 
     /// Create a new [`RoomDetails`] struct with the
     /// [`SelectedTab::ReadReceipts`] as the selected tab.
     pub fn with_receipts_as_selected() -> Self {
-        Self { selected_tab: SelectedTab::ReadReceipts }
+        Self { selected_tab: SelectedTab::ReadReceipts, ..Default::default() }
     }
 
     /// Create a new [`RoomDetails`] struct with the
     /// [`SelectedTab::LinkedChunks`] as the selected tab.
     pub fn with_chunks_as_selected() -> Self {
-        Self { selected_tab: SelectedTab::LinkedChunks }
+        Self { selected_tab: SelectedTab::LinkedChunks, ..Default::default() }
+    }
+
+    /// Create a new [`RoomDetails`] struct with the [`SelectedTab::Members`]
+    /// as the selected tab.
+    pub fn with_members_as_selected() -> Self {
+        Self { selected_tab: SelectedTab::Members, ..Default::default() }
+    }
+
+    /// Create a new [`RoomDetails`] struct with the [`SelectedTab::Invites`]
+    /// as the selected tab.
+    pub fn with_invites_as_selected() -> Self {
+        Self { selected_tab: SelectedTab::Invites, ..Default::default() }
+    }
+
+    /// Create a new [`RoomDetails`] struct with the
+    /// [`SelectedTab::PinnedEvents`] as the selected tab.
+    pub fn with_pinned_events_as_selected() -> Self {
+        Self { selected_tab: SelectedTab::PinnedEvents, ..Default::default() }
+    }
+
+    /// Create a new [`RoomDetails`] struct with the [`SelectedTab::Reactions`]
+    /// as the selected tab.
+    pub fn with_reactions_as_selected() -> Self {
+        Self { selected_tab: SelectedTab::Reactions, ..Default::default() }
     }
 
-    pub fn handle_key_press(&mut self, event: KeyEvent) -> ShouldExit {
+    /// Create a new [`RoomDetails`] struct with the [`SelectedTab::Settings`]
+    /// as the selected tab.
+    pub fn with_settings_as_selected() -> Self {
+        Self { selected_tab: SelectedTab::Settings, ..Default::default() }
+    }
+
+    /// Create a new [`RoomDetails`] struct with the [`SelectedTab::SendQueue`]
+    /// as the selected tab.
+    pub fn with_send_queue_as_selected() -> Self {
+        Self { selected_tab: SelectedTab::SendQueue, ..Default::default() }
+    }
+
+    /// Is the user writing some text in the details, so the key presses must
+    /// not be interpreted as shortcuts?
+    pub fn is_editing_text(&self) -> bool {
+        match self.selected_tab {
+            SelectedTab::Members => self.members.is_editing_text(),
+            SelectedTab::Settings => self.settings.is_editing_text(),
+            SelectedTab::SendQueue => self.send_queue.is_editing_text(),
+            _ => false,
+        }
+    }
+
+    pub fn handle_key_press(
+        &mut self,
+        event: KeyEvent,
+        status_handle: &StatusHandle,
+    ) -> ShouldExit {
         use KeyCode::*;
 
         if event.kind != KeyEventKind::Press {
             return ShouldExit::No;
         }
 
+        let is_handled = match self.selected_tab {
+            SelectedTab::LinkedChunks => self.linked_chunk.handle_key_press(event, status_handle),
+            SelectedTab::Members => self.members.handle_key_press(event, status_handle),
+            SelectedTab::Invites => self.invites.handle_key_press(event, status_handle),
+            SelectedTab::PinnedEvents => self.pinned_events.handle_key_press(event, status_handle),
+            SelectedTab::Settings => self.settings.handle_key_press(event, status_handle),
+            SelectedTab::SendQueue => self.send_queue.handle_key_press(event, status_handle),
+            SelectedTab::Events | SelectedTab::ReadReceipts | SelectedTab::Reactions => false,
+        };
+
+        if is_handled {
+            return ShouldExit::No;
+        }
+
         match event.code {
             Char('l') | Right => {
                 self.next_tab();
@@ -154,6 +272,30 @@ impl RoomDetails {
         }
     }
 
+    /// Select the tab whose title is at the given position of the screen, and
+    /// return whether there's one.
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        if !self.tabs_area.contains(Position::new(column, row)) {
+            return false;
+        }
+
+        let mut x = self.tabs_area.x;
+
+        for tab in SelectedTab::iter() {
+            // The titles are separated by a single space.
+            x = x.saturating_add(u16::try_from(tab.title().width()).unwrap_or(u16::MAX));
+
+            if column < x {
+                self.selected_tab = tab;
+                return true;
+            }
+
+            x = x.saturating_add(1);
+        }
+
+        false
+    }
+
     fn cycle_next_tab(&mut self) {
         self.selected_tab = self.selected_tab.cycle_next();
     }
@@ -199,6 +341,8 @@ impl<'a> StatefulWidget for &'a mut RoomDetails {
 
         let tabs_area = inner_area.inner(Margin::new(1, 1));
 
+        self.tabs_area = tab_title_area;
+
         Tabs::new(titles)
             .highlight_style(highlight_style)
             .select(selected_tab_index)
@@ -206,10 +350,37 @@ impl<'a> StatefulWidget for &'a mut RoomDetails {
             .divider(" ")
             .render(tab_title_area, buf);
 
-        self.selected_tab.render(tabs_area, buf, state);
+        let footer = match self.selected_tab {
+            SelectedTab::LinkedChunks => {
+                self.linked_chunk.render(state.selected_room, tabs_area, buf);
+                "◄ ► to change tab | j k to scroll | Enter: expand, r: resolve gap | q to exit"
+            }
+            SelectedTab::Members => {
+                self.members.render(state.selected_room, tabs_area, buf);
+                "◄ ► to change tab | j k to scroll | a: invite, K: kick, B: ban | q to exit"
+            }
+            SelectedTab::Invites => {
+                self.invites.render(state.selected_room, tabs_area, buf);
+                "◄ ► to change tab | j k to scroll | a: accept, d: decline | q to exit"
+            }
+            SelectedTab::PinnedEvents => {
+                self.pinned_events.render(state.selected_room, tabs_area, buf);
+                "◄ ► to change tab | j k to scroll | U: unpin | q to exit"
+            }
+            SelectedTab::Settings => {
+                self.settings.render(state.selected_room, tabs_area, buf);
+                "◄ ► to change tab | j k to scroll | Enter: edit | q to exit"
+            }
+            SelectedTab::SendQueue => {
+                self.send_queue.render(state.selected_room, tabs_area, buf);
+                "◄ ► to change tab | j k to scroll | r: retry, e: edit, a: abort | q to exit"
+            }
+            SelectedTab::Events | SelectedTab::ReadReceipts | SelectedTab::Reactions => {
+                self.selected_tab.render(tabs_area, buf, state);
+                "◄ ► to change tab | Press q to exit the details screen"
+            }
+        };
 
-        Line::raw("◄ ► to change tab | Press q to exit the details screen")
-            .centered()
-            .render(footer_area, buf);
+        Line::raw(footer).centered().render(footer_area, buf);
     }
 }
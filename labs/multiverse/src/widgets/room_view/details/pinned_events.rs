@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use futures_util::StreamExt as _;
+use imbl::Vector;
+use matrix_sdk::{Room, locks::Mutex, ruma::OwnedEventId};
+use matrix_sdk_ui::{
+    Timeline,
+    timeline::{Profile, TimelineBuilder, TimelineDetails, TimelineFocus, TimelineItem},
+};
+use ratatui::{
+    prelude::*,
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+};
+use style::palette::tailwind;
+use tokio::{spawn, sync::OnceCell, task::JoinHandle};
+
+use crate::{config::theme, widgets::status::StatusHandle};
+
+/// The list of the pinned events of the room, with a keybinding to unpin
+/// them.
+#[derive(Default)]
+pub struct PinnedEventsView {
+    /// The room whose pinned events are listed.
+    room: Option<Room>,
+
+    /// The timeline focused on the pinned events of the room.
+    timeline: Arc<OnceCell<Arc<Timeline>>>,
+
+    /// Items in the pinned events timeline.
+    items: Arc<Mutex<Vector<Arc<TimelineItem>>>>,
+
+    /// Task listening to updates from the pinned events timeline, to maintain
+    /// the `items` field over time.
+    task: Option<JoinHandle<()>>,
+
+    /// The error which happened when building the timeline, if any.
+    error: Arc<Mutex<Option<String>>>,
+
+    list_state: ListState,
+}
+
+impl PinnedEventsView {
+    /// Handle a key press, and return whether it has been used by the pinned
+    /// events list.
+    pub fn handle_key_press(&mut self, key: KeyEvent, status_handle: &StatusHandle) -> bool {
+        use KeyCode::*;
+
+        match key.code {
+            Char('j') => self.list_state.select_next(),
+            Char('k') => self.list_state.select_previous(),
+            Char('U') => self.unpin_selected_event(status_handle),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Unpin the selected event, in the background.
+    fn unpin_selected_event(&self, status_handle: &StatusHandle) {
+        let Some(timeline) = self.timeline.get().cloned() else {
+            status_handle.set_message("the pinned events are still loading".to_owned());
+            return;
+        };
+
+        let Some(event_id) = self.selected_event_id() else {
+            status_handle.set_message("no selected pinned event".to_owned());
+            return;
+        };
+
+        let status_handle = status_handle.clone();
+
+        // Once the event is unpinned, the timeline will remove it from the list.
+        spawn(async move {
+            match timeline.unpin_event(&event_id).await {
+                Ok(true) => status_handle.set_message(format!("{event_id} unpinned!")),
+                Ok(false) => status_handle.set_message(format!("{event_id} wasn't pinned")),
+                Err(err) => status_handle.set_error(format!("couldn't unpin {event_id}: {err}")),
+            }
+        });
+    }
+
+    /// The ID of the selected pinned event, skipping the virtual items of the
+    /// timeline which aren't listed.
+    fn selected_event_id(&self) -> Option<OwnedEventId> {
+        let index = self.list_state.selected()?;
+        let items = self.items.lock();
+        let event = items.iter().filter_map(|item| item.as_event()).nth(index)?;
+        event.event_id().map(ToOwned::to_owned)
+    }
+
+    /// Start loading the pinned events of the room, if it isn't the room whose
+    /// pinned events are already listed.
+    fn load(&mut self, room: &Room) {
+        if self.room.as_ref().is_some_and(|current| current.room_id() == room.room_id()) {
+            return;
+        }
+
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        self.room = Some(room.clone());
+        self.list_state = ListState::default();
+        self.timeline = Default::default();
+        self.items = Default::default();
+        self.error = Default::default();
+
+        let room = room.clone();
+        let t = self.timeline.clone();
+        let items = self.items.clone();
+        let error = self.error.clone();
+
+        self.task = Some(spawn(async move {
+            let timeline = match TimelineBuilder::new(&room)
+                .with_focus(TimelineFocus::PinnedEvents {
+                    max_events_to_load: 100,
+                    max_concurrent_requests: 10,
+                })
+                .build()
+                .await
+            {
+                Ok(timeline) => timeline,
+                Err(err) => {
+                    *error.lock() = Some(err.to_string());
+                    return;
+                }
+            };
+
+            let (initial_items, mut stream) = timeline.subscribe().await;
+
+            t.set(Arc::new(timeline)).unwrap();
+            *items.lock() = initial_items;
+
+            while let Some(diffs) = stream.next().await {
+                let mut items = items.lock();
+                for diff in diffs {
+                    diff.apply(&mut items);
+                }
+            }
+        }));
+    }
+
+    pub fn render(&mut self, room: Option<&Room>, area: Rect, buf: &mut Buffer) {
+        let Some(room) = room else {
+            Paragraph::new("(room disappeared in the room list service)")
+                .fg(theme().text)
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+            return;
+        };
+
+        self.load(room);
+
+        if self.timeline.get().is_none() {
+            let text = match &*self.error.lock() {
+                Some(err) => format!("Couldn't load the pinned events: {err}"),
+                None => "Loading the pinned events…".to_owned(),
+            };
+
+            Paragraph::new(text).fg(theme().text).wrap(Wrap { trim: false }).render(area, buf);
+            return;
+        }
+
+        let items = self.items.lock();
+        let events = items.iter().filter_map(|item| item.as_event()).collect::<Vec<_>>();
+
+        if events.is_empty() {
+            Paragraph::new("No pinned events.").fg(theme().text).render(area, buf);
+            return;
+        }
+
+        let list_items = events.into_iter().map(|event| {
+            let sender = match event.sender_profile() {
+                TimelineDetails::Ready(Profile { display_name: Some(name), .. }) => name.clone(),
+                _ => event.sender().to_string(),
+            };
+
+            let body = match event.content().as_message() {
+                Some(message) => message.body().to_owned(),
+                None => "(not a message)".to_owned(),
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::from(format!("{sender}: ")).fg(tailwind::CYAN.c400).bold(),
+                Span::from(body),
+            ]))
+        });
+
+        let list = List::new(list_items)
+            .fg(theme().text)
+            .highlight_symbol(">")
+            .highlight_style(theme().selected_fg);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+    }
+}
+
+impl Drop for PinnedEventsView {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use matrix_sdk::{Room, ruma::UserId};
+use matrix_sdk_common::executor::Handle;
+use matrix_sdk_ui::timeline::ReactionStatus;
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Wrap},
+};
+use style::palette::tailwind;
+
+use crate::{config::theme, widgets::room_view::DetailsState};
+
+/// The reactions to the selected event, with the names of the members who
+/// sent them.
+pub struct ReactionsView<'a> {
+    state: &'a DetailsState<'a>,
+}
+
+impl<'a> ReactionsView<'a> {
+    pub(super) fn new(state: &'a DetailsState<'a>) -> Self {
+        Self { state }
+    }
+}
+
+impl Widget for &mut ReactionsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let Some(room) = self.state.selected_room else {
+            let content = "(room disappeared in the room list service)";
+            Paragraph::new(content).fg(theme().text).wrap(Wrap { trim: false }).render(area, buf);
+            return;
+        };
+
+        let reactions = self
+            .state
+            .selected_item
+            .as_deref()
+            .and_then(|item| item.as_event())
+            .and_then(|event| event.content().reactions());
+
+        let Some(reactions) = reactions else {
+            Paragraph::new("Select an event in the timeline to see its reactions.")
+                .fg(theme().text)
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+            return;
+        };
+
+        if reactions.is_empty() {
+            Paragraph::new("No reactions.").fg(theme().text).render(area, buf);
+            return;
+        }
+
+        let mut lines = Vec::new();
+
+        for (key, senders) in reactions.iter() {
+            lines.push(Line::from(format!("{key} ({})", senders.len())).bold());
+
+            for (user_id, info) in senders {
+                let mut line = Line::from(format!("  {}", member_name(room, user_id)));
+
+                if !matches!(info.status, ReactionStatus::RemoteToRemote(_)) {
+                    line.push_span(Span::from(" (sending)").fg(tailwind::GRAY.c400));
+                }
+
+                lines.push(line);
+            }
+        }
+
+        Paragraph::new(lines).fg(theme().text).wrap(Wrap { trim: false }).render(area, buf);
+    }
+}
+
+/// The display name of the member, or their user ID if it's unknown.
+fn member_name(room: &Room, user_id: &UserId) -> String {
+    let member = tokio::task::block_in_place(|| {
+        Handle::current().block_on(async { room.get_member_no_sync(user_id).await })
+    });
+
+    match member {
+        Ok(Some(member)) => format!("{} ({user_id})", member.name()),
+        _ => user_id.to_string(),
+    }
+}
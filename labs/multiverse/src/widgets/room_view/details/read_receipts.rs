@@ -1,12 +1,20 @@
-use matrix_sdk::Room;
+use std::sync::Arc;
+
+use imbl::Vector;
+use indexmap::IndexMap;
+use matrix_sdk::{
+    Room,
+    ruma::{OwnedUserId, events::receipt::Receipt},
+};
 use matrix_sdk_base::read_receipts::RoomReadReceipts;
 use matrix_sdk_ui::timeline::TimelineItem;
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Paragraph, Wrap},
 };
+use style::palette::tailwind;
 
-use crate::{TEXT_COLOR, widgets::room_view::DetailsState};
+use crate::{config::theme, widgets::room_view::DetailsState};
 
 pub struct ReadReceipts<'a> {
     state: &'a DetailsState<'a>,
@@ -32,23 +40,75 @@ fn render_room_stats(room: &Room, area: Rect, buf: &mut Buffer) {
     ];
 
     Paragraph::new(content)
-        .fg(TEXT_COLOR)
+        .fg(theme().text)
         .wrap(Wrap { trim: false })
         .block(Block::new().borders(Borders::BOTTOM))
         .render(area, buf);
 }
 
+/// The maximum number of users named in the compact list of the read receipts
+/// of an event, before only counting the others.
+const MAX_NAMED_READERS: usize = 2;
+
+/// Render the events of the timeline which have read receipts, with a compact
+/// list of the users whose read receipt sits on each of them.
+fn render_receipts_per_event(items: &Vector<Arc<TimelineItem>>, area: Rect, buf: &mut Buffer) {
+    let lines = items
+        .iter()
+        .filter_map(|item| item.as_event())
+        .filter(|event| !event.read_receipts().is_empty())
+        .map(|event| {
+            let body = match event.content().as_message() {
+                Some(message) => message.body().lines().next().unwrap_or_default().to_owned(),
+                None => "(not a message)".to_owned(),
+            };
+
+            Line::from(vec![
+                Span::from(format!("{}: {body} ", event.sender())),
+                Span::from(format_readers(event.read_receipts())).fg(tailwind::GREEN.c400),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        Paragraph::new("No read receipts on the loaded events.").fg(theme().text).render(area, buf);
+        return;
+    }
+
+    Paragraph::new(lines).fg(theme().text).wrap(Wrap { trim: false }).render(area, buf);
+}
+
+/// Format the readers of an event, e.g. `✓ alice, bob +3`.
+fn format_readers(receipts: &IndexMap<OwnedUserId, Receipt>) -> String {
+    let names = receipts
+        .keys()
+        .take(MAX_NAMED_READERS)
+        .map(|user_id| user_id.localpart())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match receipts.len().saturating_sub(MAX_NAMED_READERS) {
+        0 => format!("✓ {names}"),
+        others => format!("✓ {names} +{others}"),
+    }
+}
+
 impl Widget for &mut ReadReceipts<'_> {
     fn render(self, area: Rect, buf: &mut Buffer)
     where
         Self: Sized,
     {
         if let Some(room) = self.state.selected_room {
+            let [area, events_area] =
+                Layout::vertical([Constraint::Length(9), Constraint::Min(0)]).areas(area);
+
+            render_receipts_per_event(&self.state.timeline_items, events_area, buf);
+
             match self.state.selected_item.as_deref() {
                 Some(selected_event) => {
                     if let Some(item) = format_timeline_item(selected_event) {
                         Paragraph::new(item)
-                            .fg(TEXT_COLOR)
+                            .fg(theme().text)
                             .wrap(Wrap { trim: false })
                             .block(Block::new().borders(Borders::BOTTOM))
                             .render(area, buf);
@@ -60,7 +120,7 @@ impl Widget for &mut ReadReceipts<'_> {
             }
         } else {
             let content = "(room disappeared in the room list service)";
-            Paragraph::new(content).fg(TEXT_COLOR).wrap(Wrap { trim: false }).render(area, buf);
+            Paragraph::new(content).fg(theme().text).wrap(Wrap { trim: false }).render(area, buf);
         }
     }
 }
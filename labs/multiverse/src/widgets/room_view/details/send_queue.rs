@@ -0,0 +1,427 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use matrix_sdk::{
+    Room,
+    locks::Mutex,
+    ruma::{
+        OwnedTransactionId,
+        events::{
+            AnyMessageLikeEventContent,
+            room::message::{MessageType, RoomMessageEventContent},
+        },
+    },
+    send_queue::{LocalEcho, LocalEchoContent, RoomSendQueueUpdate},
+};
+use ratatui::{
+    prelude::*,
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+};
+use style::palette::tailwind;
+use tokio::{spawn, sync::broadcast::error::RecvError, task::JoinHandle};
+
+use crate::{
+    config::theme,
+    widgets::{
+        popup_input::{PopupInput, PopupInputBuilder},
+        status::StatusHandle,
+    },
+};
+
+/// A request waiting in the send queue of the room.
+struct QueuedRequest {
+    echo: LocalEcho,
+
+    /// The last error which happened when sending the request, if any.
+    error: Option<String>,
+
+    /// Is the request wedged, i.e. will it stay in the queue until it's
+    /// retried or aborted?
+    is_wedged: bool,
+}
+
+impl QueuedRequest {
+    fn new(echo: LocalEcho) -> Self {
+        let (error, is_wedged) = match &echo.content {
+            LocalEchoContent::Event { send_error: Some(err), .. } => (Some(err.to_string()), true),
+            _ => (None, false),
+        };
+
+        Self { echo, error, is_wedged }
+    }
+
+    /// The body of the text message to send, if it is one, or `None`.
+    fn text_body(&self) -> Option<String> {
+        let LocalEchoContent::Event { serialized_event, .. } = &self.echo.content else {
+            return None;
+        };
+
+        match serialized_event.deserialize().ok()? {
+            AnyMessageLikeEventContent::RoomMessage(content) => match content.msgtype {
+                MessageType::Text(text) => Some(text.body),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// A one-line description of the request.
+    fn description(&self) -> String {
+        match &self.echo.content {
+            LocalEchoContent::Event { serialized_event, .. } => {
+                match serialized_event.deserialize() {
+                    Ok(AnyMessageLikeEventContent::RoomMessage(content)) => {
+                        format!("{}: {}", content.msgtype(), content.body())
+                    }
+                    Ok(content) => content.event_type().to_string(),
+                    Err(err) => format!("(invalid event: {err})"),
+                }
+            }
+            LocalEchoContent::React { key, applies_to, .. } => {
+                format!("reaction {key} to {applies_to}")
+            }
+        }
+    }
+}
+
+/// The list of the requests waiting in the send queue of the room, with
+/// keybindings to retry, edit or abort them.
+#[derive(Default)]
+pub struct SendQueueView {
+    /// The room whose send queue is listed.
+    room: Option<Room>,
+
+    requests: Arc<Mutex<Vec<QueuedRequest>>>,
+
+    /// Task listening to the updates of the send queue, to maintain the
+    /// `requests` field over time.
+    task: Option<JoinHandle<()>>,
+
+    list_state: ListState,
+
+    /// The input for the new body of the selected message, if the user is
+    /// editing it.
+    input: Option<(OwnedTransactionId, PopupInput)>,
+}
+
+impl SendQueueView {
+    /// Is the user writing the new body of a queued message?
+    pub fn is_editing_text(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Handle a key press, and return whether it has been used by the send
+    /// queue list.
+    pub fn handle_key_press(&mut self, key: KeyEvent, status_handle: &StatusHandle) -> bool {
+        use KeyCode::*;
+
+        if let Some((transaction_id, input)) = &mut self.input {
+            match key.code {
+                Enter => {
+                    let transaction_id = transaction_id.clone();
+                    let body = input.get_input();
+                    self.input = None;
+                    self.edit(&transaction_id, body, status_handle);
+                }
+                Esc => self.input = None,
+                _ => input.handle_key_press(key),
+            }
+
+            return true;
+        }
+
+        match key.code {
+            Char('j') => self.list_state.select_next(),
+            Char('k') => self.list_state.select_previous(),
+            Char('r') => self.retry_selected_request(status_handle),
+            Char('e') => self.start_editing_selected_request(status_handle),
+            Char('a') => self.abort_selected_request(status_handle),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Run the function on the selected request, or tell there's none.
+    fn with_selected_request<T>(
+        &self,
+        status_handle: &StatusHandle,
+        function: impl FnOnce(&QueuedRequest) -> T,
+    ) -> Option<T> {
+        let requests = self.requests.lock();
+        let request = self.list_state.selected().and_then(|index| requests.get(index));
+
+        if request.is_none() {
+            status_handle.set_message("no selected request".to_owned());
+        }
+
+        request.map(function)
+    }
+
+    /// Send the selected request again, after it's been wedged or the send
+    /// queue of the room has been disabled by an error.
+    fn retry_selected_request(&self, status_handle: &StatusHandle) {
+        let Some(room) = self.room.clone() else {
+            return;
+        };
+
+        let Some(retry) = self.with_selected_request(status_handle, |request| {
+            let send_handle = match &request.echo.content {
+                LocalEchoContent::Event { send_handle, .. } if request.is_wedged => {
+                    Some(send_handle.clone())
+                }
+                _ => None,
+            };
+
+            (request.echo.transaction_id.clone(), send_handle)
+        }) else {
+            return;
+        };
+
+        let status_handle = status_handle.clone();
+
+        spawn(async move {
+            let (transaction_id, send_handle) = retry;
+
+            if let Some(send_handle) = send_handle
+                && let Err(err) = send_handle.unwedge().await
+            {
+                status_handle.set_error(format!("couldn't retry {transaction_id}: {err}"));
+                return;
+            }
+
+            // A recoverable error disables the send queue of the room, which must be
+            // enabled again for the requests to be sent.
+            room.send_queue().set_enabled(true);
+            status_handle.set_message(format!("retrying {transaction_id}"));
+        });
+    }
+
+    /// Open the popup to write the new body of the selected message.
+    fn start_editing_selected_request(&mut self, status_handle: &StatusHandle) {
+        let Some(edit) = self.with_selected_request(status_handle, |request| {
+            request.text_body().map(|body| (request.echo.transaction_id.clone(), body))
+        }) else {
+            return;
+        };
+
+        let Some((transaction_id, body)) = edit else {
+            status_handle.set_message("only the text messages can be edited".to_owned());
+            return;
+        };
+
+        let mut input =
+            PopupInputBuilder::new("New message (Enter: save, Esc: cancel)", "").build();
+        input.set_input(&body);
+        self.input = Some((transaction_id, input));
+    }
+
+    /// Replace the body of the queued message, in the background.
+    fn edit(
+        &self,
+        transaction_id: &OwnedTransactionId,
+        body: String,
+        status_handle: &StatusHandle,
+    ) {
+        let send_handle =
+            self.requests.lock().iter().find_map(|request| match &request.echo.content {
+                LocalEchoContent::Event { send_handle, .. }
+                    if request.echo.transaction_id == *transaction_id =>
+                {
+                    Some(send_handle.clone())
+                }
+                _ => None,
+            });
+
+        let Some(send_handle) = send_handle else {
+            status_handle.set_message(format!("{transaction_id} has already been sent"));
+            return;
+        };
+
+        let transaction_id = transaction_id.clone();
+        let status_handle = status_handle.clone();
+
+        spawn(async move {
+            match send_handle.edit(RoomMessageEventContent::text_plain(body).into()).await {
+                Ok(true) => status_handle.set_message(format!("{transaction_id} edited!")),
+                Ok(false) => {
+                    status_handle.set_message(format!("{transaction_id} has already been sent"))
+                }
+                Err(err) => {
+                    status_handle.set_error(format!("couldn't edit {transaction_id}: {err}"))
+                }
+            }
+        });
+    }
+
+    /// Remove the selected request from the send queue, in the background.
+    fn abort_selected_request(&self, status_handle: &StatusHandle) {
+        let Some(content) =
+            self.with_selected_request(status_handle, |request| request.echo.content.clone())
+        else {
+            return;
+        };
+
+        let status_handle = status_handle.clone();
+
+        // Once the request is aborted, the send queue will remove it from the list.
+        spawn(async move {
+            let result = match content {
+                LocalEchoContent::Event { send_handle, .. } => send_handle.abort().await,
+                LocalEchoContent::React { send_handle, .. } => send_handle.abort().await,
+            };
+
+            match result {
+                Ok(true) => status_handle.set_message("request aborted!".to_owned()),
+                Ok(false) => {
+                    status_handle.set_message("the request has already been sent".to_owned())
+                }
+                Err(err) => status_handle.set_error(format!("couldn't abort the request: {err}")),
+            }
+        });
+    }
+
+    /// Start listening to the send queue of the room, if it isn't the room
+    /// whose send queue is already listed.
+    fn load(&mut self, room: &Room) {
+        if self.room.as_ref().is_some_and(|current| current.room_id() == room.room_id()) {
+            return;
+        }
+
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+
+        self.room = Some(room.clone());
+        self.list_state = ListState::default();
+        self.requests = Default::default();
+        self.input = None;
+
+        let send_queue = room.send_queue();
+        let requests = self.requests.clone();
+
+        self.task = Some(spawn(async move {
+            loop {
+                let Ok((echoes, mut updates)) = send_queue.subscribe().await else {
+                    return;
+                };
+
+                *requests.lock() = echoes.into_iter().map(QueuedRequest::new).collect();
+
+                loop {
+                    match updates.recv().await {
+                        Ok(update) => apply_update(&mut requests.lock(), update),
+
+                        // Some updates have been missed, the whole queue must be reloaded.
+                        Err(RecvError::Lagged(_)) => break,
+
+                        Err(RecvError::Closed) => return,
+                    }
+                }
+            }
+        }));
+    }
+
+    pub fn render(&mut self, room: Option<&Room>, area: Rect, buf: &mut Buffer) {
+        let Some(room) = room else {
+            Paragraph::new("(room disappeared in the room list service)")
+                .fg(theme().text)
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+            return;
+        };
+
+        self.load(room);
+
+        let requests = self.requests.lock();
+
+        if requests.is_empty() {
+            let text = if room.send_queue().is_enabled() {
+                "No pending requests."
+            } else {
+                "No pending requests, but the send queue is disabled."
+            };
+
+            Paragraph::new(text).fg(theme().text).render(area, buf);
+        } else {
+            let items = requests.iter().map(|request| {
+                let (status, color) = match &request.error {
+                    Some(err) if request.is_wedged => {
+                        (format!("wedged: {err}"), tailwind::RED.c400)
+                    }
+                    Some(err) => {
+                        (format!("failed, the send queue is disabled: {err}"), tailwind::AMBER.c400)
+                    }
+                    None => ("pending".to_owned(), tailwind::GRAY.c400),
+                };
+
+                ListItem::new(vec![
+                    Line::from(request.description()),
+                    Line::from(format!("  {} — {status}", request.echo.transaction_id)).fg(color),
+                ])
+            });
+
+            let list = List::new(items)
+                .fg(theme().text)
+                .highlight_symbol(">")
+                .highlight_style(theme().selected_fg);
+
+            StatefulWidget::render(list, area, buf, &mut self.list_state);
+        }
+
+        if let Some((_, input)) = &mut self.input {
+            input.render(area, buf);
+        }
+    }
+}
+
+impl Drop for SendQueueView {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Update the list of the queued requests after a change in the send queue.
+fn apply_update(requests: &mut Vec<QueuedRequest>, update: RoomSendQueueUpdate) {
+    let find = |requests: &[QueuedRequest], transaction_id: &OwnedTransactionId| {
+        requests.iter().position(|request| request.echo.transaction_id == *transaction_id)
+    };
+
+    match update {
+        RoomSendQueueUpdate::NewLocalEvent(echo) => requests.push(QueuedRequest::new(echo)),
+
+        RoomSendQueueUpdate::CancelledLocalEvent { transaction_id }
+        | RoomSendQueueUpdate::SentEvent { transaction_id, .. } => {
+            if let Some(index) = find(requests, &transaction_id) {
+                requests.remove(index);
+            }
+        }
+
+        RoomSendQueueUpdate::ReplacedLocalEvent { transaction_id, new_content } => {
+            if let Some(index) = find(requests, &transaction_id)
+                && let LocalEchoContent::Event { serialized_event, .. } =
+                    &mut requests[index].echo.content
+            {
+                *serialized_event = new_content;
+            }
+        }
+
+        RoomSendQueueUpdate::SendError { transaction_id, error, is_recoverable } => {
+            if let Some(index) = find(requests, &transaction_id) {
+                requests[index].error = Some(error.to_string());
+                requests[index].is_wedged = !is_recoverable;
+            }
+        }
+
+        RoomSendQueueUpdate::RetryEvent { transaction_id } => {
+            if let Some(index) = find(requests, &transaction_id) {
+                requests[index].error = None;
+                requests[index].is_wedged = false;
+            }
+        }
+
+        RoomSendQueueUpdate::MediaUpload { .. } => {}
+    }
+}
@@ -0,0 +1,315 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use matrix_sdk::{
+    Room,
+    ruma::events::{
+        StateEventType,
+        room::{
+            history_visibility::{HistoryVisibility, RoomHistoryVisibilityEventContent},
+            join_rules::{JoinRule, RoomJoinRulesEventContent},
+        },
+    },
+};
+use ratatui::{
+    prelude::*,
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+};
+use style::palette::tailwind;
+use tokio::spawn;
+
+use crate::{
+    config::theme,
+    widgets::{
+        popup_input::{PopupInput, PopupInputBuilder},
+        status::StatusHandle,
+    },
+};
+
+/// The settings of the room which can be edited, in the order they're listed.
+#[derive(Clone, Copy)]
+enum Field {
+    Name,
+    Topic,
+    JoinRule,
+    HistoryVisibility,
+    Encryption,
+}
+
+const FIELDS: [Field; 5] =
+    [Field::Name, Field::Topic, Field::JoinRule, Field::HistoryVisibility, Field::Encryption];
+
+/// A change of the settings of the room.
+enum Change {
+    Name(String),
+    Topic(String),
+    JoinRule(JoinRule),
+    HistoryVisibility(HistoryVisibility),
+    EnableEncryption,
+}
+
+impl Change {
+    /// Send the state event for the change, after checking that our power
+    /// level allows it.
+    async fn apply(self, room: &Room) -> Result<(), String> {
+        let own_member = match room.get_member_no_sync(room.own_user_id()).await {
+            Ok(Some(member)) => member,
+            Ok(None) => return Err("missing own room member".to_owned()),
+            Err(err) => return Err(format!("error when loading member: {err}")),
+        };
+
+        let label = self.label();
+
+        if !own_member.can_send_state(self.state_event_type()) {
+            return Err(format!("our power level doesn't allow to change the {label}"));
+        }
+
+        let result = match self {
+            Self::Name(name) => room.set_name(name).await.map(|_| ()),
+            Self::Topic(topic) => room.set_room_topic(&topic).await.map(|_| ()),
+            Self::JoinRule(join_rule) => {
+                room.send_state_event(RoomJoinRulesEventContent::new(join_rule)).await.map(|_| ())
+            }
+            Self::HistoryVisibility(visibility) => room
+                .send_state_event(RoomHistoryVisibilityEventContent::new(visibility))
+                .await
+                .map(|_| ()),
+            Self::EnableEncryption => room.enable_encryption().await,
+        };
+
+        result.map_err(|err| format!("couldn't change the {label}: {err}"))
+    }
+
+    fn state_event_type(&self) -> StateEventType {
+        match self {
+            Self::Name(_) => StateEventType::RoomName,
+            Self::Topic(_) => StateEventType::RoomTopic,
+            Self::JoinRule(_) => StateEventType::RoomJoinRules,
+            Self::HistoryVisibility(_) => StateEventType::RoomHistoryVisibility,
+            Self::EnableEncryption => StateEventType::RoomEncryption,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Name(_) => "name",
+            Self::Topic(_) => "topic",
+            Self::JoinRule(_) => "join rule",
+            Self::HistoryVisibility(_) => "history visibility",
+            Self::EnableEncryption => "encryption",
+        }
+    }
+}
+
+/// The editor of the name, topic, join rule, history visibility and
+/// encryption of the room.
+#[derive(Default)]
+pub struct RoomSettingsView {
+    /// The room whose settings are edited.
+    room: Option<Room>,
+
+    list_state: ListState,
+
+    /// The input for the new name or topic, if the user is editing one of
+    /// them.
+    input: Option<(Field, PopupInput)>,
+
+    /// Has the user asked once to enable the encryption? Since it can't be
+    /// disabled afterwards, it must be confirmed.
+    is_confirming_encryption: bool,
+}
+
+impl RoomSettingsView {
+    /// Is the user writing the new name or topic of the room?
+    pub fn is_editing_text(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Handle a key press, and return whether it has been used by the
+    /// settings editor.
+    pub fn handle_key_press(&mut self, key: KeyEvent, status_handle: &StatusHandle) -> bool {
+        use KeyCode::*;
+
+        if let Some((field, input)) = &mut self.input {
+            match key.code {
+                Enter => {
+                    let value = input.get_input();
+                    let change = match field {
+                        Field::Topic => Change::Topic(value),
+                        _ => Change::Name(value.trim().to_owned()),
+                    };
+
+                    self.input = None;
+                    self.apply(change, status_handle);
+                }
+                Esc => self.input = None,
+                _ => input.handle_key_press(key),
+            }
+
+            return true;
+        }
+
+        match key.code {
+            Char('j') => self.select(|list_state| list_state.select_next()),
+            Char('k') => self.select(|list_state| list_state.select_previous()),
+            Enter => self.edit_selected_field(status_handle),
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn select(&mut self, select: impl FnOnce(&mut ListState)) {
+        select(&mut self.list_state);
+        self.is_confirming_encryption = false;
+    }
+
+    /// Edit the selected setting: the name and the topic are written in a
+    /// popup, the join rule and history visibility switch to the next value.
+    fn edit_selected_field(&mut self, status_handle: &StatusHandle) {
+        let Some(room) = &self.room else {
+            return;
+        };
+
+        let Some(field) = self.list_state.selected().and_then(|index| FIELDS.get(index)) else {
+            status_handle.set_message("no selected setting".to_owned());
+            return;
+        };
+
+        match field {
+            Field::Name | Field::Topic => {
+                let (title, current) = match field {
+                    Field::Name => ("New room name (Enter: save, Esc: cancel)", room.name()),
+                    _ => ("New room topic (Enter: save, Esc: cancel)", room.topic()),
+                };
+
+                let mut input = PopupInputBuilder::new(title, "").build();
+                input.set_input(&current.unwrap_or_default());
+                self.input = Some((*field, input));
+            }
+
+            Field::JoinRule => {
+                let join_rule = next_join_rule(room.join_rule().as_ref());
+                self.apply(Change::JoinRule(join_rule), status_handle);
+            }
+
+            Field::HistoryVisibility => {
+                let visibility = next_history_visibility(&room.history_visibility_or_default());
+                self.apply(Change::HistoryVisibility(visibility), status_handle);
+            }
+
+            Field::Encryption => {
+                if room.encryption_state().is_encrypted() {
+                    status_handle.set_message("the encryption can't be disabled".to_owned());
+                } else if self.is_confirming_encryption {
+                    self.is_confirming_encryption = false;
+                    self.apply(Change::EnableEncryption, status_handle);
+                } else {
+                    self.is_confirming_encryption = true;
+                    status_handle.set_message(
+                        "press Enter again to enable the encryption, it can't be disabled later"
+                            .to_owned(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Apply the change in the background.
+    fn apply(&self, change: Change, status_handle: &StatusHandle) {
+        let Some(room) = self.room.clone() else {
+            return;
+        };
+
+        let status_handle = status_handle.clone();
+
+        spawn(async move {
+            let label = change.label();
+
+            match change.apply(&room).await {
+                Ok(()) => status_handle.set_message(format!("{label} changed!")),
+                Err(err) => status_handle.set_message(err),
+            }
+        });
+    }
+
+    pub fn render(&mut self, room: Option<&Room>, area: Rect, buf: &mut Buffer) {
+        let Some(room) = room else {
+            Paragraph::new("(room disappeared in the room list service)")
+                .fg(theme().text)
+                .wrap(Wrap { trim: false })
+                .render(area, buf);
+            return;
+        };
+
+        if self.room.as_ref().is_none_or(|current| current.room_id() != room.room_id()) {
+            self.room = Some(room.clone());
+            self.input = None;
+            self.is_confirming_encryption = false;
+        }
+
+        let items = FIELDS.iter().map(|field| {
+            let (label, value) = match field {
+                Field::Name => ("Name", room.name().unwrap_or_default()),
+                Field::Topic => ("Topic", room.topic().unwrap_or_default()),
+                Field::JoinRule => {
+                    ("Join rule", join_rule_label(room.join_rule().as_ref()).to_owned())
+                }
+                Field::HistoryVisibility => {
+                    ("History visibility", room.history_visibility_or_default().as_str().to_owned())
+                }
+                Field::Encryption => {
+                    let value =
+                        if room.encryption_state().is_encrypted() { "enabled" } else { "disabled" };
+                    ("Encryption", value.to_owned())
+                }
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::from(format!("{label:>18}: ")).fg(tailwind::SKY.c400),
+                Span::from(value),
+            ]))
+        });
+
+        let list = List::new(items)
+            .fg(theme().text)
+            .highlight_symbol(">")
+            .highlight_style(theme().selected_fg);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+
+        if let Some((_, input)) = &mut self.input {
+            input.render(area, buf);
+        }
+    }
+}
+
+fn join_rule_label(join_rule: Option<&JoinRule>) -> &'static str {
+    match join_rule {
+        None => "unknown",
+        Some(JoinRule::Public) => "public",
+        Some(JoinRule::Invite) => "invite only",
+        Some(JoinRule::Knock) => "knock",
+        Some(JoinRule::Restricted(_)) => "restricted",
+        Some(JoinRule::KnockRestricted(_)) => "knock or restricted",
+        Some(JoinRule::Private) => "private",
+        Some(_) => "custom",
+    }
+}
+
+/// The join rule to switch to from the given one: only the join rules which
+/// don't need a list of allowed rooms are cycled through.
+fn next_join_rule(join_rule: Option<&JoinRule>) -> JoinRule {
+    match join_rule {
+        Some(JoinRule::Invite) => JoinRule::Public,
+        Some(JoinRule::Public) => JoinRule::Knock,
+        _ => JoinRule::Invite,
+    }
+}
+
+fn next_history_visibility(visibility: &HistoryVisibility) -> HistoryVisibility {
+    match visibility {
+        HistoryVisibility::Shared => HistoryVisibility::Invited,
+        HistoryVisibility::Invited => HistoryVisibility::Joined,
+        HistoryVisibility::Joined => HistoryVisibility::WorldReadable,
+        _ => HistoryVisibility::Shared,
+    }
+}
@@ -0,0 +1,135 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use clap::ValueEnum;
+use matrix_sdk::ruma::MilliSecondsSinceUnixEpoch;
+use matrix_sdk_ui::{
+    Timeline,
+    timeline::{EventTimelineItem, MsgLikeContent, MsgLikeKind, TimelineItemContent},
+};
+
+use crate::widgets::status::StatusHandle;
+
+/// The number of events requested by each back-pagination, when exporting
+/// the whole timeline.
+const PAGINATION_BATCH_SIZE: u16 = 50;
+
+/// The format of an exported timeline.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    /// A JSON array of the original events, as received from the homeserver.
+    Json,
+
+    /// A plain-text transcript, with one line per message.
+    Text,
+}
+
+/// Write the events of the timeline to the file, after paginating backwards
+/// until the start of the room if `paginate_to_start` is set.
+///
+/// The progress and the outcome are shown in the status bar.
+pub async fn export_timeline(
+    timeline: Arc<Timeline>,
+    path: PathBuf,
+    format: ExportFormat,
+    paginate_to_start: bool,
+    status_handle: StatusHandle,
+) {
+    if paginate_to_start {
+        loop {
+            match timeline.paginate_backwards(PAGINATION_BATCH_SIZE).await {
+                Ok(true) => break,
+                Ok(false) => {
+                    let num_items = timeline.items().await.len();
+                    status_handle.set_message(format!("exporting: {num_items} items loaded…"));
+                }
+                Err(err) => {
+                    status_handle.set_error(format!("couldn't paginate the timeline: {err}"));
+                    return;
+                }
+            }
+        }
+    }
+
+    let items = timeline.items().await;
+    let events = items.iter().filter_map(|item| item.as_event()).collect::<Vec<_>>();
+
+    let content = match format {
+        ExportFormat::Json => {
+            // The local echoes haven't been sent yet, so they don't have any JSON.
+            let raw_events =
+                events.iter().filter_map(|event| event.original_json()).collect::<Vec<_>>();
+
+            match serde_json::to_string_pretty(&raw_events) {
+                Ok(json) => json,
+                Err(err) => {
+                    status_handle.set_error(format!("couldn't serialize the events: {err}"));
+                    return;
+                }
+            }
+        }
+        ExportFormat::Text => events.iter().copied().filter_map(transcript_line).fold(
+            String::new(),
+            |mut transcript, line| {
+                transcript.push_str(&line);
+                transcript.push('\n');
+                transcript
+            },
+        ),
+    };
+
+    match fs::write(&path, content) {
+        Ok(()) => status_handle.set_message(format!(
+            "{} events exported to {}",
+            events.len(),
+            path.display()
+        )),
+        Err(err) => status_handle.set_error(format!("couldn't write to {}: {err}", path.display())),
+    }
+}
+
+/// The line of the transcript for the event, or `None` if it isn't a message.
+fn transcript_line(event: &EventTimelineItem) -> Option<String> {
+    let body = match event.content() {
+        TimelineItemContent::MsgLike(MsgLikeContent { kind, .. }) => match kind {
+            MsgLikeKind::Message(message) => match message.effect() {
+                Some(effect) => format!("({effect}) {}", message.body()),
+                None => message.body().to_owned(),
+            },
+            MsgLikeKind::Poll(poll) => {
+                format!("(poll) {}", poll.fallback_text().unwrap_or_default())
+            }
+            MsgLikeKind::Sticker(sticker) => format!("(sticker) {}", sticker.content().body),
+            MsgLikeKind::Redacted => "(redacted)".to_owned(),
+            MsgLikeKind::UnableToDecrypt(_) => "(unable to decrypt)".to_owned(),
+        },
+        _ => return None,
+    };
+
+    Some(format!("[{}] {}: {body}", format_timestamp(event.timestamp()), event.sender()))
+}
+
+/// Format the timestamp as a UTC date and time, e.g. `2024-05-21 14:03:59`.
+pub(crate) fn format_timestamp(timestamp: MilliSecondsSinceUnixEpoch) -> String {
+    let seconds = i64::from(timestamp.as_secs());
+    let (days, seconds_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+    // Convert the number of days since the epoch to a date in the proleptic
+    // Gregorian calendar, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+    let shifted_days = days + 719_468;
+    let era = shifted_days.div_euclid(146_097);
+    let day_of_era = shifted_days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
@@ -0,0 +1,198 @@
+use crossterm::event::KeyEvent;
+use matrix_sdk::{
+    Room,
+    ruma::{
+        OwnedEventId,
+        api::client::{
+            filter::RoomEventFilter,
+            search::search_events::v3::{Categories, Criteria, Request},
+        },
+        events::{
+            AnySyncMessageLikeEvent, AnySyncTimelineEvent, room::message::SyncRoomMessageEvent,
+        },
+    },
+};
+use ratatui::{prelude::*, widgets::*};
+use style::palette::tailwind;
+use tui_textarea::TextArea;
+
+/// A bar to find the messages of the room containing some text, and to jump
+/// from one to the other in the timeline.
+pub struct FindBar {
+    textarea: TextArea<'static>,
+
+    /// The query whose matches are listed, to know when it must be searched
+    /// again.
+    searched_query: Option<String>,
+
+    /// The events matching the query, from the most recent to the oldest.
+    matches: Vec<OwnedEventId>,
+
+    /// The index of the match selected in the timeline, if any.
+    current: Option<usize>,
+
+    /// Whether the server is searched too, for the events which aren't in the
+    /// event cache.
+    include_server: bool,
+}
+
+impl FindBar {
+    pub fn new() -> Self {
+        let mut textarea = TextArea::default();
+        textarea.set_placeholder_text("(Enter the text to find)");
+        textarea.set_placeholder_style(Style::new().fg(tailwind::GRAY.c50));
+
+        Self {
+            textarea,
+            searched_query: None,
+            matches: Vec::new(),
+            current: None,
+            include_server: false,
+        }
+    }
+
+    /// Edit the query.
+    pub fn handle_key_press(&mut self, key: KeyEvent) {
+        self.textarea.input(key);
+    }
+
+    pub fn query(&self) -> String {
+        self.textarea.lines().join(" ")
+    }
+
+    /// Has the query or the scope of the search changed since the last
+    /// search?
+    pub fn needs_search(&self) -> bool {
+        self.searched_query.as_deref() != Some(&self.query())
+    }
+
+    pub fn include_server(&self) -> bool {
+        self.include_server
+    }
+
+    /// Search the server too, or only the event cache; the query will be
+    /// searched again.
+    pub fn toggle_server(&mut self) {
+        self.include_server = !self.include_server;
+        self.searched_query = None;
+    }
+
+    pub fn set_matches(&mut self, query: String, matches: Vec<OwnedEventId>) {
+        self.searched_query = Some(query);
+        self.matches = matches;
+        self.current = None;
+    }
+
+    /// Select the match older than the current one, or the most recent one
+    /// if none is selected yet.
+    pub fn older_match(&mut self) -> Option<&OwnedEventId> {
+        let index = self.current.map_or(0, |current| current + 1);
+        self.select(index)
+    }
+
+    /// Select the match more recent than the current one.
+    pub fn newer_match(&mut self) -> Option<&OwnedEventId> {
+        let index = self.current?.checked_sub(1)?;
+        self.select(index)
+    }
+
+    fn select(&mut self, index: usize) -> Option<&OwnedEventId> {
+        let event_id = self.matches.get(index)?;
+        self.current = Some(index);
+        Some(event_id)
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let summary = if self.searched_query.is_none() {
+            String::new()
+        } else if self.matches.is_empty() {
+            "no matches".to_owned()
+        } else {
+            let current =
+                self.current.map_or_else(|| "-".to_owned(), |index| (index + 1).to_string());
+            format!("{current}/{}", self.matches.len())
+        };
+
+        let scope = if self.include_server { "cache + server" } else { "cache" };
+        let status = format!(" {summary} ({scope}) ");
+
+        let [label_area, text_area, status_area] = Layout::horizontal([
+            Constraint::Length(6),
+            Constraint::Min(0),
+            Constraint::Length(u16::try_from(status.len()).unwrap_or(u16::MAX)),
+        ])
+        .areas(area);
+
+        let style = Style::new().fg(tailwind::GRAY.c50).bg(tailwind::BLUE.c600);
+
+        Paragraph::new("Find:").style(style).bold().render(label_area, buf);
+        Paragraph::new(status).style(style).render(status_area, buf);
+
+        self.textarea.set_block(Block::new().borders(Borders::NONE).bg(tailwind::BLUE.c400));
+        self.textarea.set_cursor_style(Style::new().add_modifier(Modifier::REVERSED));
+        self.textarea.set_cursor_line_style(Style::new());
+        self.textarea.render(text_area, buf);
+    }
+}
+
+/// Find the messages of the room containing the query, ignoring the case,
+/// from the most recent to the oldest.
+///
+/// The events of the event cache come first; the ones only found by the
+/// server, if it's searched, come after them, by rank.
+pub async fn find_messages(
+    room: &Room,
+    query: &str,
+    include_server: bool,
+) -> Result<Vec<OwnedEventId>, String> {
+    let query_lowercase = query.to_lowercase();
+
+    let (cache, _drop_handles) = room.event_cache().await.map_err(|err| err.to_string())?;
+
+    let mut matches = cache
+        .events()
+        .await
+        .iter()
+        .rev()
+        .filter_map(|event| {
+            let Ok(AnySyncTimelineEvent::MessageLike(AnySyncMessageLikeEvent::RoomMessage(
+                SyncRoomMessageEvent::Original(event),
+            ))) = event.raw().deserialize()
+            else {
+                return None;
+            };
+
+            let body = event.content.msgtype.body().to_lowercase();
+            body.contains(&query_lowercase).then_some(event.event_id)
+        })
+        .collect::<Vec<_>>();
+
+    if include_server {
+        let mut filter = RoomEventFilter::default();
+        filter.rooms = Some(vec![room.room_id().to_owned()]);
+
+        let mut criteria = Criteria::new(query.to_owned());
+        criteria.filter = filter;
+
+        let mut categories = Categories::new();
+        categories.room_events = Some(criteria);
+
+        let response =
+            room.client().send(Request::new(categories)).await.map_err(|err| err.to_string())?;
+
+        for result in response.search_categories.room_events.results {
+            let Some(event_id) = result
+                .result
+                .and_then(|event| event.get_field::<OwnedEventId>("event_id").ok().flatten())
+            else {
+                continue;
+            };
+
+            if !matches.contains(&event_id) {
+                matches.push(event_id);
+            }
+        }
+    }
+
+    Ok(matches)
+}
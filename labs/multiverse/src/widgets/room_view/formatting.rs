@@ -0,0 +1,65 @@
+//! Helpers to build the formatted body of the messages sent by the commands.
+
+/// Build the HTML of a message with each character of the text in the next
+/// color of the rainbow.
+pub fn rainbow_html(text: &str) -> String {
+    let num_chars = text.chars().filter(|c| !c.is_whitespace()).count().max(1);
+    let mut html = String::new();
+    let mut index = 0;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            html.push(c);
+            continue;
+        }
+
+        let (red, green, blue) = hue_to_rgb(360.0 * index as f64 / num_chars as f64);
+        html.push_str(&format!(
+            "<font data-mx-color=\"#{red:02x}{green:02x}{blue:02x}\">{}</font>",
+            escape_html(&c.to_string())
+        ));
+
+        index += 1;
+    }
+
+    html
+}
+
+/// The plain text fallback of an HTML message body: the tags are removed and
+/// the common entities are unescaped.
+pub fn html_to_plain(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The fully saturated, fully bright color with the given hue, in degrees.
+fn hue_to_rgb(hue: f64) -> (u8, u8, u8) {
+    let x = 1.0 - ((hue / 60.0) % 2.0 - 1.0).abs();
+
+    let (red, green, blue) = match hue as u32 / 60 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+
+    let to_u8 = |value: f64| (value * 255.0).round() as u8;
+    (to_u8(red), to_u8(green), to_u8(blue))
+}
@@ -0,0 +1,354 @@
+//! Inline previews of the images of the timeline, with the graphics protocols
+//! of the terminals supporting them.
+//!
+//! The kitty and iTerm2 protocols take the encoded image as is, so the
+//! thumbnails downloaded from the homeserver can be displayed directly. Sixel
+//! would require encoding the pixels of the images as sixels, which multiverse
+//! doesn't do, so the terminals only supporting sixel get the text placeholder.
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use crossterm::{cursor::MoveTo, queue};
+use matrix_sdk::{
+    Client,
+    locks::Mutex,
+    media::{MediaFormat, MediaRequestParameters, MediaThumbnailSettings},
+    ruma::{
+        OwnedMxcUri,
+        events::room::{MediaSource, message::ImageMessageEventContent},
+        uint,
+    },
+};
+use tokio::{fs, spawn};
+use tracing::warn;
+
+/// The number of terminal rows taken by a preview.
+pub const PREVIEW_ROWS: u16 = 8;
+
+/// The number of terminal columns taken by a preview.
+const PREVIEW_COLUMNS: u16 = 24;
+
+/// The terminal graphics protocols supported for the previews.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GraphicsProtocol {
+    Kitty,
+    Iterm2,
+}
+
+impl GraphicsProtocol {
+    /// Guess the protocol supported by the terminal, from its environment
+    /// variables.
+    fn detect() -> Option<Self> {
+        let term = env::var("TERM").unwrap_or_default();
+        let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term_program == "ghostty"
+        {
+            Some(Self::Kitty)
+        } else if matches!(term_program.as_str(), "iTerm.app" | "WezTerm") {
+            Some(Self::Iterm2)
+        } else {
+            None
+        }
+    }
+
+    /// Can this protocol display an image encoded in the given data?
+    fn supports(self, data: &[u8]) -> bool {
+        let is_png = data.starts_with(b"\x89PNG\r\n\x1a\n");
+
+        match self {
+            // Kitty only decodes PNG on its own.
+            Self::Kitty => is_png,
+            Self::Iterm2 => {
+                is_png
+                    || data.starts_with(&[0xff, 0xd8])
+                    || data.starts_with(b"GIF87a")
+                    || data.starts_with(b"GIF89a")
+            }
+        }
+    }
+}
+
+enum Preview {
+    Loading,
+    Ready { id: u32, data: Arc<Vec<u8>> },
+    Unavailable,
+}
+
+/// Where a preview is displayed on the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Placement {
+    id: u32,
+    x: u16,
+    y: u16,
+}
+
+/// Downloads the thumbnails of the images, and displays them at the positions
+/// found when rendering the timeline.
+pub struct ImagePreviews {
+    client: Client,
+
+    /// The directory where the downloaded thumbnails are cached.
+    cache_dir: PathBuf,
+
+    /// The protocol of the terminal, or `None` if it can't display images.
+    protocol: Option<GraphicsProtocol>,
+
+    previews: Arc<Mutex<HashMap<OwnedMxcUri, Preview>>>,
+
+    next_id: u32,
+
+    /// The kitty images which have already been sent to the terminal.
+    transmitted: HashSet<u32>,
+
+    /// The placements found when rendering the current frame.
+    placements: Vec<Placement>,
+
+    /// The data of the images placed in the current frame.
+    placed_images: HashMap<u32, Arc<Vec<u8>>>,
+
+    /// The placements currently displayed by the terminal.
+    displayed: Vec<Placement>,
+}
+
+impl ImagePreviews {
+    pub fn new(client: Client, cache_dir: PathBuf) -> Self {
+        Self {
+            client,
+            cache_dir,
+            protocol: GraphicsProtocol::detect(),
+            previews: Default::default(),
+            next_id: 1,
+            transmitted: HashSet::new(),
+            placements: Vec::new(),
+            placed_images: HashMap::new(),
+            displayed: Vec::new(),
+        }
+    }
+
+    /// Forget about the placements of the previous frame.
+    pub fn begin_frame(&mut self) {
+        self.placements.clear();
+        self.placed_images.clear();
+    }
+
+    /// The number of rows to reserve below the image message, if its preview
+    /// can be displayed.
+    ///
+    /// The first call for an image starts downloading its thumbnail.
+    pub fn rows(&mut self, content: &ImageMessageEventContent) -> Option<u16> {
+        let protocol = self.protocol?;
+        let source = content.info.as_ref().and_then(|info| info.thumbnail_source.clone());
+        let source = source.unwrap_or_else(|| content.source.clone());
+        let uri = mxc_uri(&source);
+
+        let mut previews = self.previews.lock();
+
+        match previews.get(&uri) {
+            Some(Preview::Ready { .. }) => Some(PREVIEW_ROWS),
+            Some(Preview::Loading | Preview::Unavailable) => None,
+            None => {
+                previews.insert(uri.clone(), Preview::Loading);
+
+                let id = self.next_id;
+                self.next_id += 1;
+
+                spawn(load_preview(
+                    self.client.clone(),
+                    self.cache_dir.clone(),
+                    self.previews.clone(),
+                    protocol,
+                    id,
+                    uri,
+                    source,
+                ));
+
+                None
+            }
+        }
+    }
+
+    /// Display the preview of the image at the given position of the screen,
+    /// if it's ready.
+    pub fn place(&mut self, content: &ImageMessageEventContent, x: u16, y: u16) {
+        let source = content.info.as_ref().and_then(|info| info.thumbnail_source.as_ref());
+        let uri = mxc_uri(source.unwrap_or(&content.source));
+
+        if let Some(Preview::Ready { id, data }) = self.previews.lock().get(&uri) {
+            self.placements.push(Placement { id: *id, x, y });
+            self.placed_images.insert(*id, data.clone());
+        }
+    }
+
+    /// Hide all the previews, e.g. because a popup covers the timeline.
+    pub fn hide(&mut self) {
+        self.begin_frame();
+    }
+
+    /// Have the previews moved since they've been displayed?
+    ///
+    /// The screen needs to be cleared before flushing them, to remove the
+    /// previews from their old positions.
+    pub fn have_changed(&self) -> bool {
+        self.placements != self.displayed
+    }
+
+    /// Display the previews of the current frame.
+    pub fn flush(&mut self, writer: &mut impl Write) -> io::Result<()> {
+        let Some(protocol) = self.protocol else {
+            return Ok(());
+        };
+
+        for placement in &self.placements {
+            let Some(data) = self.placed_images.get(&placement.id) else {
+                continue;
+            };
+
+            match protocol {
+                GraphicsProtocol::Kitty => {
+                    if self.transmitted.insert(placement.id) {
+                        transmit_kitty_image(writer, placement.id, data)?;
+                    }
+
+                    queue!(writer, MoveTo(placement.x, placement.y))?;
+                    // `C=1` keeps the cursor where it is.
+                    write!(
+                        writer,
+                        "\x1b_Ga=p,i={},c={PREVIEW_COLUMNS},r={PREVIEW_ROWS},C=1,q=2\x1b\\",
+                        placement.id
+                    )?;
+                }
+
+                GraphicsProtocol::Iterm2 => {
+                    queue!(writer, MoveTo(placement.x, placement.y))?;
+                    write!(
+                        writer,
+                        "\x1b]1337;File=inline=1;width={PREVIEW_COLUMNS};height={PREVIEW_ROWS};\
+                         preserveAspectRatio=1:{}\x07",
+                        BASE64.encode(data.as_slice())
+                    )?;
+                }
+            }
+        }
+
+        self.displayed = self.placements.clone();
+        writer.flush()
+    }
+}
+
+/// Send an image to kitty, without displaying it yet.
+///
+/// The data is sent in chunks, as required by the protocol.
+fn transmit_kitty_image(writer: &mut impl Write, id: u32, data: &[u8]) -> io::Result<()> {
+    let encoded = BASE64.encode(data);
+    let mut chunks = encoded.as_bytes().chunks(4096).peekable();
+    let mut is_first = true;
+
+    while let Some(chunk) = chunks.next() {
+        let more = u8::from(chunks.peek().is_some());
+        let chunk = std::str::from_utf8(chunk).expect("base64 is ASCII");
+
+        if is_first {
+            write!(writer, "\x1b_Ga=t,f=100,i={id},q=2,m={more};{chunk}\x1b\\")?;
+            is_first = false;
+        } else {
+            write!(writer, "\x1b_Gm={more};{chunk}\x1b\\")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a downloaded thumbnail to the cache directory.
+async fn cache_preview(cache_dir: &Path, path: &Path, data: &[u8]) -> io::Result<()> {
+    fs::create_dir_all(cache_dir).await?;
+    fs::write(path, data).await
+}
+
+fn mxc_uri(source: &MediaSource) -> OwnedMxcUri {
+    match source {
+        MediaSource::Plain(uri) => uri.clone(),
+        MediaSource::Encrypted(file) => file.url.clone(),
+    }
+}
+
+/// Load a thumbnail from the cache directory, or download it from the
+/// homeserver.
+///
+/// Only the thumbnails of the unencrypted images are cached in the directory:
+/// the decrypted ones are only kept in the media store of the client, so they
+/// never end up on disk in plaintext.
+async fn load_preview(
+    client: Client,
+    cache_dir: PathBuf,
+    previews: Arc<Mutex<HashMap<OwnedMxcUri, Preview>>>,
+    protocol: GraphicsProtocol,
+    id: u32,
+    uri: OwnedMxcUri,
+    source: MediaSource,
+) {
+    let path = match &source {
+        MediaSource::Plain(_) => {
+            // The MXC URIs can't contain any path separator once sanitized.
+            let file_name: String = uri
+                .as_str()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            Some(cache_dir.join(file_name))
+        }
+        MediaSource::Encrypted(_) => None,
+    };
+
+    let cached = match &path {
+        Some(path) => fs::read(path).await.ok(),
+        None => None,
+    };
+
+    let data = match cached {
+        Some(data) => Some(data),
+        None => {
+            // The homeserver can't generate the thumbnails of encrypted media, whose source
+            // is already the thumbnail of the image when it has one.
+            let format = match &source {
+                MediaSource::Plain(_) => {
+                    MediaFormat::Thumbnail(MediaThumbnailSettings::new(uint!(320), uint!(240)))
+                }
+                MediaSource::Encrypted(_) => MediaFormat::File,
+            };
+            let request = MediaRequestParameters { source, format };
+
+            match client.media().get_media_content(&request, true).await {
+                Ok(data) => {
+                    if let Some(path) = &path
+                        && let Err(err) = cache_preview(&cache_dir, path, &data).await
+                    {
+                        warn!("couldn't cache the thumbnail of {uri}: {err}");
+                    }
+
+                    Some(data)
+                }
+                Err(err) => {
+                    warn!("couldn't download the thumbnail of {uri}: {err}");
+                    None
+                }
+            }
+        }
+    };
+
+    let preview = match data {
+        Some(data) if protocol.supports(&data) => Preview::Ready { id, data: Arc::new(data) },
+        _ => Preview::Unavailable,
+    };
+
+    previews.lock().insert(uri, preview);
+}
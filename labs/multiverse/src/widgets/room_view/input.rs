@@ -1,10 +1,18 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{CommandFactory as _, Parser, Subcommand};
 use crossterm::event::KeyEvent;
-use matrix_sdk::Room;
+use matrix_sdk::{
+    Room,
+    ruma::{OwnedEventId, OwnedUserId},
+};
+use matrix_sdk_ui::timeline::TimelineEventItemId;
 use ratatui::{prelude::*, widgets::*};
 use style::palette::tailwind;
 use tui_textarea::TextArea;
 
+use super::export::ExportFormat;
+
 #[derive(Debug, Parser)]
 #[command(name = "multiverse", disable_help_flag = true, disable_help_subcommand = true)]
 struct Cli {
@@ -14,10 +22,113 @@ struct Cli {
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
-    Invite { user_id: String },
+    Invite {
+        user_id: String,
+    },
     Leave,
     Subscribe,
     Unsubscribe,
+    /// Upload a file; the thumbnail of an image is generated, unless another
+    /// one is given.
+    Attach {
+        path: PathBuf,
+        #[arg(long)]
+        thumbnail: Option<PathBuf>,
+    },
+    /// Send an audio file as a voice message; its duration, in seconds, is
+    /// only needed when it can't be read from the file.
+    Voice {
+        path: PathBuf,
+        #[arg(long)]
+        duration: Option<u64>,
+    },
+    /// Create a private room with the given name, and select it.
+    Create {
+        #[arg(required = true)]
+        name: Vec<String>,
+        #[arg(long)]
+        encrypted: bool,
+    },
+    /// Select the DM with the given user, creating it if there's none yet.
+    Dm {
+        user_id: String,
+    },
+    /// Join a room from its ID or one of its aliases, and select it.
+    Join {
+        room_id_or_alias: String,
+    },
+    /// Open a `matrix.to` or `matrix:` permalink to a room or an event,
+    /// joining the room if needed.
+    Goto {
+        uri: String,
+    },
+    /// Send an emote, e.g. `/me waves`.
+    Me {
+        #[arg(required = true, allow_hyphen_values = true)]
+        text: Vec<String>,
+    },
+    /// Send a message formatted with the given HTML.
+    Html {
+        #[arg(required = true, allow_hyphen_values = true)]
+        html: Vec<String>,
+    },
+    /// Send a message with each letter in another color of the rainbow.
+    Rainbow {
+        #[arg(required = true, allow_hyphen_values = true)]
+        text: Vec<String>,
+    },
+    /// Change our display name in this room only.
+    Myroomnick {
+        #[arg(required = true)]
+        name: Vec<String>,
+    },
+    /// Give a power level to a member, 100 by default to make them an admin.
+    Op {
+        user_id: String,
+        #[arg(default_value_t = 100)]
+        power_level: i64,
+    },
+    /// Export the timeline of the selected room to a file.
+    Export {
+        path: PathBuf,
+        #[arg(long, value_enum, default_value = "json")]
+        format: ExportFormat,
+        /// Paginate backwards until the start of the room first.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Log out, delete the session and its stores, and quit.
+    Logout,
+}
+
+/// The names of the commands, to complete them.
+fn command_names() -> Vec<String> {
+    Cli::command().get_subcommands().map(|command| command.get_name().to_owned()).collect()
+}
+
+/// What the word being written at the end of the input can be completed to.
+pub enum Completion {
+    /// The name of a command, written after the `/`.
+    Command(Vec<String>),
+
+    /// The ID of a user: the word starts with a `@`, or it's an argument of a
+    /// command.
+    UserId { prefix: String },
+}
+
+/// The longest prefix shared by `first` and all the `others`.
+fn common_prefix<'a>(first: &'a str, others: &[String]) -> &'a str {
+    let len = others.iter().fold(first.len(), |len, other| {
+        let shared = first
+            .char_indices()
+            .zip(other.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(first.len().min(other.len()), |((index, _), _)| index);
+
+        len.min(shared)
+    });
+
+    &first[..len]
 }
 
 pub enum MessageOrCommand {
@@ -25,11 +136,37 @@ pub enum MessageOrCommand {
     Command(Command),
 }
 
+/// The maximum number of lines the input area grows to, before scrolling.
+const MAX_HEIGHT: u16 = 5;
+
+/// The event a message is being written in reply to.
+pub struct ReplyTo {
+    pub event_id: OwnedEventId,
+    pub sender: OwnedUserId,
+    /// The text of the replied-to event, to quote it above the text area.
+    pub body: String,
+}
+
+/// What the message being written is about, if it's not a new message.
+pub enum ComposerMode {
+    /// The message is a reply to another event.
+    Reply(ReplyTo),
+
+    /// The message is the new content of one of our own events.
+    Edit { item_id: TimelineEventItemId },
+}
+
 /// A widget representing a text input to send messages to a room.
 #[derive(Default)]
 pub struct Input {
     /// The text area that will keep track of what the user has input.
     textarea: TextArea<'static>,
+
+    /// Whether the key presses go to the text area.
+    focused: bool,
+
+    /// What the message is about, or `None` for a new message.
+    mode: Option<ComposerMode>,
 }
 
 impl Input {
@@ -37,17 +174,75 @@ impl Input {
     pub fn new() -> Self {
         let textarea = Self::text_area();
 
-        Self { textarea }
+        Self { textarea, focused: false, mode: None }
     }
 
     /// Receive a key press event and handle it.
+    ///
+    /// This handles the cursor movements and the edition of the text, see
+    /// [`TextArea::input`]; the submission is up to the caller.
     pub fn handle_key_press(&mut self, event: KeyEvent) {
         self.textarea.input(event);
     }
 
+    /// Start a new line at the cursor position.
+    pub fn insert_newline(&mut self) {
+        self.textarea.insert_newline();
+    }
+
+    /// Is the input area focused, i.e. should it receive the key presses?
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Give the focus to the input area.
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    /// Take the focus away from the input area, keeping its content.
+    pub fn unfocus(&mut self) {
+        self.focused = false;
+    }
+
+    /// Write the message in reply to another event, or as an edit of one of
+    /// our own events.
+    pub fn set_mode(&mut self, mode: ComposerMode) {
+        self.mode = Some(mode);
+    }
+
+    /// What the message being written is about, or `None` for a new message.
+    pub fn mode(&self) -> Option<&ComposerMode> {
+        self.mode.as_ref()
+    }
+
+    /// Replace the text of the input area, e.g. with the body of the message
+    /// to edit, and put the cursor at its end.
+    pub fn set_text(&mut self, text: &str) {
+        self.textarea = Self::text_area();
+        self.textarea.insert_str(text);
+    }
+
+    /// The height the input area needs to display its content.
+    pub fn height(&self) -> u16 {
+        let text_height = if self.focused {
+            u16::try_from(self.textarea.lines().len()).unwrap_or(MAX_HEIGHT).clamp(1, MAX_HEIGHT)
+        } else {
+            1
+        };
+
+        // One more line to tell what the message is about.
+        text_height + u16::from(self.mode.is_some())
+    }
+
+    /// Get the raw text of the input area.
+    pub fn text(&self) -> String {
+        self.textarea.lines().join("\n")
+    }
+
     /// Get the currently input text.
     pub fn get_input(&self) -> Result<MessageOrCommand, clap::Error> {
-        let input = self.textarea.lines().join("\n");
+        let input = self.text();
 
         if let Some(input) = input.strip_prefix("/") {
             let arguments = input.split_whitespace();
@@ -63,15 +258,68 @@ impl Input {
         }
     }
 
+    /// What the last word of the input can be completed to, if anything.
+    ///
+    /// The user IDs aren't known by the input area, so their candidates must
+    /// be found by the caller.
+    pub fn completion(&self) -> Option<Completion> {
+        let text = self.text();
+
+        if let Some(command) = text.strip_prefix('/')
+            && !command.contains(char::is_whitespace)
+        {
+            let candidates =
+                command_names().into_iter().filter(|name| name.starts_with(command)).collect();
+            return Some(Completion::Command(candidates));
+        }
+
+        let word = text.rsplit(char::is_whitespace).next().unwrap_or_default();
+
+        if word.starts_with('@') || (text.starts_with('/') && !word.is_empty()) {
+            Some(Completion::UserId { prefix: word.to_owned() })
+        } else {
+            None
+        }
+    }
+
+    /// Complete the last word of the input with the given candidates: with
+    /// the candidate if there's only one, or with their common prefix
+    /// otherwise.
+    ///
+    /// Returns whether the input has changed.
+    pub fn complete(&mut self, candidates: &[String]) -> bool {
+        let text = self.text();
+        let word = text.rsplit(char::is_whitespace).next().unwrap_or_default();
+        let start = &text[..text.len() - word.len()];
+
+        let completed = match candidates {
+            [] => return false,
+            [candidate] => format!("{candidate} "),
+            [first, others @ ..] => common_prefix(first, others).to_owned(),
+        };
+
+        // The `/` of a command isn't part of its name.
+        let start = if start.is_empty() && text.starts_with('/') { "/" } else { start };
+
+        if format!("{start}{completed}") == text {
+            return false;
+        }
+
+        self.set_text(&format!("{start}{completed}"));
+        true
+    }
+
     /// Is the input area empty, returns false if the user hasn't input anything
     /// yet.
     pub fn is_empty(&self) -> bool {
         self.textarea.is_empty()
     }
 
-    /// Clear the text from the input area.
+    /// Clear the text from the input area, and go back to writing a new
+    /// message.
     pub fn clear(&mut self) {
         self.textarea = Self::text_area();
+        self.mode = None;
     }
 
     fn text_area() -> TextArea<'static> {
@@ -93,7 +341,9 @@ impl<'a> StatefulWidget for &'a mut Input {
         //
         // We assume that the encryption state is synced because the RoomListService
         // sets it as required state.
-        if let Some(room) = room.as_deref() {
+        if !self.focused {
+            self.textarea.set_placeholder_text("(Press i to write a message)");
+        } else if let Some(room) = room.as_deref() {
             let is_encrypted = room.encryption_state().is_encrypted();
 
             if is_encrypted {
@@ -108,6 +358,35 @@ impl<'a> StatefulWidget for &'a mut Input {
         // Let's first create a block to set the background color.
         let input_block = Block::new().borders(Borders::NONE).bg(tailwind::BLUE.c400);
 
+        // Only show the cursor when the key presses go to the textarea.
+        let cursor_style =
+            if self.focused { Style::new().add_modifier(Modifier::REVERSED) } else { Style::new() };
+        self.textarea.set_cursor_style(cursor_style);
+        self.textarea.set_cursor_line_style(Style::new());
+
+        // Tell what the message is about above the textarea.
+        let area = if let Some(mode) = &self.mode {
+            let [mode_area, area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+
+            let description = match mode {
+                ComposerMode::Reply(reply_to) => {
+                    format!("> In reply to {}: {}", reply_to.sender, reply_to.body)
+                }
+                ComposerMode::Edit { .. } => "> Editing a message".to_owned(),
+            };
+
+            Paragraph::new(description)
+                .italic()
+                .fg(tailwind::GRAY.c50)
+                .bg(tailwind::BLUE.c600)
+                .render(mode_area, buf);
+
+            area
+        } else {
+            area
+        };
+
         // Now we set the block and we render the textarea.
         self.textarea.set_block(input_block);
         self.textarea.render(area, buf);
@@ -1,49 +1,128 @@
-use std::sync::Arc;
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
-use crossterm::event::{Event, KeyCode, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use futures_util::StreamExt;
 use imbl::Vector;
-use input::MessageOrCommand;
+use input::{Completion, ComposerMode, MessageOrCommand, ReplyTo};
 use invited_room::InvitedRoomView;
 use matrix_sdk::{
-    Client, Room, RoomState,
+    Client, ComposerDraft, ComposerDraftType, Room, RoomMemberships, RoomState,
     locks::Mutex,
+    room::edit::EditedContent,
     ruma::{
-        OwnedEventId, OwnedRoomId, RoomId, UserId,
-        api::client::receipt::create_receipt::v3::ReceiptType,
-        events::room::message::RoomMessageEventContent,
+        EventId, Int, MatrixToUri, MatrixUri, OwnedEventId, OwnedRoomId, OwnedRoomOrAliasId,
+        OwnedServerName, OwnedUserId, RoomId, UserId,
+        api::client::{
+            receipt::create_receipt::v3::ReceiptType,
+            room::create_room::v3::{Request as CreateRoomRequest, RoomPreset},
+        },
+        events::{
+            InitialStateEvent,
+            poll::{
+                unstable_end::UnstablePollEndEventContent,
+                unstable_response::UnstablePollResponseEventContent,
+            },
+            room::{
+                encryption::RoomEncryptionEventContent,
+                message::{
+                    EmoteMessageEventContent, MessageType, RoomMessageEventContent,
+                    RoomMessageEventContentWithoutRelation,
+                },
+            },
+        },
+        matrix_uri::MatrixId,
     },
 };
 use matrix_sdk_ui::{
     Timeline,
-    timeline::{TimelineBuilder, TimelineFocus, TimelineItem},
+    timeline::{PollResult, TimelineBuilder, TimelineEventItemId, TimelineFocus, TimelineItem},
 };
 use ratatui::{prelude::*, widgets::*};
+use style::palette::tailwind;
 use tokio::{spawn, sync::OnceCell, task::JoinHandle};
 use tracing::info;
 
-use self::{details::RoomDetails, input::Input, timeline::TimelineView};
-use super::status::StatusHandle;
+use self::{
+    details::RoomDetails,
+    find::FindBar,
+    image_preview::ImagePreviews,
+    input::Input,
+    raw_json::RawJsonView,
+    reaction_picker::{PickerAction, ReactionPicker},
+    timeline::TimelineView,
+    typing::{TypingNotifier, TypingUsers},
+};
+use super::{
+    popup_input::{PopupInput, PopupInputBuilder},
+    status::StatusHandle,
+};
 use crate::{
-    HEADER_BG, NORMAL_ROW_COLOR, TEXT_COLOR, Timelines,
+    Timelines,
+    config::{Action, keymap, theme},
     widgets::{recovery::ShouldExit, room_view::timeline::TimelineListState},
 };
 
+mod attachment;
 mod details;
+pub(crate) mod export;
+mod find;
+mod formatting;
+mod image_preview;
 mod input;
 mod invited_room;
+mod raw_json;
+mod reaction_picker;
 mod timeline;
+mod typing;
 
 const DEFAULT_TILING_DIRECTION: Direction = Direction::Horizontal;
 
 pub struct DetailsState<'a> {
     selected_room: Option<&'a Room>,
     selected_item: Option<Arc<TimelineItem>>,
+    /// All the items of the selected timeline.
+    timeline_items: Vector<Arc<TimelineItem>>,
 }
 
 enum Mode {
-    Normal { invited_room_view: Option<InvitedRoomView> },
-    Details { tiling_direction: Direction, view: RoomDetails },
+    Normal {
+        invited_room_view: Option<InvitedRoomView>,
+    },
+    Details {
+        tiling_direction: Direction,
+        view: RoomDetails,
+    },
+    /// Waiting for the user to confirm the redaction of an event, with an
+    /// optional reason.
+    Redact {
+        item_id: TimelineEventItemId,
+        reason: PopupInput,
+    },
+    /// Waiting for the user to confirm that they want to leave the room.
+    ConfirmLeave {
+        room: Room,
+    },
+    /// Waiting for the user to pick the reaction to toggle on an item.
+    React {
+        item_id: TimelineEventItemId,
+        picker: ReactionPicker,
+    },
+    /// Finding the messages of the room containing some text.
+    Find {
+        bar: FindBar,
+    },
+    /// Showing the raw JSON of a timeline item.
+    RawJson {
+        view: RawJsonView,
+    },
 }
 
 enum TimelineKind {
@@ -63,6 +142,24 @@ enum TimelineKind {
         /// the `items` field over time.
         task: JoinHandle<()>,
     },
+
+    /// A timeline focused on an event, opened from a permalink.
+    Event {
+        room: OwnedRoomId,
+        event_id: OwnedEventId,
+        /// The event-focused timeline around this event.
+        timeline: Arc<OnceCell<Arc<Timeline>>>,
+        /// Items in the event-focused timeline.
+        items: Arc<Mutex<Vector<Arc<TimelineItem>>>>,
+        /// Task listening to updates from the event-focused timeline, to
+        /// maintain the `items` field over time.
+        task: JoinHandle<()>,
+        /// Has the focused event been selected in the list yet?
+        is_highlighted: bool,
+        /// Has a forward pagination reached the end of the room, meaning the
+        /// live timeline can be shown instead?
+        reached_live_end: Arc<AtomicBool>,
+    },
 }
 
 pub struct RoomView {
@@ -81,11 +178,41 @@ pub struct RoomView {
     timeline_list: TimelineListState,
 
     input: Input,
+
+    /// The users typing in the selected room.
+    typing_users: Option<TypingUsers>,
+
+    /// Sends our own typing notices.
+    typing_notifier: TypingNotifier,
+
+    /// The inline previews of the images of the timeline.
+    image_previews: ImagePreviews,
+
+    /// A room created or found by a command, which must be selected once it
+    /// appears in the room list.
+    room_to_select: Option<OwnedRoomId>,
+
+    /// A room we've just left, which must be removed from the room list.
+    left_room: Option<OwnedRoomId>,
+
+    /// An event from a permalink, which must be focused once its room is
+    /// selected.
+    event_to_focus: Option<(OwnedRoomId, OwnedEventId)>,
+
+    /// Whether the `/logout` command has been used, and multiverse must log
+    /// out and quit.
+    logout_requested: bool,
 }
 
 impl RoomView {
-    pub fn new(client: Client, timelines: Timelines, status_handle: StatusHandle) -> Self {
+    pub fn new(
+        client: Client,
+        timelines: Timelines,
+        status_handle: StatusHandle,
+        media_cache_dir: PathBuf,
+    ) -> Self {
         Self {
+            image_previews: ImagePreviews::new(client.clone(), media_cache_dir),
             client,
             timelines,
             status_handle,
@@ -94,6 +221,12 @@ impl RoomView {
             kind: TimelineKind::Room { room: None },
             input: Input::new(),
             timeline_list: TimelineListState::default(),
+            typing_users: None,
+            typing_notifier: TypingNotifier::default(),
+            room_to_select: None,
+            left_room: None,
+            event_to_focus: None,
+            logout_requested: false,
         }
     }
 
@@ -102,8 +235,8 @@ impl RoomView {
             TimelineKind::Room { room: prev_room } => {
                 self.kind = TimelineKind::Room { room: room.or(prev_room.take()) };
             }
-            TimelineKind::Thread { task, room, .. } => {
-                // If we were in a thread, abort the task.
+            TimelineKind::Thread { task, room, .. } | TimelineKind::Event { task, room, .. } => {
+                // If we were in a thread or around an event, abort the task.
                 task.abort();
                 self.kind = TimelineKind::Room { room: Some(room.clone()) };
             }
@@ -141,21 +274,75 @@ impl RoomView {
 
         info!("Opening thread view for event {root_event_id} in room {}", room.room_id());
 
-        let thread_timeline = Arc::new(OnceCell::new());
+        let (timeline, items, task) = self.spawn_focused_timeline(
+            room.clone(),
+            TimelineFocus::Thread { root_event_id: root_event_id.clone() },
+        );
+
+        self.timeline_list.unselect();
+
+        self.kind = TimelineKind::Thread {
+            thread_root: root_event_id,
+            room: room.room_id().to_owned(),
+            timeline,
+            items,
+            task,
+        };
+    }
+
+    fn switch_to_event_timeline(&mut self, room: Room, event_id: OwnedEventId) {
+        info!("Opening event view for event {event_id} in room {}", room.room_id());
+
+        let (timeline, items, task) = self.spawn_focused_timeline(
+            room.clone(),
+            TimelineFocus::Event {
+                target: event_id.clone(),
+                num_context_events: 20,
+                hide_threaded_events: true,
+            },
+        );
+
+        self.switch_to_room_timeline(None);
+        self.timeline_list.unselect();
+
+        self.kind = TimelineKind::Event {
+            room: room.room_id().to_owned(),
+            event_id,
+            timeline,
+            items,
+            task,
+            is_highlighted: false,
+            reached_live_end: Default::default(),
+        };
+    }
+
+    /// Build a timeline with the given focus in the background, and maintain
+    /// its items over time.
+    #[allow(clippy::type_complexity)]
+    fn spawn_focused_timeline(
+        &self,
+        room: Room,
+        focus: TimelineFocus,
+    ) -> (Arc<OnceCell<Arc<Timeline>>>, Arc<Mutex<Vector<Arc<TimelineItem>>>>, JoinHandle<()>) {
+        let focused_timeline = Arc::new(OnceCell::new());
         let items = Arc::new(Mutex::new(Default::default()));
 
         let i = items.clone();
-        let t = thread_timeline.clone();
-        let root = root_event_id;
-        let cloned_root = root.clone();
-        let r = room.clone();
+        let t = focused_timeline.clone();
+        let status_handle = self.status_handle.clone();
         let task = spawn(async move {
-            let timeline = TimelineBuilder::new(&r)
-                .with_focus(TimelineFocus::Thread { root_event_id: cloned_root })
+            let timeline = match TimelineBuilder::new(&room)
+                .with_focus(focus)
                 .track_read_marker_and_receipts()
                 .build()
                 .await
-                .unwrap();
+            {
+                Ok(timeline) => timeline,
+                Err(err) => {
+                    status_handle.set_error(format!("couldn't open the timeline: {err}"));
+                    return;
+                }
+            };
 
             let items = i;
             let (initial_items, mut stream) = timeline.subscribe().await;
@@ -171,21 +358,14 @@ impl RoomView {
             }
         });
 
-        self.timeline_list.unselect();
-
-        self.kind = TimelineKind::Thread {
-            thread_root: root,
-            room: room.room_id().to_owned(),
-            timeline: thread_timeline,
-            items,
-            task,
-        };
+        (focused_timeline, items, task)
     }
 
-    fn room_id(&self) -> Option<&RoomId> {
+    /// Get the ID of the currently focused room, if any.
+    pub fn room_id(&self) -> Option<&RoomId> {
         match &self.kind {
             TimelineKind::Room { room } => room.as_deref(),
-            TimelineKind::Thread { room, .. } => Some(room),
+            TimelineKind::Thread { room, .. } | TimelineKind::Event { room, .. } => Some(room),
         }
     }
 
@@ -194,6 +374,37 @@ impl RoomView {
         self.room_id().and_then(|room_id| self.client.get_room(room_id))
     }
 
+    /// Hide the previews of the images, e.g. because a popup covers the
+    /// timeline.
+    pub fn hide_image_previews(&mut self) {
+        self.image_previews.hide();
+    }
+
+    /// Have the previews of the images moved since the last frame? If so, the
+    /// screen must be cleared before flushing them.
+    pub fn image_previews_have_changed(&self) -> bool {
+        self.image_previews.have_changed()
+    }
+
+    /// Display the previews of the images on the terminal, once the current
+    /// frame has been drawn.
+    pub fn flush_image_previews(&mut self, writer: &mut impl Write) -> io::Result<()> {
+        self.image_previews.flush(writer)
+    }
+
+    /// Is the user writing some text in this view, e.g. composing a message,
+    /// so the key presses must not be interpreted as shortcuts?
+    pub fn is_editing_text(&self) -> bool {
+        match &self.mode {
+            Mode::Normal { .. } => self.input.is_focused(),
+            Mode::Details { view, .. } => view.is_editing_text(),
+            Mode::Redact { .. } | Mode::React { .. } | Mode::Find { .. } | Mode::RawJson { .. } => {
+                true
+            }
+            Mode::ConfirmLeave { .. } => false,
+        }
+    }
+
     pub async fn handle_event(&mut self, event: Event) {
         use KeyCode::*;
 
@@ -201,15 +412,19 @@ impl RoomView {
             Mode::Normal { invited_room_view } => {
                 if let Some(view) = invited_room_view {
                     view.handle_event(event);
-                } else if let Event::Key(key) = event {
+                } else if let Event::Key(key) = event
+                    && self.input.is_focused()
+                {
                     match (key.modifiers, key.code) {
                         (KeyModifiers::NONE, Enter) => {
+                            self.typing_notifier.stopped(self.room());
+
                             if !self.input.is_empty() {
                                 let message_or_command = self.input.get_input();
 
                                 match message_or_command {
                                     Ok(MessageOrCommand::Message(message)) => {
-                                        self.send_message(message).await
+                                        self.send_message(MessageType::text_plain(message)).await
                                     }
                                     Ok(MessageOrCommand::Command(command)) => {
                                         self.handle_command(command).await
@@ -222,133 +437,282 @@ impl RoomView {
                             }
                         }
 
-                        // Pressing Escape on a threaded timeline will get back to the room
-                        // timeline.
-                        (KeyModifiers::NONE, Esc)
-                            if matches!(self.kind, TimelineKind::Thread { .. }) =>
-                        {
-                            self.switch_to_room_timeline(None);
+                        (KeyModifiers::ALT | KeyModifiers::SHIFT, Enter) => {
+                            self.input.insert_newline()
                         }
 
-                        // Pressing 'Alt+s' on a threaded timeline will print the current
-                        // subscription status.
-                        (KeyModifiers::ALT, Char('s')) => {
-                            self.print_thread_subscription_status().await;
-                        }
+                        (KeyModifiers::NONE, Tab) => self.complete_input().await,
 
-                        (KeyModifiers::CONTROL, Char('l')) => {
-                            self.toggle_reaction_to_latest_msg().await
+                        // Cancel the message being composed.
+                        (_, Esc) => {
+                            self.typing_notifier.stopped(self.room());
+                            self.input.clear();
+                            self.input.unfocus();
                         }
 
-                        (KeyModifiers::NONE, PageUp) => self.back_paginate(),
-
-                        (KeyModifiers::ALT, Char('e')) => {
-                            if let TimelineKind::Room { room: Some(_) } = self.kind {
-                                self.mode = Mode::Details {
-                                    tiling_direction: DEFAULT_TILING_DIRECTION,
-                                    view: RoomDetails::with_events_as_selected(),
-                                }
-                            }
+                        _ => {
+                            self.input.handle_key_press(key);
+                            self.typing_notifier.typing(self.room());
                         }
+                    }
+                } else if let Event::Key(key) = event {
+                    let action = keymap().action(&key);
 
-                        (KeyModifiers::ALT, Char('r')) => {
-                            if let TimelineKind::Room { room: Some(_) } = self.kind {
-                                self.mode = Mode::Details {
-                                    tiling_direction: DEFAULT_TILING_DIRECTION,
-                                    view: RoomDetails::with_receipts_as_selected(),
-                                }
-                            }
+                    if let Some(view) = action.and_then(details_view_for) {
+                        if let TimelineKind::Room { room: Some(_) } = self.kind {
+                            self.mode =
+                                Mode::Details { tiling_direction: DEFAULT_TILING_DIRECTION, view };
                         }
+                        return;
+                    }
 
-                        (KeyModifiers::ALT, Char('l')) => {
-                            if let TimelineKind::Room { room: Some(_) } = self.kind {
-                                self.mode = Mode::Details {
-                                    tiling_direction: DEFAULT_TILING_DIRECTION,
-                                    view: RoomDetails::with_chunks_as_selected(),
-                                }
+                    match action {
+                        Some(Action::WriteMessage) => self.input.focus(),
+
+                        // Open the command line, unless a message is already being written.
+                        Some(Action::CommandLine) => {
+                            if self.input.is_empty() {
+                                self.input.set_text("/");
                             }
+                            self.input.focus();
                         }
 
-                        (_, Down) | (KeyModifiers::CONTROL, Char('n')) => {
-                            self.timeline_list.select_next()
+                        Some(Action::Reply) => self.reply_to_selected_item(),
+                        Some(Action::Edit) => self.edit_selected_item(),
+                        Some(Action::Redact) => self.ask_redaction_of_selected_item().await,
+                        Some(Action::Leave) => self.ask_leave_confirmation(),
+                        Some(Action::TogglePin) => self.toggle_pin_of_selected_item().await,
+                        Some(Action::EndPoll) => self.end_selected_poll().await,
+
+                        // Print the subscription status of the current thread.
+                        Some(Action::ThreadSubscriptionStatus) => {
+                            self.print_thread_subscription_status().await;
                         }
-                        (_, Up) | (KeyModifiers::CONTROL, Char('p')) => {
-                            self.timeline_list.select_previous()
+
+                        Some(Action::React) => self.open_reaction_picker(),
+
+                        Some(Action::ShowRawJson) => self.show_raw_json_of_selected_item(),
+
+                        Some(Action::FindInRoom) if self.room_id().is_some() => {
+                            self.mode = Mode::Find { bar: FindBar::new() };
                         }
-                        (_, Esc) => self.timeline_list.unselect(),
 
-                        (KeyModifiers::CONTROL, Char('t'))
+                        Some(Action::BackPaginate) => self.back_paginate(),
+                        Some(Action::ForwardPaginate) => self.forward_paginate(),
+
+                        Some(Action::NextItem) => self.timeline_list.select_next(),
+                        Some(Action::PreviousItem) => self.timeline_list.select_previous(),
+
+                        Some(Action::OpenThread)
                             if matches!(self.kind, TimelineKind::Room { .. }) =>
                         {
                             self.switch_to_thread_timeline();
                         }
 
-                        _ => self.input.handle_key_press(key),
+                        _ => match (key.modifiers, key.code) {
+                            // Vote for the n-th answer of the focused poll.
+                            (KeyModifiers::NONE, Char(digit @ '1'..='9')) => {
+                                let answer_index = digit as usize - '1' as usize;
+                                self.vote_on_selected_poll(answer_index).await;
+                            }
+
+                            // Pressing Escape on a threaded or event-focused timeline will get
+                            // back to the room timeline.
+                            (KeyModifiers::NONE, Esc)
+                                if !matches!(self.kind, TimelineKind::Room { .. }) =>
+                            {
+                                self.switch_to_room_timeline(None);
+                            }
+
+                            (_, Esc) => self.timeline_list.unselect(),
+
+                            _ => {}
+                        },
                     }
+                } else if let Event::Mouse(mouse) = event {
+                    self.handle_mouse_event(mouse);
                 }
             }
 
-            Mode::Details { view, tiling_direction } => {
+            Mode::Redact { item_id, reason } => {
                 if let Event::Key(key) = event {
                     match (key.modifiers, key.code) {
-                        (KeyModifiers::NONE, PageUp) => self.back_paginate(),
-
-                        (KeyModifiers::ALT, Char('t')) => {
-                            let new_layout = match tiling_direction {
-                                Direction::Horizontal => Direction::Vertical,
-                                Direction::Vertical => Direction::Horizontal,
-                            };
-
-                            *tiling_direction = new_layout;
+                        (KeyModifiers::NONE, Enter) => {
+                            let item_id = item_id.clone();
+                            let reason = reason.get_input();
+                            self.mode = Mode::Normal { invited_room_view: None };
+                            self.redact(&item_id, reason).await;
                         }
 
-                        (KeyModifiers::ALT, Char('e')) => {
-                            self.mode = Mode::Details {
-                                tiling_direction: *tiling_direction,
-                                view: RoomDetails::with_events_as_selected(),
-                            }
+                        (_, Esc) => {
+                            self.mode = Mode::Normal { invited_room_view: None };
+                            self.status_handle.set_message("redaction cancelled".to_owned());
                         }
 
-                        (KeyModifiers::ALT, Char('r')) => {
-                            self.mode = Mode::Details {
-                                tiling_direction: *tiling_direction,
-                                view: RoomDetails::with_receipts_as_selected(),
-                            }
+                        _ => reason.handle_key_press(key),
+                    }
+                }
+            }
+
+            Mode::React { item_id, picker } => {
+                if let Event::Key(key) = event {
+                    match picker.handle_key_press(key) {
+                        PickerAction::None => {}
+
+                        PickerAction::Toggle(reaction) => {
+                            let item_id = item_id.clone();
+                            self.mode = Mode::Normal { invited_room_view: None };
+                            self.toggle_reaction(&item_id, reaction).await;
                         }
 
-                        (KeyModifiers::ALT, Char('l')) => {
-                            self.mode = Mode::Details {
-                                tiling_direction: *tiling_direction,
-                                view: RoomDetails::with_chunks_as_selected(),
-                            }
+                        PickerAction::Cancel => {
+                            self.mode = Mode::Normal { invited_room_view: None };
                         }
+                    }
+                }
+            }
+
+            Mode::Find { bar } => {
+                if let Event::Key(key) = event {
+                    match key.code {
+                        // The selected match stays selected in the timeline.
+                        Esc => self.mode = Mode::Normal { invited_room_view: None },
+
+                        Tab => bar.toggle_server(),
+
+                        Enter | Up => self.find_older_match().await,
+
+                        Down => match bar.newer_match().cloned() {
+                            Some(event_id) => self.select_event(event_id),
+                            None => self.status_handle.set_message("no newer match".to_owned()),
+                        },
+
+                        _ => bar.handle_key_press(key),
+                    }
+                }
+            }
+
+            Mode::RawJson { view } => {
+                if let Event::Key(key) = event
+                    && view.handle_key_press(key)
+                {
+                    self.mode = Mode::Normal { invited_room_view: None };
+                }
+            }
 
-                        (_, Down) | (KeyModifiers::CONTROL, Char('n')) => {
-                            self.timeline_list.select_next()
+            Mode::ConfirmLeave { room } => {
+                if let Event::Key(key) = event {
+                    match key.code {
+                        Enter | Char('y') => {
+                            let room = room.clone();
+                            self.mode = Mode::Normal { invited_room_view: None };
+                            self.leave(room).await;
                         }
 
-                        (_, Up) | (KeyModifiers::CONTROL, Char('p')) => {
-                            self.timeline_list.select_previous()
+                        Esc | Char('n') => self.mode = Mode::Normal { invited_room_view: None },
+
+                        _ => {}
+                    }
+                }
+            }
+
+            Mode::Details { view, tiling_direction } => {
+                if let Event::Key(key) = event
+                    && view.is_editing_text()
+                {
+                    // The text being written must not trigger the shortcuts.
+                    if let ShouldExit::Yes = view.handle_key_press(key, &self.status_handle) {
+                        self.mode = Mode::Normal { invited_room_view: None };
+                    }
+                } else if let Event::Key(key) = event {
+                    let action = keymap().action(&key);
+
+                    if let Some(view) = action.and_then(details_view_for) {
+                        self.mode = Mode::Details { tiling_direction: *tiling_direction, view };
+                        return;
+                    }
+
+                    match action {
+                        Some(Action::BackPaginate) => self.back_paginate(),
+
+                        Some(Action::ForwardPaginate) => self.forward_paginate(),
+
+                        Some(Action::ToggleTiling) => {
+                            let new_layout = match tiling_direction {
+                                Direction::Horizontal => Direction::Vertical,
+                                Direction::Vertical => Direction::Horizontal,
+                            };
+
+                            *tiling_direction = new_layout;
                         }
 
-                        _ => match view.handle_key_press(key) {
+                        Some(Action::TogglePin) => self.toggle_pin_of_selected_item().await,
+
+                        Some(Action::NextItem) => self.timeline_list.select_next(),
+
+                        Some(Action::PreviousItem) => self.timeline_list.select_previous(),
+
+                        _ => match view.handle_key_press(key, &self.status_handle) {
                             ShouldExit::No => {}
                             ShouldExit::OnlySubScreen => {}
                             ShouldExit::Yes => self.mode = Mode::Normal { invited_room_view: None },
                         },
                     }
+                } else if let Event::Mouse(mouse) = event {
+                    self.handle_mouse_event(mouse);
+                }
+            }
+        }
+    }
+
+    /// Select the clicked timeline item or details tab, or focus on the next
+    /// or previous timeline item with the scroll wheel.
+    fn handle_mouse_event(&mut self, event: MouseEvent) {
+        let MouseEvent { kind, column, row, .. } = event;
+
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Mode::Details { view, .. } = &mut self.mode
+                    && view.click(column, row)
+                {
+                    return;
                 }
+
+                self.timeline_list.select_at(column, row);
+            }
+
+            MouseEventKind::ScrollDown if self.timeline_list.contains(column, row) => {
+                self.timeline_list.select_next()
+            }
+
+            MouseEventKind::ScrollUp if self.timeline_list.contains(column, row) => {
+                self.timeline_list.select_previous()
             }
+
+            _ => {}
         }
     }
 
-    pub fn set_selected_room(&mut self, room_id: Option<OwnedRoomId>) {
+    pub async fn set_selected_room(&mut self, room_id: Option<OwnedRoomId>) {
         if let Some(room_id) = room_id.as_deref() {
             let maybe_room = self.client.get_room(room_id);
 
             if let Some(room) = maybe_room {
+                let is_same_room = self.room_id() == Some(room_id);
+
+                // Keep the message being written in the previous room for later.
+                if !is_same_room {
+                    self.typing_notifier.stopped(self.room());
+                    self.save_draft().await;
+                }
+
                 self.switch_to_room_timeline(Some(room_id.to_owned()));
 
+                if !is_same_room {
+                    self.restore_draft().await;
+                    self.typing_users = Some(TypingUsers::new(room.clone()));
+                }
+
                 if matches!(room.state(), RoomState::Invited) {
                     let view = InvitedRoomView::new(room);
                     self.mode = Mode::Normal { invited_room_view: Some(view) };
@@ -358,12 +722,130 @@ impl RoomView {
                             invited_room_view.take();
                         }
                         Mode::Details { .. } => {}
+                        // The event to redact or react to, the room to leave, or the
+                        // messages to find, belong to the previous room.
+                        Mode::Redact { .. }
+                        | Mode::ConfirmLeave { .. }
+                        | Mode::React { .. }
+                        | Mode::Find { .. }
+                        | Mode::RawJson { .. } => {
+                            self.mode = Mode::Normal { invited_room_view: None };
+                        }
                     }
                 }
             }
         }
 
         self.timeline_list = TimelineListState::default();
+
+        // The room of a permalink has been selected, focus on its event.
+        if let Some((event_room_id, event_id)) = self.event_to_focus.take() {
+            match self.client.get_room(&event_room_id) {
+                Some(room) if room_id.as_deref() == Some(&*event_room_id) => {
+                    self.switch_to_event_timeline(room, event_id);
+                }
+                _ => self.event_to_focus = Some((event_room_id, event_id)),
+            }
+        }
+    }
+
+    /// The root of the thread of the threaded timeline, if it's the selected
+    /// one.
+    fn thread_root(&self) -> Option<&EventId> {
+        match &self.kind {
+            TimelineKind::Room { .. } | TimelineKind::Event { .. } => None,
+            TimelineKind::Thread { thread_root, .. } => Some(thread_root),
+        }
+    }
+
+    /// Save the content of the composer as the draft of the selected room, or
+    /// clear the draft of the room if the composer is empty.
+    pub async fn save_draft(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let result = if self.input.is_empty() {
+            room.clear_composer_draft(self.thread_root()).await
+        } else {
+            let draft_type = match self.input.mode() {
+                None => ComposerDraftType::NewMessage,
+                Some(ComposerMode::Reply(reply_to)) => {
+                    ComposerDraftType::Reply { event_id: reply_to.event_id.clone() }
+                }
+                Some(ComposerMode::Edit { item_id }) => match item_id {
+                    TimelineEventItemId::EventId(event_id) => {
+                        ComposerDraftType::Edit { event_id: event_id.clone() }
+                    }
+                    // A local echo won't exist anymore after a restart, so only the text can
+                    // be kept.
+                    TimelineEventItemId::TransactionId(_) => ComposerDraftType::NewMessage,
+                },
+            };
+
+            let draft =
+                ComposerDraft { plain_text: self.input.text(), html_text: None, draft_type };
+            room.save_composer_draft(draft, self.thread_root()).await
+        };
+
+        if let Err(err) = result {
+            self.status_handle.set_error(format!("error when saving the draft: {err}"));
+        }
+    }
+
+    /// Replace the content of the composer with the draft of the selected
+    /// room, if any.
+    async fn restore_draft(&mut self) {
+        self.input.clear();
+        self.input.unfocus();
+
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let draft = match room.load_composer_draft(self.thread_root()).await {
+            Ok(Some(draft)) => draft,
+            Ok(None) => return,
+            Err(err) => {
+                self.status_handle.set_error(format!("error when loading the draft: {err}"));
+                return;
+            }
+        };
+
+        self.input.set_text(&draft.plain_text);
+
+        match draft.draft_type {
+            ComposerDraftType::NewMessage => {}
+
+            ComposerDraftType::Reply { event_id } => {
+                // The quote needs the replied-to event; if it can't be found, keep the text
+                // as a new message.
+                let replied_to = match self.get_selected_timeline() {
+                    Some(timeline) => timeline.item_by_event_id(&event_id).await,
+                    None => None,
+                };
+
+                if let Some(event) = replied_to {
+                    let body = event
+                        .content()
+                        .as_message()
+                        .map(|message| message.body().to_owned())
+                        .unwrap_or_else(|| "(not a message)".to_owned());
+
+                    self.input.set_mode(ComposerMode::Reply(ReplyTo {
+                        event_id,
+                        sender: event.sender().to_owned(),
+                        body,
+                    }));
+                }
+            }
+
+            ComposerDraftType::Edit { event_id } => {
+                self.input.set_mode(ComposerMode::Edit {
+                    item_id: TimelineEventItemId::EventId(event_id),
+                });
+            }
+        }
     }
 
     fn get_selected_timeline(&self) -> Option<Arc<Timeline>> {
@@ -371,7 +853,9 @@ impl RoomView {
             TimelineKind::Room { room } => room
                 .as_deref()
                 .and_then(|room_id| Some(self.timelines.lock().get(room_id)?.timeline.clone())),
-            TimelineKind::Thread { timeline, .. } => timeline.get().cloned(),
+            TimelineKind::Thread { timeline, .. } | TimelineKind::Event { timeline, .. } => {
+                timeline.get().cloned()
+            }
         }
     }
 
@@ -380,7 +864,9 @@ impl RoomView {
             TimelineKind::Room { room } => room
                 .as_deref()
                 .and_then(|room_id| Some(self.timelines.lock().get(room_id)?.items.lock().clone())),
-            TimelineKind::Thread { items, .. } => Some(items.lock().clone()),
+            TimelineKind::Thread { items, .. } | TimelineKind::Event { items, .. } => {
+                Some(items.lock().clone())
+            }
         }
     }
 
@@ -404,62 +890,236 @@ impl RoomView {
         // Request to back-paginate 5 events.
         *pagination = Some(spawn(async move {
             if let Err(err) = sdk_timeline.paginate_backwards(5).await {
-                status_handle.set_message(format!("Error during backpagination: {err}"));
+                status_handle.set_error(format!("Error during backpagination: {err}"));
             }
         }));
     }
 
-    pub async fn toggle_reaction_to_latest_msg(&mut self) {
-        let Some((sdk_timeline, items)) =
-            self.get_selected_timeline().zip(self.get_selected_timeline_items())
-        else {
-            self.status_handle.set_message("missing timeline for room".to_owned());
+    /// Run a small forward pagination in a timeline focused on an event; once
+    /// it reaches the end of the room, [`Self::on_tick`] gets back to the live
+    /// timeline.
+    pub fn forward_paginate(&mut self) {
+        let TimelineKind::Event { timeline, reached_live_end, .. } = &self.kind else {
+            self.status_handle
+                .set_message("only the timelines focused on an event can go forward".to_owned());
             return;
         };
 
-        // Look for the latest (most recent) room message.
-        let Some(item_id) = items.iter().rev().find_map(|it| {
-            let event_item = it.as_event()?;
-            event_item.content().as_message()?;
-            Some(event_item.identifier())
-        }) else {
-            self.status_handle.set_message("no item to react to".to_owned());
+        let Some(sdk_timeline) = timeline.get().cloned() else {
+            self.status_handle.set_message("the focused timeline is still loading".to_owned());
             return;
         };
 
-        // If found, send a reaction.
-        match sdk_timeline.toggle_reaction(&item_id, "🥰").await {
-            Ok(_) => {
-                self.status_handle.set_message("reaction sent!".to_owned());
-            }
-            Err(err) => self.status_handle.set_message(format!("error when reacting: {err}")),
+        let mut pagination = self.current_pagination.lock();
+
+        // Cancel the previous pagination, if any.
+        if let Some(prev) = pagination.take() {
+            prev.abort();
         }
+
+        let status_handle = self.status_handle.clone();
+        let reached_live_end = reached_live_end.clone();
+
+        // Request to paginate 5 events forwards.
+        *pagination = Some(spawn(async move {
+            match sdk_timeline.paginate_forwards(5).await {
+                Ok(true) => reached_live_end.store(true, Ordering::SeqCst),
+                Ok(false) => {}
+                Err(err) => {
+                    status_handle.set_error(format!("Error during forward pagination: {err}"))
+                }
+            }
+        }));
     }
 
-    /// Attempt to find the currently selected room and pass it to the async
-    /// callback.
-    async fn call_with_room(&self, function: impl AsyncFnOnce(Room, &StatusHandle)) {
-        if let Some(room) = self.room() {
-            function(room, &self.status_handle).await
-        } else {
-            self.status_handle
-                .set_message("Couldn't find a room selected room to perform an action".to_owned());
+    /// Get back to the live timeline, once the forward pagination of a
+    /// timeline focused on an event has caught up with it.
+    pub fn on_tick(&mut self) {
+        if let TimelineKind::Event { reached_live_end, .. } = &self.kind
+            && reached_live_end.load(Ordering::SeqCst)
+        {
+            self.switch_to_room_timeline(None);
+            self.timeline_list.unselect();
+            self.status_handle.set_message("reached the live timeline".to_owned());
         }
     }
 
-    async fn invite_member(&mut self, user_id: &str) {
-        self.call_with_room(async move |room, status_handle| {
-            let user_id = match UserId::parse_with_server_name(
-                user_id,
-                room.client().user_id().unwrap().server_name(),
-            ) {
-                Ok(user_id) => user_id,
-                Err(e) => {
-                    status_handle
-                        .set_message(format!("Failed to parse {user_id} as a user ID: {e:?}"));
-                    return;
-                }
-            };
+    /// Search the messages containing the text of the find bar if it has
+    /// changed, and select the match older than the selected one.
+    async fn find_older_match(&mut self) {
+        let Mode::Find { bar } = &mut self.mode else {
+            return;
+        };
+
+        if bar.needs_search() {
+            let query = bar.query();
+            let include_server = bar.include_server();
+
+            let Some(room) = self.room().filter(|_| !query.is_empty()) else {
+                return;
+            };
+
+            match find::find_messages(&room, &query, include_server).await {
+                Ok(matches) => {
+                    let is_empty = matches.is_empty();
+
+                    if let Mode::Find { bar } = &mut self.mode {
+                        bar.set_matches(query.clone(), matches);
+                    }
+
+                    if is_empty {
+                        self.status_handle.set_message(format!("no message contains {query:?}"));
+                        return;
+                    }
+                }
+                Err(err) => {
+                    self.status_handle.set_error(format!("couldn't search the room: {err}"));
+                    return;
+                }
+            }
+        }
+
+        let Mode::Find { bar } = &mut self.mode else {
+            return;
+        };
+
+        match bar.older_match().cloned() {
+            Some(event_id) => self.select_event(event_id),
+            None => self.status_handle.set_message("no older match".to_owned()),
+        }
+    }
+
+    /// Select an event in the timeline, or open a timeline focused on it if
+    /// it isn't there.
+    fn select_event(&mut self, event_id: OwnedEventId) {
+        let items = self.get_selected_timeline_items().unwrap_or_default();
+        let position = items.iter().position(|item| {
+            item.as_event().and_then(|event| event.event_id()) == Some(&*event_id)
+        });
+
+        if let Some(index) = position
+            && self.timeline_list.select_item(index)
+        {
+            return;
+        }
+
+        if let Some(room) = self.room() {
+            self.switch_to_event_timeline(room, event_id);
+        }
+    }
+
+    fn send_attachment(&mut self, path: &Path, thumbnail_path: Option<&Path>) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        attachment::send_attachment(sdk_timeline, path, thumbnail_path, self.status_handle.clone());
+        self.input.clear();
+    }
+
+    fn send_voice_message(&mut self, path: &Path, duration: Option<Duration>) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        attachment::send_voice_message(sdk_timeline, path, duration, self.status_handle.clone());
+        self.input.clear();
+    }
+
+    /// Show the raw JSON of the selected timeline item, as the server sent it.
+    fn show_raw_json_of_selected_item(&mut self) {
+        let Some(item) = self.get_selected_event() else {
+            self.status_handle.set_message("no selected item".to_owned());
+            return;
+        };
+
+        let Some(raw) = item.as_event().and_then(|event| event.original_json()) else {
+            self.status_handle
+                .set_message("only the events echoed back by the server have JSON".to_owned());
+            return;
+        };
+
+        self.mode = Mode::RawJson { view: RawJsonView::new(raw) };
+    }
+
+    /// Open the reaction picker for the selected timeline item, or for the
+    /// latest message if no item is selected.
+    fn open_reaction_picker(&mut self) {
+        let Some(items) = self.get_selected_timeline_items() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        let event = match self.timeline_list.selected() {
+            Some(selected) => items.get(selected).and_then(|item| item.as_event()),
+            // Look for the latest (most recent) room message.
+            None => items.iter().rev().find_map(|item| {
+                let event = item.as_event()?;
+                event.content().as_message()?;
+                Some(event)
+            }),
+        };
+
+        let Some(event) = event else {
+            self.status_handle.set_message("no item to react to".to_owned());
+            return;
+        };
+
+        let Some(reactions) = event.content().reactions() else {
+            self.status_handle.set_message("can't react to this item".to_owned());
+            return;
+        };
+
+        let own_user_id = self.client.user_id();
+        let own_reactions = reactions
+            .iter()
+            .filter(|(_, senders)| own_user_id.is_some_and(|user_id| senders.contains_key(user_id)))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        self.mode =
+            Mode::React { item_id: event.identifier(), picker: ReactionPicker::new(own_reactions) };
+    }
+
+    async fn toggle_reaction(&mut self, item_id: &TimelineEventItemId, reaction: &str) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        match sdk_timeline.toggle_reaction(item_id, reaction).await {
+            Ok(true) => self.status_handle.set_message(format!("reacted with {reaction}!")),
+            Ok(false) => self.status_handle.set_message(format!("removed the {reaction} reaction")),
+            Err(err) => self.status_handle.set_error(format!("error when reacting: {err}")),
+        }
+    }
+
+    /// Attempt to find the currently selected room and pass it to the async
+    /// callback.
+    async fn call_with_room(&self, function: impl AsyncFnOnce(Room, &StatusHandle)) {
+        if let Some(room) = self.room() {
+            function(room, &self.status_handle).await
+        } else {
+            self.status_handle
+                .set_message("Couldn't find a room selected room to perform an action".to_owned());
+        }
+    }
+
+    async fn invite_member(&mut self, user_id: &str) {
+        self.call_with_room(async move |room, status_handle| {
+            let user_id = match UserId::parse_with_server_name(
+                user_id,
+                room.client().user_id().unwrap().server_name(),
+            ) {
+                Ok(user_id) => user_id,
+                Err(e) => {
+                    status_handle
+                        .set_message(format!("Failed to parse {user_id} as a user ID: {e:?}"));
+                    return;
+                }
+            };
 
             match room.invite_user_by_id(&user_id).await {
                 Ok(_) => {
@@ -478,21 +1138,123 @@ impl RoomView {
     }
 
     async fn leave_room(&mut self) {
-        self.call_with_room(async |room, status_handle| {
-            let _ = room.leave().await.inspect_err(|e| {
-                status_handle.set_message(format!("Couldn't leave the room {e:?}"))
-            });
-        })
-        .await;
+        if let Some(room) = self.room() {
+            self.leave(room).await;
+        } else {
+            self.status_handle
+                .set_message("Couldn't find a room selected room to perform an action".to_owned());
+        }
+
+        self.input.clear();
+    }
+
+    /// Ask the user to confirm that they want to leave the selected room.
+    fn ask_leave_confirmation(&mut self) {
+        match self.room() {
+            Some(room) => self.mode = Mode::ConfirmLeave { room },
+            None => self.status_handle.set_message("no selected room to leave".to_owned()),
+        }
+    }
+
+    async fn leave(&mut self, room: Room) {
+        match room.leave().await {
+            Ok(()) => {
+                self.status_handle.set_message(format!("left {}", room.room_id()));
+                self.left_room = Some(room.room_id().to_owned());
+            }
+            Err(e) => self.status_handle.set_message(format!("Couldn't leave the room {e:?}")),
+        }
+    }
+
+    /// A room we've just left, which must be removed from the room list
+    /// without waiting for the next sync.
+    pub fn take_left_room(&mut self) -> Option<OwnedRoomId> {
+        self.left_room.take()
+    }
+
+    /// Whether the `/logout` command has been used since the last call.
+    pub fn take_logout_request(&mut self) -> bool {
+        std::mem::take(&mut self.logout_requested)
+    }
+
+    /// Join a room from its ID or alias, and select it.
+    async fn join_room(&mut self, room_id_or_alias: &str) {
+        let room_id_or_alias = match OwnedRoomOrAliasId::try_from(room_id_or_alias) {
+            Ok(room_id_or_alias) => room_id_or_alias,
+            Err(err) => {
+                self.status_handle.set_message(format!(
+                    "{room_id_or_alias} isn't a valid room ID or alias: {err}"
+                ));
+                return;
+            }
+        };
+
+        match self.client.join_room_by_id_or_alias(&room_id_or_alias, &[]).await {
+            Ok(room) => {
+                self.status_handle.set_message(format!("joined {room_id_or_alias}!"));
+                self.room_to_select = Some(room.room_id().to_owned());
+            }
+            Err(err) => {
+                self.status_handle.set_error(format!("couldn't join {room_id_or_alias}: {err}"))
+            }
+        }
 
         self.input.clear();
     }
 
+    /// Select the room of a permalink, joining it if needed, and focus the
+    /// timeline on the event of the permalink, if any.
+    async fn go_to_permalink(&mut self, uri: &str) {
+        let (room_id_or_alias, event_id, via) = match parse_permalink(uri) {
+            Ok(permalink) => permalink,
+            Err(err) => {
+                self.status_handle.set_message(err);
+                return;
+            }
+        };
+
+        // Look for the room among the joined ones first, to avoid a request to the
+        // homeserver.
+        let target = room_id_or_alias.as_str();
+        let known_room = self.client.joined_rooms().into_iter().find(|room| {
+            if room_id_or_alias.is_room_id() {
+                room.room_id().as_str() == target
+            } else {
+                room.canonical_alias().is_some_and(|alias| alias.as_str() == target)
+                    || room.alt_aliases().iter().any(|alias| alias.as_str() == target)
+            }
+        });
+
+        let room = match known_room {
+            Some(room) => room,
+            None => match self.client.join_room_by_id_or_alias(&room_id_or_alias, &via).await {
+                Ok(room) => {
+                    self.status_handle.set_message(format!("joined {room_id_or_alias}!"));
+                    room
+                }
+                Err(err) => {
+                    self.status_handle
+                        .set_error(format!("couldn't join {room_id_or_alias}: {err}"));
+                    return;
+                }
+            },
+        };
+
+        let room_id = room.room_id().to_owned();
+
+        if let Some(event_id) = event_id {
+            self.event_to_focus = Some((room_id.clone(), event_id));
+        }
+
+        self.room_to_select = Some(room_id);
+        self.input.clear();
+    }
+
     async fn subscribe_thread(&mut self) {
         if let TimelineKind::Thread { thread_root, .. } = &self.kind {
             self.call_with_room(async |room, status_handle| {
                 if let Err(err) = room.subscribe_thread(thread_root.clone(), None).await {
-                    status_handle.set_message(format!("error when subscribing to a thread: {err}"));
+                    status_handle.set_error(format!("error when subscribing to a thread: {err}"));
                 } else {
                     status_handle.set_message("Subscribed to thread!".to_owned());
                 }
@@ -507,8 +1269,7 @@ impl RoomView {
         if let TimelineKind::Thread { thread_root, .. } = &self.kind {
             self.call_with_room(async |room, status_handle| {
                 if let Err(err) = room.unsubscribe_thread(thread_root.clone()).await {
-                    status_handle
-                        .set_message(format!("error when unsubscribing to a thread: {err}"));
+                    status_handle.set_error(format!("error when unsubscribing to a thread: {err}"));
                 } else {
                     status_handle.set_message("Unsubscribed from thread!".to_owned());
                 }
@@ -539,7 +1300,7 @@ impl RoomView {
                     }
                     Err(err) => {
                         status_handle
-                            .set_message(format!("Error getting thread subscription: {err}"));
+                            .set_error(format!("Error getting thread subscription: {err}"));
                     }
                 }
             })
@@ -553,17 +1314,517 @@ impl RoomView {
             input::Command::Leave => self.leave_room().await,
             input::Command::Subscribe => self.subscribe_thread().await,
             input::Command::Unsubscribe => self.unsubscribe_thread().await,
+            input::Command::Attach { path, thumbnail } => {
+                self.send_attachment(&path, thumbnail.as_deref())
+            }
+            input::Command::Voice { path, duration } => {
+                self.send_voice_message(&path, duration.map(Duration::from_secs))
+            }
+            input::Command::Create { name, encrypted } => {
+                self.create_room(name.join(" "), encrypted).await
+            }
+            input::Command::Dm { user_id } => self.open_dm(&user_id).await,
+            input::Command::Join { room_id_or_alias } => self.join_room(&room_id_or_alias).await,
+            input::Command::Goto { uri } => self.go_to_permalink(&uri).await,
+            input::Command::Me { text } => {
+                let content = EmoteMessageEventContent::plain(text.join(" "));
+                self.send_message(MessageType::Emote(content)).await
+            }
+            input::Command::Html { html } => {
+                let html = html.join(" ");
+                let body = formatting::html_to_plain(&html);
+                self.send_message(MessageType::text_html(body, html)).await
+            }
+            input::Command::Rainbow { text } => {
+                let text = text.join(" ");
+                let html = formatting::rainbow_html(&text);
+                self.send_message(MessageType::text_html(text, html)).await
+            }
+            input::Command::Myroomnick { name } => self.set_room_display_name(name.join(" ")).await,
+            input::Command::Op { user_id, power_level } => {
+                self.set_power_level(&user_id, power_level).await
+            }
+            input::Command::Export { path, format, all } => self.export_timeline(path, format, all),
+            input::Command::Logout => {
+                self.logout_requested = true;
+                self.input.clear();
+            }
+        }
+    }
+
+    /// Export the selected timeline to a file, in the background.
+    fn export_timeline(&mut self, path: PathBuf, format: export::ExportFormat, all: bool) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        spawn(export::export_timeline(sdk_timeline, path, format, all, self.status_handle.clone()));
+
+        self.input.clear();
+    }
+
+    /// Complete the command name or the user ID being written at the end of
+    /// the input, or list the candidates if there are several of them.
+    async fn complete_input(&mut self) {
+        let candidates = match self.input.completion() {
+            None => return,
+            Some(Completion::Command(names)) => names,
+            Some(Completion::UserId { prefix }) => {
+                let Some(room) = self.room() else {
+                    return;
+                };
+
+                let members = match room.members_no_sync(RoomMemberships::ACTIVE).await {
+                    Ok(members) => members,
+                    Err(err) => {
+                        self.status_handle.set_error(format!("couldn't load the members: {err}"));
+                        return;
+                    }
+                };
+
+                // The localpart is enough when the `@` hasn't been written.
+                members
+                    .iter()
+                    .map(|member| member.user_id())
+                    .filter(|user_id| {
+                        user_id.as_str().starts_with(&prefix)
+                            || user_id.localpart().starts_with(&prefix)
+                    })
+                    .map(ToString::to_string)
+                    .collect()
+            }
+        };
+
+        if candidates.is_empty() {
+            self.status_handle.set_message("no completion".to_owned());
+        } else if !self.input.complete(&candidates) {
+            self.status_handle.set_message(candidates.join(", "));
+        }
+    }
+
+    /// Change our display name in the selected room only, by sending a new
+    /// member event.
+    async fn set_room_display_name(&mut self, name: String) {
+        self.call_with_room(async move |room, status_handle| {
+            let own_user_id = room.own_user_id();
+
+            let member = match room.get_member_no_sync(own_user_id).await {
+                Ok(Some(member)) => member,
+                Ok(None) => {
+                    status_handle.set_message("missing own room member".to_owned());
+                    return;
+                }
+                Err(err) => {
+                    status_handle.set_error(format!("error when loading member: {err}"));
+                    return;
+                }
+            };
+
+            let Some(mut content) = member.event().original_content().cloned() else {
+                status_handle.set_message("our member event has been redacted".to_owned());
+                return;
+            };
+
+            content.displayname = Some(name);
+
+            match room.send_state_event_for_key(own_user_id, content).await {
+                Ok(_) => status_handle.set_message("room display name changed!".to_owned()),
+                Err(err) => {
+                    status_handle.set_error(format!("couldn't change the room display name: {err}"))
+                }
+            }
+        })
+        .await;
+
+        self.input.clear();
+    }
+
+    /// Give the power level to the member of the selected room.
+    async fn set_power_level(&mut self, user_id: &str, power_level: i64) {
+        self.call_with_room(async move |room, status_handle| {
+            let user_id = match UserId::parse_with_server_name(
+                user_id,
+                room.client().user_id().unwrap().server_name(),
+            ) {
+                Ok(user_id) => user_id,
+                Err(err) => {
+                    status_handle.set_message(format!("{user_id} isn't a valid user ID: {err}"));
+                    return;
+                }
+            };
+
+            let Ok(power_level) = Int::try_from(power_level) else {
+                status_handle.set_message(format!("{power_level} isn't a valid power level"));
+                return;
+            };
+
+            match room.update_power_levels(vec![(&user_id, power_level)]).await {
+                Ok(_) => status_handle
+                    .set_message(format!("{user_id} now has the power level {power_level}")),
+                Err(err) => status_handle
+                    .set_error(format!("couldn't change the power level of {user_id}: {err}")),
+            }
+        })
+        .await;
+
+        self.input.clear();
+    }
+
+    /// The room which must be selected in the room list, once it appears in
+    /// it.
+    pub fn room_to_select(&self) -> Option<&RoomId> {
+        self.room_to_select.as_deref()
+    }
+
+    /// The room returned by [`Self::room_to_select`] has been selected.
+    pub fn clear_room_to_select(&mut self) {
+        self.room_to_select = None;
+    }
+
+    async fn create_room(&mut self, name: String, encrypted: bool) {
+        let mut request = CreateRoomRequest::new();
+        request.preset = Some(RoomPreset::PrivateChat);
+
+        if encrypted {
+            request.initial_state = vec![
+                InitialStateEvent::new(RoomEncryptionEventContent::with_recommended_defaults())
+                    .to_raw_any(),
+            ];
+        }
+
+        request.name = Some(name);
+
+        match self.client.create_room(request).await {
+            Ok(room) => {
+                self.status_handle.set_message("room created!".to_owned());
+                self.room_to_select = Some(room.room_id().to_owned());
+            }
+            Err(err) => self.status_handle.set_error(format!("couldn't create the room: {err}")),
+        }
+
+        self.input.clear();
+    }
+
+    /// Select the DM with the given user, or create it if there's none.
+    async fn open_dm(&mut self, user_id: &str) {
+        let user_id = match self.parse_user_id(user_id) {
+            Ok(user_id) => user_id,
+            Err(err) => {
+                self.status_handle.set_message(format!("{user_id} isn't a valid user ID: {err}"));
+                return;
+            }
+        };
+
+        let room = match self.client.get_dm_room(&user_id) {
+            Some(room) => room,
+            None => match self.client.create_dm(&user_id).await {
+                Ok(room) => {
+                    self.status_handle.set_message(format!("DM with {user_id} created!"));
+                    room
+                }
+                Err(err) => {
+                    self.status_handle
+                        .set_error(format!("couldn't create a DM with {user_id}: {err}"));
+                    return;
+                }
+            },
+        };
+
+        self.room_to_select = Some(room.room_id().to_owned());
+        self.input.clear();
+    }
+
+    /// Parse a user ID, which may be only the localpart of a user of our own
+    /// homeserver.
+    fn parse_user_id(&self, user_id: &str) -> Result<OwnedUserId, matrix_sdk::IdParseError> {
+        let server_name = self.client.user_id().expect("the client is logged in").server_name();
+        UserId::parse_with_server_name(user_id, server_name)
+    }
+
+    /// Start writing a reply to the selected timeline item.
+    fn reply_to_selected_item(&mut self) {
+        let Some(item) = self.get_selected_event() else {
+            self.status_handle.set_message("no selected item to reply to".to_owned());
+            return;
+        };
+
+        let Some(event) = item.as_event() else {
+            self.status_handle.set_message("the selected item isn't an event".to_owned());
+            return;
+        };
+
+        let Some(event_id) = event.event_id() else {
+            self.status_handle.set_message("can't reply to a local echo".to_owned());
+            return;
+        };
+
+        let body = event
+            .content()
+            .as_message()
+            .map(|message| message.body().to_owned())
+            .unwrap_or_else(|| "(not a message)".to_owned());
+
+        self.input.set_mode(ComposerMode::Reply(ReplyTo {
+            event_id: event_id.to_owned(),
+            sender: event.sender().to_owned(),
+            body,
+        }));
+        self.input.focus();
+    }
+
+    /// Start editing the selected timeline item, if it's one of our own
+    /// messages.
+    fn edit_selected_item(&mut self) {
+        let Some(item) = self.get_selected_event() else {
+            self.status_handle.set_message("no selected item to edit".to_owned());
+            return;
+        };
+
+        let Some(event) = item.as_event() else {
+            self.status_handle.set_message("the selected item isn't an event".to_owned());
+            return;
+        };
+
+        if !event.is_own() {
+            self.status_handle.set_message("can only edit our own messages".to_owned());
+            return;
+        }
+
+        let Some(message) = event.content().as_message().filter(|_| event.is_editable()) else {
+            self.status_handle.set_message("this event can't be edited".to_owned());
+            return;
+        };
+
+        self.input.set_text(message.body());
+        self.input.set_mode(ComposerMode::Edit { item_id: event.identifier() });
+        self.input.focus();
+    }
+
+    /// Pin the selected timeline item, or unpin it if it's already pinned.
+    async fn toggle_pin_of_selected_item(&mut self) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        let Some(item) = self.get_selected_event() else {
+            self.status_handle.set_message("no selected item to pin".to_owned());
+            return;
+        };
+
+        let Some(event_id) = item.as_event().and_then(|event| event.event_id()) else {
+            self.status_handle.set_message("can only pin a remote event".to_owned());
+            return;
+        };
+
+        let message = match sdk_timeline.pin_event(event_id).await {
+            Ok(true) => "event pinned!".to_owned(),
+            // The event was already pinned, so unpin it instead.
+            Ok(false) => match sdk_timeline.unpin_event(event_id).await {
+                Ok(_) => "event unpinned!".to_owned(),
+                Err(err) => format!("error when unpinning the event: {err}"),
+            },
+            Err(err) => format!("error when pinning the event: {err}"),
+        };
+
+        self.status_handle.set_message(message);
+    }
+
+    /// The focused poll, with the ID of its start event and whether it's one
+    /// of ours, if the selected timeline item is a poll which can still be
+    /// voted on.
+    fn get_selected_ongoing_poll(&self) -> Option<(OwnedEventId, PollResult, bool)> {
+        let Some(item) = self.get_selected_event() else {
+            self.status_handle.set_message("no selected poll".to_owned());
+            return None;
+        };
+
+        let Some(event) = item.as_event() else {
+            self.status_handle.set_message("the selected item isn't an event".to_owned());
+            return None;
+        };
+
+        let Some(poll) = event.content().as_poll() else {
+            self.status_handle.set_message("the selected event isn't a poll".to_owned());
+            return None;
+        };
+
+        let Some(event_id) = event.event_id() else {
+            self.status_handle.set_message("the poll hasn't been sent yet".to_owned());
+            return None;
+        };
+
+        let results = poll.results();
+
+        if results.end_time.is_some() {
+            self.status_handle.set_message("the poll has ended".to_owned());
+            return None;
+        }
+
+        Some((event_id.to_owned(), results, event.is_own()))
+    }
+
+    /// Vote for the answer at the given index of the focused poll.
+    async fn vote_on_selected_poll(&mut self, answer_index: usize) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        let Some((event_id, results, _)) = self.get_selected_ongoing_poll() else {
+            return;
+        };
+
+        let Some(answer) = results.answers.get(answer_index) else {
+            self.status_handle
+                .set_message(format!("the poll only has {} answers", results.answers.len()));
+            return;
+        };
+
+        let content = UnstablePollResponseEventContent::new(vec![answer.id.clone()], event_id);
+
+        match sdk_timeline.send(content.into()).await {
+            Ok(_) => self.status_handle.set_message(format!("voted for \"{}\"!", answer.text)),
+            Err(err) => self.status_handle.set_error(format!("error when voting: {err}")),
         }
     }
 
-    async fn send_message(&mut self, message: String) {
+    /// End the focused poll, if it's one of ours.
+    async fn end_selected_poll(&mut self) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        let Some((event_id, results, is_own)) = self.get_selected_ongoing_poll() else {
+            return;
+        };
+
+        if !is_own {
+            self.status_handle.set_message("can only end our own polls".to_owned());
+            return;
+        }
+
+        let content = UnstablePollEndEventContent::new(
+            format!("The poll \"{}\" has ended", results.question),
+            event_id,
+        );
+
+        match sdk_timeline.send(content.into()).await {
+            Ok(_) => self.status_handle.set_message("poll ended!".to_owned()),
+            Err(err) => self.status_handle.set_error(format!("error when ending the poll: {err}")),
+        }
+    }
+
+    /// Ask for the confirmation of the redaction of the selected timeline
+    /// item, if our power level allows it.
+    async fn ask_redaction_of_selected_item(&mut self) {
+        let Some(item) = self.get_selected_event() else {
+            self.status_handle.set_message("no selected item to redact".to_owned());
+            return;
+        };
+
+        let Some(event) = item.as_event() else {
+            self.status_handle.set_message("the selected item isn't an event".to_owned());
+            return;
+        };
+
+        if event.content().is_redacted() {
+            self.status_handle.set_message("this event has already been redacted".to_owned());
+            return;
+        }
+
+        // Local echoes are always ours, and can always be redacted, i.e. aborted.
+        if event.event_id().is_some() {
+            let Some(room) = self.room() else {
+                self.status_handle.set_message("missing room".to_owned());
+                return;
+            };
+
+            let own_member = match room.get_member_no_sync(room.own_user_id()).await {
+                Ok(Some(member)) => member,
+                Ok(None) => {
+                    self.status_handle.set_message("missing own room member".to_owned());
+                    return;
+                }
+                Err(err) => {
+                    self.status_handle.set_error(format!("error when loading member: {err}"));
+                    return;
+                }
+            };
+
+            let allowed = if event.is_own() {
+                own_member.can_redact_own()
+            } else {
+                own_member.can_redact_other()
+            };
+
+            if !allowed {
+                self.status_handle
+                    .set_message("our power level doesn't allow redacting this event".to_owned());
+                return;
+            }
+        }
+
+        self.mode = Mode::Redact {
+            item_id: event.identifier(),
+            reason: PopupInputBuilder::new(
+                "Redact this event? (Enter: yes, Esc: no)",
+                "(Optional reason)",
+            )
+            .build(),
+        };
+    }
+
+    /// Redact the given timeline item.
+    async fn redact(&mut self, item_id: &TimelineEventItemId, reason: String) {
+        let Some(sdk_timeline) = self.get_selected_timeline() else {
+            self.status_handle.set_message("missing timeline for room".to_owned());
+            return;
+        };
+
+        let reason = Some(reason.as_str()).filter(|reason| !reason.is_empty());
+
+        match sdk_timeline.redact(item_id, reason).await {
+            Ok(()) => self.status_handle.set_message("event redacted!".to_owned()),
+            Err(err) => self.status_handle.set_error(format!("error when redacting: {err}")),
+        }
+    }
+
+    async fn send_message(&mut self, message: MessageType) {
         if let Some(sdk_timeline) = self.get_selected_timeline() {
-            match sdk_timeline.send(RoomMessageEventContent::text_plain(message).into()).await {
+            let result = match self.input.mode() {
+                Some(ComposerMode::Reply(reply_to)) => {
+                    sdk_timeline
+                        .send_reply(
+                            RoomMessageEventContentWithoutRelation::new(message),
+                            reply_to.event_id.clone(),
+                        )
+                        .await
+                }
+                Some(ComposerMode::Edit { item_id }) => {
+                    sdk_timeline
+                        .edit(
+                            item_id,
+                            EditedContent::RoomMessage(
+                                RoomMessageEventContentWithoutRelation::new(message),
+                            ),
+                        )
+                        .await
+                }
+                None => sdk_timeline
+                    .send(RoomMessageEventContent::new(message).into())
+                    .await
+                    .map(|_| ()),
+            };
+
+            match result {
                 Ok(_) => {
                     self.input.clear();
                 }
                 Err(err) => {
-                    self.status_handle.set_message(format!("error when sending event: {err}"));
+                    self.status_handle.set_error(format!("error when sending event: {err}"));
                 }
             }
         } else {
@@ -586,7 +1847,7 @@ impl RoomView {
                 ));
             }
             Err(err) => {
-                self.status_handle.set_message(format!("error when marking a room as read: {err}"));
+                self.status_handle.set_error(format!("error when marking a room as read: {err}"));
             }
         }
     }
@@ -606,7 +1867,12 @@ impl RoomView {
                     self.mode = Mode::Normal { invited_room_view: None };
                 }
             }
-            Mode::Details { .. } => {}
+            Mode::Details { .. }
+            | Mode::Redact { .. }
+            | Mode::ConfirmLeave { .. }
+            | Mode::React { .. }
+            | Mode::Find { .. }
+            | Mode::RawJson { .. } => {}
         }
     }
 }
@@ -617,25 +1883,34 @@ impl Widget for &mut RoomView {
         Self: Sized,
     {
         self.update();
+        self.image_previews.begin_frame();
 
         // Create a space for the header, timeline, and input area.
-        let vertical =
-            Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]);
-        let [header_area, middle_area, input_area] = vertical.areas(area);
-
-        let is_thread_view = matches!(self.kind, TimelineKind::Thread { .. });
-        let title = if is_thread_view { "Thread view" } else { "Room view" };
+        let typing_height = self.typing_users.as_ref().map_or(0, TypingUsers::height);
+        let vertical = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(typing_height),
+            Constraint::Length(self.input.height()),
+        ]);
+        let [header_area, middle_area, typing_area, input_area] = vertical.areas(area);
+
+        let title = match self.kind {
+            TimelineKind::Room { .. } => "Room view",
+            TimelineKind::Thread { .. } => "Thread view",
+            TimelineKind::Event { .. } => "Event view",
+        };
 
         let header_block = Block::default()
             .borders(Borders::NONE)
-            .fg(TEXT_COLOR)
-            .bg(HEADER_BG)
+            .fg(theme().text)
+            .bg(theme().header_bg)
             .title(title)
             .title_alignment(Alignment::Center);
 
         let middle_block = Block::default()
             .border_set(symbols::border::THICK)
-            .bg(NORMAL_ROW_COLOR)
+            .bg(theme().normal_row)
             .padding(Padding::horizontal(1));
 
         // Let's render the backgrounds for the header and the timeline.
@@ -645,7 +1920,7 @@ impl Widget for &mut RoomView {
         // Helper to render some string as a paragraph.
         let render_paragraph = |buf: &mut Buffer, content: String| {
             Paragraph::new(content)
-                .fg(TEXT_COLOR)
+                .fg(theme().text)
                 .wrap(Wrap { trim: false })
                 .render(middle_area, buf);
         };
@@ -655,6 +1930,7 @@ impl Widget for &mut RoomView {
             let mut maybe_room = maybe_room.as_ref();
 
             let selected_event = self.get_selected_event();
+            let timeline_items = self.get_selected_timeline_items().unwrap_or_default();
 
             let timeline_area = match &mut self.mode {
                 Mode::Normal { invited_room_view } => {
@@ -663,11 +1939,25 @@ impl Widget for &mut RoomView {
 
                         None
                     } else {
+                        if let Some(typing_users) = &self.typing_users {
+                            typing_users.render(typing_area, buf);
+                        }
                         self.input.render(input_area, buf, &mut maybe_room);
                         Some(middle_area)
                     }
                 }
 
+                Mode::Redact { .. }
+                | Mode::ConfirmLeave { .. }
+                | Mode::React { .. }
+                | Mode::RawJson { .. } => Some(middle_area),
+
+                // The find bar takes the place of the message input.
+                Mode::Find { bar } => {
+                    bar.render(input_area, buf);
+                    Some(middle_area)
+                }
+
                 Mode::Details { tiling_direction, view } => {
                     let vertical = Layout::new(
                         *tiling_direction,
@@ -676,8 +1966,11 @@ impl Widget for &mut RoomView {
                     let [timeline_area, details_area] = vertical.areas(middle_area);
                     Clear.render(details_area, buf);
 
-                    let mut state =
-                        DetailsState { selected_room: maybe_room, selected_item: selected_event };
+                    let mut state = DetailsState {
+                        selected_room: maybe_room,
+                        selected_item: selected_event,
+                        timeline_items,
+                    };
 
                     view.render(details_area, buf, &mut state);
 
@@ -689,11 +1982,85 @@ impl Widget for &mut RoomView {
                 && let Some(items) = self.get_selected_timeline_items()
             {
                 let is_thread = matches!(self.kind, TimelineKind::Thread { .. });
-                let mut timeline = TimelineView::new(&items, is_thread);
+                let mut timeline = TimelineView::new(&items, is_thread, &mut self.image_previews);
                 timeline.render(timeline_area, buf, &mut self.timeline_list);
+
+                // Select the focused event, once it's been rendered.
+                if let TimelineKind::Event { event_id, is_highlighted, .. } = &mut self.kind
+                    && !*is_highlighted
+                    && let Some(index) = items.iter().position(|item| {
+                        item.as_event().and_then(|event| event.event_id()) == Some(&**event_id)
+                    })
+                {
+                    *is_highlighted = self.timeline_list.select_item(index);
+                }
+            }
+
+            // The confirmations pop up on top of the timeline.
+            match &mut self.mode {
+                Mode::Redact { reason, .. } => reason.render(middle_area, buf),
+                Mode::ConfirmLeave { room } => render_leave_confirmation(room, middle_area, buf),
+                Mode::React { picker, .. } => picker.render(middle_area, buf),
+                Mode::RawJson { view } => view.render(middle_area, buf),
+                Mode::Normal { .. } | Mode::Details { .. } | Mode::Find { .. } => {}
             }
         } else {
             render_paragraph(buf, "Nothing to see here...".to_owned())
         }
     }
 }
+
+/// The details view opened by the action, if it opens one.
+fn details_view_for(action: Action) -> Option<RoomDetails> {
+    Some(match action {
+        Action::EventsDetails => RoomDetails::with_events_as_selected(),
+        Action::ReadReceiptsDetails => RoomDetails::with_receipts_as_selected(),
+        Action::LinkedChunksDetails => RoomDetails::with_chunks_as_selected(),
+        Action::MembersDetails => RoomDetails::with_members_as_selected(),
+        Action::InvitesDetails => RoomDetails::with_invites_as_selected(),
+        Action::PinnedEventsDetails => RoomDetails::with_pinned_events_as_selected(),
+        Action::ReactionsDetails => RoomDetails::with_reactions_as_selected(),
+        Action::SettingsDetails => RoomDetails::with_settings_as_selected(),
+        Action::SendQueueDetails => RoomDetails::with_send_queue_as_selected(),
+        _ => return None,
+    })
+}
+
+/// Parse a `matrix.to` or `matrix:` permalink, into the room it points to, the
+/// event it points to if any, and the servers to join the room through.
+fn parse_permalink(
+    uri: &str,
+) -> Result<(OwnedRoomOrAliasId, Option<OwnedEventId>, Vec<OwnedServerName>), String> {
+    let (id, via) = if let Ok(uri) = MatrixToUri::parse(uri) {
+        (uri.id().clone(), uri.via().to_owned())
+    } else if let Ok(uri) = MatrixUri::parse(uri) {
+        (uri.id().clone(), uri.via().to_owned())
+    } else {
+        return Err(format!("{uri} isn't a valid matrix.to or matrix: permalink"));
+    };
+
+    match id {
+        MatrixId::Room(room_id) => Ok((room_id.into(), None, via)),
+        MatrixId::RoomAlias(alias) => Ok((alias.into(), None, via)),
+        MatrixId::Event(room_id_or_alias, event_id) => Ok((room_id_or_alias, Some(event_id), via)),
+        _ => Err(format!("{uri} doesn't point to a room or an event")),
+    }
+}
+
+/// Render the popup asking to confirm that we want to leave the room.
+fn render_leave_confirmation(room: &Room, area: Rect, buf: &mut Buffer) {
+    let vertical = Layout::vertical([Constraint::Length(3)]).flex(layout::Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(layout::Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    Clear.render(area, buf);
+
+    let name = room
+        .cached_display_name()
+        .map_or_else(|| room.room_id().to_string(), |name| name.to_string());
+
+    Paragraph::new(format!("Leave {name}? (Enter: yes, Esc: no)"))
+        .centered()
+        .block(Block::bordered().bg(tailwind::RED.c800).fg(tailwind::GRAY.c50))
+        .render(area, buf);
+}
@@ -0,0 +1,63 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use matrix_sdk::ruma::{events::AnySyncTimelineEvent, serde::Raw};
+use ratatui::{prelude::*, widgets::*};
+use style::palette::tailwind;
+
+/// A popup showing the raw JSON of a timeline item, as received from the
+/// server.
+pub struct RawJsonView {
+    json: String,
+
+    /// The number of lines scrolled past.
+    scroll: u16,
+}
+
+impl RawJsonView {
+    pub fn new(raw: &Raw<AnySyncTimelineEvent>) -> Self {
+        // Keep the JSON as is if it can't be re-indented.
+        let json = serde_json::from_str::<serde_json::Value>(raw.json().get())
+            .and_then(|value| serde_json::to_string_pretty(&value))
+            .unwrap_or_else(|_| raw.json().get().to_owned());
+
+        Self { json, scroll: 0 }
+    }
+
+    /// Scroll the JSON, and return whether the popup must be closed.
+    pub fn handle_key_press(&mut self, key: KeyEvent) -> bool {
+        use KeyCode::*;
+
+        let max_scroll = u16::try_from(self.json.lines().count()).unwrap_or(u16::MAX);
+
+        match key.code {
+            Down | Char('j') => self.scroll = (self.scroll + 1).min(max_scroll),
+            Up | Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            PageDown => self.scroll = self.scroll.saturating_add(10).min(max_scroll),
+            PageUp => self.scroll = self.scroll.saturating_sub(10),
+            Esc | Char('q') => return true,
+            _ => {}
+        }
+
+        false
+    }
+}
+
+impl Widget for &RawJsonView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([Constraint::Percentage(80)]).flex(layout::Flex::Center);
+        let horizontal =
+            Layout::horizontal([Constraint::Percentage(80)]).flex(layout::Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Clear.render(area, buf);
+
+        Paragraph::new(self.json.as_str())
+            .scroll((self.scroll, 0))
+            .block(
+                Block::bordered()
+                    .title(" Raw JSON (▲ ▼ to scroll, Esc: close) ")
+                    .bg(tailwind::SLATE.c800)
+                    .fg(tailwind::GRAY.c50),
+            )
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,90 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+use style::palette::tailwind;
+
+/// The reactions which can be picked.
+const REACTIONS: [&str; 8] = ["👍", "👎", "😄", "🎉", "😕", "❤️", "🚀", "👀"];
+
+/// What to do after a key press in the picker.
+pub enum PickerAction {
+    /// Nothing, the picker stays open.
+    None,
+
+    /// Toggle the given reaction on the item, and close the picker.
+    Toggle(&'static str),
+
+    /// Close the picker without reacting.
+    Cancel,
+}
+
+/// A popup to pick the reaction to toggle on a timeline item.
+pub struct ReactionPicker {
+    selected: usize,
+
+    /// The reactions we've already sent on the item, which would be removed
+    /// if picked again.
+    own_reactions: Vec<String>,
+}
+
+impl ReactionPicker {
+    pub fn new(own_reactions: Vec<String>) -> Self {
+        Self { selected: 0, own_reactions }
+    }
+
+    pub fn handle_key_press(&mut self, key: KeyEvent) -> PickerAction {
+        use KeyCode::*;
+
+        match key.code {
+            Left | Char('h') => self.selected = self.selected.saturating_sub(1),
+            Right | Char('l') => self.selected = (self.selected + 1).min(REACTIONS.len() - 1),
+
+            Enter => return PickerAction::Toggle(REACTIONS[self.selected]),
+
+            Char(digit @ '1'..='9') => {
+                if let Some(reaction) = REACTIONS.get(digit as usize - '1' as usize) {
+                    return PickerAction::Toggle(reaction);
+                }
+            }
+
+            Esc | Char('q') => return PickerAction::Cancel,
+
+            _ => {}
+        }
+
+        PickerAction::None
+    }
+}
+
+impl Widget for &ReactionPicker {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let vertical = Layout::vertical([Constraint::Length(3)]).flex(layout::Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Length(60)]).flex(layout::Flex::Center);
+        let [area] = vertical.areas(area);
+        let [area] = horizontal.areas(area);
+        Clear.render(area, buf);
+
+        let spans = REACTIONS.iter().enumerate().map(|(i, reaction)| {
+            let is_own = self.own_reactions.iter().any(|own| own == reaction);
+            let mut span = Span::from(format!(" {}:{reaction} ", i + 1));
+
+            if is_own {
+                span = span.bg(tailwind::BLUE.c700);
+            }
+            if i == self.selected {
+                span = span.reversed();
+            }
+
+            span
+        });
+
+        Paragraph::new(Line::from(spans.collect::<Vec<_>>()))
+            .centered()
+            .block(
+                Block::bordered()
+                    .title(" React (◄ ►, Enter or 1-8: toggle, Esc: cancel) ")
+                    .bg(tailwind::SLATE.c800)
+                    .fg(tailwind::GRAY.c50),
+            )
+            .render(area, buf);
+    }
+}
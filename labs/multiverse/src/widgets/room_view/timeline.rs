@@ -1,27 +1,38 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use imbl::Vector;
 use indexmap::IndexMap;
 use matrix_sdk::ruma::{
     OwnedUserId, UserId,
-    events::{receipt::Receipt, room::message::MessageType},
+    events::{
+        poll::start::PollKind,
+        receipt::Receipt,
+        room::message::{AudioMessageEventContent, ImageMessageEventContent, MessageType},
+    },
 };
 use matrix_sdk_ui::timeline::{
-    MembershipChange, Message, MsgLikeContent, MsgLikeKind, RoomMembershipChange, ThreadSummary,
-    TimelineDetails, TimelineItem, TimelineItemContent, TimelineItemKind, VirtualTimelineItem,
+    LiveLocationState, MembershipChange, Message, MsgLikeContent, MsgLikeKind, PollState,
+    RoomMembershipChange, ThreadSummary, TimelineDetails, TimelineItem, TimelineItemContent,
+    TimelineItemKind, VirtualTimelineItem,
 };
 use ratatui::{prelude::*, widgets::*};
 
-use crate::{ALT_ROW_COLOR, NORMAL_ROW_COLOR, SELECTED_STYLE_FG, TEXT_COLOR};
+use super::image_preview::{ImagePreviews, PREVIEW_ROWS};
+use crate::config::theme;
 
 pub struct TimelineView<'a> {
     items: &'a Vector<Arc<TimelineItem>>,
     is_thread: bool,
+    image_previews: &'a mut ImagePreviews,
 }
 
 impl<'a> TimelineView<'a> {
-    pub fn new(items: &'a Vector<Arc<TimelineItem>>, is_thread: bool) -> Self {
-        Self { items, is_thread }
+    pub fn new(
+        items: &'a Vector<Arc<TimelineItem>>,
+        is_thread: bool,
+        image_previews: &'a mut ImagePreviews,
+    ) -> Self {
+        Self { items, is_thread, image_previews }
     }
 }
 
@@ -31,6 +42,11 @@ pub struct TimelineListState {
     /// An index from a rendered list item to the original timeline item index
     /// (since some timeline items may not be rendered).
     list_index_to_item_index: Vec<usize>,
+
+    /// The area of the list in the last frame, and the heights of its
+    /// rendered items, to find the item under the mouse.
+    area: Rect,
+    heights: Vec<usize>,
 }
 
 impl TimelineListState {
@@ -47,6 +63,42 @@ impl TimelineListState {
         let rendered_index = self.state.selected()?;
         self.list_index_to_item_index.get(rendered_index).copied()
     }
+
+    /// Select the rendered list item of the given timeline item, and return
+    /// whether it has been rendered in the last frame.
+    pub fn select_item(&mut self, item_index: usize) -> bool {
+        let rendered_index = self.list_index_to_item_index.iter().position(|i| *i == item_index);
+        if rendered_index.is_some() {
+            self.state.select(rendered_index);
+        }
+        rendered_index.is_some()
+    }
+
+    /// Is the given position of the screen in the list?
+    pub fn contains(&self, column: u16, row: u16) -> bool {
+        self.area.contains(Position::new(column, row))
+    }
+
+    /// Select the rendered list item at the given position of the screen, and
+    /// return whether there's one.
+    pub fn select_at(&mut self, column: u16, row: u16) -> bool {
+        if !self.contains(column, row) {
+            return false;
+        }
+
+        let mut y = self.area.y;
+
+        for (index, height) in self.heights.iter().enumerate().skip(self.state.offset()) {
+            y = y.saturating_add(u16::try_from(*height).unwrap_or(u16::MAX));
+
+            if row < y {
+                self.state.select(Some(index));
+                return true;
+            }
+        }
+
+        false
+    }
 }
 
 impl StatefulWidget for &mut TimelineView<'_> {
@@ -58,9 +110,15 @@ impl StatefulWidget for &mut TimelineView<'_> {
     {
         timeline_list_state.list_index_to_item_index.clear();
 
-        let content = self.items.iter().enumerate().filter_map(|(i, item)| {
-            let result = format_timeline_item(item, self.is_thread)?;
+        // The images whose preview is displayed, by index of the rendered list item.
+        let mut images = Vec::new();
+
+        let items = self.items;
+        let content = items.iter().enumerate().filter_map(|(i, item)| {
+            let (result, image) =
+                format_timeline_item(item, self.is_thread, &mut *self.image_previews)?;
             timeline_list_state.list_index_to_item_index.push(i);
+            images.push(image);
             Some(result)
         });
 
@@ -68,24 +126,55 @@ impl StatefulWidget for &mut TimelineView<'_> {
             .enumerate()
             .map(|(i, line)| {
                 let bg_color = match i % 2 {
-                    0 => NORMAL_ROW_COLOR,
-                    _ => ALT_ROW_COLOR,
+                    0 => theme().normal_row,
+                    _ => theme().alt_row,
                 };
 
-                line.fg(TEXT_COLOR).bg(bg_color)
+                line.fg(theme().text).bg(bg_color)
             })
             .collect::<Vec<_>>();
 
+        let heights = list_items.iter().map(ListItem::height).collect::<Vec<_>>();
+        timeline_list_state.area = area;
+        timeline_list_state.heights.clone_from(&heights);
+
         let list = List::new(list_items)
             .highlight_spacing(HighlightSpacing::Always)
             .highlight_symbol(">")
-            .highlight_style(SELECTED_STYLE_FG);
+            .highlight_style(theme().selected_fg);
 
         StatefulWidget::render(list, area, buf, &mut timeline_list_state.state);
+
+        // Find where the visible previews land, below the first line of their
+        // message, after the highlight symbol and a small margin.
+        let mut y = area.y;
+
+        for (height, image) in heights.iter().zip(&images).skip(timeline_list_state.state.offset())
+        {
+            if y >= area.bottom() {
+                break;
+            }
+
+            if let Some(image) = image
+                && y + 1 + PREVIEW_ROWS <= area.bottom()
+            {
+                self.image_previews.place(image, area.x + 3, y + 1);
+            }
+
+            y = y.saturating_add(u16::try_from(*height).unwrap_or(u16::MAX));
+        }
     }
 }
 
-fn format_timeline_item(item: &Arc<TimelineItem>, is_thread: bool) -> Option<ListItem<'_>> {
+/// Format a timeline item, and return the image whose preview is displayed
+/// below it, if any.
+fn format_timeline_item<'a>(
+    item: &'a Arc<TimelineItem>,
+    is_thread: bool,
+    image_previews: &mut ImagePreviews,
+) -> Option<(ListItem<'a>, Option<&'a ImageMessageEventContent>)> {
+    let mut image = None;
+
     let item = match item.kind() {
         TimelineItemKind::Event(ev) => {
             let sender = ev.sender();
@@ -95,9 +184,21 @@ fn format_timeline_item(item: &Arc<TimelineItem>, is_thread: bool) -> Option<Lis
                     kind: MsgLikeKind::Message(message),
                     ..
                 }) => {
-                    let thread_summary =
-                        if is_thread { None } else { ev.content().thread_summary() };
-                    format_text_message(sender, message, thread_summary, ev.read_receipts())?
+                    if let MessageType::Image(content) = message.msgtype() {
+                        let rows = image_previews.rows(content);
+                        if rows.is_some() {
+                            image = Some(content);
+                        }
+                        format_image_message(sender, content, rows)
+                    } else if let MessageType::Audio(content) = message.msgtype() {
+                        format_audio_message(sender, content)
+                    } else if let Some(effect) = message.effect() {
+                        format!("{sender}: ✨ [{effect}] {}", message.body()).into()
+                    } else {
+                        let thread_summary =
+                            if is_thread { None } else { ev.content().thread_summary() };
+                        format_text_message(sender, message, thread_summary, ev.read_receipts())
+                    }
                 }
 
                 TimelineItemContent::MsgLike(MsgLikeContent {
@@ -113,16 +214,21 @@ fn format_timeline_item(item: &Arc<TimelineItem>, is_thread: bool) -> Option<Lis
                 TimelineItemContent::MembershipChange(m) => format_membership_change(m)?,
 
                 TimelineItemContent::MsgLike(MsgLikeContent {
-                    kind: MsgLikeKind::Sticker(_),
+                    kind: MsgLikeKind::Poll(poll),
                     ..
-                })
-                | TimelineItemContent::ProfileChange(_)
+                }) => format_poll(sender, poll),
+
+                TimelineItemContent::MsgLike(MsgLikeContent {
+                    kind: MsgLikeKind::Sticker(sticker),
+                    ..
+                }) => format!("{sender}: 🏷️ [sticker: {}]", sticker.content().body).into(),
+
+                TimelineItemContent::LiveLocation(state) => format_live_location(sender, state),
+
+                TimelineItemContent::ProfileChange(_)
                 | TimelineItemContent::OtherState(_)
                 | TimelineItemContent::FailedToParseMessageLike { .. }
                 | TimelineItemContent::FailedToParseState { .. }
-                | TimelineItemContent::MsgLike(MsgLikeContent {
-                    kind: MsgLikeKind::Poll(_), ..
-                })
                 | TimelineItemContent::CallInvite
                 | TimelineItemContent::RtcNotification => {
                     return None;
@@ -134,73 +240,214 @@ fn format_timeline_item(item: &Arc<TimelineItem>, is_thread: bool) -> Option<Lis
             VirtualTimelineItem::DateDivider(unix_ts) => format!("Date: {unix_ts:?}").into(),
             VirtualTimelineItem::ReadMarker => "Read marker".to_owned().into(),
             VirtualTimelineItem::TimelineStart => "🥳 Timeline start! 🥳".to_owned().into(),
+            VirtualTimelineItem::TimelineReset => "Timeline reset after a gap".to_owned().into(),
+            VirtualTimelineItem::MembershipSummary(summary) => format!(
+                "{} joined, {} left, {} profile changes",
+                summary.joined_count(),
+                summary.left_count(),
+                summary.profile_changes_count()
+            )
+            .into(),
+            VirtualTimelineItem::Custom(item) => format!("Custom item: {}", item.id()).into(),
         },
     };
 
-    Some(item)
+    Some((item, image))
+}
+
+/// Format an image message, leaving the given number of blank rows for its
+/// preview, or only showing a placeholder if there's no preview.
+fn format_image_message(
+    sender: &UserId,
+    content: &ImageMessageEventContent,
+    preview_rows: Option<u16>,
+) -> ListItem<'static> {
+    let mut lines = vec![Line::from(format!("{sender}: 🖼️ [image: {}]", content.body))];
+
+    for _ in 0..preview_rows.unwrap_or(0) {
+        lines.push(Line::default());
+    }
+
+    ListItem::from(lines)
+}
+
+/// Format an audio message with its duration, and the waveform of voice
+/// messages below it.
+fn format_audio_message(sender: &UserId, content: &AudioMessageEventContent) -> ListItem<'static> {
+    let duration = content
+        .audio
+        .as_ref()
+        .map(|audio| audio.duration)
+        .or_else(|| content.info.as_ref()?.duration)
+        .map_or_else(|| "?:??".to_owned(), format_duration);
+
+    let mut lines = if content.voice.is_some() {
+        vec![Line::from(format!("{sender}: 🎤 [voice message: {duration}]"))]
+    } else {
+        vec![Line::from(format!("{sender}: 🔊 [audio: {}, {duration}]", content.body))]
+    };
+
+    if let Some(audio) = &content.audio
+        && !audio.waveform.is_empty()
+    {
+        let amplitudes = audio.waveform.iter().map(|amplitude| u64::from(amplitude.get()));
+        lines.push(Line::from(format!("  {}", format_waveform(amplitudes.collect()))));
+    }
+
+    ListItem::from(lines)
 }
 
+/// Format a duration as `minutes:seconds`.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// The width of a waveform, in columns.
+const WAVEFORM_COLUMNS: usize = 40;
+
+/// Draw a waveform with block characters, keeping the highest amplitude of the
+/// ones sharing a column.
+fn format_waveform(amplitudes: Vec<u64>) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let max = amplitudes.iter().copied().max().unwrap_or(0).max(1);
+    let chunk_len = amplitudes.len().div_ceil(WAVEFORM_COLUMNS).max(1);
+
+    amplitudes
+        .chunks(chunk_len)
+        .map(|chunk| {
+            let peak = chunk.iter().copied().max().unwrap_or(0);
+            BARS[(peak * (BARS.len() as u64 - 1) / max) as usize]
+        })
+        .collect()
+}
+
+/// Format a message which isn't an image nor an audio message, with its
+/// thread summary and read receipts.
+///
+/// The message types without a dedicated rendering, including the custom ones,
+/// are shown with their type and body.
 fn format_text_message(
     sender: &UserId,
     message: &Message,
     thread_summary: Option<ThreadSummary>,
     read_receipts: &IndexMap<OwnedUserId, Receipt>,
-) -> Option<ListItem<'static>> {
-    if let MessageType::Text(text) = message.msgtype() {
-        let mut lines = Vec::new();
-        let first_line = Line::from(format!("{}: {}", sender, text.body));
+) -> ListItem<'static> {
+    let mut lines = Vec::new();
+    let first_line = match message.msgtype() {
+        MessageType::Text(text) => Line::from(format!("{}: {}", sender, text.body)),
+        MessageType::Emote(emote) => Line::from(format!("* {sender} {}", emote.body)),
+        msgtype => Line::from(format!("{sender}: [{}] {}", msgtype.msgtype(), msgtype.body())),
+    };
 
-        lines.push(first_line);
+    lines.push(first_line);
+
+    if let Some(thread_summary) = thread_summary {
+        match thread_summary.latest_event {
+            TimelineDetails::Unavailable | TimelineDetails::Pending => {
+                let thread_line = Line::from("  💬 ...");
+                lines.push(thread_line);
+            }
+            TimelineDetails::Ready(e) => {
+                let sender = e.sender;
+                let content = e.content.as_message().map(|m| m.msgtype());
+
+                if let Some(MessageType::Text(text)) = content {
+                    let replies = if thread_summary.num_replies == 1 {
+                        "1 reply".to_owned()
+                    } else {
+                        format!("{} replies", { thread_summary.num_replies })
+                    };
+                    let thread_line = Line::from(format!("  💬 {replies} {sender}: {}", text.body));
 
-        if let Some(thread_summary) = thread_summary {
-            match thread_summary.latest_event {
-                TimelineDetails::Unavailable | TimelineDetails::Pending => {
-                    let thread_line = Line::from("  💬 ...");
                     lines.push(thread_line);
                 }
-                TimelineDetails::Ready(e) => {
-                    let sender = e.sender;
-                    let content = e.content.as_message().map(|m| m.msgtype());
-
-                    if let Some(MessageType::Text(text)) = content {
-                        let replies = if thread_summary.num_replies == 1 {
-                            "1 reply".to_owned()
-                        } else {
-                            format!("{} replies", { thread_summary.num_replies })
-                        };
-                        let thread_line =
-                            Line::from(format!("  💬 {replies} {sender}: {}", text.body));
-
-                        lines.push(thread_line);
-                    }
-                }
-                TimelineDetails::Error(_) => {}
             }
+            TimelineDetails::Error(_) => {}
         }
+    }
 
-        if !read_receipts.is_empty() {
-            // Read by [5 first users who read it], optionally followed by "and X others".
-            let mut read_by = read_receipts
-                .iter()
-                .take(5)
-                .map(|(user_id, _)| user_id.as_str())
-                .collect::<Vec<_>>()
-                .join(", ");
-            if read_receipts.len() > 5 {
-                let others_count = read_receipts.len() - 5;
-                if others_count == 1 {
-                    read_by.push_str(" and 1 other");
-                } else {
-                    read_by = format!("{read_by} and {others_count} others");
-                }
+    if !read_receipts.is_empty() {
+        // Read by [5 first users who read it], optionally followed by "and X others".
+        let mut read_by = read_receipts
+            .iter()
+            .take(5)
+            .map(|(user_id, _)| user_id.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if read_receipts.len() > 5 {
+            let others_count = read_receipts.len() - 5;
+            if others_count == 1 {
+                read_by.push_str(" and 1 other");
+            } else {
+                read_by = format!("{read_by} and {others_count} others");
             }
-            lines.push(Line::from(format!("  👀 read by {read_by}")));
         }
+        lines.push(Line::from(format!("  👀 read by {read_by}")));
+    }
 
-        Some(ListItem::from(lines))
-    } else {
-        None
+    ListItem::from(lines)
+}
+
+/// Format a poll, with the numbers of its answers to vote for them, and the
+/// votes each answer got so far, unless they're only disclosed once the poll
+/// has ended.
+/// Format a live location share, with the last location received for it.
+fn format_live_location(sender: &UserId, state: &LiveLocationState) -> ListItem<'static> {
+    let mut title = format!("{sender}: 📍 live location");
+    if let Some(description) = state.description() {
+        title.push_str(&format!(" ({description})"));
+    }
+    if !state.is_live() {
+        title.push_str(" (ended)");
+    }
+
+    let location = match state.last_location() {
+        Some(last) => format!("  {}", last.location.uri),
+        None => "  (no location yet)".to_owned(),
+    };
+
+    ListItem::from(vec![Line::from(title), Line::from(location).italic()])
+}
+
+fn format_poll(sender: &UserId, poll: &PollState) -> ListItem<'static> {
+    let results = poll.results();
+    let has_ended = results.end_time.is_some();
+
+    let mut title = format!("{sender}: 📊 {}", results.question);
+    if has_ended {
+        title.push_str(" (ended)");
+    }
+
+    let mut lines = vec![Line::from(title)];
+
+    let show_votes = has_ended || !matches!(results.kind, PollKind::Undisclosed);
+    let most_votes =
+        results.votes.values().map(Vec::len).max().filter(|most_votes| *most_votes > 0);
+
+    for (i, answer) in results.answers.iter().enumerate() {
+        let votes = results.votes.get(&answer.id).map_or(0, Vec::len);
+
+        let mut line = Line::from(format!("  {}. {}", i + 1, answer.text));
+
+        if show_votes {
+            let suffix = if votes == 1 { "" } else { "s" };
+            line.push_span(Span::from(format!(" — {votes} vote{suffix}")).italic());
+
+            if has_ended && most_votes == Some(votes) {
+                line.push_span(Span::from(" 🏆"));
+            }
+        }
+
+        lines.push(line);
     }
+
+    if !show_votes {
+        lines.push(Line::from("  (the votes are disclosed once the poll ends)").italic());
+    }
+
+    ListItem::from(lines)
 }
 
 fn format_membership_change(membership: &RoomMembershipChange) -> Option<ListItem<'static>> {
@@ -0,0 +1,126 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use matrix_sdk::{Room, event_handler::EventHandlerDropGuard, locks::Mutex};
+use ratatui::{prelude::*, widgets::*};
+use style::palette::tailwind;
+use tokio::{spawn, task::JoinHandle};
+use tracing::warn;
+
+/// The minimum delay between two typing notices sent while the user is
+/// writing a message.
+const TYPING_NOTICE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The users typing in a room, kept up to date by listening to the typing
+/// notifications of the room.
+pub struct TypingUsers {
+    /// The names of the users currently typing.
+    names: Arc<Mutex<Vec<String>>>,
+
+    /// Task listening to the typing notifications, to maintain the `names`
+    /// field over time.
+    task: JoinHandle<()>,
+
+    /// Keeps the typing notifications handler alive.
+    _guard: EventHandlerDropGuard,
+}
+
+impl TypingUsers {
+    /// Start listening to the typing notifications of the given room.
+    pub fn new(room: Room) -> Self {
+        let (guard, mut receiver) = room.subscribe_to_typing_notifications();
+        let names = Arc::new(Mutex::new(Vec::new()));
+
+        let n = names.clone();
+        let task = spawn(async move {
+            while let Ok(user_ids) = receiver.recv().await {
+                let mut new_names = Vec::with_capacity(user_ids.len());
+
+                for user_id in user_ids {
+                    let name = match room.get_member_no_sync(&user_id).await {
+                        Ok(Some(member)) => member.name().to_owned(),
+                        Ok(None) => user_id.to_string(),
+                        Err(err) => {
+                            warn!("couldn't load the typing member {user_id}: {err}");
+                            user_id.to_string()
+                        }
+                    };
+                    new_names.push(name);
+                }
+
+                *n.lock() = new_names;
+            }
+        });
+
+        Self { names, task, _guard: guard }
+    }
+
+    /// The height needed to render the typing users, i.e. nothing if nobody
+    /// is typing.
+    pub fn height(&self) -> u16 {
+        u16::from(!self.names.lock().is_empty())
+    }
+}
+
+impl Drop for TypingUsers {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Widget for &TypingUsers {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let names = self.names.lock();
+
+        let text = match names.as_slice() {
+            [] => return,
+            [name] => format!("{name} is typing…"),
+            [first, second] => format!("{first} and {second} are typing…"),
+            [first, others @ ..] => format!("{first} and {} others are typing…", others.len()),
+        };
+
+        Paragraph::new(text).italic().fg(tailwind::GRAY.c400).render(area, buf);
+    }
+}
+
+/// Sends the typing notices of the user, at most once per
+/// [`TYPING_NOTICE_INTERVAL`].
+#[derive(Default)]
+pub struct TypingNotifier {
+    /// When the last typing notice has been sent, if the user is typing.
+    last_notice: Option<Instant>,
+}
+
+impl TypingNotifier {
+    /// The user has typed something in the composer of the given room.
+    pub fn typing(&mut self, room: Option<Room>) {
+        if self.last_notice.is_some_and(|last| last.elapsed() < TYPING_NOTICE_INTERVAL) {
+            return;
+        }
+
+        self.last_notice = Some(Instant::now());
+        Self::send(room, true);
+    }
+
+    /// The user has sent or cancelled their message.
+    pub fn stopped(&mut self, room: Option<Room>) {
+        if self.last_notice.take().is_some() {
+            Self::send(room, false);
+        }
+    }
+
+    fn send(room: Option<Room>, typing: bool) {
+        let Some(room) = room else {
+            return;
+        };
+
+        // Don't block the UI while the request is being sent.
+        spawn(async move {
+            if let Err(err) = room.typing_notice(typing).await {
+                warn!("couldn't send the typing notice: {err}");
+            }
+        });
+    }
+}
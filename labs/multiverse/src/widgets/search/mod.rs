@@ -18,7 +18,7 @@ use ratatui::{
 use tui_widget_list::{ListBuilder, ListState, ListView};
 
 use crate::{
-    ALT_ROW_COLOR, HEADER_BG, NORMAL_ROW_COLOR, SELECTED_STYLE_FG, TEXT_COLOR,
+    config::theme,
     widgets::popup_input::{PopupInput, PopupInputBuilder},
 };
 
@@ -44,7 +44,7 @@ impl SearchingView {
                 .width_constraint(Constraint::Percentage(100))
                 .border_set(border_set)
                 .borders(Borders::BOTTOM)
-                .bg(HEADER_BG)
+                .bg(theme().header_bg)
                 .build(),
             results: None,
             list_state: ListState::default(),
@@ -77,7 +77,7 @@ impl Widget for &mut SearchingView {
         let block = Block::bordered()
             .title("Search")
             .title_alignment(Alignment::Center)
-            .border_style(Style::new().bg(HEADER_BG))
+            .border_style(Style::new().bg(theme().header_bg))
             .border_type(BorderType::Double);
         let inner_area = block.inner(area);
 
@@ -110,13 +110,14 @@ impl Widget for &mut SearchingView {
                     + 3;
 
             if context.index % 2 == 0 {
-                message_widget.style = Style::default().fg(TEXT_COLOR).bg(ALT_ROW_COLOR);
+                message_widget.style = Style::default().fg(theme().text).bg(theme().alt_row);
             } else {
-                message_widget.style = Style::default().fg(TEXT_COLOR).bg(NORMAL_ROW_COLOR);
+                message_widget.style = Style::default().fg(theme().text).bg(theme().normal_row);
             }
 
             if context.is_selected {
-                message_widget.style = Style::default().bg(NORMAL_ROW_COLOR).fg(SELECTED_STYLE_FG);
+                message_widget.style =
+                    Style::default().bg(theme().normal_row).fg(theme().selected_fg);
             }
 
             (message_widget, main_axis_size as u16)
@@ -127,7 +128,7 @@ impl Widget for &mut SearchingView {
         block.render(area, buf);
 
         Clear.render(results_area, buf);
-        Block::new().borders(Borders::NONE).bg(HEADER_BG).render(results_area, buf);
+        Block::new().borders(Borders::NONE).bg(theme().header_bg).render(results_area, buf);
 
         list.render(results_area, buf, &mut self.list_state);
 
@@ -162,7 +163,7 @@ impl MessageWidget {
             title: title.into(),
             time: time.into(),
             content: content.into(),
-            style: Style::default().fg(TEXT_COLOR).bg(NORMAL_ROW_COLOR),
+            style: Style::default().fg(theme().text).bg(theme().normal_row),
         }
     }
 }
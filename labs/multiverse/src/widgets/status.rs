@@ -1,33 +1,60 @@
 use std::{
+    collections::VecDeque,
     sync::{
         Arc,
         mpsc::{self, Receiver},
     },
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
-use matrix_sdk_common::locks::Mutex;
+use matrix_sdk_common::{
+    clock::{Clock, SystemClock},
+    locks::Mutex,
+};
 use ratatui::{
     prelude::{Buffer, Rect, *},
     widgets::Paragraph,
 };
+use style::palette::tailwind;
 use tokio::{
     spawn,
     task::{JoinHandle, spawn_blocking},
-    time::sleep,
 };
 
 use crate::{AppState, GlobalMode};
 
 const MESSAGE_DURATION: Duration = Duration::from_secs(4);
 
+/// The maximum number of messages kept in the log, the oldest ones being
+/// dropped first.
+const MAX_LOG_ENTRIES: usize = 1000;
+
+/// A status message, kept in the log once it's been displayed.
+#[derive(Clone)]
+pub struct LogEntry {
+    /// When the message has been set.
+    pub time: SystemTime,
+    pub text: String,
+    /// Whether the message tells about an error.
+    pub is_error: bool,
+}
+
+impl LogEntry {
+    fn new(text: String, is_error: bool) -> Self {
+        Self { time: SystemTime::now(), text, is_error }
+    }
+}
+
 pub struct Status {
-    /// Content of the latest status message, if set.
-    last_status_message: Arc<Mutex<Option<String>>>,
+    /// Latest status message, if set.
+    last_status_message: Arc<Mutex<Option<LogEntry>>>,
+
+    /// All the status messages, from the oldest to the most recent one.
+    log: Arc<Mutex<VecDeque<LogEntry>>>,
 
     /// An [mpsc::Sender] that other widgets can use to change the status
     /// message.
-    message_sender: mpsc::Sender<String>,
+    message_sender: mpsc::Sender<LogEntry>,
 
     /// The task listening for status messages to be received over the
     /// [mpsc::Receiver].
@@ -36,7 +63,7 @@ pub struct Status {
 
 impl Default for Status {
     fn default() -> Self {
-        Self::new()
+        Self::new(Arc::new(SystemClock))
     }
 }
 
@@ -44,14 +71,24 @@ impl Default for Status {
 /// threads where it can be used to set the status message.
 #[derive(Clone)]
 pub struct StatusHandle {
-    message_sender: mpsc::Sender<String>,
+    message_sender: mpsc::Sender<LogEntry>,
 }
 
 impl StatusHandle {
     /// Set the current status message (displayed at the bottom), for a few
     /// seconds.
     pub fn set_message(&self, status: String) {
-        self.message_sender.send(status).expect(
+        self.send(LogEntry::new(status, false));
+    }
+
+    /// Set an error as the current status message, highlighted in the status
+    /// bar and in the log.
+    pub fn set_error(&self, error: String) {
+        self.send(LogEntry::new(error, true));
+    }
+
+    fn send(&self, entry: LogEntry) {
+        self.message_sender.send(entry).expect(
             "We should be able to send the status message since the receiver is alive \
                   as long as we are alive",
         );
@@ -59,20 +96,28 @@ impl StatusHandle {
 }
 
 impl Status {
-    /// Create a new empty [`Status`] widget.
-    pub fn new() -> Self {
+    /// Create a new empty [`Status`] widget, whose messages expire according to
+    /// the given clock.
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
         let (message_sender, receiver) = mpsc::channel();
         let last_status_message = Arc::new(Mutex::new(None));
+        let log = Arc::new(Mutex::new(VecDeque::new()));
 
         let receiver_task = spawn_blocking({
             let last_status_message = last_status_message.clone();
-            move || Self::receiving_task(receiver, last_status_message)
+            let log = log.clone();
+            move || Self::receiving_task(receiver, last_status_message, log, clock)
         });
 
-        Self { last_status_message, _receiver_task: receiver_task, message_sender }
+        Self { last_status_message, log, _receiver_task: receiver_task, message_sender }
     }
 
-    fn receiving_task(receiver: Receiver<String>, status_message: Arc<Mutex<Option<String>>>) {
+    fn receiving_task(
+        receiver: Receiver<LogEntry>,
+        status_message: Arc<Mutex<Option<LogEntry>>>,
+        log: Arc<Mutex<VecDeque<LogEntry>>>,
+        clock: Arc<dyn Clock>,
+    ) {
         let mut clear_message_task: Option<JoinHandle<()>> = None;
 
         while let Ok(message) = receiver.recv() {
@@ -80,6 +125,14 @@ impl Status {
                 task.abort();
             }
 
+            {
+                let mut log = log.lock();
+                if log.len() == MAX_LOG_ENTRIES {
+                    log.pop_front();
+                }
+                log.push_back(message.clone());
+            }
+
             {
                 let mut status_message = status_message.lock();
                 *status_message = Some(message);
@@ -87,10 +140,11 @@ impl Status {
 
             clear_message_task = Some(spawn({
                 let status_message = status_message.clone();
+                let sleep = clock.sleep(MESSAGE_DURATION);
 
                 async move {
                     // Clear the status message after the standard duration.
-                    sleep(MESSAGE_DURATION).await;
+                    sleep.await;
                     status_message.lock().take();
                 }
             }));
@@ -100,10 +154,13 @@ impl Status {
     /// Set the current status message (displayed at the bottom), for a few
     /// seconds.
     pub fn set_message(&self, status: String) {
-        self.message_sender.send(status).expect(
-            "We should be able to send the status message since the receiver is alive \
-                  as long as we are alive",
-        );
+        self.handle().set_message(status);
+    }
+
+    /// All the status messages set so far, from the oldest to the most recent
+    /// one.
+    pub fn log(&self) -> Vec<LogEntry> {
+        self.log.lock().iter().cloned().collect()
     }
 
     /// Get a handle to the [`Status`] widget, this can be used to set the
@@ -121,19 +178,26 @@ impl StatefulWidget for &mut Status {
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         let status_message = self.last_status_message.lock();
 
-        let content = if let Some(status_message) = status_message.as_deref() {
-            status_message
-        } else {
-            let AppState { global_mode, throbber_state: _ } = state;
-
-            match global_mode {
-                GlobalMode::Help => "Press q to exit the help screen",
-                GlobalMode::Settings { .. } => "Press ESC to exit the settings screen",
-                GlobalMode::CreateRoom { .. } => "Press ESC to exit the create room screen",
-                GlobalMode::Searching { .. } => "Press ESC to exit the search screen",
-                GlobalMode::Default => "Press F1 to show the help screen",
-                GlobalMode::Exiting { .. } => "",
-            }
+        if let Some(LogEntry { text, is_error, .. }) = status_message.as_ref() {
+            let paragraph = Paragraph::new(text.as_str()).centered();
+            let paragraph = if *is_error { paragraph.fg(tailwind::RED.c400) } else { paragraph };
+            paragraph.render(area, buf);
+            return;
+        }
+
+        let AppState { global_mode, throbber_state: _ } = state;
+
+        let content = match global_mode {
+            GlobalMode::Help => "Press q to exit the help screen",
+            GlobalMode::Settings { .. } => "Press ESC to exit the settings screen",
+            GlobalMode::CreateRoom { .. } => "Press ESC to exit the create room screen",
+            GlobalMode::Searching { .. } => "Press ESC to exit the search screen",
+            GlobalMode::FilteringRooms { .. } => "Press Enter to keep the filter, ESC to clear it",
+            GlobalMode::Verifying { .. } => "Press ESC to cancel the verification",
+            GlobalMode::StatusLog { .. } => "Press q to exit the status log",
+            GlobalMode::Devices { .. } => "Press q to exit the device manager",
+            GlobalMode::Default => "Press F1 to show the help screen",
+            GlobalMode::Exiting { .. } => "",
         };
 
         Paragraph::new(content).centered().render(area, buf);
@@ -0,0 +1,85 @@
+use std::time::UNIX_EPOCH;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Padding},
+};
+use style::palette::tailwind;
+
+use crate::{config::theme, popup_area, widgets::status::LogEntry};
+
+/// The number of messages skipped by PageUp and PageDown.
+const PAGE_LEN: u16 = 10;
+
+/// A scrollable list of all the status messages, with the time they've been
+/// set, to read the ones which disappeared too quickly.
+#[derive(Default)]
+pub struct StatusLogView {
+    list_state: ListState,
+}
+
+impl StatusLogView {
+    /// Create a new [`StatusLogView`], showing the most recent messages.
+    pub fn new() -> Self {
+        Self { list_state: ListState::default().with_selected(Some(usize::MAX)) }
+    }
+
+    /// Handle a key press, and return whether the log must be closed.
+    pub fn handle_key_press(&mut self, key: KeyEvent) -> bool {
+        use KeyCode::*;
+
+        match key.code {
+            Char('j') | Down => self.list_state.select_next(),
+            Char('k') | Up => self.list_state.select_previous(),
+            PageDown => self.list_state.scroll_down_by(PAGE_LEN),
+            PageUp => self.list_state.scroll_up_by(PAGE_LEN),
+            Home => self.list_state.select_first(),
+            End => self.list_state.select_last(),
+            Char('q') | Esc => return true,
+            _ => {}
+        }
+
+        false
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, entries: &[LogEntry]) {
+        let block = Block::bordered()
+            .title(" Status log ")
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1));
+        let area = popup_area(area, 70, 70);
+        Clear.render(area, buf);
+
+        if entries.is_empty() {
+            Text::from("No status messages yet.").fg(theme().text).render(block.inner(area), buf);
+            block.render(area, buf);
+            return;
+        }
+
+        let items = entries.iter().map(|entry| {
+            let time = Span::from(format!("{} ", format_time(entry))).fg(tailwind::SLATE.c400);
+            let text = if entry.is_error {
+                Span::from(entry.text.as_str()).fg(tailwind::RED.c400)
+            } else {
+                Span::from(entry.text.as_str())
+            };
+
+            ListItem::new(Line::from(vec![time, text]))
+        });
+
+        let list = List::new(items)
+            .block(block)
+            .fg(theme().text)
+            .highlight_symbol(">")
+            .highlight_style(theme().selected_fg);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+    }
+}
+
+/// Format the time of a message as `HH:MM:SS`, in UTC.
+fn format_time(entry: &LogEntry) -> String {
+    let seconds = entry.time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() % 86_400;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
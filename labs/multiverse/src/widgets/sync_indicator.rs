@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use matrix_sdk::locks::Mutex;
+use matrix_sdk_ui::sync_service::{self, SyncService};
+use ratatui::{prelude::*, widgets::*};
+use style::palette::tailwind;
+use tokio::{spawn, task::JoinHandle};
+
+/// The state of the sync of an account, and the last error it ran into, kept
+/// up to date by listening to the states of the [`SyncService`].
+pub struct SyncIndicator {
+    state: Arc<Mutex<sync_service::State>>,
+
+    /// The message of the last sync error, kept once the sync has been
+    /// restarted, to know why it stopped.
+    last_error: Arc<Mutex<Option<String>>>,
+
+    /// Task listening to the states of the sync service, to maintain the
+    /// `state` and `last_error` fields over time.
+    task: JoinHandle<()>,
+}
+
+impl SyncIndicator {
+    pub fn new(sync_service: &SyncService) -> Self {
+        let mut states = sync_service.state();
+        let state = Arc::new(Mutex::new(states.get()));
+        let last_error = Arc::new(Mutex::new(None));
+
+        let s = state.clone();
+        let e = last_error.clone();
+        let task = spawn(async move {
+            while let Some(new_state) = states.next().await {
+                if let sync_service::State::Error(error) = &new_state {
+                    *e.lock() = Some(error.to_string());
+                }
+
+                *s.lock() = new_state;
+            }
+        });
+
+        Self { state, last_error, task }
+    }
+}
+
+impl Drop for SyncIndicator {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Widget for &SyncIndicator {
+    /// Render the state on the first line of the area, and the last error
+    /// below it, right-aligned.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (label, color) = match &*self.state.lock() {
+            sync_service::State::Running => ("running", tailwind::GREEN.c500),
+            sync_service::State::Idle => ("idle", tailwind::SLATE.c400),
+            sync_service::State::Terminated => ("stopped", tailwind::AMBER.c500),
+            sync_service::State::Error(_) => ("error", tailwind::RED.c500),
+            sync_service::State::Offline => ("offline", tailwind::ORANGE.c500),
+        };
+
+        let mut lines = vec![Line::from(vec![Span::from("● ").fg(color), Span::from(label)])];
+
+        if let Some(error) = &*self.last_error.lock() {
+            lines.push(Line::from(format!("last sync error: {error}")).fg(tailwind::RED.c400));
+        }
+
+        Paragraph::new(lines).right_aligned().render(area, buf);
+    }
+}
@@ -0,0 +1,339 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use futures_util::StreamExt as _;
+use matrix_sdk::{
+    Client,
+    encryption::verification::{
+        Emoji, SasState, SasVerification, Verification, VerificationRequest,
+        VerificationRequestState,
+    },
+    locks::Mutex,
+    ruma::events::{
+        key::verification::request::ToDeviceKeyVerificationRequestEvent,
+        room::message::{MessageType, OriginalSyncRoomMessageEvent},
+    },
+};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Padding, Paragraph, Wrap},
+};
+use tokio::{spawn, task::JoinHandle};
+use tracing::warn;
+
+use crate::popup_area;
+
+/// The verification requests received from the other sessions or users, which
+/// haven't been shown to the user yet.
+pub type VerificationRequests = Arc<Mutex<VecDeque<VerificationRequest>>>;
+
+/// Collect the incoming verification requests, sent either as to-device
+/// events or as room messages, into the returned queue.
+pub fn listen_to_verification_requests(client: &Client) -> VerificationRequests {
+    let requests = VerificationRequests::default();
+
+    let r = requests.clone();
+    client.add_event_handler(move |ev: ToDeviceKeyVerificationRequestEvent, client: Client| {
+        let requests = r.clone();
+        async move {
+            if let Some(request) = client
+                .encryption()
+                .get_verification_request(&ev.sender, &ev.content.transaction_id)
+                .await
+            {
+                requests.lock().push_back(request);
+            }
+        }
+    });
+
+    let r = requests.clone();
+    client.add_event_handler(move |ev: OriginalSyncRoomMessageEvent, client: Client| {
+        let requests = r.clone();
+        async move {
+            if !matches!(ev.content.msgtype, MessageType::VerificationRequest(_)) {
+                return;
+            }
+
+            if let Some(request) =
+                client.encryption().get_verification_request(&ev.sender, &ev.event_id).await
+            {
+                requests.lock().push_back(request);
+            }
+        }
+    });
+
+    requests
+}
+
+/// The steps of a verification, as shown to the user.
+enum State {
+    /// The other side has requested a verification, the user can accept it.
+    Requested,
+
+    /// We've requested a verification, the other side must accept it.
+    Requesting,
+
+    /// The request has been accepted, and the emoji comparison can be started,
+    /// by either side.
+    Ready,
+
+    /// The emoji comparison has started, we're waiting for the short
+    /// authentication string.
+    Starting,
+
+    /// The user must check that the short authentication string matches the
+    /// one displayed by the other side.
+    Comparing { sas: SasVerification, emojis: Option<[Emoji; 7]>, decimals: (u16, u16, u16) },
+
+    /// We've confirmed the short authentication string, the other side must
+    /// confirm it too.
+    Confirmed,
+
+    /// The verification has succeeded.
+    Done,
+
+    /// The verification has been cancelled, by either side, or has failed.
+    Cancelled { reason: String },
+}
+
+impl State {
+    fn is_finished(&self) -> bool {
+        matches!(self, Self::Done | Self::Cancelled { .. })
+    }
+}
+
+/// A popup driving an incoming SAS verification request.
+pub struct VerificationView {
+    request: VerificationRequest,
+
+    state: Arc<Mutex<State>>,
+
+    /// Task listening to the changes of the verification, to maintain the
+    /// `state` field over time.
+    task: JoinHandle<()>,
+}
+
+impl VerificationView {
+    pub fn new(request: VerificationRequest) -> Self {
+        let state = Arc::new(Mutex::new(if request.we_started() {
+            State::Requesting
+        } else {
+            State::Requested
+        }));
+        let task = spawn(Self::listen_to_changes(request.clone(), state.clone()));
+
+        Self { request, state, task }
+    }
+
+    async fn listen_to_changes(request: VerificationRequest, state: Arc<Mutex<State>>) {
+        let mut changes = request.changes();
+
+        while let Some(change) = changes.next().await {
+            match change {
+                VerificationRequestState::Created { .. }
+                | VerificationRequestState::Requested { .. } => {}
+
+                VerificationRequestState::Ready { .. } => *state.lock() = State::Ready,
+
+                VerificationRequestState::Transitioned {
+                    verification: Verification::SasV1(sas),
+                } => {
+                    return Self::listen_to_sas_changes(sas, state).await;
+                }
+
+                VerificationRequestState::Transitioned { .. } => {
+                    if let Err(err) = request.cancel().await {
+                        warn!("couldn't cancel the verification: {err}");
+                    }
+
+                    *state.lock() = State::Cancelled {
+                        reason: "only the emoji verification is supported".to_owned(),
+                    };
+                    return;
+                }
+
+                VerificationRequestState::Done => {
+                    *state.lock() = State::Done;
+                    return;
+                }
+
+                VerificationRequestState::Cancelled(info) => {
+                    *state.lock() = State::Cancelled { reason: info.reason().to_owned() };
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn listen_to_sas_changes(sas: SasVerification, state: Arc<Mutex<State>>) {
+        *state.lock() = State::Starting;
+
+        // The other side started the emoji comparison, accept it.
+        if !sas.we_started()
+            && let Err(err) = sas.accept().await
+        {
+            *state.lock() = State::Cancelled { reason: format!("couldn't accept: {err}") };
+            return;
+        }
+
+        let mut changes = sas.changes();
+
+        while let Some(change) = changes.next().await {
+            match change {
+                SasState::Created { .. } | SasState::Started { .. } => {}
+                SasState::Accepted { .. } => {}
+
+                SasState::KeysExchanged { emojis, decimals } => {
+                    *state.lock() = State::Comparing {
+                        sas: sas.clone(),
+                        emojis: emojis.map(|emojis| emojis.emojis),
+                        decimals,
+                    };
+                }
+
+                SasState::Confirmed => *state.lock() = State::Confirmed,
+
+                SasState::Done { .. } => {
+                    *state.lock() = State::Done;
+                    return;
+                }
+
+                SasState::Cancelled(info) => {
+                    *state.lock() = State::Cancelled { reason: info.reason().to_owned() };
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Handle a key press, and return whether the popup must be closed.
+    pub async fn handle_key_press(&mut self, key: KeyEvent) -> bool {
+        use KeyCode::*;
+
+        let sas = match &*self.state.lock() {
+            State::Comparing { sas, .. } => Some(sas.clone()),
+            _ => None,
+        };
+
+        let result = match (key.code, sas) {
+            (Char('y') | Enter, Some(sas)) => sas.confirm().await,
+            (Char('n'), Some(sas)) => sas.mismatch().await,
+
+            (Char('y') | Enter, None) if matches!(*self.state.lock(), State::Requested) => {
+                self.request.accept().await
+            }
+
+            (Char('s'), None) if matches!(*self.state.lock(), State::Ready) => {
+                self.request.start_sas().await.map(|_| ())
+            }
+
+            (Esc | Char('q') | Enter, None) if self.state.lock().is_finished() => return true,
+
+            (Esc | Char('n'), _) => {
+                if let Err(err) = self.request.cancel().await {
+                    warn!("couldn't cancel the verification: {err}");
+                }
+                return true;
+            }
+
+            _ => return false,
+        };
+
+        if let Err(err) = result {
+            *self.state.lock() = State::Cancelled { reason: err.to_string() };
+        }
+
+        false
+    }
+
+    fn title(&self) -> String {
+        if self.request.we_started() {
+            if self.request.is_self_verification() {
+                "Verifying another session of yours.".to_owned()
+            } else {
+                format!("Verifying {}.", self.request.other_user_id())
+            }
+        } else if self.request.is_self_verification() {
+            "Another session of yours wants to be verified.".to_owned()
+        } else {
+            format!("{} wants to verify you.", self.request.other_user_id())
+        }
+    }
+}
+
+impl Drop for VerificationView {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Widget for &mut VerificationView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(" Verification ")
+            .borders(Borders::ALL)
+            .padding(Padding::uniform(1));
+        let area = popup_area(area, 50, 50);
+        Clear.render(area, buf);
+
+        let mut lines = vec![Line::from(self.title()), Line::default()];
+
+        match &*self.state.lock() {
+            State::Requested => {
+                lines.push(Line::from("Press y to accept the request, or Esc to decline it."));
+            }
+
+            State::Requesting => {
+                lines.push(Line::from("Waiting for the other side to accept the request…"));
+                lines.push(Line::from("Press Esc to cancel."));
+            }
+
+            State::Ready => {
+                lines.push(Line::from("Waiting for the other side to start the emoji comparison."));
+                lines.push(Line::from("Press s to start it from here, or Esc to cancel."));
+            }
+
+            State::Starting => {
+                lines.push(Line::from("Starting the emoji comparison…"));
+            }
+
+            State::Comparing { emojis, decimals, .. } => {
+                if let Some(emojis) = emojis {
+                    lines.push(Line::from("Check that the other side shows these emojis:"));
+                    lines.push(Line::default());
+
+                    for emoji in emojis {
+                        lines
+                            .push(Line::from(format!("  {}  {}", emoji.symbol, emoji.description)));
+                    }
+                } else {
+                    let (first, second, third) = decimals;
+                    lines.push(Line::from("Check that the other side shows these numbers:"));
+                    lines.push(Line::default());
+                    lines.push(Line::from(format!("  {first} {second} {third}")).bold());
+                }
+
+                lines.push(Line::default());
+                lines.push(Line::from("Press y if they match, n if they don't."));
+            }
+
+            State::Confirmed => {
+                lines.push(Line::from("Waiting for the other side to confirm…"));
+            }
+
+            State::Done => {
+                lines.push(Line::from("The verification has succeeded!").green());
+                lines.push(Line::from("Press Esc to close."));
+            }
+
+            State::Cancelled { reason } => {
+                lines.push(
+                    Line::from(format!("The verification has been cancelled: {reason}")).red(),
+                );
+                lines.push(Line::from("Press Esc to close."));
+            }
+        }
+
+        Paragraph::new(lines).wrap(Wrap { trim: false }).block(block).render(area, buf);
+    }
+}
@@ -409,6 +409,7 @@ fn run_wasm_pack_tests(cmd: Option<WasmFeatureSet>) -> Result<()> {
         ),
         (WasmFeatureSet::MatrixSdkBase, ("crates/matrix-sdk-base", "--features js")),
         (WasmFeatureSet::MatrixSdkCommon, ("crates/matrix-sdk-common", "--features js")),
+        (WasmFeatureSet::MatrixSdkUi, ("crates/matrix-sdk-ui", "--features js")),
         (
             WasmFeatureSet::MatrixSdkIndexeddbStoresNoCrypto,
             ("crates/matrix-sdk", "--no-default-features --features js,indexeddb,rustls-tls --lib"),